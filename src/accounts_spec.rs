@@ -0,0 +1,434 @@
+//! Machine-readable accounts of the instructions.
+//!
+//! Every processor exports the accounts it expects, in order, as an [AccountsSpec], which
+//! [accounts_spec] returns per instruction. A spec lists the accounts required by the
+//! instruction, without the optional trailing accounts documented by the processor. The
+//! roles are the ones the processor always requires: an account writable or signing only in
+//! some cases, e.g. the delegation metadata of a commit allowing the undelegation, is
+//! declared readonly and not signing.
+
+use crate::discriminator::DlpDiscriminator;
+use crate::processor::{self, fast};
+
+/// An account of an instruction, with the roles required by its processor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountSpec {
+    /// The name of the account, as documented by the processor
+    pub name: &'static str,
+    /// Whether the account must be writable
+    pub writable: bool,
+    /// Whether the account must sign
+    pub signer: bool,
+}
+
+/// The accounts of an instruction, in order
+pub type AccountsSpec = &'static [AccountSpec];
+
+impl AccountSpec {
+    pub const fn readonly(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: false,
+            signer: false,
+        }
+    }
+
+    pub const fn writable(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: true,
+            signer: false,
+        }
+    }
+
+    pub const fn signer(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: false,
+            signer: true,
+        }
+    }
+
+    pub const fn writable_signer(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: true,
+            signer: true,
+        }
+    }
+
+    /// Whether an account passed with the given roles grants the roles of the spec
+    pub const fn is_granted(&self, is_signer: bool, is_writable: bool) -> bool {
+        (is_signer || !self.signer) && (is_writable || !self.writable)
+    }
+}
+
+/// Returns the accounts of the instruction of a discriminator
+pub fn accounts_spec(discriminator: DlpDiscriminator) -> AccountsSpec {
+    match discriminator {
+        DlpDiscriminator::Delegate => fast::DELEGATE_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitState => fast::COMMIT_STATE_ACCOUNTS_SPEC,
+        DlpDiscriminator::Finalize => fast::FINALIZE_ACCOUNTS_SPEC,
+        DlpDiscriminator::Undelegate => fast::UNDELEGATE_ACCOUNTS_SPEC,
+        DlpDiscriminator::InitProtocolFeesVault => {
+            processor::INIT_PROTOCOL_FEES_VAULT_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::InitValidatorFeesVault => {
+            processor::INIT_VALIDATOR_FEES_VAULT_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ValidatorClaimFees => processor::VALIDATOR_CLAIM_FEES_ACCOUNTS_SPEC,
+        DlpDiscriminator::WhitelistValidatorForProgram => {
+            processor::WHITELIST_VALIDATOR_FOR_PROGRAM_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::TopUpEphemeralBalance => {
+            processor::TOP_UP_EPHEMERAL_BALANCE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::DelegateEphemeralBalance => {
+            processor::DELEGATE_EPHEMERAL_BALANCE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::CloseEphemeralBalance => processor::CLOSE_EPHEMERAL_BALANCE_ACCOUNTS_SPEC,
+        DlpDiscriminator::ProtocolClaimFees => processor::PROTOCOL_CLAIM_FEES_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitStateFromBuffer => fast::COMMIT_STATE_FROM_BUFFER_ACCOUNTS_SPEC,
+        DlpDiscriminator::CloseValidatorFeesVault => {
+            processor::CLOSE_VALIDATOR_FEES_VAULT_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::CallHandler => processor::CALL_HANDLER_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitDiff => fast::COMMIT_DIFF_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitDiffFromBuffer => fast::COMMIT_DIFF_FROM_BUFFER_ACCOUNTS_SPEC,
+        DlpDiscriminator::SplitDelegation => processor::SPLIT_DELEGATION_ACCOUNTS_SPEC,
+        DlpDiscriminator::TopUpProgramEphemeralBalance => {
+            processor::TOP_UP_PROGRAM_EPHEMERAL_BALANCE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::DelegateProgramEphemeralBalance => {
+            processor::DELEGATE_PROGRAM_EPHEMERAL_BALANCE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::CloseProgramEphemeralBalance => {
+            processor::CLOSE_PROGRAM_EPHEMERAL_BALANCE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::SetFeatureGate => processor::SET_FEATURE_GATE_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetProgramAllowedDataLens => {
+            processor::SET_PROGRAM_ALLOWED_DATA_LENS_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::CrankFinalize => fast::CRANK_FINALIZE_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitFinalize => fast::COMMIT_FINALIZE_ACCOUNTS_SPEC,
+        DlpDiscriminator::InitDelegateBuffer => processor::INIT_DELEGATE_BUFFER_ACCOUNTS_SPEC,
+        DlpDiscriminator::WriteDelegateBufferChunk => {
+            processor::WRITE_DELEGATE_BUFFER_CHUNK_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::SetValidatorInfo => processor::SET_VALIDATOR_INFO_ACCOUNTS_SPEC,
+        DlpDiscriminator::ApproveUndelegateAndClose => {
+            processor::APPROVE_UNDELEGATE_AND_CLOSE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::UndelegateAndClose => fast::UNDELEGATE_AND_CLOSE_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitSessionBegin => processor::COMMIT_SESSION_BEGIN_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitSessionEnd => processor::COMMIT_SESSION_END_ACCOUNTS_SPEC,
+        DlpDiscriminator::GrowCommitState => processor::GROW_COMMIT_STATE_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetProtocolConfig => processor::SET_PROTOCOL_CONFIG_ACCOUNTS_SPEC,
+        DlpDiscriminator::BootstrapProtocol => processor::BOOTSTRAP_PROTOCOL_ACCOUNTS_SPEC,
+        DlpDiscriminator::InitReadLock => processor::INIT_READ_LOCK_ACCOUNTS_SPEC,
+        DlpDiscriminator::WhitelistValidatorsForProgramBatch => {
+            processor::WHITELIST_VALIDATORS_FOR_PROGRAM_BATCH_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::RequestUndelegation => processor::REQUEST_UNDELEGATION_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitDiffShadow => fast::COMMIT_DIFF_SHADOW_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetVersion => processor::SET_VERSION_ACCOUNTS_SPEC,
+        DlpDiscriminator::GetVersion => processor::GET_VERSION_ACCOUNTS_SPEC,
+        DlpDiscriminator::GrantFeeExemption => processor::GRANT_FEE_EXEMPTION_ACCOUNTS_SPEC,
+        DlpDiscriminator::ValidateDelegation => processor::VALIDATE_DELEGATION_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetProgramUndelegateLamportsTolerance => {
+            processor::SET_PROGRAM_UNDELEGATE_LAMPORTS_TOLERANCE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::SetCommitSchedule => processor::SET_COMMIT_SCHEDULE_ACCOUNTS_SPEC,
+        DlpDiscriminator::CloseCommitSchedule => processor::CLOSE_COMMIT_SCHEDULE_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetProgramMaxDelegationSlots => {
+            processor::SET_PROGRAM_MAX_DELEGATION_SLOTS_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::InitEarningsLedgerPage => {
+            processor::INIT_EARNINGS_LEDGER_PAGE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ScheduleForceUndelegate => {
+            processor::SCHEDULE_FORCE_UNDELEGATE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ExecuteForceUndelegate => {
+            processor::EXECUTE_FORCE_UNDELEGATE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::SetProgramValidateDelegations => {
+            processor::SET_PROGRAM_VALIDATE_DELEGATIONS_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ResyncProtocolStats => processor::RESYNC_PROTOCOL_STATS_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetCallHandlerPermissions => {
+            processor::SET_CALL_HANDLER_PERMISSIONS_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::TopUpEphemeralBalanceBatch => {
+            processor::TOP_UP_EPHEMERAL_BALANCE_BATCH_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::RegisterCommitRelayer => processor::REGISTER_COMMIT_RELAYER_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetValidatorCommitQuota => {
+            processor::SET_VALIDATOR_COMMIT_QUOTA_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::RedelegateEphemeralBalance => {
+            processor::REDELEGATE_EPHEMERAL_BALANCE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::IsValidatorWhitelisted => {
+            processor::IS_VALIDATOR_WHITELISTED_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ExportDelegationPackage => {
+            processor::EXPORT_DELEGATION_PACKAGE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ImportDelegationPackage => {
+            processor::IMPORT_DELEGATION_PACKAGE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ProposeProtocolVaultMigration => {
+            processor::PROPOSE_PROTOCOL_VAULT_MIGRATION_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ExecuteProtocolVaultMigration => {
+            processor::EXECUTE_PROTOCOL_VAULT_MIGRATION_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::GetDelegationSummaries => {
+            processor::GET_DELEGATION_SUMMARIES_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::GetEscrowSummaries => processor::GET_ESCROW_SUMMARIES_ACCOUNTS_SPEC,
+        DlpDiscriminator::GetPendingCommitSummaries => {
+            processor::GET_PENDING_COMMIT_SUMMARIES_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::WhitelistValidatorShardForProgram => {
+            processor::WHITELIST_VALIDATOR_SHARD_FOR_PROGRAM_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::MigrateProgramConfigWhitelist => {
+            processor::MIGRATE_PROGRAM_CONFIG_WHITELIST_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::UndelegateStage1 => fast::UNDELEGATE_STAGE1_ACCOUNTS_SPEC,
+        DlpDiscriminator::UndelegateStage2 => fast::UNDELEGATE_STAGE2_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetProgramUndelegateDiscriminatorOverride => {
+            processor::SET_PROGRAM_UNDELEGATE_DISCRIMINATOR_OVERRIDE_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::ValidatorHeartbeat => processor::VALIDATOR_HEARTBEAT_ACCOUNTS_SPEC,
+        DlpDiscriminator::MigrateDelegationRecord => {
+            processor::MIGRATE_DELEGATION_RECORD_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::FundEscrowFromDelegated => {
+            processor::FUND_ESCROW_FROM_DELEGATED_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::CloseProgramConfig => processor::CLOSE_PROGRAM_CONFIG_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitFromOwner => processor::COMMIT_FROM_OWNER_ACCOUNTS_SPEC,
+        DlpDiscriminator::SetAuthorityGrant => processor::SET_AUTHORITY_GRANT_ACCOUNTS_SPEC,
+        DlpDiscriminator::InitCommitBuffer => processor::INIT_COMMIT_BUFFER_ACCOUNTS_SPEC,
+        DlpDiscriminator::WriteCommitBuffer => processor::WRITE_COMMIT_BUFFER_ACCOUNTS_SPEC,
+        DlpDiscriminator::CloseCommitBuffer => processor::CLOSE_COMMIT_BUFFER_ACCOUNTS_SPEC,
+        DlpDiscriminator::ClaimParkedUndelegation => {
+            processor::CLAIM_PARKED_UNDELEGATION_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::InitSessionReport => processor::INIT_SESSION_REPORT_ACCOUNTS_SPEC,
+        DlpDiscriminator::CloseSessionReport => processor::CLOSE_SESSION_REPORT_ACCOUNTS_SPEC,
+        DlpDiscriminator::ForceUndelegate => processor::FORCE_UNDELEGATE_ACCOUNTS_SPEC,
+        DlpDiscriminator::DelegateStakeAccount => processor::DELEGATE_STAKE_ACCOUNT_ACCOUNTS_SPEC,
+        DlpDiscriminator::UndelegateStakeAccount => {
+            processor::UNDELEGATE_STAKE_ACCOUNT_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::AddDelegationAuthority => {
+            processor::ADD_DELEGATION_AUTHORITY_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::RemoveDelegationAuthority => {
+            processor::REMOVE_DELEGATION_AUTHORITY_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::UpdateDelegationAuthority => {
+            processor::UPDATE_DELEGATION_AUTHORITY_ACCOUNTS_SPEC
+        }
+        DlpDiscriminator::CommitStateRoot => fast::COMMIT_STATE_ROOT_ACCOUNTS_SPEC,
+        DlpDiscriminator::CommitStateChunk => processor::COMMIT_STATE_CHUNK_ACCOUNTS_SPEC,
+        DlpDiscriminator::CloseStreamedCommitState => {
+            processor::CLOSE_STREAMED_COMMIT_STATE_ACCOUNTS_SPEC
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::instruction::Instruction;
+    use solana_program::pubkey::Pubkey;
+
+    use super::*;
+    use crate::args::{
+        CommitStateArgs, CommitStateChunkArgs, CommitStateFromBufferArgs, CommitStateRootArgs,
+        DelegateArgs, DelegateEphemeralBalanceArgs, DelegateStakeAccountArgs,
+        DelegationAuthorityArgs, GrantFeeExemptionArgs, SplitDelegationArgs,
+        UndelegateStakeAccountArgs, UpdateDelegationAuthorityArgs,
+    };
+    use crate::instruction_builder;
+
+    /// Require the builder to pass the accounts of the spec of its instruction, with their
+    /// roles, followed by the optional accounts of the instruction, if any
+    fn assert_builder_matches_spec(ix: &Instruction) {
+        let discriminator = DlpDiscriminator::try_from(ix.data[0]).unwrap();
+        let spec = accounts_spec(discriminator);
+        assert!(
+            ix.accounts.len() >= spec.len(),
+            "{:?} builds {} accounts, expected at least {}",
+            discriminator,
+            ix.accounts.len(),
+            spec.len()
+        );
+        for (account, meta) in spec.iter().zip(&ix.accounts) {
+            assert!(
+                account.is_granted(meta.is_signer, meta.is_writable),
+                "{:?} builds the {} without its roles",
+                discriminator,
+                account.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_accounts_spec_of_every_instruction() {
+        let discriminators = (0..=u8::MAX).filter_map(|d| DlpDiscriminator::try_from(d).ok());
+        for discriminator in discriminators {
+            let spec = accounts_spec(discriminator);
+            assert!(!spec.is_empty(), "{:?} has no accounts", discriminator);
+            for (i, account) in spec.iter().enumerate() {
+                assert!(
+                    !spec[..i].iter().any(|other| other.name == account.name),
+                    "{:?} declares the {} twice",
+                    discriminator,
+                    account.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_builders_match_accounts_spec() {
+        let validator = Pubkey::new_unique();
+        let delegated_account = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let ixs = [
+            instruction_builder::delegate(
+                validator,
+                delegated_account,
+                Some(owner),
+                DelegateArgs::default(),
+            ),
+            instruction_builder::commit_state(
+                validator,
+                delegated_account,
+                owner,
+                CommitStateArgs::default(),
+            ),
+            instruction_builder::commit_state_from_buffer(
+                validator,
+                delegated_account,
+                owner,
+                other,
+                CommitStateFromBufferArgs::default(),
+            ),
+            instruction_builder::commit_finalize(
+                validator,
+                delegated_account,
+                owner,
+                CommitStateArgs::default(),
+            ),
+            instruction_builder::finalize(validator, delegated_account),
+            instruction_builder::crank_finalize(other, validator, delegated_account),
+            instruction_builder::undelegate(validator, delegated_account, owner, other),
+            instruction_builder::undelegate_stage1(validator, delegated_account, owner),
+            instruction_builder::undelegate_stage2(validator, delegated_account, other),
+            instruction_builder::undelegate_and_close(validator, delegated_account, other, other),
+            instruction_builder::init_protocol_fees_vault(validator),
+            instruction_builder::init_validator_fees_vault(other, other, validator),
+            instruction_builder::close_validator_fees_vault(other, other, validator),
+            instruction_builder::protocol_claim_fees(other),
+            instruction_builder::validator_claim_fees(validator, None),
+            instruction_builder::whitelist_validator_for_program(other, validator, owner, true),
+            instruction_builder::whitelist_validator_shard_for_program(
+                other, validator, owner, true,
+            ),
+            instruction_builder::top_up_ephemeral_balance(other, owner, None, None),
+            instruction_builder::delegate_ephemeral_balance(
+                other,
+                owner,
+                DelegateEphemeralBalanceArgs::default(),
+            ),
+            instruction_builder::close_ephemeral_balance(other, 0),
+            instruction_builder::split_delegation(
+                validator,
+                delegated_account,
+                other,
+                owner,
+                SplitDelegationArgs::default(),
+            ),
+            instruction_builder::redelegate_ephemeral_balance(validator, owner, 0, other),
+            instruction_builder::fund_escrow_from_delegated(
+                validator,
+                delegated_account,
+                owner,
+                0,
+                0,
+            ),
+            instruction_builder::grow_commit_state(validator, delegated_account, vec![]),
+            instruction_builder::close_streamed_commit_state(validator, delegated_account, other),
+            instruction_builder::init_read_lock(other, delegated_account),
+            instruction_builder::request_undelegation(other, delegated_account),
+            instruction_builder::grant_fee_exemption(
+                other,
+                delegated_account,
+                GrantFeeExemptionArgs { expiry_slot: 0 },
+            ),
+            instruction_builder::validate_delegation(delegated_account),
+            instruction_builder::init_commit_buffer(validator, delegated_account, 0),
+            instruction_builder::write_commit_buffer(validator, delegated_account, 0, vec![]),
+            instruction_builder::close_commit_buffer(validator, delegated_account),
+            instruction_builder::init_delegate_buffer(other, delegated_account, 0),
+            instruction_builder::write_delegate_buffer_chunk(other, delegated_account, 0, vec![]),
+            instruction_builder::commit_from_owner(other, delegated_account, 0, vec![]),
+            instruction_builder::force_undelegate(other, delegated_account),
+            instruction_builder::schedule_force_undelegate(other, delegated_account),
+            instruction_builder::execute_force_undelegate(other, delegated_account),
+            instruction_builder::migrate_delegation_record(other, delegated_account),
+            instruction_builder::validator_heartbeat(validator),
+            instruction_builder::register_commit_relayer(validator, other, true),
+            instruction_builder::is_validator_whitelisted(validator, owner),
+            instruction_builder::delegate_stake_account(
+                other,
+                delegated_account,
+                DelegateStakeAccountArgs::default(),
+            ),
+            instruction_builder::undelegate_stake_account(
+                validator,
+                delegated_account,
+                other,
+                UndelegateStakeAccountArgs::default(),
+            ),
+            instruction_builder::add_delegation_authority(
+                validator,
+                delegated_account,
+                DelegationAuthorityArgs { validator: other },
+            ),
+            instruction_builder::remove_delegation_authority(
+                validator,
+                delegated_account,
+                DelegationAuthorityArgs { validator: other },
+            ),
+            instruction_builder::update_delegation_authority(
+                validator,
+                delegated_account,
+                UpdateDelegationAuthorityArgs {
+                    new_authority: other,
+                },
+            ),
+            instruction_builder::commit_state_root(
+                validator,
+                delegated_account,
+                owner,
+                CommitStateRootArgs::default(),
+            ),
+            instruction_builder::commit_state_chunk(
+                validator,
+                delegated_account,
+                CommitStateChunkArgs::default(),
+            ),
+        ];
+        for ix in &ixs {
+            assert_builder_matches_spec(ix);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct ApproveUndelegateAndCloseArgs {
+    /// The account receiving the lamports of the delegated account when it is closed
+    pub close_destination: Pubkey,
+}
@@ -1,9 +1,73 @@
+use alloc::vec::Vec;
+use borsh::io::{Error, ErrorKind, Result, Write};
+
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use crate::state::trailing::deserialize_trailing;
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct CallHandlerArgs {
     pub escrow_index: u8,
     /// This is raw instruction data, it could include discriminator + args
     /// or can be in any other custom format
     pub data: Vec<u8>,
+    /// The lamports the escrow spends through the handler, enforced by the call handler and
+    /// cross-checked by the finalize of a commit of the escrow declaring its spend, see
+    /// [crate::args::CommitStateArgs::escrow_spend].
+    /// Skipped by borsh, it trails the instruction data instead.
+    #[borsh(skip)]
+    pub escrow_spend: Option<u64>,
+    /// The context the validator declares the handler is called in, verified against the
+    /// context derived from the transaction when the call handler permissions of the escrow
+    /// are passed, see [crate::state::CallHandlerPermissions].
+    /// Skipped by borsh, it trails the escrow spend instead.
+    #[borsh(skip)]
+    pub context: Option<CallHandlerContext>,
+}
+
+impl CallHandlerArgs {
+    /// Serialize the args of a call handler instruction, appending the escrow spend and the
+    /// context if set so that the previous layout is unchanged
+    pub fn to_instruction_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.write_instruction_data(&mut data).unwrap();
+        data
+    }
+
+    /// See [CallHandlerArgs::to_instruction_data], writing into a writer instead
+    pub fn write_instruction_data<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.serialize(writer)?;
+        if self.escrow_spend.is_some() || self.context.is_some() {
+            self.escrow_spend.serialize(writer)?;
+        }
+        if self.context.is_some() {
+            self.context.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize the args of a call handler instruction, with or without the escrow spend
+    /// and the context
+    pub fn try_from_instruction_data(mut data: &[u8]) -> Result<Self> {
+        let mut args = Self::deserialize(&mut data)?;
+        args.escrow_spend = deserialize_trailing(&mut data)?;
+        args.context = deserialize_trailing(&mut data)?;
+        if !data.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "Not all bytes read"));
+        }
+        Ok(args)
+    }
+}
+
+/// The context a handler is called in by the validator
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[borsh(use_discriminant = true)]
+pub enum CallHandlerContext {
+    /// Along the commit of a delegated account
+    Commit = 0,
+    /// Along the undelegation of a delegated account
+    Undelegate = 1,
+    /// On its own, without a commit nor an undelegation
+    Standalone = 2,
 }
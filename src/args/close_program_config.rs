@@ -0,0 +1,8 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct CloseProgramConfigArgs {
+    /// The authority attests that no account of the program is delegated anymore, as the
+    /// delegation program keeps no index of the delegations of a program
+    pub no_active_delegations: bool,
+}
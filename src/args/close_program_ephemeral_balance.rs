@@ -0,0 +1,10 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct CloseProgramEphemeralBalanceArgs {
+    /// The program the ephemeral balance is scoped to.
+    pub program_id: Pubkey,
+    /// The index of the ephemeral balance account to close.
+    pub index: u8,
+}
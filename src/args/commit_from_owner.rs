@@ -0,0 +1,11 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct CommitFromOwnerArgs {
+    /// The next nonce of the commits of the account with [crate::consts::OWNER_COMMIT_NONCE_FLAG]
+    /// set, binding the pushed state to the finalized state it corrects
+    pub nonce: u64,
+    /// The state pushed to the delegated account
+    pub data: Vec<u8>,
+}
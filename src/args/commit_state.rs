@@ -1,7 +1,12 @@
-use std::mem::size_of;
+use alloc::vec::Vec;
+use borsh::io::{Error, ErrorKind, Result, Write};
+use core::mem::size_of;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use crate::args::ArgsReader;
+use crate::state::trailing::deserialize_trailing;
+
 #[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
 pub struct CommitStateArgs {
     /// "Nonce" of an account. Updates are submitted historically and nonce incremented by 1
@@ -13,6 +18,160 @@ pub struct CommitStateArgs {
     pub allow_undelegation: bool,
     /// The account data
     pub data: Vec<u8>,
+    /// The hash of the ER block which produced the committed state, see [ErBlockHash].
+    /// Skipped by borsh, it trails the instruction data instead, see
+    /// [CommitStateArgs::to_instruction_data].
+    #[borsh(skip)]
+    pub er_block_hash: Option<ErBlockHash>,
+    /// The hash of the data the validator started the rollup from, see [commit_state_hash],
+    /// checked by the first commit against the hash recorded at delegation, see
+    /// [crate::state::DelegationMetadata::delegated_data_hash].
+    /// Skipped by borsh, it trails the ER block hash instead.
+    #[borsh(skip)]
+    pub base_state_hash: Option<[u8; 32]>,
+    /// The lamports the committed escrow spent through the [crate::processor::process_call_handler]
+    /// instructions of the finalize transaction, cross-checked by the finalize, see
+    /// [crate::state::CommitRecord::escrow_spend].
+    /// Skipped by borsh, it trails the base state hash instead.
+    #[borsh(skip)]
+    pub escrow_spend: Option<u64>,
+    /// How [CommitStateArgs::data] is encoded, decoded by the processor before it is
+    /// written to the commit state, see [Encoding].
+    /// Skipped by borsh, it trails the escrow spend instead, only when not [Encoding::Raw].
+    #[borsh(skip)]
+    pub encoding: Encoding,
+}
+
+/// The encoding of a committed state, in the instruction data or in the state buffer of a
+/// commit from buffer. Compressing a state lets a larger account fit in a transaction, at
+/// the cost of its decompression by the processor.
+#[repr(u8)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[borsh(use_discriminant = true)]
+pub enum Encoding {
+    /// The state as is
+    #[default]
+    Raw = 0,
+    /// The state compressed by [crate::compression::compress_lz4]: its length followed by
+    /// an LZ4 block of it
+    Lz4 = 1,
+}
+
+/// The hash of the ephemeral rollup block which produced a committed state.
+/// It is stored in the commit record and must change between successive commits of an
+/// account, binding every commit to a distinct ER block.
+pub type ErBlockHash = [u8; 32];
+
+/// Appends the ER block hash to the serialized args, only when set so that the previous
+/// layout is unchanged
+fn append_er_block_hash<W: Write>(
+    writer: &mut W,
+    er_block_hash: Option<ErBlockHash>,
+) -> Result<()> {
+    if er_block_hash.is_some() {
+        er_block_hash.serialize(writer)?;
+    }
+    Ok(())
+}
+
+impl CommitStateArgs {
+    /// Serialize the args of a commit instruction, appending the ER block hash if set, the
+    /// base state hash after it if set, the escrow spend after them if set, and the
+    /// encoding last if not raw
+    pub fn to_instruction_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.write_instruction_data(&mut data).unwrap();
+        data
+    }
+
+    /// See [CommitStateArgs::to_instruction_data], writing into a writer instead, e.g. a
+    /// borrowed buffer, see [crate::fast_instruction_builder]
+    pub fn write_instruction_data<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.serialize(writer)?;
+        let trailing_fields = self.serialized_trailing_fields();
+        if trailing_fields > 0 {
+            self.er_block_hash.serialize(writer)?;
+        }
+        if trailing_fields > 1 {
+            self.base_state_hash.serialize(writer)?;
+        }
+        if trailing_fields > 2 {
+            self.escrow_spend.serialize(writer)?;
+        }
+        if trailing_fields > 3 {
+            self.encoding.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    /// The number of trailing fields serialized, up to the last one set
+    fn serialized_trailing_fields(&self) -> usize {
+        if self.encoding != Encoding::Raw {
+            4
+        } else if self.escrow_spend.is_some() {
+            3
+        } else if self.base_state_hash.is_some() {
+            2
+        } else if self.er_block_hash.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Deserialize the args of a commit instruction, with or without the trailing hashes
+    pub fn try_from_instruction_data(mut data: &[u8]) -> Result<Self> {
+        let mut args = Self::deserialize(&mut data)?;
+        args.er_block_hash = deserialize_trailing(&mut data)?;
+        args.base_state_hash = deserialize_trailing(&mut data)?;
+        args.escrow_spend = deserialize_trailing(&mut data)?;
+        args.encoding = deserialize_trailing(&mut data)?;
+        if !data.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "Not all bytes read"));
+        }
+        Ok(args)
+    }
+}
+
+/// The args of a commit state instruction borrowing the committed data from the
+/// instruction data, read by the fast processors instead of [CommitStateArgs]
+#[derive(Default, Debug)]
+pub struct CommitStateArgsRef<'a> {
+    /// See [CommitStateArgs::nonce]
+    pub nonce: u64,
+    /// See [CommitStateArgs::lamports]
+    pub lamports: u64,
+    /// See [CommitStateArgs::allow_undelegation]
+    pub allow_undelegation: bool,
+    /// See [CommitStateArgs::data]
+    pub data: &'a [u8],
+    /// See [CommitStateArgs::er_block_hash]
+    pub er_block_hash: Option<ErBlockHash>,
+    /// See [CommitStateArgs::base_state_hash]
+    pub base_state_hash: Option<[u8; 32]>,
+    /// See [CommitStateArgs::escrow_spend]
+    pub escrow_spend: Option<u64>,
+    /// See [CommitStateArgs::encoding]
+    pub encoding: Encoding,
+}
+
+impl<'a> CommitStateArgsRef<'a> {
+    /// See [CommitStateArgs::try_from_instruction_data]
+    pub fn try_from_instruction_data(data: &'a [u8]) -> Result<Self> {
+        let mut reader = ArgsReader::new(data);
+        let args = Self {
+            nonce: reader.read_u64()?,
+            lamports: reader.read_u64()?,
+            allow_undelegation: reader.read_bool()?,
+            data: reader.read_bytes()?,
+            er_block_hash: reader.read_trailing(|r| r.read_option(|r| r.read_array()))?,
+            base_state_hash: reader.read_trailing(|r| r.read_option(|r| r.read_array()))?,
+            escrow_spend: reader.read_trailing(|r| r.read_option(|r| r.read_u64()))?,
+            encoding: reader.read_trailing(|r| r.read_borsh())?,
+        };
+        reader.finish()?;
+        Ok(args)
+    }
 }
 
 #[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
@@ -24,6 +183,93 @@ pub struct CommitStateFromBufferArgs {
     pub lamports: u64,
     /// Whether the account can be undelegated after the commit completes
     pub allow_undelegation: bool,
+    /// See [CommitStateArgs::er_block_hash]
+    #[borsh(skip)]
+    pub er_block_hash: Option<ErBlockHash>,
+    /// How the state buffer is encoded, see [CommitStateArgs::encoding]. It trails the ER
+    /// block hash, only when not [Encoding::Raw].
+    #[borsh(skip)]
+    pub encoding: Encoding,
+}
+
+impl CommitStateFromBufferArgs {
+    /// See [CommitStateArgs::to_instruction_data]
+    pub fn to_instruction_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.write_instruction_data(&mut data).unwrap();
+        data
+    }
+
+    /// See [CommitStateArgs::write_instruction_data]
+    pub fn write_instruction_data<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.serialize(writer)?;
+        if self.encoding != Encoding::Raw {
+            self.er_block_hash.serialize(writer)?;
+            self.encoding.serialize(writer)
+        } else {
+            append_er_block_hash(writer, self.er_block_hash)
+        }
+    }
+
+    /// See [CommitStateArgs::try_from_instruction_data]
+    pub fn try_from_instruction_data(mut data: &[u8]) -> Result<Self> {
+        let mut args = Self::deserialize(&mut data)?;
+        args.er_block_hash = deserialize_trailing(&mut data)?;
+        args.encoding = deserialize_trailing(&mut data)?;
+        if !data.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "Not all bytes read"));
+        }
+        Ok(args)
+    }
+}
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct CommitStateRootArgs {
+    /// "Nonce" of an account. Updates are submitted historically and nonce incremented by 1
+    pub nonce: u64,
+    /// The lamports that the account holds in the ephemeral validator
+    pub lamports: u64,
+    /// Whether the account can be undelegated after the commit completes
+    pub allow_undelegation: bool,
+    /// The Merkle root of the chunks of the committed state, see
+    /// [crate::state::ChunkMerkleTree]
+    pub root: [u8; 32],
+    /// The length of the committed state
+    pub data_len: u64,
+    /// The length of the chunks of the committed state, but the last one, each written with
+    /// its Merkle proof by [crate::processor::process_commit_state_chunk]
+    pub chunk_size: u32,
+    /// The number of chunks which differ from the data of the delegated account, see
+    /// [crate::state::ChunkedCommitState::chunks_to_write]
+    pub chunks_to_write: u32,
+    /// See [CommitStateArgs::er_block_hash]
+    #[borsh(skip)]
+    pub er_block_hash: Option<ErBlockHash>,
+}
+
+impl CommitStateRootArgs {
+    /// See [CommitStateArgs::to_instruction_data]
+    pub fn to_instruction_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.write_instruction_data(&mut data).unwrap();
+        data
+    }
+
+    /// See [CommitStateArgs::write_instruction_data]
+    pub fn write_instruction_data<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.serialize(writer)?;
+        append_er_block_hash(writer, self.er_block_hash)
+    }
+
+    /// See [CommitStateArgs::try_from_instruction_data]
+    pub fn try_from_instruction_data(mut data: &[u8]) -> Result<Self> {
+        let mut args = Self::deserialize(&mut data)?;
+        args.er_block_hash = deserialize_trailing(&mut data)?;
+        if !data.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "Not all bytes read"));
+        }
+        Ok(args)
+    }
 }
 
 #[derive(Default, Debug, BorshSerialize)]
@@ -31,8 +277,8 @@ pub struct CommitDiffArgs {
     /// The account diff
     /// SAFETY: this must be the FIRST field in the struct because the serialized format
     /// is manually split: the diff (with Borsh Vec prefix) followed by the fixed-size
-    /// fields. The processor uses `data.split_at(data.len() - SIZE_COMMIT_DIFF_ARGS_WITHOUT_DIFF)`
-    /// to separate them during deserialization.
+    /// fields and the optional ER block hash. The processor uses the Borsh Vec prefix to
+    /// separate them during deserialization, see [CommitDiffArgsRef].
     pub diff: Vec<u8>,
 
     /// "Nonce" of an account. Updates are submitted historically and nonce incremented by 1
@@ -44,6 +290,25 @@ pub struct CommitDiffArgs {
 
     /// Whether the account can be undelegated after the commit completes
     pub allow_undelegation: bool,
+
+    /// See [CommitStateArgs::er_block_hash]
+    #[borsh(skip)]
+    pub er_block_hash: Option<ErBlockHash>,
+}
+
+impl CommitDiffArgs {
+    /// See [CommitStateArgs::to_instruction_data]
+    pub fn to_instruction_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.write_instruction_data(&mut data).unwrap();
+        data
+    }
+
+    /// See [CommitStateArgs::write_instruction_data]
+    pub fn write_instruction_data<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.serialize(writer)?;
+        append_er_block_hash(writer, self.er_block_hash)
+    }
 }
 
 #[derive(Default, Debug, BorshDeserialize)]
@@ -59,3 +324,313 @@ pub struct CommitDiffArgsWithoutDiff {
 
 pub const SIZE_COMMIT_DIFF_ARGS_WITHOUT_DIFF: usize =
     size_of::<u64>() + size_of::<u64>() + size_of::<bool>();
+
+/// The args of a commit diff instruction borrowing the diff from the instruction data,
+/// read by the fast processors instead of [CommitDiffArgs]
+#[derive(Default, Debug)]
+pub struct CommitDiffArgsRef<'a> {
+    /// See [CommitDiffArgs::diff], without its Borsh Vec prefix
+    pub diff: &'a [u8],
+    /// See [CommitDiffArgs::nonce]
+    pub nonce: u64,
+    /// See [CommitDiffArgs::lamports]
+    pub lamports: u64,
+    /// See [CommitDiffArgs::allow_undelegation]
+    pub allow_undelegation: bool,
+    /// See [CommitStateArgs::er_block_hash]
+    pub er_block_hash: Option<ErBlockHash>,
+}
+
+impl<'a> CommitDiffArgsRef<'a> {
+    /// Deserialize the args of a commit diff instruction, with or without the ER block hash
+    pub fn try_from_instruction_data(data: &'a [u8]) -> Result<Self> {
+        let mut reader = ArgsReader::new(data);
+        let args = Self {
+            diff: reader.read_bytes()?,
+            nonce: reader.read_u64()?,
+            lamports: reader.read_u64()?,
+            allow_undelegation: reader.read_bool()?,
+            er_block_hash: reader.read_trailing(|r| r.read_option(|r| r.read_array()))?,
+        };
+        reader.finish()?;
+        Ok(args)
+    }
+}
+
+#[derive(Default, Debug, BorshSerialize)]
+pub struct CommitDiffShadowArgs {
+    /// The account diff
+    /// SAFETY: this must be the FIRST field in the struct, see [CommitDiffArgs::diff]
+    pub diff: Vec<u8>,
+
+    /// "Nonce" of an account. Updates are submitted historically and nonce incremented by 1
+    /// Deprecated: The ephemeral slot at which the account data is committed
+    pub nonce: u64,
+
+    /// The lamports that the account holds in the ephemeral validator
+    pub lamports: u64,
+
+    /// Whether the account can be undelegated after the commit completes
+    pub allow_undelegation: bool,
+
+    /// The hash of the full state expected once the diff is applied, see [commit_state_hash]
+    pub expected_state_hash: [u8; 32],
+}
+
+#[derive(Default, Debug, BorshDeserialize)]
+pub struct CommitDiffShadowArgsWithoutDiff {
+    /// "Nonce" of an account. Updates are submitted historically and nonce incremented by 1
+    /// Deprecated: The ephemeral slot at which the account data is committed
+    pub nonce: u64,
+    /// The lamports that the account holds in the ephemeral validator
+    pub lamports: u64,
+    /// Whether the account can be undelegated after the commit completes
+    pub allow_undelegation: bool,
+    /// The hash of the full state expected once the diff is applied
+    pub expected_state_hash: [u8; 32],
+}
+
+pub const SIZE_COMMIT_DIFF_SHADOW_ARGS_WITHOUT_DIFF: usize =
+    SIZE_COMMIT_DIFF_ARGS_WITHOUT_DIFF + size_of::<[u8; 32]>();
+
+/// The hash of a committed state, checked against the state produced by the diff of a
+/// [CommitDiffShadowArgs]
+pub fn commit_state_hash(data: &[u8]) -> [u8; 32] {
+    solana_program::hash::hash(data).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::to_vec;
+
+    use super::*;
+
+    #[test]
+    fn test_commit_diff_shadow_args_split() {
+        let args = CommitDiffShadowArgs {
+            diff: vec![1, 2, 3],
+            nonce: 7,
+            lamports: 100,
+            allow_undelegation: true,
+            expected_state_hash: commit_state_hash(&[4, 5, 6]),
+        };
+        let data = to_vec(&args).unwrap();
+
+        let (diff, data) = data.split_at(data.len() - SIZE_COMMIT_DIFF_SHADOW_ARGS_WITHOUT_DIFF);
+        assert_eq!(Vec::<u8>::try_from_slice(diff).unwrap(), args.diff);
+        let without_diff = CommitDiffShadowArgsWithoutDiff::try_from_slice(data).unwrap();
+        assert_eq!(without_diff.nonce, args.nonce);
+        assert_eq!(without_diff.lamports, args.lamports);
+        assert!(without_diff.allow_undelegation);
+        assert_eq!(without_diff.expected_state_hash, args.expected_state_hash);
+    }
+
+    #[test]
+    fn test_commit_diff_args_with_er_block_hash() {
+        let mut args = CommitDiffArgs {
+            diff: vec![1, 2, 3],
+            nonce: 7,
+            lamports: 100,
+            allow_undelegation: true,
+            er_block_hash: None,
+        };
+
+        // Without the ER block hash the previous layout is kept
+        let data = args.to_instruction_data();
+        assert_eq!(data, to_vec(&args).unwrap());
+        let args_ref = CommitDiffArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.diff, args.diff);
+        assert_eq!(args_ref.nonce, args.nonce);
+        assert_eq!(args_ref.er_block_hash, None);
+
+        args.er_block_hash = Some([9; 32]);
+        let data = args.to_instruction_data();
+        let args_ref = CommitDiffArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.diff, args.diff);
+        assert_eq!(args_ref.lamports, args.lamports);
+        assert!(args_ref.allow_undelegation);
+        assert_eq!(args_ref.er_block_hash, args.er_block_hash);
+
+        // The diff length is larger than the data
+        assert!(CommitDiffArgsRef::try_from_instruction_data(&[4, 0, 0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_commit_state_args_with_er_block_hash() {
+        let mut args = CommitStateArgs {
+            nonce: 1,
+            lamports: 100,
+            allow_undelegation: false,
+            data: vec![1, 2, 3],
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        };
+        let data = args.to_instruction_data();
+        assert_eq!(data, to_vec(&args).unwrap());
+        assert_eq!(
+            CommitStateArgs::try_from_instruction_data(&data)
+                .unwrap()
+                .er_block_hash,
+            None
+        );
+
+        args.er_block_hash = Some([9; 32]);
+        let data = args.to_instruction_data();
+        let deserialized = CommitStateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.er_block_hash, args.er_block_hash);
+        assert_eq!(deserialized.data, args.data);
+
+        assert!(
+            CommitStateArgs::try_from_instruction_data(&[data.clone(), vec![2]].concat()).is_err()
+        );
+
+        // The borrowing reader reads the same layout
+        let args_ref = CommitStateArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.data, args.data.as_slice());
+        assert_eq!(args_ref.nonce, args.nonce);
+        assert_eq!(args_ref.lamports, args.lamports);
+        assert_eq!(args_ref.er_block_hash, args.er_block_hash);
+        assert!(CommitStateArgsRef::try_from_instruction_data(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_commit_state_args_with_base_state_hash() {
+        let mut args = CommitStateArgs {
+            nonce: 1,
+            lamports: 100,
+            allow_undelegation: false,
+            data: vec![1, 2, 3],
+            er_block_hash: None,
+            base_state_hash: Some(commit_state_hash(&[4, 5, 6])),
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        };
+
+        // The ER block hash is serialized before the base state hash, even if not set
+        let data = args.to_instruction_data();
+        assert_eq!(data.len(), to_vec(&args).unwrap().len() + 1 + 33);
+        let deserialized = CommitStateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.er_block_hash, None);
+        assert_eq!(deserialized.base_state_hash, args.base_state_hash);
+        let args_ref = CommitStateArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.er_block_hash, None);
+        assert_eq!(args_ref.base_state_hash, args.base_state_hash);
+
+        args.er_block_hash = Some([9; 32]);
+        let data = args.to_instruction_data();
+        let args_ref = CommitStateArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.er_block_hash, args.er_block_hash);
+        assert_eq!(args_ref.base_state_hash, args.base_state_hash);
+        assert_eq!(args_ref.data, args.data.as_slice());
+    }
+
+    #[test]
+    fn test_commit_state_args_with_encoding() {
+        let mut args = CommitStateArgs {
+            nonce: 1,
+            lamports: 100,
+            allow_undelegation: false,
+            data: vec![1, 2, 3],
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        };
+
+        // A raw state keeps the previous layout
+        assert_eq!(args.to_instruction_data(), to_vec(&args).unwrap());
+
+        // The hashes and the escrow spend are serialized before the encoding, even if not set
+        args.encoding = Encoding::Lz4;
+        let data = args.to_instruction_data();
+        assert_eq!(data.len(), to_vec(&args).unwrap().len() + 1 + 1 + 1 + 1);
+        let deserialized = CommitStateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.encoding, Encoding::Lz4);
+        assert_eq!(deserialized.escrow_spend, None);
+        let args_ref = CommitStateArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.encoding, Encoding::Lz4);
+        assert_eq!(args_ref.data, args.data.as_slice());
+
+        let mut args = CommitStateFromBufferArgs {
+            nonce: 1,
+            lamports: 100,
+            allow_undelegation: false,
+            er_block_hash: None,
+            encoding: Encoding::Raw,
+        };
+        assert_eq!(args.to_instruction_data(), to_vec(&args).unwrap());
+        args.encoding = Encoding::Lz4;
+        let data = args.to_instruction_data();
+        assert_eq!(data.len(), to_vec(&args).unwrap().len() + 1 + 1);
+        let deserialized = CommitStateFromBufferArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.er_block_hash, None);
+        assert_eq!(deserialized.encoding, Encoding::Lz4);
+    }
+
+    #[test]
+    fn test_commit_state_args_lz4_round_trip() {
+        use crate::compression::{compress_lz4, decompress_lz4_into, split_decompressed_len};
+
+        let state = (0..2_000).map(|i| (i % 7) as u8).collect::<Vec<_>>();
+        let args = CommitStateArgs {
+            nonce: 1,
+            lamports: 100,
+            allow_undelegation: true,
+            data: compress_lz4(&state),
+            er_block_hash: Some([9; 32]),
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Lz4,
+        };
+        let data = args.to_instruction_data();
+
+        // The encoding is serialized as its discriminant, last
+        assert_eq!(data.last(), Some(&(Encoding::Lz4 as u8)));
+
+        // Both readers read back the compressed state, which decompresses to the state
+        let deserialized = CommitStateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.encoding, Encoding::Lz4);
+        assert_eq!(deserialized.er_block_hash, args.er_block_hash);
+        assert_eq!(deserialized.data, args.data);
+        let args_ref = CommitStateArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.encoding, Encoding::Lz4);
+        assert_eq!(args_ref.allow_undelegation, args.allow_undelegation);
+        let (len, block) = split_decompressed_len(args_ref.data).unwrap();
+        let mut decompressed = vec![0; len];
+        decompress_lz4_into(block, &mut decompressed).unwrap();
+        assert_eq!(decompressed, state);
+
+        // An unknown encoding is rejected
+        let mut data = data;
+        *data.last_mut().unwrap() = 2;
+        assert!(CommitStateArgs::try_from_instruction_data(&data).is_err());
+        assert!(CommitStateArgsRef::try_from_instruction_data(&data).is_err());
+    }
+
+    #[test]
+    fn test_commit_state_args_with_escrow_spend() {
+        let args = CommitStateArgs {
+            nonce: 1,
+            lamports: 100,
+            allow_undelegation: false,
+            data: vec![1, 2, 3],
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: Some(5_000),
+            encoding: Encoding::Raw,
+        };
+
+        // The hashes are serialized before the escrow spend, even if not set
+        let data = args.to_instruction_data();
+        assert_eq!(data.len(), to_vec(&args).unwrap().len() + 1 + 1 + 9);
+        let deserialized = CommitStateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.base_state_hash, None);
+        assert_eq!(deserialized.escrow_spend, args.escrow_spend);
+        let args_ref = CommitStateArgsRef::try_from_instruction_data(&data).unwrap();
+        assert_eq!(args_ref.er_block_hash, None);
+        assert_eq!(args_ref.escrow_spend, args.escrow_spend);
+        assert_eq!(args_ref.data, args.data.as_slice());
+    }
+}
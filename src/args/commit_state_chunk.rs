@@ -0,0 +1,13 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct CommitStateChunkArgs {
+    /// The index of the chunk in the committed state
+    pub index: u32,
+    /// The chunk of the committed state
+    pub data: Vec<u8>,
+    /// The Merkle proof of the chunk against the committed root, the hashes of its siblings
+    /// from the leaf up, see [crate::state::ChunkMerkleTree::proof]
+    pub proof: Vec<[u8; 32]>,
+}
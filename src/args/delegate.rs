@@ -1,13 +1,367 @@
+use alloc::vec::Vec;
+use borsh::io::{Result, Write};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
+use crate::args::{ArgsReader, SeedTemplate, Seeds};
+
 #[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
 pub struct DelegateArgs {
     /// The frequency at which the validator should commit the account data
     /// if no commit is triggered by the owning program
     pub commit_frequency_ms: u32,
     /// The seeds used to derive the PDA of the delegated account
-    pub seeds: Vec<Vec<u8>>,
+    pub seeds: Seeds,
     /// The validator authority that is added to the delegation record
     pub validator: Option<Pubkey>,
+    /// Whether the owner program expects the v2 payload in the external undelegate CPI,
+    /// see [crate::args::ExternalUndelegateArgsV2].
+    /// Skipped by borsh as the args are nested in other args, it trails the instruction data
+    /// instead, see [DelegateArgs::to_instruction_data].
+    #[borsh(skip)]
+    pub extended_undelegate_payload: bool,
+    /// The template of the seeds, resolved with the payer and the owner program, replacing
+    /// the seeds which must then be empty, see [SeedTemplate].
+    /// Skipped by borsh and trailing the instruction data after the v2 payload flag.
+    #[borsh(skip)]
+    pub seed_template: Option<SeedTemplate>,
+    /// The discriminator of the external undelegate instruction of the owner program, in
+    /// place of [crate::consts::EXTERNAL_UNDELEGATE_DISCRIMINATOR] when it collides with an
+    /// existing instruction. Only accepted if the program config of the owner program allows
+    /// it, see [crate::state::ProgramConfig::allow_undelegate_discriminator_override].
+    /// Skipped by borsh and trailing the instruction data after the seed template.
+    #[borsh(skip)]
+    pub undelegate_discriminator: Option<[u8; 8]>,
+    /// The slots the validator can go without a heartbeat before the account can be
+    /// undelegated without its commit, see [crate::state::ValidatorLiveness].
+    /// Skipped by borsh and trailing the instruction data after the undelegate discriminator.
+    #[borsh(skip)]
+    pub heartbeat_timeout_slots: Option<u64>,
+    /// The maximum data length the commits of the account can set, unlimited if unset, so
+    /// that a compromised validator cannot grow the account and the rent owed on L1.
+    /// Skipped by borsh and trailing the instruction data after the heartbeat timeout.
+    #[borsh(skip)]
+    pub max_account_size: Option<MaxAccountSize>,
+    /// A second payer of the rent of the delegation PDAs, paying its share of it to the
+    /// payer and refunded what it paid, net of its share of the rent fees, on undelegation,
+    /// see [RentCoPayer].
+    /// Skipped by borsh and trailing the instruction data after the account size cap.
+    #[borsh(skip)]
+    pub rent_co_payer: Option<RentCoPayer>,
+}
+
+/// The basis points of a whole share, see [RentCoPayer::share_bps]
+pub const RENT_SHARE_BPS: u16 = 10_000;
+
+/// A second payer of the rent of a delegation, see [DelegateArgs::rent_co_payer]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct RentCoPayer {
+    /// The co-payer, signing the delegation
+    pub payer: Pubkey,
+    /// The share of the rent paid by the co-payer, in basis points of [RENT_SHARE_BPS],
+    /// the payer paying the rest
+    pub share_bps: u16,
+}
+
+impl RentCoPayer {
+    /// Whether both payers pay a non-zero share of the rent
+    pub fn is_valid(&self) -> bool {
+        self.share_bps > 0 && self.share_bps < RENT_SHARE_BPS
+    }
+
+    /// The share of the lamports owed by, or refunded to, the co-payer, rounded down so that
+    /// the payer takes the remainder and the shares always sum up to the lamports
+    pub fn share_of(&self, lamports: u64) -> u64 {
+        (lamports as u128 * self.share_bps as u128 / RENT_SHARE_BPS as u128) as u64
+    }
+}
+
+/// The data length cap of a delegated account, see [DelegateArgs::max_account_size]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct MaxAccountSize {
+    /// The maximum data length of the account
+    pub max_len: u32,
+    /// How a committed state longer than the cap is handled
+    pub policy: OversizedStatePolicy,
+}
+
+impl MaxAccountSize {
+    /// Whether a committed state of the data length is rejected
+    pub fn rejects(&self, data_len: usize) -> bool {
+        self.policy == OversizedStatePolicy::Reject && data_len > self.max_len as usize
+    }
+
+    /// The data length a committed state of the data length is finalized with
+    pub fn retained_len(&self, data_len: usize) -> usize {
+        match self.policy {
+            OversizedStatePolicy::Reject => data_len,
+            OversizedStatePolicy::Truncate => data_len.min(self.max_len as usize),
+        }
+    }
+}
+
+/// How a committed state longer than [MaxAccountSize::max_len] is handled
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum OversizedStatePolicy {
+    /// The commit is rejected
+    Reject,
+    /// The state is truncated to the cap when finalized
+    Truncate,
+}
+
+impl DelegateArgs {
+    /// Serialize the args of a delegate instruction, appending the v2 external undelegate
+    /// payload flag, the seed template, the undelegate discriminator, the heartbeat timeout,
+    /// the account size cap and the rent co-payer only up to the last one set so that the
+    /// previous layout is unchanged
+    pub fn to_instruction_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.write_instruction_data(&mut data).unwrap();
+        data
+    }
+
+    /// See [DelegateArgs::to_instruction_data], writing into a writer instead, e.g. a
+    /// borrowed buffer, see [crate::fast_instruction_builder]
+    pub fn write_instruction_data<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.serialize(writer)?;
+        let trailing_fields = self.serialized_trailing_fields();
+        if trailing_fields > 0 {
+            writer.write_all(&[self.extended_undelegate_payload as u8])?;
+        }
+        if trailing_fields > 1 {
+            self.seed_template.serialize(writer)?;
+        }
+        if trailing_fields > 2 {
+            self.undelegate_discriminator.serialize(writer)?;
+        }
+        if trailing_fields > 3 {
+            self.heartbeat_timeout_slots.serialize(writer)?;
+        }
+        if trailing_fields > 4 {
+            self.max_account_size.serialize(writer)?;
+        }
+        if trailing_fields > 5 {
+            self.rent_co_payer.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    /// The number of trailing fields which are serialized: up to the last one that is set
+    fn serialized_trailing_fields(&self) -> usize {
+        if self.rent_co_payer.is_some() {
+            6
+        } else if self.max_account_size.is_some() {
+            5
+        } else if self.heartbeat_timeout_slots.is_some() {
+            4
+        } else if self.undelegate_discriminator.is_some() {
+            3
+        } else if self.seed_template.is_some() {
+            2
+        } else if self.extended_undelegate_payload {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Deserialize the args of a delegate instruction, with or without the trailing v2
+    /// external undelegate payload flag, seed template, undelegate discriminator, heartbeat
+    /// timeout, account size cap and rent co-payer.
+    /// The seeds are read from the instruction data straight into their stack container,
+    /// see [ArgsReader].
+    pub fn try_from_instruction_data(data: &[u8]) -> Result<Self> {
+        let mut reader = ArgsReader::new(data);
+        let commit_frequency_ms = reader.read_u32()?;
+        let mut seeds = Seeds::default();
+        for _ in 0..reader.read_u32()? {
+            seeds.push(reader.read_bytes()?)?;
+        }
+        let args = Self {
+            commit_frequency_ms,
+            seeds,
+            validator: reader.read_option(|r| r.read_array().map(Pubkey::new_from_array))?,
+            extended_undelegate_payload: reader.read_trailing(|r| r.read_bool())?,
+            seed_template: reader.read_trailing(|r| r.read_option(|r| r.read_borsh()))?,
+            undelegate_discriminator: reader
+                .read_trailing(|r| r.read_option(|r| r.read_array()))?,
+            heartbeat_timeout_slots: reader.read_trailing(|r| r.read_option(|r| r.read_u64()))?,
+            max_account_size: reader.read_trailing(|r| r.read_option(|r| r.read_borsh()))?,
+            rent_co_payer: reader.read_trailing(|r| r.read_option(|r| r.read_borsh()))?,
+        };
+        reader.finish()?;
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::SeedPlaceholder;
+
+    #[test]
+    fn test_instruction_data_with_extended_undelegate_payload() {
+        let mut args = DelegateArgs {
+            commit_frequency_ms: 1_000,
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            validator: Some(Pubkey::new_unique()),
+            extended_undelegate_payload: false,
+            seed_template: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            rent_co_payer: None,
+        };
+
+        // Without the flag the previous layout is kept
+        let data = args.to_instruction_data();
+        assert_eq!(data, borsh::to_vec(&args).unwrap());
+        assert!(
+            !DelegateArgs::try_from_instruction_data(&data)
+                .unwrap()
+                .extended_undelegate_payload
+        );
+
+        args.extended_undelegate_payload = true;
+        let data = args.to_instruction_data();
+        let deserialized = DelegateArgs::try_from_instruction_data(&data).unwrap();
+        assert!(deserialized.extended_undelegate_payload);
+        assert_eq!(deserialized.validator, args.validator);
+
+        assert!(DelegateArgs::try_from_instruction_data(&[data, vec![2]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_instruction_data_with_seed_template() {
+        let mut seed_template = SeedTemplate::default();
+        seed_template.push_literal(b"seed").unwrap();
+        seed_template
+            .push_placeholder(SeedPlaceholder::RentPayer)
+            .unwrap();
+        let args = DelegateArgs {
+            seed_template: Some(seed_template),
+            ..Default::default()
+        };
+
+        let data = args.to_instruction_data();
+        let deserialized = DelegateArgs::try_from_instruction_data(&data).unwrap();
+        assert!(!deserialized.extended_undelegate_payload);
+        assert_eq!(deserialized.seed_template, Some(seed_template));
+        assert!(deserialized.seeds.is_empty());
+    }
+
+    #[test]
+    fn test_instruction_data_with_undelegate_discriminator() {
+        let args = DelegateArgs {
+            commit_frequency_ms: 1_000,
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            undelegate_discriminator: Some([1, 2, 3, 4, 5, 6, 7, 8]),
+            ..Default::default()
+        };
+
+        // The previous trailing fields are serialized before the discriminator
+        let data = args.to_instruction_data();
+        assert_eq!(data.len(), borsh::to_vec(&args).unwrap().len() + 1 + 1 + 9);
+        let deserialized = DelegateArgs::try_from_instruction_data(&data).unwrap();
+        assert!(!deserialized.extended_undelegate_payload);
+        assert_eq!(deserialized.seed_template, None);
+        assert_eq!(
+            deserialized.undelegate_discriminator,
+            args.undelegate_discriminator
+        );
+    }
+
+    #[test]
+    fn test_instruction_data_with_heartbeat_timeout_slots() {
+        let args = DelegateArgs {
+            heartbeat_timeout_slots: Some(1_000),
+            max_account_size: None,
+            ..Default::default()
+        };
+
+        let data = args.to_instruction_data();
+        assert_eq!(
+            data.len(),
+            borsh::to_vec(&args).unwrap().len() + 1 + 1 + 1 + 9
+        );
+        let deserialized = DelegateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.undelegate_discriminator, None);
+        assert_eq!(deserialized.heartbeat_timeout_slots, Some(1_000));
+    }
+
+    #[test]
+    fn test_instruction_data_with_max_account_size() {
+        let max_account_size = MaxAccountSize {
+            max_len: 100,
+            policy: OversizedStatePolicy::Truncate,
+        };
+        let args = DelegateArgs {
+            max_account_size: Some(max_account_size),
+            ..Default::default()
+        };
+
+        let data = args.to_instruction_data();
+        assert_eq!(
+            data.len(),
+            borsh::to_vec(&args).unwrap().len() + 1 + 1 + 1 + 1 + 6
+        );
+        let deserialized = DelegateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.heartbeat_timeout_slots, None);
+        assert_eq!(deserialized.max_account_size, Some(max_account_size));
+
+        assert_eq!(max_account_size.retained_len(150), 100);
+        assert!(!max_account_size.rejects(150));
+        let max_account_size = MaxAccountSize {
+            policy: OversizedStatePolicy::Reject,
+            ..max_account_size
+        };
+        assert_eq!(max_account_size.retained_len(150), 150);
+        assert!(max_account_size.rejects(150));
+        assert!(!max_account_size.rejects(100));
+    }
+
+    #[test]
+    fn test_instruction_data_with_rent_co_payer() {
+        let rent_co_payer = RentCoPayer {
+            payer: Pubkey::new_unique(),
+            share_bps: 3_000,
+        };
+        let args = DelegateArgs {
+            rent_co_payer: Some(rent_co_payer),
+            ..Default::default()
+        };
+
+        let data = args.to_instruction_data();
+        assert_eq!(
+            data.len(),
+            borsh::to_vec(&args).unwrap().len() + 1 + 1 + 1 + 1 + 1 + 35
+        );
+        let deserialized = DelegateArgs::try_from_instruction_data(&data).unwrap();
+        assert_eq!(deserialized.max_account_size, None);
+        assert_eq!(deserialized.rent_co_payer, Some(rent_co_payer));
+    }
+
+    #[test]
+    fn test_rent_co_payer_share() {
+        let rent_co_payer = RentCoPayer {
+            payer: Pubkey::new_unique(),
+            share_bps: 3_333,
+        };
+        assert!(rent_co_payer.is_valid());
+
+        // The share is rounded down, the payer taking the remainder
+        assert_eq!(rent_co_payer.share_of(2_282_880), 760_883);
+        assert_eq!(rent_co_payer.share_of(1), 0);
+        assert_eq!(rent_co_payer.share_of(0), 0);
+        assert_eq!(rent_co_payer.share_of(u64::MAX), 6_148_299_799_767_393_553);
+
+        // Both payers must pay a non-zero share
+        for share_bps in [0, RENT_SHARE_BPS, u16::MAX] {
+            assert!(!RentCoPayer {
+                share_bps,
+                ..rent_co_payer
+            }
+            .is_valid());
+        }
+    }
 }
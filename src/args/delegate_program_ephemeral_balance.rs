@@ -0,0 +1,12 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::DelegateArgs;
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct DelegateProgramEphemeralBalanceArgs {
+    pub delegate_args: DelegateArgs,
+    /// The program the ephemeral balance is scoped to.
+    pub program_id: Pubkey,
+    pub index: u8,
+}
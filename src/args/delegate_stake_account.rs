@@ -0,0 +1,12 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct DelegateStakeAccountArgs {
+    /// The frequency at which the validator should settle the stake account, the stake
+    /// authority being able to undelegate it after a multiple of it, see
+    /// [crate::state::DelegationRecord::commit_timeout_slots]
+    pub commit_frequency_ms: u32,
+    /// The validator authority that is added to the delegation record
+    pub validator: Option<Pubkey>,
+}
@@ -0,0 +1,8 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct DelegationAuthorityArgs {
+    /// The validator added to or removed from the authorities of the delegation
+    pub validator: Pubkey,
+}
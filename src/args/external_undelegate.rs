@@ -0,0 +1,19 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::args::Seeds;
+
+/// The v2 payload of the external undelegate CPI, following
+/// [crate::consts::EXTERNAL_UNDELEGATE_DISCRIMINATOR] and the
+/// [crate::consts::EXTERNAL_UNDELEGATE_PAYLOAD_V2] version byte.
+/// The v1 payload only carries the seeds.
+#[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ExternalUndelegateArgsV2 {
+    /// The seeds used to derive the PDA of the delegated account
+    pub seeds: Seeds,
+    /// The nonce of the last finalized commit
+    pub nonce: u64,
+    /// The lamports at the time of delegation or from the last state finalization
+    pub lamports: u64,
+    /// The slot at which the delegation was created
+    pub delegation_slot: u64,
+}
@@ -0,0 +1,9 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct FundEscrowFromDelegatedArgs {
+    /// The lamports moved from the delegated account to the ephemeral balance
+    pub amount: u64,
+    /// The index of the ephemeral balance, see [crate::pda::ephemeral_balance_pda_from_payer]
+    pub index: u8,
+}
@@ -0,0 +1,8 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct GetEscrowSummariesArgs {
+    /// The index of each escrow passed, in the order of the accounts
+    pub indexes: Vec<u8>,
+}
@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct GrantFeeExemptionArgs {
+    /// See [crate::state::FeeExemption::expiry_slot]
+    pub expiry_slot: u64,
+}
@@ -0,0 +1,8 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct GrowCommitStateArgs {
+    /// The chunk of data to append to the streamed commit state
+    pub data: Vec<u8>,
+}
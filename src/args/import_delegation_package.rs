@@ -0,0 +1,9 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::state::DelegationPackage;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct ImportDelegationPackageArgs {
+    /// The package exported by [crate::processor::process_export_delegation_package]
+    pub package: DelegationPackage,
+}
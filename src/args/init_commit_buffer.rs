@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct InitCommitBufferArgs {
+    /// The length of the state to stage, which is the length of the committed data
+    pub data_len: u32,
+}
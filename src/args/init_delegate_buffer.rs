@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct InitDelegateBufferArgs {
+    /// The length of the data to stage, which must be the length of the delegated account
+    pub data_len: u32,
+}
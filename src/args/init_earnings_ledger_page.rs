@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct InitEarningsLedgerPageArgs {
+    /// The index of the page, following the last page of the ledger
+    pub page: u32,
+}
@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct InitSessionReportArgs {
+    /// The index of the escrow of the authority
+    pub escrow_index: u8,
+}
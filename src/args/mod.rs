@@ -1,15 +1,105 @@
+mod approve_undelegate_and_close;
 mod call_handler;
+mod close_program_config;
+mod close_program_ephemeral_balance;
+mod commit_from_owner;
 mod commit_state;
+mod commit_state_chunk;
 mod delegate;
 mod delegate_ephemeral_balance;
+mod delegate_program_ephemeral_balance;
+mod delegate_stake_account;
+mod delegation_authority;
+mod external_undelegate;
+mod fund_escrow_from_delegated;
+mod get_escrow_summaries;
+mod grant_fee_exemption;
+mod grow_commit_state;
+mod import_delegation_package;
+mod init_commit_buffer;
+mod init_delegate_buffer;
+mod init_earnings_ledger_page;
+mod init_session_report;
+mod reader;
+mod redelegate_ephemeral_balance;
+mod register_commit_relayer;
+mod resync_protocol_stats;
+mod seeds;
+mod set_authority_grant;
+mod set_call_handler_permissions;
+mod set_commit_schedule;
+mod set_feature_gate;
+mod set_program_allowed_data_lens;
+mod set_program_max_delegation_slots;
+mod set_program_undelegate_discriminator_override;
+mod set_program_undelegate_lamports_tolerance;
+mod set_program_validate_delegations;
+mod set_protocol_config;
+mod set_validator_commit_quota;
+mod set_validator_info;
+mod set_version;
+mod split_delegation;
 mod top_up_ephemeral_balance;
+mod top_up_ephemeral_balance_batch;
+mod top_up_program_ephemeral_balance;
+mod undelegate;
+mod undelegate_stake_account;
+mod update_delegation_authority;
 mod validator_claim_fees;
 mod whitelist_validator_for_program;
+mod whitelist_validators_for_program_batch;
+mod write_commit_buffer;
+mod write_delegate_buffer_chunk;
 
+pub use approve_undelegate_and_close::*;
 pub use call_handler::*;
+pub use close_program_config::*;
+pub use close_program_ephemeral_balance::*;
+pub use commit_from_owner::*;
 pub use commit_state::*;
+pub use commit_state_chunk::*;
 pub use delegate::*;
 pub use delegate_ephemeral_balance::*;
+pub use delegate_program_ephemeral_balance::*;
+pub use delegate_stake_account::*;
+pub use delegation_authority::*;
+pub use external_undelegate::*;
+pub use fund_escrow_from_delegated::*;
+pub use get_escrow_summaries::*;
+pub use grant_fee_exemption::*;
+pub use grow_commit_state::*;
+pub use import_delegation_package::*;
+pub use init_commit_buffer::*;
+pub use init_delegate_buffer::*;
+pub use init_earnings_ledger_page::*;
+pub use init_session_report::*;
+pub use reader::*;
+pub use redelegate_ephemeral_balance::*;
+pub use register_commit_relayer::*;
+pub use resync_protocol_stats::*;
+pub use seeds::*;
+pub use set_authority_grant::*;
+pub use set_call_handler_permissions::*;
+pub use set_commit_schedule::*;
+pub use set_feature_gate::*;
+pub use set_program_allowed_data_lens::*;
+pub use set_program_max_delegation_slots::*;
+pub use set_program_undelegate_discriminator_override::*;
+pub use set_program_undelegate_lamports_tolerance::*;
+pub use set_program_validate_delegations::*;
+pub use set_protocol_config::*;
+pub use set_validator_commit_quota::*;
+pub use set_validator_info::*;
+pub use set_version::*;
+pub use split_delegation::*;
 pub use top_up_ephemeral_balance::*;
+pub use top_up_ephemeral_balance_batch::*;
+pub use top_up_program_ephemeral_balance::*;
+pub use undelegate::*;
+pub use undelegate_stake_account::*;
+pub use update_delegation_authority::*;
 pub use validator_claim_fees::*;
 pub use whitelist_validator_for_program::*;
+pub use whitelist_validators_for_program_batch::*;
+pub use write_commit_buffer::*;
+pub use write_delegate_buffer_chunk::*;
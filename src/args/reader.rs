@@ -0,0 +1,133 @@
+use borsh::io::{Error, ErrorKind, Result};
+
+use borsh::BorshDeserialize;
+
+/// A cursor reading instruction args with the borsh layout, without allocating: the
+/// variable length fields are borrowed from the instruction data instead of being copied.
+pub struct ArgsReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ArgsReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn read_slice(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Unexpected length of input",
+            ));
+        }
+        let (slice, data) = self.data.split_at(len);
+        self.data = data;
+        Ok(slice)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.read_slice(N)?
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "Unexpected length of input"))
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid bool representation",
+            )),
+        }
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a `Vec<u8>`, borrowing its bytes
+    pub fn read_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_u32()? as usize;
+        self.read_slice(len)
+    }
+
+    pub fn read_option<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<Option<T>> {
+        match self.read_u8()? {
+            0 => Ok(None),
+            1 => read(self).map(Some),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid Option representation",
+            )),
+        }
+    }
+
+    /// Reads a field appended to the args layout, falling back to its default value if the
+    /// args were serialized with the previous layout, see
+    /// [crate::state::trailing::deserialize_trailing]
+    pub fn read_trailing<T: Default>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if self.is_empty() {
+            return Ok(T::default());
+        }
+        read(self)
+    }
+
+    /// Reads a borsh type which does not allocate when deserialized
+    pub fn read_borsh<T: BorshDeserialize>(&mut self) -> Result<T> {
+        T::deserialize(&mut self.data)
+    }
+
+    /// Checks that all the args were read
+    pub fn finish(self) -> Result<()> {
+        if !self.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "Not all bytes read"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_args_reader_borsh_layout() {
+        let data = borsh::to_vec(&(7u64, true, vec![1u8, 2, 3], Some([9u8; 4]))).unwrap();
+        let mut reader = ArgsReader::new(&data);
+        assert_eq!(reader.read_u64().unwrap(), 7);
+        assert!(reader.read_bool().unwrap());
+        assert_eq!(reader.read_bytes().unwrap(), &[1, 2, 3]);
+        assert_eq!(
+            reader.read_option(|r| r.read_array::<4>()).unwrap(),
+            Some([9; 4])
+        );
+        assert_eq!(reader.read_trailing(|r| r.read_u32()).unwrap(), 0);
+        reader.finish().unwrap();
+
+        // Truncated data and leftover bytes are rejected
+        assert!(ArgsReader::new(&data[9..14]).read_bytes().is_err());
+        let mut reader = ArgsReader::new(&data);
+        reader.read_u64().unwrap();
+        assert!(reader.finish().is_err());
+        assert!(ArgsReader::new(&[2]).read_bool().is_err());
+    }
+}
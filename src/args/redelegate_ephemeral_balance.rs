@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct RedelegateEphemeralBalanceArgs {
+    /// The index of the ephemeral balance, see [crate::pda::ephemeral_balance_pda_from_payer]
+    pub index: u8,
+}
@@ -0,0 +1,10 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct RegisterCommitRelayerArgs {
+    /// The relayer submitting commits on behalf of the validator
+    pub relayer: Pubkey,
+    /// Whether the relayer is approved, false revokes it
+    pub approved: bool,
+}
@@ -0,0 +1,8 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct ResyncProtocolStatsArgs {
+    /// Whether the total value locked is reset to the lamports of the delegation records
+    /// passed, otherwise they are added to it, to resync over several transactions
+    pub reset: bool,
+}
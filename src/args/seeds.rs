@@ -0,0 +1,292 @@
+use alloc::vec::Vec;
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use core::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// The maximum number of seeds a [Seeds] container can hold.
+pub const MAX_SEEDS: usize = 16;
+
+/// The maximum length of a single seed.
+pub const MAX_SEED_LEN: usize = 32;
+
+/// A bounded, stack-allocated list of seeds.
+///
+/// It is serialized exactly like `Vec<Vec<u8>>`, but deserializing it does not allocate,
+/// which matters for the delegate instruction since it is the hottest one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Seeds {
+    len: u8,
+    seed_lens: [u8; MAX_SEEDS],
+    seeds: [[u8; MAX_SEED_LEN]; MAX_SEEDS],
+}
+
+impl Default for Seeds {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            seed_lens: [0; MAX_SEEDS],
+            seeds: [[0; MAX_SEED_LEN]; MAX_SEEDS],
+        }
+    }
+}
+
+impl Seeds {
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        (index < self.len()).then(|| &self.seeds[index][..self.seed_lens[index] as usize])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        (0..self.len()).map(|index| &self.seeds[index][..self.seed_lens[index] as usize])
+    }
+
+    /// Appends a seed, erroring if the container is full or the seed is too long.
+    pub fn push(&mut self, seed: &[u8]) -> Result<()> {
+        if self.len() == MAX_SEEDS {
+            return Err(Error::new(ErrorKind::InvalidData, "too many seeds"));
+        }
+        if seed.len() > MAX_SEED_LEN {
+            return Err(Error::new(ErrorKind::InvalidData, "seed too long"));
+        }
+        let index = self.len();
+        self.seeds[index][..seed.len()].copy_from_slice(seed);
+        self.seed_lens[index] = seed.len() as u8;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// The size of the seeds once serialized.
+    pub fn serialized_size(&self) -> usize {
+        4 + self.iter().map(|seed| 4 + seed.len()).sum::<usize>()
+    }
+
+    pub fn to_vec(&self) -> Vec<Vec<u8>> {
+        self.iter().map(|seed| seed.to_vec()).collect()
+    }
+}
+
+impl TryFrom<&[&[u8]]> for Seeds {
+    type Error = Error;
+
+    fn try_from(slices: &[&[u8]]) -> Result<Self> {
+        let mut seeds = Seeds::default();
+        for seed in slices {
+            seeds.push(seed)?;
+        }
+        Ok(seeds)
+    }
+}
+
+impl TryFrom<Vec<Vec<u8>>> for Seeds {
+    type Error = Error;
+
+    fn try_from(vecs: Vec<Vec<u8>>) -> Result<Self> {
+        let mut seeds = Seeds::default();
+        for seed in vecs.iter() {
+            seeds.push(seed)?;
+        }
+        Ok(seeds)
+    }
+}
+
+impl fmt::Debug for Seeds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl BorshSerialize for Seeds {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        (self.len as u32).serialize(writer)?;
+        for seed in self.iter() {
+            (seed.len() as u32).serialize(writer)?;
+            writer.write_all(seed)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for Seeds {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        if len > MAX_SEEDS {
+            return Err(Error::new(ErrorKind::InvalidData, "too many seeds"));
+        }
+        let mut seeds = Seeds::default();
+        for index in 0..len {
+            let seed_len = u32::deserialize_reader(reader)? as usize;
+            if seed_len > MAX_SEED_LEN {
+                return Err(Error::new(ErrorKind::InvalidData, "seed too long"));
+            }
+            reader.read_exact(&mut seeds.seeds[index][..seed_len])?;
+            seeds.seed_lens[index] = seed_len as u8;
+        }
+        seeds.len = len as u8;
+        Ok(seeds)
+    }
+}
+
+/// A key resolved into a seed of a [SeedTemplate] when the seeds are needed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SeedPlaceholder {
+    /// The payer of the delegation, which is the rent payer of the delegation metadata
+    RentPayer = 1,
+    /// The owner program of the delegated account
+    OwnerProgram = 2,
+}
+
+/// The tag of a literal seed in a serialized [SeedTemplate]
+const LITERAL_SEED_TAG: u8 = 0;
+
+impl TryFrom<u8> for SeedPlaceholder {
+    type Error = Error;
+
+    fn try_from(tag: u8) -> Result<Self> {
+        match tag {
+            1 => Ok(Self::RentPayer),
+            2 => Ok(Self::OwnerProgram),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                "invalid seed placeholder",
+            )),
+        }
+    }
+}
+
+/// Seeds of a delegated account made of literal seeds and of placeholders for the keys of
+/// the delegation, see [SeedPlaceholder].
+///
+/// The delegation stores the template instead of the resolved seeds, so that a key costs a
+/// single byte, and both the delegate and the undelegate instructions check that the resolved
+/// seeds derive the delegated account.
+///
+/// It is serialized as a `Vec` of entries, each being the tag of the placeholder, or the
+/// literal seed tag (0) followed by the seed as a `Vec<u8>`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeedTemplate {
+    /// The literal seeds, empty at the index of a placeholder
+    literals: Seeds,
+    /// The placeholder tags, [LITERAL_SEED_TAG] at the index of a literal seed
+    placeholders: [u8; MAX_SEEDS],
+}
+
+impl SeedTemplate {
+    pub fn len(&self) -> usize {
+        self.literals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty()
+    }
+
+    /// Appends a literal seed, erroring if the template is full or the seed is too long.
+    pub fn push_literal(&mut self, seed: &[u8]) -> Result<()> {
+        self.literals.push(seed)
+    }
+
+    /// Appends a placeholder, erroring if the template is full.
+    pub fn push_placeholder(&mut self, placeholder: SeedPlaceholder) -> Result<()> {
+        let index = self.len();
+        self.literals.push(&[])?;
+        self.placeholders[index] = placeholder as u8;
+        Ok(())
+    }
+
+    /// The placeholder at the index, None if the seed is a literal or out of bounds
+    pub fn placeholder(&self, index: usize) -> Option<SeedPlaceholder> {
+        (index < self.len())
+            .then(|| SeedPlaceholder::try_from(self.placeholders[index]).ok())
+            .flatten()
+    }
+
+    /// Resolve the placeholders into the keys of the delegation
+    pub fn resolve(&self, rent_payer: &[u8; 32], owner_program: &[u8; 32]) -> Seeds {
+        let mut seeds = Seeds::default();
+        for (index, literal) in self.literals.iter().enumerate() {
+            let seed: &[u8] = match self.placeholder(index) {
+                Some(SeedPlaceholder::RentPayer) => rent_payer,
+                Some(SeedPlaceholder::OwnerProgram) => owner_program,
+                None => literal,
+            };
+            // A key fits in a seed and there are as many seeds as entries in the template
+            seeds.seeds[index][..seed.len()].copy_from_slice(seed);
+            seeds.seed_lens[index] = seed.len() as u8;
+        }
+        seeds.len = self.literals.len;
+        seeds
+    }
+
+    /// The size of the template once serialized.
+    pub fn serialized_size(&self) -> usize {
+        4 + (0..self.len())
+            .map(|index| match self.placeholder(index) {
+                Some(_) => 1,
+                None => 1 + 4 + self.literals.get(index).map_or(0, <[u8]>::len),
+            })
+            .sum::<usize>()
+    }
+}
+
+impl fmt::Debug for SeedTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for (index, literal) in self.literals.iter().enumerate() {
+            match self.placeholder(index) {
+                Some(placeholder) => list.entry(&placeholder),
+                None => list.entry(&literal),
+            };
+        }
+        list.finish()
+    }
+}
+
+impl BorshSerialize for SeedTemplate {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        (self.len() as u32).serialize(writer)?;
+        for (index, literal) in self.literals.iter().enumerate() {
+            match self.placeholder(index) {
+                Some(placeholder) => (placeholder as u8).serialize(writer)?,
+                None => {
+                    LITERAL_SEED_TAG.serialize(writer)?;
+                    (literal.len() as u32).serialize(writer)?;
+                    writer.write_all(literal)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for SeedTemplate {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        if len > MAX_SEEDS {
+            return Err(Error::new(ErrorKind::InvalidData, "too many seeds"));
+        }
+        let mut template = SeedTemplate::default();
+        for _ in 0..len {
+            match u8::deserialize_reader(reader)? {
+                LITERAL_SEED_TAG => {
+                    let seed_len = u32::deserialize_reader(reader)? as usize;
+                    if seed_len > MAX_SEED_LEN {
+                        return Err(Error::new(ErrorKind::InvalidData, "seed too long"));
+                    }
+                    let mut seed = [0u8; MAX_SEED_LEN];
+                    reader.read_exact(&mut seed[..seed_len])?;
+                    template.push_literal(&seed[..seed_len])?;
+                }
+                tag => template.push_placeholder(SeedPlaceholder::try_from(tag)?)?,
+            }
+        }
+        Ok(template)
+    }
+}
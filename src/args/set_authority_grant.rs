@@ -0,0 +1,33 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetAuthorityGrantArgs {
+    /// The sub-key the delegator grants its commit authority to
+    pub key: Pubkey,
+    /// The slot from which the grant is expired
+    pub expiry_slot: u64,
+    /// What the sub-key can do on behalf of the delegator
+    pub scope: AuthorityScope,
+    /// Whether the sub-key is granted, false revokes it
+    pub approved: bool,
+}
+
+/// What a sub-key can do on behalf of the key granting it authority, see
+/// [crate::state::AuthorityDelegationChain]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[borsh(use_discriminant = true)]
+pub enum AuthorityScope {
+    /// Commit the delegated accounts, without allowing their undelegation
+    Commit = 0,
+    /// Commit the delegated accounts, allowing their undelegation
+    CommitAndUndelegate = 1,
+}
+
+impl AuthorityScope {
+    /// Returns true if the scope allows everything the `other` scope allows
+    pub fn covers(self, other: AuthorityScope) -> bool {
+        self as u8 >= other as u8
+    }
+}
@@ -0,0 +1,13 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetCallHandlerPermissionsArgs {
+    /// The index of the escrow of the authority
+    pub escrow_index: u8,
+    /// Whether the handlers can be called along a commit
+    pub allow_commit: bool,
+    /// Whether the handlers can be called along an undelegation
+    pub allow_undelegate: bool,
+    /// Whether the handlers can be called on their own
+    pub allow_standalone: bool,
+}
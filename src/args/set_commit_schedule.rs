@@ -0,0 +1,13 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetCommitScheduleArgs {
+    /// The index of the ephemeral balance escrow of the rent payer funding the commits
+    pub escrow_index: u8,
+    /// See [crate::state::CommitSchedule::interval_slots]
+    pub interval_slots: u64,
+    /// See [crate::state::CommitSchedule::max_commits_per_interval]
+    pub max_commits_per_interval: u64,
+    /// See [crate::state::CommitSchedule::commit_fee_lamports]
+    pub commit_fee_lamports: u64,
+}
@@ -0,0 +1,9 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetFeatureGateArgs {
+    /// The discriminator of the instruction to toggle
+    pub discriminator: u8,
+    /// Whether the instruction is enabled or disabled
+    pub enabled: bool,
+}
@@ -0,0 +1,8 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetProgramAllowedDataLensArgs {
+    /// The data lengths the accounts of the program can have, an empty list allows any length
+    pub allowed_data_lens: Vec<u32>,
+}
@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetProgramMaxDelegationSlotsArgs {
+    /// The slots a delegation of an account of the program can last, unlimited if zero
+    pub max_delegation_slots: u64,
+}
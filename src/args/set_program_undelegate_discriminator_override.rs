@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetProgramUndelegateDiscriminatorOverrideArgs {
+    /// See [crate::state::ProgramConfig::allow_undelegate_discriminator_override]
+    pub allow_undelegate_discriminator_override: bool,
+}
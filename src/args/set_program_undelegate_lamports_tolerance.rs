@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetProgramUndelegateLamportsToleranceArgs {
+    /// The lamports the program can add to the validator in its external undelegate handler
+    pub undelegate_lamports_tolerance: u64,
+}
@@ -0,0 +1,7 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetProgramValidateDelegationsArgs {
+    /// See [crate::state::ProgramConfig::validate_delegations]
+    pub validate_delegations: bool,
+}
@@ -0,0 +1,12 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetProtocolConfigArgs {
+    /// See [crate::state::ProtocolConfig::escrow_dust_threshold]
+    pub escrow_dust_threshold: u64,
+    /// See [crate::state::ProtocolConfig::undelegation_grace_slots]
+    pub undelegation_grace_slots: u64,
+    /// See [crate::state::ProtocolConfig::trusted_exporter]
+    pub trusted_exporter: Pubkey,
+}
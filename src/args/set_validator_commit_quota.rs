@@ -0,0 +1,9 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetValidatorCommitQuotaArgs {
+    /// The commits accepted per slot, if zero the commits are unlimited
+    pub max_commits_per_slot: u64,
+    /// The bytes of committed state accepted per slot, if zero the bytes are unlimited
+    pub max_bytes_per_slot: u64,
+}
@@ -0,0 +1,12 @@
+use alloc::string::String;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetValidatorInfoArgs {
+    /// The RPC endpoint of the ephemeral rollup
+    pub endpoint: String,
+    /// The version of the validator software
+    pub version: String,
+    /// The identifier of the cluster the validator belongs to
+    pub cluster_id: String,
+}
@@ -0,0 +1,17 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct SetVersionArgs {
+    /// See [crate::state::ProgramVersion::major]
+    pub major: u16,
+    /// See [crate::state::ProgramVersion::minor]
+    pub minor: u16,
+    /// See [crate::state::ProgramVersion::patch]
+    pub patch: u16,
+    /// See [crate::state::ProgramVersion::git_hash]
+    pub git_hash: [u8; 20],
+    /// See [crate::state::ProgramVersion::min_discriminator]
+    pub min_discriminator: u8,
+    /// See [crate::state::ProgramVersion::max_discriminator]
+    pub max_discriminator: u8,
+}
@@ -0,0 +1,76 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::args::Seeds;
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct SplitDelegationArgs {
+    /// The half-open byte ranges [start, end) of the delegated account data that are
+    /// moved into the new delegated account. Ranges must be sorted and non-overlapping.
+    pub ranges: Vec<(u32, u32)>,
+    /// The seeds used to derive the PDA of the new delegated account
+    pub seeds: Seeds,
+}
+
+impl SplitDelegationArgs {
+    /// Split the data into the bytes in the ranges and the remaining bytes, or None if there
+    /// is no range or if a range is empty, unsorted, overlapping or out of the data
+    pub fn split(&self, data: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+        if self.ranges.is_empty() {
+            return None;
+        }
+        let mut new_data = Vec::new();
+        let mut remaining_data = Vec::with_capacity(data.len());
+        let mut cursor = 0;
+        for &(start, end) in self.ranges.iter() {
+            let (start, end) = (start as usize, end as usize);
+            let split = data.get(start..end).filter(|split| !split.is_empty())?;
+            remaining_data.extend_from_slice(data.get(cursor..start)?);
+            new_data.extend_from_slice(split);
+            cursor = end;
+        }
+        remaining_data.extend_from_slice(data.get(cursor..).unwrap_or_default());
+        Some((new_data, remaining_data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(ranges: Vec<(u32, u32)>, data: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+        SplitDelegationArgs {
+            ranges,
+            seeds: Seeds::default(),
+        }
+        .split(data)
+    }
+
+    #[test]
+    fn test_split() {
+        let data: Vec<u8> = (0..16).collect();
+        let (new_data, remaining_data) = split(vec![(0, 4), (10, 12)], &data).unwrap();
+        assert_eq!(new_data, [&data[0..4], &data[10..12]].concat());
+        assert_eq!(remaining_data, [&data[4..10], &data[12..]].concat());
+
+        // The whole data can be moved
+        let (new_data, remaining_data) = split(vec![(0, 16)], &data).unwrap();
+        assert_eq!(new_data, data);
+        assert!(remaining_data.is_empty());
+    }
+
+    #[test]
+    fn test_split_invalid_ranges() {
+        let data = [0; 16];
+        // No range
+        assert!(split(vec![], &data).is_none());
+        // Overlapping
+        assert!(split(vec![(0, 6), (4, 8)], &data).is_none());
+        // Unsorted
+        assert!(split(vec![(8, 10), (0, 4)], &data).is_none());
+        // Empty
+        assert!(split(vec![(4, 4)], &data).is_none());
+        // Out of the data
+        assert!(split(vec![(10, 17)], &data).is_none());
+    }
+}
@@ -0,0 +1,45 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// The maximum number of escrows funded by a top-up batch
+pub const MAX_TOP_UP_BATCH_ESCROWS: usize = 16;
+
+/// The index of an ephemeral balance escrow, see
+/// [crate::pda::ephemeral_balance_pda_from_payer] and
+/// [crate::pda::wide_ephemeral_balance_pda_from_payer]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum EphemeralBalanceIndex {
+    /// The index of the original u8 index space
+    Narrow(u8),
+    /// The index of the wider u32 index space
+    Wide(u32),
+}
+
+impl EphemeralBalanceIndex {
+    /// The ephemeral balance escrow of the payer at this index
+    pub fn pda(&self, payer: &Pubkey) -> Pubkey {
+        match *self {
+            EphemeralBalanceIndex::Narrow(index) => {
+                crate::pda::ephemeral_balance_pda_from_payer(payer, index)
+            }
+            EphemeralBalanceIndex::Wide(index) => {
+                crate::pda::wide_ephemeral_balance_pda_from_payer(payer, index)
+            }
+        }
+    }
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct TopUpEphemeralBalanceBatchEntry {
+    /// The index of the escrow of the pubkey to top up
+    pub index: EphemeralBalanceIndex,
+    /// The amount to add to the escrow
+    pub amount: u64,
+}
+
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct TopUpEphemeralBalanceBatchArgs {
+    /// The escrows to top up, in the order of their accounts
+    pub entries: Vec<TopUpEphemeralBalanceBatchEntry>,
+}
@@ -0,0 +1,13 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct TopUpProgramEphemeralBalanceArgs {
+    /// The amount to add to the ephemeral balance.
+    pub amount: u64,
+    /// The program the ephemeral balance is scoped to.
+    pub program_id: Pubkey,
+    /// The index of the ephemeral balance account to top up which allows
+    /// one payer to have multiple ephemeral balance accounts for the same program.
+    pub index: u8,
+}
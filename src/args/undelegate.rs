@@ -0,0 +1,33 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// The args of [crate::processor::fast::process_undelegate]. An undelegate instruction
+/// without data undelegates with [UndelegateMode::Owner].
+#[derive(Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UndelegateArgs {
+    pub mode: UndelegateMode,
+}
+
+/// How the state of a delegated account is given back on undelegation
+#[repr(u8)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[borsh(use_discriminant = true)]
+pub enum UndelegateMode {
+    /// CPI the external undelegate instruction of the owner program to re-open the account
+    #[default]
+    Owner = 0,
+    /// Park the state and the lamports of the account in the parked undelegation PDA, claimable
+    /// by the rent payer, without the CPI to the owner program. This recovers the accounts of an
+    /// owner program whose external undelegate instruction fails, and requires the signature of
+    /// the rent payer.
+    ToBuffer = 1,
+}
+
+impl UndelegateArgs {
+    /// Parse the args of an undelegate instruction, its data being optional
+    pub fn try_from_instruction_data(data: &[u8]) -> Result<Self, borsh::io::Error> {
+        if data.is_empty() {
+            return Ok(Self::default());
+        }
+        Self::try_from_slice(data)
+    }
+}
@@ -0,0 +1,9 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Default, Debug, BorshSerialize, BorshDeserialize)]
+pub struct UndelegateStakeAccountArgs {
+    /// The state of the stake account in the ephemeral rollup, in the layout of the stake
+    /// program, see [crate::state::NativeStakeState]
+    pub stake_state: Vec<u8>,
+}
@@ -0,0 +1,8 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct UpdateDelegationAuthorityArgs {
+    /// The validator taking over the delegation
+    pub new_authority: Pubkey,
+}
@@ -0,0 +1,14 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// The maximum number of validators inserted and removed by a whitelist batch
+pub const MAX_WHITELIST_BATCH_VALIDATORS: usize = 32;
+
+#[derive(Debug, Default, BorshSerialize, BorshDeserialize)]
+pub struct WhitelistValidatorsForProgramBatchArgs {
+    /// The validator identities to insert into the program whitelist
+    pub insert: Vec<Pubkey>,
+    /// The validator identities to remove from the program whitelist, after the insertions
+    pub remove: Vec<Pubkey>,
+}
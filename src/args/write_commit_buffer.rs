@@ -0,0 +1,10 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct WriteCommitBufferArgs {
+    /// The offset of the chunk in the staged state
+    pub offset: u32,
+    /// The chunk of state to write
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,10 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct WriteDelegateBufferChunkArgs {
+    /// The offset of the chunk in the staged data
+    pub offset: u32,
+    /// The chunk of data to write
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,266 @@
+//! LZ4 block compression of committed states, see [crate::args::Encoding].
+//!
+//! A compressed state is the length of the decompressed state, as a little endian u32,
+//! followed by an LZ4 block, the layout of `lz4_flex::compress_prepend_size`. The block
+//! follows the LZ4 block format: a sequence is a token, whose high and low nibbles hold the
+//! literals length and the match length minus [MIN_MATCH], the literals, a little endian
+//! u16 offset back into the decompressed bytes and the extension bytes of a nibble equal to
+//! 15. The last sequence only holds literals.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(not(feature = "sdk"))]
+use pinocchio::program_error::ProgramError;
+
+#[cfg(not(feature = "sdk"))]
+use crate::error::DlpError;
+
+/// Size of the decompressed length prefixing a compressed state
+pub const SIZE_OF_DECOMPRESSED_LEN: usize = 4;
+
+/// Minimum length of a match, subtracted from the match length of a token
+const MIN_MATCH: usize = 4;
+
+/// The last literals of a block, which no match can cover, as required by the LZ4 format
+const LAST_LITERALS: usize = 5;
+
+/// Matches must start at least that many bytes before the end of the block
+const MF_LIMIT: usize = 12;
+
+/// Farthest offset of a match
+const MAX_OFFSET: usize = u16::MAX as usize;
+
+/// Nibble of a token whose length is extended by the bytes following it
+const EXTENDED_LENGTH: usize = 15;
+
+const HASH_LOG: u32 = 12;
+
+/// Compress a state into the length of the state followed by an LZ4 block of it, with a
+/// greedy single pass compressor trading ratio for speed
+pub fn compress_lz4(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(SIZE_OF_DECOMPRESSED_LEN + data.len() + data.len() / 255 + 16);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+    // Positions of the last 4 bytes sequences seen, plus one so that 0 is empty
+    let mut table = vec![0usize; 1 << HASH_LOG];
+    let match_limit = data.len().saturating_sub(MF_LIMIT);
+    let end_of_matches = data.len().saturating_sub(LAST_LITERALS);
+    let mut anchor = 0;
+    let mut position = 0;
+    while position < match_limit {
+        let sequence = read_sequence(data, position);
+        let Some(slot) = table.get_mut(hash(sequence)) else {
+            break;
+        };
+        let Some(candidate) = core::mem::replace(slot, position + 1).checked_sub(1) else {
+            position += 1;
+            continue;
+        };
+        if position - candidate > MAX_OFFSET || read_sequence(data, candidate) != sequence {
+            position += 1;
+            continue;
+        }
+
+        let mut match_len = MIN_MATCH;
+        while position + match_len < end_of_matches
+            && data.get(candidate + match_len) == data.get(position + match_len)
+        {
+            match_len += 1;
+        }
+        write_sequence(
+            &mut out,
+            data.get(anchor..position).unwrap_or_default(),
+            Some((position - candidate, match_len)),
+        );
+        position += match_len;
+        anchor = position;
+    }
+    write_sequence(&mut out, data.get(anchor..).unwrap_or_default(), None);
+    out
+}
+
+/// Read the 4 bytes sequence at a position, 0 past the end of the data
+fn read_sequence(data: &[u8], position: usize) -> u32 {
+    data.get(position..position + MIN_MATCH)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map_or(0, u32::from_le_bytes)
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
+
+/// Write a sequence of literals followed by a match of an offset and length, if any
+fn write_sequence(out: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_len = matched.map_or(0, |(_, len)| len - MIN_MATCH);
+    let token = (literals.len().min(EXTENDED_LENGTH) << 4) | match_len.min(EXTENDED_LENGTH);
+    out.push(token as u8);
+    write_extended_length(out, literals.len());
+    out.extend_from_slice(literals);
+    if let Some((offset, _)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        write_extended_length(out, match_len);
+    }
+}
+
+fn write_extended_length(out: &mut Vec<u8>, len: usize) {
+    let Some(mut remaining) = len.checked_sub(EXTENDED_LENGTH) else {
+        return;
+    };
+    while remaining >= 255 {
+        out.push(255);
+        remaining -= 255;
+    }
+    out.push(remaining as u8);
+}
+
+/// Split a compressed state into the length of the decompressed state and its LZ4 block
+#[cfg(not(feature = "sdk"))]
+pub fn split_decompressed_len(compressed: &[u8]) -> Result<(usize, &[u8]), ProgramError> {
+    let (len, block) = compressed
+        .split_first_chunk::<SIZE_OF_DECOMPRESSED_LEN>()
+        .ok_or(DlpError::InvalidCompressedState)?;
+    Ok((u32::from_le_bytes(*len) as usize, block))
+}
+
+/// Decompress an LZ4 block into `out`, which the block must fill exactly.
+///
+/// A malformed block, reading past its end, matching before the start of `out` or writing
+/// past its end, is rejected instead of panicking.
+#[cfg(not(feature = "sdk"))]
+pub fn decompress_lz4_into(block: &[u8], out: &mut [u8]) -> Result<(), ProgramError> {
+    let mut input = 0;
+    let mut output = 0;
+    loop {
+        let token = *block.get(input).ok_or(DlpError::InvalidCompressedState)? as usize;
+        input += 1;
+
+        let literals_len = read_extended_length(block, &mut input, token >> 4)?;
+        let literals = input
+            .checked_add(literals_len)
+            .and_then(|literals_end| block.get(input..literals_end))
+            .ok_or(DlpError::InvalidCompressedState)?;
+        out.get_mut(output..output + literals_len)
+            .ok_or(DlpError::InvalidCompressedState)?
+            .copy_from_slice(literals);
+        input += literals_len;
+        output += literals_len;
+
+        // The last sequence only holds literals
+        if input == block.len() {
+            break;
+        }
+
+        let offset = block
+            .get(input..input + 2)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u16::from_le_bytes)
+            .ok_or(DlpError::InvalidCompressedState)? as usize;
+        input += 2;
+        if offset == 0 || offset > output {
+            return Err(DlpError::InvalidCompressedState.into());
+        }
+        let match_len = read_extended_length(block, &mut input, token & 0xF)? + MIN_MATCH;
+        if match_len > out.len() - output {
+            return Err(DlpError::InvalidCompressedState.into());
+        }
+
+        // A match overlapping its own output repeats the bytes of its offset
+        let mut remaining = match_len;
+        while remaining > 0 {
+            let chunk = remaining.min(offset);
+            out.copy_within(output - offset..output - offset + chunk, output);
+            output += chunk;
+            remaining -= chunk;
+        }
+    }
+
+    if output != out.len() {
+        return Err(DlpError::InvalidCompressedState.into());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "sdk"))]
+fn read_extended_length(
+    block: &[u8],
+    input: &mut usize,
+    nibble: usize,
+) -> Result<usize, ProgramError> {
+    let mut len = nibble;
+    if nibble == EXTENDED_LENGTH {
+        loop {
+            let byte = *block.get(*input).ok_or(DlpError::InvalidCompressedState)?;
+            *input += 1;
+            len = len
+                .checked_add(byte as usize)
+                .ok_or(DlpError::InvalidCompressedState)?;
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(all(test, not(feature = "sdk")))]
+mod tests {
+    use super::*;
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let compressed = compress_lz4(data);
+        let (len, block) = split_decompressed_len(&compressed).unwrap();
+        assert_eq!(len, data.len());
+        let mut out = vec![0; len];
+        decompress_lz4_into(block, &mut out).unwrap();
+        assert_eq!(out, data);
+        compressed
+    }
+
+    #[test]
+    fn test_lz4_round_trip() {
+        round_trip(&[]);
+        round_trip(&[7]);
+        round_trip(b"not enough bytes to match");
+
+        // Repetitive states, e.g. zeroed accounts, are compressed
+        let zeroes = vec![0; 10_240];
+        assert!(round_trip(&zeroes).len() < 100);
+        let pattern: Vec<u8> = (0..5_000).map(|i| (i % 7) as u8).collect();
+        assert!(round_trip(&pattern).len() < 100);
+
+        // Pseudo random bytes are not, but still round trip, with long literals
+        let mut seed = 1u32;
+        let random: Vec<u8> = (0..3_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as u8
+            })
+            .collect();
+        round_trip(&random);
+        let mixed = [random.as_slice(), &zeroes, &random[..100], &pattern].concat();
+        round_trip(&mixed);
+    }
+
+    #[test]
+    fn test_lz4_rejects_malformed_block() {
+        let data = vec![3; 100];
+        let compressed = compress_lz4(&data);
+        let (len, block) = split_decompressed_len(&compressed).unwrap();
+
+        // The block must fill the decompressed length exactly
+        assert!(decompress_lz4_into(block, &mut vec![0; len - 1]).is_err());
+        assert!(decompress_lz4_into(block, &mut vec![0; len + 1]).is_err());
+        // Truncated block
+        assert!(decompress_lz4_into(&block[..block.len() - 1], &mut vec![0; len]).is_err());
+        assert!(decompress_lz4_into(&[], &mut []).is_err());
+        // Literals past the end of the block
+        assert!(decompress_lz4_into(&[0x20, 1], &mut [0; 2]).is_err());
+        // Match before the start of the output, or with a zero offset
+        assert!(decompress_lz4_into(&[0x10, 1, 2, 0, 0x00], &mut [0; 5]).is_err());
+        assert!(decompress_lz4_into(&[0x10, 1, 0, 0, 0x00], &mut [0; 5]).is_err());
+        // Missing decompressed length
+        assert!(split_decompressed_len(&[1, 2]).is_err());
+    }
+}
@@ -7,12 +7,76 @@ pub const RENT_FEES_PERCENTAGE: u8 = 10;
 /// The fees extracted from the validator earnings (extracted in percentage from the validator fees claims).
 pub const PROTOCOL_FEES_PERCENTAGE: u8 = 10;
 
+/// The number of slots after a commit from which anyone can finalize it with a crank.
+pub const CRANK_FINALIZE_DELAY_SLOTS: u64 = 1_500;
+
+/// The number of slots after the first stage of a staged undelegation from which anyone can
+/// run its second stage, if the validator which ran the first stage did not.
+pub const UNDELEGATE_STAGE2_TIMEOUT_SLOTS: u64 = 1_500;
+
+/// The bounty paid from the validator fees vault to the cranker of a pending commit.
+pub const CRANK_FINALIZE_BOUNTY_LAMPORTS: u64 = 10_000;
+
+/// The number of slots after an undelegation request from which commits must allow the
+/// undelegation, unless set in the protocol config.
+pub const DEFAULT_UNDELEGATION_GRACE_SLOTS: u64 = 9_000;
+
+/// The number of slots after the admin scheduled a force undelegation from which it can be
+/// executed, about a day, leaving time to notice the loud schedule event.
+pub const FORCE_UNDELEGATE_TIMELOCK_SLOTS: u64 = 216_000;
+
+/// The number of commit frequencies of a delegation its validator can go without committing
+/// before anyone can force the undelegation, see [crate::processor::process_force_undelegate].
+pub const COMMIT_TIMEOUT_FREQUENCY_MULTIPLIER: u64 = 10;
+
+/// The target duration of a slot, converting the commit frequency of a delegation to slots.
+pub const MS_PER_SLOT: u64 = 400;
+
+/// The number of slots after the admin proposed a protocol fees vault migration from which
+/// it can be executed, about a day.
+pub const PROTOCOL_VAULT_MIGRATION_TIMELOCK_SLOTS: u64 = 216_000;
+
+/// The flag of the nonces reserved to the commits pushed by the owner program of a delegated
+/// account, out of the range of the validator commits, see
+/// [crate::processor::process_commit_from_owner].
+pub const OWNER_COMMIT_NONCE_FLAG: u64 = 1 << 63;
+
+/// The maximum lamports a program config can let its owner program add to the validator in
+/// the external undelegate CPI, see [crate::state::ProgramConfig::undelegate_lamports_tolerance].
+pub const MAX_UNDELEGATE_LAMPORTS_TOLERANCE: u64 = 10_000_000;
+
 /// The discriminator for the external undelegate instruction.
 pub const EXTERNAL_UNDELEGATE_DISCRIMINATOR: [u8; 8] = [196, 28, 41, 206, 48, 37, 51, 167];
 
+/// The discriminator for the external validate delegation instruction, invoked at
+/// delegation when the program config of the owner program validates the delegations, see
+/// [crate::state::ProgramConfig::validate_delegations].
+pub const EXTERNAL_VALIDATE_DELEGATION_DISCRIMINATOR: [u8; 8] = [108, 29, 74, 9, 225, 236, 60, 97];
+
+/// The version byte following the external undelegate discriminator when the owner program
+/// opted into the v2 payload, see [crate::args::ExternalUndelegateArgsV2].
+pub const EXTERNAL_UNDELEGATE_PAYLOAD_V2: u8 = 2;
+
+/// The maximum data length of an account, see `MAX_PERMITTED_DATA_LENGTH` of the runtime.
+/// Bounds the changed length of a diff and the committed data copied on finalize.
+pub const MAX_ACCOUNT_DATA_LEN: usize = 10 * 1024 * 1024;
+
+/// The maximum length of the data of a delegated account whose hash is recorded at
+/// delegation, see [crate::state::DelegationMetadata::delegated_data_hash].
+/// Bounds the compute units hashing adds to the delegation of large accounts.
+pub const MAX_HASHED_DELEGATED_DATA_LEN: usize = 64 * 1024;
+
 /// The program ID of the delegation program.
 pub const DELEGATION_PROGRAM_ID: Pubkey = crate::id();
 
+/// The feature gates PDA, see [crate::pda::feature_gates_pda].
+/// Hardcoded so the entrypoint can check gated instructions without deriving it, it is
+/// switched with the program id.
+#[cfg(not(feature = "id-devtest"))]
+pub const FEATURE_GATES_PDA: Pubkey = pubkey!("Ha6KfEbUduu6NwHojViYnp5XEokt5PEuMRdwadvBv9SG");
+#[cfg(feature = "id-devtest")]
+pub const FEATURE_GATES_PDA: Pubkey = pubkey!("CJUw1teSkcFUBYEwypazeuR9EDNn5H2mwHGYRzdgLgNR");
+
 /// Default validator identity (used when none is provided during delegation).
 #[cfg(not(feature = "unit_test_config"))]
 pub const DEFAULT_VALIDATOR_IDENTITY: Pubkey =
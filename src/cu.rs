@@ -1,41 +1,46 @@
+use crate::log::log;
 use pinocchio::syscalls::sol_remaining_compute_units;
-use pinocchio_log::log;
 
-pub struct BenchmarkComputeUnit {
+use crate::discriminator::DlpDiscriminator;
+
+fn remaining_cu() -> u64 {
+    unsafe { sol_remaining_compute_units() }
+}
+
+/// Logs the compute units consumed by an instruction of the delegation program when dropped,
+/// as a single line parsed by [crate::cu_metrics::InstructionCost::try_from_log]:
+/// `CU name=<discriminator> consumed=<n> data_len=<m> accounts=<k>`
+pub struct InstructionComputeUnits {
     name: &'static str,
+    data_len: usize,
+    accounts: usize,
     remaining_at_start: u64,
 }
 
-impl BenchmarkComputeUnit {
-    pub fn start(name: &'static str) -> BenchmarkComputeUnit {
-        log!("BENCHMARK BEGIN: [{}]", name);
+impl InstructionComputeUnits {
+    pub fn start(data: &[u8], accounts: usize) -> InstructionComputeUnits {
+        let name = data
+            .first()
+            .and_then(|discriminator| DlpDiscriminator::try_from(*discriminator).ok())
+            .map_or("Unknown", |discriminator| discriminator.name());
         Self {
             name,
-            remaining_at_start: Self::remaining_cu(),
+            data_len: data.len(),
+            accounts,
+            remaining_at_start: remaining_cu(),
         }
     }
-
-    fn remaining_cu() -> u64 {
-        unsafe { sol_remaining_compute_units() }
-    }
 }
 
-impl Drop for BenchmarkComputeUnit {
+impl Drop for InstructionComputeUnits {
     fn drop(&mut self) {
-        let consumed = self.remaining_at_start - Self::remaining_cu();
+        let consumed = self.remaining_at_start - remaining_cu();
         log!(
-            "BENCHMARK END: [{}] consumed {} of {} compute units.",
+            "CU name={} consumed={} data_len={} accounts={}",
             self.name,
             consumed,
-            self.remaining_at_start
+            self.data_len,
+            self.accounts
         )
     }
 }
-
-#[macro_export]
-macro_rules! compute {
-    ($msg:expr=> $($tt:tt)*) => {
-        let _log = $crate::cu::BenchmarkComputeUnit::start($msg);
-        $($tt)*
-    };
-}
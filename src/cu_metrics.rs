@@ -0,0 +1,165 @@
+//! Parsing of the compute units logged per instruction under the `log-cost` feature.
+//!
+//! The entrypoint logs a single line per instruction of the delegation program:
+//! `CU name=<discriminator> consumed=<n> data_len=<m> accounts=<k>`. The lines are collected
+//! from the logs of the transactions and aggregated per discriminator, e.g. into a CSV for
+//! performance tracking dashboards.
+
+use alloc::collections::BTreeMap;
+use core::fmt::Write;
+
+/// The prefix of the compute units line of an instruction
+const CU_LOG_PREFIX: &str = "CU ";
+
+/// The prefix of the log lines of a program in the transaction logs
+const PROGRAM_LOG_PREFIX: &str = "Program log: ";
+
+/// The compute units consumed by an instruction of the delegation program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstructionCost {
+    /// The name of the discriminator of the instruction
+    pub name: String,
+    /// The compute units consumed by the instruction
+    pub consumed: u64,
+    /// The length of the instruction data
+    pub data_len: u64,
+    /// The number of accounts of the instruction
+    pub accounts: u64,
+}
+
+impl InstructionCost {
+    /// Parse the compute units line of an instruction, with or without the `Program log: `
+    /// prefix of the transaction logs. Returns None if the line is not a compute units line.
+    pub fn try_from_log(line: &str) -> Option<Self> {
+        let line = line.strip_prefix(PROGRAM_LOG_PREFIX).unwrap_or(line);
+        let mut fields = line.strip_prefix(CU_LOG_PREFIX)?.split(' ');
+        let mut field = |key: &str| {
+            fields
+                .next()?
+                .strip_prefix(key)?
+                .strip_prefix('=')
+                .map(str::to_string)
+        };
+        let name = field("name")?;
+        let consumed = field("consumed")?.parse().ok()?;
+        let data_len = field("data_len")?.parse().ok()?;
+        let accounts = field("accounts")?.parse().ok()?;
+        Some(Self {
+            name,
+            consumed,
+            data_len,
+            accounts,
+        })
+    }
+}
+
+/// The compute units consumed by the instructions of a discriminator
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstructionCostSummary {
+    /// The name of the discriminator
+    pub name: String,
+    /// The number of instructions
+    pub count: u64,
+    /// The compute units consumed by all the instructions
+    pub total_consumed: u64,
+    /// The lowest compute units consumed by an instruction
+    pub min_consumed: u64,
+    /// The highest compute units consumed by an instruction
+    pub max_consumed: u64,
+    /// The length of the data of all the instructions
+    pub total_data_len: u64,
+    /// The number of accounts of all the instructions
+    pub total_accounts: u64,
+}
+
+impl InstructionCostSummary {
+    /// The header of the CSV written by [InstructionCostSummary::to_csv]
+    pub const CSV_HEADER: &'static str =
+        "name,count,total_consumed,avg_consumed,min_consumed,max_consumed,avg_data_len,avg_accounts";
+
+    /// Aggregate the compute units lines found in transaction logs per discriminator,
+    /// sorted by name. The other lines are ignored.
+    pub fn aggregate<'a>(logs: impl IntoIterator<Item = &'a str>) -> Vec<Self> {
+        let mut summaries: BTreeMap<String, Self> = BTreeMap::new();
+        for cost in logs.into_iter().filter_map(InstructionCost::try_from_log) {
+            let summary = summaries.entry(cost.name.clone()).or_insert_with(|| Self {
+                name: cost.name,
+                min_consumed: u64::MAX,
+                ..Default::default()
+            });
+            summary.count += 1;
+            summary.total_consumed = summary.total_consumed.saturating_add(cost.consumed);
+            summary.min_consumed = summary.min_consumed.min(cost.consumed);
+            summary.max_consumed = summary.max_consumed.max(cost.consumed);
+            summary.total_data_len = summary.total_data_len.saturating_add(cost.data_len);
+            summary.total_accounts = summary.total_accounts.saturating_add(cost.accounts);
+        }
+        summaries.into_values().collect()
+    }
+
+    /// Write the summaries as a CSV, header included, the averages being rounded down
+    pub fn to_csv(summaries: &[Self]) -> String {
+        let mut csv = String::from(Self::CSV_HEADER);
+        csv.push('\n');
+        for summary in summaries {
+            let count = summary.count.max(1);
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                summary.name,
+                summary.count,
+                summary.total_consumed,
+                summary.total_consumed / count,
+                summary.min_consumed,
+                summary.max_consumed,
+                summary.total_data_len / count,
+                summary.total_accounts / count,
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_from_log() {
+        assert_eq!(
+            InstructionCost::try_from_log(
+                "Program log: CU name=CommitState consumed=4200 data_len=74 accounts=9"
+            ),
+            Some(InstructionCost {
+                name: "CommitState".to_string(),
+                consumed: 4200,
+                data_len: 74,
+                accounts: 9,
+            })
+        );
+        assert!(InstructionCost::try_from_log("CU name=Finalize consumed=10").is_none());
+        assert!(InstructionCost::try_from_log("Program log: BENCHMARK BEGIN: [x]").is_none());
+    }
+
+    #[test]
+    fn test_aggregate_to_csv() {
+        let logs = [
+            "Program DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh invoke [1]",
+            "Program log: CU name=Finalize consumed=3000 data_len=8 accounts=8",
+            "Program log: CU name=CommitState consumed=4000 data_len=70 accounts=9",
+            "Program log: CU name=CommitState consumed=5001 data_len=80 accounts=9",
+        ];
+        let summaries = InstructionCostSummary::aggregate(logs);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].name, "CommitState");
+        assert_eq!(summaries[0].min_consumed, 4000);
+        assert_eq!(summaries[0].max_consumed, 5001);
+        assert_eq!(
+            InstructionCostSummary::to_csv(&summaries),
+            format!(
+                "{}\nCommitState,2,9001,4500,4000,5001,75,9\nFinalize,1,3000,3000,3000,3000,8,8\n",
+                InstructionCostSummary::CSV_HEADER
+            )
+        );
+    }
+}
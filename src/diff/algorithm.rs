@@ -1,4 +1,5 @@
-use std::cmp::{min, Ordering};
+use alloc::vec::Vec;
+use core::cmp::{min, Ordering};
 
 use pinocchio::program_error::ProgramError;
 use rkyv::util::AlignedVec;
@@ -124,26 +125,36 @@ pub fn compute_diff(original: &[u8], changed: &[u8]) -> AlignedVec {
     let mut diffs: Vec<(usize, &[u8])> = Vec::new();
     let min_len = min(original.len(), changed.len());
     let mut diff_size = 0;
-    let mut i = 0;
-    while i < min_len {
-        if original[i] != changed[i] {
+    let mut start = None;
+    for (i, (original_byte, changed_byte)) in original.iter().zip(changed).enumerate() {
+        match (original_byte != changed_byte, start) {
             // start of diff
-            let start = i;
-            while i < min_len && original[i] != changed[i] {
-                i += 1;
+            (true, None) => start = Some(i),
+            // end of diff
+            (false, Some(diff_start)) => {
+                diffs.push((diff_start, changed.get(diff_start..i).unwrap_or_default()));
+                diff_size += i - diff_start;
+                start = None;
             }
-            diffs.push((start, &changed[start..i]));
-            diff_size += i - start;
-        } else {
-            i += 1;
+            _ => {}
         }
     }
+    if let Some(diff_start) = start {
+        diffs.push((
+            diff_start,
+            changed.get(diff_start..min_len).unwrap_or_default(),
+        ));
+        diff_size += min_len - diff_start;
+    }
 
     // 2. handle expansion/shrinkage
     match changed.len().cmp(&original.len()) {
         Ordering::Greater => {
             // extra bytes at the end
-            diffs.push((original.len(), &changed[original.len()..]));
+            diffs.push((
+                original.len(),
+                changed.get(original.len()..).unwrap_or_default(),
+            ));
             diff_size += changed.len() - original.len();
         }
         Ordering::Less => {
@@ -211,66 +222,71 @@ pub fn apply_diff_in_place(original: &mut [u8], diffset: &DiffSet<'_>) -> Result
 /// This function creates a copy of original, possibly extending or shrinking it,
 /// and then applies the diff to it, before returning it.
 pub fn apply_diff_copy(original: &[u8], diffset: &DiffSet<'_>) -> Result<Vec<u8>, ProgramError> {
-    Ok(match detect_size_change(original, diffset) {
-        Some(SizeChanged::Expanded(new_size)) => {
-            let mut applied = Vec::with_capacity(new_size);
-            applied.extend_from_slice(original);
-            applied.resize(new_size, 0);
-            apply_diff_impl(applied.as_mut(), diffset)?;
-            applied
-        }
-        Some(SizeChanged::Shrunk(new_size)) => {
-            let mut applied = Vec::from(&original[0..new_size]);
-            apply_diff_impl(applied.as_mut(), diffset)?;
-            applied
-        }
-        None => {
-            let mut applied = Vec::from(original);
-            apply_diff_impl(applied.as_mut(), diffset)?;
-            applied
-        }
-    })
+    let mut applied = vec![0; diffset.changed_len()];
+    merge_diff_copy(&mut applied, original, diffset)?;
+    Ok(applied)
 }
 
 /// This function constructs destination by merging original with diff such that destination
-/// becomes the changed version of the original.
+/// becomes the changed version of the original, without copying the original first. The
+/// original may be longer or shorter than the changed data: the bytes past its end are
+/// zeroed, as when the account is expanded.
 ///
 /// Precondition:
-///     - destination.len() == original.len()
+///     - destination.len() == diffset.changed_len()
 pub fn merge_diff_copy(
     destination: &mut [u8],
     original: &[u8],
     diffset: &DiffSet<'_>,
 ) -> Result<(), ProgramError> {
-    if destination.len() != original.len() {
+    if destination.len() != diffset.changed_len() {
         return Err(DlpError::MergeDiffError.into());
     }
     let mut write_index = 0;
     for item in diffset.iter() {
         let (diff_segment, OffsetInData { start, end }) = item?;
         if write_index < start {
-            // copy the unchanged bytes
-            destination[write_index..start].copy_from_slice(&original[write_index..start]);
+            let unchanged_destination = destination
+                .get_mut(write_index..start)
+                .ok_or(DlpError::MergeDiffError)?;
+            copy_unchanged(unchanged_destination, original, write_index);
         }
-        destination[start..end].copy_from_slice(diff_segment);
+        destination
+            .get_mut(start..end)
+            .ok_or(DlpError::MergeDiffError)?
+            .copy_from_slice(diff_segment);
         write_index = end;
     }
-    if write_index < original.len() {
-        destination[write_index..].copy_from_slice(&original[write_index..]);
+    if let Some(unchanged_destination) = destination.get_mut(write_index..) {
+        copy_unchanged(unchanged_destination, original, write_index);
     }
     Ok(())
 }
 
+/// Copies the unchanged bytes of the original at the offset into destination, zeroing the
+/// bytes past the end of the original
+fn copy_unchanged(destination: &mut [u8], original: &[u8], offset: usize) {
+    let original = original.get(offset..).unwrap_or_default();
+    let copied_len = destination.len().min(original.len());
+    let (copied, zeroed) = destination.split_at_mut(copied_len);
+    copied.copy_from_slice(original.get(..copied_len).unwrap_or_default());
+    zeroed.fill(0);
+}
+
 // private function that does the actual work.
 fn apply_diff_impl(original: &mut [u8], diffset: &DiffSet<'_>) -> Result<(), ProgramError> {
     for item in diffset.iter() {
         let (diff_segment, offset_range) = item?;
-        original[offset_range].copy_from_slice(diff_segment);
+        original
+            .get_mut(offset_range)
+            .ok_or(DlpError::DiffSegmentOutOfRange)?
+            .copy_from_slice(diff_segment);
     }
     Ok(())
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_range_loop)]
 mod tests {
 
     use rand::{
@@ -279,8 +295,261 @@ mod tests {
         Rng, RngCore, SeedableRng,
     };
 
+    use pinocchio::program_error::ProgramError;
+    use rkyv::util::AlignedVec;
+    use serde::Deserialize;
+
+    use crate::consts::MAX_ACCOUNT_DATA_LEN;
+    use crate::error::DlpError;
     use crate::{apply_diff_copy, apply_diff_in_place, compute_diff, merge_diff_copy, DiffSet};
 
+    /// The directory of the golden diffs: every `<name>.bin` holds the serialized diff
+    /// described by `<name>.json`, see [DiffFixture]
+    const DIFF_FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/diff");
+
+    /// The description of a golden diff, the bytes being hex encoded
+    #[derive(Deserialize)]
+    struct DiffFixture {
+        description: String,
+        original: String,
+        changed: String,
+        changed_len: usize,
+        segments: Vec<DiffFixtureSegment>,
+    }
+
+    #[derive(Deserialize)]
+    struct DiffFixtureSegment {
+        offset_in_diff: u32,
+        offset_in_data: u32,
+        data: String,
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        assert!(hex.len() % 2 == 0, "odd length hex: {hex}");
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Loads the golden diffs as (name, description, serialized diff), sorted by name
+    fn load_diff_fixtures() -> Vec<(String, DiffFixture, Vec<u8>)> {
+        let mut fixtures = std::fs::read_dir(DIFF_FIXTURES_DIR)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .map(|path| {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                let fixture: DiffFixture =
+                    serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
+                        .unwrap_or_else(|err| panic!("invalid diff fixture {name}: {err}"));
+                let diff = std::fs::read(path.with_extension("bin"))
+                    .unwrap_or_else(|err| panic!("missing serialized diff {name}: {err}"));
+                (name, fixture, diff)
+            })
+            .collect::<Vec<_>>();
+        fixtures.sort_by(|a, b| a.0.cmp(&b.0));
+        fixtures
+    }
+
+    /// Serialize a diff from (offset_in_diff, offset_in_data) pairs, valid or not
+    fn raw_diff(changed_len: u32, offset_pairs: &[(u32, u32)], concat_diff: &[u8]) -> AlignedVec {
+        let mut diff = AlignedVec::new();
+        diff.extend_from_slice(&changed_len.to_le_bytes());
+        diff.extend_from_slice(&(offset_pairs.len() as u32).to_le_bytes());
+        for (offset_in_diff, offset_in_data) in offset_pairs {
+            diff.extend_from_slice(&offset_in_diff.to_le_bytes());
+            diff.extend_from_slice(&offset_in_data.to_le_bytes());
+        }
+        diff.extend_from_slice(concat_diff);
+        diff
+    }
+
+    fn try_new_error(diff: &AlignedVec) -> Option<ProgramError> {
+        DiffSet::try_new(diff).err()
+    }
+
+    #[test]
+    fn test_invalid_segments_are_rejected_upfront() {
+        let concat_diff = [1, 2, 3, 4];
+
+        // Adjacent segments are valid
+        assert!(try_new_error(&raw_diff(10, &[(0, 0), (2, 2)], &concat_diff)).is_none());
+
+        // Out of the range of the changed data
+        assert_eq!(
+            try_new_error(&raw_diff(10, &[(0, 8)], &concat_diff)),
+            Some(DlpError::DiffSegmentOutOfRange.into())
+        );
+        // Out of the range of the diff
+        assert_eq!(
+            try_new_error(&raw_diff(10, &[(0, 0), (5, 4)], &concat_diff)),
+            Some(DlpError::DiffSegmentOutOfRange.into())
+        );
+        // Misordered in the diff, the first segment is empty
+        assert_eq!(
+            try_new_error(&raw_diff(10, &[(2, 0), (1, 5)], &concat_diff)),
+            Some(DlpError::DiffSegmentMisordered.into())
+        );
+        // Misordered in the changed data
+        assert_eq!(
+            try_new_error(&raw_diff(10, &[(0, 5), (2, 0)], &concat_diff)),
+            Some(DlpError::DiffSegmentMisordered.into())
+        );
+        // Overlapping in the changed data
+        assert_eq!(
+            try_new_error(&raw_diff(10, &[(0, 0), (2, 1)], &concat_diff)),
+            Some(DlpError::DiffSegmentOverlap.into())
+        );
+    }
+
+    #[test]
+    fn test_max_account_size_boundary() {
+        // Segments at the first byte, in the middle and at the last byte of the largest
+        // account, the last one ending exactly at the maximum data length
+        let original = vec![0u8; MAX_ACCOUNT_DATA_LEN];
+        let mut changed = original.clone();
+        changed[0] = 1;
+        changed[MAX_ACCOUNT_DATA_LEN / 2] = 2;
+        changed[MAX_ACCOUNT_DATA_LEN - 1] = 3;
+
+        let diff = compute_diff(&original, &changed);
+        let diffset = DiffSet::try_new(&diff).unwrap();
+        assert_eq!(diffset.changed_len(), MAX_ACCOUNT_DATA_LEN);
+        assert_eq!(diffset.segments_count(), 3);
+        let (_, last_range) = diffset.diff_segment_at(2).unwrap().unwrap();
+        assert_eq!(last_range, MAX_ACCOUNT_DATA_LEN - 1..MAX_ACCOUNT_DATA_LEN);
+
+        let mut applied = original.clone();
+        apply_diff_in_place(&mut applied, &diffset).unwrap();
+        assert!(applied == changed);
+        assert!(apply_diff_copy(&original, &diffset).unwrap() == changed);
+
+        // Expanded from an empty account to the maximum data length
+        let diff = compute_diff(&[], &changed);
+        let diffset = DiffSet::try_new(&diff).unwrap();
+        assert!(apply_diff_copy(&[], &diffset).unwrap() == changed);
+
+        // A changed length past the maximum data length is rejected
+        let max_len = MAX_ACCOUNT_DATA_LEN as u32;
+        assert_eq!(
+            try_new_error(&raw_diff(max_len + 1, &[(0, 0)], &[1])),
+            Some(DlpError::AccountDataTooLarge.into())
+        );
+        // A segment ending past the maximum data length is rejected
+        assert_eq!(
+            try_new_error(&raw_diff(max_len, &[(0, max_len - 1)], &[1, 2])),
+            Some(DlpError::DiffSegmentOutOfRange.into())
+        );
+        // Offsets at the u32 boundary do not overflow
+        assert_eq!(
+            try_new_error(&raw_diff(max_len, &[(0, u32::MAX)], &[1])),
+            Some(DlpError::DiffSegmentOutOfRange.into())
+        );
+        assert_eq!(
+            try_new_error(&raw_diff(u32::MAX, &[(0, 0)], &[1])),
+            Some(DlpError::AccountDataTooLarge.into())
+        );
+    }
+
+    #[test]
+    fn test_malformed_diffs_do_not_panic() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..10_000 {
+            // Random headers, mostly small so that some of them pass the validation
+            let changed_len = if rng.gen_bool(0.1) {
+                rng.gen()
+            } else {
+                rng.gen_range(0..64)
+            };
+            let offset_pairs: Vec<(u32, u32)> = (0..rng.gen_range(0..6))
+                .map(|_| {
+                    if rng.gen_bool(0.1) {
+                        (rng.gen(), rng.gen())
+                    } else {
+                        (rng.gen_range(0..32), rng.gen_range(0..64))
+                    }
+                })
+                .collect();
+            let concat_diff: Vec<u8> = (0..rng.gen_range(0..32)).map(|_| rng.gen()).collect();
+            let mut diff = raw_diff(changed_len, &offset_pairs, &concat_diff);
+            if rng.gen_bool(0.2) {
+                // Truncated, the segments count lying about the offset pairs
+                let truncated = diff[..rng.gen_range(0..=diff.len())].to_vec();
+                diff = AlignedVec::new();
+                diff.extend_from_slice(&truncated);
+            }
+
+            let Ok(diffset) = DiffSet::try_new(&diff) else {
+                continue;
+            };
+            let _ = diffset.iter().collect::<Vec<_>>();
+            let _ = diffset.diff_segment_at(rng.gen_range(0..8));
+
+            // Apply it to data of any length, not necessarily the one it was computed from
+            let mut original = vec![0u8; rng.gen_range(0..64)];
+            let _ = apply_diff_copy(&original, &diffset);
+            let mut destination = original.clone();
+            let _ = merge_diff_copy(&mut destination, &original, &diffset);
+            let _ = apply_diff_in_place(&mut original, &diffset);
+        }
+
+        // The borsh encoded diff of the instructions
+        assert!(DiffSet::try_new_from_borsh_vec(&[1, 0]).is_err());
+    }
+
+    #[test]
+    fn test_golden_diffs() {
+        let fixtures = load_diff_fixtures();
+        assert!(!fixtures.is_empty());
+
+        for (name, fixture, golden_diff) in fixtures {
+            let original = from_hex(&fixture.original);
+            let changed = from_hex(&fixture.changed);
+            assert_eq!(changed.len(), fixture.changed_len, "{name}");
+
+            // The diff is serialized byte-for-byte as the golden one
+            let diff = compute_diff(&original, &changed);
+            assert_eq!(
+                diff.as_slice(),
+                golden_diff.as_slice(),
+                "{name}: {}",
+                fixture.description
+            );
+
+            // The golden diff is parsed into the described segments
+            let mut aligned_diff = AlignedVec::new();
+            aligned_diff.extend_from_slice(&golden_diff);
+            let diffset = DiffSet::try_new(&aligned_diff).unwrap();
+            assert_eq!(diffset.raw_diff(), golden_diff.as_slice(), "{name}");
+            assert_eq!(diffset.changed_len(), fixture.changed_len, "{name}");
+            assert_eq!(diffset.segments_count(), fixture.segments.len(), "{name}");
+            for (index, segment) in fixture.segments.iter().enumerate() {
+                let pair = diffset.offset_pairs()[index];
+                assert_eq!(pair.offset_in_diff, segment.offset_in_diff, "{name}");
+                assert_eq!(pair.offset_in_data, segment.offset_in_data, "{name}");
+
+                let data = from_hex(&segment.data);
+                let (diff_segment, range) = diffset.diff_segment_at(index).unwrap().unwrap();
+                assert_eq!(diff_segment, data.as_slice(), "{name}");
+                let offset_in_data = segment.offset_in_data as usize;
+                assert_eq!(range, offset_in_data..offset_in_data + data.len(), "{name}");
+            }
+
+            // Applying the golden diff restores the changed data
+            assert_eq!(
+                apply_diff_copy(&original, &diffset).unwrap(),
+                changed,
+                "{name}"
+            );
+
+            // Merging it into a destination of the changed length too, whatever it held
+            let mut destination = vec![255; fixture.changed_len];
+            merge_diff_copy(&mut destination, &original, &diffset).unwrap();
+            assert_eq!(destination, changed, "{name}");
+        }
+    }
+
     #[test]
     fn test_no_change() {
         let original = [0; 100];
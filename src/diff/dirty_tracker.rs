@@ -0,0 +1,203 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ops::Range;
+
+///
+/// Records the byte ranges of an account that are known to be modified and serializes
+/// them directly into the diff wire format (see [crate::compute_diff] for the format).
+///
+/// This is meant for validators that already know which parts of an account they have
+/// mutated, so they do not need to keep a copy of the original data around and scan the
+/// whole account on every commit, which becomes expensive for multi-MB accounts.
+///
+/// Ranges may be recorded in any order and may overlap or touch each other; they are
+/// sorted and merged when the diff is emitted. Since a recorded range is only a hint that
+/// the bytes *might* have changed, the emitted diff may contain bytes that are equal to
+/// the original ones. Applying such a diff yields exactly the same result as applying the
+/// diff computed by [crate::compute_diff].
+///
+#[derive(Debug, Clone, Default)]
+pub struct DirtyTracker {
+    data_len: usize,
+    ranges: Vec<Range<usize>>,
+}
+
+impl DirtyTracker {
+    /// Creates a tracker for an account whose current data length is `data_len`.
+    pub fn new(data_len: usize) -> Self {
+        Self {
+            data_len,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Returns the current length of the tracked account data.
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    /// Returns true if no range has been marked dirty since the last reset.
+    pub fn is_clean(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Marks the half-open range [start, end) as dirty.
+    ///
+    /// Empty ranges are ignored and ranges are clamped to the current data length.
+    pub fn mark_dirty(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.data_len);
+        if range.start < end {
+            self.ranges.push(range.start..end);
+        }
+    }
+
+    /// Records that the account was reallocated to `new_len`.
+    ///
+    /// - If the account expanded, the newly added tail is marked dirty.
+    /// - If the account shrunk, the ranges past the new end are dropped/truncated.
+    pub fn resize(&mut self, new_len: usize) {
+        let old_len = self.data_len;
+        self.data_len = new_len;
+        match new_len.cmp(&old_len) {
+            Ordering::Greater => self.ranges.push(old_len..new_len),
+            Ordering::Less => self.ranges.retain_mut(|range| {
+                range.end = range.end.min(new_len);
+                range.start < range.end
+            }),
+            Ordering::Equal => {}
+        }
+    }
+
+    /// Forgets all the dirty ranges, typically after a successful commit.
+    pub fn reset(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Returns the dirty ranges sorted by offset, with overlapping and adjacent ranges merged.
+    pub fn dirty_ranges(&self) -> Vec<Range<usize>> {
+        let mut sorted = self.ranges.clone();
+        sorted.sort_unstable_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => {
+                    last.end = last.end.max(range.end);
+                }
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+
+    ///
+    /// Serializes the dirty ranges of `changed` into the diff wire format.
+    ///
+    /// `changed` is the current content of the account and its length must be equal to
+    /// the tracked data length, otherwise None is returned.
+    ///
+    /// Note that [crate::DiffSet::try_new] requires a 4-byte aligned buffer. The returned
+    /// bytes are meant to be sent in the instruction data (e.g as `CommitDiffArgs::diff`),
+    /// so copy them into an aligned buffer first if they have to be parsed locally.
+    ///
+    pub fn compute_diff(&self, changed: &[u8]) -> Option<Vec<u8>> {
+        if changed.len() != self.data_len {
+            return None;
+        }
+
+        let ranges = self.dirty_ranges();
+        let diff_size: usize = ranges.iter().map(|range| range.len()).sum();
+
+        // | Length (u32) | # Offset Pairs (u32) | Offset Pairs (u32, u32) ... | Concatenated Diff |
+        let mut output = Vec::with_capacity(4 + 4 + 8 * ranges.len() + diff_size);
+
+        output.extend_from_slice(&(changed.len() as u32).to_le_bytes());
+        output.extend_from_slice(&(ranges.len() as u32).to_le_bytes());
+
+        let mut offset_in_diff = 0u32;
+        for range in &ranges {
+            output.extend_from_slice(&offset_in_diff.to_le_bytes());
+            output.extend_from_slice(&(range.start as u32).to_le_bytes());
+            offset_in_diff += range.len() as u32;
+        }
+
+        for range in ranges {
+            output.extend_from_slice(changed.get(range)?);
+        }
+
+        Some(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::util::AlignedVec;
+
+    use crate::{apply_diff_copy, compute_diff, DiffSet, DirtyTracker};
+
+    fn aligned(bytes: &[u8]) -> AlignedVec {
+        let mut vec = AlignedVec::with_capacity(bytes.len());
+        vec.extend_from_slice(bytes);
+        vec
+    }
+
+    #[test]
+    fn test_exact_ranges_match_compute_diff() {
+        let original = [0u8; 100];
+        let mut changed = original;
+        changed[11..=14].copy_from_slice(&0x01020304u32.to_le_bytes());
+        changed[71..=78].copy_from_slice(&0x0102030405060708u64.to_le_bytes());
+
+        let mut tracker = DirtyTracker::new(changed.len());
+        tracker.mark_dirty(71..79);
+        tracker.mark_dirty(11..15);
+
+        let diff = tracker.compute_diff(&changed).unwrap();
+        assert_eq!(
+            diff.as_slice(),
+            compute_diff(&original, &changed).as_slice()
+        );
+    }
+
+    #[test]
+    fn test_overlapping_and_adjacent_ranges_are_merged() {
+        let mut tracker = DirtyTracker::new(64);
+        tracker.mark_dirty(10..20);
+        tracker.mark_dirty(15..25);
+        tracker.mark_dirty(25..30);
+        tracker.mark_dirty(40..41);
+        tracker.mark_dirty(5..5);
+
+        assert_eq!(tracker.dirty_ranges(), vec![10..30, 40..41]);
+    }
+
+    #[test]
+    fn test_resize() {
+        let original = vec![1u8; 32];
+        let mut changed = original.clone();
+        changed.resize(48, 7);
+        changed[3] = 9;
+
+        let mut tracker = DirtyTracker::new(original.len());
+        tracker.mark_dirty(3..4);
+        tracker.resize(changed.len());
+
+        let diff = aligned(&tracker.compute_diff(&changed).unwrap());
+        let diffset = DiffSet::try_new(&diff).unwrap();
+        assert_eq!(apply_diff_copy(&original, &diffset).unwrap(), changed);
+
+        let shrunk = &changed[..20];
+        tracker.resize(shrunk.len());
+        assert_eq!(tracker.dirty_ranges(), vec![3..4]);
+
+        let diff = aligned(&tracker.compute_diff(shrunk).unwrap());
+        let diffset = DiffSet::try_new(&diff).unwrap();
+        assert_eq!(apply_diff_copy(&original, &diffset).unwrap(), shrunk);
+    }
+
+    #[test]
+    fn test_len_mismatch() {
+        let tracker = DirtyTracker::new(10);
+        assert!(tracker.compute_diff(&[0; 11]).is_none());
+    }
+}
@@ -1,5 +1,17 @@
+#[cfg(not(feature = "sdk"))]
 mod algorithm;
+mod dirty_tracker;
+mod reconcile;
+#[cfg(feature = "std")]
+mod streaming;
+#[cfg(not(feature = "sdk"))]
 mod types;
 
+#[cfg(not(feature = "sdk"))]
 pub use algorithm::*;
+pub use dirty_tracker::*;
+pub use reconcile::*;
+#[cfg(feature = "std")]
+pub use streaming::*;
+#[cfg(not(feature = "sdk"))]
 pub use types::*;
@@ -0,0 +1,295 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use solana_program::pubkey::Pubkey;
+
+use crate::pda::{delegation_record_pda_from_delegated_account, validator_info_pda_from_validator};
+use crate::state::{DelegationRecord, ValidatorInfo};
+
+use super::DirtyTracker;
+
+///
+/// The divergence between the data of a delegated account finalized on L1 and the data
+/// reported by its validator, see [reconcile].
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DivergenceReport {
+    /// The data length of the account on L1
+    pub l1_len: usize,
+    /// The data length reported by the validator
+    pub reported_len: usize,
+    /// The ranges of the reported data differing from the L1 data, sorted and merged. The
+    /// tail of the reported data past the L1 data is included if it is longer.
+    pub ranges: Vec<Range<usize>>,
+    /// The diff turning the L1 data into the reported data, in the diff wire format (see
+    /// [crate::compute_diff] for the format), as a validator would commit it
+    pub diff: Vec<u8>,
+}
+
+impl DivergenceReport {
+    /// Returns true if the reported data differs from the L1 data
+    pub fn is_diverged(&self) -> bool {
+        self.l1_len != self.reported_len || !self.ranges.is_empty()
+    }
+
+    /// Returns the number of reported bytes differing from the L1 data
+    pub fn diverged_bytes(&self) -> usize {
+        self.ranges.iter().map(|range| range.len()).sum()
+    }
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_diverged() {
+            return write!(f, "in sync ({} bytes)", self.l1_len);
+        }
+        write!(
+            f,
+            "{} bytes differ in {} ranges (L1 {} bytes, reported {} bytes)",
+            self.diverged_bytes(),
+            self.ranges.len(),
+            self.l1_len,
+            self.reported_len
+        )?;
+        for (index, range) in self.ranges.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { ", " };
+            write!(f, "{}{}..{}", separator, range.start, range.end)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Compares the data of a delegated account finalized on L1 with the data reported by its
+/// validator, e.g. read from the ephemeral rollup, and summarizes the differing ranges.
+///
+/// This is meant for the monitoring agents detecting a validator whose claimed state
+/// diverges from the L1 state beyond its pending commits.
+///
+pub fn reconcile(
+    delegated_account_data: &[u8],
+    validator_reported_data: &[u8],
+) -> DivergenceReport {
+    let mut tracker = DirtyTracker::new(delegated_account_data.len());
+    tracker.resize(validator_reported_data.len());
+
+    // Mark the runs of differing bytes of the common prefix
+    let mut run_start = None;
+    let mut common_len = 0;
+    for (offset, (l1, reported)) in delegated_account_data
+        .iter()
+        .zip(validator_reported_data)
+        .enumerate()
+    {
+        common_len = offset + 1;
+        match (l1 != reported, run_start) {
+            (true, None) => run_start = Some(offset),
+            (false, Some(start)) => {
+                tracker.mark_dirty(start..offset);
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = run_start {
+        tracker.mark_dirty(start..common_len);
+    }
+
+    DivergenceReport {
+        l1_len: delegated_account_data.len(),
+        reported_len: validator_reported_data.len(),
+        ranges: tracker.dirty_ranges(),
+        diff: tracker
+            .compute_diff(validator_reported_data)
+            .unwrap_or_default(),
+    }
+}
+
+/// The data of a delegated account on L1 and as reported by its validator, fetched by
+/// [fetch_reconcile_sides]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconcileSides {
+    /// The validator the account is delegated to
+    pub validator: Pubkey,
+    /// The endpoint of the ephemeral rollup of the validator, see [ValidatorInfo::endpoint]
+    pub endpoint: String,
+    /// The data of the account on L1
+    pub l1_data: Vec<u8>,
+    /// The data of the account reported by the ephemeral rollup
+    pub reported_data: Vec<u8>,
+}
+
+impl ReconcileSides {
+    /// See [reconcile]
+    pub fn reconcile(&self) -> DivergenceReport {
+        reconcile(&self.l1_data, &self.reported_data)
+    }
+}
+
+/// The failure of [fetch_reconcile_sides]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconcileError<E> {
+    /// The account fetcher failed
+    Fetch(E),
+    /// The account has no delegation record on L1
+    NotDelegated,
+    /// The validator of the account did not set its info, see [ValidatorInfo]
+    MissingValidatorInfo(Pubkey),
+    /// The account does not exist on the ephemeral rollup
+    MissingReportedAccount,
+    /// The delegation record or the validator info cannot be decoded
+    InvalidAccountData,
+}
+
+///
+/// Fetches the data of a delegated account on L1 and on the ephemeral rollup of its
+/// validator, to be compared with [reconcile].
+///
+/// The validator is read from the delegation record of the account and the endpoint of
+/// its ephemeral rollup from its [ValidatorInfo], both on L1. `fetch_account` returns the
+/// data of an account from an RPC endpoint, or None if the account does not exist, which
+/// leaves the transport to the caller.
+///
+pub fn fetch_reconcile_sides<E>(
+    l1_rpc_url: &str,
+    delegated_account: &Pubkey,
+    mut fetch_account: impl FnMut(&str, &Pubkey) -> Result<Option<Vec<u8>>, E>,
+) -> Result<ReconcileSides, ReconcileError<E>> {
+    let mut fetch = |rpc_url: &str, pubkey: &Pubkey| {
+        fetch_account(rpc_url, pubkey).map_err(ReconcileError::Fetch)
+    };
+
+    let delegation_record_data = fetch(
+        l1_rpc_url,
+        &delegation_record_pda_from_delegated_account(delegated_account),
+    )?
+    .ok_or(ReconcileError::NotDelegated)?;
+    let validator = DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)
+        .map_err(|_| ReconcileError::InvalidAccountData)?
+        .authority;
+
+    let validator_info_data = fetch(l1_rpc_url, &validator_info_pda_from_validator(&validator))?
+        .ok_or(ReconcileError::MissingValidatorInfo(validator))?;
+    let endpoint = ValidatorInfo::try_from_bytes_with_discriminator(&validator_info_data)
+        .map_err(|_| ReconcileError::InvalidAccountData)?
+        .endpoint;
+
+    // The account is closed on L1 only if undelegated, reported as empty
+    let l1_data = fetch(l1_rpc_url, delegated_account)?.unwrap_or_default();
+    let reported_data =
+        fetch(&endpoint, delegated_account)?.ok_or(ReconcileError::MissingReportedAccount)?;
+    Ok(ReconcileSides {
+        validator,
+        endpoint,
+        l1_data,
+        reported_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_reconcile() {
+        let l1 = [0u8; 16];
+
+        let report = reconcile(&l1, &l1);
+        assert!(!report.is_diverged());
+        assert_eq!(report.to_string(), "in sync (16 bytes)");
+
+        let mut reported = l1.to_vec();
+        reported[2] = 1;
+        reported[3] = 1;
+        reported[15] = 1;
+        reported.extend_from_slice(&[1, 2]);
+        let report = reconcile(&l1, &reported);
+        assert!(report.is_diverged());
+        assert_eq!(report.ranges, vec![2..4, 15..18]);
+        assert_eq!(report.diverged_bytes(), 5);
+        assert_eq!(
+            report.to_string(),
+            "5 bytes differ in 2 ranges (L1 16 bytes, reported 18 bytes): 2..4, 15..18"
+        );
+
+        // The diff applied to the L1 data yields the reported data
+        let mut diff = rkyv::util::AlignedVec::new();
+        diff.extend_from_slice(&report.diff);
+        let diffset = crate::DiffSet::try_new(&diff).unwrap();
+        assert_eq!(crate::apply_diff_copy(&l1, &diffset).unwrap(), reported);
+
+        // A shrunk account diverges even if its prefix matches
+        let report = reconcile(&l1, &l1[..8]);
+        assert!(report.is_diverged());
+        assert!(report.ranges.is_empty());
+    }
+
+    #[test]
+    fn test_fetch_reconcile_sides() {
+        const L1: &str = "http://l1";
+        const ER: &str = "http://er";
+        let delegated_account = Pubkey::new_unique();
+        let validator = Pubkey::new_unique();
+
+        let delegation_record = DelegationRecord {
+            authority: validator,
+            owner: Pubkey::new_unique(),
+            delegation_slot: 0,
+            lamports: 0,
+            commit_frequency_ms: 0,
+            version: DelegationRecord::VERSION,
+            padding: [0; 7],
+        };
+        let mut delegation_record_data = vec![0; DelegationRecord::size_with_discriminator()];
+        delegation_record
+            .to_bytes_with_discriminator(&mut delegation_record_data)
+            .unwrap();
+        let validator_info = ValidatorInfo {
+            endpoint: ER.to_string(),
+            ..Default::default()
+        };
+        let mut validator_info_data = vec![0; validator_info.size_with_discriminator()];
+        validator_info
+            .to_bytes_with_discriminator(&mut validator_info_data.as_mut_slice())
+            .unwrap();
+
+        let mut accounts = HashMap::from([
+            (
+                (
+                    L1.to_string(),
+                    delegation_record_pda_from_delegated_account(&delegated_account),
+                ),
+                delegation_record_data,
+            ),
+            ((L1.to_string(), delegated_account), vec![1, 2, 3]),
+            ((ER.to_string(), delegated_account), vec![1, 2, 4]),
+        ]);
+        let fetch = |accounts: &HashMap<(String, Pubkey), Vec<u8>>| {
+            fetch_reconcile_sides::<()>(L1, &delegated_account, |rpc_url, pubkey| {
+                Ok(accounts.get(&(rpc_url.to_string(), *pubkey)).cloned())
+            })
+        };
+
+        // The validator must publish its endpoint
+        assert_eq!(
+            fetch(&accounts),
+            Err(ReconcileError::MissingValidatorInfo(validator))
+        );
+
+        accounts.insert(
+            (
+                L1.to_string(),
+                validator_info_pda_from_validator(&validator),
+            ),
+            validator_info_data,
+        );
+        let sides = fetch(&accounts).unwrap();
+        assert_eq!(sides.validator, validator);
+        assert_eq!(sides.endpoint, ER);
+        assert_eq!(sides.reconcile().ranges, vec![2..3]);
+    }
+}
@@ -0,0 +1,258 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The length of the chunks read from each source by [compute_diff_streaming]
+pub const STREAMING_DIFF_CHUNK_LEN: usize = 4096;
+
+///
+/// Computes the diff between `original` and `changed` and writes it to `writer` in the
+/// diff wire format (see [crate::compute_diff] for the format), returning the number of
+/// bytes written.
+///
+/// Unlike [crate::compute_diff], neither the sources nor the diff are held in memory: the
+/// working memory is bounded by two chunks of [STREAMING_DIFF_CHUNK_LEN] bytes, whatever
+/// the length of the account and the number of changed segments. This is meant for the
+/// off-chain use of the diff module in low-RAM environments, e.g diffing multi-MB
+/// accounts stored in files.
+///
+/// Since the header precedes the concatenated diff, the sources are scanned three times:
+/// once to count the segments and their bytes, once to write the offset pairs and once to
+/// write the changed bytes. They are rewound before every scan, hence the `Seek` bound;
+/// slices can be wrapped in a [std::io::Cursor]. The writes are small, so wrap the writer
+/// in a [std::io::BufWriter] if it is unbuffered.
+///
+/// The output is byte for byte the one of [crate::compute_diff].
+///
+pub fn compute_diff_streaming<O, C, W>(
+    original: &mut O,
+    changed: &mut C,
+    writer: &mut W,
+) -> io::Result<usize>
+where
+    O: Read + Seek,
+    C: Read + Seek,
+    W: Write,
+{
+    compute_diff_streaming_with_chunk_len(original, changed, writer, STREAMING_DIFF_CHUNK_LEN)
+}
+
+fn compute_diff_streaming_with_chunk_len<O, C, W>(
+    original: &mut O,
+    changed: &mut C,
+    writer: &mut W,
+    chunk_len: usize,
+) -> io::Result<usize>
+where
+    O: Read + Seek,
+    C: Read + Seek,
+    W: Write,
+{
+    let original_len = original.seek(SeekFrom::End(0))?;
+    let changed_len = changed.seek(SeekFrom::End(0))?;
+    let changed_len_u32 = to_u32(changed_len)?;
+    let mut scanner = SegmentScanner {
+        original,
+        changed,
+        original_len,
+        changed_len,
+        original_chunk: vec![0; chunk_len],
+        changed_chunk: vec![0; chunk_len],
+    };
+
+    // 1. count the segments and their bytes
+    let mut num_segments = 0u32;
+    let mut diff_size = 0u64;
+    scanner.scan(|_, bytes, is_new_segment| {
+        if is_new_segment {
+            num_segments = num_segments.checked_add(1).ok_or_else(too_large)?;
+        }
+        diff_size += bytes.len() as u64;
+        Ok(())
+    })?;
+    to_u32(diff_size)?;
+
+    // 2. write the header: size of changed data, number of offset pairs and offset pairs
+    writer.write_all(&changed_len_u32.to_le_bytes())?;
+    writer.write_all(&num_segments.to_le_bytes())?;
+    let mut offset_in_diff = 0u32;
+    scanner.scan(|offset_in_account, bytes, is_new_segment| {
+        if is_new_segment {
+            writer.write_all(&offset_in_diff.to_le_bytes())?;
+            writer.write_all(&to_u32(offset_in_account)?.to_le_bytes())?;
+        }
+        // The size of the diff was checked to fit in a u32
+        offset_in_diff = offset_in_diff.wrapping_add(bytes.len() as u32);
+        Ok(())
+    })?;
+
+    // 3. write the concatenated diff bytes
+    scanner.scan(|_, bytes, _| writer.write_all(bytes))?;
+
+    Ok(8 + 8 * num_segments as usize + diff_size as usize)
+}
+
+/// Scans two sources chunk by chunk for the segments of changed bytes
+struct SegmentScanner<'a, O, C> {
+    original: &'a mut O,
+    changed: &'a mut C,
+    original_len: u64,
+    changed_len: u64,
+    original_chunk: Vec<u8>,
+    changed_chunk: Vec<u8>,
+}
+
+impl<O: Read + Seek, C: Read + Seek> SegmentScanner<'_, O, C> {
+    /// Rewinds the sources and calls `on_diff(offset_in_account, bytes, is_new_segment)`
+    /// for every run of changed bytes, a segment spanning several chunks being reported as
+    /// several runs. The bytes appended when the account expanded form their own segment,
+    /// as in [crate::compute_diff].
+    fn scan(
+        &mut self,
+        mut on_diff: impl FnMut(u64, &[u8], bool) -> io::Result<()>,
+    ) -> io::Result<()> {
+        self.original.seek(SeekFrom::Start(0))?;
+        self.changed.seek(SeekFrom::Start(0))?;
+
+        let common_len = self.original_len.min(self.changed_len);
+        let mut position = 0u64;
+        // Whether the previous chunk ended in the middle of a segment
+        let mut in_segment = false;
+        while position < common_len {
+            let len = chunk_len(common_len - position, self.changed_chunk.len());
+            let (Some(original), Some(changed)) = (
+                self.original_chunk.get_mut(..len),
+                self.changed_chunk.get_mut(..len),
+            ) else {
+                return Err(io::ErrorKind::InvalidInput.into());
+            };
+            self.original.read_exact(original)?;
+            self.changed.read_exact(changed)?;
+
+            let mut run_start = None;
+            for (i, (original_byte, changed_byte)) in
+                original.iter().zip(changed.iter()).enumerate()
+            {
+                match (original_byte != changed_byte, run_start) {
+                    (true, None) => run_start = Some(i),
+                    (false, Some(start)) => {
+                        let bytes = changed.get(start..i).unwrap_or_default();
+                        on_diff(position + start as u64, bytes, start > 0 || !in_segment)?;
+                        run_start = None;
+                        in_segment = false;
+                    }
+                    _ => {}
+                }
+            }
+            match run_start {
+                Some(start) => {
+                    let bytes = changed.get(start..).unwrap_or_default();
+                    on_diff(position + start as u64, bytes, start > 0 || !in_segment)?;
+                    in_segment = true;
+                }
+                None => in_segment = false,
+            }
+            position += len as u64;
+        }
+
+        // The bytes appended to the account
+        let mut is_new_segment = true;
+        while position < self.changed_len {
+            let len = chunk_len(self.changed_len - position, self.changed_chunk.len());
+            let changed = self
+                .changed_chunk
+                .get_mut(..len)
+                .ok_or(io::ErrorKind::InvalidInput)?;
+            self.changed.read_exact(changed)?;
+            on_diff(position, changed, is_new_segment)?;
+            is_new_segment = false;
+            position += len as u64;
+        }
+        Ok(())
+    }
+}
+
+fn chunk_len(remaining: u64, max_len: usize) -> usize {
+    usize::try_from(remaining).map_or(max_len, |remaining| remaining.min(max_len))
+}
+
+fn to_u32(value: u64) -> io::Result<u32> {
+    u32::try_from(value).map_err(|_| too_large())
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "diff does not fit in the wire format",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::compute_diff_streaming_with_chunk_len;
+    use crate::{compute_diff, compute_diff_streaming};
+
+    fn streamed_diff(original: &[u8], changed: &[u8], chunk_len: usize) -> Vec<u8> {
+        let mut diff = Vec::new();
+        let written = compute_diff_streaming_with_chunk_len(
+            &mut Cursor::new(original),
+            &mut Cursor::new(changed),
+            &mut diff,
+            chunk_len,
+        )
+        .unwrap();
+        assert_eq!(written, diff.len());
+        diff
+    }
+
+    #[test]
+    fn test_matches_compute_diff() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let original: Vec<u8> = (0..1000).map(|_| rng.gen()).collect();
+
+        for changed_len in [0, 1, 500, 999, 1000, 1001, 1500] {
+            let mut changed = original.clone();
+            changed.resize(changed_len, 0xAB);
+            // Sparse changes, some of which straddle the chunk boundaries
+            for _ in 0..30 {
+                if changed_len == 0 {
+                    break;
+                }
+                let start = rng.gen_range(0..changed_len);
+                let end = (start + rng.gen_range(1..20)).min(changed_len);
+                for byte in &mut changed[start..end] {
+                    *byte = byte.wrapping_add(1);
+                }
+            }
+
+            let expected = compute_diff(&original, &changed);
+            for chunk_len in [1, 7, 64, 4096] {
+                assert_eq!(
+                    streamed_diff(&original, &changed, chunk_len),
+                    expected.as_slice(),
+                    "changed len {changed_len}, chunk len {chunk_len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_segment_ending_at_the_end_of_the_original() {
+        // The changed tail of the original and the appended bytes are two segments
+        let original = [0u8; 16];
+        let mut changed = [0u8; 24];
+        changed[12..].fill(1);
+
+        let mut diff = Vec::new();
+        compute_diff_streaming(
+            &mut Cursor::new(&original),
+            &mut Cursor::new(&changed),
+            &mut diff,
+        )
+        .unwrap();
+        assert_eq!(diff, compute_diff(&original, &changed).as_slice());
+        assert_eq!(u32::from_le_bytes(diff[4..8].try_into().unwrap()), 2);
+    }
+}
@@ -1,12 +1,15 @@
 use core::{
+    cmp::Ordering,
     mem::{align_of, size_of},
+    ops::Range,
     slice,
 };
-use std::{cmp::Ordering, ops::Range};
 
+use crate::log::log;
 use pinocchio::program_error::ProgramError;
 use static_assertions::const_assert;
 
+use crate::consts::MAX_ACCOUNT_DATA_LEN;
 use crate::error::DlpError;
 
 #[derive(Debug, Clone, Copy)]
@@ -61,6 +64,17 @@ impl<'a> DiffSet<'a> {
         let changed_len = unsafe { *(buf as *const u32) as usize };
         let segments_count = unsafe { *(buf.add(4) as *const u32) as usize };
 
+        // The changed data is the data of an account, the offsets in it are then
+        // bounded by the maximum data length of an account
+        if changed_len > MAX_ACCOUNT_DATA_LEN {
+            log!(
+                "diff changed length {} exceeds the maximum data length {}",
+                changed_len,
+                MAX_ACCOUNT_DATA_LEN
+            );
+            return Err(DlpError::AccountDataTooLarge.into());
+        }
+
         let mut this = Self {
             buf,
             buflen,
@@ -70,9 +84,10 @@ impl<'a> DiffSet<'a> {
             concat_diff: b"",
         };
 
-        let header_len = SIZE_OF_CHANGED_LEN
-            + SIZE_OF_NUM_OFFSET_PAIRS
-            + segments_count * SIZE_OF_SINGLE_OFFSET_PAIR;
+        let header_len = segments_count
+            .checked_mul(SIZE_OF_SINGLE_OFFSET_PAIR)
+            .and_then(|len| len.checked_add(SIZE_OF_CHANGED_LEN + SIZE_OF_NUM_OFFSET_PAIRS))
+            .ok_or(DlpError::InvalidDiff)?;
 
         match diff.len().cmp(&header_len) {
             Ordering::Equal => {
@@ -96,18 +111,85 @@ impl<'a> DiffSet<'a> {
                         as *const OffsetPair;
                     slice::from_raw_parts(raw_pairs, segments_count)
                 };
-                this.concat_diff = &diff[header_len..];
+                this.concat_diff = diff.get(header_len..).ok_or(DlpError::InvalidDiff)?;
             }
         }
 
+        this.validate_segments()?;
         Ok(this)
     }
 
+    /// Validates every segment upfront, so that applying the diff cannot fail mid-way:
+    /// - each segment is non-empty, in the diff and in the changed data
+    /// - segments are in ascending order, both in the diff and in the changed data
+    /// - segments do not overlap in the changed data
+    ///
+    /// The failing segment index is logged along with the reason.
+    fn validate_segments(&self) -> Result<(), ProgramError> {
+        let mut previous_offset_in_data = 0;
+        let mut previous_end_in_data = 0;
+        for index in 0..self.segments_count {
+            let (segment_begin, segment_end, offset_in_data) = self.segment_bounds(index)?;
+
+            if segment_begin >= segment_end {
+                log!("diff segment {} is empty or misordered in the diff", index);
+                return Err(DlpError::DiffSegmentMisordered.into());
+            }
+            if segment_end > self.concat_diff.len() {
+                log!("diff segment {} is out of the range of the diff", index);
+                return Err(DlpError::DiffSegmentOutOfRange.into());
+            }
+
+            // The u32 offsets widened to usize cannot overflow on 64-bit targets, the
+            // arithmetic is still checked so that it does not depend on the target
+            let end_in_data = offset_in_data
+                .checked_add(segment_end - segment_begin)
+                .ok_or(DlpError::DiffSegmentOutOfRange)?;
+            if end_in_data > self.changed_len {
+                log!(
+                    "diff segment {} is out of the range of the changed data",
+                    index
+                );
+                return Err(DlpError::DiffSegmentOutOfRange.into());
+            }
+            if index > 0 && offset_in_data < previous_end_in_data {
+                if offset_in_data < previous_offset_in_data {
+                    log!("diff segment {} is misordered in the changed data", index);
+                    return Err(DlpError::DiffSegmentMisordered.into());
+                }
+                log!("diff segment {} overlaps the previous segment", index);
+                return Err(DlpError::DiffSegmentOverlap.into());
+            }
+            previous_offset_in_data = offset_in_data;
+            previous_end_in_data = end_in_data;
+        }
+        Ok(())
+    }
+
+    /// Returns the half-open range of the segment at index in the concatenated diff,
+    /// and its offset in the changed data. The index must be less than segments_count.
+    fn segment_bounds(&self, index: usize) -> Result<(usize, usize, usize), ProgramError> {
+        let OffsetPair {
+            offset_in_diff,
+            offset_in_data,
+        } = *self.offset_pairs.get(index).ok_or(DlpError::InvalidDiff)?;
+
+        let segment_end = match self.offset_pairs.get(index.saturating_add(1)) {
+            Some(next) => next.offset_in_diff as usize,
+            None => self.concat_diff.len(),
+        };
+        Ok((
+            offset_in_diff as usize,
+            segment_end,
+            offset_in_data as usize,
+        ))
+    }
+
     pub fn try_new_from_borsh_vec(vec_buffer: &'a [u8]) -> Result<Self, ProgramError> {
-        if vec_buffer.len() < 4 {
+        let Some((_, diff)) = vec_buffer.split_first_chunk::<4>() else {
             return Err(ProgramError::InvalidInstructionData);
-        }
-        Self::try_new(&vec_buffer[4..])
+        };
+        Self::try_new(diff)
     }
 
     pub fn raw_diff(&self) -> &'a [u8] {
@@ -140,37 +222,21 @@ impl<'a> DiffSet<'a> {
         &self,
         index: usize,
     ) -> Result<Option<(&'a [u8], OffsetInData)>, ProgramError> {
-        let offsets = self.offset_pairs();
-        if index >= offsets.len() {
+        if index >= self.segments_count {
             return Ok(None);
         }
 
-        let OffsetPair {
-            offset_in_diff: segment_begin,
-            offset_in_data,
-        } = offsets[index];
-
-        let segment_end = if index + 1 < offsets.len() {
-            offsets[index + 1].offset_in_diff
-        } else {
-            self.concat_diff.len() as u32
-        };
-
-        // Note: segment is the half-open interval [segment_begin, segment_end)
-        if segment_end > self.concat_diff.len() as u32
-            || segment_begin >= segment_end
-            || offset_in_data >= self.changed_len() as u32
-        {
-            return Err(DlpError::InvalidDiff.into());
-        }
-
-        let segment = &self.concat_diff[segment_begin as usize..segment_end as usize];
-        let range =
-            offset_in_data as usize..(offset_in_data + segment_end - segment_begin) as usize;
-
-        if range.end > self.changed_len() {
-            return Err(DlpError::InvalidDiff.into());
-        }
+        // Note: the segments were validated by try_new, and segment is the half-open
+        // interval [segment_begin, segment_end)
+        let (segment_begin, segment_end, offset_in_data) = self.segment_bounds(index)?;
+        let segment = self
+            .concat_diff
+            .get(segment_begin..segment_end)
+            .ok_or(DlpError::DiffSegmentOutOfRange)?;
+        let end_in_data = offset_in_data
+            .checked_add(segment.len())
+            .ok_or(DlpError::DiffSegmentOutOfRange)?;
+        let range = offset_in_data..end_in_data;
 
         Ok(Some((segment, range)))
     }
@@ -181,7 +247,7 @@ impl<'a> DiffSet<'a> {
     ) -> impl Iterator<Item = Result<(&'a [u8], OffsetInData), ProgramError>> + '_ {
         (0..self.segments_count).map(|index| {
             self.diff_segment_at(index)
-                .map(|val| val.expect("impossible: index can never be greater than segments_count"))
+                .and_then(|val| val.ok_or(DlpError::InvalidDiff.into()))
         })
     }
 }
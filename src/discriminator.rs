@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use num_enum::TryFromPrimitive;
 use strum::IntoStaticStr;
 
@@ -39,6 +40,154 @@ pub enum DlpDiscriminator {
     CommitDiff = 16,
     /// See [crate::processor::process_commit_diff_from_buffer] for docs.
     CommitDiffFromBuffer = 17,
+    /// See [crate::processor::process_split_delegation] for docs.
+    SplitDelegation = 18,
+    /// See [crate::processor::process_top_up_program_ephemeral_balance] for docs.
+    TopUpProgramEphemeralBalance = 19,
+    /// See [crate::processor::process_delegate_program_ephemeral_balance] for docs.
+    DelegateProgramEphemeralBalance = 20,
+    /// See [crate::processor::process_close_program_ephemeral_balance] for docs.
+    CloseProgramEphemeralBalance = 21,
+    /// See [crate::processor::process_set_feature_gate] for docs.
+    SetFeatureGate = 22,
+    /// See [crate::processor::process_set_program_allowed_data_lens] for docs.
+    SetProgramAllowedDataLens = 23,
+    /// See [crate::processor::fast::process_crank_finalize] for docs.
+    CrankFinalize = 24,
+    /// See [crate::processor::fast::process_commit_finalize] for docs.
+    CommitFinalize = 25,
+    /// See [crate::processor::process_init_delegate_buffer] for docs.
+    InitDelegateBuffer = 26,
+    /// See [crate::processor::process_write_delegate_buffer_chunk] for docs.
+    WriteDelegateBufferChunk = 27,
+    /// See [crate::processor::process_set_validator_info] for docs.
+    SetValidatorInfo = 28,
+    /// See [crate::processor::process_approve_undelegate_and_close] for docs.
+    ApproveUndelegateAndClose = 29,
+    /// See [crate::processor::fast::process_undelegate_and_close] for docs.
+    UndelegateAndClose = 30,
+    /// See [crate::processor::process_commit_session_begin] for docs.
+    CommitSessionBegin = 31,
+    /// See [crate::processor::process_commit_session_end] for docs.
+    CommitSessionEnd = 32,
+    /// See [crate::processor::process_grow_commit_state] for docs.
+    GrowCommitState = 33,
+    /// See [crate::processor::process_set_protocol_config] for docs.
+    SetProtocolConfig = 34,
+    /// See [crate::processor::process_bootstrap_protocol] for docs.
+    BootstrapProtocol = 35,
+    /// See [crate::processor::process_init_read_lock] for docs.
+    InitReadLock = 36,
+    /// See [crate::processor::process_whitelist_validators_for_program_batch] for docs.
+    WhitelistValidatorsForProgramBatch = 37,
+    /// See [crate::processor::process_request_undelegation] for docs.
+    RequestUndelegation = 38,
+    /// See [crate::processor::fast::process_commit_diff_shadow] for docs.
+    CommitDiffShadow = 39,
+    /// See [crate::processor::process_set_version] for docs.
+    SetVersion = 40,
+    /// See [crate::processor::process_get_version] for docs.
+    GetVersion = 41,
+    /// See [crate::processor::process_grant_fee_exemption] for docs.
+    GrantFeeExemption = 42,
+    /// See [crate::processor::process_validate_delegation] for docs.
+    ValidateDelegation = 43,
+    /// See [crate::processor::process_set_program_undelegate_lamports_tolerance] for docs.
+    SetProgramUndelegateLamportsTolerance = 44,
+    /// See [crate::processor::process_set_commit_schedule] for docs.
+    SetCommitSchedule = 45,
+    /// See [crate::processor::process_close_commit_schedule] for docs.
+    CloseCommitSchedule = 46,
+    /// See [crate::processor::process_set_program_max_delegation_slots] for docs.
+    SetProgramMaxDelegationSlots = 48,
+    /// See [crate::processor::process_init_earnings_ledger_page] for docs.
+    InitEarningsLedgerPage = 49,
+    /// See [crate::processor::process_schedule_force_undelegate] for docs.
+    ScheduleForceUndelegate = 50,
+    /// See [crate::processor::process_execute_force_undelegate] for docs.
+    ExecuteForceUndelegate = 51,
+    /// See [crate::processor::process_set_program_validate_delegations] for docs.
+    SetProgramValidateDelegations = 52,
+    /// See [crate::processor::process_resync_protocol_stats] for docs.
+    ResyncProtocolStats = 53,
+    /// See [crate::processor::process_set_call_handler_permissions] for docs.
+    SetCallHandlerPermissions = 54,
+    /// See [crate::processor::process_top_up_ephemeral_balance_batch] for docs.
+    TopUpEphemeralBalanceBatch = 55,
+    /// See [crate::processor::process_register_commit_relayer] for docs.
+    RegisterCommitRelayer = 56,
+    /// See [crate::processor::process_set_validator_commit_quota] for docs.
+    SetValidatorCommitQuota = 57,
+    /// See [crate::processor::process_redelegate_ephemeral_balance] for docs.
+    RedelegateEphemeralBalance = 58,
+    /// See [crate::processor::process_is_validator_whitelisted] for docs.
+    IsValidatorWhitelisted = 59,
+    /// See [crate::processor::process_export_delegation_package] for docs.
+    ExportDelegationPackage = 60,
+    /// See [crate::processor::process_import_delegation_package] for docs.
+    ImportDelegationPackage = 61,
+    /// See [crate::processor::process_propose_protocol_vault_migration] for docs.
+    ProposeProtocolVaultMigration = 62,
+    /// See [crate::processor::process_execute_protocol_vault_migration] for docs.
+    ExecuteProtocolVaultMigration = 63,
+    /// See [crate::processor::process_get_delegation_summaries] for docs.
+    GetDelegationSummaries = 64,
+    /// See [crate::processor::process_get_escrow_summaries] for docs.
+    GetEscrowSummaries = 65,
+    /// See [crate::processor::process_get_pending_commit_summaries] for docs.
+    GetPendingCommitSummaries = 66,
+    /// See [crate::processor::process_whitelist_validator_shard_for_program] for docs.
+    WhitelistValidatorShardForProgram = 67,
+    /// See [crate::processor::process_migrate_program_config_whitelist] for docs.
+    MigrateProgramConfigWhitelist = 68,
+    /// See [crate::processor::fast::process_undelegate_stage1] for docs.
+    UndelegateStage1 = 69,
+    /// See [crate::processor::fast::process_undelegate_stage2] for docs.
+    UndelegateStage2 = 70,
+    /// See [crate::processor::process_set_program_undelegate_discriminator_override] for docs.
+    SetProgramUndelegateDiscriminatorOverride = 71,
+    /// See [crate::processor::process_validator_heartbeat] for docs.
+    ValidatorHeartbeat = 72,
+    /// See [crate::processor::process_migrate_delegation_record] for docs.
+    MigrateDelegationRecord = 73,
+    /// See [crate::processor::process_fund_escrow_from_delegated] for docs.
+    FundEscrowFromDelegated = 74,
+    /// See [crate::processor::process_close_program_config] for docs.
+    CloseProgramConfig = 75,
+    /// See [crate::processor::process_commit_from_owner] for docs.
+    CommitFromOwner = 76,
+    /// See [crate::processor::process_set_authority_grant] for docs.
+    SetAuthorityGrant = 77,
+    /// See [crate::processor::process_init_commit_buffer] for docs.
+    InitCommitBuffer = 78,
+    /// See [crate::processor::process_write_commit_buffer] for docs.
+    WriteCommitBuffer = 79,
+    /// See [crate::processor::process_close_commit_buffer] for docs.
+    CloseCommitBuffer = 80,
+    /// See [crate::processor::process_claim_parked_undelegation] for docs.
+    ClaimParkedUndelegation = 81,
+    /// See [crate::processor::process_init_session_report] for docs.
+    InitSessionReport = 82,
+    /// See [crate::processor::process_close_session_report] for docs.
+    CloseSessionReport = 83,
+    /// See [crate::processor::process_force_undelegate] for docs.
+    ForceUndelegate = 84,
+    /// See [crate::processor::process_delegate_stake_account] for docs.
+    DelegateStakeAccount = 85,
+    /// See [crate::processor::process_undelegate_stake_account] for docs.
+    UndelegateStakeAccount = 86,
+    /// See [crate::processor::process_add_delegation_authority] for docs.
+    AddDelegationAuthority = 87,
+    /// See [crate::processor::process_remove_delegation_authority] for docs.
+    RemoveDelegationAuthority = 88,
+    /// See [crate::processor::process_update_delegation_authority] for docs.
+    UpdateDelegationAuthority = 89,
+    /// See [crate::processor::fast::process_commit_state_root] for docs.
+    CommitStateRoot = 90,
+    /// See [crate::processor::process_commit_state_chunk] for docs.
+    CommitStateChunk = 91,
+    /// See [crate::processor::process_close_streamed_commit_state] for docs.
+    CloseStreamedCommitState = 92,
 }
 
 impl DlpDiscriminator {
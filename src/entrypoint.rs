@@ -17,6 +17,8 @@ pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
 
     let (program_id, count, data) =
         pinocchio::entrypoint::deserialize::<{ pinocchio::MAX_TX_ACCOUNTS }>(input, &mut accounts);
+    #[cfg(feature = "log-cost")]
+    let _compute_units = crate::cu::InstructionComputeUnits::start(data, count);
     match fast_process_instruction(
         program_id,
         core::slice::from_raw_parts(accounts.as_ptr() as _, count),
@@ -81,6 +81,128 @@ pub enum DlpError {
     UndelegateBufferAlreadyInitialized = 36,
     #[error("Undelegate buffer PDA immutable")]
     UndelegateBufferImmutable = 37,
+    #[error("Invalid byte ranges to split the delegated account")]
+    InvalidSplitRanges = 38,
+    #[error("Instruction is disabled by the feature gates")]
+    InstructionDisabled = 39,
+    #[error("Committed data length is not allowed by the program config")]
+    InvalidCommittedDataLen = 40,
+    #[error("Commit cannot be finalized by a crank before the finalize delay elapsed")]
+    CrankFinalizeTooEarly = 41,
+    #[error("Staged delegate buffer is incomplete or does not match the delegated account")]
+    InvalidStagedDelegateBuffer = 42,
+    #[error("Validator info is empty or exceeds the maximum lengths")]
+    InvalidValidatorInfo = 43,
+    #[error("Close destination was not approved by the owner program or does not match")]
+    InvalidCloseDestination = 44,
+    #[error("Commit session is unmatched or does not contain only ordered commits")]
+    InvalidCommitSession = 45,
+    #[error("Committed lamports cannot be settled at finalize")]
+    UnsettleableCommit = 46,
+    #[error("Invalid streamed commit state")]
+    InvalidStreamedCommitState = 47,
+    #[error("Diff segment is out of the range of the diff or of the changed data")]
+    DiffSegmentOutOfRange = 48,
+    #[error("Diff segment is empty or not in ascending order")]
+    DiffSegmentMisordered = 49,
+    #[error("Diff segment overlaps the previous segment")]
+    DiffSegmentOverlap = 50,
+    #[error("Too many validators in the whitelist batch")]
+    TooManyValidatorsInBatch = 51,
+    #[error("Undelegation was requested and its grace period has not elapsed")]
+    UndelegationGracePeriod = 52,
+    #[error("Undelegation was requested and is overdue, commits must allow the undelegation")]
+    UndelegationRequired = 53,
+    #[error("State produced by the diff does not match the expected state hash")]
+    CommitStateHashMismatch = 54,
+    #[error("Invalid range of supported discriminators")]
+    InvalidDiscriminatorRange = 55,
+    #[error("ER block hash must change between successive commits of an account")]
+    ErBlockHashNotChanged = 56,
+    #[error("Commit PDAs cannot refund the rent advanced by the validator")]
+    CommitRentNotRefundable = 57,
+    #[error("Undelegate lamports tolerance exceeds the maximum")]
+    UndelegateLamportsToleranceTooHigh = 58,
+    #[error("Invalid commit schedule")]
+    InvalidCommitSchedule = 59,
+    #[error("Commits of the current interval of the commit schedule are exhausted")]
+    CommitScheduleExceeded = 60,
+    #[error("Delegated account cannot be funded to stay rent exempt after finalize")]
+    DelegatedAccountNotRentExempt = 61,
+    #[error("Delegation outlived the maximum delegation slots of the program, commits must allow the undelegation")]
+    DelegationExpired = 62,
+    #[error("Invalid earnings ledger page")]
+    InvalidEarningsLedgerPage = 63,
+    #[error("Earnings ledger page is full")]
+    EarningsLedgerPageFull = 64,
+    #[error("Timelock of the force undelegation has not elapsed")]
+    ForceUndelegationTimelock = 65,
+    #[error("Call handler context is disabled by the escrow authority")]
+    CallHandlerContextDisabled = 66,
+    #[error("Too many escrows in the top-up batch")]
+    TooManyEscrowsInBatch = 67,
+    #[error("Too many commit relayers approved by the validator")]
+    TooManyCommitRelayers = 68,
+    #[error("Commit quota of the validator exceeded for the slot")]
+    CommitQuotaExceeded = 69,
+    #[error("Invalid delegation package")]
+    InvalidDelegationPackage = 70,
+    #[error("Committed lamports are below the rent exempt minimum of the committed data")]
+    CommittedLamportsNotRentExempt = 71,
+    #[error("No protocol fees vault migration is pending")]
+    NoPendingProtocolVaultMigration = 72,
+    #[error("Timelock of the protocol fees vault migration has not elapsed")]
+    ProtocolVaultMigrationTimelock = 73,
+    #[error("Protocol fees vault was retired by a migration")]
+    RetiredProtocolFeesVault = 74,
+    #[error("Base state hash does not match the data of the account at delegation")]
+    BaseStateHashMismatch = 75,
+    #[error("Escrow spend does not match the lamports moved by the call handlers")]
+    EscrowSpendMismatch = 76,
+    #[error("Undelegate progress does not match the staged undelegation")]
+    InvalidUndelegateProgress = 77,
+    #[error("Program config does not allow the undelegate discriminator override")]
+    UndelegateDiscriminatorOverrideNotAllowed = 78,
+    #[error("Committed data length exceeds the maximum account size of the delegation")]
+    CommittedDataTooLarge = 79,
+    #[error(
+        "Delegated account lacks the lamports above its rent exempt minimum to fund the escrow"
+    )]
+    NotEnoughExcessLamports = 80,
+    #[error("Closing the program config requires attesting it has no active delegations")]
+    ActiveDelegationsNotAttested = 81,
+    #[error("Owner commits can only be pushed by the owner program of a delegated PDA")]
+    InvalidOwnerCommit = 82,
+    #[error(
+        "Authority delegation chain does not grant the signer the authority of the delegation"
+    )]
+    InvalidAuthorityChain = 83,
+    #[error("Too many sub-keys granted the authority of the delegator")]
+    TooManyAuthorityGrants = 84,
+    #[error("Commit buffer is incomplete or the chunk does not fit in its data length")]
+    InvalidCommitBuffer = 85,
+    #[error("Data length exceeds the maximum data length of an account")]
+    AccountDataTooLarge = 86,
+    #[error("The validator committed within the commit timeout of the delegation")]
+    CommitTimeoutNotElapsed = 87,
+    #[error("Invalid state of the native stake account")]
+    InvalidStakeState = 88,
+    #[error("Too many validators added to the authorities of the delegation")]
+    TooManyDelegationAuthorities = 89,
+    #[error("Invalid rent co-payer or share of the rent of the delegation")]
+    InvalidRentCoPayer = 90,
+    #[error("Compressed state is malformed or does not match its decompressed length")]
+    InvalidCompressedState = 91,
+    #[error("Chunk does not match the Merkle root of the chunked commit state")]
+    InvalidChunkProof = 92,
+    #[error("Chunked commit state is not fully written, or its root or chunk size is invalid")]
+    InvalidChunkedCommitState = 93,
+    #[error("Split must be approved by the owner program signing for a PDA of its own")]
+    SplitNotApprovedByOwner = 94,
+    #[error("Call handler context does not match the instructions of the transaction")]
+    CallHandlerContextMismatch = 95,
+    #[error("Commit state does not have the layout recorded by its commit")]
+    InvalidCommitStateLayout = 96,
 }
 
 impl From<DlpError> for ProgramError {
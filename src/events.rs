@@ -0,0 +1,302 @@
+//! Events emitted by the delegation program.
+//!
+//! Events are logged with `sol_log_data`, so they appear as a `Program data:` log line
+//! holding a single base64 encoded buffer: the event discriminator followed by the borsh
+//! serialized event.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use solana_program::pubkey::Pubkey;
+
+use crate::state::DelegationPackage;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+pub enum EventDiscriminator {
+    Delegate = 0,
+    EscrowClosed = 1,
+    Commit = 2,
+    ForceUndelegateScheduled = 3,
+    ForceUndelegateExecuted = 4,
+    DelegationExported = 5,
+    ProtocolVaultMigrationProposed = 6,
+    ProtocolVaultMigrationExecuted = 7,
+    ProgramConfigClosed = 8,
+    DelegationAuthorityUpdated = 9,
+}
+
+/// Emitted when an account is delegated
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct DelegateEvent {
+    /// The delegated account
+    pub delegated_account: Pubkey,
+    /// The program owning the delegated account
+    pub owner: Pubkey,
+    /// The validator the account is delegated to
+    pub validator: Pubkey,
+    /// The validator info PDA, see [crate::pda::validator_info_pda_from_validator]
+    pub validator_info: Pubkey,
+}
+
+impl DelegateEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 4 * 32;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a delegate event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()? != EventDiscriminator::Delegate {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when a dust escrow is closed at undelegation, see
+/// [crate::state::ProtocolConfig::escrow_dust_threshold]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct EscrowClosedEvent {
+    /// The closed ephemeral balance escrow
+    pub escrow: Pubkey,
+    /// The rent payer refunded with the escrowed lamports
+    pub refunded: Pubkey,
+    /// The lamports refunded
+    pub lamports: u64,
+}
+
+impl EscrowClosedEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 2 * 32 + 8;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not an escrow closed event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()? != EventDiscriminator::EscrowClosed {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when a state providing the hash of the ER block which produced it is committed,
+/// see [crate::args::CommitStateArgs::er_block_hash]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct CommitEvent {
+    /// The delegated account whose state is committed
+    pub delegated_account: Pubkey,
+    /// The validator committing the state
+    pub validator: Pubkey,
+    /// The nonce of the commit
+    pub nonce: u64,
+    /// The hash of the ER block which produced the committed state
+    pub er_block_hash: [u8; 32],
+}
+
+impl CommitEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 2 * 32 + 8 + 32;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a commit event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()? != EventDiscriminator::Commit {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when the admin schedules the force undelegation of an account, see
+/// [crate::state::ForceUndelegation]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ForceUndelegateScheduledEvent {
+    /// The delegated account to force undelegate
+    pub delegated_account: Pubkey,
+    /// The validator the account is delegated to
+    pub validator: Pubkey,
+    /// The admin scheduling the force undelegation
+    pub admin: Pubkey,
+    /// The slot from which the force undelegation can be executed
+    pub executable_slot: u64,
+}
+
+impl ForceUndelegateScheduledEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 3 * 32 + 8;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a force undelegate scheduled event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()?
+            != EventDiscriminator::ForceUndelegateScheduled
+        {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when the admin executes the force undelegation of an account, marking it
+/// undelegatable
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ForceUndelegateExecutedEvent {
+    /// The delegated account marked undelegatable
+    pub delegated_account: Pubkey,
+    /// The validator the account is delegated to
+    pub validator: Pubkey,
+    /// The admin executing the force undelegation
+    pub admin: Pubkey,
+}
+
+impl ForceUndelegateExecutedEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 3 * 32;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a force undelegate executed event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()?
+            != EventDiscriminator::ForceUndelegateExecuted
+        {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when the admin exports a delegation, holding the package to import on another
+/// cluster, see [DelegationPackage]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct DelegationExportedEvent {
+    /// The exported delegation
+    pub package: DelegationPackage,
+}
+
+impl DelegationExportedEvent {
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a delegation exported event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()?
+            != EventDiscriminator::DelegationExported
+        {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when the admin proposes to migrate the protocol fees to a new vault, see
+/// [crate::processor::process_propose_protocol_vault_migration]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ProtocolVaultMigrationProposedEvent {
+    /// The admin proposing the migration
+    pub admin: Pubkey,
+    /// The protocol fees vault collecting the fees until the migration is executed
+    pub current_vault: Pubkey,
+    /// The protocol fees vault the migration moves the fees to
+    pub new_vault: Pubkey,
+    /// The slot from which the migration can be executed
+    pub executable_slot: u64,
+}
+
+impl ProtocolVaultMigrationProposedEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 3 * 32 + 8;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a protocol vault migration proposed event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()?
+            != EventDiscriminator::ProtocolVaultMigrationProposed
+        {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when the admin executes a protocol fees vault migration, the new vault collecting
+/// the fees from then on
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ProtocolVaultMigrationExecutedEvent {
+    /// The admin executing the migration
+    pub admin: Pubkey,
+    /// The retired protocol fees vault
+    pub old_vault: Pubkey,
+    /// The protocol fees vault collecting the fees
+    pub new_vault: Pubkey,
+    /// The lamports moved from the retired vault
+    pub lamports: u64,
+}
+
+impl ProtocolVaultMigrationExecutedEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 3 * 32 + 8;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a protocol vault migration executed event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()?
+            != EventDiscriminator::ProtocolVaultMigrationExecuted
+        {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when the program config of a program is closed
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ProgramConfigClosedEvent {
+    /// The authority closing the program config, refunded with its rent
+    pub authority: Pubkey,
+    /// The program the closed config belonged to
+    pub program: Pubkey,
+    /// The lamports refunded
+    pub lamports: u64,
+}
+
+impl ProgramConfigClosedEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 2 * 32 + 8;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a program config closed event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()?
+            != EventDiscriminator::ProgramConfigClosed
+        {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
+
+/// Emitted when the authority of a delegation is handed to another validator, see
+/// [crate::processor::process_update_delegation_authority]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct DelegationAuthorityUpdatedEvent {
+    /// The delegated account
+    pub delegated_account: Pubkey,
+    /// The validator handing over the delegation
+    pub old_authority: Pubkey,
+    /// The validator taking over the delegation
+    pub new_authority: Pubkey,
+}
+
+impl DelegationAuthorityUpdatedEvent {
+    pub const SIZE_WITH_DISCRIMINATOR: usize = 1 + 3 * 32;
+
+    /// Parse the event from the decoded data of a `Program data:` log line.
+    /// Returns None if the data is not a delegation authority updated event.
+    pub fn try_from_log_data(data: &[u8]) -> Option<Self> {
+        let (discriminator, event) = data.split_first()?;
+        if EventDiscriminator::try_from(*discriminator).ok()?
+            != EventDiscriminator::DelegationAuthorityUpdated
+        {
+            return None;
+        }
+        Self::try_from_slice(event).ok()
+    }
+}
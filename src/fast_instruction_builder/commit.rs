@@ -0,0 +1,574 @@
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::instructions::INSTRUCTIONS_ID;
+
+use super::{discriminator, encode, encode_borsh, DlpInstruction, FEATURE_GATES_ID};
+use crate::args::{
+    CallHandlerArgs, CommitDiffArgs, CommitDiffShadowArgs, CommitFromOwnerArgs, CommitStateArgs,
+    CommitStateChunkArgs, CommitStateFromBufferArgs, CommitStateRootArgs,
+    SetCallHandlerPermissionsArgs,
+};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes a commit state instruction, see [crate::instruction_builder::commit_state].
+/// The whitelist shard of the owner program trails the accounts, see
+/// [crate::instruction_builder::commit_state_with_whitelist_shard].
+#[allow(clippy::too_many_arguments)]
+pub fn commit_state<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    program_config: &'a Pubkey,
+    args: &CommitStateArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 9>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::CommitState, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::readonly(delegation_record),
+            // The delegation metadata is only written by commits allowing the undelegation
+            AccountMeta::new(delegation_metadata, args.allow_undelegation, false),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a commit state from buffer instruction, see
+/// [crate::instruction_builder::commit_state_from_buffer]
+#[allow(clippy::too_many_arguments)]
+pub fn commit_state_from_buffer<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_state_buffer: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    program_config: &'a Pubkey,
+    args: &CommitStateFromBufferArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 10>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::CommitStateFromBuffer, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::new(delegation_metadata, args.allow_undelegation, false),
+            AccountMeta::readonly(commit_state_buffer),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an init commit buffer instruction, see
+/// [crate::instruction_builder::init_commit_buffer]
+pub fn init_commit_buffer<'a>(
+    authority: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_buffer: &'a Pubkey,
+    data_len: u32,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::InitCommitBuffer, &data_len)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_buffer),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a write commit buffer instruction, see
+/// [crate::instruction_builder::write_commit_buffer]
+pub fn write_commit_buffer<'a>(
+    authority: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_buffer: &'a Pubkey,
+    offset: u32,
+    chunk: &[u8],
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::WriteCommitBuffer,
+        &(offset, chunk),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_buffer),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a close commit buffer instruction, see
+/// [crate::instruction_builder::close_commit_buffer]
+pub fn close_commit_buffer<'a>(
+    authority: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_buffer: &'a Pubkey,
+) -> DlpInstruction<'a, 3> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_buffer),
+        ],
+        discriminator(DlpDiscriminator::CloseCommitBuffer),
+    )
+}
+
+/// Encodes a commit diff instruction, see [crate::instruction_builder::commit_diff]
+#[allow(clippy::too_many_arguments)]
+pub fn commit_diff<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    program_config: &'a Pubkey,
+    args: &CommitDiffArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 10>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::CommitDiff, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::new(delegation_metadata, args.allow_undelegation, false),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a commit diff from buffer instruction, see
+/// [crate::instruction_builder::commit_diff_from_buffer]
+#[allow(clippy::too_many_arguments)]
+pub fn commit_diff_from_buffer<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_state_buffer: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    program_config: &'a Pubkey,
+    args: &CommitStateFromBufferArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 11>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::CommitDiffFromBuffer, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::new(delegation_metadata, args.allow_undelegation, false),
+            AccountMeta::readonly(commit_state_buffer),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a commit diff shadow instruction, see
+/// [crate::instruction_builder::commit_diff_shadow]
+#[allow(clippy::too_many_arguments)]
+pub fn commit_diff_shadow<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    program_config: &'a Pubkey,
+    args: &CommitDiffShadowArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 10>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::CommitDiffShadow, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::new(delegation_metadata, args.allow_undelegation, false),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a commit and finalize instruction, see
+/// [crate::instruction_builder::commit_finalize]
+#[allow(clippy::too_many_arguments)]
+pub fn commit_finalize<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    program_config: &'a Pubkey,
+    args: &CommitStateArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 9>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::CommitFinalize, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a grow commit state instruction, see
+/// [crate::instruction_builder::grow_commit_state].
+/// The validator fees vault trails the accounts when a commit relayer grows the commit
+/// state, see [crate::instruction_builder::grow_commit_state_from_relayer].
+pub fn grow_commit_state<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    chunk: &[u8],
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::GrowCommitState, &chunk)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a commit state root instruction, see
+/// [crate::instruction_builder::commit_state_root]
+#[allow(clippy::too_many_arguments)]
+pub fn commit_state_root<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    program_config: &'a Pubkey,
+    args: &CommitStateRootArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 9>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::CommitStateRoot, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::new(delegation_metadata, args.allow_undelegation, false),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a commit state chunk instruction, see
+/// [crate::instruction_builder::commit_state_chunk]
+pub fn commit_state_chunk<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    args: &CommitStateChunkArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::CommitStateChunk, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::readonly(commit_record),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a commit session begin instruction, see
+/// [crate::instruction_builder::commit_session_begin]
+pub fn commit_session_begin(validator: &Pubkey) -> DlpInstruction<'_, 2> {
+    DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(&INSTRUCTIONS_ID),
+        ],
+        discriminator(DlpDiscriminator::CommitSessionBegin),
+    )
+}
+
+/// Encodes a commit session end instruction, see
+/// [crate::instruction_builder::commit_session_end]
+pub fn commit_session_end(validator: &Pubkey) -> DlpInstruction<'_, 2> {
+    DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly(&INSTRUCTIONS_ID),
+        ],
+        discriminator(DlpDiscriminator::CommitSessionEnd),
+    )
+}
+
+/// Encodes a finalize instruction, see [crate::instruction_builder::finalize].
+/// The read lock or the instructions sysvar trail the accounts, see
+/// [crate::instruction_builder::finalize_with_read_lock] and
+/// [crate::instruction_builder::finalize_with_escrow_spend], the payer of a commit pushed by
+/// the owner program trailing them, see [crate::instruction_builder::finalize_owner_commit].
+pub fn finalize<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+) -> DlpInstruction<'a, 8> {
+    finalize_with_validator_fees_vault(
+        validator,
+        delegated_account,
+        commit_state,
+        commit_record,
+        delegation_record,
+        delegation_metadata,
+        AccountMeta::writable(validator_fees_vault),
+    )
+}
+
+/// Encodes a finalize instruction passing the validator fees vault read-only, see
+/// [crate::instruction_builder::finalize_settled]
+pub fn finalize_settled<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+) -> DlpInstruction<'a, 8> {
+    finalize_with_validator_fees_vault(
+        validator,
+        delegated_account,
+        commit_state,
+        commit_record,
+        delegation_record,
+        delegation_metadata,
+        AccountMeta::readonly(validator_fees_vault),
+    )
+}
+
+/// Encodes a finalize instruction, see [finalize] and [finalize_settled]
+fn finalize_with_validator_fees_vault<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: AccountMeta<'a>,
+) -> DlpInstruction<'a, 8> {
+    DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            validator_fees_vault,
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::Finalize),
+    )
+}
+
+/// Encodes a crank finalize instruction, see [crate::instruction_builder::crank_finalize]
+#[allow(clippy::too_many_arguments)]
+pub fn crank_finalize<'a>(
+    cranker: &'a Pubkey,
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+) -> DlpInstruction<'a, 10> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(cranker),
+            AccountMeta::writable(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        discriminator(DlpDiscriminator::CrankFinalize),
+    )
+}
+
+/// Encodes a commit from owner instruction, see [crate::instruction_builder::commit_from_owner]
+#[allow(clippy::too_many_arguments)]
+pub fn commit_from_owner<'a>(
+    payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    args: &CommitFromOwnerArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 7>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::CommitFromOwner, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly_signer(delegated_account),
+            AccountMeta::writable(commit_state),
+            AccountMeta::writable(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a call handler instruction with up to `N` accounts, the accounts of the handler
+/// trailing the accounts of the delegation program, see
+/// [crate::instruction_builder::call_handler]
+#[allow(clippy::too_many_arguments)]
+pub fn call_handler<'a, const N: usize>(
+    validator: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    destination_program: &'a Pubkey,
+    escrow_authority: &'a Pubkey,
+    escrow: &'a Pubkey,
+    call_handler_permissions: &'a Pubkey,
+    other_accounts: &[AccountMeta<'a>],
+    args: &CallHandlerArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, N>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::CallHandler, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(destination_program),
+            AccountMeta::writable(escrow_authority),
+            AccountMeta::writable(escrow),
+            AccountMeta::readonly(&INSTRUCTIONS_ID),
+            AccountMeta::readonly(call_handler_permissions),
+        ],
+        data,
+    )
+    .with_accounts(other_accounts)
+}
+
+/// Encodes a set call handler permissions instruction, see
+/// [crate::instruction_builder::set_call_handler_permissions]
+pub fn set_call_handler_permissions<'a>(
+    escrow_authority: &'a Pubkey,
+    escrow: &'a Pubkey,
+    call_handler_permissions: &'a Pubkey,
+    args: &SetCallHandlerPermissionsArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SetCallHandlerPermissions, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(escrow_authority),
+            AccountMeta::readonly(escrow),
+            AccountMeta::writable(call_handler_permissions),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
@@ -0,0 +1,551 @@
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::instructions::INSTRUCTIONS_ID;
+
+use super::{discriminator, encode, encode_borsh, DlpInstruction, FEATURE_GATES_ID};
+use crate::args::{
+    DelegateArgs, DelegateStakeAccountArgs, DelegationAuthorityArgs, GrantFeeExemptionArgs,
+    ImportDelegationPackageArgs, SetCommitScheduleArgs, SplitDelegationArgs,
+    UndelegateStakeAccountArgs, UpdateDelegationAuthorityArgs,
+};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes a delegate instruction, see [crate::instruction_builder::delegate].
+/// The staged delegate buffer, the program config of the owner program or the rent co-payer
+/// of the args trail the accounts, see [DlpInstruction::with_accounts].
+#[allow(clippy::too_many_arguments)]
+pub fn delegate<'a>(
+    payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    owner: &'a Pubkey,
+    delegate_buffer: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    args: &DelegateArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 7>, ProgramError> {
+    let data = encode(buffer, DlpDiscriminator::Delegate, |writer| {
+        args.write_instruction_data(writer)
+    })?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::writable_signer(delegated_account),
+            AccountMeta::readonly(owner),
+            AccountMeta::writable(delegate_buffer),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an init delegate buffer instruction, see
+/// [crate::instruction_builder::init_delegate_buffer]
+pub fn init_delegate_buffer<'a>(
+    payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    staged_buffer: &'a Pubkey,
+    data_len: u32,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::InitDelegateBuffer, &data_len)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly_signer(delegated_account),
+            AccountMeta::writable(staged_buffer),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a write delegate buffer chunk instruction, see
+/// [crate::instruction_builder::write_delegate_buffer_chunk]
+pub fn write_delegate_buffer_chunk<'a>(
+    authority: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    staged_buffer: &'a Pubkey,
+    offset: u32,
+    chunk: &[u8],
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::WriteDelegateBufferChunk,
+        &(offset, chunk),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(staged_buffer),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a split delegation instruction, see [crate::instruction_builder::split_delegation]
+#[allow(clippy::too_many_arguments)]
+pub fn split_delegation<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    new_delegated_account: &'a Pubkey,
+    new_delegation_record: &'a Pubkey,
+    new_delegation_metadata: &'a Pubkey,
+    owner_program: &'a Pubkey,
+    args: &SplitDelegationArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 12>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SplitDelegation, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::writable_signer(new_delegated_account),
+            AccountMeta::writable(new_delegation_record),
+            AccountMeta::writable(new_delegation_metadata),
+            AccountMeta::readonly(owner_program),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a validate delegation instruction, see
+/// [crate::instruction_builder::validate_delegation]
+pub fn validate_delegation<'a>(
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+) -> DlpInstruction<'a, 5> {
+    DlpInstruction::new(
+        [
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+        ],
+        discriminator(DlpDiscriminator::ValidateDelegation),
+    )
+}
+
+/// Encodes a request undelegation instruction, see
+/// [crate::instruction_builder::request_undelegation].
+/// The delegation record and the program config of the owner program trail the accounts
+/// to request the undelegation of an expired delegation, see
+/// [crate::instruction_builder::request_expired_undelegation].
+pub fn request_undelegation<'a>(
+    requester: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    protocol_config: &'a Pubkey,
+) -> DlpInstruction<'a, 6> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(requester),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::readonly(protocol_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::RequestUndelegation),
+    )
+}
+
+/// Encodes an approve undelegate and close instruction, see
+/// [crate::instruction_builder::approve_undelegate_and_close]
+pub fn approve_undelegate_and_close<'a>(
+    payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    close_destination: &Pubkey,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::ApproveUndelegateAndClose,
+        close_destination,
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly_signer(delegated_account),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a set commit schedule instruction, see
+/// [crate::instruction_builder::set_commit_schedule]
+pub fn set_commit_schedule<'a>(
+    rent_payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_schedule: &'a Pubkey,
+    escrow: &'a Pubkey,
+    args: &SetCommitScheduleArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SetCommitSchedule, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(rent_payer),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(commit_schedule),
+            AccountMeta::readonly(escrow),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a close commit schedule instruction, see
+/// [crate::instruction_builder::close_commit_schedule]
+pub fn close_commit_schedule<'a>(
+    escrow_payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_schedule: &'a Pubkey,
+) -> DlpInstruction<'a, 5> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(escrow_payer),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(commit_schedule),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::CloseCommitSchedule),
+    )
+}
+
+/// Encodes an init read lock instruction, see [crate::instruction_builder::init_read_lock]
+pub fn init_read_lock<'a>(
+    payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    read_lock: &'a Pubkey,
+) -> DlpInstruction<'a, 5> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::writable(read_lock),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::InitReadLock),
+    )
+}
+
+/// Encodes a grant fee exemption instruction, see
+/// [crate::instruction_builder::grant_fee_exemption]
+pub fn grant_fee_exemption<'a>(
+    admin: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    fee_exemption: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    args: &GrantFeeExemptionArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 5>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::GrantFeeExemption, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(fee_exemption),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a schedule force undelegate instruction, see
+/// [crate::instruction_builder::schedule_force_undelegate]
+pub fn schedule_force_undelegate<'a>(
+    admin: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    force_undelegation: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+) -> DlpInstruction<'a, 6> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::writable(force_undelegation),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::ScheduleForceUndelegate),
+    )
+}
+
+/// Encodes an execute force undelegate instruction, see
+/// [crate::instruction_builder::execute_force_undelegate]
+pub fn execute_force_undelegate<'a>(
+    admin: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    force_undelegation: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+) -> DlpInstruction<'a, 7> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::writable(force_undelegation),
+            AccountMeta::readonly(delegation_program_data),
+        ],
+        discriminator(DlpDiscriminator::ExecuteForceUndelegate),
+    )
+}
+
+/// Encodes a force undelegate instruction, see
+/// [crate::instruction_builder::force_undelegate]
+pub fn force_undelegate<'a>(
+    payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_record: &'a Pubkey,
+) -> DlpInstruction<'a, 6> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::ForceUndelegate),
+    )
+}
+
+/// Encodes a delegate stake account instruction, see
+/// [crate::instruction_builder::delegate_stake_account]
+#[allow(clippy::too_many_arguments)]
+pub fn delegate_stake_account<'a>(
+    authority: &'a Pubkey,
+    stake_account: &'a Pubkey,
+    stake_authority: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    stake_program: &'a Pubkey,
+    clock_sysvar: &'a Pubkey,
+    args: &DelegateStakeAccountArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 8>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::DelegateStakeAccount, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::writable(stake_account),
+            AccountMeta::readonly(stake_authority),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(stake_program),
+            AccountMeta::readonly(clock_sysvar),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an undelegate stake account instruction, see
+/// [crate::instruction_builder::undelegate_stake_account]
+#[allow(clippy::too_many_arguments)]
+pub fn undelegate_stake_account<'a>(
+    signer: &'a Pubkey,
+    stake_account: &'a Pubkey,
+    stake_authority: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    authority: &'a Pubkey,
+    stake_program: &'a Pubkey,
+    clock_sysvar: &'a Pubkey,
+    args: &UndelegateStakeAccountArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 8>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::UndelegateStakeAccount, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(signer),
+            AccountMeta::writable(stake_account),
+            AccountMeta::readonly(stake_authority),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(authority),
+            AccountMeta::readonly(stake_program),
+            AccountMeta::readonly(clock_sysvar),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an add delegation authority instruction, see
+/// [crate::instruction_builder::add_delegation_authority]
+pub fn add_delegation_authority<'a>(
+    authority: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_authorities: &'a Pubkey,
+    args: &DelegationAuthorityArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 5>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::AddDelegationAuthority, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::writable(delegation_authorities),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a remove delegation authority instruction, see
+/// [crate::instruction_builder::remove_delegation_authority]
+pub fn remove_delegation_authority<'a>(
+    authority: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_authorities: &'a Pubkey,
+    args: &DelegationAuthorityArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 5>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::RemoveDelegationAuthority, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::writable(delegation_authorities),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an update delegation authority instruction, see
+/// [crate::instruction_builder::update_delegation_authority]
+pub fn update_delegation_authority<'a>(
+    authority: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    args: &UpdateDelegationAuthorityArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 5>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::UpdateDelegationAuthority, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(authority),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+            AccountMeta::readonly(commit_record),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an export delegation package instruction, see
+/// [crate::instruction_builder::export_delegation_package]
+pub fn export_delegation_package<'a>(
+    admin: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+) -> DlpInstruction<'a, 5> {
+    DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(admin),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+        ],
+        discriminator(DlpDiscriminator::ExportDelegationPackage),
+    )
+}
+
+/// Encodes an import delegation package instruction, see
+/// [crate::instruction_builder::import_delegation_package].
+/// The Ed25519 program instruction verifying the signature of the package by the admin
+/// must precede it in the transaction, it is not encoded here.
+pub fn import_delegation_package<'a>(
+    admin: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    args: &ImportDelegationPackageArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 7>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::ImportDelegationPackage, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(&INSTRUCTIONS_ID),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a migrate delegation record instruction, see
+/// [crate::instruction_builder::migrate_delegation_record]
+pub fn migrate_delegation_record<'a>(
+    payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+) -> DlpInstruction<'a, 4> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::MigrateDelegationRecord),
+    )
+}
@@ -0,0 +1,340 @@
+use borsh::BorshSerialize;
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+
+use super::{discriminator, encode, encode_borsh, DlpInstruction, FEATURE_GATES_ID};
+use crate::args::{
+    DelegateEphemeralBalanceArgs, DelegateProgramEphemeralBalanceArgs, EphemeralBalanceIndex,
+    InitSessionReportArgs,
+};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes a top-up ephemeral balance instruction, see
+/// [crate::instruction_builder::top_up_ephemeral_balance]
+pub fn top_up_ephemeral_balance<'a>(
+    payer: &'a Pubkey,
+    pubkey: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    amount: u64,
+    index: u8,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::TopUpEphemeralBalance,
+        &(amount, index),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly(pubkey),
+            AccountMeta::writable(ephemeral_balance),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// An escrow topped up by [top_up_ephemeral_balance_batch]
+pub struct TopUpEscrow<'a> {
+    /// The pubkey owning the ephemeral balance
+    pub pubkey: &'a Pubkey,
+    /// The ephemeral balance PDA of the pubkey at the index
+    pub ephemeral_balance: &'a Pubkey,
+    /// The index of the ephemeral balance
+    pub index: EphemeralBalanceIndex,
+    /// The lamports to top up
+    pub amount: u64,
+}
+
+/// Encodes a top-up of the ephemeral balances of the escrows with up to `N` accounts, see
+/// [crate::instruction_builder::top_up_ephemeral_balance_batch]
+pub fn top_up_ephemeral_balance_batch<'a, const N: usize>(
+    payer: &'a Pubkey,
+    escrows: &[TopUpEscrow<'a>],
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, N>, ProgramError> {
+    let entries = u32::try_from(escrows.len()).map_err(|_| ProgramError::InvalidArgument)?;
+    let data = encode(
+        buffer,
+        DlpDiscriminator::TopUpEphemeralBalanceBatch,
+        |writer| {
+            entries.serialize(writer)?;
+            escrows
+                .iter()
+                .try_for_each(|escrow| (escrow.index, escrow.amount).serialize(writer))
+        },
+    )?;
+    let mut instruction = DlpInstruction::empty(data);
+    instruction.push(AccountMeta::writable_signer(payer))?;
+    instruction.push(AccountMeta::readonly(&pinocchio_system::ID))?;
+    for escrow in escrows {
+        instruction.push(AccountMeta::readonly(escrow.pubkey))?;
+        instruction.push(AccountMeta::writable(escrow.ephemeral_balance))?;
+    }
+    Ok(instruction)
+}
+
+/// Encodes a delegate ephemeral balance instruction, see
+/// [crate::instruction_builder::delegate_ephemeral_balance]
+#[allow(clippy::too_many_arguments)]
+pub fn delegate_ephemeral_balance<'a>(
+    payer: &'a Pubkey,
+    pubkey: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    delegate_buffer: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    args: &DelegateEphemeralBalanceArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 8>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::DelegateEphemeralBalance, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly_signer(pubkey),
+            AccountMeta::writable(ephemeral_balance),
+            AccountMeta::writable(delegate_buffer),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&crate::fast::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a close ephemeral balance instruction, see
+/// [crate::instruction_builder::close_ephemeral_balance] and
+/// [crate::instruction_builder::close_wide_ephemeral_balance]
+pub fn close_ephemeral_balance<'a>(
+    payer: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    index: EphemeralBalanceIndex,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 3>, ProgramError> {
+    // The index is written without its enum tag, its width tells a narrow from a wide index
+    let data = encode(
+        buffer,
+        DlpDiscriminator::CloseEphemeralBalance,
+        |writer| match index {
+            EphemeralBalanceIndex::Narrow(index) => index.serialize(writer),
+            EphemeralBalanceIndex::Wide(index) => index.serialize(writer),
+        },
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::writable(ephemeral_balance),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a top-up program ephemeral balance instruction, see
+/// [crate::instruction_builder::top_up_program_ephemeral_balance]
+pub fn top_up_program_ephemeral_balance<'a>(
+    payer: &'a Pubkey,
+    pubkey: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    amount: u64,
+    program_id: &Pubkey,
+    index: u8,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 5>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::TopUpProgramEphemeralBalance,
+        &(amount, program_id, index),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly(pubkey),
+            AccountMeta::writable(ephemeral_balance),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a delegate program ephemeral balance instruction, see
+/// [crate::instruction_builder::delegate_program_ephemeral_balance]. The whitelist shard of
+/// the validator is passed for the programs whitelisting it in a shard.
+#[allow(clippy::too_many_arguments)]
+pub fn delegate_program_ephemeral_balance<'a>(
+    payer: &'a Pubkey,
+    pubkey: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    delegate_buffer: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    program_config: &'a Pubkey,
+    whitelist_shard: Option<&'a Pubkey>,
+    args: &DelegateProgramEphemeralBalanceArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 11>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::DelegateProgramEphemeralBalance,
+        args,
+    )?;
+    let mut instruction = DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::readonly_signer(pubkey),
+            AccountMeta::writable(ephemeral_balance),
+            AccountMeta::writable(delegate_buffer),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&crate::fast::ID),
+        ],
+        data,
+    )
+    .with_accounts(&[])?;
+    if let Some(whitelist_shard) = whitelist_shard {
+        instruction.push(AccountMeta::readonly(whitelist_shard))?;
+    }
+    instruction.push(AccountMeta::readonly(&FEATURE_GATES_ID))?;
+    Ok(instruction)
+}
+
+/// Encodes a close program ephemeral balance instruction, see
+/// [crate::instruction_builder::close_program_ephemeral_balance]
+pub fn close_program_ephemeral_balance<'a>(
+    payer: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    program_id: &Pubkey,
+    index: u8,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::CloseProgramEphemeralBalance,
+        &(program_id, index),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::writable(ephemeral_balance),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(&FEATURE_GATES_ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a redelegate ephemeral balance instruction, see
+/// [crate::instruction_builder::redelegate_ephemeral_balance]
+#[allow(clippy::too_many_arguments)]
+pub fn redelegate_ephemeral_balance<'a>(
+    validator: &'a Pubkey,
+    pubkey: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    new_validator: &'a Pubkey,
+    new_validator_fees_vault: &'a Pubkey,
+    index: u8,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 9>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::RedelegateEphemeralBalance, &index)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::readonly_signer(validator),
+            AccountMeta::readonly_signer(pubkey),
+            AccountMeta::readonly(ephemeral_balance),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::readonly(new_validator),
+            AccountMeta::readonly(new_validator_fees_vault),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a fund escrow from delegated instruction, see
+/// [crate::instruction_builder::fund_escrow_from_delegated]
+#[allow(clippy::too_many_arguments)]
+pub fn fund_escrow_from_delegated<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    ephemeral_balance: &'a Pubkey,
+    amount: u64,
+    index: u8,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 8>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::FundEscrowFromDelegated,
+        &(amount, index),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::writable(ephemeral_balance),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an init session report instruction, see
+/// [crate::instruction_builder::init_session_report]
+pub fn init_session_report<'a>(
+    escrow_authority: &'a Pubkey,
+    escrow: &'a Pubkey,
+    session_report: &'a Pubkey,
+    escrow_index: u8,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::InitSessionReport,
+        &InitSessionReportArgs { escrow_index },
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(escrow_authority),
+            AccountMeta::readonly(escrow),
+            AccountMeta::writable(session_report),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a close session report instruction, see
+/// [crate::instruction_builder::close_session_report]
+pub fn close_session_report<'a>(
+    escrow_authority: &'a Pubkey,
+    escrow: &'a Pubkey,
+    session_report: &'a Pubkey,
+) -> DlpInstruction<'a, 3> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(escrow_authority),
+            AccountMeta::readonly(escrow),
+            AccountMeta::writable(session_report),
+        ],
+        discriminator(DlpDiscriminator::CloseSessionReport),
+    )
+}
@@ -0,0 +1,230 @@
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+
+use super::{discriminator, encode_borsh, DlpInstruction};
+use crate::args::{SetAuthorityGrantArgs, SetValidatorCommitQuotaArgs, SetValidatorInfoArgs};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes an init protocol fees vault instruction, see
+/// [crate::instruction_builder::init_protocol_fees_vault]
+pub fn init_protocol_fees_vault<'a>(
+    payer: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+) -> DlpInstruction<'a, 3> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::InitProtocolFeesVault),
+    )
+}
+
+/// Encodes an init validator fees vault instruction, see
+/// [crate::instruction_builder::init_validator_fees_vault]
+pub fn init_validator_fees_vault<'a>(
+    payer: &'a Pubkey,
+    admin: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    validator_identity: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+) -> DlpInstruction<'a, 6> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::writable_signer(admin),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::writable(validator_identity),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::InitValidatorFeesVault),
+    )
+}
+
+/// Encodes a close validator fees vault instruction, see
+/// [crate::instruction_builder::close_validator_fees_vault]
+pub fn close_validator_fees_vault<'a>(
+    payer: &'a Pubkey,
+    admin: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    validator_identity: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+) -> DlpInstruction<'a, 5> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(payer),
+            AccountMeta::writable_signer(admin),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::writable(validator_identity),
+            AccountMeta::writable(validator_fees_vault),
+        ],
+        discriminator(DlpDiscriminator::CloseValidatorFeesVault),
+    )
+}
+
+/// Encodes a validator claim fees instruction, see
+/// [crate::instruction_builder::validator_claim_fees]
+pub fn validator_claim_fees<'a>(
+    validator: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    amount: Option<u64>,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 3>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::ValidatorClaimFees, &amount)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::writable(validator_fees_vault),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a protocol claim fees instruction, see
+/// [crate::instruction_builder::protocol_claim_fees]
+pub fn protocol_claim_fees<'a>(
+    admin: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+) -> DlpInstruction<'a, 3> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::readonly(delegation_program_data),
+        ],
+        discriminator(DlpDiscriminator::ProtocolClaimFees),
+    )
+}
+
+/// Encodes a register commit relayer instruction, see
+/// [crate::instruction_builder::register_commit_relayer]
+pub fn register_commit_relayer<'a>(
+    validator: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    relayer: &Pubkey,
+    approved: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 3>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::RegisterCommitRelayer,
+        &(relayer, approved),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a set authority grant instruction, see
+/// [crate::instruction_builder::set_authority_grant]
+pub fn set_authority_grant<'a>(
+    delegator: &'a Pubkey,
+    authority_delegation_chain: &'a Pubkey,
+    args: &SetAuthorityGrantArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 3>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SetAuthorityGrant, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(delegator),
+            AccountMeta::writable(authority_delegation_chain),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a set validator commit quota instruction, see
+/// [crate::instruction_builder::set_validator_commit_quota]
+pub fn set_validator_commit_quota<'a>(
+    admin: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    validator: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    args: &SetValidatorCommitQuotaArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 5>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SetValidatorCommitQuota, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(validator),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a set validator info instruction, see
+/// [crate::instruction_builder::set_validator_info]
+pub fn set_validator_info<'a>(
+    validator: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    validator_info: &'a Pubkey,
+    args: &SetValidatorInfoArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SetValidatorInfo, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::writable(validator_info),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a validator heartbeat instruction, see
+/// [crate::instruction_builder::validator_heartbeat]
+pub fn validator_heartbeat<'a>(
+    validator: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    validator_liveness: &'a Pubkey,
+) -> DlpInstruction<'a, 4> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::writable(validator_liveness),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::ValidatorHeartbeat),
+    )
+}
+
+/// Encodes an init earnings ledger page instruction, see
+/// [crate::instruction_builder::init_earnings_ledger_page].
+/// The previous page trails the accounts from the second page on.
+pub fn init_earnings_ledger_page<'a>(
+    validator: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    earnings_ledger_page: &'a Pubkey,
+    page: u32,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::InitEarningsLedgerPage, &page)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::readonly(validator_fees_vault),
+            AccountMeta::writable(earnings_ledger_page),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
@@ -0,0 +1,368 @@
+//! Pinocchio encoders of the instructions of the delegation program, for the on-chain
+//! programs composing with it through `invoke` / `invoke_signed` without solana-program.
+//!
+//! Each encoder mirrors the builder of the same name in [crate::instruction_builder], with
+//! the same accounts in the same order, except that nothing is derived nor allocated: the
+//! caller passes every account key, PDAs included, and the instruction data is written into
+//! a buffer borrowed from the caller. The instructions without args carry their
+//! discriminator inline and take no buffer.
+//!
+//! The optional trailing accounts of an instruction are appended with
+//! [DlpInstruction::with_accounts], into an instruction of a larger capacity.
+
+use borsh::io::{Result as IoResult, Write};
+
+use borsh::BorshSerialize;
+use pinocchio::instruction::{AccountMeta, Instruction};
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+
+use crate::discriminator::DlpDiscriminator;
+
+mod commit;
+mod delegation;
+mod ephemeral_balance;
+mod fees_vault;
+mod program_config;
+mod protocol;
+mod summaries;
+mod undelegate;
+
+pub use commit::*;
+pub use delegation::*;
+pub use ephemeral_balance::*;
+pub use fees_vault::*;
+pub use program_config::*;
+pub use protocol::*;
+pub use summaries::*;
+pub use undelegate::*;
+
+/// The feature gates PDA, see [crate::consts::FEATURE_GATES_PDA], passed last to the gated
+/// instructions
+pub const FEATURE_GATES_ID: Pubkey = crate::consts::FEATURE_GATES_PDA.to_bytes();
+
+/// An instruction of the delegation program holding up to `N` accounts, borrowing its
+/// account keys and its data from the caller
+pub struct DlpInstruction<'a, const N: usize> {
+    accounts: [AccountMeta<'a>; N],
+    len: usize,
+    data: InstructionData<'a>,
+}
+
+/// The data of a [DlpInstruction]
+#[derive(Clone, Copy, Debug)]
+pub enum InstructionData<'a> {
+    /// The discriminator of an instruction without args
+    Discriminator([u8; 8]),
+    /// The discriminator and the args, written into a buffer of the caller
+    Buffer(&'a [u8]),
+}
+
+impl<'a, const N: usize> DlpInstruction<'a, N> {
+    /// An instruction with all its `N` accounts
+    fn new(accounts: [AccountMeta<'a>; N], data: InstructionData<'a>) -> Self {
+        Self {
+            accounts,
+            len: N,
+            data,
+        }
+    }
+
+    /// An instruction without accounts yet, see [DlpInstruction::push]
+    fn empty(data: InstructionData<'a>) -> Self {
+        Self {
+            accounts: core::array::from_fn(|_| AccountMeta::readonly(&crate::fast::ID)),
+            len: 0,
+            data,
+        }
+    }
+
+    /// Append an account, failing if the capacity of the instruction is exceeded
+    fn push(&mut self, account: AccountMeta<'a>) -> Result<(), ProgramError> {
+        let slot = self
+            .accounts
+            .get_mut(self.len)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        *slot = account;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Copy the instruction into an instruction of capacity `M`, appending the trailing
+    /// accounts, e.g. the optional accounts of the instruction. Fails with
+    /// [ProgramError::NotEnoughAccountKeys] if the accounts do not fit in `M`.
+    pub fn with_accounts<const M: usize>(
+        &self,
+        trailing_accounts: &[AccountMeta<'a>],
+    ) -> Result<DlpInstruction<'a, M>, ProgramError> {
+        let mut instruction = DlpInstruction::empty(self.data);
+        for account in self.accounts().iter().chain(trailing_accounts) {
+            instruction.push(AccountMeta::new(
+                account.pubkey,
+                account.is_writable,
+                account.is_signer,
+            ))?;
+        }
+        Ok(instruction)
+    }
+
+    /// The accounts of the instruction
+    pub fn accounts(&self) -> &[AccountMeta<'a>] {
+        self.accounts.get(..self.len).unwrap_or_default()
+    }
+
+    /// The data of the instruction, starting with its discriminator
+    pub fn data(&self) -> &[u8] {
+        match &self.data {
+            InstructionData::Discriminator(discriminator) => discriminator,
+            InstructionData::Buffer(data) => data,
+        }
+    }
+
+    /// The instruction to `invoke` or `invoke_signed`
+    pub fn instruction(&self) -> Instruction<'a, '_, '_, '_> {
+        Instruction {
+            program_id: &crate::fast::ID,
+            data: self.data(),
+            accounts: self.accounts(),
+        }
+    }
+}
+
+/// The data of an instruction without args
+fn discriminator(discriminator: DlpDiscriminator) -> InstructionData<'static> {
+    InstructionData::Discriminator((discriminator as u64).to_le_bytes())
+}
+
+/// Write the discriminator and the args of an instruction at the start of the buffer,
+/// failing with [ProgramError::BorshIoError] if the buffer is too small
+fn encode<'a>(
+    buffer: &'a mut [u8],
+    discriminator: DlpDiscriminator,
+    write_args: impl FnOnce(&mut &mut [u8]) -> IoResult<()>,
+) -> Result<InstructionData<'a>, ProgramError> {
+    let capacity = buffer.len();
+    let written = {
+        let mut writer = &mut *buffer;
+        writer
+            .write_all(&(discriminator as u64).to_le_bytes())
+            .map_err(|_| ProgramError::BorshIoError)?;
+        write_args(&mut writer).map_err(|_| ProgramError::BorshIoError)?;
+        capacity - writer.len()
+    };
+    let buffer: &'a [u8] = buffer;
+    buffer
+        .get(..written)
+        .map(InstructionData::Buffer)
+        .ok_or(ProgramError::BorshIoError)
+}
+
+/// Write the discriminator and the borsh serialized args, see [encode]
+fn encode_borsh<'a>(
+    buffer: &'a mut [u8],
+    discriminator: DlpDiscriminator,
+    args: &impl BorshSerialize,
+) -> Result<InstructionData<'a>, ProgramError> {
+    encode(buffer, discriminator, |writer| args.serialize(writer))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::instruction::Instruction as SolanaInstruction;
+    use solana_program::pubkey::Pubkey as SolanaPubkey;
+
+    use super::*;
+    use crate::args::{CommitStateArgs, DelegateArgs, Seeds};
+    use crate::consts::FEATURE_GATES_PDA;
+    use crate::pda;
+
+    /// Require the fast instruction to encode the same accounts and data as the builder
+    fn assert_same<const N: usize>(fast: &DlpInstruction<N>, builder: &SolanaInstruction) {
+        let fast = fast.instruction();
+        assert_eq!(fast.program_id, &crate::id().to_bytes());
+        assert_eq!(fast.data, builder.data.as_slice());
+        assert_eq!(fast.accounts.len(), builder.accounts.len());
+        for (fast, builder) in fast.accounts.iter().zip(&builder.accounts) {
+            assert_eq!(fast.pubkey, &builder.pubkey.to_bytes());
+            assert_eq!(fast.is_writable, builder.is_writable);
+            assert_eq!(fast.is_signer, builder.is_signer);
+        }
+    }
+
+    #[test]
+    fn test_feature_gates_id() {
+        assert_eq!(FEATURE_GATES_ID, FEATURE_GATES_PDA.to_bytes());
+    }
+
+    #[test]
+    fn test_delegate() {
+        let payer = SolanaPubkey::new_unique();
+        let delegated_account = SolanaPubkey::new_unique();
+        let owner = SolanaPubkey::new_unique();
+        let validator = SolanaPubkey::new_unique();
+        let args = || DelegateArgs {
+            commit_frequency_ms: 1000,
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            validator: Some(validator),
+            heartbeat_timeout_slots: Some(100),
+            ..Default::default()
+        };
+        let builder =
+            crate::instruction_builder::delegate(payer, delegated_account, Some(owner), args());
+
+        let keys = [
+            payer,
+            delegated_account,
+            owner,
+            pda::delegate_buffer_pda_from_delegated_account_and_owner_program(
+                &delegated_account,
+                &owner,
+            ),
+            pda::delegation_record_pda_from_delegated_account(&delegated_account),
+            pda::delegation_metadata_pda_from_delegated_account(&delegated_account),
+        ]
+        .map(|key| key.to_bytes());
+        let mut buffer = [0; 256];
+        let fast = delegate(
+            &keys[0],
+            &keys[1],
+            &keys[2],
+            &keys[3],
+            &keys[4],
+            &keys[5],
+            &args(),
+            &mut buffer,
+        )
+        .unwrap();
+        assert_same(&fast, &builder);
+
+        // The staged delegate buffer trails the accounts
+        let staged_buffer =
+            pda::staged_delegate_buffer_pda_from_delegated_account(&delegated_account);
+        let builder = crate::instruction_builder::delegate_from_staged_buffer(
+            payer,
+            delegated_account,
+            Some(owner),
+            args(),
+        );
+        let staged_buffer = staged_buffer.to_bytes();
+        let fast = fast
+            .with_accounts::<8>(&[AccountMeta::writable(&staged_buffer)])
+            .unwrap();
+        assert_same(&fast, &builder);
+
+        // The capacity bounds the trailing accounts
+        assert!(fast
+            .with_accounts::<8>(&[AccountMeta::readonly(&keys[0])])
+            .is_err());
+    }
+
+    #[test]
+    fn test_commit_state() {
+        let validator = SolanaPubkey::new_unique();
+        let delegated_account = SolanaPubkey::new_unique();
+        let owner = SolanaPubkey::new_unique();
+        let args = || CommitStateArgs {
+            nonce: 1,
+            lamports: 1_000_000,
+            allow_undelegation: true,
+            data: vec![1, 2, 3, 4],
+            er_block_hash: Some([7; 32]),
+            ..Default::default()
+        };
+        let builder =
+            crate::instruction_builder::commit_state(validator, delegated_account, owner, args());
+
+        let keys = [
+            validator,
+            delegated_account,
+            pda::commit_state_pda_from_delegated_account(&delegated_account),
+            pda::commit_record_pda_from_delegated_account(&delegated_account),
+            pda::delegation_record_pda_from_delegated_account(&delegated_account),
+            pda::delegation_metadata_pda_from_delegated_account(&delegated_account),
+            pda::validator_fees_vault_pda_from_validator(&validator),
+            pda::program_config_from_program_id(&owner),
+        ]
+        .map(|key| key.to_bytes());
+        let mut buffer = [0; 128];
+        let fast = commit_state(
+            &keys[0],
+            &keys[1],
+            &keys[2],
+            &keys[3],
+            &keys[4],
+            &keys[5],
+            &keys[6],
+            &keys[7],
+            &args(),
+            &mut buffer,
+        )
+        .unwrap();
+        assert_same(&fast, &builder);
+
+        // The buffer must fit the data
+        let mut buffer = [0; 16];
+        assert!(commit_state(
+            &keys[0],
+            &keys[1],
+            &keys[2],
+            &keys[3],
+            &keys[4],
+            &keys[5],
+            &keys[6],
+            &keys[7],
+            &args(),
+            &mut buffer,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_top_up_ephemeral_balance() {
+        let payer = SolanaPubkey::new_unique();
+        let pubkey = SolanaPubkey::new_unique();
+        let builder =
+            crate::instruction_builder::top_up_ephemeral_balance(payer, pubkey, Some(500), Some(3));
+
+        let keys = [
+            payer,
+            pubkey,
+            pda::ephemeral_balance_pda_from_payer(&pubkey, 3),
+        ]
+        .map(|key| key.to_bytes());
+        let mut buffer = [0; 32];
+        let fast =
+            top_up_ephemeral_balance(&keys[0], &keys[1], &keys[2], 500, 3, &mut buffer).unwrap();
+        assert_same(&fast, &builder);
+    }
+
+    #[test]
+    fn test_undelegate_stage2() {
+        let validator = SolanaPubkey::new_unique();
+        let delegated_account = SolanaPubkey::new_unique();
+        let rent_reimbursement = SolanaPubkey::new_unique();
+        let builder = crate::instruction_builder::undelegate_stage2(
+            validator,
+            delegated_account,
+            rent_reimbursement,
+        );
+
+        let keys = [
+            validator,
+            delegated_account,
+            pda::delegation_record_pda_from_delegated_account(&delegated_account),
+            pda::delegation_metadata_pda_from_delegated_account(&delegated_account),
+            pda::undelegate_progress_pda_from_delegated_account(&delegated_account),
+            rent_reimbursement,
+            pda::fees_vault_pda(),
+            pda::validator_fees_vault_pda_from_validator(&validator),
+            pda::fee_exemption_pda_from_delegated_account(&delegated_account),
+        ]
+        .map(|key| key.to_bytes());
+        let fast = undelegate_stage2(
+            &keys[0], &keys[1], &keys[2], &keys[3], &keys[4], &keys[5], &keys[6], &keys[7],
+            &keys[8],
+        );
+        assert_same(&fast, &builder);
+    }
+}
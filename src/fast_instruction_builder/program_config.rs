@@ -0,0 +1,325 @@
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+
+use super::{discriminator, encode_borsh, DlpInstruction, InstructionData};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes a whitelist validator for program instruction, see
+/// [crate::instruction_builder::whitelist_validator_for_program]
+#[allow(clippy::too_many_arguments)]
+pub fn whitelist_validator_for_program<'a>(
+    authority: &'a Pubkey,
+    validator_identity: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    insert: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 7>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::WhitelistValidatorForProgram,
+        &insert,
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(validator_identity),
+            AccountMeta::readonly(program),
+            AccountMeta::readonly(program_data),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::writable(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a whitelist validator shard for program instruction, see
+/// [crate::instruction_builder::whitelist_validator_shard_for_program]
+#[allow(clippy::too_many_arguments)]
+pub fn whitelist_validator_shard_for_program<'a>(
+    authority: &'a Pubkey,
+    validator_identity: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    whitelist_shard: &'a Pubkey,
+    insert: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 8>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::WhitelistValidatorShardForProgram,
+        &insert,
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(validator_identity),
+            AccountMeta::readonly(program),
+            AccountMeta::readonly(program_data),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::writable(program_config),
+            AccountMeta::writable(whitelist_shard),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a whitelist validators for program batch instruction, see
+/// [crate::instruction_builder::whitelist_validators_for_program_batch]
+#[allow(clippy::too_many_arguments)]
+pub fn whitelist_validators_for_program_batch<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    insert: &[Pubkey],
+    remove: &[Pubkey],
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::WhitelistValidatorsForProgramBatch,
+        &(insert, remove),
+    )?;
+    Ok(program_config_instruction(
+        authority,
+        program,
+        program_data,
+        delegation_program_data,
+        program_config,
+        data,
+    ))
+}
+
+/// Encodes a migrate program config whitelist instruction with up to `N` accounts, the
+/// whitelist shards of the migrated validators trailing the accounts, see
+/// [crate::instruction_builder::migrate_program_config_whitelist]
+pub fn migrate_program_config_whitelist<'a, const N: usize>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    whitelist_shards: &[&'a Pubkey],
+) -> Result<DlpInstruction<'a, N>, ProgramError> {
+    let mut instruction = program_config_instruction(
+        authority,
+        program,
+        program_data,
+        delegation_program_data,
+        program_config,
+        discriminator(DlpDiscriminator::MigrateProgramConfigWhitelist),
+    )
+    .with_accounts(&[])?;
+    for &whitelist_shard in whitelist_shards {
+        instruction.push(AccountMeta::writable(whitelist_shard))?;
+    }
+    Ok(instruction)
+}
+
+/// Encodes a set program allowed data lens instruction, see
+/// [crate::instruction_builder::set_program_allowed_data_lens]
+pub fn set_program_allowed_data_lens<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    allowed_data_lens: &[u32],
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::SetProgramAllowedDataLens,
+        &allowed_data_lens,
+    )?;
+    Ok(program_config_instruction(
+        authority,
+        program,
+        program_data,
+        delegation_program_data,
+        program_config,
+        data,
+    ))
+}
+
+/// Encodes a set program max delegation slots instruction, see
+/// [crate::instruction_builder::set_program_max_delegation_slots]
+pub fn set_program_max_delegation_slots<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    max_delegation_slots: u64,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::SetProgramMaxDelegationSlots,
+        &max_delegation_slots,
+    )?;
+    Ok(program_config_instruction(
+        authority,
+        program,
+        program_data,
+        delegation_program_data,
+        program_config,
+        data,
+    ))
+}
+
+/// Encodes a set program undelegate discriminator override instruction, see
+/// [crate::instruction_builder::set_program_undelegate_discriminator_override]
+pub fn set_program_undelegate_discriminator_override<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    allow_undelegate_discriminator_override: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::SetProgramUndelegateDiscriminatorOverride,
+        &allow_undelegate_discriminator_override,
+    )?;
+    Ok(program_config_instruction(
+        authority,
+        program,
+        program_data,
+        delegation_program_data,
+        program_config,
+        data,
+    ))
+}
+
+/// Encodes a set program undelegate lamports tolerance instruction, see
+/// [crate::instruction_builder::set_program_undelegate_lamports_tolerance]
+pub fn set_program_undelegate_lamports_tolerance<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    undelegate_lamports_tolerance: u64,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::SetProgramUndelegateLamportsTolerance,
+        &undelegate_lamports_tolerance,
+    )?;
+    Ok(program_config_instruction(
+        authority,
+        program,
+        program_data,
+        delegation_program_data,
+        program_config,
+        data,
+    ))
+}
+
+/// Encodes a set program validate delegations instruction, see
+/// [crate::instruction_builder::set_program_validate_delegations]
+pub fn set_program_validate_delegations<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    validate_delegations: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 6>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::SetProgramValidateDelegations,
+        &validate_delegations,
+    )?;
+    Ok(program_config_instruction(
+        authority,
+        program,
+        program_data,
+        delegation_program_data,
+        program_config,
+        data,
+    ))
+}
+
+/// Encodes a close program config instruction, see
+/// [crate::instruction_builder::close_program_config]
+pub fn close_program_config<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    no_active_delegations: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 5>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::CloseProgramConfig,
+        &no_active_delegations,
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(program),
+            AccountMeta::readonly(program_data),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::writable(program_config),
+        ],
+        data,
+    ))
+}
+
+/// Encodes an is validator whitelisted instruction, see
+/// [crate::instruction_builder::is_validator_whitelisted]
+pub fn is_validator_whitelisted<'a>(
+    validator: &'a Pubkey,
+    program: &'a Pubkey,
+    program_config: &'a Pubkey,
+    whitelist_shard: &'a Pubkey,
+) -> DlpInstruction<'a, 4> {
+    DlpInstruction::new(
+        [
+            AccountMeta::readonly(validator),
+            AccountMeta::readonly(program),
+            AccountMeta::readonly(program_config),
+            AccountMeta::readonly(whitelist_shard),
+        ],
+        discriminator(DlpDiscriminator::IsValidatorWhitelisted),
+    )
+}
+
+/// The accounts shared by the instructions setting the program config of a program
+fn program_config_instruction<'a>(
+    authority: &'a Pubkey,
+    program: &'a Pubkey,
+    program_data: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    program_config: &'a Pubkey,
+    data: InstructionData<'a>,
+) -> DlpInstruction<'a, 6> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(authority),
+            AccountMeta::readonly(program),
+            AccountMeta::readonly(program_data),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::writable(program_config),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    )
+}
@@ -0,0 +1,169 @@
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+
+use super::{discriminator, encode_borsh, DlpInstruction, FEATURE_GATES_ID};
+use crate::args::{SetProtocolConfigArgs, SetVersionArgs};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes a bootstrap protocol instruction, see
+/// [crate::instruction_builder::bootstrap_protocol]
+pub fn bootstrap_protocol<'a>(
+    admin: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+    protocol_config: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+) -> DlpInstruction<'a, 6> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::writable(&FEATURE_GATES_ID),
+            AccountMeta::writable(protocol_config),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::BootstrapProtocol),
+    )
+}
+
+/// Encodes a set feature gate instruction, see [crate::instruction_builder::set_feature_gate]
+pub fn set_feature_gate<'a>(
+    admin: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    discriminator: u8,
+    enabled: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::SetFeatureGate,
+        &(discriminator, enabled),
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(&FEATURE_GATES_ID),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a set protocol config instruction, see
+/// [crate::instruction_builder::set_protocol_config]
+pub fn set_protocol_config<'a>(
+    admin: &'a Pubkey,
+    protocol_config: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    args: &SetProtocolConfigArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SetProtocolConfig, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(protocol_config),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a set version instruction, see [crate::instruction_builder::set_version]
+pub fn set_version<'a>(
+    admin: &'a Pubkey,
+    program_version: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    args: &SetVersionArgs,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 4>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::SetVersion, args)?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(program_version),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a get version instruction, see [crate::instruction_builder::get_version]
+pub fn get_version(program_version: &Pubkey) -> DlpInstruction<'_, 1> {
+    DlpInstruction::new(
+        [AccountMeta::readonly(program_version)],
+        discriminator(DlpDiscriminator::GetVersion),
+    )
+}
+
+/// Encodes a resync protocol stats instruction with up to `N` accounts, the delegation
+/// records of the delegated accounts trailing the accounts, see
+/// [crate::instruction_builder::resync_protocol_stats]
+pub fn resync_protocol_stats<'a, const N: usize>(
+    admin: &'a Pubkey,
+    protocol_stats: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+    delegation_records: &[&'a Pubkey],
+    reset: bool,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, N>, ProgramError> {
+    let data = encode_borsh(buffer, DlpDiscriminator::ResyncProtocolStats, &reset)?;
+    let mut instruction = DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(protocol_stats),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        data,
+    )
+    .with_accounts(&[])?;
+    for &delegation_record in delegation_records {
+        instruction.push(AccountMeta::readonly(delegation_record))?;
+    }
+    Ok(instruction)
+}
+
+/// Encodes a propose protocol vault migration instruction, see
+/// [crate::instruction_builder::propose_protocol_vault_migration]
+pub fn propose_protocol_vault_migration<'a>(
+    admin: &'a Pubkey,
+    protocol_config: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+) -> DlpInstruction<'a, 4> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(protocol_config),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::ProposeProtocolVaultMigration),
+    )
+}
+
+/// Encodes an execute protocol vault migration instruction, see
+/// [crate::instruction_builder::execute_protocol_vault_migration]
+pub fn execute_protocol_vault_migration<'a>(
+    admin: &'a Pubkey,
+    protocol_config: &'a Pubkey,
+    current_fees_vault: &'a Pubkey,
+    next_fees_vault: &'a Pubkey,
+    delegation_program_data: &'a Pubkey,
+) -> DlpInstruction<'a, 6> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(admin),
+            AccountMeta::writable(protocol_config),
+            AccountMeta::writable(current_fees_vault),
+            AccountMeta::writable(next_fees_vault),
+            AccountMeta::readonly(delegation_program_data),
+            AccountMeta::readonly(&pinocchio_system::ID),
+        ],
+        discriminator(DlpDiscriminator::ExecuteProtocolVaultMigration),
+    )
+}
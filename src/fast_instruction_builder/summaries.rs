@@ -0,0 +1,59 @@
+use borsh::BorshSerialize;
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+
+use super::{discriminator, encode, DlpInstruction};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes a get delegation summaries instruction with up to `N` accounts, see
+/// [crate::instruction_builder::get_delegation_summaries]. Each entry holds the delegated
+/// account, its delegation record, its delegation metadata and the program config of its
+/// owner program.
+pub fn get_delegation_summaries<'a, const N: usize>(
+    accounts: &[[&'a Pubkey; 4]],
+) -> Result<DlpInstruction<'a, N>, ProgramError> {
+    let mut instruction =
+        DlpInstruction::empty(discriminator(DlpDiscriminator::GetDelegationSummaries));
+    for account in accounts.iter().flatten() {
+        instruction.push(AccountMeta::readonly(account))?;
+    }
+    Ok(instruction)
+}
+
+/// Encodes a get escrow summaries instruction with up to `N` accounts, see
+/// [crate::instruction_builder::get_escrow_summaries]. Each entry holds the payer of the
+/// escrow, the escrow, its delegation record and its index.
+pub fn get_escrow_summaries<'a, const N: usize>(
+    escrows: &[(&'a Pubkey, &'a Pubkey, &'a Pubkey, u8)],
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, N>, ProgramError> {
+    let indexes = u32::try_from(escrows.len()).map_err(|_| ProgramError::InvalidArgument)?;
+    let data = encode(buffer, DlpDiscriminator::GetEscrowSummaries, |writer| {
+        indexes.serialize(writer)?;
+        escrows
+            .iter()
+            .try_for_each(|(_, _, _, index)| index.serialize(writer))
+    })?;
+    let mut instruction = DlpInstruction::empty(data);
+    for &(payer, escrow, delegation_record, _) in escrows {
+        instruction.push(AccountMeta::readonly(payer))?;
+        instruction.push(AccountMeta::readonly(escrow))?;
+        instruction.push(AccountMeta::readonly(delegation_record))?;
+    }
+    Ok(instruction)
+}
+
+/// Encodes a get pending commit summaries instruction with up to `N` accounts, see
+/// [crate::instruction_builder::get_pending_commit_summaries]. Each entry holds the
+/// delegated account, its commit state and its commit record.
+pub fn get_pending_commit_summaries<'a, const N: usize>(
+    delegated_accounts: &[[&'a Pubkey; 3]],
+) -> Result<DlpInstruction<'a, N>, ProgramError> {
+    let mut instruction =
+        DlpInstruction::empty(discriminator(DlpDiscriminator::GetPendingCommitSummaries));
+    for account in delegated_accounts.iter().flatten() {
+        instruction.push(AccountMeta::readonly(account))?;
+    }
+    Ok(instruction)
+}
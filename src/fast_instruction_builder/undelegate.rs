@@ -0,0 +1,208 @@
+use pinocchio::instruction::AccountMeta;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+
+use super::{discriminator, encode_borsh, DlpInstruction};
+use crate::args::{UndelegateArgs, UndelegateMode};
+use crate::discriminator::DlpDiscriminator;
+
+/// Encodes an undelegate instruction, see [crate::instruction_builder::undelegate]
+#[allow(clippy::too_many_arguments)]
+pub fn undelegate<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    owner_program: &'a Pubkey,
+    undelegate_buffer: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    rent_reimbursement: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    protocol_config: &'a Pubkey,
+    fee_exemption: &'a Pubkey,
+    program_config: &'a Pubkey,
+) -> DlpInstruction<'a, 15> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::readonly(owner_program),
+            AccountMeta::writable(undelegate_buffer),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(rent_reimbursement),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(protocol_config),
+            AccountMeta::readonly(fee_exemption),
+            AccountMeta::readonly(program_config),
+        ],
+        discriminator(DlpDiscriminator::Undelegate),
+    )
+}
+
+/// Encodes an undelegate instruction parking the state in the parked undelegation PDA, see
+/// [crate::instruction_builder::undelegate_to_buffer]
+#[allow(clippy::too_many_arguments)]
+pub fn undelegate_to_buffer<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    owner_program: &'a Pubkey,
+    parked_undelegation: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    rent_payer: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    protocol_config: &'a Pubkey,
+    fee_exemption: &'a Pubkey,
+    program_config: &'a Pubkey,
+    buffer: &'a mut [u8],
+) -> Result<DlpInstruction<'a, 15>, ProgramError> {
+    let data = encode_borsh(
+        buffer,
+        DlpDiscriminator::Undelegate,
+        &UndelegateArgs {
+            mode: UndelegateMode::ToBuffer,
+        },
+    )?;
+    Ok(DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::readonly(owner_program),
+            AccountMeta::writable(parked_undelegation),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable_signer(rent_payer),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(protocol_config),
+            AccountMeta::readonly(fee_exemption),
+            AccountMeta::readonly(program_config),
+        ],
+        data,
+    ))
+}
+
+/// Encodes a claim parked undelegation instruction, see
+/// [crate::instruction_builder::claim_parked_undelegation]
+pub fn claim_parked_undelegation<'a>(
+    rent_payer: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    parked_undelegation: &'a Pubkey,
+) -> DlpInstruction<'a, 3> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(rent_payer),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(parked_undelegation),
+        ],
+        discriminator(DlpDiscriminator::ClaimParkedUndelegation),
+    )
+}
+
+/// Encodes an undelegate and close instruction, see
+/// [crate::instruction_builder::undelegate_and_close]
+#[allow(clippy::too_many_arguments)]
+pub fn undelegate_and_close<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    close_destination: &'a Pubkey,
+    rent_reimbursement: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+) -> DlpInstruction<'a, 10> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(close_destination),
+            AccountMeta::writable(rent_reimbursement),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::writable(validator_fees_vault),
+        ],
+        discriminator(DlpDiscriminator::UndelegateAndClose),
+    )
+}
+
+/// Encodes the first stage of a staged undelegation, see
+/// [crate::instruction_builder::undelegate_stage1]
+#[allow(clippy::too_many_arguments)]
+pub fn undelegate_stage1<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    owner_program: &'a Pubkey,
+    undelegate_buffer: &'a Pubkey,
+    commit_state: &'a Pubkey,
+    commit_record: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    undelegate_progress: &'a Pubkey,
+    program_config: &'a Pubkey,
+) -> DlpInstruction<'a, 11> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::writable(delegated_account),
+            AccountMeta::readonly(owner_program),
+            AccountMeta::writable(undelegate_buffer),
+            AccountMeta::readonly(commit_state),
+            AccountMeta::readonly(commit_record),
+            AccountMeta::readonly(delegation_record),
+            AccountMeta::readonly(delegation_metadata),
+            AccountMeta::writable(undelegate_progress),
+            AccountMeta::readonly(&pinocchio_system::ID),
+            AccountMeta::readonly(program_config),
+        ],
+        discriminator(DlpDiscriminator::UndelegateStage1),
+    )
+}
+
+/// Encodes the second stage of a staged undelegation, see
+/// [crate::instruction_builder::undelegate_stage2]
+#[allow(clippy::too_many_arguments)]
+pub fn undelegate_stage2<'a>(
+    validator: &'a Pubkey,
+    delegated_account: &'a Pubkey,
+    delegation_record: &'a Pubkey,
+    delegation_metadata: &'a Pubkey,
+    undelegate_progress: &'a Pubkey,
+    rent_reimbursement: &'a Pubkey,
+    fees_vault: &'a Pubkey,
+    validator_fees_vault: &'a Pubkey,
+    fee_exemption: &'a Pubkey,
+) -> DlpInstruction<'a, 9> {
+    DlpInstruction::new(
+        [
+            AccountMeta::writable_signer(validator),
+            AccountMeta::readonly(delegated_account),
+            AccountMeta::writable(delegation_record),
+            AccountMeta::writable(delegation_metadata),
+            AccountMeta::writable(undelegate_progress),
+            AccountMeta::writable(rent_reimbursement),
+            AccountMeta::writable(fees_vault),
+            AccountMeta::writable(validator_fees_vault),
+            AccountMeta::readonly(fee_exemption),
+        ],
+        discriminator(DlpDiscriminator::UndelegateStage2),
+    )
+}
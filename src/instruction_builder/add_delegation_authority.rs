@@ -0,0 +1,37 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::DelegationAuthorityArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_authorities_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+};
+
+/// Builds an add delegation authority instruction.
+/// See [crate::processor::process_add_delegation_authority] for docs.
+pub fn add_delegation_authority(
+    authority: Pubkey,
+    delegated_account: Pubkey,
+    args: DelegationAuthorityArgs,
+) -> Instruction {
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    let delegation_authorities_pda =
+        delegation_authorities_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new_readonly(delegation_record_pda, false),
+            AccountMeta::new(delegation_authorities_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::AddDelegationAuthority.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
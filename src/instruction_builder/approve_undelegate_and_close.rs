@@ -0,0 +1,34 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::ApproveUndelegateAndCloseArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::delegation_metadata_pda_from_delegated_account;
+
+/// Builds an approve undelegate and close instruction, to be invoked by the owner program.
+/// See [crate::processor::process_approve_undelegate_and_close] for docs.
+pub fn approve_undelegate_and_close(
+    payer: Pubkey,
+    delegated_account: Pubkey,
+    close_destination: Pubkey,
+) -> Instruction {
+    let delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&delegated_account);
+    let args = ApproveUndelegateAndCloseArgs { close_destination };
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(delegated_account, true),
+            AccountMeta::new(delegation_metadata_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::ApproveUndelegateAndClose.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -1,8 +1,12 @@
 use crate::args::CallHandlerArgs;
 use crate::discriminator::DlpDiscriminator;
-use crate::pda::{ephemeral_balance_pda_from_payer, validator_fees_vault_pda_from_validator};
-use borsh::to_vec;
+use crate::pda::{
+    call_handler_permissions_pda_from_escrow, ephemeral_balance_pda_from_payer,
+    validator_fees_vault_pda_from_validator,
+};
+use alloc::vec::Vec;
 use solana_program::instruction::Instruction;
+use solana_program::sysvar::instructions;
 use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
 
 /// Builds a call handler instruction.
@@ -24,6 +28,11 @@ pub fn call_handler(
         AccountMeta::new_readonly(destination_program, false),
         AccountMeta::new(escrow_authority, false),
         AccountMeta::new(escrow_account, false),
+        AccountMeta::new_readonly(instructions::id(), false),
+        AccountMeta::new_readonly(
+            call_handler_permissions_pda_from_escrow(&escrow_account),
+            false,
+        ),
     ];
     // append other accounts at the end
     accounts.extend(other_accounts);
@@ -33,7 +42,7 @@ pub fn call_handler(
         accounts,
         data: [
             DlpDiscriminator::CallHandler.to_vec(),
-            to_vec(&args).unwrap(),
+            args.to_instruction_data(),
         ]
         .concat(),
     }
@@ -0,0 +1,22 @@
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::parked_undelegation_pda_from_delegated_account;
+
+/// Builds a claim parked undelegation instruction.
+/// See [crate::processor::process_claim_parked_undelegation] for docs.
+pub fn claim_parked_undelegation(rent_payer: Pubkey, delegated_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(rent_payer, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                parked_undelegation_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+        ],
+        data: DlpDiscriminator::ClaimParkedUndelegation.to_vec(),
+    }
+}
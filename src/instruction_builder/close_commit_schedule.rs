@@ -0,0 +1,30 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_schedule_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+};
+
+/// Builds a close commit schedule instruction.
+/// See [crate::processor::process_close_commit_schedule] for docs.
+pub fn close_commit_schedule(escrow_payer: Pubkey, delegated_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(escrow_payer, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                commit_schedule_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::CloseCommitSchedule.to_vec(),
+    }
+}
@@ -0,0 +1,41 @@
+use borsh::to_vec;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::CloseProgramConfigArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_config_from_program_id;
+
+/// Close the program config of a program, refunding its rent to the authority
+///
+/// See [crate::processor::process_close_program_config] for docs.
+pub fn close_program_config(
+    authority: Pubkey,
+    program: Pubkey,
+    no_active_delegations: bool,
+) -> Instruction {
+    let args = CloseProgramConfigArgs {
+        no_active_delegations,
+    };
+    let program_data =
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let program_config_pda = program_config_from_program_id(&program);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(program, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new(program_config_pda, false),
+        ],
+        data: [
+            DlpDiscriminator::CloseProgramConfig.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
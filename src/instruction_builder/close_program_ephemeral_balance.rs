@@ -0,0 +1,34 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_program};
+
+use crate::args::CloseProgramEphemeralBalanceArgs;
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_ephemeral_balance_pda_from_payer;
+
+/// Creates instruction to close a program ephemeral balance account
+/// See [crate::processor::process_close_program_ephemeral_balance] for docs.
+pub fn close_program_ephemeral_balance(
+    payer: Pubkey,
+    program_id: Pubkey,
+    index: u8,
+) -> Instruction {
+    let args = CloseProgramEphemeralBalanceArgs { program_id, index };
+    let ephemeral_balance_pda =
+        program_ephemeral_balance_pda_from_payer(&payer, &program_id, index);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(ephemeral_balance_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(FEATURE_GATES_PDA, false),
+        ],
+        data: [
+            DlpDiscriminator::CloseProgramEphemeralBalance.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,20 @@
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{ephemeral_balance_pda_from_payer, session_report_pda_from_escrow};
+
+/// Builds a close session report instruction.
+/// See [crate::processor::process_close_session_report] for docs.
+pub fn close_session_report(escrow_authority: Pubkey, escrow_index: u8) -> Instruction {
+    let escrow = ephemeral_balance_pda_from_payer(&escrow_authority, escrow_index);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(escrow_authority, true),
+            AccountMeta::new_readonly(escrow, false),
+            AccountMeta::new(session_report_pda_from_escrow(&escrow), false),
+        ],
+        data: DlpDiscriminator::CloseSessionReport.to_vec(),
+    }
+}
@@ -0,0 +1,70 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::CommitStateArgs;
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account, program_config_from_program_id,
+    read_lock_pda_from_delegated_account, validator_fees_vault_pda_from_validator,
+};
+
+/// Builds a commit and finalize instruction.
+/// See [crate::processor::fast::process_commit_finalize] for docs.
+pub fn commit_finalize(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    commit_args: CommitStateArgs,
+) -> Instruction {
+    let commit_args = commit_args.to_instruction_data();
+    let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    let delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&delegated_account);
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator);
+    let program_config_pda = program_config_from_program_id(&delegated_account_owner);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new(delegated_account, false),
+            AccountMeta::new_readonly(commit_record_pda, false),
+            AccountMeta::new(delegation_record_pda, false),
+            AccountMeta::new(delegation_metadata_pda, false),
+            AccountMeta::new(validator_fees_vault_pda, false),
+            AccountMeta::new_readonly(program_config_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(FEATURE_GATES_PDA, false),
+        ],
+        data: [DlpDiscriminator::CommitFinalize.to_vec(), commit_args].concat(),
+    }
+}
+
+/// Builds a commit and finalize instruction locking the reads of the delegated account until
+/// the next slot, see [crate::processor::process_init_read_lock] to initialize the read lock.
+/// See [crate::processor::fast::process_commit_finalize] for docs.
+pub fn commit_finalize_with_read_lock(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    commit_args: CommitStateArgs,
+) -> Instruction {
+    let mut ix = commit_finalize(
+        validator,
+        delegated_account,
+        delegated_account_owner,
+        commit_args,
+    );
+    // Gated instructions carry the feature gates PDA as their last account
+    ix.accounts.insert(
+        ix.accounts.len() - 1,
+        AccountMeta::new(
+            read_lock_pda_from_delegated_account(&delegated_account),
+            false,
+        ),
+    );
+    ix
+}
@@ -0,0 +1,47 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::CommitFromOwnerArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+};
+
+/// Push a state to a delegated PDA, invoked by its owner program signing for the PDA.
+/// The pushed state is finalized with [crate::instruction_builder::finalize_owner_commit].
+///
+/// See [crate::processor::process_commit_from_owner] for docs.
+pub fn commit_from_owner(
+    payer: Pubkey,
+    delegated_account: Pubkey,
+    nonce: u64,
+    data: Vec<u8>,
+) -> Instruction {
+    let args = CommitFromOwnerArgs { nonce, data };
+    let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
+    let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    let delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(delegated_account, true),
+            AccountMeta::new(commit_state_pda, false),
+            AccountMeta::new(commit_record_pda, false),
+            AccountMeta::new_readonly(delegation_record_pda, false),
+            AccountMeta::new_readonly(delegation_metadata_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::CommitFromOwner.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,32 @@
+use alloc::vec::Vec;
+use solana_program::instruction::Instruction;
+use solana_program::sysvar::instructions;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::instruction_builder::commit_session_end;
+
+/// Builds a commit session begin instruction.
+/// See [crate::processor::process_commit_session_begin] for docs.
+pub fn commit_session_begin(validator: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(validator, true),
+            AccountMeta::new_readonly(instructions::id(), false),
+        ],
+        data: DlpDiscriminator::CommitSessionBegin.to_vec(),
+    }
+}
+
+/// Wraps the commit instructions of a validator in a commit session, sorting them by
+/// delegated account as required by the session.
+/// See [crate::processor::process_commit_session_begin] for docs.
+pub fn commit_session(validator: Pubkey, mut commits: Vec<Instruction>) -> Vec<Instruction> {
+    commits.sort_by_key(|ix| ix.accounts[1].pubkey);
+    let mut ixs = Vec::with_capacity(commits.len() + 2);
+    ixs.push(commit_session_begin(validator));
+    ixs.extend(commits);
+    ixs.push(commit_session_end(validator));
+    ixs
+}
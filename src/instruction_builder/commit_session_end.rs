@@ -0,0 +1,18 @@
+use solana_program::instruction::Instruction;
+use solana_program::sysvar::instructions;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+
+/// Builds a commit session end instruction.
+/// See [crate::processor::process_commit_session_end] for docs.
+pub fn commit_session_end(validator: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(validator, true),
+            AccountMeta::new_readonly(instructions::id(), false),
+        ],
+        data: DlpDiscriminator::CommitSessionEnd.to_vec(),
+    }
+}
@@ -1,14 +1,16 @@
-use borsh::to_vec;
 use solana_program::instruction::Instruction;
 use solana_program::system_program;
 use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
 
-use crate::args::CommitStateArgs;
+use crate::args::{CommitStateArgs, Encoding};
+use crate::compression::compress_lz4;
 use crate::discriminator::DlpDiscriminator;
 use crate::pda::{
-    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    authority_delegation_chain_pda_from_delegator, commit_record_pda_from_delegated_account,
+    commit_state_pda_from_delegated_account, delegation_authorities_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
     program_config_from_program_id, validator_fees_vault_pda_from_validator,
+    validator_whitelist_shard_pda_from_program_id,
 };
 
 /// Builds a commit state instruction.
@@ -19,7 +21,8 @@ pub fn commit_state(
     delegated_account_owner: Pubkey,
     commit_args: CommitStateArgs,
 ) -> Instruction {
-    let commit_args = to_vec(&commit_args).unwrap();
+    let allow_undelegation = commit_args.allow_undelegation;
+    let commit_args = commit_args.to_instruction_data();
     let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
     let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
     let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
@@ -35,7 +38,12 @@ pub fn commit_state(
             AccountMeta::new(commit_state_pda, false),
             AccountMeta::new(commit_record_pda, false),
             AccountMeta::new_readonly(delegation_record_pda, false),
-            AccountMeta::new(delegation_metadata_pda, false),
+            // The delegation metadata is only written by commits allowing the undelegation
+            AccountMeta {
+                pubkey: delegation_metadata_pda,
+                is_signer: false,
+                is_writable: allow_undelegation,
+            },
             AccountMeta::new_readonly(validator_fees_vault_pda, false),
             AccountMeta::new_readonly(program_config_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
@@ -43,3 +51,131 @@ pub fn commit_state(
         data: [DlpDiscriminator::CommitState.to_vec(), commit_args].concat(),
     }
 }
+
+/// Builds a commit state instruction compressing the committed state, see
+/// [compress_commit_state_args].
+/// See [crate::processor::process_commit_state] for docs.
+pub fn commit_state_compressed(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    commit_args: CommitStateArgs,
+) -> Instruction {
+    commit_state(
+        validator,
+        delegated_account,
+        delegated_account_owner,
+        compress_commit_state_args(commit_args),
+    )
+}
+
+/// Compresses the committed state of raw args with LZ4, unless it does not make it
+/// smaller, see [Encoding::Lz4]. The compressed args can be passed to any commit state
+/// builder but the commit finalize, which only accepts a raw state.
+pub fn compress_commit_state_args(commit_args: CommitStateArgs) -> CommitStateArgs {
+    if commit_args.encoding != Encoding::Raw {
+        return commit_args;
+    }
+    let compressed = compress_lz4(&commit_args.data);
+    if compressed.len() >= commit_args.data.len() {
+        return commit_args;
+    }
+    CommitStateArgs {
+        data: compressed,
+        encoding: Encoding::Lz4,
+        ..commit_args
+    }
+}
+
+/// Builds a commit state instruction signed by a commit relayer of the validator, the
+/// commit being attributed to the validator identity.
+/// See [crate::processor::process_commit_state] for docs.
+pub fn commit_state_from_relayer(
+    relayer: Pubkey,
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    commit_args: CommitStateArgs,
+) -> Instruction {
+    let mut ix = commit_state(
+        validator,
+        delegated_account,
+        delegated_account_owner,
+        commit_args,
+    );
+    ix.accounts[0] = AccountMeta::new_readonly(relayer, true);
+    ix
+}
+
+/// Builds a commit state instruction passing the whitelist shard of the validator, for the
+/// programs whitelisting it in a shard, see [crate::state::ValidatorWhitelistShard].
+/// See [crate::processor::process_commit_state] for docs.
+pub fn commit_state_with_whitelist_shard(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    commit_args: CommitStateArgs,
+) -> Instruction {
+    let mut ix = commit_state(
+        validator,
+        delegated_account,
+        delegated_account_owner,
+        commit_args,
+    );
+    ix.accounts.push(AccountMeta::new_readonly(
+        validator_whitelist_shard_pda_from_program_id(&delegated_account_owner, &validator),
+        false,
+    ));
+    ix
+}
+
+/// Builds a commit state instruction signed by a sub-key of the validator, granted through
+/// the authority delegation chains of the validator and of the intermediate `grantors`, in
+/// order, see [crate::state::AuthorityDelegationChain].
+/// See [crate::processor::process_commit_state] for docs.
+pub fn commit_state_from_sub_key(
+    sub_key: Pubkey,
+    validator: Pubkey,
+    grantors: &[Pubkey],
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    commit_args: CommitStateArgs,
+) -> Instruction {
+    let mut ix = commit_state_from_relayer(
+        sub_key,
+        validator,
+        delegated_account,
+        delegated_account_owner,
+        commit_args,
+    );
+    for delegator in [validator].iter().chain(grantors) {
+        ix.accounts.push(AccountMeta::new_readonly(
+            authority_delegation_chain_pda_from_delegator(delegator),
+            false,
+        ));
+    }
+    ix
+}
+
+/// Builds a commit state instruction signed by one of the delegation authorities of the
+/// delegated account, passing them, the commit being attributed to the validator, see
+/// [crate::state::DelegationAuthorities].
+/// See [crate::processor::process_commit_state] for docs.
+pub fn commit_state_from_delegation_authority(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    commit_args: CommitStateArgs,
+) -> Instruction {
+    let mut ix = commit_state(
+        validator,
+        delegated_account,
+        delegated_account_owner,
+        commit_args,
+    );
+    ix.accounts.push(AccountMeta::new_readonly(
+        delegation_authorities_pda_from_delegated_account(&delegated_account),
+        false,
+    ));
+    ix
+}
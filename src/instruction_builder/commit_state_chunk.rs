@@ -0,0 +1,70 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::CommitStateChunkArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+};
+use crate::state::ChunkMerkleTree;
+
+/// Builds a commit state chunk instruction.
+/// See [crate::processor::process_commit_state_chunk] for docs.
+pub fn commit_state_chunk(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    args: CommitStateChunkArgs,
+) -> Instruction {
+    let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
+    let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(validator, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(commit_state_pda, false),
+            AccountMeta::new_readonly(commit_record_pda, false),
+        ],
+        data: [
+            DlpDiscriminator::CommitStateChunk.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Builds the commit state chunk instructions writing the chunks of a state committed by the
+/// root of its [ChunkMerkleTree] which differ from the data of the delegated account, see
+/// [crate::instruction_builder::commit_state_root]. Their number is the `chunks_to_write` of
+/// the commit.
+pub fn commit_state_chunks(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_data: &[u8],
+    state: &[u8],
+    chunk_size: u32,
+) -> Vec<Instruction> {
+    let tree = ChunkMerkleTree::new(state, chunk_size as usize);
+    let chunk_size = chunk_size.max(1) as usize;
+    state
+        .chunks(chunk_size)
+        .enumerate()
+        .filter(|(index, chunk)| {
+            let start = index * chunk_size;
+            delegated_data.get(start..start + chunk.len()) != Some(*chunk)
+        })
+        .map(|(index, chunk)| {
+            commit_state_chunk(
+                validator,
+                delegated_account,
+                CommitStateChunkArgs {
+                    index: index as u32,
+                    data: chunk.to_vec(),
+                    proof: tree.proof(index),
+                },
+            )
+        })
+        .collect()
+}
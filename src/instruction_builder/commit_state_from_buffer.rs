@@ -1,4 +1,3 @@
-use borsh::to_vec;
 use solana_program::instruction::Instruction;
 use solana_program::system_program;
 use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
@@ -20,7 +19,8 @@ pub fn commit_state_from_buffer(
     commit_state_buffer: Pubkey,
     commit_args: CommitStateFromBufferArgs,
 ) -> Instruction {
-    let commit_args = to_vec(&commit_args).unwrap();
+    let allow_undelegation = commit_args.allow_undelegation;
+    let commit_args = commit_args.to_instruction_data();
     let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
     let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
     let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
@@ -36,7 +36,12 @@ pub fn commit_state_from_buffer(
             AccountMeta::new(commit_state_pda, false),
             AccountMeta::new(commit_record_pda, false),
             AccountMeta::new_readonly(delegation_record_pda, false),
-            AccountMeta::new(delegation_metadata_pda, false),
+            // The delegation metadata is only written by commits allowing the undelegation
+            AccountMeta {
+                pubkey: delegation_metadata_pda,
+                is_signer: false,
+                is_writable: allow_undelegation,
+            },
             AccountMeta::new_readonly(commit_state_buffer, false),
             AccountMeta::new_readonly(validator_fees_vault_pda, false),
             AccountMeta::new_readonly(program_config_pda, false),
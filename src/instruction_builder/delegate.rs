@@ -1,4 +1,3 @@
-use borsh::to_vec;
 use solana_program::instruction::Instruction;
 use solana_program::system_program;
 use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
@@ -8,6 +7,7 @@ use crate::discriminator::DlpDiscriminator;
 use crate::pda::{
     delegate_buffer_pda_from_delegated_account_and_owner_program,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    program_config_from_program_id, staged_delegate_buffer_pda_from_delegated_account,
 };
 
 /// Builds a delegate instruction
@@ -25,7 +25,7 @@ pub fn delegate(
     let delegation_metadata_pda =
         delegation_metadata_pda_from_delegated_account(&delegated_account);
     let mut data = DlpDiscriminator::Delegate.to_vec();
-    data.extend_from_slice(&to_vec(&args).unwrap());
+    data.extend_from_slice(&args.to_instruction_data());
 
     Instruction {
         program_id: crate::id(),
@@ -41,3 +41,46 @@ pub fn delegate(
         data,
     }
 }
+
+/// Builds a delegate instruction copying the data from the staged delegate buffer
+/// See [crate::processor::process_delegate] for docs.
+pub fn delegate_from_staged_buffer(
+    payer: Pubkey,
+    delegated_account: Pubkey,
+    owner: Option<Pubkey>,
+    args: DelegateArgs,
+) -> Instruction {
+    let mut ix = delegate(payer, delegated_account, owner, args);
+    ix.accounts.push(AccountMeta::new(
+        staged_delegate_buffer_pda_from_delegated_account(&delegated_account),
+        false,
+    ));
+    ix
+}
+
+/// Builds a delegate instruction passing the program config of the owner program, so that
+/// the owner program validates the delegation if its program config requires it
+/// See [crate::processor::process_delegate] for docs.
+pub fn delegate_with_program_config(
+    payer: Pubkey,
+    delegated_account: Pubkey,
+    owner: Pubkey,
+    args: DelegateArgs,
+) -> Instruction {
+    let mut ix = delegate(payer, delegated_account, Some(owner), args);
+    ix.accounts.push(AccountMeta::new_readonly(
+        program_config_from_program_id(&owner),
+        false,
+    ));
+    ix
+}
+
+/// Pass the rent co-payer to a delegate instruction with [DelegateArgs::rent_co_payer],
+/// signing, or to an undelegate, undelegate stage 2 or undelegate and close instruction of a
+/// delegation with a rent co-payer, refunded the rent it paid.
+/// The rent co-payer is passed last, after the other optional accounts.
+pub fn with_rent_co_payer(mut ix: Instruction, rent_co_payer: Pubkey) -> Instruction {
+    let is_signer = ix.data.starts_with(&DlpDiscriminator::Delegate.to_vec());
+    ix.accounts.push(AccountMeta::new(rent_co_payer, is_signer));
+    ix
+}
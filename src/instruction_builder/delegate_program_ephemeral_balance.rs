@@ -0,0 +1,62 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::DelegateProgramEphemeralBalanceArgs;
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegate_buffer_pda_from_delegated_account_and_owner_program,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    program_config_from_program_id, program_ephemeral_balance_pda_from_payer,
+    validator_whitelist_shard_pda_from_program_id,
+};
+
+/// Delegate program ephemeral balance
+/// See [crate::processor::process_delegate_program_ephemeral_balance] for docs.
+pub fn delegate_program_ephemeral_balance(
+    payer: Pubkey,
+    pubkey: Pubkey,
+    args: DelegateProgramEphemeralBalanceArgs,
+) -> Instruction {
+    let delegated_account =
+        program_ephemeral_balance_pda_from_payer(&pubkey, &args.program_id, args.index);
+    let delegate_buffer_pda = delegate_buffer_pda_from_delegated_account_and_owner_program(
+        &delegated_account,
+        &system_program::id(),
+    );
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    let delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&delegated_account);
+    let program_config_pda = program_config_from_program_id(&args.program_id);
+    // The shard of the validator, for programs whitelisting it in a shard
+    let whitelist_shard_pda = args.delegate_args.validator.map(|validator| {
+        validator_whitelist_shard_pda_from_program_id(&args.program_id, &validator)
+    });
+    let mut data = DlpDiscriminator::DelegateProgramEphemeralBalance.to_vec();
+    data.extend_from_slice(&to_vec(&args).unwrap());
+
+    let mut accounts = vec![
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(pubkey, true),
+        AccountMeta::new(delegated_account, false),
+        AccountMeta::new(delegate_buffer_pda, false),
+        AccountMeta::new(delegation_record_pda, false),
+        AccountMeta::new(delegation_metadata_pda, false),
+        AccountMeta::new_readonly(program_config_pda, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(crate::id(), false),
+    ];
+    accounts.extend(
+        whitelist_shard_pda
+            .map(|whitelist_shard_pda| AccountMeta::new_readonly(whitelist_shard_pda, false)),
+    );
+    accounts.push(AccountMeta::new_readonly(FEATURE_GATES_PDA, false));
+
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data,
+    }
+}
@@ -0,0 +1,47 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::stake;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_program, sysvar};
+
+use crate::args::DelegateStakeAccountArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    stake_authority_pda_from_stake_account,
+};
+
+/// Builds a delegate stake account instruction.
+/// See [crate::processor::process_delegate_stake_account] for docs.
+pub fn delegate_stake_account(
+    authority: Pubkey,
+    stake_account: Pubkey,
+    args: DelegateStakeAccountArgs,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(stake_account, false),
+            AccountMeta::new_readonly(
+                stake_authority_pda_from_stake_account(&stake_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_record_pda_from_delegated_account(&stake_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&stake_account),
+                false,
+            ),
+            AccountMeta::new_readonly(stake::program::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::DelegateStakeAccount.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,40 @@
+use solana_program::instruction::Instruction;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account, force_undelegation_pda_from_delegated_account,
+};
+
+/// Builds an execute force undelegate instruction.
+/// See [crate::processor::process_execute_force_undelegate] for docs.
+pub fn execute_force_undelegate(admin: Pubkey, delegated_account: Pubkey) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new_readonly(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                force_undelegation_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(delegation_program_data, false),
+        ],
+        data: DlpDiscriminator::ExecuteForceUndelegate.to_vec(),
+    }
+}
@@ -0,0 +1,29 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{fees_vault_pda_from_generation, protocol_config_pda};
+
+/// Builds an execute protocol vault migration instruction, migrating the vault of the
+/// current generation of the protocol config to the next one.
+/// See [crate::processor::process_execute_protocol_vault_migration] for docs.
+pub fn execute_protocol_vault_migration(admin: Pubkey, current_generation: u64) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(protocol_config_pda(), false),
+            AccountMeta::new(fees_vault_pda_from_generation(current_generation), false),
+            AccountMeta::new(
+                fees_vault_pda_from_generation(current_generation + 1),
+                false,
+            ),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::ExecuteProtocolVaultMigration.to_vec(),
+    }
+}
@@ -0,0 +1,31 @@
+use solana_program::instruction::Instruction;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+};
+
+/// Builds an export delegation package instruction.
+/// See [crate::processor::process_export_delegation_package] for docs.
+pub fn export_delegation_package(admin: Pubkey, delegated_account: Pubkey) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new_readonly(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+        ],
+        data: DlpDiscriminator::ExportDelegationPackage.to_vec(),
+    }
+}
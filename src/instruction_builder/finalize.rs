@@ -1,12 +1,13 @@
 use solana_program::instruction::Instruction;
 use solana_program::system_program;
+use solana_program::sysvar::instructions;
 use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
 
 use crate::discriminator::DlpDiscriminator;
 use crate::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
-    validator_fees_vault_pda_from_validator,
+    read_lock_pda_from_delegated_account, validator_fees_vault_pda_from_validator,
 };
 
 /// Builds a finalize state instruction.
@@ -33,3 +34,49 @@ pub fn finalize(validator: Pubkey, delegated_account: Pubkey) -> Instruction {
         data: DlpDiscriminator::Finalize.to_vec(),
     }
 }
+
+/// Builds a finalize instruction passing the validator fees vault read-only, for commits
+/// which did not decrease the lamports of the delegated account and settle without it.
+/// See [crate::processor::process_finalize] for docs.
+pub fn finalize_settled(validator: Pubkey, delegated_account: Pubkey) -> Instruction {
+    let mut ix = finalize(validator, delegated_account);
+    ix.accounts[6].is_writable = false;
+    ix
+}
+
+/// Builds a finalize instruction locking the reads of the delegated account until the next
+/// slot, see [crate::processor::process_init_read_lock] to initialize the read lock.
+/// See [crate::processor::process_finalize] for docs.
+pub fn finalize_with_read_lock(validator: Pubkey, delegated_account: Pubkey) -> Instruction {
+    let mut ix = finalize(validator, delegated_account);
+    ix.accounts.push(AccountMeta::new(
+        read_lock_pda_from_delegated_account(&delegated_account),
+        false,
+    ));
+    ix
+}
+
+/// Builds a finalize instruction refunding the payer of a commit pushed by the owner program
+/// the rent of the commit PDAs, see [crate::instruction_builder::commit_from_owner].
+/// See [crate::processor::process_finalize] for docs.
+pub fn finalize_owner_commit(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    let mut ix = finalize(validator, delegated_account);
+    ix.accounts.push(AccountMeta::new(payer, false));
+    ix
+}
+
+/// Builds a finalize instruction passing the instructions sysvar, required to finalize a
+/// commit declaring an escrow spend, which is cross-checked against the call handler
+/// instructions of the escrow in the same transaction, see
+/// [crate::args::CommitStateArgs::escrow_spend].
+/// See [crate::processor::process_finalize] for docs.
+pub fn finalize_with_escrow_spend(validator: Pubkey, delegated_account: Pubkey) -> Instruction {
+    let mut ix = finalize(validator, delegated_account);
+    ix.accounts
+        .push(AccountMeta::new_readonly(instructions::id(), false));
+    ix
+}
@@ -0,0 +1,35 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account,
+};
+
+/// Builds a force undelegate instruction.
+/// See [crate::processor::process_force_undelegate] for docs.
+pub fn force_undelegate(payer: Pubkey, delegated_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new_readonly(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::ForceUndelegate.to_vec(),
+    }
+}
@@ -0,0 +1,56 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::FundEscrowFromDelegatedArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    ephemeral_balance_pda_from_payer,
+};
+
+/// Builds a fund escrow from delegated instruction, funding the ephemeral balance at `index`
+/// of the rent payer of the delegation with `amount` lamports of the delegated account.
+/// Pass the protocol stats with [crate::instruction_builder::with_protocol_stats].
+/// See [crate::processor::process_fund_escrow_from_delegated] for docs.
+pub fn fund_escrow_from_delegated(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    rent_payer: Pubkey,
+    amount: u64,
+    index: u8,
+) -> Instruction {
+    let args = FundEscrowFromDelegatedArgs { amount, index };
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new(delegated_account, false),
+            AccountMeta::new(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_state_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(ephemeral_balance_pda_from_payer(&rent_payer, index), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::FundEscrowFromDelegated.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
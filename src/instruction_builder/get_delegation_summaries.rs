@@ -0,0 +1,34 @@
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    program_config_from_program_id,
+};
+
+/// Builds a get delegation summaries instruction from the accounts and their owner programs.
+/// See [crate::processor::process_get_delegation_summaries] for docs.
+pub fn get_delegation_summaries(accounts: &[(Pubkey, Pubkey)]) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: accounts
+            .iter()
+            .flat_map(|(account, owner_program)| {
+                [
+                    AccountMeta::new_readonly(*account, false),
+                    AccountMeta::new_readonly(
+                        delegation_record_pda_from_delegated_account(account),
+                        false,
+                    ),
+                    AccountMeta::new_readonly(
+                        delegation_metadata_pda_from_delegated_account(account),
+                        false,
+                    ),
+                    AccountMeta::new_readonly(program_config_from_program_id(owner_program), false),
+                ]
+            })
+            .collect(),
+        data: DlpDiscriminator::GetDelegationSummaries.to_vec(),
+    }
+}
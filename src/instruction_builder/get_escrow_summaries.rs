@@ -0,0 +1,38 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::GetEscrowSummariesArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{delegation_record_pda_from_delegated_account, ephemeral_balance_pda_from_payer};
+
+/// Builds a get escrow summaries instruction from the payers and the indexes of their
+/// escrows.
+/// See [crate::processor::process_get_escrow_summaries] for docs.
+pub fn get_escrow_summaries(escrows: &[(Pubkey, u8)]) -> Instruction {
+    let args = GetEscrowSummariesArgs {
+        indexes: escrows.iter().map(|(_, index)| *index).collect(),
+    };
+    Instruction {
+        program_id: crate::id(),
+        accounts: escrows
+            .iter()
+            .flat_map(|(payer, index)| {
+                let escrow = ephemeral_balance_pda_from_payer(payer, *index);
+                [
+                    AccountMeta::new_readonly(*payer, false),
+                    AccountMeta::new_readonly(escrow, false),
+                    AccountMeta::new_readonly(
+                        delegation_record_pda_from_delegated_account(&escrow),
+                        false,
+                    ),
+                ]
+            })
+            .collect(),
+        data: [
+            DlpDiscriminator::GetEscrowSummaries.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,32 @@
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+};
+
+/// Builds a get pending commit summaries instruction from the delegated accounts.
+/// See [crate::processor::process_get_pending_commit_summaries] for docs.
+pub fn get_pending_commit_summaries(delegated_accounts: &[Pubkey]) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: delegated_accounts
+            .iter()
+            .flat_map(|delegated_account| {
+                [
+                    AccountMeta::new_readonly(*delegated_account, false),
+                    AccountMeta::new_readonly(
+                        commit_state_pda_from_delegated_account(delegated_account),
+                        false,
+                    ),
+                    AccountMeta::new_readonly(
+                        commit_record_pda_from_delegated_account(delegated_account),
+                        false,
+                    ),
+                ]
+            })
+            .collect(),
+        data: DlpDiscriminator::GetPendingCommitSummaries.to_vec(),
+    }
+}
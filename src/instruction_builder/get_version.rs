@@ -0,0 +1,15 @@
+use solana_program::instruction::AccountMeta;
+use solana_program::instruction::Instruction;
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_version_pda;
+
+/// Builds a get version instruction.
+/// See [crate::processor::process_get_version] for docs.
+pub fn get_version() -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![AccountMeta::new_readonly(program_version_pda(), false)],
+        data: DlpDiscriminator::GetVersion.to_vec(),
+    }
+}
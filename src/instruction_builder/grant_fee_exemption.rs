@@ -0,0 +1,37 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::GrantFeeExemptionArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::fee_exemption_pda_from_delegated_account;
+
+/// Builds a grant fee exemption instruction.
+/// See [crate::processor::process_grant_fee_exemption] for docs.
+pub fn grant_fee_exemption(
+    admin: Pubkey,
+    delegated_account: Pubkey,
+    args: GrantFeeExemptionArgs,
+) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                fee_exemption_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::GrantFeeExemption.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,81 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::GrowCommitStateArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account, validator_fees_vault_pda_from_validator,
+};
+
+/// Builds a grow commit state instruction.
+/// See [crate::processor::process_grow_commit_state] for docs.
+pub fn grow_commit_state(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    data: Vec<u8>,
+) -> Instruction {
+    let args = GrowCommitStateArgs { data };
+    let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
+    let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(commit_state_pda, false),
+            AccountMeta::new_readonly(commit_record_pda, false),
+            AccountMeta::new_readonly(delegation_record_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::GrowCommitState.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Builds a grow commit state instruction signed by a commit relayer of the validator.
+/// See [crate::processor::process_grow_commit_state] for docs.
+pub fn grow_commit_state_from_relayer(
+    relayer: Pubkey,
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    data: Vec<u8>,
+) -> Instruction {
+    let mut ix = grow_commit_state(relayer, delegated_account, data);
+    ix.accounts.push(AccountMeta::new_readonly(
+        validator_fees_vault_pda_from_validator(&validator),
+        false,
+    ));
+    ix
+}
+
+/// Builds a close streamed commit state instruction, refunding the payer of the stream.
+/// See [crate::processor::process_close_streamed_commit_state] for docs.
+pub fn close_streamed_commit_state(
+    authority: Pubkey,
+    delegated_account: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
+    let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(commit_state_pda, false),
+            AccountMeta::new_readonly(commit_record_pda, false),
+            AccountMeta::new_readonly(delegation_record_pda, false),
+            AccountMeta::new(payer, false),
+        ],
+        data: DlpDiscriminator::CloseStreamedCommitState.to_vec(),
+    }
+}
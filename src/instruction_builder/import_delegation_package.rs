@@ -0,0 +1,87 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::sysvar::instructions;
+use solana_program::{
+    bpf_loader_upgradeable, ed25519_program, instruction::AccountMeta, pubkey::Pubkey,
+    system_program,
+};
+
+use crate::args::ImportDelegationPackageArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    protocol_config_pda,
+};
+use crate::state::DelegationPackage;
+
+/// Builds the instructions importing a delegation package: the Ed25519 program instruction
+/// verifying the `signature` of the borsh serialized package by its exporter, followed by the
+/// import delegation package instruction.
+/// See [crate::processor::process_import_delegation_package] for docs.
+pub fn import_delegation_package(
+    admin: Pubkey,
+    package: DelegationPackage,
+    signature: [u8; 64],
+) -> Vec<Instruction> {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegated_account = package.delegated_account;
+    let signature_ix = ed25519_verify(&package.exporter, &signature, &to_vec(&package).unwrap());
+    let args = ImportDelegationPackageArgs { package };
+    let import_ix = Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(instructions::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(protocol_config_pda(), false),
+        ],
+        data: [
+            DlpDiscriminator::ImportDelegationPackage.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    };
+    vec![signature_ix, import_ix]
+}
+
+/// Builds an Ed25519 program instruction verifying a single signature, whose public key,
+/// signature and message are held by the instruction itself
+fn ed25519_verify(public_key: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Instruction {
+    const CURRENT_INSTRUCTION: u16 = u16::MAX;
+    let public_key_offset: u16 = 2 + 7 * 2;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+    let offsets = [
+        signature_offset,
+        CURRENT_INSTRUCTION,
+        public_key_offset,
+        CURRENT_INSTRUCTION,
+        message_offset,
+        message.len() as u16,
+        CURRENT_INSTRUCTION,
+    ];
+    let mut data = vec![1, 0];
+    for offset in offsets {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(public_key.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
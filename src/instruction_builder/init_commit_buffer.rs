@@ -0,0 +1,34 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::InitCommitBufferArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::commit_buffer_pda_from_delegated_account_and_authority;
+
+/// Builds an init commit buffer instruction.
+/// See [crate::processor::process_init_commit_buffer] for docs.
+pub fn init_commit_buffer(
+    authority: Pubkey,
+    delegated_account: Pubkey,
+    data_len: u32,
+) -> Instruction {
+    let args = InitCommitBufferArgs { data_len };
+    let commit_buffer_pda =
+        commit_buffer_pda_from_delegated_account_and_authority(&delegated_account, &authority);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(commit_buffer_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::InitCommitBuffer.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,33 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::InitDelegateBufferArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::staged_delegate_buffer_pda_from_delegated_account;
+
+/// Builds an init delegate buffer instruction.
+/// See [crate::processor::process_init_delegate_buffer] for docs.
+pub fn init_delegate_buffer(
+    payer: Pubkey,
+    delegated_account: Pubkey,
+    data_len: u32,
+) -> Instruction {
+    let args = InitDelegateBufferArgs { data_len };
+    let staged_buffer_pda = staged_delegate_buffer_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(delegated_account, true),
+            AccountMeta::new(staged_buffer_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::InitDelegateBuffer.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
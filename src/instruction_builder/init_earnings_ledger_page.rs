@@ -0,0 +1,51 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::InitEarningsLedgerPageArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    earnings_ledger_page_pda_from_validator, validator_fees_vault_pda_from_validator,
+};
+
+/// Builds an init earnings ledger page instruction.
+/// See [crate::processor::process_init_earnings_ledger_page] for docs.
+pub fn init_earnings_ledger_page(validator: Pubkey, page: u32) -> Instruction {
+    let args = InitEarningsLedgerPageArgs { page };
+    let mut accounts = vec![
+        AccountMeta::new(validator, true),
+        AccountMeta::new_readonly(validator_fees_vault_pda_from_validator(&validator), false),
+        AccountMeta::new(
+            earnings_ledger_page_pda_from_validator(&validator, page),
+            false,
+        ),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(previous) = page.checked_sub(1) {
+        accounts.push(AccountMeta::new_readonly(
+            earnings_ledger_page_pda_from_validator(&validator, previous),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: [
+            DlpDiscriminator::InitEarningsLedgerPage.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Pass the earnings ledger page of the validator to a finalize, commit finalize, crank
+/// finalize, undelegate or undelegate and close instruction, so that it records what the
+/// validator fees vault earns
+pub fn with_earnings_ledger_page(mut ix: Instruction, validator: Pubkey, page: u32) -> Instruction {
+    ix.accounts.push(AccountMeta::new(
+        earnings_ledger_page_pda_from_validator(&validator, page),
+        false,
+    ));
+    ix
+}
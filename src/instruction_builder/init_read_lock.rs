@@ -0,0 +1,30 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_record_pda_from_delegated_account, read_lock_pda_from_delegated_account,
+};
+
+/// Builds an init read lock instruction.
+/// See [crate::processor::process_init_read_lock] for docs.
+pub fn init_read_lock(payer: Pubkey, delegated_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new_readonly(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                read_lock_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::InitReadLock.to_vec(),
+    }
+}
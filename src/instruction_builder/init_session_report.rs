@@ -0,0 +1,38 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::InitSessionReportArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{ephemeral_balance_pda_from_payer, session_report_pda_from_escrow};
+
+/// Builds an init session report instruction.
+/// See [crate::processor::process_init_session_report] for docs.
+pub fn init_session_report(escrow_authority: Pubkey, escrow_index: u8) -> Instruction {
+    let escrow = ephemeral_balance_pda_from_payer(&escrow_authority, escrow_index);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(escrow_authority, true),
+            AccountMeta::new_readonly(escrow, false),
+            AccountMeta::new(session_report_pda_from_escrow(&escrow), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::InitSessionReport.to_vec(),
+            to_vec(&InitSessionReportArgs { escrow_index }).unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Pass the session report of an escrow to a finalize of a commit of the escrow, a call
+/// handler spending from it or its undelegation, so that it records the session
+pub fn with_session_report(mut ix: Instruction, escrow: &Pubkey) -> Instruction {
+    ix.accounts.push(AccountMeta::new(
+        session_report_pda_from_escrow(escrow),
+        false,
+    ));
+    ix
+}
@@ -0,0 +1,23 @@
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{program_config_from_program_id, validator_whitelist_shard_pda_from_program_id};
+
+/// Builds an is validator whitelisted instruction.
+/// See [crate::processor::process_is_validator_whitelisted] for docs.
+pub fn is_validator_whitelisted(validator: Pubkey, program: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(validator, false),
+            AccountMeta::new_readonly(program, false),
+            AccountMeta::new_readonly(program_config_from_program_id(&program), false),
+            AccountMeta::new_readonly(
+                validator_whitelist_shard_pda_from_program_id(&program, &validator),
+                false,
+            ),
+        ],
+        data: DlpDiscriminator::IsValidatorWhitelisted.to_vec(),
+    }
+}
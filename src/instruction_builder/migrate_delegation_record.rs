@@ -0,0 +1,22 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::delegation_record_pda_from_delegated_account;
+
+/// Builds a migrate delegation record instruction.
+/// See [crate::processor::process_migrate_delegation_record] for docs.
+pub fn migrate_delegation_record(payer: Pubkey, delegated_account: Pubkey) -> Instruction {
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(delegation_record_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::MigrateDelegationRecord.to_vec(),
+    }
+}
@@ -0,0 +1,41 @@
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{program_config_from_program_id, validator_whitelist_shard_pda_from_program_id};
+
+/// Migrate the first validators of the whitelist of a program config to their shards,
+/// `validators` being the first `approved_validators` of the program config, in order.
+///
+/// See [crate::processor::process_migrate_program_config_whitelist] for docs.
+pub fn migrate_program_config_whitelist(
+    authority: Pubkey,
+    program: Pubkey,
+    validators: &[Pubkey],
+) -> Instruction {
+    let program_data =
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let mut accounts = vec![
+        AccountMeta::new(authority, true),
+        AccountMeta::new_readonly(program, false),
+        AccountMeta::new_readonly(program_data, false),
+        AccountMeta::new_readonly(delegation_program_data, false),
+        AccountMeta::new(program_config_from_program_id(&program), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(validators.iter().map(|validator| {
+        AccountMeta::new(
+            validator_whitelist_shard_pda_from_program_id(&program, validator),
+            false,
+        )
+    }));
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: DlpDiscriminator::MigrateProgramConfigWhitelist.to_vec(),
+    }
+}
@@ -1,35 +1,185 @@
+mod add_delegation_authority;
+mod approve_undelegate_and_close;
+mod bootstrap_protocol;
 mod call_handler;
+mod claim_parked_undelegation;
+mod close_commit_buffer;
+mod close_commit_schedule;
 mod close_ephemeral_balance;
+mod close_program_config;
+mod close_program_ephemeral_balance;
+mod close_session_report;
 mod close_validator_fees_vault;
 mod commit_diff;
 mod commit_diff_from_buffer;
+mod commit_diff_shadow;
+mod commit_finalize;
+mod commit_from_owner;
+mod commit_session_begin;
+mod commit_session_end;
 mod commit_state;
+mod commit_state_chunk;
 mod commit_state_from_buffer;
+mod commit_state_root;
+mod crank_finalize;
 mod delegate;
 mod delegate_ephemeral_balance;
+mod delegate_program_ephemeral_balance;
+mod delegate_stake_account;
+mod execute_force_undelegate;
+mod execute_protocol_vault_migration;
+mod export_delegation_package;
 mod finalize;
+mod force_undelegate;
+mod fund_escrow_from_delegated;
+mod get_delegation_summaries;
+mod get_escrow_summaries;
+mod get_pending_commit_summaries;
+mod get_version;
+mod grant_fee_exemption;
+mod grow_commit_state;
+mod import_delegation_package;
+mod init_commit_buffer;
+mod init_delegate_buffer;
+mod init_earnings_ledger_page;
 mod init_protocol_fees_vault;
+mod init_read_lock;
+mod init_session_report;
 mod init_validator_fees_vault;
+mod is_validator_whitelisted;
+mod migrate_delegation_record;
+mod migrate_program_config_whitelist;
+mod plan_commit;
+mod propose_protocol_vault_migration;
 mod protocol_claim_fees;
+mod redelegate_ephemeral_balance;
+mod register_commit_relayer;
+mod remove_delegation_authority;
+mod request_undelegation;
+mod resync_protocol_stats;
+mod schedule_force_undelegate;
+mod set_authority_grant;
+mod set_call_handler_permissions;
+mod set_commit_schedule;
+mod set_feature_gate;
+mod set_program_allowed_data_lens;
+mod set_program_max_delegation_slots;
+mod set_program_undelegate_discriminator_override;
+mod set_program_undelegate_lamports_tolerance;
+mod set_program_validate_delegations;
+mod set_protocol_config;
+mod set_validator_commit_quota;
+mod set_validator_info;
+mod set_version;
+mod split_delegation;
 mod top_up_ephemeral_balance;
+mod top_up_ephemeral_balance_batch;
+mod top_up_program_ephemeral_balance;
+mod transaction_packer;
 mod undelegate;
+mod undelegate_and_close;
+mod undelegate_stage1;
+mod undelegate_stage2;
+mod undelegate_stake_account;
+mod update_delegation_authority;
+mod validate_delegation;
 mod validator_claim_fees;
+mod validator_heartbeat;
 mod whitelist_validator_for_program;
+mod whitelist_validator_shard_for_program;
+mod whitelist_validators_for_program_batch;
+mod write_commit_buffer;
+mod write_delegate_buffer_chunk;
 
+pub use add_delegation_authority::*;
+pub use approve_undelegate_and_close::*;
+pub use bootstrap_protocol::*;
 pub use call_handler::*;
+pub use claim_parked_undelegation::*;
+pub use close_commit_buffer::*;
+pub use close_commit_schedule::*;
 pub use close_ephemeral_balance::*;
+pub use close_program_config::*;
+pub use close_program_ephemeral_balance::*;
+pub use close_session_report::*;
 pub use close_validator_fees_vault::*;
 pub use commit_diff::*;
 pub use commit_diff_from_buffer::*;
+pub use commit_diff_shadow::*;
+pub use commit_finalize::*;
+pub use commit_from_owner::*;
+pub use commit_session_begin::*;
+pub use commit_session_end::*;
 pub use commit_state::*;
+pub use commit_state_chunk::*;
 pub use commit_state_from_buffer::*;
+pub use commit_state_root::*;
+pub use crank_finalize::*;
 pub use delegate::*;
 pub use delegate_ephemeral_balance::*;
+pub use delegate_program_ephemeral_balance::*;
+pub use delegate_stake_account::*;
+pub use execute_force_undelegate::*;
+pub use execute_protocol_vault_migration::*;
+pub use export_delegation_package::*;
 pub use finalize::*;
+pub use force_undelegate::*;
+pub use fund_escrow_from_delegated::*;
+pub use get_delegation_summaries::*;
+pub use get_escrow_summaries::*;
+pub use get_pending_commit_summaries::*;
+pub use get_version::*;
+pub use grant_fee_exemption::*;
+pub use grow_commit_state::*;
+pub use import_delegation_package::*;
+pub use init_commit_buffer::*;
+pub use init_delegate_buffer::*;
+pub use init_earnings_ledger_page::*;
 pub use init_protocol_fees_vault::*;
+pub use init_read_lock::*;
+pub use init_session_report::*;
 pub use init_validator_fees_vault::*;
+pub use is_validator_whitelisted::*;
+pub use migrate_delegation_record::*;
+pub use migrate_program_config_whitelist::*;
+pub use plan_commit::*;
+pub use propose_protocol_vault_migration::*;
 pub use protocol_claim_fees::*;
+pub use redelegate_ephemeral_balance::*;
+pub use register_commit_relayer::*;
+pub use remove_delegation_authority::*;
+pub use request_undelegation::*;
+pub use resync_protocol_stats::*;
+pub use schedule_force_undelegate::*;
+pub use set_authority_grant::*;
+pub use set_call_handler_permissions::*;
+pub use set_commit_schedule::*;
+pub use set_feature_gate::*;
+pub use set_program_allowed_data_lens::*;
+pub use set_program_max_delegation_slots::*;
+pub use set_program_undelegate_discriminator_override::*;
+pub use set_program_undelegate_lamports_tolerance::*;
+pub use set_program_validate_delegations::*;
+pub use set_protocol_config::*;
+pub use set_validator_commit_quota::*;
+pub use set_validator_info::*;
+pub use set_version::*;
+pub use split_delegation::*;
 pub use top_up_ephemeral_balance::*;
+pub use top_up_ephemeral_balance_batch::*;
+pub use top_up_program_ephemeral_balance::*;
+pub use transaction_packer::*;
 pub use undelegate::*;
+pub use undelegate_and_close::*;
+pub use undelegate_stage1::*;
+pub use undelegate_stage2::*;
+pub use undelegate_stake_account::*;
+pub use update_delegation_authority::*;
+pub use validate_delegation::*;
 pub use validator_claim_fees::*;
+pub use validator_heartbeat::*;
 pub use whitelist_validator_for_program::*;
+pub use whitelist_validator_shard_for_program::*;
+pub use whitelist_validators_for_program_batch::*;
+pub use write_commit_buffer::*;
+pub use write_delegate_buffer_chunk::*;
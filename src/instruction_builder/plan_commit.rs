@@ -0,0 +1,304 @@
+use alloc::vec::Vec;
+use solana_program::instruction::Instruction;
+use solana_program::message::Message;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{CommitDiffArgs, CommitStateArgs, CommitStateFromBufferArgs, Encoding};
+use crate::instruction_builder::{
+    commit_diff, commit_state, commit_state_from_buffer, grow_commit_state,
+};
+use crate::pda::commit_state_pda_from_delegated_account;
+use crate::{compute_diff, DiffSet};
+
+/// The maximum size of a serialized transaction
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Estimated compute units of a commit, excluding the data it processes
+pub const COMMIT_BASE_CU: u32 = 20_000;
+
+/// Estimated compute units of appending a chunk to a streamed commit state
+pub const GROW_COMMIT_STATE_CU: u32 = 15_000;
+
+/// Estimated compute units of applying a segment of a diff
+pub const DIFF_SEGMENT_CU: u32 = 150;
+
+/// Estimated number of bytes of data processed per compute unit
+pub const BYTES_PER_CU: u32 = 100;
+
+/// The instruction a commit plan commits the account with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitMode {
+    /// The full state in a commit state instruction
+    State,
+    /// The diff of the state in a commit diff instruction
+    Diff,
+    /// The full state streamed into the commit state with grow commit state instructions,
+    /// then committed from it with a commit state from buffer instruction
+    StreamedState,
+}
+
+/// A commit ready to be sent, see [plan_commit]
+#[derive(Clone, Debug)]
+pub struct CommitPlan {
+    pub mode: CommitMode,
+    /// The transactions to send in order, the commit being in the last one
+    pub transactions: Vec<Vec<Instruction>>,
+    /// The signers of every transaction, also paying for them
+    pub signers: Vec<Pubkey>,
+    /// The estimated compute units of the most expensive transaction
+    pub estimated_cu: u32,
+}
+
+/// Plans the cheapest commit of a delegated account from its original data to the
+/// changed data in `args`, such that every transaction fits in `max_tx_size` bytes and
+/// `max_cu` compute units.
+///
+/// The diff is preferred when its instruction is smaller than the one of the full state.
+/// When neither fits in a transaction, the state is streamed into the commit state over
+/// several transactions. A commit claiming a base state hash is only verified by the commit
+/// state instruction, see [CommitStateArgs::base_state_hash], and is planned in that mode
+/// only, as is a compressed state, see [CommitStateArgs::encoding]. Returns None if no plan
+/// fits the limits.
+pub fn plan_commit(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    original: &[u8],
+    args: CommitStateArgs,
+    max_tx_size: usize,
+    max_cu: u32,
+) -> Option<CommitPlan> {
+    let single = |mode: CommitMode, ix: Instruction, estimated_cu: u32| {
+        let size = transaction_size(&validator, &[ix.clone()]);
+        (size <= max_tx_size && estimated_cu <= max_cu).then(|| {
+            (
+                size,
+                CommitPlan {
+                    mode,
+                    transactions: vec![vec![ix]],
+                    signers: vec![validator],
+                    estimated_cu,
+                },
+            )
+        })
+    };
+
+    let state = single(
+        CommitMode::State,
+        commit_state(
+            validator,
+            delegated_account,
+            delegated_account_owner,
+            CommitStateArgs {
+                data: args.data.clone(),
+                ..args
+            },
+        ),
+        data_cu(COMMIT_BASE_CU, args.data.len()),
+    );
+
+    if args.base_state_hash.is_some() || args.encoding != Encoding::Raw {
+        return state.map(|(_, plan)| plan);
+    }
+
+    let diff = compute_diff(original, &args.data).to_vec();
+    let segments = DiffSet::try_new(&diff).map_or(0, |diffset| diffset.segments_count()) as u32;
+    let diff = single(
+        CommitMode::Diff,
+        commit_diff(
+            validator,
+            delegated_account,
+            delegated_account_owner,
+            CommitDiffArgs {
+                diff,
+                nonce: args.nonce,
+                lamports: args.lamports,
+                allow_undelegation: args.allow_undelegation,
+                er_block_hash: args.er_block_hash,
+            },
+        ),
+        data_cu(COMMIT_BASE_CU, args.data.len())
+            .saturating_add(segments.saturating_mul(DIFF_SEGMENT_CU)),
+    );
+
+    match (state, diff) {
+        (Some((state_size, state)), Some((diff_size, diff))) => {
+            Some(if diff_size < state_size { diff } else { state })
+        }
+        (Some((_, plan)), None) | (None, Some((_, plan))) => Some(plan),
+        (None, None) => plan_streamed_commit(
+            validator,
+            delegated_account,
+            delegated_account_owner,
+            args,
+            max_tx_size,
+            max_cu,
+        ),
+    }
+}
+
+/// Streams the state into the commit state in chunks as large as the transactions allow
+fn plan_streamed_commit(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    delegated_account_owner: Pubkey,
+    args: CommitStateArgs,
+    max_tx_size: usize,
+    max_cu: u32,
+) -> Option<CommitPlan> {
+    let commit_ix = commit_state_from_buffer(
+        validator,
+        delegated_account,
+        delegated_account_owner,
+        commit_state_pda_from_delegated_account(&delegated_account),
+        CommitStateFromBufferArgs {
+            nonce: args.nonce,
+            lamports: args.lamports,
+            allow_undelegation: args.allow_undelegation,
+            er_block_hash: args.er_block_hash,
+            encoding: Encoding::Raw,
+        },
+    );
+    let commit_cu = data_cu(COMMIT_BASE_CU, args.data.len());
+    if transaction_size(&validator, &[commit_ix.clone()]) > max_tx_size || commit_cu > max_cu {
+        return None;
+    }
+
+    let empty_chunk_size = transaction_size(
+        &validator,
+        &[grow_commit_state(validator, delegated_account, vec![])],
+    );
+    // The compact-u16 length of the instruction data grows from 1 byte when empty to up
+    // to 3 bytes with the chunk
+    let chunk_len = max_tx_size.checked_sub(empty_chunk_size + 2)?;
+    if chunk_len == 0 {
+        return None;
+    }
+
+    let mut transactions = vec![];
+    let mut estimated_cu = commit_cu;
+    for chunk in args.data.chunks(chunk_len) {
+        let chunk_cu = data_cu(GROW_COMMIT_STATE_CU, chunk.len());
+        if chunk_cu > max_cu {
+            return None;
+        }
+        estimated_cu = estimated_cu.max(chunk_cu);
+        transactions.push(vec![grow_commit_state(
+            validator,
+            delegated_account,
+            chunk.to_vec(),
+        )]);
+    }
+    transactions.push(vec![commit_ix]);
+
+    Some(CommitPlan {
+        mode: CommitMode::StreamedState,
+        transactions,
+        signers: vec![validator],
+        estimated_cu,
+    })
+}
+
+/// The estimated compute units of an instruction processing the data
+fn data_cu(base_cu: u32, data_len: usize) -> u32 {
+    base_cu.saturating_add((data_len as u32).div_ceil(BYTES_PER_CU))
+}
+
+/// The size of a transaction of the instructions signed by the payer only
+pub fn transaction_size(payer: &Pubkey, ixs: &[Instruction]) -> usize {
+    signed_message_size(&Message::new(ixs, Some(payer)))
+}
+
+/// The size of a transaction of the message once signed
+pub(crate) fn signed_message_size(message: &Message) -> usize {
+    let signatures = message.header.num_required_signatures as usize;
+    // The signatures are prefixed by their compact-u16 count, a single byte here
+    1 + signatures * 64 + bincode::serialized_size(message).unwrap() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::commit_state_hash;
+
+    fn plan_for(original: &[u8], changed: Vec<u8>, max_cu: u32) -> Option<CommitPlan> {
+        plan_commit(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            original,
+            CommitStateArgs {
+                nonce: 1,
+                lamports: 100,
+                allow_undelegation: false,
+                data: changed,
+                er_block_hash: None,
+                base_state_hash: None,
+                escrow_spend: None,
+                encoding: Encoding::Raw,
+            },
+            MAX_TRANSACTION_SIZE,
+            max_cu,
+        )
+    }
+
+    #[test]
+    fn test_plan_commit_modes() {
+        // A small change of a large account is committed as a diff
+        let original = vec![0; 800];
+        let mut changed = original.clone();
+        changed[10] = 1;
+        let plan = plan_for(&original, changed, 200_000).unwrap();
+        assert_eq!(plan.mode, CommitMode::Diff);
+        assert_eq!(plan.transactions.len(), 1);
+
+        // A small account is committed as a full state
+        let plan = plan_for(&[0; 8], vec![1; 8], 200_000).unwrap();
+        assert_eq!(plan.mode, CommitMode::State);
+
+        // A large rewrite is streamed over several transactions
+        let changed = vec![1; 3_000];
+        let plan = plan_for(&[0; 3_000], changed, 200_000).unwrap();
+        assert_eq!(plan.mode, CommitMode::StreamedState);
+        assert!(plan.transactions.len() > 2);
+        assert!(plan
+            .transactions
+            .iter()
+            .all(|ixs| transaction_size(&plan.signers[0], ixs) <= MAX_TRANSACTION_SIZE));
+
+        // Nothing fits below the base cost of a commit
+        assert!(plan_for(&[0; 8], vec![1; 8], COMMIT_BASE_CU - 1).is_none());
+    }
+
+    #[test]
+    fn test_plan_commit_with_base_state_hash() {
+        let original = vec![0; 400];
+        let mut changed = original.clone();
+        changed[10] = 1;
+        let args = |data: Vec<u8>| CommitStateArgs {
+            nonce: 1,
+            lamports: 100,
+            allow_undelegation: false,
+            data,
+            er_block_hash: None,
+            base_state_hash: Some(commit_state_hash(&original)),
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        };
+        let plan = |data: Vec<u8>| {
+            plan_commit(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &original,
+                args(data),
+                MAX_TRANSACTION_SIZE,
+                200_000,
+            )
+        };
+
+        // The base state hash is only verified by a commit of the full state
+        assert_eq!(plan(changed).unwrap().mode, CommitMode::State);
+        assert!(plan(vec![1; 3_000]).is_none());
+    }
+}
@@ -0,0 +1,23 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::protocol_config_pda;
+
+/// Builds a propose protocol vault migration instruction.
+/// See [crate::processor::process_propose_protocol_vault_migration] for docs.
+pub fn propose_protocol_vault_migration(admin: Pubkey) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(protocol_config_pda(), false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::ProposeProtocolVaultMigration.to_vec(),
+    }
+}
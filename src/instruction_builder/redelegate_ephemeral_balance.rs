@@ -0,0 +1,57 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::RedelegateEphemeralBalanceArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    ephemeral_balance_pda_from_payer, validator_fees_vault_pda_from_validator,
+};
+
+/// Builds a redelegate ephemeral balance instruction.
+/// See [crate::processor::process_redelegate_ephemeral_balance] for docs.
+pub fn redelegate_ephemeral_balance(
+    validator: Pubkey,
+    pubkey: Pubkey,
+    index: u8,
+    new_validator: Pubkey,
+) -> Instruction {
+    let ephemeral_balance = ephemeral_balance_pda_from_payer(&pubkey, index);
+    let args = RedelegateEphemeralBalanceArgs { index };
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(validator, true),
+            AccountMeta::new_readonly(pubkey, true),
+            AccountMeta::new_readonly(ephemeral_balance, false),
+            AccountMeta::new(
+                delegation_record_pda_from_delegated_account(&ephemeral_balance),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                delegation_metadata_pda_from_delegated_account(&ephemeral_balance),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_state_pda_from_delegated_account(&ephemeral_balance),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_record_pda_from_delegated_account(&ephemeral_balance),
+                false,
+            ),
+            AccountMeta::new_readonly(new_validator, false),
+            AccountMeta::new_readonly(
+                validator_fees_vault_pda_from_validator(&new_validator),
+                false,
+            ),
+        ],
+        data: [
+            DlpDiscriminator::RedelegateEphemeralBalance.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
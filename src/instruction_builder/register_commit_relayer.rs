@@ -0,0 +1,28 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::RegisterCommitRelayerArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::validator_fees_vault_pda_from_validator;
+
+/// Builds a register commit relayer instruction.
+/// See [crate::processor::process_register_commit_relayer] for docs.
+pub fn register_commit_relayer(validator: Pubkey, relayer: Pubkey, approved: bool) -> Instruction {
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator);
+    let args = RegisterCommitRelayerArgs { relayer, approved };
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new(validator_fees_vault_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::RegisterCommitRelayer.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
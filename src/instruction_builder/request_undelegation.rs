@@ -0,0 +1,54 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account, program_config_from_program_id,
+    protocol_config_pda,
+};
+
+/// Builds a request undelegation instruction, to be signed by the rent payer of the
+/// delegated account. The owner program invoking it marks the delegated account as signer.
+/// See [crate::processor::process_request_undelegation] for docs.
+pub fn request_undelegation(requester: Pubkey, delegated_account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(requester, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(protocol_config_pda(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::RequestUndelegation.to_vec(),
+    }
+}
+
+/// Builds a request undelegation instruction forcing the settlement of a delegation which
+/// outlived the maximum delegation slots of the program config of its owner program, which
+/// any requester can sign.
+/// See [crate::processor::process_request_undelegation] for docs.
+pub fn request_expired_undelegation(
+    requester: Pubkey,
+    delegated_account: Pubkey,
+    owner_program: Pubkey,
+) -> Instruction {
+    let mut ix = request_undelegation(requester, delegated_account);
+    ix.accounts.extend([
+        AccountMeta::new_readonly(
+            delegation_record_pda_from_delegated_account(&delegated_account),
+            false,
+        ),
+        AccountMeta::new_readonly(program_config_from_program_id(&owner_program), false),
+    ]);
+    ix
+}
@@ -0,0 +1,54 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::ResyncProtocolStatsArgs;
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{delegation_record_pda_from_delegated_account, protocol_stats_pda};
+
+/// Builds a resync protocol stats instruction from the delegated accounts.
+/// See [crate::processor::process_resync_protocol_stats] for docs.
+pub fn resync_protocol_stats(
+    admin: Pubkey,
+    delegated_accounts: &[Pubkey],
+    reset: bool,
+) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let mut accounts = vec![
+        AccountMeta::new(admin, true),
+        AccountMeta::new(protocol_stats_pda(), false),
+        AccountMeta::new_readonly(delegation_program_data, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(delegated_accounts.iter().map(|delegated_account| {
+        AccountMeta::new_readonly(
+            delegation_record_pda_from_delegated_account(delegated_account),
+            false,
+        )
+    }));
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: [
+            DlpDiscriminator::ResyncProtocolStats.to_vec(),
+            to_vec(&ResyncProtocolStatsArgs { reset }).unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Pass the protocol stats to a delegate, finalize, crank finalize, undelegate, fund escrow
+/// from delegated, split delegation or import delegation package instruction, so that it
+/// maintains the total value locked. The feature gates PDA of a gated instruction stays its last account.
+pub fn with_protocol_stats(mut ix: Instruction) -> Instruction {
+    let index = match ix.accounts.last() {
+        Some(meta) if meta.pubkey == FEATURE_GATES_PDA => ix.accounts.len() - 1,
+        _ => ix.accounts.len(),
+    };
+    ix.accounts
+        .insert(index, AccountMeta::new(protocol_stats_pda(), false));
+    ix
+}
@@ -0,0 +1,27 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetAuthorityGrantArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::authority_delegation_chain_pda_from_delegator;
+
+/// Builds a set authority grant instruction.
+/// See [crate::processor::process_set_authority_grant] for docs.
+pub fn set_authority_grant(delegator: Pubkey, args: SetAuthorityGrantArgs) -> Instruction {
+    let authority_delegation_chain_pda = authority_delegation_chain_pda_from_delegator(&delegator);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(delegator, true),
+            AccountMeta::new(authority_delegation_chain_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetAuthorityGrant.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,31 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetCallHandlerPermissionsArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{call_handler_permissions_pda_from_escrow, ephemeral_balance_pda_from_payer};
+
+/// Builds a set call handler permissions instruction.
+/// See [crate::processor::process_set_call_handler_permissions] for docs.
+pub fn set_call_handler_permissions(
+    escrow_authority: Pubkey,
+    args: SetCallHandlerPermissionsArgs,
+) -> Instruction {
+    let escrow = ephemeral_balance_pda_from_payer(&escrow_authority, args.escrow_index);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(escrow_authority, true),
+            AccountMeta::new_readonly(escrow, false),
+            AccountMeta::new(call_handler_permissions_pda_from_escrow(&escrow), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetCallHandlerPermissions.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
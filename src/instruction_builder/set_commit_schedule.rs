@@ -0,0 +1,71 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetCommitScheduleArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_schedule_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    ephemeral_balance_pda_from_payer,
+};
+
+/// Builds a set commit schedule instruction.
+/// See [crate::processor::process_set_commit_schedule] for docs.
+pub fn set_commit_schedule(
+    rent_payer: Pubkey,
+    delegated_account: Pubkey,
+    args: SetCommitScheduleArgs,
+) -> Instruction {
+    let escrow = ephemeral_balance_pda_from_payer(&rent_payer, args.escrow_index);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(rent_payer, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                commit_schedule_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(escrow, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetCommitSchedule.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Appends the commit schedule of the delegated account and its escrow to a commit
+/// instruction, required once the commits of the account are scheduled. The validator
+/// is made writable to be refunded by the escrow.
+/// See [crate::processor::fast::process_commit_state] for docs.
+pub fn with_commit_schedule(
+    mut ix: Instruction,
+    delegated_account: Pubkey,
+    escrow: Pubkey,
+) -> Instruction {
+    ix.accounts[0].is_writable = true;
+    ix.accounts.extend([
+        AccountMeta::new(
+            commit_schedule_pda_from_delegated_account(&delegated_account),
+            false,
+        ),
+        AccountMeta::new(escrow, false),
+    ]);
+    ix
+}
+
+/// Appends the escrow which funded a commit to a finalize or crank finalize instruction,
+/// so that it is refunded the rent of the commit PDAs.
+/// See [crate::processor::fast::process_finalize] for docs.
+pub fn with_commit_escrow(mut ix: Instruction, escrow: Pubkey) -> Instruction {
+    ix.accounts.push(AccountMeta::new(escrow, false));
+    ix
+}
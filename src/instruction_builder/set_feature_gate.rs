@@ -0,0 +1,33 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetFeatureGateArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::feature_gates_pda;
+
+/// Builds a set feature gate instruction.
+/// See [crate::processor::process_set_feature_gate] for docs.
+pub fn set_feature_gate(admin: Pubkey, discriminator: u8, enabled: bool) -> Instruction {
+    let args = SetFeatureGateArgs {
+        discriminator,
+        enabled,
+    };
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(feature_gates_pda(), false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetFeatureGate.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,42 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetProgramAllowedDataLensArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_config_from_program_id;
+
+/// Set the data lengths allowed for the accounts of a program
+///
+/// See [crate::processor::process_set_program_allowed_data_lens] for docs.
+pub fn set_program_allowed_data_lens(
+    authority: Pubkey,
+    program: Pubkey,
+    allowed_data_lens: Vec<u32>,
+) -> Instruction {
+    let args = SetProgramAllowedDataLensArgs { allowed_data_lens };
+    let program_data =
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let program_config_pda = program_config_from_program_id(&program);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(program, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new(program_config_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetProgramAllowedDataLens.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
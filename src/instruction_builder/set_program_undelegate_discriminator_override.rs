@@ -0,0 +1,44 @@
+use borsh::to_vec;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetProgramUndelegateDiscriminatorOverrideArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_config_from_program_id;
+
+/// Set whether the delegations of the accounts of the program can override the discriminator
+/// of its external undelegate instruction
+///
+/// See [crate::processor::process_set_program_undelegate_discriminator_override] for docs.
+pub fn set_program_undelegate_discriminator_override(
+    authority: Pubkey,
+    program: Pubkey,
+    allow_undelegate_discriminator_override: bool,
+) -> Instruction {
+    let args = SetProgramUndelegateDiscriminatorOverrideArgs {
+        allow_undelegate_discriminator_override,
+    };
+    let program_data =
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let program_config_pda = program_config_from_program_id(&program);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(program, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new(program_config_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetProgramUndelegateDiscriminatorOverride.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,43 @@
+use borsh::to_vec;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetProgramValidateDelegationsArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_config_from_program_id;
+
+/// Set whether the program validates the delegations of its accounts
+///
+/// See [crate::processor::process_set_program_validate_delegations] for docs.
+pub fn set_program_validate_delegations(
+    authority: Pubkey,
+    program: Pubkey,
+    validate_delegations: bool,
+) -> Instruction {
+    let args = SetProgramValidateDelegationsArgs {
+        validate_delegations,
+    };
+    let program_data =
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let program_config_pda = program_config_from_program_id(&program);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(program, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new(program_config_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetProgramValidateDelegations.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
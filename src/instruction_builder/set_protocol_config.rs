@@ -0,0 +1,29 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetProtocolConfigArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::protocol_config_pda;
+
+/// Builds a set protocol config instruction.
+/// See [crate::processor::process_set_protocol_config] for docs.
+pub fn set_protocol_config(admin: Pubkey, args: SetProtocolConfigArgs) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(protocol_config_pda(), false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetProtocolConfig.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
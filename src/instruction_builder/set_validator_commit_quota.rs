@@ -0,0 +1,48 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetValidatorCommitQuotaArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::validator_fees_vault_pda_from_validator;
+
+/// Builds a set validator commit quota instruction.
+/// See [crate::processor::process_set_validator_commit_quota] for docs.
+pub fn set_validator_commit_quota(
+    admin: Pubkey,
+    validator: Pubkey,
+    args: SetValidatorCommitQuotaArgs,
+) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(validator, false),
+            AccountMeta::new(validator_fees_vault_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetValidatorCommitQuota.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Marks the validator fees vault of a commit instruction writable, which is required
+/// once the validator has a commit quota, see [crate::state::CommitQuota].
+/// See [crate::processor::fast::process_commit_state] for docs.
+pub fn with_commit_quota(mut ix: Instruction, validator: Pubkey) -> Instruction {
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator);
+    for account in &mut ix.accounts {
+        if account.pubkey == validator_fees_vault_pda {
+            account.is_writable = true;
+        }
+    }
+    ix
+}
@@ -0,0 +1,29 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetValidatorInfoArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{validator_fees_vault_pda_from_validator, validator_info_pda_from_validator};
+
+/// Builds a set validator info instruction.
+/// See [crate::processor::process_set_validator_info] for docs.
+pub fn set_validator_info(validator: Pubkey, args: SetValidatorInfoArgs) -> Instruction {
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator);
+    let validator_info_pda = validator_info_pda_from_validator(&validator);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new_readonly(validator_fees_vault_pda, false),
+            AccountMeta::new(validator_info_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetValidatorInfo.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,29 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{bpf_loader_upgradeable, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SetVersionArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_version_pda;
+
+/// Builds a set version instruction.
+/// See [crate::processor::process_set_version] for docs.
+pub fn set_version(admin: Pubkey, args: SetVersionArgs) -> Instruction {
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(admin, true),
+            AccountMeta::new(program_version_pda(), false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::SetVersion.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
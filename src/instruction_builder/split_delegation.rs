@@ -0,0 +1,55 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::SplitDelegationArgs;
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+};
+
+/// Builds a split delegation instruction.
+/// Pass the protocol stats with [crate::instruction_builder::with_protocol_stats].
+/// See [crate::processor::process_split_delegation] for docs.
+pub fn split_delegation(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    new_delegated_account: Pubkey,
+    owner_program: Pubkey,
+    args: SplitDelegationArgs,
+) -> Instruction {
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    let delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&delegated_account);
+    let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
+    let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+    let new_delegation_record_pda =
+        delegation_record_pda_from_delegated_account(&new_delegated_account);
+    let new_delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&new_delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new(delegated_account, false),
+            AccountMeta::new_readonly(delegation_record_pda, false),
+            AccountMeta::new_readonly(delegation_metadata_pda, false),
+            AccountMeta::new_readonly(commit_state_pda, false),
+            AccountMeta::new_readonly(commit_record_pda, false),
+            AccountMeta::new(new_delegated_account, true),
+            AccountMeta::new(new_delegation_record_pda, false),
+            AccountMeta::new(new_delegation_metadata_pda, false),
+            AccountMeta::new_readonly(owner_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(FEATURE_GATES_PDA, false),
+        ],
+        data: [
+            DlpDiscriminator::SplitDelegation.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
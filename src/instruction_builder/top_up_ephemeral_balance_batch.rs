@@ -0,0 +1,42 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::{
+    EphemeralBalanceIndex, TopUpEphemeralBalanceBatchArgs, TopUpEphemeralBalanceBatchEntry,
+};
+use crate::discriminator::DlpDiscriminator;
+
+/// Builds a top-up of the ephemeral balances of the `(pubkey, index, amount)` escrows,
+/// all funded by the payer.
+/// See [crate::processor::process_top_up_ephemeral_balance_batch] for docs.
+pub fn top_up_ephemeral_balance_batch(
+    payer: Pubkey,
+    escrows: &[(Pubkey, EphemeralBalanceIndex, u64)],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    let mut entries = Vec::with_capacity(escrows.len());
+    for (pubkey, index, amount) in escrows {
+        accounts.push(AccountMeta::new_readonly(*pubkey, false));
+        accounts.push(AccountMeta::new(index.pda(pubkey), false));
+        entries.push(TopUpEphemeralBalanceBatchEntry {
+            index: *index,
+            amount: *amount,
+        });
+    }
+    let args = TopUpEphemeralBalanceBatchArgs { entries };
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: [
+            DlpDiscriminator::TopUpEphemeralBalanceBatch.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,42 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::TopUpProgramEphemeralBalanceArgs;
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_ephemeral_balance_pda_from_payer;
+
+/// Builds a top-up program ephemeral balance instruction.
+/// See [crate::processor::process_top_up_program_ephemeral_balance] for docs.
+pub fn top_up_program_ephemeral_balance(
+    payer: Pubkey,
+    pubkey: Pubkey,
+    program_id: Pubkey,
+    amount: Option<u64>,
+    index: Option<u8>,
+) -> Instruction {
+    let args = TopUpProgramEphemeralBalanceArgs {
+        amount: amount.unwrap_or(10000),
+        program_id,
+        index: index.unwrap_or(0),
+    };
+    let ephemeral_balance_pda =
+        program_ephemeral_balance_pda_from_payer(&pubkey, &program_id, args.index);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(pubkey, false),
+            AccountMeta::new(ephemeral_balance_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(FEATURE_GATES_PDA, false),
+        ],
+        data: [
+            DlpDiscriminator::TopUpProgramEphemeralBalance.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
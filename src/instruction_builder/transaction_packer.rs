@@ -0,0 +1,232 @@
+use alloc::vec::Vec;
+use solana_program::instruction::Instruction;
+use solana_program::message::Message;
+use solana_program::pubkey::Pubkey;
+
+use crate::instruction_builder::plan_commit::signed_message_size;
+
+/// A transaction packed by a [TransactionPacker], ready to be signed by the payer
+#[derive(Clone, Debug)]
+pub struct PackedTransaction {
+    /// The message of the transaction, whose accounts are deduplicated and ordered by
+    /// [Message::new]: the payer first, then the signers, the writable and the readonly
+    /// accounts, each in the order of their first appearance in the instructions
+    pub message: Message,
+    /// The size of the signed transaction
+    pub size: usize,
+    /// The estimated compute units of the instructions of the transaction
+    pub estimated_cu: u32,
+}
+
+/// Packs planned commit and finalize instructions into as few transactions as possible,
+/// e.g. the instructions of the single-transaction [crate::instruction_builder::CommitPlan]s
+/// of a commit bundle.
+///
+/// The accounts shared by the instructions (the validator, the fees vaults, the program
+/// configs, ...) are counted once per transaction. The instructions keep their order, a
+/// finalize staying after the commit it finalizes, so that filling every transaction
+/// before starting the next one is the minimal number of transactions. Given the same
+/// instructions, the packed messages are the same byte for byte.
+#[derive(Clone, Debug)]
+pub struct TransactionPacker {
+    payer: Pubkey,
+    max_tx_size: usize,
+    max_cu: u32,
+    instructions: Vec<(Instruction, u32)>,
+}
+
+impl TransactionPacker {
+    /// A packer of transactions paid by `payer` fitting in `max_tx_size` bytes and
+    /// `max_cu` compute units, see [crate::instruction_builder::MAX_TRANSACTION_SIZE]
+    pub fn new(payer: Pubkey, max_tx_size: usize, max_cu: u32) -> Self {
+        Self {
+            payer,
+            max_tx_size,
+            max_cu,
+            instructions: vec![],
+        }
+    }
+
+    /// Add an instruction with its estimated compute units, see
+    /// [crate::instruction_builder::COMMIT_BASE_CU]
+    pub fn push(&mut self, ix: Instruction, estimated_cu: u32) -> &mut Self {
+        self.instructions.push((ix, estimated_cu));
+        self
+    }
+
+    /// The estimated size of the transactions and compute units of all the instructions
+    pub fn estimate(&self) -> Option<(usize, u32)> {
+        let transactions = self.pack()?;
+        Some(transactions.iter().fold((0, 0), |(size, cu), tx| {
+            (size + tx.size, cu.saturating_add(tx.estimated_cu))
+        }))
+    }
+
+    /// Split the instructions into transactions respecting the limits.
+    /// Returns None if an instruction does not fit in a transaction on its own.
+    pub fn pack(&self) -> Option<Vec<PackedTransaction>> {
+        let mut transactions = vec![];
+        let mut ixs: Vec<Instruction> = vec![];
+        let mut estimated_cu = 0u32;
+        for (ix, ix_cu) in &self.instructions {
+            ixs.push(ix.clone());
+            estimated_cu = estimated_cu.saturating_add(*ix_cu);
+            if self.transaction(&ixs, estimated_cu).is_some() {
+                continue;
+            }
+
+            // Close the transaction without the instruction and start the next one with it
+            let ix = ixs.pop()?;
+            if ixs.is_empty() {
+                return None;
+            }
+            let full_cu = estimated_cu - ix_cu;
+            transactions.push(self.transaction(&ixs, full_cu)?);
+            ixs = vec![ix];
+            estimated_cu = *ix_cu;
+            self.transaction(&ixs, estimated_cu)?;
+        }
+        if !ixs.is_empty() {
+            transactions.push(self.transaction(&ixs, estimated_cu)?);
+        }
+        Some(transactions)
+    }
+
+    /// The transaction of the instructions, if it respects the limits
+    fn transaction(&self, ixs: &[Instruction], estimated_cu: u32) -> Option<PackedTransaction> {
+        let message = Message::new(ixs, Some(&self.payer));
+        let size = signed_message_size(&message);
+        (size <= self.max_tx_size && estimated_cu <= self.max_cu).then_some(PackedTransaction {
+            message,
+            size,
+            estimated_cu,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::{CommitStateArgs, Encoding};
+    use crate::instruction_builder::{
+        commit_state, finalize, transaction_size, COMMIT_BASE_CU, MAX_TRANSACTION_SIZE,
+    };
+
+    fn commit(validator: Pubkey, owner: Pubkey, delegated_account: Pubkey) -> Instruction {
+        commit_state(
+            validator,
+            delegated_account,
+            owner,
+            CommitStateArgs {
+                nonce: 1,
+                lamports: 100,
+                allow_undelegation: false,
+                data: vec![1; 8],
+                er_block_hash: None,
+                base_state_hash: None,
+                escrow_spend: None,
+                encoding: Encoding::Raw,
+            },
+        )
+    }
+
+    #[test]
+    fn test_pack_commit_bundle() {
+        let validator = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let accounts: Vec<Pubkey> = (0..12).map(|_| Pubkey::new_unique()).collect();
+
+        let mut packer = TransactionPacker::new(validator, MAX_TRANSACTION_SIZE, 1_400_000);
+        for account in &accounts {
+            packer.push(commit(validator, owner, *account), COMMIT_BASE_CU);
+            packer.push(finalize(validator, *account), COMMIT_BASE_CU);
+        }
+        let transactions = packer.pack().unwrap();
+
+        // The shared accounts are deduplicated, several commits fitting in a transaction
+        assert!(transactions.len() > 1);
+        assert!(transactions.len() < accounts.len());
+        let mut packed = 0;
+        for tx in &transactions {
+            assert!(tx.size <= MAX_TRANSACTION_SIZE);
+            assert_eq!(tx.message.account_keys[0], validator);
+            let keys = &tx.message.account_keys;
+            assert!(keys
+                .iter()
+                .enumerate()
+                .all(|(i, key)| !keys[..i].contains(key)));
+            packed += tx.message.instructions.len();
+        }
+        assert_eq!(packed, 2 * accounts.len());
+
+        // Each transaction is full, the first instruction of the next one not fitting in it
+        let mut start = 0;
+        for pair in transactions.windows(2) {
+            let end = start + pair[0].message.instructions.len();
+            let ixs: Vec<Instruction> = packer.instructions[start..=end]
+                .iter()
+                .map(|(ix, _)| ix.clone())
+                .collect();
+            assert!(transaction_size(&validator, &ixs) > MAX_TRANSACTION_SIZE);
+            start = end;
+        }
+
+        // Packing is deterministic
+        let again = packer.pack().unwrap();
+        assert!(transactions
+            .iter()
+            .zip(&again)
+            .all(|(a, b)| a.message == b.message));
+        let (size, cu) = packer.estimate().unwrap();
+        assert_eq!(size, transactions.iter().map(|tx| tx.size).sum::<usize>());
+        assert_eq!(cu, 2 * accounts.len() as u32 * COMMIT_BASE_CU);
+    }
+
+    #[test]
+    fn test_pack_compute_units_limit() {
+        let validator = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut packer =
+            TransactionPacker::new(validator, MAX_TRANSACTION_SIZE, 2 * COMMIT_BASE_CU);
+        for _ in 0..3 {
+            packer.push(
+                commit(validator, owner, Pubkey::new_unique()),
+                COMMIT_BASE_CU,
+            );
+        }
+        let transactions = packer.pack().unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].estimated_cu, 2 * COMMIT_BASE_CU);
+        assert_eq!(transactions[1].estimated_cu, COMMIT_BASE_CU);
+
+        // An instruction exceeding the limits on its own cannot be packed
+        packer.push(
+            commit(validator, owner, Pubkey::new_unique()),
+            3 * COMMIT_BASE_CU,
+        );
+        assert!(packer.pack().is_none());
+        assert!(
+            TransactionPacker::new(validator, MAX_TRANSACTION_SIZE, COMMIT_BASE_CU)
+                .push(
+                    commit_state(
+                        validator,
+                        Pubkey::new_unique(),
+                        owner,
+                        CommitStateArgs {
+                            nonce: 1,
+                            lamports: 100,
+                            allow_undelegation: false,
+                            data: vec![1; MAX_TRANSACTION_SIZE],
+                            er_block_hash: None,
+                            base_state_hash: None,
+                            escrow_spend: None,
+                            encoding: Encoding::Raw,
+                        },
+                    ),
+                    COMMIT_BASE_CU,
+                )
+                .pack()
+                .is_none()
+        );
+    }
+}
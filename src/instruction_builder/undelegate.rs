@@ -1,12 +1,16 @@
+use borsh::to_vec;
 use solana_program::instruction::Instruction;
 use solana_program::system_program;
 use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
 
+use crate::args::{UndelegateArgs, UndelegateMode};
 use crate::discriminator::DlpDiscriminator;
 use crate::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
-    fees_vault_pda, undelegate_buffer_pda_from_delegated_account,
+    fee_exemption_pda_from_delegated_account, fees_vault_pda,
+    parked_undelegation_pda_from_delegated_account, program_config_from_program_id,
+    protocol_config_pda, undelegate_buffer_pda_from_delegated_account,
     validator_fees_vault_pda_from_validator,
 };
 
@@ -42,7 +46,35 @@ pub fn undelegate(
             AccountMeta::new(fees_vault_pda, false),
             AccountMeta::new(validator_fees_vault_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(protocol_config_pda(), false),
+            AccountMeta::new_readonly(
+                fee_exemption_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(program_config_from_program_id(&owner_program), false),
         ],
         data: DlpDiscriminator::Undelegate.to_vec(),
     }
 }
+
+/// Builds an undelegate instruction parking the state of the account in its parked
+/// undelegation PDA instead of the CPI to its owner program, signed by the rent payer.
+/// See [crate::args::UndelegateMode::ToBuffer] for docs.
+pub fn undelegate_to_buffer(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    owner_program: Pubkey,
+    rent_payer: Pubkey,
+) -> Instruction {
+    let mut ix = undelegate(validator, delegated_account, owner_program, rent_payer);
+    ix.accounts[3] = AccountMeta::new(
+        parked_undelegation_pda_from_delegated_account(&delegated_account),
+        false,
+    );
+    ix.accounts[8] = AccountMeta::new(rent_payer, true);
+    let args = UndelegateArgs {
+        mode: UndelegateMode::ToBuffer,
+    };
+    ix.data.extend(to_vec(&args).unwrap());
+    ix
+}
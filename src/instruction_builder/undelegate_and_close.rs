@@ -0,0 +1,42 @@
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fees_vault_pda, validator_fees_vault_pda_from_validator,
+};
+
+/// Builds an undelegate and close instruction.
+/// See [crate::processor::fast::process_undelegate_and_close] for docs.
+pub fn undelegate_and_close(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    close_destination: Pubkey,
+    rent_reimbursement: Pubkey,
+) -> Instruction {
+    let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
+    let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&delegated_account);
+    let delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&delegated_account);
+    let fees_vault_pda = fees_vault_pda();
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new(delegated_account, false),
+            AccountMeta::new_readonly(commit_state_pda, false),
+            AccountMeta::new_readonly(commit_record_pda, false),
+            AccountMeta::new(delegation_record_pda, false),
+            AccountMeta::new(delegation_metadata_pda, false),
+            AccountMeta::new(close_destination, false),
+            AccountMeta::new(rent_reimbursement, false),
+            AccountMeta::new(fees_vault_pda, false),
+            AccountMeta::new(validator_fees_vault_pda, false),
+        ],
+        data: DlpDiscriminator::UndelegateAndClose.to_vec(),
+    }
+}
@@ -0,0 +1,55 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    program_config_from_program_id, undelegate_buffer_pda_from_delegated_account,
+    undelegate_progress_pda_from_delegated_account,
+};
+
+/// Builds an undelegate stage 1 instruction.
+/// See [crate::processor::fast::process_undelegate_stage1] for docs.
+pub fn undelegate_stage1(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    owner_program: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new(delegated_account, false),
+            AccountMeta::new_readonly(owner_program, false),
+            AccountMeta::new(
+                undelegate_buffer_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_state_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                undelegate_progress_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(program_config_from_program_id(&owner_program), false),
+        ],
+        data: DlpDiscriminator::UndelegateStage1.to_vec(),
+    }
+}
@@ -0,0 +1,60 @@
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fee_exemption_pda_from_delegated_account, fees_vault_pda,
+    undelegate_progress_pda_from_delegated_account, validator_fees_vault_pda_from_validator,
+};
+
+/// Builds an undelegate stage 2 instruction.
+/// See [crate::processor::fast::process_undelegate_stage2] for docs.
+pub fn undelegate_stage2(
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    rent_reimbursement: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(
+                undelegate_progress_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new(rent_reimbursement, false),
+            AccountMeta::new(fees_vault_pda(), false),
+            AccountMeta::new(validator_fees_vault_pda_from_validator(&validator), false),
+            AccountMeta::new_readonly(
+                fee_exemption_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+        ],
+        data: DlpDiscriminator::UndelegateStage2.to_vec(),
+    }
+}
+
+/// Builds an undelegate stage 2 instruction signed by another payer than the validator which
+/// ran the first stage, once the timeout of the second stage elapsed, see
+/// [crate::consts::UNDELEGATE_STAGE2_TIMEOUT_SLOTS].
+/// See [crate::processor::fast::process_undelegate_stage2] for docs.
+pub fn undelegate_stage2_after_timeout(
+    payer: Pubkey,
+    validator: Pubkey,
+    delegated_account: Pubkey,
+    rent_reimbursement: Pubkey,
+) -> Instruction {
+    let mut ix = undelegate_stage2(validator, delegated_account, rent_reimbursement);
+    ix.accounts[0] = AccountMeta::new(payer, true);
+    ix
+}
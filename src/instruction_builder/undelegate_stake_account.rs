@@ -0,0 +1,49 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::stake;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey, sysvar};
+
+use crate::args::UndelegateStakeAccountArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    stake_authority_pda_from_stake_account,
+};
+
+/// Builds an undelegate stake account instruction, signed by the validator or, once the
+/// commit timeout of the delegation elapsed, by the stake authority.
+/// See [crate::processor::process_undelegate_stake_account] for docs.
+pub fn undelegate_stake_account(
+    signer: Pubkey,
+    stake_account: Pubkey,
+    authority: Pubkey,
+    args: UndelegateStakeAccountArgs,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(signer, true),
+            AccountMeta::new(stake_account, false),
+            AccountMeta::new_readonly(
+                stake_authority_pda_from_stake_account(&stake_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_record_pda_from_delegated_account(&stake_account),
+                false,
+            ),
+            AccountMeta::new(
+                delegation_metadata_pda_from_delegated_account(&stake_account),
+                false,
+            ),
+            AccountMeta::new(authority, false),
+            AccountMeta::new_readonly(stake::program::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::UndelegateStakeAccount.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,44 @@
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::UpdateDelegationAuthorityArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account,
+};
+
+/// Builds an update delegation authority instruction, to be signed by the authority of the
+/// delegation. The owner program invoking it marks the delegated account as signer.
+/// See [crate::processor::process_update_delegation_authority] for docs.
+pub fn update_delegation_authority(
+    authority: Pubkey,
+    delegated_account: Pubkey,
+    args: UpdateDelegationAuthorityArgs,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                commit_record_pda_from_delegated_account(&delegated_account),
+                false,
+            ),
+        ],
+        data: [
+            DlpDiscriminator::UpdateDelegationAuthority.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,23 @@
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{validator_fees_vault_pda_from_validator, validator_liveness_pda_from_validator};
+
+/// Builds a validator heartbeat instruction.
+/// See [crate::processor::process_validator_heartbeat] for docs.
+pub fn validator_heartbeat(validator: Pubkey) -> Instruction {
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator);
+    let validator_liveness_pda = validator_liveness_pda_from_validator(&validator);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(validator, true),
+            AccountMeta::new_readonly(validator_fees_vault_pda, false),
+            AccountMeta::new(validator_liveness_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: DlpDiscriminator::ValidatorHeartbeat.to_vec(),
+    }
+}
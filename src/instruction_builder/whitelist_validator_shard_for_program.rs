@@ -0,0 +1,46 @@
+use borsh::to_vec;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::WhitelistValidatorForProgramArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::{program_config_from_program_id, validator_whitelist_shard_pda_from_program_id};
+
+/// Whitelist validator for program in its own shard
+///
+/// See [crate::processor::process_whitelist_validator_shard_for_program] for docs.
+pub fn whitelist_validator_shard_for_program(
+    authority: Pubkey,
+    validator_identity: Pubkey,
+    program: Pubkey,
+    insert: bool,
+) -> Instruction {
+    let args = WhitelistValidatorForProgramArgs { insert };
+    let program_data =
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(validator_identity, false),
+            AccountMeta::new_readonly(program, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new(program_config_from_program_id(&program), false),
+            AccountMeta::new(
+                validator_whitelist_shard_pda_from_program_id(&program, &validator_identity),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::WhitelistValidatorShardForProgram.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
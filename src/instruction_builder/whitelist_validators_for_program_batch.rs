@@ -0,0 +1,43 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::WhitelistValidatorsForProgramBatchArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::program_config_from_program_id;
+
+/// Insert and remove validators of the whitelist of a program
+///
+/// See [crate::processor::process_whitelist_validators_for_program_batch] for docs.
+pub fn whitelist_validators_for_program_batch(
+    authority: Pubkey,
+    program: Pubkey,
+    insert: Vec<Pubkey>,
+    remove: Vec<Pubkey>,
+) -> Instruction {
+    let args = WhitelistValidatorsForProgramBatchArgs { insert, remove };
+    let program_data =
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let delegation_program_data =
+        Pubkey::find_program_address(&[crate::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+    let program_config_pda = program_config_from_program_id(&program);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(program, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(delegation_program_data, false),
+            AccountMeta::new(program_config_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::WhitelistValidatorsForProgramBatch.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
@@ -0,0 +1,36 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::WriteCommitBufferArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::commit_buffer_pda_from_delegated_account_and_authority;
+
+/// Builds a write commit buffer instruction.
+/// See [crate::processor::process_write_commit_buffer] for docs.
+pub fn write_commit_buffer(
+    authority: Pubkey,
+    delegated_account: Pubkey,
+    offset: u32,
+    data: Vec<u8>,
+) -> Instruction {
+    let args = WriteCommitBufferArgs { offset, data };
+    let commit_buffer_pda =
+        commit_buffer_pda_from_delegated_account_and_authority(&delegated_account, &authority);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(commit_buffer_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::WriteCommitBuffer.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
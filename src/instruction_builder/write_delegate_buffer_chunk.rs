@@ -0,0 +1,35 @@
+use alloc::vec::Vec;
+use borsh::to_vec;
+use solana_program::instruction::Instruction;
+use solana_program::system_program;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::args::WriteDelegateBufferChunkArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::pda::staged_delegate_buffer_pda_from_delegated_account;
+
+/// Builds a write delegate buffer chunk instruction.
+/// See [crate::processor::process_write_delegate_buffer_chunk] for docs.
+pub fn write_delegate_buffer_chunk(
+    authority: Pubkey,
+    delegated_account: Pubkey,
+    offset: u32,
+    data: Vec<u8>,
+) -> Instruction {
+    let args = WriteDelegateBufferChunkArgs { offset, data };
+    let staged_buffer_pda = staged_delegate_buffer_pda_from_delegated_account(&delegated_account);
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(delegated_account, false),
+            AccountMeta::new(staged_buffer_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: [
+            DlpDiscriminator::WriteDelegateBufferChunk.to_vec(),
+            to_vec(&args).unwrap(),
+        ]
+        .concat(),
+    }
+}
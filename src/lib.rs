@@ -1,4 +1,5 @@
 #![allow(unexpected_cfgs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 // Exactly one of `sdk` or `program` must be enabled
 #[cfg(all(feature = "sdk", feature = "program"))]
@@ -9,6 +10,13 @@ compile_error!(
     "Enable either `program` (default) or `sdk`. Building with neither is not supported."
 );
 
+// The minimal build mutes the logs, which the logging features add to
+#[cfg(all(
+    feature = "minimal",
+    any(feature = "logging", feature = "log-cost", feature = "trace")
+))]
+compile_error!("Feature `minimal` mutes the logs and excludes `logging`, `log-cost` and `trace`.");
+
 #[cfg(not(feature = "sdk"))]
 use {
     crate::discriminator::DlpDiscriminator,
@@ -20,26 +28,81 @@ use {
 
 use solana_program::declare_id;
 
+// The crate only uses core and alloc without the std feature, see Cargo.toml
+#[macro_use]
+extern crate alloc;
+
 #[cfg(feature = "logging")]
-use solana_program::msg;
+use crate::log::msg;
 
+#[cfg(not(feature = "sdk"))]
+pub mod accounts_spec;
 pub mod args;
 pub mod consts;
+#[cfg(feature = "std")]
+pub mod cu_metrics;
 #[cfg(not(feature = "sdk"))]
-mod discriminator;
+pub mod discriminator;
 #[cfg(not(feature = "sdk"))]
 pub mod error;
+pub mod events;
+#[cfg(not(feature = "sdk"))]
+pub mod fast_instruction_builder;
 #[cfg(not(feature = "sdk"))]
 pub mod instruction_builder;
+mod log;
 pub mod pda;
+pub mod prelude;
+// The program-side code (state, diff and processors) returns typed errors instead of
+// panicking: a panic burns the compute units of the transaction and only logs its location
+#[cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing
+    )
+)]
 pub mod state;
+pub mod trace;
 
-#[cfg(not(feature = "sdk"))]
+#[cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::std_instead_of_core,
+        clippy::std_instead_of_alloc
+    )
+)]
+pub mod compression;
+#[cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::std_instead_of_core,
+        clippy::std_instead_of_alloc
+    )
+)]
 mod diff;
 #[cfg(not(feature = "sdk"))]
+#[cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::indexing_slicing
+    )
+)]
 mod processor;
 
-#[cfg(not(feature = "sdk"))]
 pub use diff::*;
 
 // re-export
@@ -52,11 +115,20 @@ mod cu;
 #[cfg(not(feature = "no-entrypoint"))]
 mod entrypoint;
 
+// The program id is selected by feature for the clusters where the program is deployed at
+// another id. Both ids below must be switched together.
+#[cfg(not(feature = "id-devtest"))]
 declare_id!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+#[cfg(feature = "id-devtest")]
+declare_id!("GUnDsvBhB7aCeZGBW3nY6fHqEoc698VsH9XV3jSXxzop");
 
 #[cfg(not(feature = "sdk"))]
+#[doc(hidden)]
 pub mod fast {
+    #[cfg(not(feature = "id-devtest"))]
     pinocchio_pubkey::declare_id!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+    #[cfg(feature = "id-devtest")]
+    pinocchio_pubkey::declare_id!("GUnDsvBhB7aCeZGBW3nY6fHqEoc698VsH9XV3jSXxzop");
 }
 
 #[cfg(feature = "solana-security-txt")]
@@ -70,23 +142,22 @@ solana_security_txt::security_txt! {
 }
 
 #[cfg(not(feature = "sdk"))]
+#[doc(hidden)]
 pub fn fast_process_instruction(
     program_id: &pinocchio::pubkey::Pubkey,
     accounts: &[pinocchio::account_info::AccountInfo],
     data: &[u8],
 ) -> Option<pinocchio::ProgramResult> {
-    if data.len() < 8 {
+    let Some((discriminator_bytes, data)) = data.split_first_chunk::<8>() else {
         return Some(Err(
             pinocchio::program_error::ProgramError::InvalidInstructionData,
         ));
-    }
-
-    let (discriminator_bytes, data) = data.split_at(8);
+    };
 
     let discriminator = match DlpDiscriminator::try_from(discriminator_bytes[0]) {
         Ok(discriminator) => discriminator,
         Err(_) => {
-            pinocchio_log::log!("Failed to read and parse discriminator");
+            log::log!("Failed to read and parse discriminator");
             return Some(Err(
                 pinocchio::program_error::ProgramError::InvalidInstructionData,
             ));
@@ -106,33 +177,67 @@ pub fn fast_process_instruction(
         DlpDiscriminator::CommitStateFromBuffer => Some(
             processor::fast::process_commit_state_from_buffer(program_id, accounts, data),
         ),
-        DlpDiscriminator::CommitDiff => Some(processor::fast::process_commit_diff(
+        DlpDiscriminator::CommitStateRoot => Some(processor::fast::process_commit_state_root(
             program_id, accounts, data,
         )),
+        // Gated instructions carry the feature gates PDA as their last account, optional for
+        // the instructions validators sent before they were gated
+        DlpDiscriminator::CommitDiff => Some(
+            processor::fast::require_enabled_legacy_instruction(accounts, discriminator).and_then(
+                |accounts| processor::fast::process_commit_diff(program_id, accounts, data),
+            ),
+        ),
+        DlpDiscriminator::CommitDiffShadow => Some(
+            processor::fast::require_enabled_instruction(accounts, discriminator).and_then(
+                |accounts| processor::fast::process_commit_diff_shadow(program_id, accounts, data),
+            ),
+        ),
         DlpDiscriminator::CommitDiffFromBuffer => Some(
-            processor::fast::process_commit_diff_from_buffer(program_id, accounts, data),
+            processor::fast::require_enabled_legacy_instruction(accounts, discriminator).and_then(
+                |accounts| {
+                    processor::fast::process_commit_diff_from_buffer(program_id, accounts, data)
+                },
+            ),
         ),
         DlpDiscriminator::Finalize => Some(processor::fast::process_finalize(
             program_id, accounts, data,
         )),
+        DlpDiscriminator::CommitFinalize => Some(
+            processor::fast::require_enabled_legacy_instruction(accounts, discriminator).and_then(
+                |accounts| processor::fast::process_commit_finalize(program_id, accounts, data),
+            ),
+        ),
+        DlpDiscriminator::CrankFinalize => Some(
+            processor::fast::require_enabled_instruction(accounts, discriminator).and_then(
+                |accounts| processor::fast::process_crank_finalize(program_id, accounts, data),
+            ),
+        ),
         DlpDiscriminator::Undelegate => Some(processor::fast::process_undelegate(
             program_id, accounts, data,
         )),
+        DlpDiscriminator::UndelegateAndClose => Some(
+            processor::fast::process_undelegate_and_close(program_id, accounts, data),
+        ),
+        DlpDiscriminator::UndelegateStage1 => Some(processor::fast::process_undelegate_stage1(
+            program_id, accounts, data,
+        )),
+        DlpDiscriminator::UndelegateStage2 => Some(processor::fast::process_undelegate_stage2(
+            program_id, accounts, data,
+        )),
         _ => None,
     }
 }
 
 #[cfg(not(feature = "sdk"))]
+#[doc(hidden)]
 pub fn slow_process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    if data.len() < 8 {
+    let Some((tag, data)) = data.split_first_chunk::<8>() else {
         return Err(ProgramError::InvalidInstructionData);
-    }
-
-    let (tag, data) = data.split_at(8);
+    };
     let ix = DlpDiscriminator::try_from(tag[0]).or(Err(ProgramError::InvalidInstructionData))?;
 
     match ix {
@@ -166,6 +271,222 @@ pub fn slow_process_instruction(
         DlpDiscriminator::CallHandler => {
             processor::process_call_handler(program_id, accounts, data)?
         }
+        DlpDiscriminator::SetFeatureGate => {
+            processor::process_set_feature_gate(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetProgramAllowedDataLens => {
+            processor::process_set_program_allowed_data_lens(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetValidatorInfo => {
+            processor::process_set_validator_info(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ApproveUndelegateAndClose => {
+            processor::process_approve_undelegate_and_close(program_id, accounts, data)?
+        }
+        DlpDiscriminator::InitDelegateBuffer => {
+            processor::process_init_delegate_buffer(program_id, accounts, data)?
+        }
+        DlpDiscriminator::WriteDelegateBufferChunk => {
+            processor::process_write_delegate_buffer_chunk(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CommitSessionBegin => {
+            processor::process_commit_session_begin(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CommitSessionEnd => {
+            processor::process_commit_session_end(program_id, accounts, data)?
+        }
+        DlpDiscriminator::GrowCommitState => {
+            processor::process_grow_commit_state(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetProtocolConfig => {
+            processor::process_set_protocol_config(program_id, accounts, data)?
+        }
+        DlpDiscriminator::BootstrapProtocol => {
+            processor::process_bootstrap_protocol(program_id, accounts, data)?
+        }
+        DlpDiscriminator::InitReadLock => {
+            processor::process_init_read_lock(program_id, accounts, data)?
+        }
+        DlpDiscriminator::WhitelistValidatorsForProgramBatch => {
+            processor::process_whitelist_validators_for_program_batch(program_id, accounts, data)?
+        }
+        DlpDiscriminator::RequestUndelegation => {
+            processor::process_request_undelegation(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetVersion => processor::process_set_version(program_id, accounts, data)?,
+        DlpDiscriminator::GetVersion => processor::process_get_version(program_id, accounts, data)?,
+        DlpDiscriminator::GrantFeeExemption => {
+            processor::process_grant_fee_exemption(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ValidateDelegation => {
+            processor::process_validate_delegation(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetProgramUndelegateLamportsTolerance => {
+            processor::process_set_program_undelegate_lamports_tolerance(
+                program_id, accounts, data,
+            )?
+        }
+        DlpDiscriminator::SetProgramMaxDelegationSlots => {
+            processor::process_set_program_max_delegation_slots(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetProgramValidateDelegations => {
+            processor::process_set_program_validate_delegations(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetProgramUndelegateDiscriminatorOverride => {
+            processor::process_set_program_undelegate_discriminator_override(
+                program_id, accounts, data,
+            )?
+        }
+        DlpDiscriminator::ValidatorHeartbeat => {
+            processor::process_validator_heartbeat(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetCommitSchedule => {
+            processor::process_set_commit_schedule(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CloseCommitSchedule => {
+            processor::process_close_commit_schedule(program_id, accounts, data)?
+        }
+        DlpDiscriminator::InitEarningsLedgerPage => {
+            processor::process_init_earnings_ledger_page(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ScheduleForceUndelegate => {
+            processor::process_schedule_force_undelegate(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ExecuteForceUndelegate => {
+            processor::process_execute_force_undelegate(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ResyncProtocolStats => {
+            processor::process_resync_protocol_stats(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetCallHandlerPermissions => {
+            processor::process_set_call_handler_permissions(program_id, accounts, data)?
+        }
+        DlpDiscriminator::TopUpEphemeralBalanceBatch => {
+            processor::process_top_up_ephemeral_balance_batch(program_id, accounts, data)?
+        }
+        DlpDiscriminator::RegisterCommitRelayer => {
+            processor::process_register_commit_relayer(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetValidatorCommitQuota => {
+            processor::process_set_validator_commit_quota(program_id, accounts, data)?
+        }
+        DlpDiscriminator::RedelegateEphemeralBalance => {
+            processor::process_redelegate_ephemeral_balance(program_id, accounts, data)?
+        }
+        DlpDiscriminator::IsValidatorWhitelisted => {
+            processor::process_is_validator_whitelisted(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ExportDelegationPackage => {
+            processor::process_export_delegation_package(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ImportDelegationPackage => {
+            processor::process_import_delegation_package(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ProposeProtocolVaultMigration => {
+            processor::process_propose_protocol_vault_migration(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ExecuteProtocolVaultMigration => {
+            processor::process_execute_protocol_vault_migration(program_id, accounts, data)?
+        }
+        DlpDiscriminator::GetDelegationSummaries => {
+            processor::process_get_delegation_summaries(program_id, accounts, data)?
+        }
+        DlpDiscriminator::GetEscrowSummaries => {
+            processor::process_get_escrow_summaries(program_id, accounts, data)?
+        }
+        DlpDiscriminator::GetPendingCommitSummaries => {
+            processor::process_get_pending_commit_summaries(program_id, accounts, data)?
+        }
+        DlpDiscriminator::WhitelistValidatorShardForProgram => {
+            processor::process_whitelist_validator_shard_for_program(program_id, accounts, data)?
+        }
+        DlpDiscriminator::MigrateProgramConfigWhitelist => {
+            processor::process_migrate_program_config_whitelist(program_id, accounts, data)?
+        }
+        DlpDiscriminator::MigrateDelegationRecord => {
+            processor::process_migrate_delegation_record(program_id, accounts, data)?
+        }
+        DlpDiscriminator::FundEscrowFromDelegated => {
+            processor::process_fund_escrow_from_delegated(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CloseProgramConfig => {
+            processor::process_close_program_config(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CommitFromOwner => {
+            processor::process_commit_from_owner(program_id, accounts, data)?
+        }
+        DlpDiscriminator::SetAuthorityGrant => {
+            processor::process_set_authority_grant(program_id, accounts, data)?
+        }
+        DlpDiscriminator::InitCommitBuffer => {
+            processor::process_init_commit_buffer(program_id, accounts, data)?
+        }
+        DlpDiscriminator::WriteCommitBuffer => {
+            processor::process_write_commit_buffer(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CloseCommitBuffer => {
+            processor::process_close_commit_buffer(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ClaimParkedUndelegation => {
+            processor::process_claim_parked_undelegation(program_id, accounts, data)?
+        }
+        DlpDiscriminator::InitSessionReport => {
+            processor::process_init_session_report(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CloseSessionReport => {
+            processor::process_close_session_report(program_id, accounts, data)?
+        }
+        DlpDiscriminator::ForceUndelegate => {
+            processor::process_force_undelegate(program_id, accounts, data)?
+        }
+        DlpDiscriminator::DelegateStakeAccount => {
+            processor::process_delegate_stake_account(program_id, accounts, data)?
+        }
+        DlpDiscriminator::UndelegateStakeAccount => {
+            processor::process_undelegate_stake_account(program_id, accounts, data)?
+        }
+        DlpDiscriminator::AddDelegationAuthority => {
+            processor::process_add_delegation_authority(program_id, accounts, data)?
+        }
+        DlpDiscriminator::RemoveDelegationAuthority => {
+            processor::process_remove_delegation_authority(program_id, accounts, data)?
+        }
+        DlpDiscriminator::UpdateDelegationAuthority => {
+            processor::process_update_delegation_authority(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CommitStateChunk => {
+            processor::process_commit_state_chunk(program_id, accounts, data)?
+        }
+        DlpDiscriminator::CloseStreamedCommitState => {
+            processor::process_close_streamed_commit_state(program_id, accounts, data)?
+        }
+        // Gated instructions carry the feature gates PDA as their last account
+        DlpDiscriminator::SplitDelegation => processor::process_split_delegation(
+            program_id,
+            processor::load_enabled_instruction(accounts, ix)?,
+            data,
+        )?,
+        DlpDiscriminator::TopUpProgramEphemeralBalance => {
+            processor::process_top_up_program_ephemeral_balance(
+                program_id,
+                processor::load_enabled_instruction(accounts, ix)?,
+                data,
+            )?
+        }
+        DlpDiscriminator::DelegateProgramEphemeralBalance => {
+            processor::process_delegate_program_ephemeral_balance(
+                program_id,
+                processor::load_enabled_instruction(accounts, ix)?,
+                data,
+            )?
+        }
+        DlpDiscriminator::CloseProgramEphemeralBalance => {
+            processor::process_close_program_ephemeral_balance(
+                program_id,
+                processor::load_enabled_instruction(accounts, ix)?,
+                data,
+            )?
+        }
         _ => {
             #[cfg(feature = "logging")]
             msg!("PANIC: Instruction must be processed by fast_process_instruction");
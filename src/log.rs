@@ -0,0 +1,28 @@
+//! The logs of the program, muted by the `minimal` feature
+//!
+//! The processors log with [msg] and [log], the macros of solana-program and pinocchio-log,
+//! unless the program is built with `minimal`: the macros then only borrow their args, so
+//! that the formatting code and the log syscalls are dropped from the program. The events
+//! logged with `sol_log_data` are kept, clients decoding them.
+
+#[cfg(all(feature = "program", not(feature = "minimal")))]
+pub(crate) use pinocchio_log::log;
+#[cfg(all(feature = "program", not(feature = "minimal")))]
+pub(crate) use solana_program::msg;
+
+#[cfg(feature = "minimal")]
+macro_rules! msg {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+}
+
+#[cfg(feature = "minimal")]
+macro_rules! log {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+}
+
+#[cfg(feature = "minimal")]
+pub(crate) use {log, msg};
@@ -1,4 +1,6 @@
+use alloc::vec::Vec;
 use solana_program::pubkey::Pubkey;
+use static_assertions::const_assert;
 
 pub const DELEGATION_RECORD_TAG: &[u8] = b"delegation";
 #[macro_export]
@@ -60,10 +62,147 @@ macro_rules! undelegate_buffer_seeds_from_delegated_account {
     };
 }
 
+pub const UNDELEGATE_PROGRESS_TAG: &[u8] = b"undelegate-progress";
+#[macro_export]
+macro_rules! undelegate_progress_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[
+            $crate::pda::UNDELEGATE_PROGRESS_TAG,
+            &$delegated_account.as_ref(),
+        ]
+    };
+}
+
+pub const STAGED_DELEGATE_BUFFER_TAG: &[u8] = b"staged-buffer";
+#[macro_export]
+macro_rules! staged_delegate_buffer_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[
+            $crate::pda::STAGED_DELEGATE_BUFFER_TAG,
+            &$delegated_account.as_ref(),
+        ]
+    };
+}
+
+pub const READ_LOCK_TAG: &[u8] = b"read-lock";
+#[macro_export]
+macro_rules! read_lock_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[$crate::pda::READ_LOCK_TAG, &$delegated_account.as_ref()]
+    };
+}
+
+pub const FEES_VAULT_TAG: &[u8] = b"fees-vault";
 #[macro_export]
 macro_rules! fees_vault_seeds {
     () => {
-        &[b"fees-vault"]
+        &[$crate::pda::FEES_VAULT_TAG]
+    };
+}
+
+/// The seeds of the protocol fees vaults created by a migration, the first vault of
+/// generation 0 being derived from [fees_vault_seeds]
+#[macro_export]
+macro_rules! fees_vault_seeds_from_generation {
+    ($generation: expr) => {
+        &[$crate::pda::FEES_VAULT_TAG, &$generation.to_le_bytes()]
+    };
+}
+
+pub const FEE_EXEMPTION_TAG: &[u8] = b"fee-exemption";
+#[macro_export]
+macro_rules! fee_exemption_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[$crate::pda::FEE_EXEMPTION_TAG, &$delegated_account.as_ref()]
+    };
+}
+
+pub const COMMIT_SCHEDULE_TAG: &[u8] = b"commit-schedule";
+#[macro_export]
+macro_rules! commit_schedule_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[
+            $crate::pda::COMMIT_SCHEDULE_TAG,
+            &$delegated_account.as_ref(),
+        ]
+    };
+}
+
+pub const CALL_HANDLER_PERMISSIONS_TAG: &[u8] = b"call-handler-permissions";
+#[macro_export]
+macro_rules! call_handler_permissions_seeds_from_escrow {
+    ($escrow: expr) => {
+        &[$crate::pda::CALL_HANDLER_PERMISSIONS_TAG, &$escrow.as_ref()]
+    };
+}
+
+pub const SESSION_REPORT_TAG: &[u8] = b"session-report";
+#[macro_export]
+macro_rules! session_report_seeds_from_escrow {
+    ($escrow: expr) => {
+        &[$crate::pda::SESSION_REPORT_TAG, &$escrow.as_ref()]
+    };
+}
+
+pub const STAKE_AUTHORITY_TAG: &[u8] = b"stake-authority";
+#[macro_export]
+macro_rules! stake_authority_seeds_from_stake_account {
+    ($stake_account: expr) => {
+        &[$crate::pda::STAKE_AUTHORITY_TAG, &$stake_account.as_ref()]
+    };
+}
+
+pub const DELEGATION_AUTHORITIES_TAG: &[u8] = b"delegation-authorities";
+#[macro_export]
+macro_rules! delegation_authorities_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[
+            $crate::pda::DELEGATION_AUTHORITIES_TAG,
+            &$delegated_account.as_ref(),
+        ]
+    };
+}
+
+pub const FORCE_UNDELEGATION_TAG: &[u8] = b"force-undelegation";
+#[macro_export]
+macro_rules! force_undelegation_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[
+            $crate::pda::FORCE_UNDELEGATION_TAG,
+            &$delegated_account.as_ref(),
+        ]
+    };
+}
+
+pub const FEATURE_GATES_TAG: &[u8] = b"feature-gates";
+#[macro_export]
+macro_rules! feature_gates_seeds {
+    () => {
+        &[$crate::pda::FEATURE_GATES_TAG]
+    };
+}
+
+pub const PROTOCOL_CONFIG_TAG: &[u8] = b"protocol-config";
+#[macro_export]
+macro_rules! protocol_config_seeds {
+    () => {
+        &[$crate::pda::PROTOCOL_CONFIG_TAG]
+    };
+}
+
+pub const PROTOCOL_STATS_TAG: &[u8] = b"protocol-stats";
+#[macro_export]
+macro_rules! protocol_stats_seeds {
+    () => {
+        &[$crate::pda::PROTOCOL_STATS_TAG]
+    };
+}
+
+pub const PROGRAM_VERSION_TAG: &[u8] = b"program-version";
+#[macro_export]
+macro_rules! program_version_seeds {
+    () => {
+        &[$crate::pda::PROGRAM_VERSION_TAG]
     };
 }
 
@@ -75,6 +214,68 @@ macro_rules! validator_fees_vault_seeds_from_validator {
     };
 }
 
+pub const VALIDATOR_INFO_TAG: &[u8] = b"validator-info";
+#[macro_export]
+macro_rules! validator_info_seeds_from_validator {
+    ($validator: expr) => {
+        &[$crate::pda::VALIDATOR_INFO_TAG, &$validator.as_ref()]
+    };
+}
+
+pub const VALIDATOR_LIVENESS_TAG: &[u8] = b"validator-liveness";
+#[macro_export]
+macro_rules! validator_liveness_seeds_from_validator {
+    ($validator: expr) => {
+        &[$crate::pda::VALIDATOR_LIVENESS_TAG, &$validator.as_ref()]
+    };
+}
+
+pub const PARKED_UNDELEGATION_TAG: &[u8] = b"parked-undelegation";
+#[macro_export]
+macro_rules! parked_undelegation_seeds_from_delegated_account {
+    ($delegated_account: expr) => {
+        &[
+            $crate::pda::PARKED_UNDELEGATION_TAG,
+            &$delegated_account.as_ref(),
+        ]
+    };
+}
+
+pub const COMMIT_BUFFER_TAG: &[u8] = b"commit-buffer";
+#[macro_export]
+macro_rules! commit_buffer_seeds_from_delegated_account_and_authority {
+    ($delegated_account: expr, $authority: expr) => {
+        &[
+            $crate::pda::COMMIT_BUFFER_TAG,
+            &$delegated_account.as_ref(),
+            &$authority.as_ref(),
+        ]
+    };
+}
+
+pub const AUTHORITY_DELEGATION_CHAIN_TAG: &[u8] = b"authority-delegation-chain";
+#[macro_export]
+macro_rules! authority_delegation_chain_seeds_from_delegator {
+    ($delegator: expr) => {
+        &[
+            $crate::pda::AUTHORITY_DELEGATION_CHAIN_TAG,
+            &$delegator.as_ref(),
+        ]
+    };
+}
+
+pub const EARNINGS_LEDGER_TAG: &[u8] = b"earnings-ledger";
+#[macro_export]
+macro_rules! earnings_ledger_page_seeds_from_validator {
+    ($validator: expr, $page: expr) => {
+        &[
+            $crate::pda::EARNINGS_LEDGER_TAG,
+            &$validator.as_ref(),
+            &$page.to_le_bytes(),
+        ]
+    };
+}
+
 pub const PROGRAM_CONFIG_TAG: &[u8] = b"p-conf";
 #[macro_export]
 macro_rules! program_config_seeds_from_program_id {
@@ -83,6 +284,17 @@ macro_rules! program_config_seeds_from_program_id {
     };
 }
 
+#[macro_export]
+macro_rules! validator_whitelist_shard_seeds_from_program_id {
+    ($program_id: expr, $validator: expr) => {
+        &[
+            $crate::pda::PROGRAM_CONFIG_TAG,
+            &$program_id.as_ref(),
+            &$validator.as_ref(),
+        ]
+    };
+}
+
 pub const EPHEMERAL_BALANCE_TAG: &[u8] = b"balance";
 #[macro_export]
 macro_rules! ephemeral_balance_seeds_from_payer {
@@ -95,34 +307,96 @@ macro_rules! ephemeral_balance_seeds_from_payer {
     };
 }
 
+/// The escrows of a wider index space share the tag of the ephemeral balances, their u32
+/// index keeping them domain separated from the u8 indexed ones
+#[macro_export]
+macro_rules! wide_ephemeral_balance_seeds_from_payer {
+    ($payer: expr, $index: expr) => {
+        &[
+            $crate::pda::EPHEMERAL_BALANCE_TAG,
+            &$payer.as_ref(),
+            &$index.to_le_bytes(),
+        ]
+    };
+}
+
+pub const PROGRAM_EPHEMERAL_BALANCE_TAG: &[u8] = b"program-balance";
+#[macro_export]
+macro_rules! program_ephemeral_balance_seeds_from_payer {
+    ($payer: expr, $program_id: expr, $index: expr) => {
+        &[
+            $crate::pda::PROGRAM_EPHEMERAL_BALANCE_TAG,
+            &$payer.as_ref(),
+            &$program_id.as_ref(),
+            &[$index],
+        ]
+    };
+}
+
 pub fn delegation_record_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    delegation_record_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [delegation_record_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn delegation_record_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         delegation_record_seeds_from_delegated_account!(delegated_account),
-        &crate::id(),
+        program_id,
     )
     .0
 }
 
 pub fn delegation_metadata_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    delegation_metadata_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [delegation_metadata_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn delegation_metadata_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         delegation_metadata_seeds_from_delegated_account!(delegated_account),
-        &crate::id(),
+        program_id,
     )
     .0
 }
 
 pub fn commit_state_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    commit_state_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [commit_state_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn commit_state_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         commit_state_seeds_from_delegated_account!(delegated_account),
-        &crate::id(),
+        program_id,
     )
     .0
 }
 
 pub fn commit_record_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    commit_record_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [commit_record_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn commit_record_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         commit_record_seeds_from_delegated_account!(delegated_account),
-        &crate::id(),
+        program_id,
     )
     .0
 }
@@ -139,37 +413,1060 @@ pub fn delegate_buffer_pda_from_delegated_account_and_owner_program(
 }
 
 pub fn undelegate_buffer_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    undelegate_buffer_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [undelegate_buffer_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn undelegate_buffer_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         undelegate_buffer_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn undelegate_progress_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    undelegate_progress_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [undelegate_progress_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn undelegate_progress_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        undelegate_progress_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn staged_delegate_buffer_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    staged_delegate_buffer_pda_from_delegated_account_with_program_id(
+        delegated_account,
+        &crate::id(),
+    )
+}
+
+/// Same as [staged_delegate_buffer_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn staged_delegate_buffer_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        staged_delegate_buffer_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn read_lock_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    read_lock_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [read_lock_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn read_lock_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        read_lock_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn fee_exemption_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    fee_exemption_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [fee_exemption_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn fee_exemption_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        fee_exemption_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn commit_schedule_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    commit_schedule_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [commit_schedule_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn commit_schedule_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        commit_schedule_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn force_undelegation_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    force_undelegation_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [force_undelegation_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn force_undelegation_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        force_undelegation_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn call_handler_permissions_pda_from_escrow(escrow: &Pubkey) -> Pubkey {
+    call_handler_permissions_pda_from_escrow_with_program_id(escrow, &crate::id())
+}
+
+/// Same as [call_handler_permissions_pda_from_escrow],
+/// for the delegation program deployed at `program_id`
+pub fn call_handler_permissions_pda_from_escrow_with_program_id(
+    escrow: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        call_handler_permissions_seeds_from_escrow!(escrow),
+        program_id,
+    )
+    .0
+}
+
+pub fn session_report_pda_from_escrow(escrow: &Pubkey) -> Pubkey {
+    session_report_pda_from_escrow_with_program_id(escrow, &crate::id())
+}
+
+/// Same as [session_report_pda_from_escrow],
+/// for the delegation program deployed at `program_id`
+pub fn session_report_pda_from_escrow_with_program_id(
+    escrow: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(session_report_seeds_from_escrow!(escrow), program_id).0
+}
+
+pub fn stake_authority_pda_from_stake_account(stake_account: &Pubkey) -> Pubkey {
+    stake_authority_pda_from_stake_account_with_program_id(stake_account, &crate::id())
+}
+
+/// Same as [stake_authority_pda_from_stake_account],
+/// for the delegation program deployed at `program_id`
+pub fn stake_authority_pda_from_stake_account_with_program_id(
+    stake_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        stake_authority_seeds_from_stake_account!(stake_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn delegation_authorities_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    delegation_authorities_pda_from_delegated_account_with_program_id(
+        delegated_account,
         &crate::id(),
     )
+}
+
+/// Same as [delegation_authorities_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn delegation_authorities_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        delegation_authorities_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
     .0
 }
 
 pub fn fees_vault_pda() -> Pubkey {
-    Pubkey::find_program_address(fees_vault_seeds!(), &crate::id()).0
+    fees_vault_pda_with_program_id(&crate::id())
+}
+
+/// Same as [fees_vault_pda], for the delegation program deployed at `program_id`
+pub fn fees_vault_pda_with_program_id(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(fees_vault_seeds!(), program_id).0
+}
+
+/// The protocol fees vault of a generation, see [crate::state::ProtocolConfig::fees_vault_generation]
+pub fn fees_vault_pda_from_generation(generation: u64) -> Pubkey {
+    fees_vault_pda_from_generation_with_program_id(generation, &crate::id())
+}
+
+/// Same as [fees_vault_pda_from_generation], for the delegation program deployed at
+/// `program_id`
+pub fn fees_vault_pda_from_generation_with_program_id(
+    generation: u64,
+    program_id: &Pubkey,
+) -> Pubkey {
+    match generation {
+        0 => fees_vault_pda_with_program_id(program_id),
+        generation => {
+            Pubkey::find_program_address(fees_vault_seeds_from_generation!(generation), program_id)
+                .0
+        }
+    }
+}
+
+pub fn feature_gates_pda() -> Pubkey {
+    feature_gates_pda_with_program_id(&crate::id())
+}
+
+/// Same as [feature_gates_pda], for the delegation program deployed at `program_id`
+pub fn feature_gates_pda_with_program_id(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(feature_gates_seeds!(), program_id).0
+}
+
+pub fn protocol_config_pda() -> Pubkey {
+    protocol_config_pda_with_program_id(&crate::id())
+}
+
+/// Same as [protocol_config_pda], for the delegation program deployed at `program_id`
+pub fn protocol_config_pda_with_program_id(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(protocol_config_seeds!(), program_id).0
+}
+
+pub fn protocol_stats_pda() -> Pubkey {
+    protocol_stats_pda_with_program_id(&crate::id())
+}
+
+/// Same as [protocol_stats_pda], for the delegation program deployed at `program_id`
+pub fn protocol_stats_pda_with_program_id(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(protocol_stats_seeds!(), program_id).0
+}
+
+pub fn program_version_pda() -> Pubkey {
+    program_version_pda_with_program_id(&crate::id())
+}
+
+/// Same as [program_version_pda], for the delegation program deployed at `program_id`
+pub fn program_version_pda_with_program_id(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(program_version_seeds!(), program_id).0
 }
 
 pub fn validator_fees_vault_pda_from_validator(validator: &Pubkey) -> Pubkey {
+    validator_fees_vault_pda_from_validator_with_program_id(validator, &crate::id())
+}
+
+/// Same as [validator_fees_vault_pda_from_validator],
+/// for the delegation program deployed at `program_id`
+pub fn validator_fees_vault_pda_from_validator_with_program_id(
+    validator: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         validator_fees_vault_seeds_from_validator!(validator),
+        program_id,
+    )
+    .0
+}
+
+pub fn validator_info_pda_from_validator(validator: &Pubkey) -> Pubkey {
+    validator_info_pda_from_validator_with_program_id(validator, &crate::id())
+}
+
+/// Same as [validator_info_pda_from_validator], for the delegation program deployed at `program_id`
+pub fn validator_info_pda_from_validator_with_program_id(
+    validator: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(validator_info_seeds_from_validator!(validator), program_id).0
+}
+
+pub fn validator_liveness_pda_from_validator(validator: &Pubkey) -> Pubkey {
+    validator_liveness_pda_from_validator_with_program_id(validator, &crate::id())
+}
+
+/// Same as [validator_liveness_pda_from_validator], for the delegation program deployed at
+/// `program_id`
+pub fn validator_liveness_pda_from_validator_with_program_id(
+    validator: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        validator_liveness_seeds_from_validator!(validator),
+        program_id,
+    )
+    .0
+}
+
+pub fn parked_undelegation_pda_from_delegated_account(delegated_account: &Pubkey) -> Pubkey {
+    parked_undelegation_pda_from_delegated_account_with_program_id(delegated_account, &crate::id())
+}
+
+/// Same as [parked_undelegation_pda_from_delegated_account],
+/// for the delegation program deployed at `program_id`
+pub fn parked_undelegation_pda_from_delegated_account_with_program_id(
+    delegated_account: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        parked_undelegation_seeds_from_delegated_account!(delegated_account),
+        program_id,
+    )
+    .0
+}
+
+pub fn commit_buffer_pda_from_delegated_account_and_authority(
+    delegated_account: &Pubkey,
+    authority: &Pubkey,
+) -> Pubkey {
+    commit_buffer_pda_from_delegated_account_and_authority_with_program_id(
+        delegated_account,
+        authority,
         &crate::id(),
     )
+}
+
+/// Same as [commit_buffer_pda_from_delegated_account_and_authority], for the delegation
+/// program deployed at `program_id`
+pub fn commit_buffer_pda_from_delegated_account_and_authority_with_program_id(
+    delegated_account: &Pubkey,
+    authority: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        commit_buffer_seeds_from_delegated_account_and_authority!(delegated_account, authority),
+        program_id,
+    )
+    .0
+}
+
+pub fn authority_delegation_chain_pda_from_delegator(delegator: &Pubkey) -> Pubkey {
+    authority_delegation_chain_pda_from_delegator_with_program_id(delegator, &crate::id())
+}
+
+/// Same as [authority_delegation_chain_pda_from_delegator], for the delegation program
+/// deployed at `program_id`
+pub fn authority_delegation_chain_pda_from_delegator_with_program_id(
+    delegator: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        authority_delegation_chain_seeds_from_delegator!(delegator),
+        program_id,
+    )
+    .0
+}
+
+pub fn earnings_ledger_page_pda_from_validator(validator: &Pubkey, page: u32) -> Pubkey {
+    earnings_ledger_page_pda_from_validator_with_program_id(validator, page, &crate::id())
+}
+
+/// Same as [earnings_ledger_page_pda_from_validator],
+/// for the delegation program deployed at `program_id`
+pub fn earnings_ledger_page_pda_from_validator_with_program_id(
+    validator: &Pubkey,
+    page: u32,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        earnings_ledger_page_seeds_from_validator!(validator, page),
+        program_id,
+    )
     .0
 }
 
 pub fn program_config_from_program_id(program_id: &Pubkey) -> Pubkey {
+    program_config_from_program_id_with_program_id(program_id, &crate::id())
+}
+
+/// Same as [program_config_from_program_id],
+/// for the delegation program deployed at `delegation_program_id`
+pub fn program_config_from_program_id_with_program_id(
+    program_id: &Pubkey,
+    delegation_program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         program_config_seeds_from_program_id!(program_id),
+        delegation_program_id,
+    )
+    .0
+}
+
+pub fn validator_whitelist_shard_pda_from_program_id(
+    program_id: &Pubkey,
+    validator: &Pubkey,
+) -> Pubkey {
+    validator_whitelist_shard_pda_from_program_id_with_program_id(
+        program_id,
+        validator,
         &crate::id(),
     )
+}
+
+/// Same as [validator_whitelist_shard_pda_from_program_id],
+/// for the delegation program deployed at `delegation_program_id`
+pub fn validator_whitelist_shard_pda_from_program_id_with_program_id(
+    program_id: &Pubkey,
+    validator: &Pubkey,
+    delegation_program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        validator_whitelist_shard_seeds_from_program_id!(program_id, validator),
+        delegation_program_id,
+    )
     .0
 }
 
 pub fn ephemeral_balance_pda_from_payer(payer: &Pubkey, index: u8) -> Pubkey {
+    ephemeral_balance_pda_from_payer_with_program_id(payer, index, &crate::id())
+}
+
+/// Same as [ephemeral_balance_pda_from_payer], for the delegation program deployed at `program_id`
+pub fn ephemeral_balance_pda_from_payer_with_program_id(
+    payer: &Pubkey,
+    index: u8,
+    program_id: &Pubkey,
+) -> Pubkey {
     Pubkey::find_program_address(
         ephemeral_balance_seeds_from_payer!(payer, index),
-        &crate::id(),
+        program_id,
     )
     .0
 }
+
+pub fn wide_ephemeral_balance_pda_from_payer(payer: &Pubkey, index: u32) -> Pubkey {
+    wide_ephemeral_balance_pda_from_payer_with_program_id(payer, index, &crate::id())
+}
+
+/// Same as [wide_ephemeral_balance_pda_from_payer],
+/// for the delegation program deployed at `program_id`
+pub fn wide_ephemeral_balance_pda_from_payer_with_program_id(
+    payer: &Pubkey,
+    index: u32,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        wide_ephemeral_balance_seeds_from_payer!(payer, index),
+        program_id,
+    )
+    .0
+}
+
+pub fn program_ephemeral_balance_pda_from_payer(
+    payer: &Pubkey,
+    program_id: &Pubkey,
+    index: u8,
+) -> Pubkey {
+    program_ephemeral_balance_pda_from_payer_with_program_id(payer, program_id, index, &crate::id())
+}
+
+/// Same as [program_ephemeral_balance_pda_from_payer],
+/// for the delegation program deployed at `delegation_program_id`
+pub fn program_ephemeral_balance_pda_from_payer_with_program_id(
+    payer: &Pubkey,
+    program_id: &Pubkey,
+    index: u8,
+    delegation_program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        program_ephemeral_balance_seeds_from_payer!(payer, program_id, index),
+        delegation_program_id,
+    )
+    .0
+}
+
+/// The kind of a seed following the tag of a PDA
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedKind {
+    /// A public key (32 bytes)
+    Pubkey,
+    /// A single byte, e.g. an index
+    U8,
+    /// A little-endian u32, e.g. a page index
+    U32,
+    /// A little-endian u64, e.g. a generation
+    U64,
+}
+
+impl SeedKind {
+    pub const fn size(self) -> usize {
+        match self {
+            SeedKind::Pubkey => 32,
+            SeedKind::U8 => 1,
+            SeedKind::U32 => 4,
+            SeedKind::U64 => 8,
+        }
+    }
+}
+
+/// The program a PDA is derived from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PdaProgram {
+    /// The delegation program
+    Delegation,
+    /// The program owning the delegated account
+    OwnerProgram,
+}
+
+/// The seed layout of a PDA: its tag followed by the kinds of the other seeds
+#[derive(Clone, Copy, Debug)]
+pub struct PdaLayout {
+    pub name: &'static str,
+    pub tag: &'static [u8],
+    pub seeds: &'static [SeedKind],
+    pub program: PdaProgram,
+}
+
+impl PdaLayout {
+    /// The total length of the seeds, tag included
+    pub const fn seeds_len(&self) -> usize {
+        let mut len = self.tag.len();
+        let mut i = 0;
+        while i < self.seeds.len() {
+            len += self.seeds[i].size();
+            i += 1;
+        }
+        len
+    }
+
+    /// Derive the PDA from the seeds following the tag.
+    /// Returns None if the seeds do not match the layout.
+    pub fn find_program_address(&self, seeds: &[&[u8]], program_id: &Pubkey) -> Option<Pubkey> {
+        if seeds.len() != self.seeds.len()
+            || seeds
+                .iter()
+                .zip(self.seeds)
+                .any(|(seed, kind)| seed.len() != kind.size())
+        {
+            return None;
+        }
+        let mut all_seeds = Vec::with_capacity(seeds.len() + 1);
+        all_seeds.push(self.tag);
+        all_seeds.extend_from_slice(seeds);
+        Some(Pubkey::find_program_address(&all_seeds, program_id).0)
+    }
+}
+
+/// Every PDA seed layout used by the delegation program.
+/// New PDAs must be added here, so that their domain separation is checked at compile time.
+pub const PDA_REGISTRY: &[PdaLayout] = &[
+    PdaLayout {
+        name: "delegation record",
+        tag: DELEGATION_RECORD_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "delegation metadata",
+        tag: DELEGATION_METADATA_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "commit state",
+        tag: COMMIT_STATE_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "commit record",
+        tag: COMMIT_RECORD_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "delegate buffer",
+        tag: DELEGATE_BUFFER_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::OwnerProgram,
+    },
+    PdaLayout {
+        name: "undelegate buffer",
+        tag: UNDELEGATE_BUFFER_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "undelegate progress",
+        tag: UNDELEGATE_PROGRESS_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "staged delegate buffer",
+        tag: STAGED_DELEGATE_BUFFER_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "read lock",
+        tag: READ_LOCK_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "fee exemption",
+        tag: FEE_EXEMPTION_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "commit schedule",
+        tag: COMMIT_SCHEDULE_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "force undelegation",
+        tag: FORCE_UNDELEGATION_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "call handler permissions",
+        tag: CALL_HANDLER_PERMISSIONS_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "session report",
+        tag: SESSION_REPORT_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "stake authority",
+        tag: STAKE_AUTHORITY_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "delegation authorities",
+        tag: DELEGATION_AUTHORITIES_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "fees vault",
+        tag: FEES_VAULT_TAG,
+        seeds: &[],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "migrated fees vault",
+        tag: FEES_VAULT_TAG,
+        seeds: &[SeedKind::U64],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "feature gates",
+        tag: FEATURE_GATES_TAG,
+        seeds: &[],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "protocol config",
+        tag: PROTOCOL_CONFIG_TAG,
+        seeds: &[],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "protocol stats",
+        tag: PROTOCOL_STATS_TAG,
+        seeds: &[],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "program version",
+        tag: PROGRAM_VERSION_TAG,
+        seeds: &[],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "validator fees vault",
+        tag: VALIDATOR_FEES_VAULT_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "validator info",
+        tag: VALIDATOR_INFO_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "validator liveness",
+        tag: VALIDATOR_LIVENESS_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "parked undelegation",
+        tag: PARKED_UNDELEGATION_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "commit buffer",
+        tag: COMMIT_BUFFER_TAG,
+        seeds: &[SeedKind::Pubkey, SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "authority delegation chain",
+        tag: AUTHORITY_DELEGATION_CHAIN_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "earnings ledger page",
+        tag: EARNINGS_LEDGER_TAG,
+        seeds: &[SeedKind::Pubkey, SeedKind::U32],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "program config",
+        tag: PROGRAM_CONFIG_TAG,
+        seeds: &[SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "validator whitelist shard",
+        tag: PROGRAM_CONFIG_TAG,
+        seeds: &[SeedKind::Pubkey, SeedKind::Pubkey],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "ephemeral balance",
+        tag: EPHEMERAL_BALANCE_TAG,
+        seeds: &[SeedKind::Pubkey, SeedKind::U8],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "wide ephemeral balance",
+        tag: EPHEMERAL_BALANCE_TAG,
+        seeds: &[SeedKind::Pubkey, SeedKind::U32],
+        program: PdaProgram::Delegation,
+    },
+    PdaLayout {
+        name: "program ephemeral balance",
+        tag: PROGRAM_EPHEMERAL_BALANCE_TAG,
+        seeds: &[SeedKind::Pubkey, SeedKind::Pubkey, SeedKind::U8],
+        program: PdaProgram::Delegation,
+    },
+];
+
+/// Returns every PDA seed layout, see [PDA_REGISTRY]
+pub fn registry() -> &'static [PdaLayout] {
+    PDA_REGISTRY
+}
+
+const fn starts_with(bytes: &[u8], prefix: &[u8]) -> bool {
+    if prefix.len() > bytes.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if bytes[i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Check that no two layouts of the same program can derive the same address.
+///
+/// The seeds are hashed concatenated, so two layouts can only collide if one tag is a
+/// prefix of the other (e.g. `delegation` and `delegation-metadata`) and their seeds have
+/// the same total length. Since all the seeds have a fixed size, this is enough to make
+/// the layouts domain separated.
+pub const fn is_domain_separated(registry: &[PdaLayout]) -> bool {
+    let mut i = 0;
+    while i < registry.len() {
+        let mut j = i + 1;
+        while j < registry.len() {
+            let (a, b) = (&registry[i], &registry[j]);
+            if a.program as u8 == b.program as u8
+                && (starts_with(a.tag, b.tag) || starts_with(b.tag, a.tag))
+                && a.seeds_len() == b.seeds_len()
+            {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+const_assert!(is_domain_separated(PDA_REGISTRY));
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn random_seeds(rng: &mut SmallRng, layout: &PdaLayout) -> Vec<Vec<u8>> {
+        layout
+            .seeds
+            .iter()
+            .map(|kind| (0..kind.size()).map(|_| rng.gen()).collect())
+            .collect()
+    }
+
+    fn derive(layout: &PdaLayout, seeds: &[Vec<u8>], owner_program: &Pubkey) -> Pubkey {
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        let program_id = match layout.program {
+            PdaProgram::Delegation => crate::id(),
+            PdaProgram::OwnerProgram => *owner_program,
+        };
+        layout.find_program_address(&seeds, &program_id).unwrap()
+    }
+
+    #[test]
+    fn test_registry_is_domain_separated() {
+        assert!(is_domain_separated(registry()));
+
+        let layout = PdaLayout {
+            name: "tag",
+            tag: b"tag",
+            seeds: &[SeedKind::Pubkey, SeedKind::U8],
+            program: PdaProgram::Delegation,
+        };
+
+        // A prefixed tag is separated by the seeds length
+        let prefixed = PdaLayout {
+            name: "tag-b",
+            tag: b"tag-b",
+            seeds: &[SeedKind::Pubkey],
+            ..layout
+        };
+        assert!(is_domain_separated(&[layout, prefixed]));
+
+        // A prefixed tag with the same seeds length may collide
+        let prefixed = PdaLayout {
+            tag: b"tag-",
+            ..prefixed
+        };
+        assert!(!is_domain_separated(&[layout, prefixed]));
+
+        // Unless it is derived from another program
+        let prefixed = PdaLayout {
+            program: PdaProgram::OwnerProgram,
+            ..prefixed
+        };
+        assert!(is_domain_separated(&[layout, prefixed]));
+    }
+
+    #[test]
+    fn test_registry_matches_pda_helpers() {
+        let key = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        for layout in registry() {
+            let (seeds, expected): (Vec<&[u8]>, Pubkey) = match layout.name {
+                "delegation record" => (
+                    vec![key.as_ref()],
+                    delegation_record_pda_from_delegated_account(&key),
+                ),
+                "delegation metadata" => (
+                    vec![key.as_ref()],
+                    delegation_metadata_pda_from_delegated_account(&key),
+                ),
+                "commit state" => (
+                    vec![key.as_ref()],
+                    commit_state_pda_from_delegated_account(&key),
+                ),
+                "commit record" => (
+                    vec![key.as_ref()],
+                    commit_record_pda_from_delegated_account(&key),
+                ),
+                "delegate buffer" => (
+                    vec![key.as_ref()],
+                    delegate_buffer_pda_from_delegated_account_and_owner_program(
+                        &key,
+                        &crate::id(),
+                    ),
+                ),
+                "undelegate buffer" => (
+                    vec![key.as_ref()],
+                    undelegate_buffer_pda_from_delegated_account(&key),
+                ),
+                "undelegate progress" => (
+                    vec![key.as_ref()],
+                    undelegate_progress_pda_from_delegated_account(&key),
+                ),
+                "staged delegate buffer" => (
+                    vec![key.as_ref()],
+                    staged_delegate_buffer_pda_from_delegated_account(&key),
+                ),
+                "read lock" => (
+                    vec![key.as_ref()],
+                    read_lock_pda_from_delegated_account(&key),
+                ),
+                "fee exemption" => (
+                    vec![key.as_ref()],
+                    fee_exemption_pda_from_delegated_account(&key),
+                ),
+                "commit schedule" => (
+                    vec![key.as_ref()],
+                    commit_schedule_pda_from_delegated_account(&key),
+                ),
+                "force undelegation" => (
+                    vec![key.as_ref()],
+                    force_undelegation_pda_from_delegated_account(&key),
+                ),
+                "call handler permissions" => (
+                    vec![key.as_ref()],
+                    call_handler_permissions_pda_from_escrow(&key),
+                ),
+                "session report" => (vec![key.as_ref()], session_report_pda_from_escrow(&key)),
+                "stake authority" => (
+                    vec![key.as_ref()],
+                    stake_authority_pda_from_stake_account(&key),
+                ),
+                "delegation authorities" => (
+                    vec![key.as_ref()],
+                    delegation_authorities_pda_from_delegated_account(&key),
+                ),
+                "fees vault" => (vec![], fees_vault_pda()),
+                "migrated fees vault" => (
+                    vec![&[3, 0, 0, 0, 0, 0, 0, 0][..]],
+                    fees_vault_pda_from_generation(3),
+                ),
+                "feature gates" => (vec![], feature_gates_pda()),
+                "protocol config" => (vec![], protocol_config_pda()),
+                "protocol stats" => (vec![], protocol_stats_pda()),
+                "program version" => (vec![], program_version_pda()),
+                "validator fees vault" => (
+                    vec![key.as_ref()],
+                    validator_fees_vault_pda_from_validator(&key),
+                ),
+                "validator info" => (vec![key.as_ref()], validator_info_pda_from_validator(&key)),
+                "validator liveness" => (
+                    vec![key.as_ref()],
+                    validator_liveness_pda_from_validator(&key),
+                ),
+                "parked undelegation" => (
+                    vec![key.as_ref()],
+                    parked_undelegation_pda_from_delegated_account(&key),
+                ),
+                "commit buffer" => (
+                    vec![key.as_ref(), other.as_ref()],
+                    commit_buffer_pda_from_delegated_account_and_authority(&key, &other),
+                ),
+                "authority delegation chain" => (
+                    vec![key.as_ref()],
+                    authority_delegation_chain_pda_from_delegator(&key),
+                ),
+                "earnings ledger page" => (
+                    vec![key.as_ref(), &[3, 0, 0, 0][..]],
+                    earnings_ledger_page_pda_from_validator(&key, 3),
+                ),
+                "program config" => (vec![key.as_ref()], program_config_from_program_id(&key)),
+                "validator whitelist shard" => (
+                    vec![key.as_ref(), other.as_ref()],
+                    validator_whitelist_shard_pda_from_program_id(&key, &other),
+                ),
+                "ephemeral balance" => (
+                    vec![key.as_ref(), &[3u8][..]],
+                    ephemeral_balance_pda_from_payer(&key, 3),
+                ),
+                "wide ephemeral balance" => (
+                    vec![key.as_ref(), &[3, 0, 0, 0][..]],
+                    wide_ephemeral_balance_pda_from_payer(&key, 3),
+                ),
+                "program ephemeral balance" => (
+                    vec![key.as_ref(), other.as_ref(), &[3u8][..]],
+                    program_ephemeral_balance_pda_from_payer(&key, &other, 3),
+                ),
+                name => panic!("No PDA helper checked for {}", name),
+            };
+            assert_eq!(
+                layout.find_program_address(&seeds, &crate::id()),
+                Some(expected),
+                "{}",
+                layout.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_collisions_for_random_inputs() {
+        let mut rng = SmallRng::seed_from_u64(42);
+        let owner_program = Pubkey::new_unique();
+        let mut derived = HashMap::new();
+        for _ in 0..16 {
+            for layout in registry() {
+                let seeds = random_seeds(&mut rng, layout);
+                let pda = derive(layout, &seeds, &owner_program);
+                if let Some((name, previous_seeds)) = derived.insert(pda, (layout.name, seeds)) {
+                    // Seedless PDAs are derived again on each round
+                    assert!(
+                        name == layout.name && previous_seeds.is_empty(),
+                        "{} collides with {}",
+                        layout.name,
+                        name
+                    );
+                }
+            }
+        }
+
+        // Seeds that do not match the layout are rejected
+        assert!(registry()[0]
+            .find_program_address(&[&[0; 31]], &crate::id())
+            .is_none());
+    }
+
+    #[test]
+    fn test_feature_gates_pda_matches_program_id() {
+        assert_eq!(
+            crate::consts::FEATURE_GATES_PDA,
+            Pubkey::find_program_address(&[FEATURE_GATES_TAG], &crate::ID).0
+        );
+    }
+
+    #[test]
+    fn test_pdas_with_program_id() {
+        // The fast path derives the PDAs from the same id as the instruction builders
+        #[cfg(not(feature = "sdk"))]
+        assert_eq!(crate::ID.to_bytes(), crate::fast::ID);
+
+        let delegated_account = Pubkey::new_unique();
+        assert_eq!(
+            delegation_record_pda_from_delegated_account_with_program_id(
+                &delegated_account,
+                &crate::id()
+            ),
+            delegation_record_pda_from_delegated_account(&delegated_account)
+        );
+
+        // A fork of the program derives other PDAs
+        let fork_id = Pubkey::new_unique();
+        assert_ne!(
+            commit_state_pda_from_delegated_account_with_program_id(&delegated_account, &fork_id),
+            commit_state_pda_from_delegated_account(&delegated_account)
+        );
+        assert_eq!(
+            fees_vault_pda_with_program_id(&fork_id),
+            Pubkey::find_program_address(fees_vault_seeds!(), &fork_id).0
+        );
+    }
+}
@@ -0,0 +1,22 @@
+//! The stable public API of the delegation program.
+//!
+//! Downstream crates should import from this module, e.g. `use dlp::prelude::*;`: the items
+//! re-exported here follow semver, while the paths of the modules they are defined in may
+//! change between releases.
+//!
+//! The instruction builders, the errors and the diff are only available without the `sdk`
+//! feature.
+
+pub use crate::args::*;
+#[cfg(not(feature = "sdk"))]
+pub use crate::error::DlpError;
+#[cfg(not(feature = "sdk"))]
+pub use crate::instruction_builder::*;
+pub use crate::pda::*;
+pub use crate::state::*;
+pub use crate::DirtyTracker;
+#[cfg(not(feature = "sdk"))]
+pub use crate::{
+    apply_diff_copy, apply_diff_in_place, compute_diff, merge_diff_copy, DiffSet, SizeChanged,
+};
+pub use crate::{id, ID};
@@ -0,0 +1,158 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::DelegationAuthorityArgs;
+use crate::error::DlpError::{InvalidAuthority, TooManyDelegationAuthorities};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_pda, load_program, load_signer,
+};
+use crate::processor::utils::pda::{create_pda, resize_funded_pda};
+use crate::state::{DelegationAuthorities, DelegationRecord, MAX_DELEGATION_AUTHORITIES};
+use crate::{
+    delegation_authorities_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_add_delegation_authority]
+pub const ADD_DELEGATION_AUTHORITY_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::writable("delegation authorities"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Add a validator to the authorities of a delegation, which can commit the delegated
+/// account besides the authority of its delegation record, see [DelegationAuthorities]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the authority of the delegation, paying the growth of the
+///                         delegation authorities
+/// 1: `[]`                 the delegated account
+/// 2: `[]`                 the delegation record
+/// 3: `[writable]`         the delegation authorities PDA of the delegated account
+/// 4: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - delegated account is owned by the delegation program
+/// - delegation record is initialized
+/// - signer is the authority of the delegation record
+/// - added validator is not the authority of the delegation record
+/// - delegation authorities are initialized or owned by the system program in which case
+///   they are created
+/// - delegation holds at most [MAX_DELEGATION_AUTHORITIES] authorities
+///
+/// Steps:
+///
+/// 1. Load the delegation authorities or create them, the authorities of a previous
+///    delegation of the account being cleared
+/// 2. Add the validator
+/// 3. Resize the delegation authorities, the signer paying the rent of their growth
+pub fn process_add_delegation_authority(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = DelegationAuthorityArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, delegated_account, delegation_record_account, delegation_authorities_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "delegation authority")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    let delegation_authorities_bump = load_pda(
+        delegation_authorities_account,
+        delegation_authorities_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation authorities",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    if !delegation_record.authority.eq(authority.key) {
+        msg!(
+            "Expected delegation authority to be {}, but got {}",
+            delegation_record.authority,
+            authority.key
+        );
+        return Err(InvalidAuthority.into());
+    }
+    if args.validator.eq(authority.key) {
+        msg!(
+            "{} is already the authority of the delegation",
+            args.validator
+        );
+        return Err(InvalidAuthority.into());
+    }
+
+    // Create the delegation authorities if they don't exist
+    let mut delegation_authorities = DelegationAuthorities {
+        delegated_account: *delegated_account.key,
+        delegation_slot: delegation_record.delegation_slot,
+        authorities: vec![],
+    };
+    if delegation_authorities_account.owner.eq(system_program.key) {
+        create_pda(
+            delegation_authorities_account,
+            &crate::id(),
+            delegation_authorities.size_with_discriminator(),
+            delegation_authorities_seeds_from_delegated_account!(delegated_account.key),
+            delegation_authorities_bump,
+            system_program,
+            authority,
+        )?;
+    } else {
+        let delegation_authorities_data = delegation_authorities_account.try_borrow_data()?;
+        let previous =
+            DelegationAuthorities::try_from_bytes_with_discriminator(&delegation_authorities_data)?;
+        // The authorities of a previous delegation of the account are cleared
+        if previous.delegation_slot == delegation_record.delegation_slot {
+            delegation_authorities = previous;
+        }
+    }
+
+    delegation_authorities.add(args.validator);
+    if delegation_authorities.authorities.len() > MAX_DELEGATION_AUTHORITIES {
+        msg!(
+            "delegation can have at most {} authorities",
+            MAX_DELEGATION_AUTHORITIES
+        );
+        return Err(TooManyDelegationAuthorities.into());
+    }
+
+    // Resize the delegation authorities, exchanging the rent of their growth with the signer
+    resize_funded_pda(
+        authority,
+        delegation_authorities_account,
+        system_program,
+        delegation_authorities.size_with_discriminator(),
+    )?;
+
+    let mut delegation_authorities_data = delegation_authorities_account.try_borrow_mut_data()?;
+    delegation_authorities
+        .to_bytes_with_discriminator(&mut delegation_authorities_data.as_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::ApproveUndelegateAndCloseArgs;
+use crate::delegation_metadata_seeds_from_delegated_account;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program, load_signer,
+};
+use crate::processor::utils::pda::resize_pda;
+use crate::state::DelegationMetadata;
+
+/// Accounts of [process_approve_undelegate_and_close]
+pub const APPROVE_UNDELEGATE_AND_CLOSE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("payer"),
+    AccountSpec::signer("delegated account"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Approve closing a delegated account on undelegation, see
+/// [crate::processor::fast::process_undelegate_and_close]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the payer, funding the growth of the delegation metadata
+/// 1: `[signer]`           the delegated account
+/// 2: `[writable]`         the delegation metadata
+/// 3: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - delegated account is owned by the delegation program and signs, which is only
+///   possible if the owner program approves the close via CPI (or if the delegated
+///   account is on curve)
+/// - delegation metadata is initialized
+///
+/// Steps:
+///
+/// 1. Set the close destination in the delegation metadata, resizing it if necessary
+pub fn process_approve_undelegate_and_close(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = ApproveUndelegateAndCloseArgs::try_from_slice(data)?;
+
+    let [payer, delegated_account, delegation_metadata_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+    load_signer(delegated_account, "delegated account")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let mut delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    delegation_metadata.close_destination = Some(args.close_destination);
+
+    resize_pda(
+        payer,
+        delegation_metadata_account,
+        system_program,
+        delegation_metadata.serialized_size(),
+    )?;
+    let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+    delegation_metadata.to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+
+    Ok(())
+}
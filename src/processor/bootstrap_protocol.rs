@@ -0,0 +1,140 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::error::DlpError::Unauthorized;
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_pda, load_program, load_program_upgrade_authority, load_signer,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::{FeatureGates, ProtocolConfig};
+use crate::{feature_gates_seeds, fees_vault_seeds, protocol_config_seeds};
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_bootstrap_protocol]
+pub const BOOTSTRAP_PROTOCOL_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("admin"),
+    AccountSpec::writable("protocol fees vault"),
+    AccountSpec::writable("feature gates"),
+    AccountSpec::writable("protocol config"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Initialize the core protocol PDAs of a new deployment in one transaction
+///
+/// Accounts:
+///
+/// 0: `[signer]`   admin account paying for the PDAs
+/// 1: `[writable]` fees vault PDA
+/// 2: `[writable]` feature gates PDA
+/// 3: `[writable]` protocol config PDA
+/// 4: `[]`         delegation program data
+/// 5: `[]`         system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+///
+/// NOTE: this operation is idempotent, the PDAs which are already initialized are left
+/// untouched
+///
+/// Steps:
+///
+/// 1. Create the fees vault if it doesn't exist
+/// 2. Create the feature gates with every instruction enabled if they don't exist
+/// 3. Create the default protocol config if it doesn't exist
+pub fn process_bootstrap_protocol(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [admin, fees_vault, feature_gates_account, protocol_config_account, delegation_program_data, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    // Create the fees vault if it doesn't exist
+    let fees_vault_bump = load_pda(
+        fees_vault,
+        fees_vault_seeds!(),
+        &crate::id(),
+        true,
+        "fees vault",
+    )?;
+    if fees_vault.owner.eq(system_program.key) {
+        create_pda(
+            fees_vault,
+            &crate::id(),
+            8,
+            fees_vault_seeds!(),
+            fees_vault_bump,
+            system_program,
+            admin,
+        )?;
+    }
+
+    // Create the feature gates if they don't exist
+    let feature_gates_bump = load_pda(
+        feature_gates_account,
+        feature_gates_seeds!(),
+        &crate::id(),
+        true,
+        "feature gates",
+    )?;
+    if feature_gates_account.owner.eq(system_program.key) {
+        create_pda(
+            feature_gates_account,
+            &crate::id(),
+            FeatureGates::size_with_discriminator(),
+            feature_gates_seeds!(),
+            feature_gates_bump,
+            system_program,
+            admin,
+        )?;
+        let mut feature_gates_data = feature_gates_account.try_borrow_mut_data()?;
+        FeatureGates::default().to_bytes_with_discriminator(&mut feature_gates_data)?;
+    }
+
+    // Create the protocol config if it doesn't exist
+    let protocol_config_bump = load_pda(
+        protocol_config_account,
+        protocol_config_seeds!(),
+        &crate::id(),
+        true,
+        "protocol config",
+    )?;
+    if protocol_config_account.owner.eq(system_program.key) {
+        create_pda(
+            protocol_config_account,
+            &crate::id(),
+            ProtocolConfig::size_with_discriminator(),
+            protocol_config_seeds!(),
+            protocol_config_bump,
+            system_program,
+            admin,
+        )?;
+        let mut protocol_config_data = protocol_config_account.try_borrow_mut_data()?;
+        ProtocolConfig::default().to_bytes_with_discriminator(&mut protocol_config_data)?;
+    }
+
+    Ok(())
+}
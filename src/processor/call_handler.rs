@@ -1,21 +1,45 @@
-use crate::args::CallHandlerArgs;
-use crate::ephemeral_balance_seeds_from_payer;
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::{CallHandlerArgs, CallHandlerContext};
+use crate::discriminator::DlpDiscriminator;
+use crate::error::DlpError::{
+    CallHandlerContextDisabled, CallHandlerContextMismatch, EscrowSpendMismatch,
+};
+use crate::processor::utils::commit_session::dlp_discriminator;
 use crate::processor::utils::loaders::{
     load_initialized_validator_fees_vault, load_owned_pda, load_pda, load_signer,
 };
+use crate::state::{AccountDiscriminator, CallHandlerPermissions, SessionReport};
+use crate::{
+    call_handler_permissions_seeds_from_escrow, ephemeral_balance_seeds_from_payer,
+    session_report_seeds_from_escrow,
+};
+use alloc::vec::Vec;
 
-use borsh::BorshDeserialize;
+use crate::log::msg;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::program::invoke_signed;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
-use solana_program::{msg, system_program};
+use solana_program::system_program;
+use solana_program::sysvar::instructions::{self, load_instruction_at_checked};
+use solana_program::{clock::Clock, sysvar::Sysvar};
 
 pub const INVALID_ESCROW_PDA: &str = "invalid escrow pda in CallHandler";
 pub const INVALID_ESCROW_OWNER: &str = "escrow can not be delegated in CallHandler";
 
+/// Accounts of [process_call_handler]
+pub const CALL_HANDLER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::readonly("validator fees vault"),
+    AccountSpec::readonly("destination program"),
+    AccountSpec::readonly("escrow authority"),
+    AccountSpec::writable("escrow"),
+    AccountSpec::readonly("instructions sysvar"),
+    AccountSpec::readonly("call handler permissions"),
+];
+
 /// Calls a handler on user specified program
 ///
 /// Accounts:
@@ -24,20 +48,34 @@ pub const INVALID_ESCROW_OWNER: &str = "escrow can not be delegated in CallHandl
 /// 2: `[]`         destination program of an action
 /// 3: `[]`         escrow authority account which created escrow account
 /// 4: `[writable]` non delegated escrow pda created from 3
-/// 5: `[readonly/writable]` other accounts needed for action
-/// 6: `[readonly/writable]` other accounts needed for action
-/// 7: ...
+/// 5: `[]`         the instructions sysvar the contexts of the call are derived from
+/// 6: `[]`         the call handler permissions of the escrow, uninitialized until its
+///                 authority sets them, see [CallHandlerPermissions]
+/// 7: `[readonly/writable]` other accounts needed for action
+/// 8: `[readonly/writable]` other accounts needed for action
+/// 9: ...
+/// n: `[writable]` (optional) the session report of the escrow, passed last and not forwarded
+///                 to the handler, see [SessionReport]
 ///
 /// Requirements:
 ///
 /// - escrow account initialized
 /// - escrow account not delegated
 /// - validator as a caller
+/// - contexts of the transaction enabled by the call handler permissions of the escrow, if
+///   initialized, see [CallHandlerPermissions]
+/// - declared context of the call, if any, among the contexts of the transaction
+/// - lamports spent by the escrow through the handler equal to the declared escrow spend,
+///   if any, see [CallHandlerArgs::escrow_spend]
 ///
 /// Steps:
 /// 1. Verify that signer is a valid registered validator
 /// 2. Verify escrow pda exists and not delegated
-/// 3. Invoke signed on behalf of escrow pda user specified action
+/// 3. Derive the contexts of the call from the transaction and verify that the escrow
+///    authority enabled them, if the call handler permissions are initialized
+/// 4. Invoke signed on behalf of escrow pda user specified action
+/// 5. Verify the lamports spent by the escrow against the declared escrow spend, if any
+/// 6. Record the lamports spent by the escrow in its session report, if provided
 ///
 /// Usage:
 ///
@@ -48,21 +86,25 @@ pub fn process_call_handler(
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    const OTHER_ACCOUNTS_OFFSET: usize = 5;
+    const OTHER_ACCOUNTS_OFFSET: usize = 7;
 
-    if accounts.len() < OTHER_ACCOUNTS_OFFSET {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    }
-
-    let (
-        [validator, validator_fees_vault, destination_program, escrow_authority_account, escrow_account],
+    let Some((
+        [validator, validator_fees_vault, destination_program, escrow_authority_account, escrow_account, instructions_sysvar, call_handler_permissions_account],
         other_accounts,
-    ) = accounts.split_at(OTHER_ACCOUNTS_OFFSET)
+    )) = accounts.split_first_chunk::<OTHER_ACCOUNTS_OFFSET>()
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    let args = CallHandlerArgs::try_from_slice(data)?;
+    let args = CallHandlerArgs::try_from_instruction_data(data)?;
+
+    // the session report of the escrow is an optional trailing account
+    let (other_accounts, session_report) = match other_accounts.split_last() {
+        Some((session_report, other_accounts)) if is_session_report(session_report) => {
+            (other_accounts, Some(session_report))
+        }
+        _ => (other_accounts, None),
+    };
 
     // verify account is a signer
     load_signer(validator, "validator")?;
@@ -89,6 +131,50 @@ pub fn process_call_handler(
     )?;
     load_owned_pda(escrow_account, &system_program::id(), INVALID_ESCROW_OWNER)?;
 
+    // verify the escrow authority enabled the contexts of the transaction, all contexts
+    // being enabled until it sets the permissions
+    if !instructions_sysvar.key.eq(&instructions::id()) {
+        msg!("{} is not the instructions sysvar", instructions_sysvar.key);
+        return Err(ProgramError::UnsupportedSysvar);
+    }
+    load_pda(
+        call_handler_permissions_account,
+        call_handler_permissions_seeds_from_escrow!(escrow_account.key),
+        &crate::id(),
+        false,
+        "call handler permissions",
+    )?;
+    let contexts = transaction_contexts(instructions_sysvar)?;
+    if let Some(context) = args.context {
+        if !contexts.is_enabled(context) {
+            msg!(
+                "{:?} context is declared outside of a transaction of this context",
+                context
+            );
+            return Err(CallHandlerContextMismatch.into());
+        }
+    }
+    if call_handler_permissions_account.owner.eq(&crate::id()) {
+        let call_handler_permissions_data = call_handler_permissions_account.try_borrow_data()?;
+        let call_handler_permissions = CallHandlerPermissions::try_from_bytes_with_discriminator(
+            &call_handler_permissions_data,
+        )?;
+        for context in [
+            CallHandlerContext::Commit,
+            CallHandlerContext::Undelegate,
+            CallHandlerContext::Standalone,
+        ] {
+            if contexts.is_enabled(context) && !call_handler_permissions.is_enabled(context) {
+                msg!(
+                    "{:?} context is disabled for the escrow {}",
+                    context,
+                    escrow_account.key
+                );
+                return Err(CallHandlerContextDisabled.into());
+            }
+        }
+    }
+
     // deduce necessary accounts for CPI
     let (accounts_meta, handler_accounts): (Vec<AccountMeta>, Vec<AccountInfo>) = other_accounts
         .iter()
@@ -115,9 +201,92 @@ pub fn process_call_handler(
     let bump_slice = &[escrow_bump];
     let escrow_signer_seeds = [escrow_seeds, &[bump_slice]].concat();
 
+    let escrow_lamports = escrow_account.lamports();
     invoke_signed(
         &handler_instruction,
         &handler_accounts,
         &[&escrow_signer_seeds],
-    )
+    )?;
+    let spent = escrow_lamports.saturating_sub(escrow_account.lamports());
+
+    // The finalize of a commit of the escrow relies on the declared spend, see
+    // [crate::args::CommitStateArgs::escrow_spend]
+    if let Some(escrow_spend) = args.escrow_spend {
+        if spent != escrow_spend {
+            msg!(
+                "escrow spent {} lamports, declared {} lamports",
+                spent,
+                escrow_spend
+            );
+            return Err(EscrowSpendMismatch.into());
+        }
+    }
+
+    if let Some(session_report) = session_report {
+        load_pda(
+            session_report,
+            session_report_seeds_from_escrow!(escrow_account.key),
+            &crate::id(),
+            true,
+            "session report",
+        )?;
+        let mut session_report_data = session_report.try_borrow_mut_data()?;
+        SessionReport::try_from_bytes_with_discriminator_mut(&mut session_report_data)?
+            .record_spend(spent, Clock::get()?.slot);
+    }
+    Ok(())
+}
+
+/// The session report is recognized by its owner and discriminator, as the other accounts
+/// can be owned by the delegation program too
+fn is_session_report(info: &AccountInfo) -> bool {
+    info.owner.eq(&crate::id())
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.len() == SessionReport::size_with_discriminator()
+                && data.starts_with(&AccountDiscriminator::SessionReport.to_bytes())
+        })
+}
+
+/// Derive the contexts a call handler is called in from the top level instructions of the
+/// delegation program in the transaction, so that the validator cannot declare a context
+/// the escrow authority enabled in place of the one it disabled. A transaction
+/// undelegating is of the undelegate context, one committing or finalizing is of the
+/// commit context, and one doing neither is standalone.
+fn transaction_contexts(
+    instructions_sysvar: &AccountInfo,
+) -> Result<CallHandlerPermissions, ProgramError> {
+    let mut contexts = CallHandlerPermissions {
+        enabled_contexts: 0,
+        ..Default::default()
+    };
+    for index in 0.. {
+        let Ok(ix) = load_instruction_at_checked(index, instructions_sysvar) else {
+            break;
+        };
+        match dlp_discriminator(&ix) {
+            Some(
+                DlpDiscriminator::Undelegate
+                | DlpDiscriminator::UndelegateAndClose
+                | DlpDiscriminator::UndelegateStage1
+                | DlpDiscriminator::UndelegateStage2,
+            ) => contexts.set_enabled(CallHandlerContext::Undelegate, true),
+            Some(
+                DlpDiscriminator::CommitState
+                | DlpDiscriminator::CommitStateFromBuffer
+                | DlpDiscriminator::CommitDiff
+                | DlpDiscriminator::CommitDiffFromBuffer
+                | DlpDiscriminator::CommitDiffShadow
+                | DlpDiscriminator::CommitStateRoot
+                | DlpDiscriminator::CommitStateChunk
+                | DlpDiscriminator::Finalize
+                | DlpDiscriminator::CommitFinalize
+                | DlpDiscriminator::CrankFinalize,
+            ) => contexts.set_enabled(CallHandlerContext::Commit, true),
+            _ => {}
+        }
+    }
+    if contexts.enabled_contexts == 0 {
+        contexts.set_enabled(CallHandlerContext::Standalone, true);
+    }
+    Ok(contexts)
 }
@@ -0,0 +1,78 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::error::DlpError::Unauthorized;
+use crate::log::msg;
+use crate::parked_undelegation_seeds_from_delegated_account;
+use crate::processor::utils::loaders::{load_initialized_pda, load_signer};
+use crate::processor::utils::pda::close_pda;
+use crate::state::ParkedUndelegation;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+/// Accounts of [process_claim_parked_undelegation]
+pub const CLAIM_PARKED_UNDELEGATION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("rent payer"),
+    AccountSpec::readonly("undelegated account"),
+    AccountSpec::writable("parked undelegation"),
+];
+
+/// Claim the lamports of an account undelegated to the parked undelegation PDA, see
+/// [crate::args::UndelegateMode::ToBuffer]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the rent payer of the undelegated delegation
+/// 1: `[]`                 the undelegated account
+/// 2: `[writable]`         the parked undelegation PDA of the undelegated account
+///
+/// Requirements:
+///
+/// - the parked undelegation is initialized
+/// - the rent payer is the one recorded in the parked undelegation
+///
+/// Steps:
+///
+/// 1. Close the parked undelegation, moving its lamports, which include the ones of the
+///    undelegated account, to the rent payer
+///
+/// Usage:
+///
+/// The parked data follows the header of the parked undelegation, see
+/// [ParkedUndelegation::parked_data], and is to be read before claiming it.
+pub fn process_claim_parked_undelegation(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [rent_payer, delegated_account, parked_undelegation_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(rent_payer, "rent payer")?;
+    load_initialized_pda(
+        parked_undelegation_account,
+        parked_undelegation_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "parked undelegation",
+    )?;
+
+    {
+        let parked_undelegation_data = parked_undelegation_account.try_borrow_data()?;
+        let parked_undelegation = ParkedUndelegation::try_from_bytes_with_discriminator(
+            parked_undelegation_data
+                .get(..ParkedUndelegation::size_with_discriminator())
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )?;
+        if parked_undelegation.rent_payer != *rent_payer.key {
+            msg!(
+                "Expected the rent payer {} of the parked undelegation, got {}",
+                parked_undelegation.rent_payer,
+                rent_payer.key
+            );
+            return Err(Unauthorized.into());
+        }
+    }
+
+    close_pda(parked_undelegation_account, rent_payer)
+}
@@ -0,0 +1,53 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::commit_buffer_seeds_from_delegated_account_and_authority;
+use crate::processor::utils::loaders::{load_initialized_pda, load_signer};
+use crate::processor::utils::pda::close_pda;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+/// Accounts of [process_close_commit_buffer]
+pub const CLOSE_COMMIT_BUFFER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("commit buffer"),
+];
+
+/// Close a commit buffer, refunding its rent to its authority
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the authority of the commit buffer
+/// 1: `[]`                 the delegated account
+/// 2: `[writable]`         the commit buffer PDA of the delegated account and the authority
+///
+/// Requirements:
+///
+/// - the commit buffer is initialized, its PDA being derived from the authority
+///
+/// Steps:
+///
+/// 1. Close the commit buffer, refunding its rent to the authority
+pub fn process_close_commit_buffer(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [authority, delegated_account, commit_buffer_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    load_initialized_pda(
+        commit_buffer_account,
+        commit_buffer_seeds_from_delegated_account_and_authority!(
+            delegated_account.key,
+            authority.key
+        ),
+        &crate::id(),
+        true,
+        "commit buffer",
+    )?;
+
+    close_pda(commit_buffer_account, authority)
+}
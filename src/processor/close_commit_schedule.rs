@@ -0,0 +1,108 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::error::DlpError::Unauthorized;
+use crate::processor::utils::loaders::{load_initialized_pda, load_pda, load_program, load_signer};
+use crate::processor::utils::pda::{close_pda, resize_pda};
+use crate::state::{CommitSchedule, DelegationMetadata};
+use crate::{
+    commit_schedule_seeds_from_delegated_account, delegation_metadata_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_close_commit_schedule]
+pub const CLOSE_COMMIT_SCHEDULE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("payer"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::writable("commit schedule"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Close the commit schedule of a delegated account, see [CommitSchedule]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the payer of the escrow of the commit schedule, refunded its rent
+/// 1: `[]`                 the delegated account
+/// 2: `[writable]`         the delegation metadata, which may be closed
+/// 3: `[writable]`         the commit schedule PDA
+/// 4: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - commit schedule is initialized
+/// - escrow payer is the one of the commit schedule
+///
+/// Steps:
+///
+/// 1. If the account is still delegated, mark its commits as no longer scheduled in the
+///    delegation metadata, shrinking it if necessary
+/// 2. Close the commit schedule
+///
+/// Note that the pending commit funded by the escrow, if any, still refunds it at finalize.
+pub fn process_close_commit_schedule(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [escrow_payer, delegated_account, delegation_metadata_account, commit_schedule_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(escrow_payer, "escrow payer")?;
+    load_initialized_pda(
+        commit_schedule_account,
+        commit_schedule_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "commit schedule",
+    )?;
+    load_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    {
+        let commit_schedule_data = commit_schedule_account.try_borrow_data()?;
+        let commit_schedule =
+            CommitSchedule::try_from_bytes_with_discriminator(&commit_schedule_data)?;
+        if !commit_schedule.escrow_payer.eq(escrow_payer.key) {
+            msg!(
+                "Expected escrow payer: {} but got {}",
+                commit_schedule.escrow_payer,
+                escrow_payer.key
+            );
+            return Err(Unauthorized.into());
+        }
+    }
+
+    // The delegation metadata is closed once the account is undelegated
+    if delegation_metadata_account.owner.eq(&crate::id()) {
+        let mut delegation_metadata = {
+            let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+            DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+        };
+        delegation_metadata.commit_scheduled = false;
+        resize_pda(
+            escrow_payer,
+            delegation_metadata_account,
+            system_program,
+            delegation_metadata.serialized_size(),
+        )?;
+        let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+        delegation_metadata.to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+    }
+
+    close_pda(commit_schedule_account, escrow_payer)
+}
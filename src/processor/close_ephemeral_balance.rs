@@ -1,6 +1,7 @@
-use crate::ephemeral_balance_seeds_from_payer;
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use crate::pda::EPHEMERAL_BALANCE_TAG;
 use crate::processor::utils::loaders::{load_pda, load_signer};
-use solana_program::msg;
 use solana_program::program::invoke_signed;
 use solana_program::program_error::ProgramError;
 use solana_program::system_instruction::transfer;
@@ -8,6 +9,13 @@ use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
 };
 
+/// Accounts of [process_close_ephemeral_balance]
+pub const CLOSE_EPHEMERAL_BALANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("payer"),
+    AccountSpec::writable("ephemeral balance"),
+    AccountSpec::readonly("system program"),
+];
+
 /// Process the closing of an ephemeral balance account
 ///
 /// Accounts:
@@ -16,6 +24,9 @@ use solana_program::{
 /// 1: `[writable]` ephemeral balance account we are closing
 /// 2: `[]` the system program
 ///
+/// The data is the index of the ephemeral balance account, a u8 or, for the escrows of the
+/// wider index space, a u32 in little endian.
+///
 /// Requirements:
 ///
 /// - ephemeral balance account is initialized
@@ -29,7 +40,11 @@ pub fn process_close_ephemeral_balance(
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    let index = *data.first().ok_or(ProgramError::InvalidInstructionData)?;
+    let index: &[u8] = match data {
+        [_, _, _, _] => data,
+        [index, ..] => core::slice::from_ref(index),
+        [] => return Err(ProgramError::InvalidInstructionData),
+    };
 
     // Load Accounts
     let [payer, ephemeral_balance_account, system_program] = accounts else {
@@ -38,7 +53,7 @@ pub fn process_close_ephemeral_balance(
 
     load_signer(payer, "payer")?;
 
-    let ephemeral_balance_seeds: &[&[u8]] = ephemeral_balance_seeds_from_payer!(payer.key, index);
+    let ephemeral_balance_seeds: &[&[u8]] = &[EPHEMERAL_BALANCE_TAG, payer.key.as_ref(), index];
     let ephemeral_balance_bump = load_pda(
         ephemeral_balance_account,
         ephemeral_balance_seeds,
@@ -0,0 +1,111 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::{to_vec, BorshDeserialize};
+use solana_program::log::sol_log_data;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::args::CloseProgramConfigArgs;
+use crate::error::DlpError::ActiveDelegationsNotAttested;
+use crate::events::{EventDiscriminator, ProgramConfigClosedEvent};
+use crate::processor::utils::loaders::{load_initialized_pda, load_signer};
+use crate::processor::utils::pda::close_pda;
+use crate::processor::whitelist_validator_for_program::validate_authority;
+use crate::program_config_seeds_from_program_id;
+use crate::state::ProgramConfig;
+
+/// Accounts of [process_close_program_config]
+pub const CLOSE_PROGRAM_CONFIG_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("authority"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program data"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("program config"),
+];
+
+/// Close the program config of a program, refunding its rent to the authority
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` authority that has rights to configure the program
+/// 1: `[]`         program the config belongs to
+/// 2: `[]`         program data account
+/// 3: `[]`         delegation program data account
+/// 4: `[writable]` program config PDA
+///
+/// Requirements:
+///
+/// - authority is either the ADMIN_PUBKEY or the program upgrade authority
+/// - authority attests that no account of the program is delegated anymore, as the
+///   undelegations of its accounts would otherwise run without the config they were
+///   delegated under
+/// - program config is initialized
+///
+/// Steps:
+///
+/// 1. Load the authority and validate it
+/// 2. Close the program config, refunding its rent to the authority
+/// 3. Emit a [ProgramConfigClosedEvent]
+///
+/// The validator whitelist shards of the program, see
+/// [crate::processor::process_whitelist_validator_shard_for_program], are not closed.
+pub fn process_close_program_config(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = CloseProgramConfigArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, program, program_data, delegation_program_data, program_config_account] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    validate_authority(authority, program, program_data, delegation_program_data)?;
+    load_initialized_pda(
+        program_config_account,
+        program_config_seeds_from_program_id!(program.key),
+        &crate::id(),
+        true,
+        "program config",
+    )?;
+
+    if !args.no_active_delegations {
+        msg!(
+            "Authority {} did not attest that program {} has no active delegations",
+            authority.key,
+            program.key
+        );
+        return Err(ActiveDelegationsNotAttested.into());
+    }
+
+    // Make sure the account is a program config
+    {
+        let program_config_data = program_config_account.try_borrow_data()?;
+        ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)?;
+    }
+
+    let lamports = program_config_account.lamports();
+    close_pda(program_config_account, authority)?;
+
+    let event = ProgramConfigClosedEvent {
+        authority: *authority.key,
+        program: *program.key,
+        lamports,
+    };
+    msg!(
+        "Program config of {} closed, {} lamports refunded",
+        event.program,
+        event.lamports
+    );
+    sol_log_data(&[&[
+        vec![EventDiscriminator::ProgramConfigClosed.into()],
+        to_vec(&event)?,
+    ]
+    .concat()]);
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::CloseProgramEphemeralBalanceArgs;
+use crate::log::msg;
+use crate::processor::utils::loaders::{load_pda, load_signer};
+use crate::program_ephemeral_balance_seeds_from_payer;
+use borsh::BorshDeserialize;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::system_instruction::transfer;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_close_program_ephemeral_balance]
+pub const CLOSE_PROGRAM_EPHEMERAL_BALANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("payer"),
+    AccountSpec::writable("program ephemeral balance"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Process the closing of a program ephemeral balance account
+///
+/// Accounts:
+///
+/// 0: `[signer]` payer to pay for the transaction and receive the refund
+/// 1: `[writable]` program ephemeral balance account we are closing
+/// 2: `[]` the system program
+///
+/// Requirements:
+///
+/// - program ephemeral balance account is initialized and not delegated
+///
+/// Steps:
+///
+/// 1. Closes the program ephemeral balance account and refunds the payer with the
+///    escrowed lamports
+pub fn process_close_program_ephemeral_balance(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = CloseProgramEphemeralBalanceArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [payer, ephemeral_balance_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+
+    let ephemeral_balance_seeds: &[&[u8]] =
+        program_ephemeral_balance_seeds_from_payer!(payer.key, args.program_id, args.index);
+    let ephemeral_balance_bump = load_pda(
+        ephemeral_balance_account,
+        ephemeral_balance_seeds,
+        &crate::id(),
+        true,
+        "program ephemeral balance",
+    )?;
+    if ephemeral_balance_account.owner != &system_program::id() {
+        msg!(
+            "program ephemeral balance expected to be owned by system program. got: {}",
+            ephemeral_balance_account.owner
+        );
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let amount = ephemeral_balance_account.lamports();
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let ephemeral_balance_bump_slice: &[u8] = &[ephemeral_balance_bump];
+    let ephemeral_balance_signer_seeds =
+        [ephemeral_balance_seeds, &[ephemeral_balance_bump_slice]].concat();
+    invoke_signed(
+        &transfer(ephemeral_balance_account.key, payer.key, amount),
+        &[
+            ephemeral_balance_account.clone(),
+            payer.clone(),
+            system_program.clone(),
+        ],
+        &[&ephemeral_balance_signer_seeds],
+    )?;
+
+    Ok(())
+}
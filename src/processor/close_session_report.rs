@@ -0,0 +1,74 @@
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::error::DlpError::Unauthorized;
+use crate::log::msg;
+use crate::processor::utils::loaders::{load_initialized_pda, load_signer};
+use crate::processor::utils::pda::close_pda;
+use crate::session_report_seeds_from_escrow;
+use crate::state::SessionReport;
+
+/// Accounts of [process_close_session_report]
+pub const CLOSE_SESSION_REPORT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("escrow authority"),
+    AccountSpec::readonly("escrow"),
+    AccountSpec::writable("session report"),
+];
+
+/// Close the session report of an escrow, refunding its rent to the escrow authority
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the escrow authority
+/// 1: `[]`                 the escrow of the session report
+/// 2: `[writable]`         the session report PDA of the escrow
+///
+/// Requirements:
+///
+/// - session report is initialized
+/// - escrow authority is the one which created the session report
+///
+/// Steps:
+///
+/// 1. Close the session report
+///
+/// Usage:
+///
+/// The report is frozen by the undelegation of the escrow, see [SessionReport::frozen], and
+/// is meant to be read before closing it. A new one can then be created for the next session.
+pub fn process_close_session_report(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [escrow_authority, escrow, session_report_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(escrow_authority, "escrow authority")?;
+    load_initialized_pda(
+        session_report_account,
+        session_report_seeds_from_escrow!(escrow.key),
+        &crate::id(),
+        true,
+        "session report",
+    )?;
+
+    {
+        let session_report_data = session_report_account.try_borrow_data()?;
+        let session_report =
+            SessionReport::try_from_bytes_with_discriminator(&session_report_data)?;
+        if session_report.authority != *escrow_authority.key {
+            msg!(
+                "Expected the escrow authority {} of the session report, got {}",
+                session_report.authority,
+                escrow_authority.key
+            );
+            return Err(Unauthorized.into());
+        }
+    }
+
+    close_pda(session_report_account, escrow_authority)
+}
@@ -0,0 +1,137 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::error::DlpError::InvalidStreamedCommitState;
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_commit_authority, load_initialized_pda, load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::close_pda;
+use crate::state::{DelegationRecord, StreamedCommitState};
+use crate::{
+    commit_record_seeds_from_delegated_account, commit_state_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+/// Accounts of [process_close_streamed_commit_state]
+pub const CLOSE_STREAMED_COMMIT_STATE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("commit state"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::writable("payer"),
+];
+
+/// Close a streamed commit state abandoned before being committed, refunding its rent to its
+/// payer
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the validator or one of its commit relayers or, once the commit timeout of
+///                 the delegation elapsed since the last chunk streamed, anyone
+/// 1: `[]`         the delegated account
+/// 2: `[writable]` the commit state PDA
+/// 3: `[]`         the commit record PDA
+/// 4: `[]`         the delegation record
+/// 5: `[writable]` the payer recorded in the streamed commit state
+/// 6: `[]`         (optional) the validator fees vault of the delegation authority,
+///                 required if a commit relayer signs, see
+///                 [crate::processor::process_register_commit_relayer]
+///
+/// Requirements:
+///
+/// - delegation record is initialized
+/// - commit record is uninitialized, i.e. the stream was not committed
+/// - commit state is a streamed commit state and its payer is passed
+/// - the signer is the delegation authority or one of its approved commit relayers, unless
+///   the commit timeout of the delegation elapsed since the last chunk streamed, see
+///   [DelegationRecord::is_commit_timed_out]
+///
+/// Steps:
+///
+/// 1. Close the commit state, refunding its rent to its payer
+///
+/// Usage:
+///
+/// A stream left behind by a validator blocks the undelegation of the account, which requires
+/// the commit state to be uninitialized, and can not be finalized without a commit record.
+/// Closing it lets the account be committed or undelegated again.
+pub fn process_close_streamed_commit_state(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [authority, delegated_account, commit_state_account, commit_record_account, delegation_record_account, payer, relayer_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+    load_initialized_pda(
+        commit_state_account,
+        commit_state_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "commit state",
+    )?;
+
+    let streamed_commit_state = {
+        let commit_state_data = commit_state_account.try_borrow_data()?;
+        if StreamedCommitState::streamed_data(&commit_state_data).is_none() {
+            msg!("Commit state {} is not streamed", commit_state_account.key);
+            return Err(InvalidStreamedCommitState.into());
+        }
+        *StreamedCommitState::try_from_bytes_with_discriminator(
+            commit_state_data
+                .get(..StreamedCommitState::size_with_discriminator())
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )?
+    };
+    if !streamed_commit_state.payer.eq(payer.key) {
+        msg!(
+            "Expected streamed commit state payer to be {}, but got {}",
+            streamed_commit_state.payer,
+            payer.key
+        );
+        return Err(InvalidStreamedCommitState.into());
+    }
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    let timed_out = delegation_record.is_commit_timed_out(
+        Some(streamed_commit_state.last_update_slot),
+        Clock::get()?.slot,
+    );
+    // The delegation authority can close the stream at any time, anyone else only once the
+    // validator stopped streaming for the commit timeout
+    if !timed_out {
+        load_commit_authority(
+            authority,
+            &delegation_record.authority,
+            relayer_accounts.first(),
+        )?;
+    }
+
+    close_pda(commit_state_account, payer)
+}
@@ -1,4 +1,5 @@
-use solana_program::msg;
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
 
@@ -9,14 +10,24 @@ use crate::processor::utils::loaders::{
 use crate::processor::utils::pda::close_pda;
 use crate::validator_fees_vault_seeds_from_validator;
 
+/// Accounts of [process_close_validator_fees_vault]
+pub const CLOSE_VALIDATOR_FEES_VAULT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("payer"),
+    AccountSpec::signer("admin"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("validator identity"),
+    AccountSpec::writable("validator fees vault"),
+];
+
 /// Process the close of the validator fees vault
 ///
 /// Accounts:
 ///
-/// 0; `[signer]` payer
-/// 1; `[signer]` admin that controls the vault
-/// 2; `[]`       validator_identity
-/// 3; `[]`       validator_fees_vault_pda
+/// 0: `[signer]`   payer
+/// 1: `[signer]`   admin that controls the vault
+/// 2: `[]`         delegation program data
+/// 3: `[writable]` validator identity, refunded the rent of the vault
+/// 4: `[writable]` validator fees vault PDA
 ///
 /// Requirements:
 ///
@@ -0,0 +1,232 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::CommitFromOwnerArgs;
+use crate::consts::OWNER_COMMIT_NONCE_FLAG;
+use crate::error::DlpError;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program, load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::{
+    CommitRecord, CommitStateLayout, DelegationMetadata, DelegationRecord, PendingState,
+    PENDING_STATE_FROM_OWNER,
+};
+use crate::{
+    commit_record_seeds_from_delegated_account, commit_state_seeds_from_delegated_account,
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_commit_from_owner]
+pub const COMMIT_FROM_OWNER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("payer"),
+    AccountSpec::signer("delegated account"),
+    AccountSpec::writable("commit state"),
+    AccountSpec::writable("commit record"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Push a state to a delegated PDA from its owner program, which signs for the PDA in a CPI,
+/// e.g. to apply a governance mandated correction while the account is delegated
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the payer of the rent of the commit PDAs, refunded at finalize
+/// 1: `[signer]`   the delegated account, signed by its owner program
+/// 2: `[writable]` the PDA storing the pushed state
+/// 3: `[writable]` the PDA storing the commit record
+/// 4: `[]`         the delegation record
+/// 5: `[]`         the delegation metadata
+/// 6: `[]`         the system program
+///
+/// Requirements:
+///
+/// - delegated account is a PDA of a program, on curve accounts having no owner program
+/// - delegated account is not undelegatable
+/// - there is no pending commit, a pending commit of the validator being finalized first
+/// - nonce is the next nonce of the commits of the account with [OWNER_COMMIT_NONCE_FLAG] set
+/// - pushed state is within the maximum account size of the delegation, if it rejects
+///   oversized states
+/// - lamports of the delegation record keep the pushed state rent exempt
+///
+/// Steps:
+///
+/// 1. Create the commit state PDA holding the pushed state behind a
+///    [crate::state::PendingState] header flagging it as pushed by the owner program
+/// 2. Create the commit record, attributed to the delegation authority so that its
+///    validator finalizes it, keeping the lamports of the delegation record and refunding
+///    its rent to the payer as the escrow of the commit
+///
+/// Conflict resolution:
+///
+/// - the pushed state is finalized as is with [crate::instruction_builder::finalize_owner_commit],
+///   the validator cannot amend it
+/// - once finalized, the pushed commit took the next nonce of the account, so that a commit
+///   of the validator which did not fold the pushed state in, reusing that nonce, is rejected
+/// - a pushed commit is rejected while the validator has a pending commit, and a pending
+///   pushed commit blocks the commits of the validator until it is finalized
+pub fn process_commit_from_owner(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = CommitFromOwnerArgs::try_from_slice(data)?;
+
+    let [payer, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+    load_signer(delegated_account, "delegated account")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation metadata",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    // An on curve account signs for itself, only a program can sign for its PDA
+    if delegation_record.owner.eq(&system_program::id()) {
+        msg!(
+            "Delegated account {} has no owner program",
+            delegated_account.key
+        );
+        return Err(DlpError::InvalidOwnerCommit.into());
+    }
+
+    let delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    if delegation_metadata.is_undelegatable {
+        msg!(
+            "Delegated account {} is undelegatable",
+            delegated_account.key
+        );
+        return Err(DlpError::AlreadyUndelegated.into());
+    }
+    let expected_nonce = delegation_metadata
+        .last_update_nonce
+        .checked_add(1)
+        .ok_or(DlpError::Overflow)?
+        | OWNER_COMMIT_NONCE_FLAG;
+    if args.nonce != expected_nonce {
+        msg!(
+            "Owner commit nonce {} is incorrect, expected {}",
+            args.nonce,
+            expected_nonce
+        );
+        return Err(DlpError::NonceOutOfOrder.into());
+    }
+
+    if let Some(max_account_size) = delegation_metadata.max_account_size {
+        if max_account_size.rejects(args.data.len()) {
+            msg!(
+                "Pushed data length {} exceeds the maximum account size {}",
+                args.data.len(),
+                max_account_size.max_len
+            );
+            return Err(DlpError::CommittedDataTooLarge.into());
+        }
+    }
+    let rent = Rent::get()?;
+    if delegation_record.lamports < rent.minimum_balance(args.data.len()) {
+        return Err(DlpError::CommittedLamportsNotRentExempt.into());
+    }
+
+    // A pending commit of the validator is finalized before the owner pushes a state
+    let commit_state_bump = load_uninitialized_pda(
+        commit_state_account,
+        commit_state_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "commit state",
+    )?;
+    let commit_record_bump = load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "commit record",
+    )?;
+
+    let pending_state = PendingState::new(
+        delegation_record.owner,
+        args.nonce,
+        args.data.len(),
+        PENDING_STATE_FROM_OWNER,
+    );
+    create_pda(
+        commit_state_account,
+        &crate::id(),
+        pending_state.account_size(),
+        commit_state_seeds_from_delegated_account!(delegated_account.key),
+        commit_state_bump,
+        system_program,
+        payer,
+    )?;
+    create_pda(
+        commit_record_account,
+        &crate::id(),
+        CommitRecord::size_with_discriminator(),
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        commit_record_bump,
+        system_program,
+        payer,
+    )?;
+
+    let commit_record = CommitRecord {
+        identity: delegation_record.authority,
+        account: *delegated_account.key,
+        nonce: args.nonce,
+        lamports: delegation_record.lamports,
+        slot: Clock::get()?.slot,
+        er_block_hash: [0; 32],
+        rent_advanced: commit_state_account
+            .lamports()
+            .checked_add(commit_record_account.lamports())
+            .ok_or(DlpError::Overflow)?,
+        escrow: *payer.key,
+        escrow_spend: 0,
+        has_escrow_spend: 0,
+        commit_state_layout: CommitStateLayout::Pending.into(),
+        padding: [0; 6],
+    };
+    let mut commit_record_data = commit_record_account.try_borrow_mut_data()?;
+    commit_record.to_bytes_with_discriminator(&mut commit_record_data)?;
+    let mut commit_state_data = commit_state_account.try_borrow_mut_data()?;
+    let (header, committed_data) = commit_state_data
+        .split_at_mut_checked(PendingState::size_with_discriminator())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    pending_state.to_bytes_with_discriminator(header)?;
+    committed_data.copy_from_slice(&args.data);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::error::DlpError::InvalidCommitSession;
+use crate::processor::utils::commit_session::{
+    dlp_discriminator, is_session_commit, session_account,
+};
+use crate::processor::utils::loaders::load_signer;
+
+/// Accounts of [process_commit_session_begin]
+pub const COMMIT_SESSION_BEGIN_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::readonly("instructions sysvar"),
+];
+
+/// Open a commit session, grouping the commits of several delegated accounts that must
+/// be committed together
+///
+/// Accounts:
+///
+/// 0: `[signer]` the validator requesting the commits
+/// 1: `[]`       the instructions sysvar
+///
+/// Requirements:
+///
+/// - a commit session end of the same validator follows in the transaction
+/// - every instruction between the begin and the end is a commit of the validator
+///   (commit state or commit diff, directly or from a buffer)
+/// - the session contains at least one commit
+/// - the delegated accounts of the commits are distinct and strictly ascending, so that
+///   the order of the commits is deterministic
+///
+/// Steps:
+///
+/// 1. Scan the instructions following the begin up to the session end
+///
+/// NOTE: finalize instructions are rejected inside a session. Since the transaction is
+///       atomic, if any commit of the session fails the commit PDAs already created by
+///       the session are reverted and can never be finalized.
+pub fn process_commit_session_begin(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [validator, instructions_sysvar] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+
+    let mut last_delegated_account: Option<Pubkey> = None;
+    for index in current_index + 1.. {
+        let Ok(ix) = load_instruction_at_checked(index, instructions_sysvar) else {
+            msg!("Commit session is not closed");
+            return Err(InvalidCommitSession.into());
+        };
+
+        match dlp_discriminator(&ix) {
+            Some(DlpDiscriminator::CommitSessionEnd) => {
+                if session_account(&ix, 0)? != *validator.key {
+                    msg!("Commit session is closed by another validator");
+                    return Err(InvalidCommitSession.into());
+                }
+                if last_delegated_account.is_none() {
+                    msg!("Commit session is empty");
+                    return Err(InvalidCommitSession.into());
+                }
+                return Ok(());
+            }
+            Some(discriminator) if is_session_commit(discriminator) => {
+                if session_account(&ix, 0)? != *validator.key {
+                    msg!("Commit session contains a commit of another validator");
+                    return Err(InvalidCommitSession.into());
+                }
+                let delegated_account = session_account(&ix, 1)?;
+                if last_delegated_account.is_some_and(|last| last >= delegated_account) {
+                    msg!(
+                        "Commit session accounts are not strictly ascending: {}",
+                        delegated_account
+                    );
+                    return Err(InvalidCommitSession.into());
+                }
+                last_delegated_account = Some(delegated_account);
+            }
+            _ => {
+                msg!("Commit session contains an instruction other than a commit");
+                return Err(InvalidCommitSession.into());
+            }
+        }
+    }
+
+    Err(InvalidCommitSession.into())
+}
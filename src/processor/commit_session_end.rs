@@ -0,0 +1,64 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::discriminator::DlpDiscriminator;
+use crate::error::DlpError::InvalidCommitSession;
+use crate::processor::utils::commit_session::{dlp_discriminator, session_account};
+use crate::processor::utils::loaders::load_signer;
+
+/// Accounts of [process_commit_session_end]
+pub const COMMIT_SESSION_END_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::readonly("instructions sysvar"),
+];
+
+/// Close a commit session opened by [crate::processor::process_commit_session_begin]
+///
+/// Accounts:
+///
+/// 0: `[signer]` the validator requesting the commits
+/// 1: `[]`       the instructions sysvar
+///
+/// Requirements:
+///
+/// - the closest session marker preceding the end is a commit session begin of the
+///   same validator, the begin validates the instructions of the session
+///
+/// Steps:
+///
+/// 1. Scan the instructions preceding the end up to the session begin
+pub fn process_commit_session_end(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [validator, instructions_sysvar] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+
+    for index in (0..current_index).rev() {
+        let ix = load_instruction_at_checked(index, instructions_sysvar)?;
+        match dlp_discriminator(&ix) {
+            Some(DlpDiscriminator::CommitSessionBegin) => {
+                if session_account(&ix, 0)? != *validator.key {
+                    msg!("Commit session is opened by another validator");
+                    return Err(InvalidCommitSession.into());
+                }
+                return Ok(());
+            }
+            Some(DlpDiscriminator::CommitSessionEnd) => break,
+            _ => {}
+        }
+    }
+
+    msg!("Commit session is not opened");
+    Err(InvalidCommitSession.into())
+}
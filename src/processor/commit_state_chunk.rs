@@ -0,0 +1,149 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::CommitStateChunkArgs;
+use crate::error::DlpError::{InvalidChunkProof, InvalidChunkedCommitState};
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_commit_authority, load_initialized_pda, load_owned_pda,
+};
+use crate::state::{verify_chunk_proof, ChunkedCommitState, CommitRecord, CommitStateLayout};
+use crate::{
+    commit_record_seeds_from_delegated_account, commit_state_seeds_from_delegated_account,
+};
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+/// Accounts of [process_commit_state_chunk]
+pub const COMMIT_STATE_CHUNK_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("commit state"),
+    AccountSpec::readonly("commit record"),
+];
+
+/// Write a chunk of a state committed by its Merkle root, along with the proof of the chunk
+/// against the root
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the validator the commit is attributed to, or one of its commit relayers
+/// 1: `[]`         the delegated account
+/// 2: `[writable]` the commit state PDA, a [ChunkedCommitState]
+/// 3: `[]`         the commit record PDA
+/// 4: `[]`         (optional) the validator fees vault of the validator, required if a
+///                 commit relayer signs, see [crate::processor::process_register_commit_relayer]
+///
+/// Requirements:
+///
+/// - delegated account is owned by delegation program
+/// - commit record is initialized
+/// - validator is the identity of the commit record or one of its approved commit relayers
+/// - commit state is a chunked commit state, as recorded by the commit record, see
+///   [crate::processor::fast::process_commit_state_root]
+/// - chunk is a chunk of the committed state, of the length of its index, and its Merkle
+///   proof leads to the committed root
+///
+/// Steps:
+///
+/// 1. Copy the chunk at its offset in the data of the commit state
+/// 2. Mark the chunk as written, counting it once if written again
+///
+/// NOTE: the chunk and its proof must fit in a transaction, the proof holding a hash per
+///       level of the tree, e.g. 14 hashes for 10MiB of chunks of 900 bytes.
+pub fn process_commit_state_chunk(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = CommitStateChunkArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [validator, delegated_account, commit_state_account, commit_record_account, relayer_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+    load_initialized_pda(
+        commit_state_account,
+        commit_state_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "commit state",
+    )?;
+
+    let commit_record = {
+        let commit_record_data = commit_record_account.try_borrow_data()?;
+        *CommitRecord::try_from_bytes_with_discriminator(&commit_record_data)?
+    };
+    load_commit_authority(validator, &commit_record.identity, relayer_accounts.first())?;
+
+    let mut commit_state_data = commit_state_account.try_borrow_mut_data()?;
+    let chunked_commit_state = match commit_record.commit_state_layout() {
+        Some(CommitStateLayout::Chunked) => ChunkedCommitState::split(&commit_state_data),
+        _ => None,
+    };
+    let Some((mut chunked_commit_state, _, bitmap)) = chunked_commit_state else {
+        msg!("Commit state {} is not chunked", commit_state_account.key);
+        return Err(InvalidChunkedCommitState.into());
+    };
+
+    // The chunk must be the one of its index in the state of the root
+    let range = chunked_commit_state
+        .chunk_range(args.index as usize)
+        .filter(|range| range.len() == args.data.len());
+    let Some(range) = range else {
+        msg!(
+            "Chunk {} of {} bytes is not a chunk of the committed state",
+            args.index,
+            args.data.len()
+        );
+        return Err(InvalidChunkProof.into());
+    };
+    if !verify_chunk_proof(
+        &chunked_commit_state.root,
+        chunked_commit_state.chunks_count(),
+        args.index,
+        &args.data,
+        &args.proof,
+    ) {
+        msg!("Chunk {} does not match the committed root", args.index);
+        return Err(InvalidChunkProof.into());
+    }
+
+    let bitmap_byte = args.index as usize / 8;
+    let bitmap_mask = 1u8 << (args.index % 8);
+    let is_written = bitmap
+        .get(bitmap_byte)
+        .ok_or(ProgramError::InvalidAccountData)?
+        & bitmap_mask
+        != 0;
+
+    let header_size = ChunkedCommitState::size_with_discriminator();
+    let (header, data) = commit_state_data
+        .split_at_mut_checked(header_size)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let (data, bitmap) = data
+        .split_at_mut_checked(chunked_commit_state.data_len as usize)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    data.get_mut(range)
+        .ok_or(ProgramError::InvalidAccountData)?
+        .copy_from_slice(&args.data);
+    if !is_written {
+        *bitmap
+            .get_mut(bitmap_byte)
+            .ok_or(ProgramError::InvalidAccountData)? |= bitmap_mask;
+        chunked_commit_state.chunks_written = chunked_commit_state.chunks_written.saturating_add(1);
+        chunked_commit_state.to_bytes_with_discriminator(header)?;
+    }
+
+    Ok(())
+}
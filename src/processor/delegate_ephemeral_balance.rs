@@ -1,4 +1,5 @@
-use crate::args::DelegateEphemeralBalanceArgs;
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::{DelegateEphemeralBalanceArgs, Seeds};
 use crate::ephemeral_balance_seeds_from_payer;
 use crate::processor::utils::loaders::{load_program, load_signer};
 use borsh::BorshDeserialize;
@@ -9,6 +10,18 @@ use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_instruction,
 };
 
+/// Accounts of [process_delegate_ephemeral_balance]
+pub const DELEGATE_EPHEMERAL_BALANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable("payer"),
+    AccountSpec::signer("delegatee"),
+    AccountSpec::writable("ephemeral balance"),
+    AccountSpec::writable("delegate buffer"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("system program"),
+    AccountSpec::readonly("delegation program"),
+];
+
 /// Delegates an account to transfer lamports which are used to fund it inside
 /// the ephemeral.
 ///
@@ -58,7 +71,7 @@ pub fn process_delegate_ephemeral_balance(
     }
 
     // Set the delegation seeds
-    args.delegate_args.seeds = ephemeral_balance_seeds.iter().map(|s| s.to_vec()).collect();
+    args.delegate_args.seeds = Seeds::try_from(ephemeral_balance_seeds)?;
 
     // Generate the ephemeral balance PDA's signer seeds
     let ephemeral_balance_bump_slice = &[ephemeral_balance_bump];
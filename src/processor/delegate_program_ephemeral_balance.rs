@@ -0,0 +1,157 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::{DelegateProgramEphemeralBalanceArgs, Seeds};
+use crate::error::DlpError;
+use crate::log::msg;
+use crate::pda::validator_whitelist_shard_pda_from_program_id;
+use crate::processor::utils::loaders::{load_pda, load_program, load_signer};
+use crate::state::ProgramConfig;
+use crate::{program_config_seeds_from_program_id, program_ephemeral_balance_seeds_from_payer};
+use borsh::BorshDeserialize;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::system_program;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_instruction,
+};
+
+/// Accounts of [process_delegate_program_ephemeral_balance]
+pub const DELEGATE_PROGRAM_EPHEMERAL_BALANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable("payer"),
+    AccountSpec::signer("delegatee"),
+    AccountSpec::writable("program ephemeral balance"),
+    AccountSpec::writable("delegate buffer"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("program config"),
+    AccountSpec::readonly("system program"),
+    AccountSpec::readonly("delegation program"),
+];
+
+/// Delegates a program ephemeral balance account so it can fund the transactions of a
+/// single program inside the ephemeral.
+///
+/// Accounts:
+///
+/// 0: `[writable]` payer account
+/// 1: `[signer]`   delegatee account from which the delegated account is derived
+/// 2: `[writable]` program ephemeral balance account
+/// 3: `[writable]` delegate buffer PDA
+/// 4: `[writable]` delegation record PDA
+/// 5: `[writable]` delegation metadata PDA
+/// 6: `[]`         program config PDA of the program the balance is scoped to
+/// 7: `[]`         system program
+/// 8: `[]`         this program
+/// 9: `[]`         (optional) the validator whitelist shard PDA of the validator for the
+///                 program, see [crate::state::ValidatorWhitelistShard]
+///
+/// Requirements:
+///
+/// - same as [crate::processor::delegate::process_delegate]
+/// - if the program has a program config, the validator is set in the delegate args and
+///   is whitelisted for the program, by the program config or by its initialized shard
+///
+/// Steps:
+///
+/// 1. Delegates the program ephemeral balance account to the delegation program so it can
+///    act as an escrow for the program only
+pub fn process_delegate_program_ephemeral_balance(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let mut args = DelegateProgramEphemeralBalanceArgs::try_from_slice(data)?;
+    let [payer, pubkey, ephemeral_balance_account, delegate_buffer, delegation_record, delegation_metadata, program_config_account, system_program, delegation_program, shard_account @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+    load_signer(pubkey, "delegatee")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_program(delegation_program, crate::id(), "delegation program")?;
+
+    // If the program whitelists validators, only those can receive the program's escrow
+    load_pda(
+        program_config_account,
+        program_config_seeds_from_program_id!(args.program_id),
+        &crate::id(),
+        false,
+        "program config",
+    )?;
+    if program_config_account.owner.eq(&crate::id()) {
+        let program_config_data = program_config_account.try_borrow_data()?;
+        let program_config =
+            ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)?;
+        let is_approved = !program_config.has_whitelist()
+            || args.delegate_args.validator.is_some_and(|validator| {
+                program_config.approved_validators.contains(&validator)
+                    || shard_account.first().is_some_and(|shard_account| {
+                        shard_account.owner.eq(&crate::id())
+                            && shard_account
+                                .key
+                                .eq(&validator_whitelist_shard_pda_from_program_id(
+                                    &args.program_id,
+                                    &validator,
+                                ))
+                    })
+            });
+        if !is_approved {
+            msg!(
+                "Validator {:?} is not whitelisted for program {}",
+                args.delegate_args.validator,
+                args.program_id
+            );
+            return Err(DlpError::InvalidWhitelistProgramConfig.into());
+        }
+    }
+
+    // Check seeds and derive bump
+    let ephemeral_balance_seeds: &[&[u8]] =
+        program_ephemeral_balance_seeds_from_payer!(pubkey.key, args.program_id, args.index);
+    let (ephemeral_balance_key, ephemeral_balance_bump) =
+        Pubkey::find_program_address(ephemeral_balance_seeds, &crate::id());
+    if !ephemeral_balance_key.eq(ephemeral_balance_account.key) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Set the delegation seeds
+    args.delegate_args.seeds = Seeds::try_from(ephemeral_balance_seeds)?;
+
+    // Generate the program ephemeral balance PDA's signer seeds
+    let ephemeral_balance_bump_slice = &[ephemeral_balance_bump];
+    let ephemeral_balance_signer_seeds =
+        [ephemeral_balance_seeds, &[ephemeral_balance_bump_slice]].concat();
+
+    // Assign as owner the delegation program
+    invoke_signed(
+        &system_instruction::assign(ephemeral_balance_account.key, &crate::id()),
+        &[ephemeral_balance_account.clone(), system_program.clone()],
+        &[&ephemeral_balance_signer_seeds],
+    )?;
+
+    // Create the delegation ix
+    let ix = crate::instruction_builder::delegate(
+        *payer.key,
+        *ephemeral_balance_account.key,
+        Some(system_program::id()),
+        args.delegate_args,
+    );
+
+    // Invoke signed delegation instruction
+    invoke_signed(
+        &ix,
+        &[
+            delegation_program.clone(),
+            payer.clone(),
+            ephemeral_balance_account.clone(),
+            delegate_buffer.clone(),
+            delegation_record.clone(),
+            delegation_metadata.clone(),
+            system_program.clone(),
+        ],
+        &[&ephemeral_balance_signer_seeds],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::stake::{self, state::StakeAuthorize};
+use solana_program::sysvar::{self, Sysvar};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::{DelegateStakeAccountArgs, Seeds};
+use crate::consts::DEFAULT_VALIDATOR_IDENTITY;
+use crate::error::DlpError::InvalidStakeState;
+use crate::processor::utils::loaders::{
+    load_owned_pda, load_pda, load_program, load_signer, load_sysvar, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::{DelegationMetadata, DelegationRecord, NativeStakeState};
+use crate::{
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account, stake_authority_seeds_from_stake_account,
+};
+
+/// Accounts of [process_delegate_stake_account]
+pub const DELEGATE_STAKE_ACCOUNT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("stake authority"),
+    AccountSpec::writable("stake account"),
+    AccountSpec::readonly("stake authority PDA"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("stake program"),
+    AccountSpec::readonly("clock sysvar"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Delegate a native stake account, so that its stake is managed in the ephemeral rollup
+///
+/// A stake account is owned by the stake program, which does not let it be assigned to the
+/// delegation program. Instead, its staker and withdrawer authorities are handed to the
+/// stake authority PDA of the account, which settles the stake account on undelegation, see
+/// [crate::processor::process_undelegate_stake_account]. The account being left to the stake
+/// program, it cannot be committed nor undelegated by the regular instructions.
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the stake authority, staker and withdrawer of the stake account,
+///                         paying for the delegation PDAs
+/// 1: `[writable]`         the stake account
+/// 2: `[]`                 the stake authority PDA of the stake account
+/// 3: `[writable]`         the delegation record
+/// 4: `[writable]`         the delegation metadata
+/// 5: `[]`                 the stake program
+/// 6: `[]`                 the clock sysvar
+/// 7: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - stake account is owned by the stake program and is initialized or staked, see
+///   [NativeStakeState]
+/// - stake authority is the staker and the withdrawer of the stake account
+/// - lockup of the stake account is not in force, the withdrawer not being transferable
+///   without its custodian
+/// - commit frequency is not zero, so that the stake authority can undelegate the account
+///   if the validator stops settling it
+/// - delegation record and delegation metadata are uninitialized
+///
+/// Steps:
+///
+/// 1. Create the delegation record, the owner of the delegation being the stake program
+/// 2. Create the delegation metadata, the stake authority being the rent payer
+/// 3. Authorize the stake authority PDA as the staker and the withdrawer of the stake account
+pub fn process_delegate_stake_account(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = DelegateStakeAccountArgs::try_from_slice(data)?;
+
+    let [authority, stake_account, stake_authority_pda, delegation_record_account, delegation_metadata_account, stake_program, clock_sysvar, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "stake authority")?;
+    load_program(stake_program, stake::program::id(), "stake program")?;
+    load_sysvar(clock_sysvar, sysvar::clock::id())?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_owned_pda(stake_account, &stake::program::id(), "stake account")?;
+    load_pda(
+        stake_authority_pda,
+        stake_authority_seeds_from_stake_account!(stake_account.key),
+        &crate::id(),
+        false,
+        "stake authority",
+    )?;
+    let delegation_record_bump = load_uninitialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(stake_account.key),
+        &crate::id(),
+        true,
+        "delegation record",
+    )?;
+    let delegation_metadata_bump = load_uninitialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(stake_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+
+    if args.commit_frequency_ms == 0 {
+        msg!("stake account delegations must have a commit frequency");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check the stake account can be handed to the stake authority PDA
+    let clock = Clock::get()?;
+    {
+        let stake_account_data = stake_account.try_borrow_data()?;
+        let Some(stake_state) = NativeStakeState::try_from_data(&stake_account_data) else {
+            msg!("stake account {} is not initialized", stake_account.key);
+            return Err(InvalidStakeState.into());
+        };
+        if !stake_state.staker().eq(authority.key) || !stake_state.withdrawer().eq(authority.key) {
+            msg!(
+                "{} is not the staker and the withdrawer of the stake account",
+                authority.key
+            );
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if stake_state.is_lockup_in_force(clock.unix_timestamp, clock.epoch) {
+            msg!("lockup of the stake account is in force");
+            return Err(InvalidStakeState.into());
+        }
+    }
+
+    // Initialize the delegation record
+    create_pda(
+        delegation_record_account,
+        &crate::id(),
+        DelegationRecord::size_with_discriminator(),
+        delegation_record_seeds_from_delegated_account!(stake_account.key),
+        delegation_record_bump,
+        system_program,
+        authority,
+    )?;
+    let delegation_record = DelegationRecord {
+        authority: args.validator.unwrap_or(DEFAULT_VALIDATOR_IDENTITY),
+        owner: stake::program::id(),
+        delegation_slot: clock.slot,
+        lamports: stake_account.lamports(),
+        commit_frequency_ms: args.commit_frequency_ms as u64,
+        version: DelegationRecord::VERSION,
+        padding: [0; 7],
+    };
+    let mut delegation_record_data = delegation_record_account.try_borrow_mut_data()?;
+    delegation_record.to_bytes_with_discriminator(&mut delegation_record_data)?;
+
+    // Initialize the delegation metadata
+    let delegation_metadata = DelegationMetadata {
+        last_update_nonce: 0,
+        is_undelegatable: false,
+        seeds: Seeds::default(),
+        rent_payer: *authority.key,
+        close_destination: None,
+        extended_undelegate_payload: false,
+        undelegation_request: None,
+        seed_template: None,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: None,
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: None,
+        max_account_size: None,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: None,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
+    };
+    create_pda(
+        delegation_metadata_account,
+        &crate::id(),
+        delegation_metadata.serialized_size(),
+        delegation_metadata_seeds_from_delegated_account!(stake_account.key),
+        delegation_metadata_bump,
+        system_program,
+        authority,
+    )?;
+    let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+    delegation_metadata.to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+
+    // Hand the authorities of the stake account to the stake authority PDA
+    for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+        invoke(
+            &stake::instruction::authorize(
+                stake_account.key,
+                authority.key,
+                stake_authority_pda.key,
+                stake_authorize,
+                None,
+            ),
+            &[
+                stake_account.clone(),
+                clock_sysvar.clone(),
+                authority.clone(),
+                stake_program.clone(),
+            ],
+        )?;
+    }
+
+    Ok(())
+}
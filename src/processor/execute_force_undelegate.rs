@@ -0,0 +1,174 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::to_vec;
+use solana_program::clock::Clock;
+use solana_program::log::sol_log_data;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::error::DlpError::{ForceUndelegationTimelock, Unauthorized};
+use crate::events::{EventDiscriminator, ForceUndelegateExecutedEvent};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program_upgrade_authority, load_signer,
+    load_uninitialized_pda,
+};
+use crate::processor::utils::pda::{close_pda, grow_pda_funded_by_pda};
+use crate::state::{DelegationMetadata, DelegationRecord, ForceUndelegation};
+use crate::{
+    commit_record_seeds_from_delegated_account, delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+    force_undelegation_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_execute_force_undelegate]
+pub const EXECUTE_FORCE_UNDELEGATE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::writable("force undelegation"),
+    AccountSpec::readonly("delegation program data"),
+];
+
+/// Execute the force undelegation of a delegated account once its timelock elapsed, see
+/// [ForceUndelegation]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account, refunded the rest of the rent of the force
+///                         undelegation PDA
+/// 1: `[]`                 the delegated account
+/// 2: `[]`                 the delegation record
+/// 3: `[writable]`         the delegation metadata
+/// 4: `[]`                 the commit record PDA
+/// 5: `[writable]`         the force undelegation PDA
+/// 6: `[]`                 delegation program data
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - delegated account is owned by the delegation program
+/// - delegation record and delegation metadata are initialized
+/// - force undelegation is initialized and its timelock elapsed
+/// - commit record is uninitialized, a pending commit being left to be finalized, see
+///   [crate::processor::fast::process_crank_finalize]
+///
+/// Steps:
+///
+/// 1. Mark the account undelegatable, so that it can be undelegated without a commit of
+///    the validator, growing the delegation metadata to record its status with the rent of
+///    the force undelegation PDA
+/// 2. Close the force undelegation
+/// 3. Emit a [ForceUndelegateExecutedEvent]
+pub fn process_execute_force_undelegate(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [admin, delegated_account, delegation_record_account, delegation_metadata_account, commit_record_account, force_undelegation_account, delegation_program_data] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+    load_initialized_pda(
+        force_undelegation_account,
+        force_undelegation_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "force undelegation",
+    )?;
+
+    // A pending commit is left to be finalized, it must not overwrite the recovered state
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+
+    let force_undelegation = {
+        let force_undelegation_data = force_undelegation_account.try_borrow_data()?;
+        *ForceUndelegation::try_from_bytes_with_discriminator(&force_undelegation_data)?
+    };
+    if !force_undelegation.is_executable(Clock::get()?.slot) {
+        msg!(
+            "Force undelegation is executable from slot {}",
+            force_undelegation.executable_slot
+        );
+        return Err(ForceUndelegationTimelock.into());
+    }
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+
+    let mut delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    delegation_metadata.is_undelegatable = true;
+    grow_pda_funded_by_pda(
+        delegation_metadata_account,
+        delegation_metadata.serialized_size(),
+        force_undelegation_account,
+    )?;
+    {
+        let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+        delegation_metadata.to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+    }
+
+    close_pda(force_undelegation_account, admin)?;
+
+    msg!(
+        "Force undelegation of {} executed, delegated to {}",
+        delegated_account.key,
+        delegation_record.authority
+    );
+    let event = ForceUndelegateExecutedEvent {
+        delegated_account: *delegated_account.key,
+        validator: delegation_record.authority,
+        admin: *admin.key,
+    };
+    sol_log_data(&[&[
+        vec![EventDiscriminator::ForceUndelegateExecuted.into()],
+        to_vec(&event)?,
+    ]
+    .concat()]);
+
+    Ok(())
+}
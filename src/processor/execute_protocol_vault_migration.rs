@@ -0,0 +1,194 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::to_vec;
+use solana_program::clock::Clock;
+use solana_program::log::sol_log_data;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::error::DlpError::{
+    NoPendingProtocolVaultMigration, ProtocolVaultMigrationTimelock, Unauthorized,
+};
+use crate::events::{EventDiscriminator, ProtocolVaultMigrationExecutedEvent};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_initialized_protocol_fees_vault, load_program,
+    load_program_upgrade_authority, load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::{create_pda, resize_funded_pda};
+use crate::state::{ProtocolConfig, RETIRED_FEES_VAULT_GENERATION};
+use crate::{fees_vault_seeds_from_generation, protocol_config_seeds};
+
+/// Accounts of [process_execute_protocol_vault_migration]
+pub const EXECUTE_PROTOCOL_VAULT_MIGRATION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::writable("protocol config"),
+    AccountSpec::writable("current protocol fees vault"),
+    AccountSpec::writable("new protocol fees vault"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Execute a proposed protocol fees vault migration once its timelock elapsed, see
+/// [crate::processor::process_propose_protocol_vault_migration]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account paying for the new vault
+/// 1: `[writable]`         protocol config PDA
+/// 2: `[writable]`         the current protocol fees vault
+/// 3: `[writable]`         the new protocol fees vault
+/// 4: `[]`                 delegation program data
+/// 5: `[]`                 system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - protocol config PDA is initialized, with a pending migration whose timelock elapsed
+/// - current protocol fees vault is the vault of the generation of the protocol config
+/// - new protocol fees vault is the uninitialized vault of the pending generation
+///
+/// Steps:
+///
+/// 1. Create the new vault, storing its generation in its data
+/// 2. Move the lamports of the current vault above its rent exemption to the new vault
+/// 3. Retire the current vault, which then fails the protocol fees vault checks of the fee
+///    paying instructions
+/// 4. Point the protocol config to the new vault and clear the pending migration
+/// 5. Emit a [ProtocolVaultMigrationExecutedEvent]
+pub fn process_execute_protocol_vault_migration(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [admin, protocol_config_account, current_vault, new_vault, delegation_program_data, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    load_initialized_pda(
+        protocol_config_account,
+        protocol_config_seeds!(),
+        &crate::id(),
+        true,
+        "protocol config",
+    )?;
+    let mut protocol_config = {
+        let protocol_config_data = protocol_config_account.try_borrow_data()?;
+        *ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_data)?
+    };
+    if protocol_config.pending_fees_vault_generation == 0 {
+        return Err(NoPendingProtocolVaultMigration.into());
+    }
+    if !protocol_config.is_fees_vault_migration_executable(Clock::get()?.slot) {
+        msg!(
+            "Protocol fees vault migration is executable from slot {}",
+            protocol_config.fees_vault_migration_slot
+        );
+        return Err(ProtocolVaultMigrationTimelock.into());
+    }
+
+    let current_generation = load_initialized_protocol_fees_vault(current_vault, true)?;
+    if current_generation != protocol_config.fees_vault_generation {
+        msg!(
+            "Expected the protocol fees vault of generation {} but got {}",
+            protocol_config.fees_vault_generation,
+            current_generation
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let new_generation = protocol_config.pending_fees_vault_generation;
+    let new_vault_bump = load_uninitialized_pda(
+        new_vault,
+        fees_vault_seeds_from_generation!(new_generation),
+        &crate::id(),
+        true,
+        "new protocol fees vault",
+    )?;
+
+    create_pda(
+        new_vault,
+        &crate::id(),
+        8,
+        fees_vault_seeds_from_generation!(new_generation),
+        new_vault_bump,
+        system_program,
+        admin,
+    )?;
+    new_vault
+        .try_borrow_mut_data()?
+        .get_mut(..8)
+        .ok_or(ProgramError::AccountDataTooSmall)?
+        .copy_from_slice(&new_generation.to_le_bytes());
+
+    // The vault created at bootstrap may hold no data to store the retired generation in
+    if current_vault.data_len() < 8 {
+        resize_funded_pda(admin, current_vault, system_program, 8)?;
+    }
+
+    // Move the fees, leaving the retired vault rent exempt
+    let min_rent = Rent::get()?.minimum_balance(current_vault.data_len());
+    let lamports = current_vault.lamports().saturating_sub(min_rent);
+    **current_vault.try_borrow_mut_lamports()? = current_vault
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **new_vault.try_borrow_mut_lamports()? = new_vault
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    current_vault
+        .try_borrow_mut_data()?
+        .get_mut(..8)
+        .ok_or(ProgramError::AccountDataTooSmall)?
+        .copy_from_slice(&RETIRED_FEES_VAULT_GENERATION.to_le_bytes());
+
+    protocol_config.fees_vault_generation = new_generation;
+    protocol_config.pending_fees_vault_generation = 0;
+    protocol_config.fees_vault_migration_slot = 0;
+    {
+        let mut protocol_config_data = protocol_config_account.try_borrow_mut_data()?;
+        protocol_config.to_bytes_with_discriminator(&mut protocol_config_data)?;
+    }
+
+    let event = ProtocolVaultMigrationExecutedEvent {
+        admin: *admin.key,
+        old_vault: *current_vault.key,
+        new_vault: *new_vault.key,
+        lamports,
+    };
+    msg!(
+        "Protocol fees vault migrated from {} to {} with {} lamports",
+        event.old_vault,
+        event.new_vault,
+        event.lamports
+    );
+    sol_log_data(&[&[
+        vec![EventDiscriminator::ProtocolVaultMigrationExecuted.into()],
+        to_vec(&event)?,
+    ]
+    .concat()]);
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::to_vec;
+use solana_program::clock::Clock;
+use solana_program::log::sol_log_data;
+use solana_program::program::{set_return_data, MAX_RETURN_DATA};
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::error::DlpError::Unauthorized;
+use crate::events::{DelegationExportedEvent, EventDiscriminator};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program_upgrade_authority, load_signer,
+};
+use crate::state::{DelegationMetadata, DelegationPackage, DelegationRecord};
+use crate::{
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_export_delegation_package]
+pub const EXPORT_DELEGATION_PACKAGE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("admin"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+];
+
+/// Export the full state of a delegation into a portable [DelegationPackage], to migrate
+/// it to another cluster or program id with
+/// [crate::processor::process_import_delegation_package]
+///
+/// Accounts:
+///
+/// 0: `[signer]` admin account
+/// 1: `[]`       delegation program data
+/// 2: `[]`       the delegated account
+/// 3: `[]`       the delegation record
+/// 4: `[]`       the delegation metadata
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - delegated account is owned by the delegation program
+/// - delegation record and metadata are initialized and valid
+///
+/// Steps:
+///
+/// 1. Build the package of the delegation record, the delegation metadata and the hash of
+///    the delegated account data
+/// 2. Emit a [DelegationExportedEvent] and set the return data to the package, if it fits
+///
+/// NOTE: the delegation is left untouched, the admin is expected to undelegate or freeze
+///       it before importing the package elsewhere. The package must be signed by the
+///       admin off-chain to be imported.
+pub fn process_export_delegation_package(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [admin, delegation_program_data, delegated_account, delegation_record_account, delegation_metadata_account] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation metadata",
+    )?;
+
+    let delegation_record_data = delegation_record_account.try_borrow_data()?;
+    let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+    DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?;
+    DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?;
+
+    let package = DelegationPackage {
+        source_program_id: crate::id(),
+        exporter: *admin.key,
+        export_slot: Clock::get()?.slot,
+        delegated_account: *delegated_account.key,
+        delegation_record: delegation_record_data.to_vec(),
+        delegation_metadata: delegation_metadata_data.to_vec(),
+        data_hash: DelegationPackage::data_hash(&delegated_account.try_borrow_data()?),
+    };
+
+    msg!("Delegation of {} exported", delegated_account.key);
+    let event = DelegationExportedEvent { package };
+    let package_data = to_vec(&event.package)?;
+    sol_log_data(&[&[
+        vec![EventDiscriminator::DelegationExported.into()],
+        to_vec(&event)?,
+    ]
+    .concat()]);
+    // The seeds of the delegation metadata can exceed the return data
+    if package_data.len() <= MAX_RETURN_DATA {
+        set_return_data(&package_data);
+    }
+
+    Ok(())
+}
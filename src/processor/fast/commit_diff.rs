@@ -1,15 +1,25 @@
-use borsh::BorshDeserialize;
+use crate::accounts_spec::AccountsSpec;
+use crate::log::log;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
 use pinocchio::{
     account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
 };
-use pinocchio_log::log;
 
-use crate::args::{CommitDiffArgsWithoutDiff, SIZE_COMMIT_DIFF_ARGS_WITHOUT_DIFF};
-use crate::processor::fast::{process_commit_state_internal, CommitStateInternalArgs};
+use crate::args::CommitDiffArgsRef;
+use crate::processor::fast::utils::authority_chain::split_authority_chain;
+use crate::processor::fast::utils::delegation_authorities::split_delegation_authorities;
+use crate::processor::fast::utils::whitelist_shard::split_whitelist_shard;
+use crate::processor::fast::{
+    process_commit_state_internal, CommitScheduleAccounts, CommitStateInternalArgs,
+    COMMIT_STATE_ACCOUNTS_SPEC,
+};
 use crate::DiffSet;
 
 use super::NewState;
 
+/// Accounts of [process_commit_diff], the ones of [crate::processor::fast::process_commit_state]
+pub const COMMIT_DIFF_ACCOUNTS_SPEC: AccountsSpec = COMMIT_STATE_ACCOUNTS_SPEC;
+
 /// Commit diff to a delegated PDA
 ///
 /// Accounts:
@@ -19,10 +29,18 @@ use super::NewState;
 /// 2: `[writable]` the PDA storing the new state
 /// 3: `[writable]` the PDA storing the commit record
 /// 4: `[]`         the delegation record
-/// 5: `[writable]` the delegation metadata
+/// 5: `[]`         the delegation metadata, writable if the commit allows the undelegation
 /// 6: `[]`         the validator fees vault
 /// 7: `[]`         the program config account
 /// 8: `[]`         the system program
+/// 9: `[writable]` (optional) the commit schedule PDA, followed by its escrow, see
+///                 [crate::processor::fast::process_commit_state]
+/// 11: `[]`        (optional) the validator whitelist shard of the delegation authority,
+///                 see [crate::processor::fast::process_commit_state]
+/// 12: `[]`        (optional) the delegation authorities of the delegated account, see
+///                 [crate::processor::fast::process_commit_state]
+/// 13: `[]`        (optional) the links of the authority delegation chain granting the
+///                 signer, passed last, see [crate::processor::fast::process_commit_state]
 ///
 /// Requirements:
 ///
@@ -35,34 +53,39 @@ use super::NewState;
 ///   - commit state
 ///   - commit record
 /// - delegated account holds at least the lamports indicated in the delegation record
+/// - committed lamports can be settled at finalize, leaving the delegated account rent
+///   exempt for the committed data length
 /// - account was not committed at a later slot
+/// - ER block hash, if provided, differs from the one of the last finalized commit
+/// - commit schedule is passed if and only if the commits of the account are scheduled,
+///   see [crate::processor::fast::process_commit_state]
 ///
 /// Steps:
 /// 1. Check that the pda is delegated
 /// 2. Init a new PDA to store the new state
-/// 3. Copy the new state to the new PDA
+/// 3. Merge the diff with the delegated account directly into the new PDA, without copying
+///    the account first, see [crate::merge_diff_copy]
 /// 4. Init a new PDA to store the record of the new state commitment
+/// 5. If the commits are scheduled, charge the escrow of the commit schedule
 pub fn process_commit_diff(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
+    let (accounts, authority_chain) = split_authority_chain(accounts);
+    let (accounts, delegation_authorities) = split_delegation_authorities(accounts);
+    let (accounts, whitelist_shard) = split_whitelist_shard(accounts);
+    let (accounts, commit_schedule) = CommitScheduleAccounts::split_trailing(accounts, 9);
     let [validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, validator_fees_vault, program_config_account, _system_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    if data.len() < SIZE_COMMIT_DIFF_ARGS_WITHOUT_DIFF {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    let (diff, data) = data.split_at(data.len() - SIZE_COMMIT_DIFF_ARGS_WITHOUT_DIFF);
-
-    let args =
-        CommitDiffArgsWithoutDiff::try_from_slice(data).map_err(|_| ProgramError::BorshIoError)?;
+    let args = CommitDiffArgsRef::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::BorshIoError)?;
 
-    let diffset = DiffSet::try_new_from_borsh_vec(diff)?;
+    let diffset = DiffSet::try_new(args.diff)?;
 
     if diffset.segments_count() == 0 {
         log!("WARN: noop; empty diff sent");
@@ -72,11 +95,16 @@ pub fn process_commit_diff(
     let commit_record_nonce = args.nonce;
     let allow_undelegation = args.allow_undelegation;
 
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
     let commit_args = CommitStateInternalArgs {
         commit_state_bytes: NewState::Diff(diffset),
         commit_record_lamports,
         commit_record_nonce,
         allow_undelegation,
+        er_block_hash: args.er_block_hash,
+        base_state_hash: None,
+        escrow_spend: None,
         validator,
         delegated_account,
         commit_state_account,
@@ -85,6 +113,12 @@ pub fn process_commit_diff(
         delegation_metadata_account,
         validator_fees_vault,
         program_config_account,
+        whitelist_shard,
+        delegation_authorities,
+        authority_chain,
+        commit_schedule,
+        rent: &rent,
+        slot,
     };
 
     process_commit_state_internal(commit_args)
@@ -1,29 +1,45 @@
+use crate::accounts_spec::AccountsSpec;
 use crate::args::CommitStateFromBufferArgs;
-use crate::processor::fast::{process_commit_state_internal, CommitStateInternalArgs};
+use crate::processor::fast::utils::authority_chain::split_authority_chain;
+use crate::processor::fast::utils::delegation_authorities::split_delegation_authorities;
+use crate::processor::fast::utils::whitelist_shard::split_whitelist_shard;
+use crate::processor::fast::{
+    process_commit_state_internal, CommitScheduleAccounts, CommitStateInternalArgs,
+    COMMIT_STATE_FROM_BUFFER_ACCOUNTS_SPEC,
+};
 use crate::DiffSet;
 
-use borsh::BorshDeserialize;
+use crate::log::log;
 use pinocchio::account_info::AccountInfo;
 use pinocchio::program_error::ProgramError;
 use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
 use pinocchio::ProgramResult;
-use pinocchio_log::log;
 
 use super::NewState;
 
+/// Accounts of [process_commit_diff_from_buffer], the ones of
+/// [crate::processor::fast::process_commit_state_from_buffer]
+pub const COMMIT_DIFF_FROM_BUFFER_ACCOUNTS_SPEC: AccountsSpec =
+    COMMIT_STATE_FROM_BUFFER_ACCOUNTS_SPEC;
+
 pub fn process_commit_diff_from_buffer(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
+    let (accounts, authority_chain) = split_authority_chain(accounts);
+    let (accounts, delegation_authorities) = split_delegation_authorities(accounts);
+    let (accounts, whitelist_shard) = split_whitelist_shard(accounts);
+    let (accounts, commit_schedule) = CommitScheduleAccounts::split_trailing(accounts, 10);
     let [validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, diff_buffer_account, validator_fees_vault, program_config_account, _system_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    let args =
-        CommitStateFromBufferArgs::try_from_slice(data).map_err(|_| ProgramError::BorshIoError)?;
+    let args = CommitStateFromBufferArgs::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::BorshIoError)?;
 
     let commit_record_lamports = args.lamports;
     let commit_record_nonce = args.nonce;
@@ -37,11 +53,16 @@ pub fn process_commit_diff_from_buffer(
         log!("WARN: noop; empty diff sent");
     }
 
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
     let commit_args = CommitStateInternalArgs {
         commit_state_bytes: NewState::Diff(diffset),
         commit_record_lamports,
         commit_record_nonce,
         allow_undelegation,
+        er_block_hash: args.er_block_hash,
+        base_state_hash: None,
+        escrow_spend: None,
         validator,
         delegated_account,
         commit_state_account,
@@ -50,6 +71,12 @@ pub fn process_commit_diff_from_buffer(
         delegation_metadata_account,
         validator_fees_vault,
         program_config_account,
+        whitelist_shard,
+        delegation_authorities,
+        authority_chain,
+        commit_schedule,
+        rent: &rent,
+        slot,
     };
     process_commit_state_internal(commit_args)
 }
@@ -0,0 +1,123 @@
+use crate::accounts_spec::AccountsSpec;
+use crate::log::log;
+use borsh::BorshDeserialize;
+use pinocchio::pubkey;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::args::{
+    commit_state_hash, CommitDiffShadowArgsWithoutDiff, SIZE_COMMIT_DIFF_SHADOW_ARGS_WITHOUT_DIFF,
+};
+use crate::error::DlpError;
+use crate::processor::fast::utils::authority_chain::split_authority_chain;
+use crate::processor::fast::utils::delegation_authorities::split_delegation_authorities;
+use crate::processor::fast::utils::whitelist_shard::split_whitelist_shard;
+use crate::processor::fast::{
+    process_commit_state_internal, CommitScheduleAccounts, CommitStateInternalArgs,
+    COMMIT_DIFF_ACCOUNTS_SPEC,
+};
+use crate::state::{CommitStateLayout, PendingState};
+use crate::DiffSet;
+
+use super::NewState;
+
+/// Accounts of [process_commit_diff_shadow], the ones of
+/// [crate::processor::fast::process_commit_diff]
+pub const COMMIT_DIFF_SHADOW_ACCOUNTS_SPEC: AccountsSpec = COMMIT_DIFF_ACCOUNTS_SPEC;
+
+/// Commit diff to a delegated PDA, verifying that the applied diff produces the full state
+/// expected by the validator. This is the shadow mode of
+/// [crate::processor::fast::process_commit_diff], to gain confidence in the diffs before
+/// they become the only commit path.
+///
+/// Accounts: same as [crate::processor::fast::process_commit_diff]
+///
+/// Requirements: same as [crate::processor::fast::process_commit_diff], and
+///
+/// - the hash of the committed state matches the expected state hash
+///
+/// Steps:
+/// 1. Commit the diff as [crate::processor::fast::process_commit_diff]
+/// 2. Hash the committed state and compare it to the expected state hash, logging the
+///    context of the commit on a mismatch
+pub fn process_commit_diff_shadow(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let (accounts, authority_chain) = split_authority_chain(accounts);
+    let (accounts, delegation_authorities) = split_delegation_authorities(accounts);
+    let (accounts, whitelist_shard) = split_whitelist_shard(accounts);
+    let (accounts, commit_schedule) = CommitScheduleAccounts::split_trailing(accounts, 9);
+    let [validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, validator_fees_vault, program_config_account, _system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (diff, data) = data
+        .len()
+        .checked_sub(SIZE_COMMIT_DIFF_SHADOW_ARGS_WITHOUT_DIFF)
+        .and_then(|diff_len| data.split_at_checked(diff_len))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    let args = CommitDiffShadowArgsWithoutDiff::try_from_slice(data)
+        .map_err(|_| ProgramError::BorshIoError)?;
+
+    let diffset = DiffSet::try_new_from_borsh_vec(diff)?;
+    let segments_count = diffset.segments_count();
+    let original_len = delegated_account.data_len();
+
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
+    let commit_args = CommitStateInternalArgs {
+        commit_state_bytes: NewState::Diff(diffset),
+        commit_record_lamports: args.lamports,
+        commit_record_nonce: args.nonce,
+        allow_undelegation: args.allow_undelegation,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        validator,
+        delegated_account,
+        commit_state_account,
+        commit_record_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        validator_fees_vault,
+        program_config_account,
+        whitelist_shard,
+        delegation_authorities,
+        authority_chain,
+        commit_schedule,
+        rent: &rent,
+        slot,
+    };
+    process_commit_state_internal(commit_args)?;
+
+    let commit_state_data = commit_state_account.try_borrow_data()?;
+    let commit_state_data =
+        PendingState::committed_data(&commit_state_data, CommitStateLayout::Pending)
+            .ok_or(DlpError::InvalidCommitStateLayout)?;
+    let state_hash = commit_state_hash(commit_state_data);
+    if state_hash != args.expected_state_hash {
+        log!("Committed state does not match the expected state hash. delegated account: ");
+        pubkey::log(delegated_account.key());
+        log!(
+            "nonce: {}, original len: {}, committed len: {}, segments: {}",
+            args.nonce,
+            original_len,
+            commit_state_data.len(),
+            segments_count
+        );
+        log!("expected state hash: ");
+        pubkey::log(&args.expected_state_hash);
+        log!("committed state hash: ");
+        pubkey::log(&state_hash);
+        return Err(DlpError::CommitStateHashMismatch.into());
+    }
+
+    Ok(())
+}
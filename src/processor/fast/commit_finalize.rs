@@ -0,0 +1,248 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::accounts_spec::AccountsSpec;
+use crate::args::{CommitStateArgsRef, Encoding};
+use crate::error::DlpError;
+use crate::log::log;
+use crate::pda;
+use crate::processor::fast::commit_state::{
+    charge_commit_schedule, emit_commit_event, validate_commit, CommitScheduleAccounts,
+    CommitValidationArgs,
+};
+use crate::processor::fast::utils::{
+    accounts_ctx::accounts_ctx,
+    authority_chain::split_authority_chain,
+    delegation_authorities::split_delegation_authorities,
+    earnings_ledger::split_earnings_ledger,
+    pda::grow_pda_funded_by_payer,
+    protocol_stats::split_protocol_stats,
+    read_lock::split_read_lock,
+    requires::{require_uninitialized_pda, CommitRecordCtx},
+    session_report::split_session_report,
+    whitelist_shard::split_whitelist_shard,
+};
+use crate::state::{CommitRecord, CommitStateLayout};
+use crate::trace::trace;
+
+use super::finalize::{finalize_commit, PendingCommit};
+use super::to_pinocchio_program_error;
+
+/// Commit a new state of a delegated PDA and finalize it in the same instruction
+///
+/// Unlike a commit followed by a finalize, no commit state nor commit record PDA is
+/// created, so the validator does not pay for their rent even temporarily.
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the validator requesting the commit or one of its commit
+///                         relayers
+/// 1: `[writable]`         the delegated account
+/// 2: `[]`                 the commit record PDA, which must be uninitialized
+/// 3: `[writable]`         the delegation record
+/// 4: `[writable]`         the delegation metadata
+/// 5: `[]`                 the validator fees vault, writable if the commit decreases the
+///                         lamports of the delegated account
+/// 6: `[]`                 the program config account
+/// 7: `[]`                 the system program
+/// 8: `[writable]`         (optional) the commit schedule PDA, see
+///                         [crate::processor::fast::process_commit_state]
+/// 9: `[writable]`         (optional) the escrow of the commit schedule, passed after it
+/// 10: `[writable]`        (optional) the read lock account, see
+///                         [crate::processor::fast::process_finalize]
+/// 11: `[]`                (optional) the validator whitelist shard of the delegation
+///                         authority, passed before the earnings ledger page, see
+///                         [crate::processor::fast::process_commit_state]
+/// 12: `[]`                (optional) the delegation authorities of the delegated account,
+///                         passed before the earnings ledger page, see
+///                         [crate::processor::fast::process_commit_state]
+/// 13: `[]`                (optional) the links of the authority delegation chain granting
+///                         the signer, passed before the earnings ledger page, see
+///                         [crate::processor::fast::process_commit_state]
+/// 14: `[writable]`        (optional) the earnings ledger page of the validator, see
+///                         [crate::processor::fast::process_finalize]
+/// 15: `[writable]`        (optional) the protocol stats PDA, see
+///                         [crate::processor::fast::process_finalize]
+/// 16: `[writable]`        (optional) the session report of the delegated account, if an
+///                         escrow, passed last, see [crate::processor::fast::process_finalize]
+///
+/// Requirements:
+///
+/// - same as [crate::processor::fast::process_commit_state]
+/// - there is no pending commit for the delegated account
+/// - committed state is raw, see [crate::args::CommitStateArgs::encoding], as it is copied
+///   to the delegated account as is
+/// - commit declares no escrow spend, see [crate::args::CommitStateArgs::escrow_spend], as
+///   there is no finalize to check it against the call handlers of the escrow
+///
+/// Steps:
+///
+/// 1. Validate the commit as in [crate::processor::fast::process_commit_state]
+/// 2. Finalize the commit as [crate::processor::fast::process_finalize] does, the validator
+///    funding the lamports the commit adds to the delegated account and the growth of the
+///    delegation metadata, which the commit PDAs would otherwise hold
+/// 3. If the commits are scheduled, pay the validator the commit fee from the escrow of the
+///    commit schedule, there being no rent to refund
+/// 4. Emit a [crate::events::CommitEvent] if the ER block hash is provided
+pub fn process_commit_finalize(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = CommitStateArgsRef::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::BorshIoError)?;
+    if args.encoding != Encoding::Raw {
+        log!("commit finalize only accepts a raw state");
+        return Err(DlpError::InvalidCompressedState.into());
+    }
+    if args.escrow_spend.is_some() {
+        log!("commit finalize does not take an escrow spend, which is checked at finalize");
+        return Err(DlpError::EscrowSpendMismatch.into());
+    }
+
+    let (accounts, session_report) = split_session_report(accounts);
+    let (accounts, protocol_stats) = split_protocol_stats(accounts);
+    let (accounts, earnings_ledger) = split_earnings_ledger(accounts);
+    let (accounts, authority_chain) = split_authority_chain(accounts);
+    let (accounts, delegation_authorities) = split_delegation_authorities(accounts);
+    let (accounts, whitelist_shard) = split_whitelist_shard(accounts);
+    let (accounts, read_lock_account) = split_read_lock(accounts);
+    let (accounts, commit_schedule) =
+        CommitScheduleAccounts::split_trailing(accounts, COMMIT_FINALIZE_ACCOUNTS);
+    let ctx = CommitFinalizeAccounts::try_from_accounts(accounts)?;
+
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
+    let (delegation_metadata, _, identity) = validate_commit(&CommitValidationArgs {
+        data_len: args.data.len(),
+        lamports: args.lamports,
+        nonce: args.nonce,
+        allow_undelegation: args.allow_undelegation,
+        er_block_hash: args.er_block_hash,
+        base_state_hash: None,
+        validator: ctx.validator,
+        delegated_account: ctx.delegated_account,
+        delegation_record_account: ctx.delegation_record_account,
+        delegation_metadata_account: ctx.delegation_metadata_account,
+        validator_fees_vault: ctx.validator_fees_vault,
+        program_config_account: ctx.program_config_account,
+        whitelist_shard,
+        delegation_authorities,
+        authority_chain,
+        has_commit_schedule: commit_schedule.is_some(),
+        rent: &rent,
+        slot,
+    })?;
+
+    trace!(
+        ctx.delegated_account.key(),
+        args.nonce,
+        "commit-finalize",
+        "enter"
+    );
+
+    // A pending commit would overwrite this state when finalized
+    require_uninitialized_pda(
+        ctx.commit_record_account,
+        &[pda::COMMIT_RECORD_TAG, ctx.delegated_account.key()],
+        &crate::fast::ID,
+        false,
+        CommitRecordCtx,
+    )?;
+
+    // Write the undelegation flag validated with the commit, the delegation metadata being
+    // reloaded at finalize, growing it to record the status of the delegation
+    grow_pda_funded_by_payer(
+        ctx.delegation_metadata_account,
+        delegation_metadata.serialized_size(),
+        ctx.validator,
+        &rent,
+    )?;
+    {
+        let mut delegation_metadata_data = ctx.delegation_metadata_account.try_borrow_mut_data()?;
+        delegation_metadata
+            .to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())
+            .map_err(to_pinocchio_program_error)?;
+    }
+
+    // Finalize the commit as if it was pending in its commit PDAs, the validator funding
+    // what the commit PDAs would have held
+    let commit_record = CommitRecord {
+        identity: identity.into(),
+        account: (*ctx.delegated_account.key()).into(),
+        nonce: args.nonce,
+        lamports: args.lamports,
+        slot,
+        er_block_hash: args.er_block_hash.unwrap_or_default(),
+        rent_advanced: 0,
+        escrow: Default::default(),
+        escrow_spend: 0,
+        has_escrow_spend: 0,
+        commit_state_layout: CommitStateLayout::Raw.into(),
+        padding: [0; 6],
+    };
+    finalize_commit(
+        &rent,
+        ctx.validator,
+        ctx.delegated_account,
+        ctx.delegation_record_account,
+        ctx.delegation_metadata_account,
+        ctx.validator_fees_vault,
+        PendingCommit::InMemory {
+            commit_record,
+            committed_data: args.data,
+            read_lock_account,
+        },
+        earnings_ledger,
+        protocol_stats,
+        None,
+        session_report,
+    )?;
+
+    if let Some(commit_schedule) = commit_schedule {
+        charge_commit_schedule(
+            commit_schedule,
+            ctx.delegated_account,
+            ctx.validator,
+            0,
+            slot,
+        )?;
+    }
+
+    if let Some(er_block_hash) = args.er_block_hash {
+        emit_commit_event(ctx.delegated_account, &identity, args.nonce, &er_block_hash);
+    }
+
+    trace!(
+        ctx.delegated_account.key(),
+        args.nonce,
+        "commit-finalize",
+        "exit"
+    );
+    Ok(())
+}
+
+/// The number of accounts of [process_commit_finalize], without the optional commit schedule
+const COMMIT_FINALIZE_ACCOUNTS: usize = COMMIT_FINALIZE_ACCOUNTS_SPEC.len();
+
+accounts_ctx! {
+    /// Accounts of [process_commit_finalize]
+    pub(crate) struct CommitFinalizeAccounts {
+        validator: [signer, writable] "validator",
+        delegated_account: [writable] "delegated account",
+        commit_record_account: [] "commit record",
+        delegation_record_account: [writable] "delegation record",
+        delegation_metadata_account: [writable] "delegation metadata",
+        validator_fees_vault: [] "validator fees vault",
+        program_config_account: [] "program config",
+        _system_program: [] "system program",
+    }
+}
+
+/// Accounts of [process_commit_finalize]
+pub const COMMIT_FINALIZE_ACCOUNTS_SPEC: AccountsSpec = CommitFinalizeAccounts::SPEC;
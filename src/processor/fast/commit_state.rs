@@ -1,24 +1,41 @@
-use borsh::BorshDeserialize;
+use crate::accounts_spec::AccountsSpec;
+use crate::log::log;
 use pinocchio::instruction::Signer;
+use pinocchio::log::sol_log_data;
 use pinocchio::pubkey::{self, pubkey_eq};
 use pinocchio::seeds;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
 use pinocchio::{
     account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
 };
-use pinocchio_log::log;
 use pinocchio_system::instructions as system;
 
-use crate::args::CommitStateArgs;
+use crate::args::{AuthorityScope, CommitStateArgsRef, Encoding, ErBlockHash, MaxAccountSize};
+use crate::compression::{decompress_lz4_into, split_decompressed_len};
+use crate::consts::OWNER_COMMIT_NONCE_FLAG;
 use crate::error::DlpError;
+use crate::events::{CommitEvent, EventDiscriminator};
+use crate::processor::fast::finalize::require_settleable_commit;
 use crate::processor::fast::utils::{
-    pda::create_pda,
+    accounts_ctx::accounts_ctx,
+    authority_chain::split_authority_chain,
+    delegation_authorities::{is_delegation_authority, split_delegation_authorities},
+    pda::{create_pda, grow_pda_funded_by_payer},
     requires::{
-        require_initialized_delegation_metadata, require_initialized_delegation_record,
-        require_initialized_validator_fees_vault, require_owned_pda, require_program_config,
-        require_signer, require_uninitialized_pda, CommitRecordCtx, CommitStateAccountCtx,
+        require_commit_authority, require_initialized_commit_schedule,
+        require_initialized_commit_state, require_initialized_delegation_metadata,
+        require_initialized_delegation_record, require_owned_pda, require_program_config,
+        require_signer, require_uninitialized_pda, require_writable, CommitRecordCtx,
+        CommitStateAccountCtx,
     },
+    whitelist_shard::{is_whitelisted_by_shard, split_whitelist_shard},
+};
+use crate::state::{
+    ChunkedCommitState, CommitRecord, CommitSchedule, CommitStateLayout, DelegationMetadata,
+    DelegationRecord, PendingState, ProgramConfig, StreamedCommitState, ValidatorFeesVault,
+    PENDING_STATE_ALLOWS_UNDELEGATION,
 };
-use crate::state::{CommitRecord, DelegationMetadata, DelegationRecord, ProgramConfig};
+use crate::trace::trace;
 use crate::{merge_diff_copy, pda, DiffSet};
 
 use super::to_pinocchio_program_error;
@@ -27,76 +44,195 @@ use super::to_pinocchio_program_error;
 ///
 /// Accounts:
 ///
-/// 0: `[signer]`   the validator requesting the commit
-/// 1: `[]`         the delegated account
-/// 2: `[writable]` the PDA storing the new state
-/// 3: `[writable]` the PDA storing the commit record
-/// 4: `[]`         the delegation record
-/// 5: `[writable]` the delegation metadata
-/// 6: `[]`         the validator fees vault
-/// 7: `[]`         the program config account
+///  0: `[signer]`   the validator requesting the commit or one of its commit relayers, see
+///                  [crate::processor::process_register_commit_relayer], writable if the
+///                  commits are scheduled as it is refunded by the escrow
+///  1: `[]`         the delegated account
+///  2: `[writable]` the PDA storing the new state
+///  3: `[writable]` the PDA storing the commit record
+///  4: `[]`         the delegation record
+///  5: `[]`         the delegation metadata, writable if the commit allows the undelegation
+///  6: `[]`         the validator fees vault of the delegation authority, writable if the
+///                  validator has a commit quota
+///  7: `[]`         the program config account
+///  8: `[]`         the system program
+///  9: `[writable]` (optional) the commit schedule PDA, required if the commits of the
+///                  account are scheduled, see [CommitSchedule]
+/// 10: `[writable]` (optional) the escrow of the commit schedule, passed after it
+/// 11: `[]`         (optional) the validator whitelist shard of the delegation authority,
+///                  required if the program config whitelists it in a shard, see
+///                  [crate::state::ValidatorWhitelistShard]
+/// 12: `[]`         (optional) the delegation authorities of the delegated account,
+///                  required if the signer is one of them, see
+///                  [crate::state::DelegationAuthorities]
+/// 13: `[]`         (optional) the links of the authority delegation chain granting the
+///                  signer, passed last, from the chain of the delegation authority to the
+///                  chain granting the signer, see [crate::state::AuthorityDelegationChain]
 ///
 /// Requirements:
 ///
 /// - delegation record is initialized
 /// - delegation metadata is initialized
 /// - validator fees vault is initialized
+/// - signer is the delegation authority, one of its approved commit relayers or a sub-key
+///   granted the authority by the authority delegation chain passed, in the commit and
+///   undelegate scope if the commit allows the undelegation, the commit being attributed
+///   to the delegation authority in the commit record
+/// - or signer is one of the delegation authorities passed, added to the current delegation
+///   of the account, the commit being attributed to the signer, whose validator fees vault
+///   is passed, see [crate::processor::process_add_delegation_authority]
 /// - program config is initialized
-/// - commit state is uninitialized
+/// - delegation authority is whitelisted by the program config or by its whitelist shard,
+///   if the program has a program config
+/// - commit quota of the validator, if any, accepts the commit and its committed bytes in
+///   the current slot, see [crate::processor::process_set_validator_commit_quota]
+/// - commit state is uninitialized, unless streamed with
+///   [crate::processor::process_grow_commit_state]
 /// - commit record is uninitialized
 /// - delegated account holds at least the lamports indicated in the delegation record
+/// - committed lamports can be settled at finalize, leaving the delegated account rent
+///   exempt for the committed data length
+/// - committed lamports are at least the rent exempt minimum of the committed data length
+/// - committed data length is within the maximum account size of the delegation, if it
+///   rejects oversized states, see [crate::args::DelegateArgs::max_account_size]
+/// - compressed state, if the data is not raw, is a well-formed LZ4 block decompressing to
+///   exactly the length it declares, see [crate::args::CommitStateArgs::encoding]
 /// - account was not committed at a later slot
+/// - commit allows the undelegation if an undelegation request is overdue, see
+///   [crate::processor::process_request_undelegation], or if the delegation outlived the
+///   maximum delegation slots of the program config
+/// - ER block hash, if provided, differs from the one of the last finalized commit
+/// - base state hash, if provided, is claimed by the first commit and matches the hash of
+///   the data of the account recorded at delegation
+/// - commit schedule is passed if and only if the commits of the account are scheduled, in
+///   which case the commits of its current interval are not exhausted and its escrow,
+///   which must not be delegated, is passed
 ///
 /// Steps:
 /// 1. Check that the pda is delegated
 /// 2. Init a new PDA to store the new state
-/// 3. Copy the new state to the new PDA
+/// 3. Copy the new state to the new PDA behind a [PendingState] header, through which the
+///    composing programs can read the pending state, decompressing it if encoded, see
+///    [crate::args::Encoding]
 /// 4. Init a new PDA to store the record of the new state commitment
+/// 5. If the commits are scheduled, refund the validator the rent of the PDAs and pay it
+///    the commit fee from the escrow of the commit schedule
+/// 6. Emit a [CommitEvent] if the ER block hash is provided
 pub fn process_commit_state(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    let args = CommitStateArgs::try_from_slice(data).map_err(|_| ProgramError::BorshIoError)?;
+    let args = CommitStateArgsRef::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::BorshIoError)?;
 
     let commit_record_lamports = args.lamports;
     let commit_record_nonce = args.nonce;
     let allow_undelegation = args.allow_undelegation;
 
-    let [validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, validator_fees_vault, program_config_account, _system_program] =
-        accounts
-    else {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    };
+    let (accounts, authority_chain) = split_authority_chain(accounts);
+    let (accounts, delegation_authorities) = split_delegation_authorities(accounts);
+    let (accounts, whitelist_shard) = split_whitelist_shard(accounts);
+    let (accounts, commit_schedule) =
+        CommitScheduleAccounts::split_trailing(accounts, COMMIT_STATE_ACCOUNTS);
+    let ctx = CommitStateAccounts::try_from_accounts(accounts)?;
 
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
     let commit_args = CommitStateInternalArgs {
-        commit_state_bytes: NewState::FullBytes(&args.data),
+        commit_state_bytes: NewState::encoded(args.encoding, args.data)?,
         commit_record_lamports,
         commit_record_nonce,
         allow_undelegation,
-        validator,
-        delegated_account,
-        commit_state_account,
-        commit_record_account,
-        delegation_record_account,
-        delegation_metadata_account,
-        validator_fees_vault,
-        program_config_account,
+        er_block_hash: args.er_block_hash,
+        base_state_hash: args.base_state_hash,
+        escrow_spend: args.escrow_spend,
+        validator: ctx.validator,
+        delegated_account: ctx.delegated_account,
+        commit_state_account: ctx.commit_state_account,
+        commit_record_account: ctx.commit_record_account,
+        delegation_record_account: ctx.delegation_record_account,
+        delegation_metadata_account: ctx.delegation_metadata_account,
+        validator_fees_vault: ctx.validator_fees_vault,
+        program_config_account: ctx.program_config_account,
+        whitelist_shard,
+        delegation_authorities,
+        authority_chain,
+        commit_schedule,
+        rent: &rent,
+        slot,
     };
 
     process_commit_state_internal(commit_args)
 }
 
+/// The number of accounts of [process_commit_state], without the optional commit schedule
+const COMMIT_STATE_ACCOUNTS: usize = COMMIT_STATE_ACCOUNTS_SPEC.len();
+
+accounts_ctx! {
+    /// Accounts of [process_commit_state]
+    pub(crate) struct CommitStateAccounts {
+        validator: [signer] "validator",
+        delegated_account: [] "delegated account",
+        commit_state_account: [writable] "commit state",
+        commit_record_account: [writable] "commit record",
+        delegation_record_account: [] "delegation record",
+        delegation_metadata_account: [] "delegation metadata",
+        validator_fees_vault: [] "validator fees vault",
+        program_config_account: [] "program config",
+        _system_program: [] "system program",
+    }
+}
+
+/// Accounts of [process_commit_state]
+pub const COMMIT_STATE_ACCOUNTS_SPEC: AccountsSpec = CommitStateAccounts::SPEC;
+
 pub(crate) enum NewState<'a> {
     FullBytes(&'a [u8]),
+    /// The state compressed in an LZ4 block, decompressed into the commit state once it is
+    /// allocated for the decompressed length, see [Encoding::Lz4]
+    Lz4 {
+        data_len: usize,
+        block: &'a [u8],
+    },
     Diff(DiffSet<'a>),
+    /// The state already streamed in the commit state, of the given length
+    Streamed(usize),
+    /// The state committed by the Merkle root of its chunks, which are written with their
+    /// proof after the commit, see [ChunkedCommitState]
+    Chunked(ChunkedCommitState),
 }
 
-impl NewState<'_> {
+impl<'a> NewState<'a> {
+    /// The full state held by bytes in the given encoding
+    pub fn encoded(encoding: Encoding, bytes: &'a [u8]) -> Result<Self, ProgramError> {
+        match encoding {
+            Encoding::Raw => Ok(NewState::FullBytes(bytes)),
+            Encoding::Lz4 => {
+                let (data_len, block) = split_decompressed_len(bytes)?;
+                Ok(NewState::Lz4 { data_len, block })
+            }
+        }
+    }
+
     pub fn data_len(&self) -> usize {
         match self {
             NewState::FullBytes(bytes) => bytes.len(),
+            NewState::Lz4 { data_len, .. } => *data_len,
             NewState::Diff(diff) => diff.changed_len(),
+            NewState::Streamed(data_len) => *data_len,
+            NewState::Chunked(chunked_commit_state) => chunked_commit_state.data_len as usize,
+        }
+    }
+
+    /// The layout of the commit state holding the state once committed
+    pub fn commit_state_layout(&self) -> CommitStateLayout {
+        match self {
+            NewState::FullBytes(_)
+            | NewState::Lz4 { .. }
+            | NewState::Diff(_)
+            | NewState::Streamed(_) => CommitStateLayout::Pending,
+            NewState::Chunked(_) => CommitStateLayout::Chunked,
         }
     }
 }
@@ -107,6 +243,10 @@ pub(crate) struct CommitStateInternalArgs<'a> {
     pub(crate) commit_record_lamports: u64,
     pub(crate) commit_record_nonce: u64,
     pub(crate) allow_undelegation: bool,
+    pub(crate) er_block_hash: Option<ErBlockHash>,
+    pub(crate) base_state_hash: Option<[u8; 32]>,
+    /// The declared spend of the committed escrow, recorded for the finalize
+    pub(crate) escrow_spend: Option<u64>,
     pub(crate) validator: &'a AccountInfo,
     pub(crate) delegated_account: &'a AccountInfo,
     pub(crate) commit_state_account: &'a AccountInfo,
@@ -115,12 +255,389 @@ pub(crate) struct CommitStateInternalArgs<'a> {
     pub(crate) delegation_metadata_account: &'a AccountInfo,
     pub(crate) validator_fees_vault: &'a AccountInfo,
     pub(crate) program_config_account: &'a AccountInfo,
+    /// The validator whitelist shard of the delegation authority, if passed
+    pub(crate) whitelist_shard: Option<&'a AccountInfo>,
+    /// The delegation authorities of the delegated account, if passed
+    pub(crate) delegation_authorities: Option<&'a AccountInfo>,
+    /// The links of the authority delegation chain granting the signer, if passed
+    pub(crate) authority_chain: &'a [AccountInfo],
+    pub(crate) commit_schedule: Option<CommitScheduleAccounts<'a>>,
+    /// The rent sysvar, fetched once by the instruction
+    pub(crate) rent: &'a Rent,
+    /// The current slot, fetched once by the instruction
+    pub(crate) slot: u64,
+}
+
+/// The commit schedule of a delegated account and its escrow, trailing the accounts of a
+/// commit when the commits of the account are scheduled, see [CommitSchedule]
+#[derive(Clone, Copy)]
+pub(crate) struct CommitScheduleAccounts<'a> {
+    pub(crate) commit_schedule_account: &'a AccountInfo,
+    pub(crate) escrow: &'a AccountInfo,
+}
+
+impl<'a> CommitScheduleAccounts<'a> {
+    /// Split the commit schedule accounts trailing the `len` accounts of a commit, if any
+    pub(crate) fn split_trailing(
+        accounts: &'a [AccountInfo],
+        len: usize,
+    ) -> (&'a [AccountInfo], Option<Self>) {
+        match accounts {
+            [accounts @ .., commit_schedule_account, escrow] if accounts.len() == len => (
+                accounts,
+                Some(Self {
+                    commit_schedule_account,
+                    escrow,
+                }),
+            ),
+            _ => (accounts, None),
+        }
+    }
 }
 
 /// Commit a new state of a delegated Pda
 pub(crate) fn process_commit_state_internal(
     args: CommitStateInternalArgs,
 ) -> Result<(), ProgramError> {
+    trace!(
+        args.delegated_account.key(),
+        args.commit_record_nonce,
+        "commit",
+        "enter"
+    );
+    let (mut delegation_metadata, delegation_record_lamports, identity) =
+        validate_commit(&CommitValidationArgs {
+            data_len: args.commit_state_bytes.data_len(),
+            lamports: args.commit_record_lamports,
+            nonce: args.commit_record_nonce,
+            allow_undelegation: args.allow_undelegation,
+            er_block_hash: args.er_block_hash,
+            base_state_hash: args.base_state_hash,
+            validator: args.validator,
+            delegated_account: args.delegated_account,
+            delegation_record_account: args.delegation_record_account,
+            delegation_metadata_account: args.delegation_metadata_account,
+            validator_fees_vault: args.validator_fees_vault,
+            program_config_account: args.program_config_account,
+            whitelist_shard: args.whitelist_shard,
+            delegation_authorities: args.delegation_authorities,
+            authority_chain: args.authority_chain,
+            has_commit_schedule: args.commit_schedule.is_some(),
+            rent: args.rent,
+            slot: args.slot,
+        })?;
+
+    // The pending state header records the program interpreting the committed data
+    let owner = {
+        let delegation_record_data = args.delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)
+            .map_err(to_pinocchio_program_error)?
+            .owner
+    };
+
+    // Update delegation metadata undelegation flag, which is only set by commits allowing
+    // the undelegation so that the others can take a read lock on the delegation metadata.
+    // The validator funds the growth of the delegation metadata recording the undelegatable
+    // status of the delegation.
+    if args.allow_undelegation {
+        grow_pda_funded_by_payer(
+            args.delegation_metadata_account,
+            delegation_metadata.serialized_size(),
+            args.validator,
+            args.rent,
+        )?;
+        let mut delegation_metadata_data =
+            args.delegation_metadata_account.try_borrow_mut_data()?;
+        delegation_metadata
+            .to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())
+            .map_err(to_pinocchio_program_error)?;
+    }
+
+    // If committed lamports are more than the previous lamports balance, deposit the difference in the commitment account
+    // If committed lamports are less than the previous lamports balance, we have collateral to settle the balance at state finalization
+    // We need to do that so that the finalizer already have all the lamports from the validators ready at finalize time
+    // The finalizer can return any extra lamport to the validator during finalize, but this acts as the validator's proof of collateral
+    let extra_lamports = args
+        .commit_record_lamports
+        .saturating_sub(delegation_record_lamports);
+    if extra_lamports > 0 {
+        trace!(
+            args.delegated_account.key(),
+            args.commit_record_nonce,
+            "commit",
+            "deposit-lamports"
+        );
+        system::Transfer {
+            from: args.validator,
+            to: args.commit_state_account,
+            lamports: extra_lamports,
+        }
+        .invoke()?;
+    }
+
+    // Load the uninitialized PDAs, a streamed commit state is already initialized
+    let is_streamed = matches!(args.commit_state_bytes, NewState::Streamed(_));
+    let commit_state_bump = if is_streamed {
+        require_initialized_commit_state(args.delegated_account, args.commit_state_account, true)?;
+        None
+    } else {
+        Some(require_uninitialized_pda(
+            args.commit_state_account,
+            &[pda::COMMIT_STATE_TAG, args.delegated_account.key()],
+            &crate::fast::ID,
+            true,
+            CommitStateAccountCtx,
+        )?)
+    };
+    let commit_record_bump = require_uninitialized_pda(
+        args.commit_record_account,
+        &[pda::COMMIT_RECORD_TAG, args.delegated_account.key()],
+        &crate::fast::ID,
+        true,
+        CommitRecordCtx,
+    )?;
+
+    // Initialize the PDA containing the new committed state behind its pending state header,
+    // or the chunked commit state awaiting the chunks of the committed root
+    let commit_state_size = match &args.commit_state_bytes {
+        NewState::Chunked(chunked_commit_state) => chunked_commit_state.account_size(),
+        commit_state_bytes => PendingState::size_with_discriminator()
+            .checked_add(commit_state_bytes.data_len())
+            .ok_or(DlpError::Overflow)?,
+    };
+    if let Some(commit_state_bump) = commit_state_bump {
+        create_pda(
+            args.commit_state_account,
+            &crate::fast::ID,
+            commit_state_size,
+            &[Signer::from(&seeds!(
+                pda::COMMIT_STATE_TAG,
+                args.delegated_account.key(),
+                &[commit_state_bump]
+            ))],
+            args.validator,
+            args.rent,
+        )?;
+    } else {
+        // A streamed commit state grows to fit the pending state header in place of its own,
+        // the validator advancing the rent of the growth
+        let growth_lamports = args.rent.minimum_balance(commit_state_size).saturating_sub(
+            args.commit_state_account
+                .lamports()
+                .saturating_sub(extra_lamports),
+        );
+        if growth_lamports > 0 {
+            system::Transfer {
+                from: args.validator,
+                to: args.commit_state_account,
+                lamports: growth_lamports,
+            }
+            .invoke()?;
+        }
+        args.commit_state_account.resize(commit_state_size)?;
+    }
+
+    // Initialize the PDA containing the record of the committed state
+    create_pda(
+        args.commit_record_account,
+        &crate::fast::ID,
+        CommitRecord::size_with_discriminator(),
+        &[Signer::from(&seeds!(
+            pda::COMMIT_RECORD_TAG,
+            args.delegated_account.key(),
+            &[commit_record_bump]
+        ))],
+        args.validator,
+        args.rent,
+    )?;
+
+    // The commit PDAs now hold the rent advanced by the validator, on top of the collateral
+    let rent_advanced = args
+        .commit_state_account
+        .lamports()
+        .checked_sub(extra_lamports)
+        .and_then(|lamports| lamports.checked_add(args.commit_record_account.lamports()))
+        .ok_or(DlpError::Overflow)?;
+
+    // The commit record holds the growth of the delegation metadata recording the ER block
+    // hash until finalize, paid by the validator on top of the rent advanced
+    if args.er_block_hash.is_some() && delegation_metadata.last_er_block_hash.is_none() {
+        delegation_metadata.last_er_block_hash = args.er_block_hash;
+        let growth_lamports = args
+            .rent
+            .minimum_balance(delegation_metadata.serialized_size())
+            .saturating_sub(args.delegation_metadata_account.lamports());
+        if growth_lamports > 0 {
+            system::Transfer {
+                from: args.validator,
+                to: args.commit_record_account,
+                lamports: growth_lamports,
+            }
+            .invoke()?;
+        }
+    }
+
+    // The escrow of the commit schedule refunds the rent advanced to the validator, and is
+    // refunded it at finalize instead
+    if let Some(commit_schedule) = args.commit_schedule {
+        charge_commit_schedule(
+            commit_schedule,
+            args.delegated_account,
+            args.validator,
+            rent_advanced,
+            args.slot,
+        )?;
+    }
+
+    // Initialize the commit record
+    let commit_record = CommitRecord {
+        identity: identity.into(),
+        account: (*args.delegated_account.key()).into(),
+        nonce: args.commit_record_nonce,
+        lamports: args.commit_record_lamports,
+        slot: args.slot,
+        er_block_hash: args.er_block_hash.unwrap_or_default(),
+        rent_advanced,
+        escrow: args
+            .commit_schedule
+            .map(|commit_schedule| (*commit_schedule.escrow.key()).into())
+            .unwrap_or_default(),
+        escrow_spend: args.escrow_spend.unwrap_or_default(),
+        has_escrow_spend: args.escrow_spend.is_some().into(),
+        commit_state_layout: args.commit_state_bytes.commit_state_layout().into(),
+        padding: [0; 6],
+    };
+    let mut commit_record_data = args.commit_record_account.try_borrow_mut_data()?;
+    commit_record
+        .to_bytes_with_discriminator(&mut commit_record_data)
+        .map_err(to_pinocchio_program_error)?;
+
+    // Copy the new state to the initialized PDA, after the pending state header. A chunked
+    // commit state holds the data of the delegated account until its changed chunks are
+    // written
+    let mut commit_state_data = args.commit_state_account.try_borrow_mut_data()?;
+    if let NewState::Chunked(chunked_commit_state) = &args.commit_state_bytes {
+        let (header, data) = commit_state_data
+            .split_at_mut_checked(ChunkedCommitState::size_with_discriminator())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        chunked_commit_state
+            .to_bytes_with_discriminator(header)
+            .map_err(to_pinocchio_program_error)?;
+        let original_data = args.delegated_account.try_borrow_data()?;
+        let prefilled_len = original_data
+            .len()
+            .min(chunked_commit_state.data_len as usize);
+        let (Some(prefilled_data), Some(original_data)) = (
+            data.get_mut(..prefilled_len),
+            original_data.get(..prefilled_len),
+        ) else {
+            return Err(ProgramError::InvalidAccountData);
+        };
+        prefilled_data.copy_from_slice(original_data);
+    } else {
+        let flags = if args.allow_undelegation {
+            PENDING_STATE_ALLOWS_UNDELEGATION
+        } else {
+            0
+        };
+        let pending_state = PendingState::new(
+            owner,
+            args.commit_record_nonce,
+            args.commit_state_bytes.data_len(),
+            flags,
+        );
+        write_pending_state(&mut commit_state_data, &args, &pending_state)?;
+    }
+
+    if let Some(er_block_hash) = args.er_block_hash {
+        emit_commit_event(
+            args.delegated_account,
+            &identity,
+            args.commit_record_nonce,
+            &er_block_hash,
+        );
+    }
+
+    // TODO - Add additional validation for the commitment, e.g. sufficient validator stake
+
+    trace!(
+        args.delegated_account.key(),
+        args.commit_record_nonce,
+        "commit",
+        "exit"
+    );
+    Ok(())
+}
+
+/// Write the new state to the commit state after its [PendingState] header, moving a
+/// streamed state from behind its own header
+fn write_pending_state(
+    commit_state_data: &mut [u8],
+    args: &CommitStateInternalArgs,
+    pending_state: &PendingState,
+) -> ProgramResult {
+    let data_len = args.commit_state_bytes.data_len();
+    if let NewState::Streamed(_) = args.commit_state_bytes {
+        // Move the streamed data from behind the streamed commit state header
+        let streamed_start = StreamedCommitState::size_with_discriminator();
+        let streamed_end = streamed_start
+            .checked_add(data_len)
+            .ok_or(DlpError::Overflow)?;
+        if streamed_end > commit_state_data.len() {
+            return Err(DlpError::InvalidStreamedCommitState.into());
+        }
+        commit_state_data.copy_within(
+            streamed_start..streamed_end,
+            PendingState::size_with_discriminator(),
+        );
+    }
+    let (header, committed_data) = commit_state_data
+        .split_at_mut_checked(PendingState::size_with_discriminator())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    match &args.commit_state_bytes {
+        NewState::FullBytes(bytes) => committed_data.copy_from_slice(bytes),
+        NewState::Lz4 { block, .. } => decompress_lz4_into(block, committed_data)?,
+        NewState::Diff(diff) => {
+            let original_data = args.delegated_account.try_borrow_data()?;
+            merge_diff_copy(committed_data, &original_data, diff)?;
+        }
+        NewState::Streamed(_) | NewState::Chunked(_) => {}
+    }
+
+    pending_state
+        .to_bytes_with_discriminator(header)
+        .map_err(to_pinocchio_program_error)
+}
+
+/// Arguments for [validate_commit]
+pub(crate) struct CommitValidationArgs<'a> {
+    pub(crate) data_len: usize,
+    pub(crate) lamports: u64,
+    pub(crate) nonce: u64,
+    pub(crate) allow_undelegation: bool,
+    pub(crate) er_block_hash: Option<ErBlockHash>,
+    pub(crate) base_state_hash: Option<[u8; 32]>,
+    pub(crate) validator: &'a AccountInfo,
+    pub(crate) delegated_account: &'a AccountInfo,
+    pub(crate) delegation_record_account: &'a AccountInfo,
+    pub(crate) delegation_metadata_account: &'a AccountInfo,
+    pub(crate) validator_fees_vault: &'a AccountInfo,
+    pub(crate) program_config_account: &'a AccountInfo,
+    pub(crate) whitelist_shard: Option<&'a AccountInfo>,
+    pub(crate) delegation_authorities: Option<&'a AccountInfo>,
+    pub(crate) authority_chain: &'a [AccountInfo],
+    pub(crate) has_commit_schedule: bool,
+    pub(crate) rent: &'a Rent,
+    pub(crate) slot: u64,
+}
+
+/// Validate that the validator can commit a new state of a delegated Pda.
+/// Returns the delegation metadata with the updated undelegation flag, which is left to
+/// the caller to write, the lamports of the delegation record and the validator identity
+/// the commit is attributed to, which signed it or approved the relayer which did.
+pub(crate) fn validate_commit(
+    args: &CommitValidationArgs,
+) -> Result<(DelegationMetadata, u64, Pubkey), ProgramError> {
     // Check that the origin account is delegated
     require_owned_pda(
         args.delegated_account,
@@ -136,26 +653,71 @@ pub(crate) fn process_commit_state_internal(
     require_initialized_delegation_metadata(
         args.delegated_account,
         args.delegation_metadata_account,
-        true,
+        args.allow_undelegation,
     )?;
-    require_initialized_validator_fees_vault(args.validator, args.validator_fees_vault, false)?;
 
     // Read delegation metadata
-    let mut delegation_metadata_data = args.delegation_metadata_account.try_borrow_mut_data()?;
+    let delegation_metadata_data = args.delegation_metadata_account.try_borrow_data()?;
     let mut delegation_metadata =
         DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)
             .map_err(to_pinocchio_program_error)?;
 
+    // The flagged nonces are reserved to the commits pushed by the owner program
+    if args.nonce & OWNER_COMMIT_NONCE_FLAG != 0 {
+        log!(
+            "Nonce {} is reserved to the owner commits. Rejecting commit",
+            args.nonce
+        );
+        return Err(DlpError::NonceOutOfOrder.into());
+    }
+
     // To preserve correct history of account updates we require sequential commits
-    if args.commit_record_nonce != delegation_metadata.last_update_nonce + 1 {
+    if args.nonce != delegation_metadata.last_update_nonce + 1 {
         log!(
             "Nonce {} is incorrect, previous nonce is {}. Rejecting commit",
-            args.commit_record_nonce,
+            args.nonce,
             delegation_metadata.last_update_nonce
         );
         return Err(DlpError::NonceOutOfOrder.into());
     }
 
+    // Every commit binding itself to an ER block must come from a new block
+    if args.er_block_hash.is_some() && args.er_block_hash == delegation_metadata.last_er_block_hash
+    {
+        log!("ER block hash did not change since the last commit. Rejecting commit");
+        return Err(DlpError::ErBlockHashNotChanged.into());
+    }
+
+    // The first commit can check that the validator started from the delegated data
+    if let Some(base_state_hash) = args.base_state_hash {
+        if args.nonce != 1 {
+            log!("base state hash can only be provided by the first commit");
+            return Err(DlpError::BaseStateHashMismatch.into());
+        }
+        match delegation_metadata.delegated_data_hash {
+            Some(delegated_data_hash) if delegated_data_hash == base_state_hash => {}
+            Some(_) => {
+                log!("base state hash does not match the data of the account at delegation");
+                return Err(DlpError::BaseStateHashMismatch.into());
+            }
+            None => {
+                log!("no hash of the data of the account was recorded at delegation");
+                return Err(DlpError::BaseStateHashMismatch.into());
+            }
+        }
+    }
+
+    // Scheduled commits are funded by the escrow of the commit schedule, which the others
+    // must not charge
+    if delegation_metadata.commit_scheduled && !args.has_commit_schedule {
+        log!("commits of the account are scheduled, the commit schedule must be passed");
+        return Err(DlpError::InvalidCommitSchedule.into());
+    }
+    if !delegation_metadata.commit_scheduled && args.has_commit_schedule {
+        log!("commits of the account are not scheduled");
+        return Err(DlpError::InvalidCommitSchedule.into());
+    }
+
     // Once the account is marked as undelegatable, any subsequent commit should fail
     if delegation_metadata.is_undelegatable {
         log!("delegation metadata is already undelegated: ");
@@ -163,11 +725,18 @@ pub(crate) fn process_commit_state_internal(
         return Err(DlpError::AlreadyUndelegated.into());
     }
 
-    // Update delegation metadata undelegation flag
+    // Once the grace period of an undelegation request elapsed, commits must undelegate
+    if let Some(undelegation_request) = delegation_metadata.undelegation_request {
+        if !args.allow_undelegation && undelegation_request.is_overdue(args.slot) {
+            log!("undelegation was requested and is overdue: ");
+            pubkey::log(args.delegation_metadata_account.key());
+            return Err(DlpError::UndelegationRequired.into());
+        }
+    }
     delegation_metadata.is_undelegatable = args.allow_undelegation;
-    delegation_metadata
-        .to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())
-        .map_err(to_pinocchio_program_error)?;
+
+    // A compromised validator must not grow the account past the cap of the delegation
+    require_committed_data_len(delegation_metadata.max_account_size, args.data_len)?;
 
     // Load delegation record
     let delegation_record_data = args.delegation_record_account.try_borrow_data()?;
@@ -175,14 +744,35 @@ pub(crate) fn process_commit_state_internal(
         DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)
             .map_err(to_pinocchio_program_error)?;
 
-    // Check that the authority is allowed to commit
-    if !pubkey_eq(delegation_record.authority.as_array(), args.validator.key()) {
-        log!("validator is not the delegation authority. validator: ");
-        pubkey::log(args.validator.key());
-        log!("delegation authority: ");
-        pubkey::log(delegation_record.authority.as_array());
-        return Err(DlpError::InvalidAuthority.into());
-    }
+    // Check that the authority is allowed to commit, itself, through a relayer or through a
+    // sub-key, which must also be granted the undelegation if the commit allows it. The
+    // delegation authorities commit as themselves, attributing the commit to the signer.
+    let identity = if is_delegation_authority(
+        args.delegation_authorities,
+        args.delegated_account.key(),
+        delegation_record.delegation_slot,
+        args.validator.key(),
+    )? {
+        *args.validator.key()
+    } else {
+        delegation_record.authority.to_bytes()
+    };
+    let scope = if args.allow_undelegation {
+        AuthorityScope::CommitAndUndelegate
+    } else {
+        AuthorityScope::Commit
+    };
+    require_commit_authority(
+        args.validator,
+        &identity,
+        args.validator_fees_vault,
+        args.authority_chain,
+        scope,
+        args.slot,
+    )?;
+
+    // Bound the commits of the validator per slot, if it has a commit quota
+    record_commit_quota(args.validator_fees_vault, args.data_len, args.slot)?;
 
     // If there was an issue with the lamport accounting in the past, abort (this should never happen)
     if args.delegated_account.lamports() < delegation_record.lamports {
@@ -192,23 +782,18 @@ pub(crate) fn process_commit_state_internal(
         return Err(DlpError::InvalidDelegatedState.into());
     }
 
-    // If committed lamports are more than the previous lamports balance, deposit the difference in the commitment account
-    // If committed lamports are less than the previous lamports balance, we have collateral to settle the balance at state finalization
-    // We need to do that so that the finalizer already have all the lamports from the validators ready at finalize time
-    // The finalizer can return any extra lamport to the validator during finalize, but this acts as the validator's proof of collateral
-    if args.commit_record_lamports > delegation_record.lamports {
-        let extra_lamports = args
-            .commit_record_lamports
-            .checked_sub(delegation_record.lamports)
-            .ok_or(DlpError::Overflow)?;
+    // Dry-run the lamports settlement so that the commit can always be finalized
+    require_settleable_commit(
+        args.delegated_account,
+        delegation_record.lamports,
+        args.lamports,
+        args.data_len,
+        args.rent,
+    )?;
 
-        system::Transfer {
-            from: args.validator,
-            to: args.commit_state_account,
-            lamports: extra_lamports,
-        }
-        .invoke()?;
-    }
+    // The committed lamports must keep the committed data rent exempt on their own, whatever
+    // the lamports the delegated account received while delegated
+    require_rent_exempt_commit(args.lamports, args.data_len, args.rent)?;
 
     // Load the program configuration and validate it, if any
     let has_program_config = require_program_config(
@@ -221,82 +806,184 @@ pub(crate) fn process_commit_state_internal(
 
         let program_config = ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)
             .map_err(to_pinocchio_program_error)?;
-        if !program_config
-            .approved_validators
-            .contains(&(*args.validator.key()).into())
+        // The validators migrated to shards are no longer in the program config
+        if program_config.has_whitelist()
+            && !program_config
+                .approved_validators
+                .contains(&identity.into())
+            && !is_whitelisted_by_shard(
+                args.whitelist_shard,
+                delegation_record.owner.as_array(),
+                &identity,
+            )
         {
             log!("validator is not whitelisted in the program config: ");
-            pubkey::log(args.validator.key());
+            pubkey::log(&identity);
             return Err(DlpError::InvalidWhitelistProgramConfig.into());
         }
+        if !program_config.is_allowed_data_len(args.data_len) {
+            log!(
+                "committed data length {} is not allowed by the program config",
+                args.data_len
+            );
+            return Err(DlpError::InvalidCommittedDataLen.into());
+        }
+        // Once the delegation outlived the maximum delegation slots, commits must undelegate
+        if !args.allow_undelegation
+            && program_config.is_delegation_expired(delegation_record.delegation_slot, args.slot)
+        {
+            log!("delegation outlived the maximum delegation slots of the program: ");
+            pubkey::log(args.delegated_account.key());
+            return Err(DlpError::DelegationExpired.into());
+        }
     }
 
-    // Load the uninitialized PDAs
-    let commit_state_bump = require_uninitialized_pda(
-        args.commit_state_account,
-        &[pda::COMMIT_STATE_TAG, args.delegated_account.key()],
-        &crate::fast::ID,
-        true,
-        CommitStateAccountCtx,
-    )?;
-    let commit_record_bump = require_uninitialized_pda(
-        args.commit_record_account,
-        &[pda::COMMIT_RECORD_TAG, args.delegated_account.key()],
-        &crate::fast::ID,
-        true,
-        CommitRecordCtx,
-    )?;
+    Ok((delegation_metadata, delegation_record.lamports, identity))
+}
 
-    // Initialize the PDA containing the new committed state
-    create_pda(
-        args.commit_state_account,
-        &crate::fast::ID,
-        args.commit_state_bytes.data_len(),
-        &[Signer::from(&seeds!(
-            pda::COMMIT_STATE_TAG,
-            args.delegated_account.key(),
-            &[commit_state_bump]
-        ))],
-        args.validator,
-    )?;
+/// Reject committed data longer than the account size cap of the delegation, unless its
+/// policy truncates the data on finalize, see [crate::args::DelegateArgs::max_account_size]
+pub(crate) fn require_committed_data_len(
+    max_account_size: Option<MaxAccountSize>,
+    data_len: usize,
+) -> ProgramResult {
+    if let Some(max_account_size) = max_account_size {
+        if max_account_size.rejects(data_len) {
+            log!(
+                "committed data length {} exceeds the maximum account size {}",
+                data_len,
+                max_account_size.max_len
+            );
+            return Err(DlpError::CommittedDataTooLarge.into());
+        }
+    }
+    Ok(())
+}
 
-    // Initialize the PDA containing the record of the committed state
-    create_pda(
-        args.commit_record_account,
-        &crate::fast::ID,
-        CommitRecord::size_with_discriminator(),
-        &[Signer::from(&seeds!(
-            pda::COMMIT_RECORD_TAG,
-            args.delegated_account.key(),
-            &[commit_record_bump]
-        ))],
-        args.validator,
-    )?;
+/// The committed data retained by the delegated account once finalized, truncated to the
+/// account size cap of the delegation if its policy truncates
+pub(crate) fn retained_committed_data(
+    max_account_size: Option<MaxAccountSize>,
+    committed_data: &[u8],
+) -> Result<&[u8], ProgramError> {
+    require_committed_data_len(max_account_size, committed_data.len())?;
+    let retained_len = max_account_size.map_or(committed_data.len(), |max_account_size| {
+        max_account_size.retained_len(committed_data.len())
+    });
+    committed_data
+        .get(..retained_len)
+        .ok_or(ProgramError::InvalidAccountData)
+}
 
-    // Initialize the commit record
-    let commit_record = CommitRecord {
-        identity: (*args.validator.key()).into(),
-        account: (*args.delegated_account.key()).into(),
-        nonce: args.commit_record_nonce,
-        lamports: args.commit_record_lamports,
+/// Reject commits claiming fewer lamports than the rent exempt minimum of the committed data
+/// length, which would leave the finalized account rent vulnerable
+pub(crate) fn require_rent_exempt_commit(
+    lamports: u64,
+    data_len: usize,
+    rent: &Rent,
+) -> ProgramResult {
+    let minimum_balance = rent.minimum_balance(data_len);
+    if lamports < minimum_balance {
+        log!(
+            "committed lamports {} are below the rent exempt minimum {} of {} bytes",
+            lamports,
+            minimum_balance,
+            data_len
+        );
+        return Err(DlpError::CommittedLamportsNotRentExempt.into());
+    }
+    Ok(())
+}
+
+/// Record a commit in the commit quota of the validator, if any, which requires the
+/// validator fees vault to be writable, see [crate::state::CommitQuota]
+pub(crate) fn record_commit_quota(
+    validator_fees_vault: &AccountInfo,
+    data_len: usize,
+    slot: u64,
+) -> ProgramResult {
+    let mut vault = {
+        let validator_fees_vault_data = validator_fees_vault.try_borrow_data()?;
+        ValidatorFeesVault::try_from_account_data(&validator_fees_vault_data)
+            .map_err(to_pinocchio_program_error)?
     };
-    let mut commit_record_data = args.commit_record_account.try_borrow_mut_data()?;
-    commit_record
-        .to_bytes_with_discriminator(&mut commit_record_data)
-        .map_err(to_pinocchio_program_error)?;
+    if !vault.commit_quota.is_limited() {
+        return Ok(());
+    }
+    require_writable(validator_fees_vault, "validator fees vault")?;
+    if !vault.commit_quota.record_commit(slot, data_len as u64) {
+        log!(
+            "commit quota accepts {} commits and {} bytes per slot",
+            vault.commit_quota.max_commits_per_slot,
+            vault.commit_quota.max_bytes_per_slot
+        );
+        return Err(DlpError::CommitQuotaExceeded.into());
+    }
+    let mut validator_fees_vault_data = validator_fees_vault.try_borrow_mut_data()?;
+    vault
+        .to_bytes_with_discriminator(&mut validator_fees_vault_data.as_mut())
+        .map_err(to_pinocchio_program_error)
+}
 
-    // Copy the new state to the initialized PDA
-    let mut commit_state_data = args.commit_state_account.try_borrow_mut_data()?;
+/// Record a commit in the commit schedule and pay the validator the rent it advanced and
+/// the commit fee from the escrow of the commit schedule
+pub(crate) fn charge_commit_schedule(
+    accounts: CommitScheduleAccounts,
+    delegated_account: &AccountInfo,
+    validator: &AccountInfo,
+    rent_advanced: u64,
+    slot: u64,
+) -> ProgramResult {
+    require_initialized_commit_schedule(delegated_account, accounts.commit_schedule_account, true)?;
+    let mut commit_schedule_data = accounts.commit_schedule_account.try_borrow_mut_data()?;
+    let commit_schedule =
+        CommitSchedule::try_from_bytes_with_discriminator_mut(&mut commit_schedule_data)
+            .map_err(to_pinocchio_program_error)?;
+    if !pubkey_eq(commit_schedule.escrow.as_array(), accounts.escrow.key()) {
+        log!("escrow is not the one of the commit schedule: ");
+        pubkey::log(accounts.escrow.key());
+        return Err(DlpError::InvalidCommitSchedule.into());
+    }
+    if !commit_schedule.record_commit(slot) {
+        log!(
+            "commit schedule accepts {} commits per interval of {} slots",
+            commit_schedule.max_commits_per_interval,
+            commit_schedule.interval_slots
+        );
+        return Err(DlpError::CommitScheduleExceeded.into());
+    }
 
-    match args.commit_state_bytes {
-        NewState::FullBytes(bytes) => (*commit_state_data).copy_from_slice(bytes),
-        NewState::Diff(diff) => {
-            let original_data = args.delegated_account.try_borrow_data()?;
-            merge_diff_copy(&mut commit_state_data, &original_data, &diff)?;
+    let lamports = rent_advanced
+        .checked_add(commit_schedule.commit_fee_lamports)
+        .ok_or(DlpError::Overflow)?;
+    if lamports > 0 {
+        system::Transfer {
+            from: accounts.escrow,
+            to: validator,
+            lamports,
         }
+        .invoke_signed(&[Signer::from(&seeds!(
+            pda::EPHEMERAL_BALANCE_TAG,
+            commit_schedule.escrow_payer.as_array(),
+            &[commit_schedule.escrow_index],
+            &[commit_schedule.escrow_bump]
+        ))])?;
     }
-
-    // TODO - Add additional validation for the commitment, e.g. sufficient validator stake
-
     Ok(())
 }
+
+/// Log the [CommitEvent], see [crate::events] for the format
+pub(crate) fn emit_commit_event(
+    delegated_account: &AccountInfo,
+    validator: &Pubkey,
+    nonce: u64,
+    er_block_hash: &ErBlockHash,
+) {
+    let mut event = [0u8; CommitEvent::SIZE_WITH_DISCRIMINATOR];
+    event[0] = EventDiscriminator::Commit.into();
+    event[1..33].copy_from_slice(delegated_account.key());
+    event[33..65].copy_from_slice(validator);
+    event[65..73].copy_from_slice(&nonce.to_le_bytes());
+    event[73..].copy_from_slice(er_block_hash);
+    sol_log_data(&[&event]);
+}
@@ -1,39 +1,96 @@
-use crate::args::CommitStateFromBufferArgs;
-use crate::processor::fast::{process_commit_state_internal, CommitStateInternalArgs};
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::{CommitStateFromBufferArgs, Encoding};
+use crate::error::DlpError;
+use crate::log::log;
+use crate::processor::fast::utils::authority_chain::split_authority_chain;
+use crate::processor::fast::utils::delegation_authorities::split_delegation_authorities;
+use crate::processor::fast::utils::whitelist_shard::split_whitelist_shard;
+use crate::processor::fast::{
+    process_commit_state_internal, CommitScheduleAccounts, CommitStateInternalArgs,
+};
+use crate::state::{AccountDiscriminator, CommitBuffer, StreamedCommitState};
 
-use borsh::BorshDeserialize;
 use pinocchio::account_info::AccountInfo;
 use pinocchio::program_error::ProgramError;
-use pinocchio::pubkey::Pubkey;
+use pinocchio::pubkey::{pubkey_eq, Pubkey};
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
 use pinocchio::ProgramResult;
 
 use super::NewState;
 
+/// Accounts of [process_commit_state_from_buffer]
+pub const COMMIT_STATE_FROM_BUFFER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("commit state"),
+    AccountSpec::writable("commit record"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("state buffer"),
+    AccountSpec::readonly("validator fees vault"),
+    AccountSpec::readonly("program config"),
+    AccountSpec::readonly("system program"),
+];
+
 pub fn process_commit_state_from_buffer(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
+    let (accounts, authority_chain) = split_authority_chain(accounts);
+    let (accounts, delegation_authorities) = split_delegation_authorities(accounts);
+    let (accounts, whitelist_shard) = split_whitelist_shard(accounts);
+    let (accounts, commit_schedule) = CommitScheduleAccounts::split_trailing(accounts, 10);
     let [validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, state_buffer_account, validator_fees_vault, program_config_account, _system_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    let args =
-        CommitStateFromBufferArgs::try_from_slice(data).map_err(|_| ProgramError::BorshIoError)?;
+    let args = CommitStateFromBufferArgs::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::BorshIoError)?;
 
     let commit_record_lamports = args.lamports;
     let commit_record_nonce = args.nonce;
     let allow_undelegation = args.allow_undelegation;
 
-    let state = state_buffer_account.try_borrow_data()?;
+    // The commit state itself is the buffer once the state was streamed into it
+    let state;
+    let commit_state_bytes = if pubkey_eq(state_buffer_account.key(), commit_state_account.key()) {
+        let commit_state_data = commit_state_account.try_borrow_data()?;
+        let streamed_data = StreamedCommitState::streamed_data(&commit_state_data)
+            .ok_or(DlpError::InvalidStreamedCommitState)?;
+        // The streamed state is committed in place, it cannot be decompressed into itself
+        if args.encoding != Encoding::Raw {
+            log!("a streamed commit state must be raw");
+            return Err(DlpError::InvalidCompressedState.into());
+        }
+        NewState::Streamed(streamed_data.len())
+    } else {
+        state = state_buffer_account.try_borrow_data()?;
+        // A commit buffer holds the state following its header, once fully allocated
+        if pubkey_eq(state_buffer_account.owner(), &crate::fast::ID)
+            && state.starts_with(&AccountDiscriminator::CommitBuffer.to_bytes())
+        {
+            NewState::encoded(
+                args.encoding,
+                CommitBuffer::staged_data(&state).ok_or(DlpError::InvalidCommitBuffer)?,
+            )?
+        } else {
+            NewState::encoded(args.encoding, &state)?
+        }
+    };
 
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
     let commit_args = CommitStateInternalArgs {
-        commit_state_bytes: NewState::FullBytes(&state),
+        commit_state_bytes,
         commit_record_lamports,
         commit_record_nonce,
         allow_undelegation,
+        er_block_hash: args.er_block_hash,
+        base_state_hash: None,
+        escrow_spend: None,
         validator,
         delegated_account,
         commit_state_account,
@@ -42,6 +99,12 @@ pub fn process_commit_state_from_buffer(
         delegation_metadata_account,
         validator_fees_vault,
         program_config_account,
+        whitelist_shard,
+        delegation_authorities,
+        authority_chain,
+        commit_schedule,
+        rent: &rent,
+        slot,
     };
     process_commit_state_internal(commit_args)
 }
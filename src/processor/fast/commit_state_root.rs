@@ -0,0 +1,116 @@
+use crate::accounts_spec::AccountsSpec;
+use crate::args::CommitStateRootArgs;
+use crate::error::DlpError;
+use crate::log::log;
+use crate::processor::fast::utils::authority_chain::split_authority_chain;
+use crate::processor::fast::utils::delegation_authorities::split_delegation_authorities;
+use crate::processor::fast::utils::whitelist_shard::split_whitelist_shard;
+use crate::processor::fast::{
+    process_commit_state_internal, CommitScheduleAccounts, CommitStateAccounts,
+    CommitStateInternalArgs, COMMIT_STATE_ACCOUNTS_SPEC,
+};
+use crate::state::ChunkedCommitState;
+
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::ProgramResult;
+
+use super::NewState;
+
+/// Accounts of [process_commit_state_root], the ones of
+/// [crate::processor::fast::process_commit_state]
+pub const COMMIT_STATE_ROOT_ACCOUNTS_SPEC: AccountsSpec = COMMIT_STATE_ACCOUNTS_SPEC;
+
+/// Commit a new state of a delegated PDA by the Merkle root of its chunks, for the accounts
+/// too large to be committed in one transaction, even as a diff
+///
+/// Accounts: the accounts of [crate::processor::fast::process_commit_state]
+///
+/// Requirements:
+///
+/// - the requirements of [crate::processor::fast::process_commit_state], for the length
+///   of the committed state
+/// - committed state is not empty and its chunks are not empty
+/// - chunks to write are at most the chunks of the committed state
+///
+/// Steps:
+///
+/// 1. Check that the pda is delegated
+/// 2. Init the commit state PDA, holding a [ChunkedCommitState] header with the root, the
+///    data of the committed state pre-filled with the data of the delegated account and the
+///    bitmap of its chunks written
+/// 3. Init a new PDA to store the record of the new state commitment
+///
+/// Usage:
+///
+/// The chunks changed from the data of the delegated account are then written across
+/// transactions with their Merkle proof by [crate::processor::process_commit_state_chunk].
+/// Finalize rejects the commit until `chunks_to_write` chunks are written, each one proven
+/// against the root, the unchanged chunks keeping the data of the delegated account.
+pub fn process_commit_state_root(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = CommitStateRootArgs::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::BorshIoError)?;
+
+    let (accounts, authority_chain) = split_authority_chain(accounts);
+    let (accounts, delegation_authorities) = split_delegation_authorities(accounts);
+    let (accounts, whitelist_shard) = split_whitelist_shard(accounts);
+    let (accounts, commit_schedule) =
+        CommitScheduleAccounts::split_trailing(accounts, COMMIT_STATE_ROOT_ACCOUNTS_SPEC.len());
+    let ctx = CommitStateAccounts::try_from_accounts(accounts)?;
+
+    if args.data_len == 0 || args.chunk_size == 0 {
+        log!(
+            "chunked state of {} bytes cannot be split in chunks of {} bytes",
+            args.data_len,
+            args.chunk_size
+        );
+        return Err(DlpError::InvalidChunkedCommitState.into());
+    }
+    let chunked_commit_state = ChunkedCommitState::new(
+        args.root,
+        args.data_len,
+        args.chunk_size,
+        args.chunks_to_write,
+    );
+    if args.chunks_to_write as usize > chunked_commit_state.chunks_count() {
+        log!(
+            "{} chunks to write exceed the {} chunks of the state",
+            args.chunks_to_write,
+            chunked_commit_state.chunks_count()
+        );
+        return Err(DlpError::InvalidChunkedCommitState.into());
+    }
+
+    let rent = Rent::get()?;
+    let slot = Clock::get()?.slot;
+    let commit_args = CommitStateInternalArgs {
+        commit_state_bytes: NewState::Chunked(chunked_commit_state),
+        commit_record_lamports: args.lamports,
+        commit_record_nonce: args.nonce,
+        allow_undelegation: args.allow_undelegation,
+        er_block_hash: args.er_block_hash,
+        base_state_hash: None,
+        escrow_spend: None,
+        validator: ctx.validator,
+        delegated_account: ctx.delegated_account,
+        commit_state_account: ctx.commit_state_account,
+        commit_record_account: ctx.commit_record_account,
+        delegation_record_account: ctx.delegation_record_account,
+        delegation_metadata_account: ctx.delegation_metadata_account,
+        validator_fees_vault: ctx.validator_fees_vault,
+        program_config_account: ctx.program_config_account,
+        whitelist_shard,
+        delegation_authorities,
+        authority_chain,
+        commit_schedule,
+        rent: &rent,
+        slot,
+    };
+    process_commit_state_internal(commit_args)
+}
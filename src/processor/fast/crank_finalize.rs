@@ -0,0 +1,157 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::log;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
+use pinocchio::ProgramResult;
+
+use crate::consts::{CRANK_FINALIZE_BOUNTY_LAMPORTS, CRANK_FINALIZE_DELAY_SLOTS};
+use crate::error::DlpError;
+use crate::processor::fast::utils::earnings_ledger::split_earnings_ledger;
+use crate::processor::fast::utils::protocol_stats::split_protocol_stats;
+use crate::processor::fast::utils::requires::{
+    require_initialized_commit_record, require_initialized_commit_state,
+    require_initialized_delegation_metadata, require_initialized_delegation_record,
+    require_initialized_validator_fees_vault, require_owned_pda, require_signer, require_writable,
+};
+use crate::state::CommitRecord;
+use crate::trace::trace;
+
+use super::finalize::{finalize_commit, PendingCommit};
+use super::to_pinocchio_program_error;
+
+/// Accounts of [process_crank_finalize]
+pub const CRANK_FINALIZE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("cranker"),
+    AccountSpec::writable("validator"),
+    AccountSpec::writable("delegated account"),
+    AccountSpec::writable("commit state"),
+    AccountSpec::writable("commit record"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::writable("validator fees vault"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Finalize a pending commit on behalf of the validator that committed it, once the
+/// finalize delay has elapsed
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the cranker account, receiving the bounty
+/// 1: `[writable]`         the validator account that committed the state
+/// 2: `[writable]`         the delegated account
+/// 3: `[writable]`         the commit state account
+/// 4: `[writable]`         the commit record account
+/// 5: `[writable]`         the delegation record account
+/// 6: `[writable]`         the delegation metadata account
+/// 7: `[writable]`         the validator fees vault account
+/// 8: `[]`                 the system program
+/// 9: `[writable]`         (optional) the escrow which funded the commit, see
+///                         [crate::processor::fast::process_finalize]
+/// 10: `[writable]`        (optional) the earnings ledger page of the validator
+/// 11: `[writable]`        (optional) the protocol stats PDA, passed last
+///
+/// Requirements:
+///
+/// - same as [crate::processor::fast::process_finalize], except that the validator
+///   does not need to sign
+/// - at least [CRANK_FINALIZE_DELAY_SLOTS] slots passed since the commit slot
+/// - commit did not declare an escrow spend, which only the validator can settle along
+///   the call handlers of the escrow, see [CommitRecord::escrow_spend]
+///
+/// Steps:
+///
+/// 1. Check that the commit is past the finalize deadline
+/// 2. Finalize the commit, refunding the commit PDAs rent to the validator
+/// 3. Pay the bounty from the validator fees vault to the cranker, keeping the
+///    vault rent exempt
+pub fn process_crank_finalize(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // The escrow, the earnings ledger page and the protocol stats are optional trailing
+    // accounts
+    let (accounts, protocol_stats) = split_protocol_stats(accounts);
+    let (accounts, earnings_ledger) = split_earnings_ledger(accounts);
+    let (accounts, escrow_account) = accounts.split_at(accounts.len().min(CRANK_FINALIZE_ACCOUNTS));
+    let [cranker, validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, validator_fees_vault, _system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    require_signer(cranker, "cranker")?;
+    require_writable(cranker, "cranker")?;
+    require_writable(validator, "validator")?;
+    require_owned_pda(delegated_account, &crate::fast::ID, "delegated account")?;
+    require_initialized_delegation_record(delegated_account, delegation_record_account, true)?;
+    require_initialized_delegation_metadata(delegated_account, delegation_metadata_account, true)?;
+    require_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
+    require_initialized_commit_state(delegated_account, commit_state_account, true)?;
+    require_initialized_commit_record(delegated_account, commit_record_account, true)?;
+
+    // Check that the finalize deadline of the commit has passed
+    let commit_record_data = commit_record_account.try_borrow_data()?;
+    let commit_record = CommitRecord::try_from_bytes_with_discriminator(&commit_record_data)
+        .map_err(to_pinocchio_program_error)?;
+    let deadline = commit_record
+        .slot
+        .saturating_add(CRANK_FINALIZE_DELAY_SLOTS);
+    let current_slot = Clock::get()?.slot;
+    if current_slot < deadline {
+        log!(
+            "Commit can be finalized by a crank from slot {}, current slot is {}",
+            deadline,
+            current_slot
+        );
+        return Err(DlpError::CrankFinalizeTooEarly.into());
+    }
+    let nonce = commit_record.nonce;
+    trace!(delegated_account.key(), nonce, "crank-finalize", "enter");
+    drop(commit_record_data);
+
+    let rent = Rent::get()?;
+    finalize_commit(
+        &rent,
+        validator,
+        delegated_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        validator_fees_vault,
+        PendingCommit::Pdas {
+            commit_state_account,
+            commit_record_account,
+            trailing_accounts: escrow_account,
+        },
+        earnings_ledger,
+        protocol_stats,
+        None,
+        None,
+    )?;
+
+    // Pay the bounty, keeping the validator fees vault rent exempt
+    let vault_min_rent = rent.minimum_balance(validator_fees_vault.data_len());
+    let bounty = validator_fees_vault
+        .lamports()
+        .saturating_sub(vault_min_rent)
+        .min(CRANK_FINALIZE_BOUNTY_LAMPORTS);
+    if bounty > 0 {
+        *validator_fees_vault.try_borrow_mut_lamports()? = validator_fees_vault
+            .lamports()
+            .checked_sub(bounty)
+            .ok_or(DlpError::Overflow)?;
+        *cranker.try_borrow_mut_lamports()? = cranker
+            .lamports()
+            .checked_add(bounty)
+            .ok_or(DlpError::Overflow)?;
+    }
+
+    trace!(delegated_account.key(), nonce, "crank-finalize", "exit");
+    Ok(())
+}
+
+/// The number of accounts of [process_crank_finalize], without the optional escrow
+const CRANK_FINALIZE_ACCOUNTS: usize = CRANK_FINALIZE_ACCOUNTS_SPEC.len();
@@ -1,26 +1,56 @@
-use borsh::BorshDeserialize;
-use pinocchio::instruction::{Seed, Signer};
+use alloc::vec::Vec;
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::log;
+use pinocchio::cpi::invoke;
+use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
+use pinocchio::log::sol_log_data;
 use pinocchio::pubkey::{self, pubkey_eq};
 use pinocchio::sysvars::clock::Clock;
+use pinocchio::sysvars::rent::Rent;
 use pinocchio::sysvars::Sysvar;
 use pinocchio::{
     account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
 };
-use pinocchio_log::log;
+use pinocchio_system::instructions as system;
 
-use crate::args::DelegateArgs;
-use crate::consts::DEFAULT_VALIDATOR_IDENTITY;
+use crate::args::{commit_state_hash, DelegateArgs, Seeds};
+use crate::consts::{
+    DEFAULT_VALIDATOR_IDENTITY, EXTERNAL_VALIDATE_DELEGATION_DISCRIMINATOR,
+    MAX_HASHED_DELEGATED_DATA_LEN,
+};
 use crate::error::DlpError;
+use crate::events::{DelegateEvent, EventDiscriminator};
 use crate::pda;
 use crate::processor::fast::to_pinocchio_program_error;
-use crate::processor::fast::utils::{pda::create_pda, requires::require_uninitialized_pda};
+use crate::processor::fast::utils::{
+    pda::{close_pda, create_pda},
+    protocol_stats::{record_tvl_change, split_protocol_stats},
+    rent_co_payer::split_rent_co_payer,
+    requires::require_uninitialized_pda,
+};
 use crate::processor::utils::curve::is_on_curve_fast;
-use crate::state::{DelegationMetadata, DelegationRecord};
+use crate::state::{
+    AccountDiscriminator, DelegationMetadata, DelegationRecord, ProgramConfig, StagedDelegateBuffer,
+};
+use crate::trace::trace;
 
 use crate::processor::fast::utils::requires::{
-    require_owned_pda, require_pda, require_signer, DelegationMetadataCtx, DelegationRecordCtx,
+    require_delegated_account_seeds, require_owned_pda, require_pda, require_program_config,
+    require_signer, DelegationMetadataCtx, DelegationRecordCtx,
 };
 
+/// Accounts of [process_delegate]
+pub const DELEGATE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("payer"),
+    AccountSpec::signer("delegated account"),
+    AccountSpec::readonly("owner program"),
+    AccountSpec::writable("delegate buffer"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("system program"),
+];
+
 /// Delegates an account
 ///
 /// Accounts:
@@ -31,35 +61,90 @@ use crate::processor::fast::utils::requires::{
 ///                 during owner change
 /// 4: `[writable]` the delegation record account
 /// 5: `[writable]` the delegation metadata account
+/// 6: `[]`         the system program
+/// 7: `[writable]` (optional) the staged delegate buffer, see
+///                 [crate::processor::process_init_delegate_buffer]
+/// 8: `[]`         (optional) the program config PDA of the owner program, so that the
+///                 owner program validates the delegation if its program config requires it
+/// 9: `[writable]` (optional) the protocol stats PDA, adding the lamports of the delegated
+///                 account to the total value locked, see [crate::state::ProtocolStats]
+/// 10: `[signer, writable]` (optional) the rent co-payer of the args, passed last, paying the
+///                 payer its share of the rent of the delegation PDAs, see
+///                 [crate::args::RentCoPayer]
 ///
 /// Requirements:
 ///
 /// - delegation buffer is initialized
 /// - delegation record is uninitialized
 /// - delegation metadata is uninitialized
+/// - if provided, the staged delegate buffer holds all the data of the delegated account
+///   and its authority is the payer
+/// - if provided, the program config is derived from the owner program and, if it
+///   validates the delegations, the owner program accepts the delegation
+/// - if the args override the undelegate discriminator, the program config is provided and
+///   allows the override
+/// - if the args have a rent co-payer, it signs and both payers pay a non-zero share of the
+///   rent
 ///
 /// Steps:
 /// 1. Checks that the account is owned by the delegation program, that the buffer is initialized and derived correctly from the PDA
 ///  - Also checks that the delegated_account is a signer (enforcing that the instruction is being called from CPI) & other constraints
-/// 2. Copies the data from the buffer into the original account
-/// 3. Creates a Delegation Record to store useful information about the delegation event
+/// 2. Copies the data from the buffer into the original account, or from the staged
+///    delegate buffer which is then closed and its rent refunded to the payer
+/// 3. Creates a Delegation Record to store useful information about the delegation event,
+///    adding the lamports it records to the total value locked of the protocol stats, if
+///    provided
 /// 4. Creates a Delegated Account Seeds to store the seeds used to derive the delegate account. Needed for undelegation.
+///    With a seed template, the template is stored instead and resolved again on undelegation,
+///    see [crate::args::SeedTemplate]. The hash of the copied data is recorded with them,
+///    for the first commit to check the state the validator starts from, see
+///    [crate::args::CommitStateArgs::base_state_hash], as are the undelegate discriminator
+///    override, the heartbeat timeout, the maximum account size and the rent co-payer, see
+///    [crate::args::DelegateArgs]. The rent co-payer pays the payer its share of the rent of
+///    both PDAs, refunded to it on undelegation.
+/// 5. If the program config of the owner program validates the delegations, invoke the
+///    external validate delegation instruction of the owner program with the delegated
+///    account and its seeds, its failure aborting the delegation
 ///
 /// Usage:
 ///
 /// This instruction is meant to be called via CPI with the owning program signing for the
 /// delegated account.
+///
+/// As a program cannot be reentered through another program, the owner program can only
+/// validate the delegations it does not invoke itself, e.g. of on-curve accounts delegated
+/// at the top level of the transaction.
 pub fn process_delegate(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    let [payer, delegated_account, owner_program, delegate_buffer_account, delegation_record_account, delegation_metadata_account, _system_program] =
+    let args = DelegateArgs::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    // The rent co-payer of the args, if any, is passed last
+    let (accounts, rent_co_payer_account) =
+        split_rent_co_payer(accounts, args.rent_co_payer.as_ref())?;
+    let (accounts, protocol_stats) = split_protocol_stats(accounts);
+    let [payer, delegated_account, owner_program, delegate_buffer_account, delegation_record_account, delegation_metadata_account, _system_program, trailing_accounts @ ..] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
+    // The staged delegate buffer and the program config are optional trailing accounts, the
+    // program config being recognized by its discriminator
+    let (staged_buffer_account, program_config_account) = match trailing_accounts {
+        [] => (None, None),
+        [program_config_account] if is_program_config(program_config_account) => {
+            (None, Some(program_config_account))
+        }
+        [staged_buffer_account] => (Some(staged_buffer_account), None),
+        [staged_buffer_account, program_config_account, ..] => {
+            (Some(staged_buffer_account), Some(program_config_account))
+        }
+    };
+
     require_owned_pda(delegated_account, &crate::fast::ID, "delegated account")?;
 
     // Check that payer and delegated_account are signers, this ensures the instruction is being called from CPI
@@ -97,75 +182,32 @@ pub fn process_delegate(
         DelegationMetadataCtx,
     )?;
 
-    let args =
-        DelegateArgs::try_from_slice(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    trace!(delegated_account.key(), 0, "delegate", "enter");
+
+    // A seed template replaces the seeds, which are resolved from the payer and the owner
+    // program, and validated the same way
+    let seeds = match args.seed_template {
+        Some(seed_template) => {
+            if !args.seeds.is_empty() {
+                log!("seeds and a seed template cannot be both provided");
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            seed_template.resolve(payer.key(), owner_program.key())
+        }
+        None => args.seeds,
+    };
 
     // Validate seeds if the delegate account is not on curve, i.e. is a PDA
     // If the owner is the system program, we check if the account is derived from the delegation program,
     // allowing delegation of escrow accounts
     if !is_on_curve_fast(delegated_account.key()) {
-        let program_id = if pubkey_eq(owner_program.key(), &pinocchio_system::ID) {
-            &crate::fast::ID
-        } else {
-            owner_program.key()
-        };
-        let seeds_to_validate: &[&[u8]] = match args.seeds.len() {
-            1 => &[&args.seeds[0]],
-            2 => &[&args.seeds[0], &args.seeds[1]],
-            3 => &[&args.seeds[0], &args.seeds[1], &args.seeds[2]],
-            4 => &[
-                &args.seeds[0],
-                &args.seeds[1],
-                &args.seeds[2],
-                &args.seeds[3],
-            ],
-            5 => &[
-                &args.seeds[0],
-                &args.seeds[1],
-                &args.seeds[2],
-                &args.seeds[3],
-                &args.seeds[4],
-            ],
-            6 => &[
-                &args.seeds[0],
-                &args.seeds[1],
-                &args.seeds[2],
-                &args.seeds[3],
-                &args.seeds[4],
-                &args.seeds[5],
-            ],
-            7 => &[
-                &args.seeds[0],
-                &args.seeds[1],
-                &args.seeds[2],
-                &args.seeds[3],
-                &args.seeds[4],
-                &args.seeds[5],
-                &args.seeds[6],
-            ],
-            8 => &[
-                &args.seeds[0],
-                &args.seeds[1],
-                &args.seeds[2],
-                &args.seeds[3],
-                &args.seeds[4],
-                &args.seeds[5],
-                &args.seeds[6],
-                &args.seeds[7],
-            ],
-            _ => return Err(DlpError::TooManySeeds.into()),
-        };
-        let derived_pda = pubkey::find_program_address(seeds_to_validate, program_id).0;
-
-        if !pubkey_eq(&derived_pda, delegated_account.key()) {
-            log!("Expected delegated PDA to be: ");
-            pubkey::log(&derived_pda);
-            log!("but got: ");
-            pubkey::log(delegated_account.key());
-            return Err(ProgramError::InvalidSeeds);
+        if seeds.is_empty() || seeds.len() > 8 {
+            return Err(DlpError::TooManySeeds.into());
         }
+        require_delegated_account_seeds(delegated_account, owner_program, &seeds)?;
     }
 
+    let rent = Rent::get()?;
     create_pda(
         delegation_record_account,
         &crate::fast::ID,
@@ -176,6 +218,7 @@ pub fn process_delegate(
             Seed::from(&[delegation_record_bump]),
         ])],
         payer,
+        &rent,
     )?;
 
     // Initialize the delegation record
@@ -185,18 +228,62 @@ pub fn process_delegate(
         commit_frequency_ms: args.commit_frequency_ms as u64,
         delegation_slot: Clock::get()?.slot,
         lamports: delegated_account.lamports(),
+        version: DelegationRecord::VERSION,
+        padding: [0; 7],
     };
 
     let mut delegation_record_data = delegation_record_account.try_borrow_mut_data()?;
     delegation_record
         .to_bytes_with_discriminator(&mut delegation_record_data)
         .map_err(to_pinocchio_program_error)?;
+    record_tvl_change(protocol_stats, 0, delegation_record.lamports)?;
+
+    emit_delegate_event(
+        delegated_account,
+        owner_program,
+        delegation_record.authority.as_array(),
+    );
 
-    let delegation_metadata = DelegationMetadata {
+    // Copy the data from the staged buffer, for accounts too large for the delegate buffer,
+    // or from the buffer into the original account
+    if let Some(staged_buffer_account) = staged_buffer_account {
+        trace!(delegated_account.key(), 0, "delegate", "staged-buffer");
+        copy_staged_delegate_buffer(payer, delegated_account, staged_buffer_account)?;
+    } else if !delegate_buffer_account.data_is_empty() {
+        let mut delegated_data = delegated_account.try_borrow_mut_data()?;
+        let delegate_buffer_data = delegate_buffer_account.try_borrow_data()?;
+        if delegate_buffer_data.len() != delegated_data.len() {
+            log!(
+                "delegate buffer of {} bytes for an account of {} bytes",
+                delegate_buffer_data.len(),
+                delegated_data.len()
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        (*delegated_data).copy_from_slice(&delegate_buffer_data);
+    }
+
+    // The metadata records the hash of the data copied from the buffer
+    let mut delegation_metadata = DelegationMetadata {
         seeds: args.seeds,
         last_update_nonce: 0,
         is_undelegatable: false,
         rent_payer: (*payer.key()).into(),
+        close_destination: None,
+        extended_undelegate_payload: args.extended_undelegate_payload,
+        undelegation_request: None,
+        seed_template: args.seed_template,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: delegated_data_hash(delegated_account)?,
+        undelegate_discriminator: args.undelegate_discriminator,
+        heartbeat_timeout_slots: args.heartbeat_timeout_slots,
+        max_account_size: args.max_account_size,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: args.rent_co_payer,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
     };
 
     // Initialize the delegation metadata PDA
@@ -210,20 +297,176 @@ pub fn process_delegate(
             Seed::from(&[delegation_metadata_bump]),
         ])],
         payer,
+        &rent,
     )?;
 
+    // The rent co-payer pays the payer its share of the rent of each delegation PDA, recorded
+    // to refund it no more than it paid
+    if let (Some(rent_co_payer), Some(rent_co_payer_account)) =
+        (args.rent_co_payer, rent_co_payer_account)
+    {
+        let lamports = rent_co_payer.share_of(delegation_record_account.lamports())
+            + rent_co_payer.share_of(delegation_metadata_account.lamports());
+        system::Transfer {
+            from: rent_co_payer_account,
+            to: payer,
+            lamports,
+        }
+        .invoke()?;
+        delegation_metadata.rent_co_payer_lamports = lamports;
+    }
+
     // Copy the seeds to the delegated metadata PDA
     let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
     delegation_metadata
         .to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())
         .map_err(to_pinocchio_program_error)?;
+    drop(delegation_metadata_data);
 
-    // Copy the data from the buffer into the original account
-    if !delegate_buffer_account.data_is_empty() {
-        let mut delegated_data = delegated_account.try_borrow_mut_data()?;
-        let delegate_buffer_data = delegate_buffer_account.try_borrow_data()?;
-        (*delegated_data).copy_from_slice(&delegate_buffer_data);
+    let program_config = match program_config_account {
+        Some(program_config_account)
+            if require_program_config(program_config_account, owner_program.key(), false)? =>
+        {
+            let program_config_data = program_config_account.try_borrow_data()?;
+            Some(
+                ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)
+                    .map_err(to_pinocchio_program_error)?,
+            )
+        }
+        _ => None,
+    };
+
+    // The external undelegate CPI is signed, only the owner programs expecting it on another
+    // instruction can redirect it
+    if delegation_metadata.undelegate_discriminator.is_some()
+        && !program_config
+            .as_ref()
+            .is_some_and(|program_config| program_config.allow_undelegate_discriminator_override)
+    {
+        log!("program config does not allow the undelegate discriminator override");
+        return Err(DlpError::UndelegateDiscriminatorOverrideNotAllowed.into());
     }
 
+    // Let the owner program validate the delegated state, if its program config requires it
+    if program_config.is_some_and(|program_config| program_config.validate_delegations) {
+        trace!(
+            delegated_account.key(),
+            0,
+            "delegate",
+            "validate-delegation"
+        );
+        cpi_external_validate_delegation(payer, delegated_account, owner_program, &seeds)?;
+    }
+
+    trace!(delegated_account.key(), 0, "delegate", "exit");
     Ok(())
 }
+
+/// The hash of the data of the delegated account, recorded in the delegation metadata
+/// unless the account is too large to hash, see [MAX_HASHED_DELEGATED_DATA_LEN]
+fn delegated_data_hash(delegated_account: &AccountInfo) -> Result<Option<[u8; 32]>, ProgramError> {
+    let delegated_data = delegated_account.try_borrow_data()?;
+    Ok((delegated_data.len() <= MAX_HASHED_DELEGATED_DATA_LEN)
+        .then(|| commit_state_hash(&delegated_data)))
+}
+
+fn is_program_config(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info
+            .try_borrow_data()
+            .is_ok_and(|data| data.starts_with(&AccountDiscriminator::ProgramConfig.to_bytes()))
+}
+
+/// Invoke the external validate delegation instruction of the owner program with the
+/// delegated account and its seeds
+fn cpi_external_validate_delegation(
+    payer: &AccountInfo,
+    delegated_account: &AccountInfo,
+    owner_program: &AccountInfo,
+    seeds: &Seeds,
+) -> ProgramResult {
+    let mut data = Vec::with_capacity(8 + seeds.serialized_size());
+    data.extend_from_slice(&EXTERNAL_VALIDATE_DELEGATION_DISCRIMINATOR);
+    borsh::to_writer(&mut data, seeds).map_err(|_| ProgramError::BorshIoError)?;
+
+    let external_validate_delegation_instruction = Instruction {
+        program_id: owner_program.key(),
+        data: &data,
+        accounts: &[
+            AccountMeta::new(delegated_account.key(), false, false),
+            AccountMeta::new(payer.key(), false, false),
+        ],
+    };
+    invoke(
+        &external_validate_delegation_instruction,
+        &[delegated_account, payer],
+    )
+}
+
+/// Copy the data of a complete staged delegate buffer into the delegated account and close it
+fn copy_staged_delegate_buffer(
+    payer: &AccountInfo,
+    delegated_account: &AccountInfo,
+    staged_buffer_account: &AccountInfo,
+) -> ProgramResult {
+    require_pda(
+        staged_buffer_account,
+        &[pda::STAGED_DELEGATE_BUFFER_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        true,
+        "staged delegate buffer",
+    )?;
+    require_owned_pda(
+        staged_buffer_account,
+        &crate::fast::ID,
+        "staged delegate buffer",
+    )?;
+
+    let staged_buffer_data = staged_buffer_account.try_borrow_data()?;
+    let (header, staged_data) = staged_buffer_data
+        .split_at_checked(StagedDelegateBuffer::size_with_discriminator())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let staged_buffer = StagedDelegateBuffer::try_from_bytes_with_discriminator(header)
+        .map_err(to_pinocchio_program_error)?;
+
+    if !pubkey_eq(staged_buffer.authority.as_array(), payer.key()) {
+        log!("payer is not the staged delegate buffer authority: ");
+        pubkey::log(staged_buffer.authority.as_array());
+        return Err(DlpError::InvalidAuthority.into());
+    }
+    if staged_buffer.data_len as usize != staged_data.len()
+        || staged_data.len() != delegated_account.data_len()
+    {
+        log!(
+            "staged {} of {} bytes for an account of {} bytes",
+            staged_data.len(),
+            staged_buffer.data_len,
+            delegated_account.data_len()
+        );
+        return Err(DlpError::InvalidStagedDelegateBuffer.into());
+    }
+
+    let mut delegated_data = delegated_account.try_borrow_mut_data()?;
+    (*delegated_data).copy_from_slice(staged_data);
+
+    drop(staged_buffer_data);
+    close_pda(staged_buffer_account, payer)
+}
+
+/// Log the [DelegateEvent], see [crate::events] for the format
+fn emit_delegate_event(
+    delegated_account: &AccountInfo,
+    owner_program: &AccountInfo,
+    validator: &Pubkey,
+) {
+    let validator_info =
+        pubkey::find_program_address(&[pda::VALIDATOR_INFO_TAG, validator], &crate::fast::ID).0;
+
+    let mut event = [0u8; DelegateEvent::SIZE_WITH_DISCRIMINATOR];
+    event[0] = EventDiscriminator::Delegate.into();
+    event[1..33].copy_from_slice(delegated_account.key());
+    event[33..65].copy_from_slice(owner_program.key());
+    event[65..97].copy_from_slice(validator);
+    event[97..].copy_from_slice(&validator_info);
+    sol_log_data(&[&event]);
+}
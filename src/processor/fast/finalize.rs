@@ -1,31 +1,77 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::log;
 use pinocchio::account_info::AccountInfo;
 use pinocchio::program_error::ProgramError;
-use pinocchio::pubkey::{pubkey_eq, Pubkey};
+use pinocchio::pubkey::{self, pubkey_eq, Pubkey};
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
 use pinocchio::ProgramResult;
-use pinocchio_log::log;
+use pinocchio_system::instructions as system;
 
+use crate::consts::MAX_ACCOUNT_DATA_LEN;
 use crate::error::DlpError;
-use crate::processor::fast::utils::pda::close_pda;
+use crate::processor::fast::commit_state::retained_committed_data;
+use crate::processor::fast::utils::earnings_ledger::{record_earnings, split_earnings_ledger};
+use crate::processor::fast::utils::escrow_spend::{
+    require_escrow_spend, split_instructions_sysvar,
+};
+use crate::processor::fast::utils::pda::{
+    close_pda, grow_pda_funded_by_payer, grow_pda_funded_by_pda,
+};
+use crate::processor::fast::utils::protocol_stats::{record_tvl_change, split_protocol_stats};
 use crate::processor::fast::utils::requires::{
     is_uninitialized_account, require_initialized_commit_record, require_initialized_commit_state,
     require_initialized_delegation_metadata, require_initialized_delegation_record,
-    require_initialized_validator_fees_vault, require_owned_pda, require_signer,
+    require_initialized_read_lock, require_initialized_validator_fees_vault, require_owned_pda,
+    require_signer, require_writable,
+};
+use crate::processor::fast::utils::session_report::{record_session_commit, split_session_report};
+use crate::state::{
+    ChunkedCommitState, CommitRecord, CommitStateLayout, DelegationMetadata, DelegationRecord,
+    EarningsKind, PendingState, ReadLock,
 };
-use crate::state::{CommitRecord, DelegationMetadata, DelegationRecord};
+use crate::trace::trace;
 
 use super::to_pinocchio_program_error;
 
+/// Accounts of [process_finalize]
+pub const FINALIZE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::writable("delegated account"),
+    AccountSpec::writable("commit state"),
+    AccountSpec::writable("commit record"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("validator fees vault"),
+    AccountSpec::readonly("system program"),
+];
+
 /// Finalize a committed state, after validation, to a delegated account
 ///
 /// Accounts:
 ///
-/// 0: `[signer]`   the validator account
-/// 1: `[writable]` the delegated account
-/// 2: `[writable]` the commit state account
-/// 3: `[writable]` the commit record account
-/// 4: `[writable]` the delegation record account
-/// 5: `[writable]` the delegation metadata account
-/// 6: `[writable]` the validator fees vault account
+///  0: `[signer]`   the validator account, writable to fund the rent exemption of the
+///                  delegated account if the commit PDAs cannot
+///  1: `[writable]` the delegated account
+///  2: `[writable]` the commit state account
+///  3: `[writable]` the commit record account
+///  4: `[writable]` the delegation record account
+///  5: `[writable]` the delegation metadata account
+///  6: `[]`         the validator fees vault account, writable if the commit decreased the
+///                  lamports of the delegated account
+///  7: `[]`         the system program
+///  8: `[writable]` (optional) the read lock account
+///  9: `[writable]` (optional) the escrow which funded the commit through the commit schedule
+///                  of the account, required if the escrow funded the commit
+/// 10: `[writable]` (optional) the earnings ledger page of the validator, recording the
+///                  lamports collected by the validator fees vault, see
+///                  [crate::state::EarningsLedgerPage]
+/// 11: `[writable]` (optional) the protocol stats PDA, applying the change of the lamports
+///                  of the delegated account to the total value locked, see
+///                  [crate::state::ProtocolStats]
+/// 12: `[]`         (optional) the instructions sysvar, required if the commit declared an
+///                  escrow spend, see [CommitRecord::escrow_spend]
+/// 13: `[writable]` (optional) the session report of the delegated account, if an escrow,
+///                  passed last, see [crate::state::SessionReport]
 ///
 /// Requirements:
 ///
@@ -37,6 +83,21 @@ use super::to_pinocchio_program_error;
 /// - commit record is initialized and derived from the delegated account key
 /// - account mentioned in commit record is the same as the delegated account
 /// - identity mentioned in commit record is the same as the validator
+/// - commit pushed by the owner program, see [crate::processor::process_commit_from_owner],
+///   follows the last finalized nonce, its payer being passed as the escrow to refund
+/// - read lock, if provided, is initialized
+/// - escrow is provided if it funded the commit, see [CommitRecord::escrow]
+/// - escrow spend declared by the commit, if any, matches the spends declared by the call
+///   handler instructions of the delegated account in the transaction
+/// - earnings ledger page, if provided, is the one of the validator and is not full
+/// - every chunk of a state committed by its Merkle root was written, see
+///   [crate::processor::fast::process_commit_state_root]
+/// - delegated account is rent exempt for the committed data length once finalized
+/// - committed data length is within the maximum account size of the delegation, if it
+///   rejects oversized states
+/// - validator fees vault is writable if it collects the lamports spent by the delegated
+///   account, otherwise it can be read-only to spare the write lock shared by all the
+///   finalizes of the validator, see [crate::instruction_builder::finalize_settled]
 ///
 /// NOTE: that if neither commit state nor commit record are as required then
 ///       we skip the finalize without an error in order to not affect other finalize
@@ -45,14 +106,31 @@ use super::to_pinocchio_program_error;
 /// Steps:
 ///
 /// 1. Validate the new state (currently state is valid if committed from a whitelisted validator)
-/// 2. If the state is valid, copy the committed state to the delegated account
-/// 3. Close the state diff account
-/// 4. Close the commit state record
+/// 2. If the state is valid, copy the committed state to the delegated account, truncated to
+///    the maximum account size of the delegation if it truncates oversized states, and
+///    record the ER block hash of the commit, if any, its slot and its timestamp in the
+///    delegation metadata
+/// 3. Fund the rent exemption of the delegated account if the committed data grew it, from
+///    the lamports of the commit PDAs refunded to the validator, then from the validator
+/// 4. Close the state diff account
+/// 5. Close the commit state record, refunding the validator the rent it advanced at
+///    commit, see [CommitRecord::rent_advanced], or the escrow if it funded the commit
+/// 6. Lock the reads of the delegated account for the rest of the slot, if a read lock is
+///    provided
+/// 7. Record the commit and the lamports spent by the delegated account in its session
+///    report, if provided
 pub fn process_finalize(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     _data: &[u8],
 ) -> ProgramResult {
+    // The read lock, the escrow, the earnings ledger page, the protocol stats, the
+    // instructions sysvar and the session report are optional trailing accounts
+    let (accounts, session_report) = split_session_report(accounts);
+    let (accounts, instructions_sysvar) = split_instructions_sysvar(accounts);
+    let (accounts, protocol_stats) = split_protocol_stats(accounts);
+    let (accounts, earnings_ledger) = split_earnings_ledger(accounts);
+    let (accounts, trailing_accounts) = accounts.split_at(accounts.len().min(FINALIZE_ACCOUNTS));
     let [validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, validator_fees_vault, _system_program] =
         accounts
     else {
@@ -63,7 +141,7 @@ pub fn process_finalize(
     require_owned_pda(delegated_account, &crate::fast::ID, "delegated account")?;
     require_initialized_delegation_record(delegated_account, delegation_record_account, true)?;
     require_initialized_delegation_metadata(delegated_account, delegation_metadata_account, true)?;
-    require_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
+    require_initialized_validator_fees_vault(validator, validator_fees_vault, false)?;
 
     let require_cs =
         require_initialized_commit_state(delegated_account, commit_state_account, true);
@@ -79,98 +157,519 @@ pub fn process_finalize(
             && is_uninitialized_account(commit_record_account)
         {
             log!("No state to be finalized. Skipping finalize.");
+            trace!(delegated_account.key(), 0, "finalize", "skip");
             return Ok(());
         }
     }
     require_cs?;
     require_cr?;
 
+    finalize_commit(
+        &Rent::get()?,
+        validator,
+        delegated_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        validator_fees_vault,
+        PendingCommit::Pdas {
+            commit_state_account,
+            commit_record_account,
+            trailing_accounts,
+        },
+        earnings_ledger,
+        protocol_stats,
+        instructions_sysvar,
+        session_report,
+    )
+}
+
+/// The number of accounts of [process_finalize], without the optional trailing accounts
+const FINALIZE_ACCOUNTS: usize = FINALIZE_ACCOUNTS_SPEC.len();
+
+/// A commit to finalize, pending in its commit PDAs or built in memory by a commit finalized
+/// in the same instruction, see [crate::processor::fast::process_commit_finalize]
+pub(crate) enum PendingCommit<'a> {
+    /// A commit pending in its commit state and commit record PDAs. The trailing accounts are
+    /// the optional read lock followed by the escrow, the latter being required if it funded
+    /// the commit
+    Pdas {
+        commit_state_account: &'a AccountInfo,
+        commit_record_account: &'a AccountInfo,
+        trailing_accounts: &'a [AccountInfo],
+    },
+    /// A commit validated in the same instruction, the validator funding the lamports it adds
+    /// to the delegated account and the growth of the delegation metadata
+    InMemory {
+        commit_record: CommitRecord,
+        committed_data: &'a [u8],
+        read_lock_account: Option<&'a AccountInfo>,
+    },
+}
+
+/// Apply a validated commit to the delegated account and close the commit PDAs, if any,
+/// refunding their rent to the validator or to the escrow which funded the commit, and lock
+/// the reads of the delegated account if a read lock is provided.
+///
+/// The lamports collected by the validator fees vault are recorded in the earnings ledger
+/// page, the change of the lamports of the delegated account in the protocol stats and the
+/// commit in the session report, if provided. The instructions sysvar is required if the
+/// commit declared an escrow spend.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn finalize_commit(
+    rent: &Rent,
+    validator: &AccountInfo,
+    delegated_account: &AccountInfo,
+    delegation_record_account: &AccountInfo,
+    delegation_metadata_account: &AccountInfo,
+    validator_fees_vault: &AccountInfo,
+    pending_commit: PendingCommit,
+    earnings_ledger: Option<&AccountInfo>,
+    protocol_stats: Option<&AccountInfo>,
+    instructions_sysvar: Option<&AccountInfo>,
+    session_report: Option<&AccountInfo>,
+) -> ProgramResult {
     // Load delegation metadata
-    let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+    let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
     let mut delegation_metadata =
         DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)
             .map_err(to_pinocchio_program_error)?;
 
     let mut delegation_record_data = delegation_record_account.try_borrow_mut_data()?;
-    let delegation_record =
-        DelegationRecord::try_from_bytes_with_discriminator_mut(&mut delegation_record_data)
+    let mut delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)
             .map_err(to_pinocchio_program_error)?;
 
-    // Load commit record
-    let commit_record_data = commit_record_account.try_borrow_data()?;
-    let commit_record = CommitRecord::try_from_bytes_with_discriminator(&commit_record_data)
-        .map_err(to_pinocchio_program_error)?;
+    // Load commit record, from its PDA if the commit is pending
+    let (commit_record, commit_pdas, committed_data, trailing_accounts) = match pending_commit {
+        PendingCommit::Pdas {
+            commit_state_account,
+            commit_record_account,
+            trailing_accounts,
+        } => {
+            let commit_record_data = commit_record_account.try_borrow_data()?;
+            let commit_record =
+                *CommitRecord::try_from_bytes_with_discriminator(&commit_record_data)
+                    .map_err(to_pinocchio_program_error)?;
+            (
+                commit_record,
+                Some((commit_state_account, commit_record_account)),
+                &[][..],
+                trailing_accounts,
+            )
+        }
+        PendingCommit::InMemory {
+            commit_record,
+            committed_data,
+            read_lock_account,
+        } => (
+            commit_record,
+            None,
+            committed_data,
+            read_lock_account
+                .map(core::slice::from_ref)
+                .unwrap_or_default(),
+        ),
+    };
 
-    // Check that the commit record is the right one
+    // Check that the commit record is the right one. The identity of a commit finalized in
+    // the same instruction was checked at commit, its signer may be a commit relayer
     if !pubkey_eq(commit_record.account.as_array(), delegated_account.key()) {
         return Err(DlpError::InvalidDelegatedAccount.into());
     }
-    if !pubkey_eq(commit_record.identity.as_array(), validator.key()) {
+    if commit_pdas.is_some() && !pubkey_eq(commit_record.identity.as_array(), validator.key()) {
         return Err(DlpError::InvalidReimbursementAccount.into());
     }
 
+    // The lamports the committed escrow declared to spend are moved by the call handlers of
+    // the transaction
+    if let Some(escrow_spend) = commit_record.escrow_spend() {
+        require_escrow_spend(instructions_sysvar, delegated_account.key(), escrow_spend)?;
+    }
+
+    // The escrow which funded the commit is refunded its rent, and passed last
+    let (escrow_account, read_lock_account) = match commit_record.escrow() {
+        Some(escrow) => match trailing_accounts.split_last() {
+            Some((escrow_account, trailing_accounts))
+                if pubkey_eq(escrow_account.key(), escrow.as_array()) =>
+            {
+                (Some(escrow_account), trailing_accounts.first())
+            }
+            _ => {
+                log!("escrow which funded the commit is missing: ");
+                pubkey::log(escrow.as_array());
+                return Err(DlpError::InvalidReimbursementAccount.into());
+            }
+        },
+        None => (None, trailing_accounts.first()),
+    };
+    trace!(
+        delegated_account.key(),
+        commit_record.nonce,
+        "finalize",
+        "enter"
+    );
+
+    // The lamports spent in the session are the ones the commit takes off the lamports
+    // recorded at the previous finalize
+    let spent = delegation_record
+        .lamports
+        .saturating_sub(commit_record.lamports);
+
     // Settle accounts lamports
-    settle_lamports_balance(
+    let collected_lamports = settle_lamports_balance(
         delegated_account,
-        commit_state_account,
+        commit_pdas.map(|(commit_state_account, _)| commit_state_account),
+        validator,
         validator_fees_vault,
         delegation_record.lamports,
         commit_record.lamports,
     )?;
+    record_earnings(
+        earnings_ledger,
+        commit_record.identity.as_array(),
+        delegated_account.key(),
+        collected_lamports,
+        EarningsKind::Settlement,
+    )?;
+    trace!(
+        delegated_account.key(),
+        commit_record.nonce,
+        "finalize",
+        "settled"
+    );
+
+    // A commit pushed by the owner program takes the next nonce of the sequence, which the
+    // next commit of the validator must follow, acknowledging the state it folded in
+    let nonce = commit_record.sequence_nonce();
+    if commit_record.is_owner_commit()
+        && Some(nonce) != delegation_metadata.last_update_nonce.checked_add(1)
+    {
+        log!(
+            "owner commit nonce {} does not follow the last finalized nonce {}",
+            nonce,
+            delegation_metadata.last_update_nonce
+        );
+        return Err(DlpError::NonceOutOfOrder.into());
+    }
 
-    // Update the delegation metadata
-    delegation_metadata.last_update_nonce = commit_record.nonce;
+    // Update the delegation metadata, growing it to record the ER block hash, the slot and the
+    // timestamp of the commit with the lamports of the commit record, which are otherwise
+    // refunded to the validator, or with the lamports of the validator without commit PDAs
+    delegation_metadata.last_update_nonce = nonce;
+    if let Some(er_block_hash) = commit_record.er_block_hash() {
+        delegation_metadata.last_er_block_hash = Some(er_block_hash);
+    }
+    let clock = Clock::get()?;
+    delegation_metadata.last_commit_slot = Some(clock.slot);
+    delegation_metadata.last_commit_timestamp = Some(clock.unix_timestamp);
+    drop(delegation_metadata_data);
+    match commit_pdas {
+        Some((_, commit_record_account)) => grow_pda_funded_by_pda(
+            delegation_metadata_account,
+            delegation_metadata.serialized_size(),
+            commit_record_account,
+            rent,
+        )?,
+        None => grow_pda_funded_by_payer(
+            delegation_metadata_account,
+            delegation_metadata.serialized_size(),
+            validator,
+            rent,
+        )?,
+    }
+    let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
     delegation_metadata
         .to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())
         .map_err(to_pinocchio_program_error)?;
 
-    // Update the delegation record
-    delegation_record.lamports = delegated_account.lamports();
+    // Load commit state, read according to the layout recorded by the commit. A state
+    // committed by its Merkle root is only finalized once its changed chunks were written,
+    // each one being proven against the root when written, the others holding the data of
+    // the delegated account
+    let commit_state_data = commit_pdas
+        .map(|(commit_state_account, _)| commit_state_account.try_borrow_data())
+        .transpose()?;
+    let committed_data = match &commit_state_data {
+        Some(commit_state_data) => {
+            let layout = commit_record
+                .commit_state_layout()
+                .ok_or(DlpError::InvalidCommitStateLayout)?;
+            if layout == CommitStateLayout::Chunked {
+                let (chunked_commit_state, _, _) = ChunkedCommitState::split(commit_state_data)
+                    .ok_or(DlpError::InvalidCommitStateLayout)?;
+                if !chunked_commit_state.is_complete() {
+                    log!(
+                        "only {} of the {} changed chunks of the committed root were written",
+                        chunked_commit_state.chunks_written,
+                        chunked_commit_state.chunks_to_write
+                    );
+                    return Err(DlpError::InvalidChunkedCommitState.into());
+                }
+            }
+            PendingState::committed_data(commit_state_data, layout)
+                .ok_or(DlpError::InvalidCommitStateLayout)?
+        }
+        None => committed_data,
+    };
 
-    // Load commit state
-    let commit_state_data = commit_state_account.try_borrow_data()?;
+    // Check the committed data against the account size cap of the delegation again, a
+    // streamed commit state being written after its commit
+    let committed_data =
+        retained_committed_data(delegation_metadata.max_account_size, committed_data)?;
 
-    // Copying the new commit state to the delegated account
-    delegated_account.resize(commit_state_data.len())?;
+    // Copying the new commit state to the delegated account, which cannot exceed the
+    // maximum data length of an account. A commit of exactly that length is copied as is
+    if committed_data.len() > MAX_ACCOUNT_DATA_LEN {
+        log!(
+            "committed data length {} exceeds the maximum data length {}",
+            committed_data.len(),
+            MAX_ACCOUNT_DATA_LEN
+        );
+        return Err(DlpError::AccountDataTooLarge.into());
+    }
+    delegated_account.resize(committed_data.len())?;
     let mut delegated_account_data = delegated_account.try_borrow_mut_data()?;
-    (*delegated_account_data).copy_from_slice(&commit_state_data);
+    (*delegated_account_data).copy_from_slice(committed_data);
 
     // Drop remaining reference before closing accounts
-    drop(commit_record_data);
+    drop(delegated_account_data);
     drop(commit_state_data);
 
-    // Closing accounts
-    close_pda(commit_state_account, validator)?;
-    close_pda(commit_record_account, validator)?;
+    if let Some((commit_state_account, commit_record_account)) = commit_pdas {
+        // Once settled, the commit PDAs hold at least the rent advanced by the validator, and
+        // any unused growth of the delegation metadata it paid at commit
+        let rent_advanced = commit_record.rent_advanced;
+        let commit_pdas_lamports = commit_state_account
+            .lamports()
+            .checked_add(commit_record_account.lamports())
+            .ok_or(DlpError::Overflow)?;
+        if commit_pdas_lamports < rent_advanced {
+            log!(
+                "commit PDAs hold {} lamports, less than the {} lamports of rent advanced",
+                commit_pdas_lamports,
+                rent_advanced
+            );
+            return Err(DlpError::CommitRentNotRefundable.into());
+        }
+
+        // Refund the escrow the rent it funded, the commit state holding it first
+        if let Some(escrow_account) = escrow_account {
+            require_writable(escrow_account, "escrow")?;
+            let from_commit_state = commit_state_account.lamports().min(rent_advanced);
+            *commit_state_account.try_borrow_mut_lamports()? -= from_commit_state;
+            *commit_record_account.try_borrow_mut_lamports()? -= rent_advanced - from_commit_state;
+            *escrow_account.try_borrow_mut_lamports()? = escrow_account
+                .lamports()
+                .checked_add(rent_advanced)
+                .ok_or(DlpError::Overflow)?;
+        }
+    }
+
+    // The committed data may grow the delegated account past its rent exemption
+    fund_rent_exemption(delegated_account, commit_pdas, validator, rent)?;
+
+    // Update the delegation record
+    record_tvl_change(
+        protocol_stats,
+        delegation_record.lamports,
+        delegated_account.lamports(),
+    )?;
+    delegation_record.lamports = delegated_account.lamports();
+    delegation_record
+        .to_bytes_with_discriminator(&mut delegation_record_data)
+        .map_err(to_pinocchio_program_error)?;
+
+    // Closing accounts, refunding the validator
+    if let Some((commit_state_account, commit_record_account)) = commit_pdas {
+        close_pda(commit_state_account, validator)?;
+        close_pda(commit_record_account, validator)?;
+    }
+
+    // Lock the reads until the next slot, the other accounts of the bundle may not be finalized yet
+    if let Some(read_lock_account) = read_lock_account {
+        require_initialized_read_lock(delegated_account, read_lock_account, true)?;
+        let mut read_lock_data = read_lock_account.try_borrow_mut_data()?;
+        let read_lock = ReadLock::try_from_bytes_with_discriminator_mut(&mut read_lock_data)
+            .map_err(to_pinocchio_program_error)?;
+        read_lock.slot = Clock::get()?.slot;
+        read_lock.nonce = delegation_metadata.last_update_nonce;
+        trace!(
+            delegated_account.key(),
+            delegation_metadata.last_update_nonce,
+            "finalize",
+            "read-lock"
+        );
+    }
+
+    record_session_commit(
+        session_report,
+        delegated_account,
+        commit_record.identity.as_array(),
+        spent,
+    )?;
+
+    trace!(
+        delegated_account.key(),
+        delegation_metadata.last_update_nonce,
+        "finalize",
+        "exit"
+    );
+    Ok(())
+}
+
+/// Top up the delegated account to the rent exempt minimum of its data length, taking the
+/// shortfall from the lamports of the commit PDAs, if any, which are otherwise refunded to
+/// the validator, then from the validator itself if it signed and is writable
+fn fund_rent_exemption(
+    delegated_account: &AccountInfo,
+    commit_pdas: Option<(&AccountInfo, &AccountInfo)>,
+    validator: &AccountInfo,
+    rent: &Rent,
+) -> ProgramResult {
+    let minimum_balance = rent.minimum_balance(delegated_account.data_len());
+    let mut shortfall = minimum_balance.saturating_sub(delegated_account.lamports());
+    if shortfall == 0 {
+        return Ok(());
+    }
+
+    for commit_pda in
+        commit_pdas
+            .into_iter()
+            .flat_map(|(commit_state_account, commit_record_account)| {
+                [commit_state_account, commit_record_account]
+            })
+    {
+        let lamports = commit_pda.lamports().min(shortfall);
+        *commit_pda.try_borrow_mut_lamports()? -= lamports;
+        *delegated_account.try_borrow_mut_lamports()? = delegated_account
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(DlpError::Overflow)?;
+        shortfall -= lamports;
+    }
+    if shortfall == 0 {
+        return Ok(());
+    }
+
+    if !validator.is_signer() || !validator.is_writable() || validator.lamports() < shortfall {
+        log!(
+            "delegated account is {} lamports short of the rent exempt minimum {}",
+            shortfall,
+            minimum_balance
+        );
+        return Err(DlpError::DelegatedAccountNotRentExempt.into());
+    }
+    system::Transfer {
+        from: validator,
+        to: delegated_account,
+        lamports: shortfall,
+    }
+    .invoke()
+}
+
+/// Transfer settling the committed lamports of a delegated account at finalize
+pub(crate) enum LamportsSettlement {
+    /// The delegated account spent lamports, collected by the validator fees vault
+    ToValidatorFeesVault(u64),
+    /// The delegated account gained lamports, deposited in the commit state at commit
+    ToDelegatedAccount(u64),
+    /// The committed lamports match the delegation record
+    Settled,
+}
+
+impl LamportsSettlement {
+    pub(crate) fn new(
+        delegation_record_lamports: u64,
+        commit_record_lamports: u64,
+    ) -> Result<Self, ProgramError> {
+        Ok(
+            match delegation_record_lamports.cmp(&commit_record_lamports) {
+                core::cmp::Ordering::Greater => Self::ToValidatorFeesVault(
+                    delegation_record_lamports
+                        .checked_sub(commit_record_lamports)
+                        .ok_or(DlpError::Overflow)?,
+                ),
+                core::cmp::Ordering::Less => Self::ToDelegatedAccount(
+                    commit_record_lamports
+                        .checked_sub(delegation_record_lamports)
+                        .ok_or(DlpError::Overflow)?,
+                ),
+                core::cmp::Ordering::Equal => Self::Settled,
+            },
+        )
+    }
+
+    /// Lamports of the delegated account once settled, if they can be settled
+    pub(crate) fn settled_lamports(&self, delegated_account_lamports: u64) -> Option<u64> {
+        match self {
+            Self::ToValidatorFeesVault(lamports) => {
+                delegated_account_lamports.checked_sub(*lamports)
+            }
+            Self::ToDelegatedAccount(lamports) => delegated_account_lamports.checked_add(*lamports),
+            Self::Settled => Some(delegated_account_lamports),
+        }
+    }
+}
+
+/// Dry-run the settlement of a commit, rejecting commits whose lamports could never be
+/// settled at finalize: the delegated account must not go negative and must stay rent
+/// exempt for the committed data length
+pub(crate) fn require_settleable_commit(
+    delegated_account: &AccountInfo,
+    delegation_record_lamports: u64,
+    commit_record_lamports: u64,
+    data_len: usize,
+    rent: &Rent,
+) -> Result<(), ProgramError> {
+    let settlement = LamportsSettlement::new(delegation_record_lamports, commit_record_lamports)?;
+    let Some(settled_lamports) = settlement.settled_lamports(delegated_account.lamports()) else {
+        log!("committed lamports cannot be settled at finalize");
+        return Err(DlpError::UnsettleableCommit.into());
+    };
+
+    let minimum_balance = rent.minimum_balance(data_len);
+    if settled_lamports < minimum_balance {
+        log!(
+            "settled lamports {} are below the rent exempt minimum {}",
+            settled_lamports,
+            minimum_balance
+        );
+        return Err(DlpError::UnsettleableCommit.into());
+    }
 
     Ok(())
 }
 
-/// Settle the committed lamports to the delegated account
+/// Settle the committed lamports to the delegated account, the lamports it gained being
+/// deposited in the commit state at commit, or transferred by the validator if there is none.
+/// Returns the lamports collected by the validator fees vault.
 fn settle_lamports_balance(
     delegated_account: &AccountInfo,
-    commit_state_account: &AccountInfo,
+    commit_state_account: Option<&AccountInfo>,
+    validator: &AccountInfo,
     validator_fees_vault: &AccountInfo,
     delegation_record_lamports: u64,
     commit_record_lamports: u64,
-) -> Result<(), ProgramError> {
+) -> Result<u64, ProgramError> {
     let (transfer_source, transfer_destination, transfer_lamports) =
-        match delegation_record_lamports.cmp(&commit_record_lamports) {
-            std::cmp::Ordering::Greater => (
-                delegated_account,
-                validator_fees_vault,
-                delegation_record_lamports
-                    .checked_sub(commit_record_lamports)
-                    .ok_or(DlpError::Overflow)?,
-            ),
-            std::cmp::Ordering::Less => (
-                commit_state_account,
-                delegated_account,
-                commit_record_lamports
-                    .checked_sub(delegation_record_lamports)
-                    .ok_or(DlpError::Overflow)?,
-            ),
-            std::cmp::Ordering::Equal => return Ok(()),
+        match LamportsSettlement::new(delegation_record_lamports, commit_record_lamports)? {
+            LamportsSettlement::ToValidatorFeesVault(lamports) => {
+                require_writable(validator_fees_vault, "validator fees vault")?;
+                (delegated_account, validator_fees_vault, lamports)
+            }
+            LamportsSettlement::ToDelegatedAccount(lamports) => match commit_state_account {
+                Some(commit_state_account) => (commit_state_account, delegated_account, lamports),
+                None => {
+                    system::Transfer {
+                        from: validator,
+                        to: delegated_account,
+                        lamports,
+                    }
+                    .invoke()?;
+                    return Ok(0);
+                }
+            },
+            LamportsSettlement::Settled => return Ok(0),
         };
 
     *transfer_source.try_borrow_mut_lamports()? = transfer_source
@@ -182,5 +681,8 @@ fn settle_lamports_balance(
         .checked_add(transfer_lamports)
         .ok_or(DlpError::Overflow)?;
 
-    Ok(())
+    if pubkey_eq(transfer_destination.key(), validator_fees_vault.key()) {
+        return Ok(transfer_lamports);
+    }
+    Ok(0)
 }
@@ -1,19 +1,34 @@
 mod commit_diff;
 mod commit_diff_from_buffer;
+mod commit_diff_shadow;
+mod commit_finalize;
 mod commit_state;
 mod commit_state_from_buffer;
+mod commit_state_root;
+mod crank_finalize;
 mod delegate;
 mod finalize;
 mod undelegate;
+mod undelegate_and_close;
+mod undelegate_stage1;
+mod undelegate_stage2;
 mod utils;
 
 pub use commit_diff::*;
 pub use commit_diff_from_buffer::*;
+pub use commit_diff_shadow::*;
+pub use commit_finalize::*;
 pub use commit_state::*;
 pub use commit_state_from_buffer::*;
+pub use commit_state_root::*;
+pub use crank_finalize::*;
 pub use delegate::*;
 pub use finalize::*;
 pub use undelegate::*;
+pub use undelegate_and_close::*;
+pub use undelegate_stage1::*;
+pub use undelegate_stage2::*;
+pub use utils::requires::{require_enabled_instruction, require_enabled_legacy_instruction};
 
 pub fn to_pinocchio_program_error(
     error: solana_program::program_error::ProgramError,
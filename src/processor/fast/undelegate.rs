@@ -1,36 +1,54 @@
+use alloc::vec::Vec;
+
+use crate::accounts_spec::AccountsSpec;
+use crate::log::log;
 use pinocchio::{
     account_info::AccountInfo,
     cpi::invoke_signed,
     instruction::{AccountMeta, Instruction, Signer},
+    log::sol_log_data,
     program_error::ProgramError,
     pubkey::{pubkey_eq, Pubkey},
-    sysvars::{rent::Rent, Sysvar},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
 use pinocchio::{pubkey, seeds};
-use pinocchio_log::log;
 use pinocchio_system::instructions as system;
 
-use crate::consts::{EXTERNAL_UNDELEGATE_DISCRIMINATOR, RENT_FEES_PERCENTAGE};
+use crate::args::{ExternalUndelegateArgsV2, UndelegateArgs, UndelegateMode};
+use crate::consts::{
+    EXTERNAL_UNDELEGATE_DISCRIMINATOR, EXTERNAL_UNDELEGATE_PAYLOAD_V2,
+    MAX_UNDELEGATE_LAMPORTS_TOLERANCE, RENT_FEES_PERCENTAGE,
+};
 use crate::error::DlpError;
+use crate::events::{EscrowClosedEvent, EventDiscriminator};
 use crate::pda;
 use crate::processor::fast::utils::{
-    pda::{close_pda, close_pda_with_fees, create_pda},
+    accounts_ctx::accounts_ctx,
+    earnings_ledger::{record_earnings, split_earnings_ledger},
+    pda::{close_pda, close_pda_with_fees_split, create_pda},
+    protocol_stats::{record_tvl_change, split_protocol_stats},
+    rent_co_payer::{rent_co_payer_refund, require_rent_co_payer, split_recorded_rent_co_payer},
     requires::{
-        require_uninitialized_pda, CommitRecordCtx, CommitStateAccountCtx, UndelegateBufferCtx,
+        require_fee_exemption, require_program_config, require_protocol_config,
+        require_uninitialized_pda, CommitRecordCtx, CommitStateAccountCtx, ParkedUndelegationCtx,
+        UndelegateBufferCtx,
     },
+    session_report::{freeze_session_report, split_session_report},
+    validator_liveness::{require_undelegatable, split_validator_liveness},
 };
-use crate::state::{DelegationMetadata, DelegationRecord};
-
-#[cfg(feature = "log-cost")]
-use crate::compute;
+use crate::state::{
+    DelegationMetadata, DelegationRecord, EarningsKind, FeeExemption, ParkedUndelegation,
+    ProgramConfig, ProtocolConfig,
+};
+use crate::trace::trace;
 
 use super::{
     to_pinocchio_program_error,
     utils::requires::{
-        require_initialized_delegation_metadata, require_initialized_delegation_record,
-        require_initialized_protocol_fees_vault, require_initialized_validator_fees_vault,
-        require_owned_pda, require_signer,
+        require_delegated_account_seeds, require_initialized_delegation_metadata,
+        require_initialized_delegation_record, require_initialized_protocol_fees_vault,
+        require_initialized_validator_fees_vault, require_owned_pda,
     },
 };
 
@@ -38,18 +56,37 @@ use super::{
 ///
 /// Accounts:
 ///
-///  0: `[signer]`   the validator account
+///  0: `[signer]`   the validator account or, if the delegation is stale, anyone
 ///  1: `[writable]` the delegated account
 ///  2: `[]`         the owner program of the delegated account
-///  3: `[writable]` the undelegate buffer PDA we use to store the data temporarily
+///  3: `[writable]` the undelegate buffer PDA we use to store the data temporarily, or the
+///                  parked undelegation PDA with [UndelegateMode::ToBuffer]
 ///  4: `[]`         the commit state PDA
 ///  5: `[]`         the commit record PDA
 ///  6: `[writable]` the delegation record PDA
 ///  7: `[writable]` the delegation metadata PDA
-///  8: `[]`         the rent reimbursement account
+///  8: `[]`         the rent reimbursement account, `[signer, writable]` with
+///                  [UndelegateMode::ToBuffer]
 ///  9: `[writable]` the protocol fees vault account
-/// 10: `[writable]` the validator fees vault account
+/// 10: `[writable]` the validator fees vault account, unused if the delegation is stale
 /// 11: `[]`         the system program (TODO (snawaz): soon to be removed from the requirement)
+/// 12: `[]`         (optional) the protocol config PDA
+/// 13: `[]`         (optional) the fee exemption PDA, passed after the protocol config
+/// 14: `[]`         (optional) the program config PDA of the owner program, passed after the
+///                  fee exemption
+/// 15: `[]`         (optional) the validator liveness PDA of the validator of the delegation,
+///                  for a delegation with a heartbeat timeout, see
+///                  [crate::state::ValidatorLiveness]
+/// 16: `[writable]` (optional) the earnings ledger page of the validator, recording the rent
+///                  fees collected by the validator fees vault, see
+///                  [crate::state::EarningsLedgerPage]
+/// 17: `[writable]` (optional) the protocol stats PDA, subtracting the lamports of the
+///                  delegated account from the total value locked, see
+///                  [crate::state::ProtocolStats]
+/// 18: `[writable]` (optional) the session report of the delegated account, if an escrow,
+///                  frozen to end the session, see [crate::state::SessionReport]
+/// 19: `[writable]` (optional) the rent co-payer of the delegation, passed last, required if
+///                  the delegation has one, see [crate::args::RentCoPayer]
 ///
 /// Requirements:
 ///
@@ -57,43 +94,118 @@ use super::{
 /// - delegation record is initialized
 /// - delegation metadata is initialized
 /// - protocol fees vault is initialized
-/// - validator fees vault is initialized
+/// - validator fees vault is initialized, unless the delegation is stale, see
+///   [crate::processor::process_force_undelegate]
 /// - commit state is uninitialized
 /// - commit record is uninitialized
-/// - delegated account is NOT undelegatable
+/// - delegated account is undelegatable or, if the delegation has a heartbeat timeout, the
+///   validator liveness of the validator of the delegation is passed and shows no heartbeat
+///   for the timeout since the delegation, see [crate::processor::process_validator_heartbeat]
 /// - owner program account matches the owner in the delegation record
 /// - rent reimbursement account matches the rent payer in the delegation metadata
+/// - rent co-payer is passed if the delegation metadata records one
+/// - if the delegation metadata holds a seed template, the delegated account is derived from
+///   the seeds it resolves to with the rent payer and the owner program
+/// - with [UndelegateMode::ToBuffer], the rent reimbursement account signs
 ///
 /// Steps:
 ///
+/// - Freeze the session report, if provided
+/// - Resolve the seed template of the delegation metadata, if any, into the seeds passed to
+///   the owner program
 /// - Close the delegation metadata
-/// - Close the delegation record
+/// - Close the delegation record, subtracting the lamports it records from the total value
+///   locked of the protocol stats, if provided
+/// - The rent of both is refunded to the rent payer without the rent fees, paid in whole to the
+///   protocol fees vault if the delegation is stale, or in whole if the
+///   delegated account has a fee exemption active at the current slot, the rent co-payer of
+///   the delegation, if any, being refunded the rent it paid at delegation net of its share
+///   of the rent fees
+/// - If the mode is [UndelegateMode::ToBuffer], create the parked undelegation PDA funded by
+///   the rent payer, store the data in it, move the lamports of the delegated account to it
+///   and close the delegated account (and stop here). The rent payer claims it with
+///   [crate::processor::process_claim_parked_undelegation]. A failing CPI to the owner program
+///   reverts the whole transaction, so the mode is chosen explicitly rather than after failed
+///   attempts, which leave no trace on chain.
+/// - If delegated account is an ephemeral balance escrow holding less lamports than the
+///   dust threshold of the protocol config, close it and refund the rent payer (and stop here)
 /// - If delegated account has no data, assign to prev owner (and stop here)
 /// - If there's data, create an "undelegate_buffer" and store the data in it
 /// - Close the original delegated account
 /// - CPI to the original owner to re-open the PDA with the original owner and the new state
 /// - CPI will be signed by the undelegation buffer PDA and will call the external program
-///   using the discriminator EXTERNAL_UNDELEGATE_DISCRIMINATOR
+///   using the discriminator EXTERNAL_UNDELEGATE_DISCRIMINATOR, or the one overriding it at
+///   delegation, followed by the seeds or, if the owner program opted into it at delegation,
+///   by the EXTERNAL_UNDELEGATE_PAYLOAD_V2 version byte and the ExternalUndelegateArgsV2
+/// - Verify that the validator paid exactly the rent of the re-opened account, or up to the
+///   `undelegate_lamports_tolerance` of the program config of the owner program less, capped
+///   at MAX_UNDELEGATE_LAMPORTS_TOLERANCE, when the owner program adds lamports to the payer
 /// - Verify that the new state is the same as the committed state
 /// - Close the undelegation buffer PDA
 pub fn process_undelegate(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _data: &[u8],
+    data: &[u8],
 ) -> ProgramResult {
-    let [validator, delegated_account, owner_program, undelegate_buffer_account, commit_state_account, commit_record_account, delegation_record_account, delegation_metadata_account, rent_reimbursement, fees_vault, validator_fees_vault, system_program] =
-        accounts
-    else {
-        return Err(ProgramError::NotEnoughAccountKeys);
-    };
+    let args = UndelegateArgs::try_from_instruction_data(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    // The protocol config, the fee exemption, the program config, the validator liveness, the
+    // earnings ledger page, the protocol stats, the session report and the rent co-payer are
+    // optional trailing accounts
+    let (accounts, rent_co_payer) =
+        split_recorded_rent_co_payer(accounts, UNDELEGATE_DELEGATION_METADATA_INDEX);
+    let (accounts, session_report) = split_session_report(accounts);
+    let (accounts, protocol_stats) = split_protocol_stats(accounts);
+    let (accounts, earnings_ledger) = split_earnings_ledger(accounts);
+    let (accounts, validator_liveness) = split_validator_liveness(accounts);
+    let (accounts, protocol_config_account, fee_exemption_account, program_config_account) =
+        match accounts {
+            [accounts @ .., protocol_config_account, fee_exemption_account, program_config_account]
+                if accounts.len() == UNDELEGATE_ACCOUNTS =>
+            {
+                (
+                    accounts,
+                    Some(protocol_config_account),
+                    Some(fee_exemption_account),
+                    Some(program_config_account),
+                )
+            }
+            [accounts @ .., protocol_config_account, fee_exemption_account]
+                if accounts.len() == UNDELEGATE_ACCOUNTS =>
+            {
+                (
+                    accounts,
+                    Some(protocol_config_account),
+                    Some(fee_exemption_account),
+                    None,
+                )
+            }
+            [accounts @ .., protocol_config_account] if accounts.len() == UNDELEGATE_ACCOUNTS => {
+                (accounts, Some(protocol_config_account), None, None)
+            }
+            _ => (accounts, None, None, None),
+        };
+    let UndelegateAccounts {
+        validator,
+        delegated_account,
+        owner_program,
+        undelegate_buffer_account,
+        commit_state_account,
+        commit_record_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        rent_reimbursement,
+        fees_vault,
+        validator_fees_vault,
+        system_program,
+    } = UndelegateAccounts::try_from_accounts(accounts)?;
 
     // Check accounts
-    require_signer(validator, "validator")?;
     require_owned_pda(delegated_account, &crate::fast::ID, "delegated account")?;
     require_initialized_delegation_record(delegated_account, delegation_record_account, true)?;
     require_initialized_delegation_metadata(delegated_account, delegation_metadata_account, true)?;
     require_initialized_protocol_fees_vault(fees_vault, true)?;
-    require_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
 
     // Make sure there is no pending commits to be finalized before this call
     require_uninitialized_pda(
@@ -128,16 +240,26 @@ pub fn process_undelegate(
 
     // Load delegated account metadata
     let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
-    let delegation_metadata =
+    let mut delegation_metadata =
         DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)
             .map_err(to_pinocchio_program_error)?;
 
-    // Check if the delegated account is undelegatable
-    if !delegation_metadata.is_undelegatable {
-        log!("delegation metadata indicates the account is not undelegatable : ");
-        pubkey::log(delegation_metadata_account.key());
-        return Err(DlpError::NotUndelegatable.into());
-    }
+    // Check if the delegated account is undelegatable, or its validator stopped heartbeating
+    require_undelegatable(
+        delegation_metadata_account,
+        &delegation_metadata,
+        &delegation_record,
+        validator_liveness,
+    )?;
+
+    // A stale delegation, force undelegated after its validator stopped committing, can be
+    // undelegated by anyone, the rent fees all going to the protocol fees vault
+    let validator_fees_vault = if delegation_metadata.is_stale {
+        None
+    } else {
+        require_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
+        Some(validator_fees_vault)
+    };
 
     // Check if the rent payer is correct
     if !pubkey_eq(
@@ -150,26 +272,116 @@ pub fn process_undelegate(
         pubkey::log(rent_reimbursement.key());
         return Err(DlpError::InvalidReimbursementAddressForDelegationRent.into());
     }
+    require_rent_co_payer(&delegation_metadata, rent_co_payer)?;
+    if args.mode == UndelegateMode::ToBuffer && !rent_reimbursement.is_signer() {
+        log!("Undelegating to the parked undelegation requires the rent payer signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // The undelegation of the escrow ends its session
+    freeze_session_report(session_report, delegated_account)?;
+
+    // Resolve the seeds from the template, checked again as the keys were only known at
+    // delegation
+    if let Some(seed_template) = delegation_metadata.seed_template {
+        let seeds = seed_template.resolve(rent_reimbursement.key(), owner_program.key());
+        require_delegated_account_seeds(delegated_account, owner_program, &seeds)?;
+        delegation_metadata.seeds = seeds;
+    }
+
+    let fee_exempt = is_fee_exempt(delegated_account, fee_exemption_account)?;
+    record_tvl_change(protocol_stats, delegation_record.lamports, 0)?;
+
+    let nonce = delegation_metadata.last_update_nonce;
+    trace!(delegated_account.key(), nonce, "undelegate", "enter");
 
     // Dropping delegation references
     drop(delegation_record_data);
     drop(delegation_metadata_data);
 
+    // Park the state and the lamports instead of the CPI to the owner program, if requested
+    if args.mode == UndelegateMode::ToBuffer {
+        trace!(delegated_account.key(), nonce, "undelegate", "park");
+        park_undelegation(
+            delegated_account,
+            owner_program,
+            undelegate_buffer_account,
+            rent_reimbursement,
+            &Rent::get()?,
+        )?;
+        process_delegation_cleanup(
+            validator.key(),
+            delegated_account,
+            delegation_record_account,
+            delegation_metadata_account,
+            rent_reimbursement,
+            rent_co_payer,
+            fees_vault,
+            validator_fees_vault,
+            earnings_ledger,
+            fee_exempt,
+        )?;
+        trace!(delegated_account.key(), nonce, "undelegate", "exit");
+        return Ok(());
+    }
+
     // If there is no program to call CPI to, we can just assign the owner back and we're done
     if delegated_account.data_is_empty() {
+        if close_dust_escrow(
+            delegated_account,
+            owner_program,
+            rent_reimbursement,
+            protocol_config_account,
+            &delegation_metadata,
+        )? {
+            trace!(
+                delegated_account.key(),
+                nonce,
+                "undelegate",
+                "close-dust-escrow"
+            );
+            process_delegation_cleanup(
+                validator.key(),
+                delegated_account,
+                delegation_record_account,
+                delegation_metadata_account,
+                rent_reimbursement,
+                rent_co_payer,
+                fees_vault,
+                validator_fees_vault,
+                earnings_ledger,
+                fee_exempt,
+            )?;
+            trace!(delegated_account.key(), nonce, "undelegate", "exit");
+            return Ok(());
+        }
+
+        trace!(delegated_account.key(), nonce, "undelegate", "assign-owner");
         // TODO - we could also do this fast-path if the data was non-empty but zeroed-out
         unsafe {
             delegated_account.assign(owner_program.key());
         }
         process_delegation_cleanup(
+            validator.key(),
+            delegated_account,
             delegation_record_account,
             delegation_metadata_account,
             rent_reimbursement,
+            rent_co_payer,
             fees_vault,
             validator_fees_vault,
+            earnings_ledger,
+            fee_exempt,
         )?;
+        trace!(delegated_account.key(), nonce, "undelegate", "exit");
         return Ok(());
     }
+    trace!(
+        delegated_account.key(),
+        nonce,
+        "undelegate",
+        "cpi-owner-program"
+    );
 
     // Initialize the undelegation buffer PDA
 
@@ -181,6 +393,7 @@ pub fn process_undelegate(
         UndelegateBufferCtx,
     )?;
 
+    let rent = Rent::get()?;
     create_pda(
         undelegate_buffer_account,
         &crate::fast::ID,
@@ -191,8 +404,11 @@ pub fn process_undelegate(
             &[undelegate_buffer_bump]
         ))],
         validator,
+        &rent,
     )?;
 
+    let lamports_tolerance = undelegate_lamports_tolerance(owner_program, program_config_account)?;
+
     // Copy data in the undelegation buffer PDA
     (*undelegate_buffer_account.try_borrow_mut_data()?)
         .copy_from_slice(&delegated_account.try_borrow_data()?);
@@ -209,7 +425,10 @@ pub fn process_undelegate(
             &[undelegate_buffer_bump]
         ))],
         delegation_metadata,
+        &delegation_record,
         system_program,
+        lamports_tolerance,
+        &rent,
     )?;
 
     // Done, close undelegation buffer
@@ -217,28 +436,202 @@ pub fn process_undelegate(
 
     // Closing delegation accounts
     process_delegation_cleanup(
+        validator.key(),
+        delegated_account,
         delegation_record_account,
         delegation_metadata_account,
         rent_reimbursement,
+        rent_co_payer,
         fees_vault,
         validator_fees_vault,
+        earnings_ledger,
+        fee_exempt,
     )?;
+    trace!(delegated_account.key(), nonce, "undelegate", "exit");
     Ok(())
 }
 
+/// The number of accounts of [process_undelegate], without the optional trailing accounts
+const UNDELEGATE_ACCOUNTS: usize = UNDELEGATE_ACCOUNTS_SPEC.len();
+
+/// The index of the delegation metadata in the accounts of [process_undelegate]
+const UNDELEGATE_DELEGATION_METADATA_INDEX: usize = 7;
+
+accounts_ctx! {
+    /// Accounts of [process_undelegate]
+    pub(crate) struct UndelegateAccounts {
+        validator: [signer] "validator",
+        delegated_account: [writable] "delegated account",
+        owner_program: [] "owner program",
+        undelegate_buffer_account: [writable] "undelegate buffer",
+        commit_state_account: [] "commit state",
+        commit_record_account: [] "commit record",
+        delegation_record_account: [writable] "delegation record",
+        delegation_metadata_account: [writable] "delegation metadata",
+        rent_reimbursement: [] "rent reimbursement",
+        fees_vault: [writable] "protocol fees vault",
+        validator_fees_vault: [writable] "validator fees vault",
+        system_program: [] "system program",
+    }
+}
+
+/// Accounts of [process_undelegate]
+pub const UNDELEGATE_ACCOUNTS_SPEC: AccountsSpec = UndelegateAccounts::SPEC;
+
+/// Park the data and the lamports of the delegated account in the parked undelegation PDA,
+/// funded by the rent payer, and close the delegated account, see [UndelegateMode::ToBuffer]
+fn park_undelegation(
+    delegated_account: &AccountInfo,
+    owner_program: &AccountInfo,
+    parked_undelegation_account: &AccountInfo,
+    rent_payer: &AccountInfo,
+    rent: &Rent,
+) -> ProgramResult {
+    let parked_undelegation_bump = require_uninitialized_pda(
+        parked_undelegation_account,
+        &[pda::PARKED_UNDELEGATION_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        true,
+        ParkedUndelegationCtx,
+    )?;
+
+    let header_size = ParkedUndelegation::size_with_discriminator();
+    let data_len = delegated_account.data_len();
+    create_pda(
+        parked_undelegation_account,
+        &crate::fast::ID,
+        header_size
+            .checked_add(data_len)
+            .ok_or(DlpError::Overflow)?,
+        &[Signer::from(&seeds!(
+            pda::PARKED_UNDELEGATION_TAG,
+            delegated_account.key(),
+            &[parked_undelegation_bump]
+        ))],
+        rent_payer,
+        rent,
+    )?;
+
+    let parked_undelegation = ParkedUndelegation {
+        rent_payer: (*rent_payer.key()).into(),
+        owner: (*owner_program.key()).into(),
+        data_len: data_len as u64,
+    };
+    {
+        let mut parked_undelegation_data = parked_undelegation_account.try_borrow_mut_data()?;
+        let (header, parked_data) = parked_undelegation_data
+            .split_at_mut_checked(header_size)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        parked_undelegation
+            .to_bytes_with_discriminator(header)
+            .map_err(to_pinocchio_program_error)?;
+        parked_data.copy_from_slice(&delegated_account.try_borrow_data()?);
+    }
+
+    close_pda(delegated_account, parked_undelegation_account)
+}
+
+/// Close an ephemeral balance escrow holding less lamports than the dust threshold of the
+/// protocol config, refunding its rent payer. Returns whether the escrow was closed.
+fn close_dust_escrow(
+    delegated_account: &AccountInfo,
+    owner_program: &AccountInfo,
+    rent_reimbursement: &AccountInfo,
+    protocol_config_account: Option<&AccountInfo>,
+    delegation_metadata: &DelegationMetadata,
+) -> Result<bool, ProgramError> {
+    let Some(protocol_config_account) = protocol_config_account else {
+        return Ok(false);
+    };
+
+    // Escrows are PDAs of the delegation program delegated on behalf of the system program
+    let is_escrow = pubkey_eq(owner_program.key(), &pinocchio_system::ID)
+        && matches!(
+            delegation_metadata.seeds.get(0),
+            Some(pda::EPHEMERAL_BALANCE_TAG | pda::PROGRAM_EPHEMERAL_BALANCE_TAG)
+        );
+    if !is_escrow || !require_protocol_config(protocol_config_account, false)? {
+        return Ok(false);
+    }
+
+    let protocol_config_data = protocol_config_account.try_borrow_data()?;
+    let protocol_config = ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_data)
+        .map_err(to_pinocchio_program_error)?;
+    let lamports = delegated_account.lamports();
+    if !protocol_config.is_escrow_dust(lamports) {
+        return Ok(false);
+    }
+
+    close_pda(delegated_account, rent_reimbursement)?;
+    emit_escrow_closed_event(delegated_account, rent_reimbursement, lamports);
+    Ok(true)
+}
+
+/// Whether the delegated account has a fee exemption active at the current slot
+pub(crate) fn is_fee_exempt(
+    delegated_account: &AccountInfo,
+    fee_exemption_account: Option<&AccountInfo>,
+) -> Result<bool, ProgramError> {
+    let Some(fee_exemption_account) = fee_exemption_account else {
+        return Ok(false);
+    };
+    if !require_fee_exemption(delegated_account, fee_exemption_account)? {
+        return Ok(false);
+    }
+
+    let fee_exemption_data = fee_exemption_account.try_borrow_data()?;
+    let fee_exemption = FeeExemption::try_from_bytes_with_discriminator(&fee_exemption_data)
+        .map_err(to_pinocchio_program_error)?;
+    Ok(fee_exemption.is_active(Clock::get()?.slot))
+}
+
+/// The lamports the owner program can add to the validator in the external undelegate CPI, set
+/// in its program config and capped at [MAX_UNDELEGATE_LAMPORTS_TOLERANCE]
+pub(crate) fn undelegate_lamports_tolerance(
+    owner_program: &AccountInfo,
+    program_config_account: Option<&AccountInfo>,
+) -> Result<u64, ProgramError> {
+    let Some(program_config_account) = program_config_account else {
+        return Ok(0);
+    };
+    if !require_program_config(program_config_account, owner_program.key(), false)? {
+        return Ok(0);
+    }
+
+    let program_config_data = program_config_account.try_borrow_data()?;
+    let program_config = ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)
+        .map_err(to_pinocchio_program_error)?;
+    Ok(program_config
+        .undelegate_lamports_tolerance
+        .min(MAX_UNDELEGATE_LAMPORTS_TOLERANCE))
+}
+
+/// Log the [EscrowClosedEvent], see [crate::events] for the format
+fn emit_escrow_closed_event(escrow: &AccountInfo, refunded: &AccountInfo, lamports: u64) {
+    let mut event = [0u8; EscrowClosedEvent::SIZE_WITH_DISCRIMINATOR];
+    event[0] = EventDiscriminator::EscrowClosed.into();
+    event[1..33].copy_from_slice(escrow.key());
+    event[33..65].copy_from_slice(refunded.key());
+    event[65..].copy_from_slice(&lamports.to_le_bytes());
+    sol_log_data(&[&event]);
+}
+
 /// 1. Close the delegated account
 /// 2. CPI to the owner program
 /// 3. Check state
 /// 4. Settle lamports balance
 #[allow(clippy::too_many_arguments)]
-fn process_undelegation_with_cpi(
+pub(crate) fn process_undelegation_with_cpi(
     validator: &AccountInfo,
     delegated_account: &AccountInfo,
     owner_program: &AccountInfo,
     undelegate_buffer_account: &AccountInfo,
     undelegate_buffer_signer_seeds: &[Signer],
     delegation_metadata: DelegationMetadata,
+    delegation_record: &DelegationRecord,
     system_program: &AccountInfo,
+    lamports_tolerance: u64,
+    rent: &Rent,
 ) -> ProgramResult {
     let delegated_account_lamports_before_close = delegated_account.lamports();
     close_pda(delegated_account, validator)?;
@@ -254,19 +647,34 @@ fn process_undelegation_with_cpi(
         system_program,
         owner_program.key(),
         delegation_metadata,
+        delegation_record,
     )?;
 
     let validator_lamports_after_cpi = validator.lamports();
 
-    // Check that the validator lamports are exactly as expected
-    let delegated_account_min_rent = Rent::get()?.minimum_balance(delegated_account.data_len());
-    if validator_lamports_before_cpi
-        != validator_lamports_after_cpi
-            .checked_add(delegated_account_min_rent)
-            .ok_or(DlpError::Overflow)?
-    {
+    // Check that the validator paid the rent of the re-opened account, the owner program
+    // adding at most the tolerance of its program config back
+    let delegated_account_min_rent = rent.minimum_balance(delegated_account.data_len());
+    let expected_validator_lamports = validator_lamports_before_cpi
+        .checked_sub(delegated_account_min_rent)
+        .ok_or(DlpError::Overflow)?;
+    let added_lamports = validator_lamports_after_cpi
+        .checked_sub(expected_validator_lamports)
+        .ok_or(DlpError::InvalidValidatorBalanceAfterCPI)?;
+    if added_lamports > lamports_tolerance {
+        log!(
+            "Owner program added {} lamports to the validator, tolerance is {}",
+            added_lamports,
+            lamports_tolerance
+        );
         return Err(DlpError::InvalidValidatorBalanceAfterCPI.into());
     }
+    if added_lamports > 0 {
+        log!(
+            "Owner program added {} lamports to the validator",
+            added_lamports
+        );
+    }
 
     // Check that the owner program properly moved the state back into the original account during CPI
     if delegated_account.try_borrow_data()?.as_ref()
@@ -289,7 +697,10 @@ fn process_undelegation_with_cpi(
     Ok(())
 }
 
-/// CPI to the original owner program to re-open the PDA with the new state
+/// CPI to the original owner program to re-open the PDA with the new state, passing the seeds
+/// or the [ExternalUndelegateArgsV2] if the owner program opted into them at delegation, to the
+/// external undelegate instruction or the one overriding it at delegation
+#[allow(clippy::too_many_arguments)]
 fn cpi_external_undelegate(
     payer: &AccountInfo,
     delegated_account: &AccountInfo,
@@ -298,13 +709,29 @@ fn cpi_external_undelegate(
     system_program: &AccountInfo,
     owner_program_id: &Pubkey,
     delegation_metadata: DelegationMetadata,
+    delegation_record: &DelegationRecord,
 ) -> ProgramResult {
     let data = {
         // GAIN: 299  (42075 => 41776)
         let mut data = Vec::with_capacity(32);
-        data.extend_from_slice(&EXTERNAL_UNDELEGATE_DISCRIMINATOR);
-        borsh::to_writer(&mut data, &delegation_metadata.seeds)
-            .map_err(|_| ProgramError::BorshIoError)?;
+        data.extend_from_slice(
+            &delegation_metadata
+                .undelegate_discriminator
+                .unwrap_or(EXTERNAL_UNDELEGATE_DISCRIMINATOR),
+        );
+        if delegation_metadata.extended_undelegate_payload {
+            data.push(EXTERNAL_UNDELEGATE_PAYLOAD_V2);
+            let args = ExternalUndelegateArgsV2 {
+                seeds: delegation_metadata.seeds,
+                nonce: delegation_metadata.last_update_nonce,
+                lamports: delegation_record.lamports,
+                delegation_slot: delegation_record.delegation_slot,
+            };
+            borsh::to_writer(&mut data, &args)
+        } else {
+            borsh::to_writer(&mut data, &delegation_metadata.seeds)
+        }
+        .map_err(|_| ProgramError::BorshIoError)?;
         data
     };
 
@@ -331,24 +758,67 @@ fn cpi_external_undelegate(
     )
 }
 
-fn process_delegation_cleanup(
+/// Close the delegation PDAs, charging the rent fees unless the account is fee exempt, and
+/// record the rent fees collected by the validator fees vault in the earnings ledger page.
+/// If the delegation has a rent co-payer, it is refunded the rent it paid at delegation net of
+/// its share of the rent fees, the rent payer being refunded the rest. Without the validator
+/// fees vault, the rent fees all go to the protocol fees vault.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn process_delegation_cleanup(
+    validator: &Pubkey,
+    delegated_account: &AccountInfo,
     delegation_record_account: &AccountInfo,
     delegation_metadata_account: &AccountInfo,
     rent_reimbursement: &AccountInfo,
+    rent_co_payer: Option<(&AccountInfo, u64)>,
     fees_vault: &AccountInfo,
-    validator_fees_vault: &AccountInfo,
+    validator_fees_vault: Option<&AccountInfo>,
+    earnings_ledger: Option<&AccountInfo>,
+    fee_exempt: bool,
 ) -> ProgramResult {
-    close_pda_with_fees(
+    if fee_exempt && rent_co_payer.is_none() {
+        close_pda(delegation_record_account, rent_reimbursement)?;
+        close_pda(delegation_metadata_account, rent_reimbursement)?;
+        return Ok(());
+    }
+    let fee_percentage = if fee_exempt { 0 } else { RENT_FEES_PERCENTAGE };
+    let fees_addresses: &[&AccountInfo] = match validator_fees_vault {
+        Some(validator_fees_vault) => &[validator_fees_vault, fees_vault],
+        None => &[fees_vault],
+    };
+    let validator_fees_vault_lamports = validator_fees_vault.map_or(0, |vault| vault.lamports());
+    let rent_co_payer = rent_co_payer.map(|(rent_co_payer_account, paid_lamports)| {
+        (
+            rent_co_payer_account,
+            rent_co_payer_refund(paid_lamports, fee_percentage),
+        )
+    });
+    let refunded = close_pda_with_fees_split(
         delegation_record_account,
         rent_reimbursement,
-        &[validator_fees_vault, fees_vault],
-        RENT_FEES_PERCENTAGE,
+        rent_co_payer,
+        fees_addresses,
+        fee_percentage,
     )?;
-    close_pda_with_fees(
+    close_pda_with_fees_split(
         delegation_metadata_account,
         rent_reimbursement,
-        &[validator_fees_vault, fees_vault],
-        RENT_FEES_PERCENTAGE,
+        rent_co_payer.map(|(rent_co_payer_account, owed_lamports)| {
+            (rent_co_payer_account, owed_lamports - refunded)
+        }),
+        fees_addresses,
+        fee_percentage,
     )?;
-    Ok(())
+    let Some(validator_fees_vault) = validator_fees_vault.filter(|_| !fee_exempt) else {
+        return Ok(());
+    };
+    record_earnings(
+        earnings_ledger,
+        validator,
+        delegated_account.key(),
+        validator_fees_vault
+            .lamports()
+            .saturating_sub(validator_fees_vault_lamports),
+        EarningsKind::RentFees,
+    )
 }
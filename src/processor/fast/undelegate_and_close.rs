@@ -0,0 +1,215 @@
+use crate::accounts_spec::AccountsSpec;
+use crate::log::log;
+use pinocchio::{
+    account_info::AccountInfo,
+    pubkey::{self, pubkey_eq, Pubkey},
+    ProgramResult,
+};
+
+use crate::consts::RENT_FEES_PERCENTAGE;
+use crate::error::DlpError;
+use crate::pda;
+use crate::processor::fast::utils::{
+    accounts_ctx::accounts_ctx,
+    earnings_ledger::{record_earnings, split_earnings_ledger},
+    pda::{close_pda, close_pda_with_fees_split},
+    rent_co_payer::{require_rent_co_payer, split_recorded_rent_co_payer},
+    requires::{require_uninitialized_pda, CommitRecordCtx, CommitStateAccountCtx},
+};
+use crate::state::{DelegationMetadata, EarningsKind};
+use crate::trace::trace;
+
+use super::{
+    to_pinocchio_program_error,
+    utils::requires::{
+        require_initialized_delegation_metadata, require_initialized_delegation_record,
+        require_initialized_protocol_fees_vault, require_initialized_validator_fees_vault,
+        require_owned_pda,
+    },
+};
+
+/// Undelegate a delegated account and close it, instead of giving it back to its owner
+/// program
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the validator account
+/// 1: `[writable]` the delegated account
+/// 2: `[]`         the commit state PDA
+/// 3: `[]`         the commit record PDA
+/// 4: `[writable]` the delegation record PDA
+/// 5: `[writable]` the delegation metadata PDA
+/// 6: `[writable]` the close destination account
+/// 7: `[writable]` the rent reimbursement account
+/// 8: `[writable]` the protocol fees vault account
+/// 9: `[writable]` the validator fees vault account
+/// 10: `[writable]` (optional) the earnings ledger page of the validator, see
+///                 [crate::processor::fast::process_undelegate]
+/// 11: `[writable]` (optional) the rent co-payer of the delegation, passed last, required if
+///                  the delegation has one, see [crate::args::RentCoPayer]
+///
+/// Requirements:
+///
+/// - same as [crate::processor::fast::process_undelegate], except for the owner program
+/// - the owner program approved the close, see
+///   [crate::processor::process_approve_undelegate_and_close]
+/// - close destination account matches the close destination in the delegation metadata
+///
+/// Steps:
+///
+/// 1. Close the delegated account, sending all its lamports to the close destination and
+///    leaving it owned by the system program
+/// 2. Close the delegation record and metadata
+pub fn process_undelegate_and_close(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let (accounts, rent_co_payer) =
+        split_recorded_rent_co_payer(accounts, UNDELEGATE_AND_CLOSE_DELEGATION_METADATA_INDEX);
+    let (accounts, earnings_ledger) = split_earnings_ledger(accounts);
+    let UndelegateAndCloseAccounts {
+        validator,
+        delegated_account,
+        commit_state_account,
+        commit_record_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        close_destination,
+        rent_reimbursement,
+        fees_vault,
+        validator_fees_vault,
+    } = UndelegateAndCloseAccounts::try_from_accounts(accounts)?;
+
+    // Check accounts
+    require_owned_pda(delegated_account, &crate::fast::ID, "delegated account")?;
+    require_initialized_delegation_record(delegated_account, delegation_record_account, true)?;
+    require_initialized_delegation_metadata(delegated_account, delegation_metadata_account, true)?;
+    require_initialized_protocol_fees_vault(fees_vault, true)?;
+    require_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
+
+    // Make sure there is no pending commits to be finalized before this call
+    require_uninitialized_pda(
+        commit_state_account,
+        &[pda::COMMIT_STATE_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        false,
+        CommitStateAccountCtx,
+    )?;
+    require_uninitialized_pda(
+        commit_record_account,
+        &[pda::COMMIT_RECORD_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        false,
+        CommitRecordCtx,
+    )?;
+
+    // Load delegated account metadata
+    let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)
+            .map_err(to_pinocchio_program_error)?;
+
+    // Check if the delegated account is undelegatable
+    if !delegation_metadata.is_undelegatable {
+        log!("delegation metadata indicates the account is not undelegatable : ");
+        pubkey::log(delegation_metadata_account.key());
+        return Err(DlpError::NotUndelegatable.into());
+    }
+
+    // Check if the rent payer is correct
+    if !pubkey_eq(
+        delegation_metadata.rent_payer.as_array(),
+        rent_reimbursement.key(),
+    ) {
+        log!("Expected rent payer to be : ");
+        pubkey::log(delegation_metadata.rent_payer.as_array());
+        log!("but got : ");
+        pubkey::log(rent_reimbursement.key());
+        return Err(DlpError::InvalidReimbursementAddressForDelegationRent.into());
+    }
+    require_rent_co_payer(&delegation_metadata, rent_co_payer)?;
+
+    // Check that the owner program approved the close to this destination
+    let Some(expected_close_destination) = delegation_metadata.close_destination else {
+        log!("delegation metadata has no close destination approved by the owner program");
+        return Err(DlpError::InvalidCloseDestination.into());
+    };
+    if !pubkey_eq(
+        expected_close_destination.as_array(),
+        close_destination.key(),
+    ) {
+        log!("Expected close destination to be : ");
+        pubkey::log(expected_close_destination.as_array());
+        log!("but got : ");
+        pubkey::log(close_destination.key());
+        return Err(DlpError::InvalidCloseDestination.into());
+    }
+
+    let nonce = delegation_metadata.last_update_nonce;
+    trace!(
+        delegated_account.key(),
+        nonce,
+        "undelegate-and-close",
+        "enter"
+    );
+    drop(delegation_metadata_data);
+
+    // Close the delegated account, no CPI to the owner program is needed to re-open it
+    close_pda(delegated_account, close_destination)?;
+
+    // Closing delegation accounts
+    let validator_fees_vault_lamports = validator_fees_vault.lamports();
+    close_pda_with_fees_split(
+        delegation_record_account,
+        rent_reimbursement,
+        rent_co_payer,
+        &[validator_fees_vault, fees_vault],
+        RENT_FEES_PERCENTAGE,
+    )?;
+    close_pda_with_fees_split(
+        delegation_metadata_account,
+        rent_reimbursement,
+        rent_co_payer,
+        &[validator_fees_vault, fees_vault],
+        RENT_FEES_PERCENTAGE,
+    )?;
+    record_earnings(
+        earnings_ledger,
+        validator.key(),
+        delegated_account.key(),
+        validator_fees_vault
+            .lamports()
+            .saturating_sub(validator_fees_vault_lamports),
+        EarningsKind::RentFees,
+    )?;
+    trace!(
+        delegated_account.key(),
+        nonce,
+        "undelegate-and-close",
+        "exit"
+    );
+    Ok(())
+}
+
+accounts_ctx! {
+    /// Accounts of [process_undelegate_and_close]
+    pub(crate) struct UndelegateAndCloseAccounts {
+        validator: [signer] "validator",
+        delegated_account: [writable] "delegated account",
+        commit_state_account: [] "commit state",
+        commit_record_account: [] "commit record",
+        delegation_record_account: [writable] "delegation record",
+        delegation_metadata_account: [writable] "delegation metadata",
+        close_destination: [writable] "close destination",
+        rent_reimbursement: [writable] "rent reimbursement",
+        fees_vault: [writable] "protocol fees vault",
+        validator_fees_vault: [writable] "validator fees vault",
+    }
+}
+
+/// The index of the delegation metadata in the accounts of [process_undelegate_and_close]
+const UNDELEGATE_AND_CLOSE_DELEGATION_METADATA_INDEX: usize = 5;
+
+/// Accounts of [process_undelegate_and_close]
+pub const UNDELEGATE_AND_CLOSE_ACCOUNTS_SPEC: AccountsSpec = UndelegateAndCloseAccounts::SPEC;
@@ -0,0 +1,294 @@
+use crate::accounts_spec::AccountsSpec;
+use crate::log::log;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    pubkey::{pubkey_eq, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio::{pubkey, seeds};
+
+use crate::error::DlpError;
+use crate::pda;
+use crate::processor::fast::utils::{
+    accounts_ctx::accounts_ctx,
+    pda::{close_pda, create_pda},
+    requires::{
+        require_uninitialized_pda, CommitRecordCtx, CommitStateAccountCtx, UndelegateBufferCtx,
+        UndelegateProgressCtx,
+    },
+    validator_liveness::{require_undelegatable, split_validator_liveness},
+};
+use crate::state::{DelegationMetadata, DelegationRecord, UndelegateProgress};
+use crate::trace::trace;
+
+use super::{
+    to_pinocchio_program_error,
+    undelegate::{process_undelegation_with_cpi, undelegate_lamports_tolerance},
+    utils::requires::{
+        require_delegated_account_seeds, require_initialized_delegation_metadata,
+        require_initialized_delegation_record, require_owned_pda,
+    },
+};
+
+/// Give a delegated account back to its owner program, the first stage of a staged
+/// undelegation
+///
+/// A staged undelegation splits [crate::processor::fast::process_undelegate] in two
+/// instructions, which can be sent in two transactions, for owner programs whose external
+/// undelegate CPI does not fit in a transaction with all the accounts of the undelegation.
+/// This stage runs the CPI and records an undelegate progress PDA, the second stage
+/// ([crate::processor::fast::process_undelegate_stage2]) closes the delegation PDAs.
+///
+/// In between, the delegated account is owned by its owner program again, so it can
+/// neither be committed nor undelegated, and its delegation record blocks a new delegation.
+/// The lamports of the delegated account are settled by this stage, only the rent of the
+/// delegation PDAs and of the progress is left to the second stage.
+///
+/// Accounts:
+///
+///  0: `[signer, writable]` the validator account
+///  1: `[writable]` the delegated account
+///  2: `[]`         the owner program of the delegated account
+///  3: `[writable]` the undelegate buffer PDA we use to store the data temporarily
+///  4: `[]`         the commit state PDA
+///  5: `[]`         the commit record PDA
+///  6: `[]`         the delegation record PDA
+///  7: `[]`         the delegation metadata PDA
+///  8: `[writable]` the undelegate progress PDA
+///  9: `[]`         the system program
+/// 10: `[]`         (optional) the program config PDA of the owner program
+/// 11: `[]`         (optional) the validator liveness PDA of the validator of the delegation,
+///                  see [crate::processor::fast::process_undelegate]
+///
+/// Requirements:
+///
+/// - same as [crate::processor::fast::process_undelegate], except for the accounts of the
+///   cleanup
+/// - delegated account has data, an account without data is undelegated without a CPI by
+///   [crate::processor::fast::process_undelegate]
+/// - undelegate progress is uninitialized
+///
+/// Steps:
+///
+/// 1. Create the undelegate progress, recording the validator and the owner program
+/// 2. Create the undelegate buffer and copy the data of the delegated account in it
+/// 3. Close the delegated account and CPI to the owner program to re-open it, verifying the
+///    rent paid by the validator and the new state, as
+///    [crate::processor::fast::process_undelegate]
+/// 4. Close the undelegate buffer
+pub fn process_undelegate_stage1(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // The program config and the validator liveness are optional trailing accounts
+    let (accounts, validator_liveness) = split_validator_liveness(accounts);
+    let (accounts, program_config_account) = match accounts {
+        [accounts @ .., program_config_account] if accounts.len() == UNDELEGATE_STAGE1_ACCOUNTS => {
+            (accounts, Some(program_config_account))
+        }
+        _ => (accounts, None),
+    };
+    let UndelegateStage1Accounts {
+        validator,
+        delegated_account,
+        owner_program,
+        undelegate_buffer_account,
+        commit_state_account,
+        commit_record_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        undelegate_progress_account,
+        system_program,
+    } = UndelegateStage1Accounts::try_from_accounts(accounts)?;
+
+    // Check accounts
+    require_owned_pda(delegated_account, &crate::fast::ID, "delegated account")?;
+    require_initialized_delegation_record(delegated_account, delegation_record_account, false)?;
+    require_initialized_delegation_metadata(delegated_account, delegation_metadata_account, false)?;
+
+    // Make sure there is no pending commits to be finalized before this call
+    require_uninitialized_pda(
+        commit_state_account,
+        &[pda::COMMIT_STATE_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        false,
+        CommitStateAccountCtx,
+    )?;
+    require_uninitialized_pda(
+        commit_record_account,
+        &[pda::COMMIT_RECORD_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        false,
+        CommitRecordCtx,
+    )?;
+    let undelegate_progress_bump = require_uninitialized_pda(
+        undelegate_progress_account,
+        &[pda::UNDELEGATE_PROGRESS_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        true,
+        UndelegateProgressCtx,
+    )?;
+
+    // Load delegation record
+    let delegation_record_data = delegation_record_account.try_borrow_data()?;
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)
+            .map_err(to_pinocchio_program_error)?;
+
+    // Check passed owner and owner stored in the delegation record match
+    if !pubkey_eq(delegation_record.owner.as_array(), owner_program.key()) {
+        log!("Expected delegation record owner to be : ");
+        pubkey::log(delegation_record.owner.as_array());
+        log!("but got : ");
+        pubkey::log(owner_program.key());
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    // Load delegated account metadata
+    let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+    let mut delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)
+            .map_err(to_pinocchio_program_error)?;
+
+    // Check if the delegated account is undelegatable, or its validator stopped heartbeating
+    require_undelegatable(
+        delegation_metadata_account,
+        &delegation_metadata,
+        &delegation_record,
+        validator_liveness,
+    )?;
+
+    // Resolve the seeds from the template with the recorded rent payer, the rent
+    // reimbursement account being only passed to the second stage
+    if let Some(seed_template) = delegation_metadata.seed_template {
+        let seeds = seed_template.resolve(
+            delegation_metadata.rent_payer.as_array(),
+            owner_program.key(),
+        );
+        require_delegated_account_seeds(delegated_account, owner_program, &seeds)?;
+        delegation_metadata.seeds = seeds;
+    }
+
+    let nonce = delegation_metadata.last_update_nonce;
+    trace!(delegated_account.key(), nonce, "undelegate-stage1", "enter");
+
+    // Dropping delegation references
+    drop(delegation_record_data);
+    drop(delegation_metadata_data);
+
+    // Without data there is no CPI to split off
+    if delegated_account.data_is_empty() {
+        log!("delegated account has no data, undelegate it in a single instruction");
+        return Err(DlpError::InvalidDelegatedState.into());
+    }
+
+    // Record the progress first, so that the second stage can only follow a CPI
+    let rent = Rent::get()?;
+    create_pda(
+        undelegate_progress_account,
+        &crate::fast::ID,
+        UndelegateProgress::size_with_discriminator(),
+        &[Signer::from(&seeds!(
+            pda::UNDELEGATE_PROGRESS_TAG,
+            delegated_account.key(),
+            &[undelegate_progress_bump]
+        ))],
+        validator,
+        &rent,
+    )?;
+    let undelegate_progress = UndelegateProgress {
+        validator: (*validator.key()).into(),
+        owner: (*owner_program.key()).into(),
+        slot: Clock::get()?.slot,
+    };
+    {
+        let mut undelegate_progress_data = undelegate_progress_account.try_borrow_mut_data()?;
+        undelegate_progress
+            .to_bytes_with_discriminator(&mut undelegate_progress_data)
+            .map_err(to_pinocchio_program_error)?;
+    }
+
+    // Initialize the undelegation buffer PDA
+    let undelegate_buffer_bump: u8 = require_uninitialized_pda(
+        undelegate_buffer_account,
+        &[pda::UNDELEGATE_BUFFER_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        true,
+        UndelegateBufferCtx,
+    )?;
+    create_pda(
+        undelegate_buffer_account,
+        &crate::fast::ID,
+        delegated_account.data_len(),
+        &[Signer::from(&seeds!(
+            pda::UNDELEGATE_BUFFER_TAG,
+            delegated_account.key(),
+            &[undelegate_buffer_bump]
+        ))],
+        validator,
+        &rent,
+    )?;
+
+    let lamports_tolerance = undelegate_lamports_tolerance(owner_program, program_config_account)?;
+
+    // Copy data in the undelegation buffer PDA
+    (*undelegate_buffer_account.try_borrow_mut_data()?)
+        .copy_from_slice(&delegated_account.try_borrow_data()?);
+
+    trace!(
+        delegated_account.key(),
+        nonce,
+        "undelegate-stage1",
+        "cpi-owner-program"
+    );
+
+    // Call a CPI to the owner program to give it back the new state
+    process_undelegation_with_cpi(
+        validator,
+        delegated_account,
+        owner_program,
+        undelegate_buffer_account,
+        &[Signer::from(&seeds!(
+            pda::UNDELEGATE_BUFFER_TAG,
+            delegated_account.key(),
+            &[undelegate_buffer_bump]
+        ))],
+        delegation_metadata,
+        &delegation_record,
+        system_program,
+        lamports_tolerance,
+        &rent,
+    )?;
+
+    // Done, close undelegation buffer
+    close_pda(undelegate_buffer_account, validator)?;
+    trace!(delegated_account.key(), nonce, "undelegate-stage1", "exit");
+    Ok(())
+}
+
+/// The number of accounts of [process_undelegate_stage1], without the optional trailing
+/// program config
+const UNDELEGATE_STAGE1_ACCOUNTS: usize = 10;
+
+accounts_ctx! {
+    /// Accounts of [process_undelegate_stage1]
+    pub(crate) struct UndelegateStage1Accounts {
+        validator: [signer, writable] "validator",
+        delegated_account: [writable] "delegated account",
+        owner_program: [] "owner program",
+        undelegate_buffer_account: [writable] "undelegate buffer",
+        commit_state_account: [] "commit state",
+        commit_record_account: [] "commit record",
+        delegation_record_account: [] "delegation record",
+        delegation_metadata_account: [] "delegation metadata",
+        undelegate_progress_account: [writable] "undelegate progress",
+        system_program: [] "system program",
+    }
+}
+
+/// Accounts of [process_undelegate_stage1]
+pub const UNDELEGATE_STAGE1_ACCOUNTS_SPEC: AccountsSpec = UndelegateStage1Accounts::SPEC;
@@ -0,0 +1,230 @@
+use crate::accounts_spec::AccountsSpec;
+use crate::log::log;
+use pinocchio::pubkey;
+use pinocchio::{
+    account_info::AccountInfo,
+    pubkey::{pubkey_eq, Pubkey},
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::consts::UNDELEGATE_STAGE2_TIMEOUT_SLOTS;
+use crate::error::DlpError;
+use crate::pda;
+use crate::processor::fast::utils::{
+    accounts_ctx::accounts_ctx,
+    earnings_ledger::split_earnings_ledger,
+    pda::close_pda,
+    protocol_stats::{record_tvl_change, split_protocol_stats},
+    rent_co_payer::{require_rent_co_payer, split_recorded_rent_co_payer},
+    requires::require_initialized_pda,
+};
+use crate::state::{DelegationMetadata, DelegationRecord, UndelegateProgress};
+use crate::trace::trace;
+
+use super::{
+    to_pinocchio_program_error,
+    undelegate::{is_fee_exempt, process_delegation_cleanup},
+    utils::requires::{
+        require_initialized_delegation_metadata, require_initialized_delegation_record,
+        require_initialized_protocol_fees_vault, require_initialized_validator_fees_vault_of,
+    },
+};
+
+/// Close the delegation PDAs of an account given back to its owner program by
+/// [crate::processor::fast::process_undelegate_stage1], the second stage of a staged
+/// undelegation
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the validator which ran the first stage or, after the timeout,
+///                         anyone
+/// 1: `[]`         the delegated account
+/// 2: `[writable]` the delegation record PDA
+/// 3: `[writable]` the delegation metadata PDA
+/// 4: `[writable]` the undelegate progress PDA
+/// 5: `[writable]` the rent reimbursement account
+/// 6: `[writable]` the protocol fees vault account
+/// 7: `[writable]` the validator fees vault of the validator which ran the first stage
+/// 8: `[]`         (optional) the fee exemption PDA
+/// 9: `[writable]` (optional) the earnings ledger page of the validator, see
+///                 [crate::processor::fast::process_undelegate]
+/// 10: `[writable]` (optional) the protocol stats PDA, see
+///                  [crate::processor::fast::process_undelegate]
+/// 11: `[writable]` (optional) the rent co-payer of the delegation, passed last, required if
+///                  the delegation has one, see [crate::args::RentCoPayer]
+///
+/// Requirements:
+///
+/// - undelegate progress is initialized
+/// - signer is the validator which ran the first stage or, once
+///   [UNDELEGATE_STAGE2_TIMEOUT_SLOTS] elapsed since the first stage, anyone, so that a
+///   validator cannot leave the delegation PDAs open and block a new delegation
+/// - delegated account is not owned by the delegation program anymore
+/// - delegation record, delegation metadata, protocol fees vault and validator fees vault
+///   are initialized
+/// - rent reimbursement account matches the rent payer in the delegation metadata
+/// - rent co-payer is passed if the delegation metadata records one
+///
+/// Steps:
+///
+/// 1. Close the undelegate progress, refunding the validator, or its fees vault if another
+///    signer runs the stage after the timeout
+/// 2. Close the delegation record and metadata as
+///    [crate::processor::fast::process_undelegate], subtracting the lamports recorded by
+///    the delegation record from the total value locked of the protocol stats, if provided
+pub fn process_undelegate_stage2(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // The fee exemption, the earnings ledger page, the protocol stats and the rent co-payer
+    // are optional trailing accounts
+    let (accounts, rent_co_payer) =
+        split_recorded_rent_co_payer(accounts, UNDELEGATE_STAGE2_DELEGATION_METADATA_INDEX);
+    let (accounts, protocol_stats) = split_protocol_stats(accounts);
+    let (accounts, earnings_ledger) = split_earnings_ledger(accounts);
+    let (accounts, fee_exemption_account) = match accounts {
+        [accounts @ .., fee_exemption_account] if accounts.len() == UNDELEGATE_STAGE2_ACCOUNTS => {
+            (accounts, Some(fee_exemption_account))
+        }
+        _ => (accounts, None),
+    };
+    let UndelegateStage2Accounts {
+        validator,
+        delegated_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        undelegate_progress_account,
+        rent_reimbursement,
+        fees_vault,
+        validator_fees_vault,
+    } = UndelegateStage2Accounts::try_from_accounts(accounts)?;
+
+    // Check accounts
+    require_initialized_pda(
+        undelegate_progress_account,
+        &[pda::UNDELEGATE_PROGRESS_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        true,
+        "undelegate progress",
+    )?;
+    require_initialized_delegation_record(delegated_account, delegation_record_account, true)?;
+    require_initialized_delegation_metadata(delegated_account, delegation_metadata_account, true)?;
+    require_initialized_protocol_fees_vault(fees_vault, true)?;
+
+    // Load the undelegate progress
+    let undelegate_progress_data = undelegate_progress_account.try_borrow_data()?;
+    let undelegate_progress =
+        *UndelegateProgress::try_from_bytes_with_discriminator(&undelegate_progress_data)
+            .map_err(to_pinocchio_program_error)?;
+    drop(undelegate_progress_data);
+
+    // The fees of the cleanup are paid to the validator which ran the first stage, which
+    // alone can run the second stage until the timeout
+    require_initialized_validator_fees_vault_of(
+        undelegate_progress.validator.as_array(),
+        validator_fees_vault,
+        true,
+    )?;
+    let is_stage1_validator = pubkey_eq(undelegate_progress.validator.as_array(), validator.key());
+    if !is_stage1_validator
+        && Clock::get()?.slot
+            < undelegate_progress
+                .slot
+                .saturating_add(UNDELEGATE_STAGE2_TIMEOUT_SLOTS)
+    {
+        log!("Expected undelegate progress validator to be : ");
+        pubkey::log(undelegate_progress.validator.as_array());
+        log!("but got : ");
+        pubkey::log(validator.key());
+        return Err(DlpError::InvalidUndelegateProgress.into());
+    }
+
+    // The first stage gave the account back to its owner program, which may have closed it
+    // since, either way it is not delegated anymore
+    if pubkey_eq(delegated_account.owner(), &crate::fast::ID) {
+        log!("delegated account is still owned by the delegation program : ");
+        pubkey::log(delegated_account.key());
+        return Err(DlpError::InvalidUndelegateProgress.into());
+    }
+
+    // Load delegation record
+    let delegation_record_data = delegation_record_account.try_borrow_data()?;
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)
+            .map_err(to_pinocchio_program_error)?;
+    drop(delegation_record_data);
+
+    // Load delegated account metadata
+    let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)
+            .map_err(to_pinocchio_program_error)?;
+
+    // Check if the rent payer is correct
+    if !pubkey_eq(
+        delegation_metadata.rent_payer.as_array(),
+        rent_reimbursement.key(),
+    ) {
+        log!("Expected rent payer to be : ");
+        pubkey::log(delegation_metadata.rent_payer.as_array());
+        log!("but got : ");
+        pubkey::log(rent_reimbursement.key());
+        return Err(DlpError::InvalidReimbursementAddressForDelegationRent.into());
+    }
+    require_rent_co_payer(&delegation_metadata, rent_co_payer)?;
+
+    let nonce = delegation_metadata.last_update_nonce;
+    trace!(delegated_account.key(), nonce, "undelegate-stage2", "enter");
+    drop(delegation_metadata_data);
+
+    let fee_exempt = is_fee_exempt(delegated_account, fee_exemption_account)?;
+    record_tvl_change(protocol_stats, delegation_record.lamports, 0)?;
+
+    if is_stage1_validator {
+        close_pda(undelegate_progress_account, validator)?;
+    } else {
+        close_pda(undelegate_progress_account, validator_fees_vault)?;
+    }
+
+    // Closing delegation accounts
+    process_delegation_cleanup(
+        undelegate_progress.validator.as_array(),
+        delegated_account,
+        delegation_record_account,
+        delegation_metadata_account,
+        rent_reimbursement,
+        rent_co_payer,
+        fees_vault,
+        Some(validator_fees_vault),
+        earnings_ledger,
+        fee_exempt,
+    )?;
+    trace!(delegated_account.key(), nonce, "undelegate-stage2", "exit");
+    Ok(())
+}
+
+/// The number of accounts of [process_undelegate_stage2], without the optional trailing
+/// accounts
+const UNDELEGATE_STAGE2_ACCOUNTS: usize = 8;
+
+/// The index of the delegation metadata in the accounts of [process_undelegate_stage2]
+const UNDELEGATE_STAGE2_DELEGATION_METADATA_INDEX: usize = 3;
+
+accounts_ctx! {
+    /// Accounts of [process_undelegate_stage2]
+    pub(crate) struct UndelegateStage2Accounts {
+        validator: [signer, writable] "validator",
+        delegated_account: [] "delegated account",
+        delegation_record_account: [writable] "delegation record",
+        delegation_metadata_account: [writable] "delegation metadata",
+        undelegate_progress_account: [writable] "undelegate progress",
+        rent_reimbursement: [] "rent reimbursement",
+        fees_vault: [writable] "protocol fees vault",
+        validator_fees_vault: [writable] "validator fees vault",
+    }
+}
+
+/// Accounts of [process_undelegate_stage2]
+pub const UNDELEGATE_STAGE2_ACCOUNTS_SPEC: AccountsSpec = UndelegateStage2Accounts::SPEC;
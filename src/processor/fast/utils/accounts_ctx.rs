@@ -0,0 +1,79 @@
+/// Declares the accounts of a fast processor, in the order documented by the processor.
+///
+/// Each account is declared as `name: [roles] "label"`, where roles is a (possibly empty) list
+/// of `signer` and `writable`. The macro generates a struct holding the accounts, a `SPEC`
+/// listing the accounts and their roles, see [crate::accounts_spec], and a
+/// `try_from_accounts` constructor which:
+///
+/// - destructures the accounts, failing with `NotEnoughAccountKeys` on a length mismatch
+/// - checks every role, logging the label of the offending account
+///
+/// ```ignore
+/// accounts_ctx! {
+///     pub(crate) struct FinalizeAccounts {
+///         validator: [signer] "validator",
+///         delegated_account: [writable] "delegated account",
+///     }
+/// }
+/// ```
+macro_rules! accounts_ctx {
+    (@role signer, $field:ident, $label:literal) => {
+        $crate::processor::fast::utils::requires::require_signer($field, $label)?
+    };
+    (@role writable, $field:ident, $label:literal) => {
+        $crate::processor::fast::utils::requires::require_writable($field, $label)?
+    };
+    (@has $want:ident;) => {
+        false
+    };
+    (@has signer; signer $(, $rest:ident)*) => {
+        true
+    };
+    (@has writable; writable $(, $rest:ident)*) => {
+        true
+    };
+    (@has $want:ident; $role:ident $(, $rest:ident)*) => {
+        accounts_ctx!(@has $want; $($rest),*)
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $( $field:ident : [$($role:ident),*] $label:literal ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name<'a> {
+            $(
+                #[doc = $label]
+                pub(crate) $field: &'a ::pinocchio::account_info::AccountInfo,
+            )*
+        }
+
+        impl<'a> $name<'a> {
+            /// The accounts and their roles, in order
+            pub(crate) const SPEC: $crate::accounts_spec::AccountsSpec = &[
+                $(
+                    $crate::accounts_spec::AccountSpec {
+                        name: $label,
+                        writable: accounts_ctx!(@has writable; $($role),*),
+                        signer: accounts_ctx!(@has signer; $($role),*),
+                    },
+                )*
+            ];
+
+            /// Destructures the accounts and checks their roles.
+            #[inline(always)]
+            pub(crate) fn try_from_accounts(
+                accounts: &'a [::pinocchio::account_info::AccountInfo],
+            ) -> Result<Self, ::pinocchio::program_error::ProgramError> {
+                let [$($field),*] = accounts else {
+                    return Err(::pinocchio::program_error::ProgramError::NotEnoughAccountKeys);
+                };
+                $( $( accounts_ctx!(@role $role, $field, $label); )* )*
+                Ok(Self { $($field),* })
+            }
+        }
+    };
+}
+
+pub(crate) use accounts_ctx;
@@ -0,0 +1,92 @@
+use crate::log::log;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{self, pubkey_eq, Pubkey};
+
+use crate::args::AuthorityScope;
+use crate::error::DlpError;
+use crate::pda::authority_delegation_chain_pda_from_delegator;
+use crate::processor::fast::to_pinocchio_program_error;
+use crate::state::{AccountDiscriminator, AuthorityDelegationChain, MAX_AUTHORITY_CHAIN_DEPTH};
+
+/// Split the links of an authority delegation chain off the end of the accounts, if passed,
+/// ordered from the chain of the delegation authority to the chain granting the signer.
+///
+/// A link is recognized by its owner and discriminator, its PDA being checked at
+/// [require_authority_chain] since the delegated accounts, also owned by the delegation
+/// program, hold the data committed by their validator.
+pub(crate) fn split_authority_chain(accounts: &[AccountInfo]) -> (&[AccountInfo], &[AccountInfo]) {
+    let links = accounts
+        .iter()
+        .rev()
+        .take(MAX_AUTHORITY_CHAIN_DEPTH)
+        .take_while(|info| is_authority_delegation_chain(info))
+        .count();
+    accounts.split_at(accounts.len().saturating_sub(links))
+}
+
+fn is_authority_delegation_chain(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.starts_with(&AccountDiscriminator::AuthorityDelegationChain.to_bytes())
+        })
+}
+
+/// Require the links to grant the signer the scope of the authority at the slot: the first
+/// link is the chain of the authority, every next link the chain of a key granted by the
+/// previous one, and the last link grants the signer.
+/// Each grant of the chain must cover the scope and be unexpired, so that a sub-key cannot
+/// outlive nor widen the grant it received.
+pub(crate) fn require_authority_chain(
+    links: &[AccountInfo],
+    authority: &Pubkey,
+    signer: &Pubkey,
+    scope: AuthorityScope,
+    slot: u64,
+) -> Result<(), ProgramError> {
+    if links.is_empty() || links.len() > MAX_AUTHORITY_CHAIN_DEPTH {
+        log!(
+            "authority delegation chain must have between 1 and {} links",
+            MAX_AUTHORITY_CHAIN_DEPTH
+        );
+        return Err(DlpError::InvalidAuthorityChain.into());
+    }
+    let mut grantor: Option<AuthorityDelegationChain> = None;
+    for link in links {
+        let chain = load_authority_delegation_chain(link)?;
+        let is_linked = match &grantor {
+            None => pubkey_eq(chain.delegator.as_array(), authority),
+            Some(grantor) => grantor.is_granted(&chain.delegator, scope, slot),
+        };
+        if !is_linked {
+            log!("authority delegation chain is not granted by the previous link: ");
+            pubkey::log(link.key());
+            return Err(DlpError::InvalidAuthorityChain.into());
+        }
+        grantor = Some(chain);
+    }
+    if !grantor.is_some_and(|grantor| grantor.is_granted(&(*signer).into(), scope, slot)) {
+        log!("authority delegation chain does not grant the signer: ");
+        pubkey::log(signer);
+        return Err(DlpError::InvalidAuthorityChain.into());
+    }
+    Ok(())
+}
+
+/// Load an authority delegation chain, which must be the PDA of the delegator it holds
+fn load_authority_delegation_chain(
+    link: &AccountInfo,
+) -> Result<AuthorityDelegationChain, ProgramError> {
+    let link_data = link.try_borrow_data()?;
+    let chain = AuthorityDelegationChain::try_from_bytes_with_discriminator(&link_data)
+        .map_err(to_pinocchio_program_error)?;
+    let pda = authority_delegation_chain_pda_from_delegator(&chain.delegator);
+    if !pubkey_eq(pda.as_array(), link.key()) || !pubkey_eq(link.owner(), &crate::fast::ID) {
+        log!("Invalid authority delegation chain PDA, expected: ");
+        pubkey::log(pda.as_array());
+        log!("but got: ");
+        pubkey::log(link.key());
+        return Err(DlpError::InvalidAuthorityChain.into());
+    }
+    Ok(chain)
+}
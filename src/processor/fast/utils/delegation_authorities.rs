@@ -0,0 +1,54 @@
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{pubkey_eq, Pubkey};
+
+use crate::pda::delegation_authorities_pda_from_delegated_account;
+use crate::processor::fast::to_pinocchio_program_error;
+use crate::state::{AccountDiscriminator, DelegationAuthorities};
+
+/// Split the delegation authorities off the end of the accounts, if passed.
+///
+/// The delegation authorities are recognized by their owner and discriminator, their PDA
+/// being checked at [is_delegation_authority] since the delegated accounts, also owned by the
+/// delegation program, hold the data committed by their validator.
+pub(crate) fn split_delegation_authorities(
+    accounts: &[AccountInfo],
+) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((delegation_authorities, accounts))
+            if is_delegation_authorities(delegation_authorities) =>
+        {
+            (accounts, Some(delegation_authorities))
+        }
+        _ => (accounts, None),
+    }
+}
+
+fn is_delegation_authorities(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.starts_with(&AccountDiscriminator::DelegationAuthorities.to_bytes())
+        })
+}
+
+/// Whether the delegation authorities, if passed, let the signer commit the delegation of
+/// the delegated account started at the delegation slot
+pub(crate) fn is_delegation_authority(
+    delegation_authorities: Option<&AccountInfo>,
+    delegated_account: &Pubkey,
+    delegation_slot: u64,
+    signer: &Pubkey,
+) -> Result<bool, ProgramError> {
+    let Some(delegation_authorities) = delegation_authorities else {
+        return Ok(false);
+    };
+    let pda = delegation_authorities_pda_from_delegated_account(&(*delegated_account).into());
+    if !pubkey_eq(pda.as_array(), delegation_authorities.key()) {
+        return Ok(false);
+    }
+    let delegation_authorities_data = delegation_authorities.try_borrow_data()?;
+    let delegation_authorities =
+        DelegationAuthorities::try_from_bytes_with_discriminator(&delegation_authorities_data)
+            .map_err(to_pinocchio_program_error)?;
+    Ok(delegation_authorities.is_authority(&(*signer).into(), delegation_slot))
+}
@@ -0,0 +1,86 @@
+use crate::log::log;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::pubkey::{self, pubkey_eq, Pubkey};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::ProgramResult;
+
+use crate::error::DlpError;
+use crate::pda;
+use crate::processor::fast::to_pinocchio_program_error;
+use crate::processor::fast::utils::requires::require_pda;
+use crate::state::{EarningsEntry, EarningsKind, EarningsLedgerPage};
+
+/// Split the earnings ledger page off the end of the accounts, if passed.
+///
+/// A page is recognized by its owner and discriminator, its PDA being checked at
+/// [record_earnings] since the delegated accounts, also owned by the delegation program,
+/// hold the data committed by their validator.
+pub(crate) fn split_earnings_ledger(
+    accounts: &[AccountInfo],
+) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((earnings_ledger, accounts)) if is_earnings_ledger_page(earnings_ledger) => {
+            (accounts, Some(earnings_ledger))
+        }
+        _ => (accounts, None),
+    }
+}
+
+fn is_earnings_ledger_page(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info
+            .try_borrow_data()
+            .is_ok_and(|data| EarningsLedgerPage::from_page_data(&data).is_ok())
+}
+
+/// Append the lamports the validator fees vault earned to the earnings ledger page of the
+/// validator, if passed
+pub(crate) fn record_earnings(
+    earnings_ledger: Option<&AccountInfo>,
+    validator: &Pubkey,
+    delegated_account: &Pubkey,
+    amount: u64,
+    kind: EarningsKind,
+) -> ProgramResult {
+    let Some(earnings_ledger) = earnings_ledger else {
+        return Ok(());
+    };
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let page = *EarningsLedgerPage::from_page_data(&earnings_ledger.try_borrow_data()?)
+        .map_err(to_pinocchio_program_error)?;
+    if !pubkey_eq(page.validator.as_array(), validator) {
+        log!("earnings ledger page is not the one of the validator: ");
+        pubkey::log(validator);
+        return Err(DlpError::InvalidEarningsLedgerPage.into());
+    }
+    require_pda(
+        earnings_ledger,
+        &[
+            pda::EARNINGS_LEDGER_TAG,
+            validator,
+            &page.page.to_le_bytes(),
+        ],
+        &crate::fast::ID,
+        true,
+        "earnings ledger page",
+    )?;
+
+    let entry = EarningsEntry {
+        slot: Clock::get()?.slot,
+        delegated_account: (*delegated_account).into(),
+        amount,
+        kind: kind.into(),
+        padding: [0; 7],
+    };
+    let mut earnings_ledger_data = earnings_ledger.try_borrow_mut_data()?;
+    if !EarningsLedgerPage::append(&mut earnings_ledger_data, &entry)
+        .map_err(to_pinocchio_program_error)?
+    {
+        log!("earnings ledger page is full, the next page must be passed");
+        return Err(DlpError::EarningsLedgerPageFull.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::log::log;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{pubkey_eq, Pubkey};
+use pinocchio::sysvars::instructions::{Instructions, INSTRUCTIONS_ID};
+use pinocchio::ProgramResult;
+
+use crate::args::CallHandlerArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::error::DlpError;
+
+/// The index of the escrow among the accounts of a call handler instruction
+const CALL_HANDLER_ESCROW_INDEX: usize = 4;
+
+/// Split the instructions sysvar off the end of the accounts, if passed
+pub(crate) fn split_instructions_sysvar(
+    accounts: &[AccountInfo],
+) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((instructions_sysvar, accounts))
+            if pubkey_eq(instructions_sysvar.key(), &INSTRUCTIONS_ID) =>
+        {
+            (accounts, Some(instructions_sysvar))
+        }
+        _ => (accounts, None),
+    }
+}
+
+/// Require the call handler instructions of the transaction spending from the escrow to
+/// declare, in total, the escrow spend declared by its commit.
+///
+/// The call handler enforces its declared spend against the lamports it moved out of the
+/// escrow, see [crate::args::CallHandlerArgs::escrow_spend], so that matching the declared
+/// spends matches the lamports actually moved. A call handler of the escrow without a
+/// declared spend cannot be accounted for and is rejected. Only the top level
+/// instructions of the transaction are visible through the instructions sysvar.
+pub(crate) fn require_escrow_spend(
+    instructions_sysvar: Option<&AccountInfo>,
+    escrow: &Pubkey,
+    escrow_spend: u64,
+) -> ProgramResult {
+    let Some(instructions_sysvar) = instructions_sysvar else {
+        log!("instructions sysvar is required to verify the escrow spend");
+        return Err(DlpError::EscrowSpendMismatch.into());
+    };
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+
+    let call_handler_discriminator = (DlpDiscriminator::CallHandler as u64).to_le_bytes();
+    let mut spent: u64 = 0;
+    for index in 0..instructions.num_instructions() {
+        let instruction = instructions.load_instruction_at(index as usize)?;
+        if !pubkey_eq(instruction.get_program_id(), &crate::fast::ID) {
+            continue;
+        }
+        let Some((discriminator, data)) =
+            instruction.get_instruction_data().split_first_chunk::<8>()
+        else {
+            continue;
+        };
+        if discriminator != &call_handler_discriminator {
+            continue;
+        }
+        let call_handler_escrow = instruction.get_account_meta_at(CALL_HANDLER_ESCROW_INDEX)?;
+        if !pubkey_eq(&call_handler_escrow.key, escrow) {
+            continue;
+        }
+        let args = CallHandlerArgs::try_from_instruction_data(data)
+            .map_err(|_| ProgramError::BorshIoError)?;
+        let Some(call_handler_spend) = args.escrow_spend else {
+            log!("call handler of the escrow does not declare its spend");
+            return Err(DlpError::EscrowSpendMismatch.into());
+        };
+        spent = spent
+            .checked_add(call_handler_spend)
+            .ok_or(DlpError::Overflow)?;
+    }
+
+    if spent != escrow_spend {
+        log!(
+            "call handlers spent {} lamports of the escrow, its commit declared {}",
+            spent,
+            escrow_spend
+        );
+        return Err(DlpError::EscrowSpendMismatch.into());
+    }
+    Ok(())
+}
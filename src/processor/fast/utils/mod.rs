@@ -1,2 +1,13 @@
+pub(crate) mod accounts_ctx;
+pub(crate) mod authority_chain;
+pub(crate) mod delegation_authorities;
+pub(crate) mod earnings_ledger;
+pub(crate) mod escrow_spend;
 pub(crate) mod pda;
+pub(crate) mod protocol_stats;
+pub(crate) mod read_lock;
+pub(crate) mod rent_co_payer;
 pub(crate) mod requires;
+pub(crate) mod session_report;
+pub(crate) mod validator_liveness;
+pub(crate) mod whitelist_shard;
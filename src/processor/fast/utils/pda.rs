@@ -1,13 +1,14 @@
+use alloc::{vec, vec::Vec};
+
 use pinocchio::account_info::AccountInfo;
 use pinocchio::instruction::Signer;
 use pinocchio::program_error::ProgramError;
 use pinocchio::pubkey::Pubkey;
 use pinocchio::sysvars::rent::Rent;
-use pinocchio::sysvars::Sysvar;
 use pinocchio::ProgramResult;
 use pinocchio_system::instructions as system;
 
-/// Creates a new pda
+/// Creates a new pda, rent exempt under the rent sysvar fetched by the instruction
 #[inline(always)]
 pub(crate) fn create_pda(
     target_account: &AccountInfo,
@@ -15,10 +16,10 @@ pub(crate) fn create_pda(
     space: usize,
     pda_signers: &[Signer],
     payer: &AccountInfo,
+    rent: &Rent,
 ) -> ProgramResult {
     // Create the account manually or using the create instruction
 
-    let rent = Rent::get()?;
     if target_account.lamports().eq(&0) {
         // If balance is zero, create account
         system::CreateAccount {
@@ -61,6 +62,60 @@ pub(crate) fn create_pda(
     }
 }
 
+/// Grow a PDA to the new length if it is shorter, moving the rent of the additional bytes
+/// from another PDA of the delegation program
+pub(crate) fn grow_pda_funded_by_pda(
+    target_account: &AccountInfo,
+    new_len: usize,
+    funding_account: &AccountInfo,
+    rent: &Rent,
+) -> ProgramResult {
+    if new_len <= target_account.data_len() {
+        return Ok(());
+    }
+
+    let lamports = rent
+        .minimum_balance(new_len)
+        .saturating_sub(target_account.lamports());
+    *funding_account.try_borrow_mut_lamports()? = funding_account
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    *target_account.try_borrow_mut_lamports()? = target_account
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    target_account.resize(new_len).map_err(Into::into)
+}
+
+/// Grow a PDA to the new length if it is shorter, the payer transferring the rent of the
+/// additional bytes
+pub(crate) fn grow_pda_funded_by_payer(
+    target_account: &AccountInfo,
+    new_len: usize,
+    payer: &AccountInfo,
+    rent: &Rent,
+) -> ProgramResult {
+    if new_len <= target_account.data_len() {
+        return Ok(());
+    }
+
+    let lamports = rent
+        .minimum_balance(new_len)
+        .saturating_sub(target_account.lamports());
+    if lamports > 0 {
+        system::Transfer {
+            from: payer,
+            to: target_account,
+            lamports,
+        }
+        .invoke()?;
+    }
+
+    target_account.resize(new_len).map_err(Into::into)
+}
+
 /// Close PDA
 #[inline(always)]
 pub(crate) fn close_pda(target_account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
@@ -82,12 +137,16 @@ pub(crate) fn close_pda(target_account: &AccountInfo, destination: &AccountInfo)
 /// Close PDA with fees, distributing the fees to the specified addresses in sequence
 /// The total fees are calculated as `fee_percentage` of the total lamports in the PDA
 /// Each fee address receives fee_percentage % of the previous fee address's amount
-pub(crate) fn close_pda_with_fees(
+/// The lamports left after the fees are refunded to the rent co-payer, if any, up to the
+/// lamports it is owed, then to the destination. Returns the lamports refunded to the
+/// rent co-payer
+pub(crate) fn close_pda_with_fees_split(
     target_account: &AccountInfo,
     destination: &AccountInfo,
+    rent_co_payer: Option<(&AccountInfo, u64)>,
     fees_addresses: &[&AccountInfo],
     fee_percentage: u8,
-) -> ProgramResult {
+) -> Result<u64, ProgramError> {
     if fees_addresses.is_empty() || fee_percentage > 100 {
         return Err(ProgramError::InvalidArgument);
     }
@@ -110,28 +169,45 @@ pub(crate) fn close_pda_with_fees(
         *fee = fee_amount;
     }
 
-    for i in 0..fees.len() - 1 {
-        fees[i] -= fees[i + 1];
-    }
-
-    for (i, &fee_address) in fees_addresses.iter().enumerate() {
+    // Each fee address keeps its fee minus the one passed on to the next fee address
+    let mut fees = fees.into_iter().peekable();
+    for &fee_address in fees_addresses {
+        let fee = fees.next().ok_or(ProgramError::InvalidArgument)?;
+        let fee = fee
+            .checked_sub(fees.peek().copied().unwrap_or(0))
+            .ok_or(ProgramError::InsufficientFunds)?;
         unsafe {
             *fee_address.borrow_mut_lamports_unchecked() = fee_address
                 .lamports()
-                .checked_add(fees[i])
+                .checked_add(fee)
                 .ok_or(ProgramError::InsufficientFunds)?;
         }
     }
 
+    let mut remaining_lamports = init_lamports
+        .checked_sub(total_fee_amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    let mut rent_co_payer_refund = 0;
+    if let Some((rent_co_payer_account, owed_lamports)) = rent_co_payer {
+        rent_co_payer_refund = owed_lamports.min(remaining_lamports);
+        remaining_lamports -= rent_co_payer_refund;
+        unsafe {
+            *rent_co_payer_account.borrow_mut_lamports_unchecked() = rent_co_payer_account
+                .lamports()
+                .checked_add(rent_co_payer_refund)
+                .ok_or(ProgramError::InsufficientFunds)?;
+        }
+    }
     unsafe {
         *destination.borrow_mut_lamports_unchecked() = destination
             .lamports()
-            .checked_add(init_lamports - total_fee_amount)
+            .checked_add(remaining_lamports)
             .ok_or(ProgramError::InsufficientFunds)?;
 
         *target_account.borrow_mut_lamports_unchecked() = 0;
 
         target_account.assign(&pinocchio_system::ID);
     }
-    target_account.resize(0).map_err(Into::into)
+    target_account.resize(0)?;
+    Ok(rent_co_payer_refund)
 }
@@ -0,0 +1,60 @@
+use pinocchio::account_info::AccountInfo;
+use pinocchio::pubkey::pubkey_eq;
+use pinocchio::ProgramResult;
+
+use crate::pda;
+use crate::processor::fast::to_pinocchio_program_error;
+use crate::processor::fast::utils::requires::require_pda;
+use crate::state::{AccountDiscriminator, ProtocolStats};
+
+/// Split the protocol stats off the end of the accounts, if passed.
+///
+/// The protocol stats are recognized by their owner and discriminator, their PDA being
+/// checked at [record_tvl_change] since the delegated accounts, also owned by the delegation
+/// program, hold the data committed by their validator.
+pub(crate) fn split_protocol_stats(
+    accounts: &[AccountInfo],
+) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((protocol_stats, accounts)) if is_protocol_stats(protocol_stats) => {
+            (accounts, Some(protocol_stats))
+        }
+        _ => (accounts, None),
+    }
+}
+
+fn is_protocol_stats(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.len() == ProtocolStats::size_with_discriminator()
+                && data.starts_with(&AccountDiscriminator::ProtocolStats.to_bytes())
+        })
+}
+
+/// Apply the change of the lamports recorded by a delegation record to the total value
+/// locked of the protocol stats, if passed
+pub(crate) fn record_tvl_change(
+    protocol_stats: Option<&AccountInfo>,
+    previous_lamports: u64,
+    lamports: u64,
+) -> ProgramResult {
+    let Some(protocol_stats) = protocol_stats else {
+        return Ok(());
+    };
+    if previous_lamports == lamports {
+        return Ok(());
+    }
+    require_pda(
+        protocol_stats,
+        &[pda::PROTOCOL_STATS_TAG],
+        &crate::fast::ID,
+        true,
+        "protocol stats",
+    )?;
+
+    let mut protocol_stats_data = protocol_stats.try_borrow_mut_data()?;
+    ProtocolStats::try_from_bytes_with_discriminator_mut(&mut protocol_stats_data)
+        .map_err(to_pinocchio_program_error)?
+        .apply_lamports_change(previous_lamports, lamports);
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use pinocchio::account_info::AccountInfo;
+use pinocchio::pubkey::pubkey_eq;
+
+use crate::state::{AccountDiscriminator, ReadLock};
+
+/// Split the read lock of a delegated account off the end of the accounts, if passed.
+///
+/// Like the protocol stats, the read lock is recognized by its owner and discriminator, its
+/// PDA being checked against the delegated account when locking it at finalize.
+pub(crate) fn split_read_lock(accounts: &[AccountInfo]) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((read_lock, accounts)) if is_read_lock(read_lock) => (accounts, Some(read_lock)),
+        _ => (accounts, None),
+    }
+}
+
+fn is_read_lock(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.len() == ReadLock::size_with_discriminator()
+                && data.starts_with(&AccountDiscriminator::ReadLock.to_bytes())
+        })
+}
@@ -0,0 +1,95 @@
+use pinocchio::account_info::AccountInfo;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::{self, pubkey_eq};
+use pinocchio::ProgramResult;
+
+use crate::args::RentCoPayer;
+use crate::error::DlpError;
+use crate::log::log;
+use crate::processor::fast::utils::requires::require_signer;
+use crate::state::DelegationMetadata;
+
+/// Split the rent co-payer of the delegate args off the end of the accounts, if any,
+/// checking that both payers pay a share of the rent and that the co-payer signs
+pub(crate) fn split_rent_co_payer<'a>(
+    accounts: &'a [AccountInfo],
+    rent_co_payer: Option<&RentCoPayer>,
+) -> Result<(&'a [AccountInfo], Option<&'a AccountInfo>), ProgramError> {
+    let Some(rent_co_payer) = rent_co_payer else {
+        return Ok((accounts, None));
+    };
+    if !rent_co_payer.is_valid() {
+        log!(
+            "rent co-payer share of {} basis points is out of range",
+            rent_co_payer.share_bps
+        );
+        return Err(DlpError::InvalidRentCoPayer.into());
+    }
+    let Some((rent_co_payer_account, accounts)) = accounts.split_last() else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !pubkey_eq(rent_co_payer_account.key(), rent_co_payer.payer.as_array()) {
+        log!("Expected rent co-payer to be : ");
+        pubkey::log(rent_co_payer.payer.as_array());
+        log!("but got : ");
+        pubkey::log(rent_co_payer_account.key());
+        return Err(DlpError::InvalidRentCoPayer.into());
+    }
+    require_signer(rent_co_payer_account, "rent co-payer")?;
+    Ok((accounts, Some(rent_co_payer_account)))
+}
+
+/// Split the rent co-payer recorded in the delegation metadata off the end of the accounts,
+/// if passed, along with the lamports it paid at delegation, see
+/// [DelegationMetadata::rent_co_payer_lamports].
+///
+/// The co-payer is recognized by its key, the delegation metadata being read at its index in
+/// the accounts before it is checked, see [require_rent_co_payer].
+pub(crate) fn split_recorded_rent_co_payer(
+    accounts: &[AccountInfo],
+    delegation_metadata_index: usize,
+) -> (&[AccountInfo], Option<(&AccountInfo, u64)>) {
+    let recorded_rent_co_payer = accounts
+        .get(delegation_metadata_index)
+        .filter(|info| pubkey_eq(info.owner(), &crate::fast::ID))
+        .and_then(|info| {
+            let data = info.try_borrow_data().ok()?;
+            let delegation_metadata =
+                DelegationMetadata::try_from_bytes_with_discriminator(&data).ok()?;
+            Some((
+                delegation_metadata.rent_co_payer?,
+                delegation_metadata.rent_co_payer_lamports,
+            ))
+        });
+    match (recorded_rent_co_payer, accounts.split_last()) {
+        (Some((rent_co_payer, lamports)), Some((rent_co_payer_account, accounts)))
+            if pubkey_eq(rent_co_payer_account.key(), rent_co_payer.payer.as_array()) =>
+        {
+            (accounts, Some((rent_co_payer_account, lamports)))
+        }
+        _ => (accounts, None),
+    }
+}
+
+/// Errors if the delegation has a rent co-payer which is not passed, as it must be refunded
+/// the rent it paid for the delegation PDAs
+pub(crate) fn require_rent_co_payer(
+    delegation_metadata: &DelegationMetadata,
+    rent_co_payer: Option<(&AccountInfo, u64)>,
+) -> ProgramResult {
+    match delegation_metadata.rent_co_payer {
+        Some(recorded_rent_co_payer) if rent_co_payer.is_none() => {
+            log!("Expected rent co-payer to be passed last : ");
+            pubkey::log(recorded_rent_co_payer.payer.as_array());
+            Err(DlpError::InvalidRentCoPayer.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The lamports refunded to the rent co-payer out of the lamports it paid at delegation, net
+/// of its share of the rent fees charged at the percentage, which is rounded up
+pub(crate) fn rent_co_payer_refund(paid_lamports: u64, fee_percentage: u8) -> u64 {
+    let fee = (paid_lamports as u128 * fee_percentage as u128).div_ceil(100) as u64;
+    paid_lamports.saturating_sub(fee)
+}
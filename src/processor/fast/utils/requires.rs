@@ -1,10 +1,18 @@
+use crate::log::log;
 use pinocchio::account_info::AccountInfo;
 use pinocchio::program_error::ProgramError;
 use pinocchio::pubkey::{pubkey_eq, Pubkey};
-use pinocchio_log::log;
 
+use crate::args::{AuthorityScope, Seeds, MAX_SEEDS};
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
 use crate::error::DlpError;
 use crate::pda::{self, program_config_from_program_id, validator_fees_vault_pda_from_validator};
+use crate::processor::fast::to_pinocchio_program_error;
+use crate::processor::fast::utils::authority_chain::require_authority_chain;
+use crate::state::{
+    fees_vault_generation, FeatureGates, ValidatorFeesVault, RETIRED_FEES_VAULT_GENERATION,
+};
 
 #[cfg(not(feature = "log-cost"))]
 use pinocchio::pubkey;
@@ -56,6 +64,19 @@ pub fn require_signer(info: &AccountInfo, label: &str) -> Result<(), ProgramErro
     Ok(())
 }
 
+/// Errors if:
+/// - Account is not writable.
+#[inline(always)]
+pub fn require_writable(info: &AccountInfo, label: &str) -> Result<(), ProgramError> {
+    if !info.is_writable() {
+        log!("Account needs to be writable. Label: {}", label);
+        pubkey::log(info.key());
+        return Err(ProgramError::Immutable);
+    }
+
+    Ok(())
+}
+
 /// Errors if:
 /// - Address does not match PDA derived from provided seeds.
 #[inline(always)]
@@ -83,6 +104,40 @@ pub fn require_pda(
     Ok(pda.1)
 }
 
+/// Errors if:
+/// - Delegated account is not derived from the seeds and the owner program, or from the
+///   delegation program if the owner is the system program, as for the escrow accounts.
+#[inline(always)]
+pub fn require_delegated_account_seeds(
+    delegated_account: &AccountInfo,
+    owner_program: &AccountInfo,
+    seeds: &Seeds,
+) -> Result<(), ProgramError> {
+    let program_id = if pubkey_eq(owner_program.key(), &pinocchio_system::ID) {
+        &crate::fast::ID
+    } else {
+        owner_program.key()
+    };
+    let mut seeds_to_validate: [&[u8]; MAX_SEEDS] = [&[]; MAX_SEEDS];
+    for (slot, seed) in seeds_to_validate.iter_mut().zip(seeds.iter()) {
+        *slot = seed;
+    }
+    let seeds_to_validate = seeds_to_validate
+        .get(..seeds.len())
+        .ok_or(ProgramError::InvalidSeeds)?;
+    let derived_pda = pubkey::find_program_address(seeds_to_validate, program_id).0;
+
+    if !pubkey_eq(&derived_pda, delegated_account.key()) {
+        log!("Expected delegated PDA to be: ");
+        pubkey::log(&derived_pda);
+        log!("but got: ");
+        pubkey::log(delegated_account.key());
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    Ok(())
+}
+
 /// Returns true if the account is uninitialized based on the following conditions:
 /// - Owner is the system program.
 /// - Data is empty.
@@ -205,18 +260,39 @@ pub fn require_program(info: &AccountInfo, key: &Pubkey, label: &str) -> Result<
 }
 
 /// Load fee vault PDA
-/// - Protocol fees vault PDA
+/// - Protocol fees vault PDA must be derived from the generation stored in its data
+/// - Protocol fees vault must not be retired by a migration
 pub fn require_initialized_protocol_fees_vault(
     fees_vault: &AccountInfo,
     is_writable: bool,
 ) -> Result<(), ProgramError> {
-    require_initialized_pda(
-        fees_vault,
-        &[b"fees-vault"],
-        &crate::fast::ID,
-        is_writable,
-        "protocol fees vault",
-    )?;
+    // A vault not owned by the program is rejected as the vault of generation 0
+    let generation = if pubkey_eq(fees_vault.owner(), &crate::fast::ID) {
+        fees_vault_generation(&fees_vault.try_borrow_data()?)
+    } else {
+        0
+    };
+    if generation == RETIRED_FEES_VAULT_GENERATION {
+        log!("Protocol fees vault was retired: ");
+        pubkey::log(fees_vault.key());
+        return Err(DlpError::RetiredProtocolFeesVault.into());
+    }
+    match generation {
+        0 => require_initialized_pda(
+            fees_vault,
+            &[pda::FEES_VAULT_TAG],
+            &crate::fast::ID,
+            is_writable,
+            "protocol fees vault",
+        )?,
+        generation => require_initialized_pda(
+            fees_vault,
+            &[pda::FEES_VAULT_TAG, &generation.to_le_bytes()],
+            &crate::fast::ID,
+            is_writable,
+            "protocol fees vault",
+        )?,
+    };
     Ok(())
 }
 
@@ -228,7 +304,17 @@ pub fn require_initialized_validator_fees_vault(
     validator_fees_vault: &AccountInfo,
     is_writable: bool,
 ) -> Result<(), ProgramError> {
-    let pda = validator_fees_vault_pda_from_validator(&(*validator.key()).into());
+    require_initialized_validator_fees_vault_of(validator.key(), validator_fees_vault, is_writable)
+}
+
+/// Load the validator fee vault PDA of a validator identity, which may not be an account
+/// of the instruction
+pub fn require_initialized_validator_fees_vault_of(
+    validator: &Pubkey,
+    validator_fees_vault: &AccountInfo,
+    is_writable: bool,
+) -> Result<(), ProgramError> {
+    let pda = validator_fees_vault_pda_from_validator(&(*validator).into());
     if !pubkey_eq(validator_fees_vault.key(), pda.as_array()) {
         log!("Invalid validator fees vault PDA, expected: ");
         pubkey::log(pda.as_array());
@@ -238,7 +324,7 @@ pub fn require_initialized_validator_fees_vault(
     }
     require_initialized_pda(
         validator_fees_vault,
-        &[pda::VALIDATOR_FEES_VAULT_TAG, validator.key()],
+        &[pda::VALIDATOR_FEES_VAULT_TAG, validator],
         &crate::fast::ID,
         is_writable,
         "validator fees vault",
@@ -246,6 +332,40 @@ pub fn require_initialized_validator_fees_vault(
     Ok(())
 }
 
+/// Check that the signer of a commit, whose signature is checked by the caller, can commit on behalf of the validator identity
+/// - Validator fees vault PDA of the validator identity must be initialized
+/// - Signer must be the validator identity, one of its approved commit relayers, see
+///   [crate::state::ValidatorFeesVault], or, if the links of an authority delegation chain
+///   are passed, a sub-key granted the scope by the chain at the slot, see
+///   [crate::state::AuthorityDelegationChain]
+pub fn require_commit_authority(
+    signer: &AccountInfo,
+    validator: &Pubkey,
+    validator_fees_vault: &AccountInfo,
+    authority_chain: &[AccountInfo],
+    scope: AuthorityScope,
+    slot: u64,
+) -> Result<(), ProgramError> {
+    require_initialized_validator_fees_vault_of(validator, validator_fees_vault, false)?;
+    if pubkey_eq(signer.key(), validator) {
+        return Ok(());
+    }
+    if !authority_chain.is_empty() {
+        return require_authority_chain(authority_chain, validator, signer.key(), scope, slot);
+    }
+    let validator_fees_vault_data = validator_fees_vault.try_borrow_data()?;
+    let vault = ValidatorFeesVault::try_from_account_data(&validator_fees_vault_data)
+        .map_err(to_pinocchio_program_error)?;
+    if !vault.is_approved_relayer(&(*signer.key()).into()) {
+        log!("signer is neither the delegation authority nor one of its commit relayers: ");
+        pubkey::log(signer.key());
+        log!("delegation authority: ");
+        pubkey::log(validator);
+        return Err(DlpError::InvalidAuthority.into());
+    }
+    Ok(())
+}
+
 /// Load program config PDA
 /// - Program config PDA must be initialized with the expected seeds and owner, or not exists
 pub fn require_program_config(
@@ -271,6 +391,38 @@ pub fn require_program_config(
     Ok(!pubkey_eq(program_config.owner(), &pinocchio_system::ID))
 }
 
+/// Load protocol config PDA
+/// - Protocol config PDA must be derived from the expected seeds, it may not exist
+pub fn require_protocol_config(
+    protocol_config: &AccountInfo,
+    is_writable: bool,
+) -> Result<bool, ProgramError> {
+    require_pda(
+        protocol_config,
+        &[pda::PROTOCOL_CONFIG_TAG],
+        &crate::fast::ID,
+        is_writable,
+        "protocol config",
+    )?;
+    Ok(pubkey_eq(protocol_config.owner(), &crate::fast::ID))
+}
+
+/// Load the fee exemption of a delegated account, returning whether it is initialized
+/// - Fee exemption must be derived from the delegated account
+pub fn require_fee_exemption(
+    delegated_account: &AccountInfo,
+    fee_exemption: &AccountInfo,
+) -> Result<bool, ProgramError> {
+    require_pda(
+        fee_exemption,
+        &[pda::FEE_EXEMPTION_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        false,
+        "fee exemption",
+    )?;
+    Ok(pubkey_eq(fee_exemption.owner(), &crate::fast::ID))
+}
+
 /// Load initialized delegation record
 /// - Delegation record must be derived from the delegated account
 pub fn require_initialized_delegation_record(
@@ -305,6 +457,40 @@ pub fn require_initialized_delegation_metadata(
     Ok(())
 }
 
+/// Load initialized read lock account
+/// - Read lock account must be derived from the delegated account pubkey
+pub fn require_initialized_read_lock(
+    delegated_account: &AccountInfo,
+    read_lock: &AccountInfo,
+    is_writable: bool,
+) -> Result<(), ProgramError> {
+    require_initialized_pda(
+        read_lock,
+        &[pda::READ_LOCK_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        is_writable,
+        "read lock",
+    )?;
+    Ok(())
+}
+
+/// Load initialized commit schedule
+/// - Commit schedule must be derived from the delegated account
+pub fn require_initialized_commit_schedule(
+    delegated_account: &AccountInfo,
+    commit_schedule: &AccountInfo,
+    is_writable: bool,
+) -> Result<(), ProgramError> {
+    require_initialized_pda(
+        commit_schedule,
+        &[pda::COMMIT_SCHEDULE_TAG, delegated_account.key()],
+        &crate::fast::ID,
+        is_writable,
+        "commit schedule",
+    )?;
+    Ok(())
+}
+
 /// Load initialized commit state account
 /// - Commit state account must be derived from the delegated account pubkey
 pub fn require_initialized_commit_state(
@@ -431,3 +617,65 @@ define_uninitialized_ctx!(
     account_already_initialized = DlpError::UndelegateBufferAlreadyInitialized,
     immutable = DlpError::UndelegateBufferImmutable
 );
+
+define_uninitialized_ctx!(
+    ParkedUndelegationCtx,
+    label = "parked undelegation",
+    invalid_seeds = ProgramError::InvalidSeeds,
+    invalid_account_owner = ProgramError::InvalidAccountOwner,
+    account_already_initialized = ProgramError::AccountAlreadyInitialized,
+    immutable = ProgramError::Immutable
+);
+
+define_uninitialized_ctx!(
+    UndelegateProgressCtx,
+    label = "undelegate progress",
+    invalid_seeds = ProgramError::InvalidSeeds,
+    invalid_account_owner = ProgramError::InvalidAccountOwner,
+    account_already_initialized = ProgramError::AccountAlreadyInitialized,
+    immutable = ProgramError::Immutable
+);
+
+/// Require an instruction sent by validators before it was gated to be enabled in the
+/// feature gates, if they are passed
+/// - The feature gates PDA is optional, the instruction being enabled without it so that the
+///   existing validator clients keep working
+/// - If passed, the feature gates are checked as in [require_enabled_instruction]
+pub fn require_enabled_legacy_instruction(
+    accounts: &[AccountInfo],
+    discriminator: DlpDiscriminator,
+) -> Result<&[AccountInfo], ProgramError> {
+    match accounts.last() {
+        Some(feature_gates) if pubkey_eq(feature_gates.key(), FEATURE_GATES_PDA.as_array()) => {
+            require_enabled_instruction(accounts, discriminator)
+        }
+        _ => Ok(accounts),
+    }
+}
+
+/// Require the instruction to be enabled in the feature gates
+/// - The feature gates PDA must be the last account, it is split off the returned accounts
+/// - If the feature gates PDA is initialized, the discriminator must be enabled
+pub fn require_enabled_instruction(
+    accounts: &[AccountInfo],
+    discriminator: DlpDiscriminator,
+) -> Result<&[AccountInfo], ProgramError> {
+    let Some((feature_gates, accounts)) = accounts.split_last() else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !pubkey_eq(feature_gates.key(), FEATURE_GATES_PDA.as_array()) {
+        log!("Invalid feature gates PDA: ");
+        pubkey::log(feature_gates.key());
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if pubkey_eq(feature_gates.owner(), &crate::fast::ID) {
+        let feature_gates_data = feature_gates.try_borrow_data()?;
+        let feature_gates = FeatureGates::try_from_bytes_with_discriminator(&feature_gates_data)
+            .map_err(to_pinocchio_program_error)?;
+        if !feature_gates.is_enabled(discriminator as u8) {
+            log!("Instruction {} is disabled", discriminator.name());
+            return Err(DlpError::InstructionDisabled.into());
+        }
+    }
+    Ok(accounts)
+}
@@ -0,0 +1,79 @@
+use pinocchio::account_info::AccountInfo;
+use pinocchio::pubkey::{pubkey_eq, Pubkey};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::ProgramResult;
+
+use crate::processor::fast::to_pinocchio_program_error;
+use crate::processor::fast::utils::requires::require_pda;
+use crate::session_report_seeds_from_escrow;
+use crate::state::{AccountDiscriminator, SessionReport};
+
+/// Split the session report of an escrow off the end of the accounts, if passed.
+///
+/// Like the protocol stats, the session report is recognized by its owner and
+/// discriminator, its PDA being checked against the escrow when recording into it.
+pub(crate) fn split_session_report(
+    accounts: &[AccountInfo],
+) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((session_report, accounts)) if is_session_report(session_report) => {
+            (accounts, Some(session_report))
+        }
+        _ => (accounts, None),
+    }
+}
+
+fn is_session_report(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.len() == SessionReport::size_with_discriminator()
+                && data.starts_with(&AccountDiscriminator::SessionReport.to_bytes())
+        })
+}
+
+/// Record a finalized commit of the escrow in its session report, if passed
+pub(crate) fn record_session_commit(
+    session_report: Option<&AccountInfo>,
+    escrow: &AccountInfo,
+    validator: &Pubkey,
+    spent: u64,
+) -> ProgramResult {
+    update_session_report(session_report, escrow, |session_report, slot| {
+        session_report.record_commit((*validator).into(), spent, slot)
+    })
+}
+
+/// Freeze the session report of the escrow at its undelegation, if passed
+pub(crate) fn freeze_session_report(
+    session_report: Option<&AccountInfo>,
+    escrow: &AccountInfo,
+) -> ProgramResult {
+    update_session_report(session_report, escrow, |session_report, slot| {
+        session_report.freeze(slot)
+    })
+}
+
+fn update_session_report(
+    session_report: Option<&AccountInfo>,
+    escrow: &AccountInfo,
+    update: impl FnOnce(&mut SessionReport, u64),
+) -> ProgramResult {
+    let Some(session_report) = session_report else {
+        return Ok(());
+    };
+    require_pda(
+        session_report,
+        session_report_seeds_from_escrow!(escrow.key()),
+        &crate::fast::ID,
+        true,
+        "session report",
+    )?;
+
+    let mut session_report_data = session_report.try_borrow_mut_data()?;
+    update(
+        SessionReport::try_from_bytes_with_discriminator_mut(&mut session_report_data)
+            .map_err(to_pinocchio_program_error)?,
+        Clock::get()?.slot,
+    );
+    Ok(())
+}
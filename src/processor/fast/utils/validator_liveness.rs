@@ -0,0 +1,89 @@
+use crate::log::log;
+use pinocchio::account_info::AccountInfo;
+use pinocchio::pubkey::{self, pubkey_eq};
+use pinocchio::sysvars::{clock::Clock, Sysvar};
+use pinocchio::ProgramResult;
+
+use crate::error::DlpError;
+use crate::pda;
+use crate::processor::fast::to_pinocchio_program_error;
+use crate::processor::fast::utils::requires::require_pda;
+use crate::state::{AccountDiscriminator, DelegationMetadata, DelegationRecord, ValidatorLiveness};
+
+/// Split the validator liveness off the end of the accounts, if passed.
+///
+/// The validator liveness is recognized by its owner and discriminator, its PDA being
+/// checked at [require_undelegatable] since the delegated accounts, also owned by the
+/// delegation program, hold the data committed by their validator.
+pub(crate) fn split_validator_liveness(
+    accounts: &[AccountInfo],
+) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((validator_liveness, accounts)) if is_validator_liveness(validator_liveness) => {
+            (accounts, Some(validator_liveness))
+        }
+        _ => (accounts, None),
+    }
+}
+
+fn is_validator_liveness(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.len() == ValidatorLiveness::size_with_discriminator()
+                && data.starts_with(&AccountDiscriminator::ValidatorLiveness.to_bytes())
+        })
+}
+
+/// Require the delegated account to be undelegatable: marked so by its delegation metadata
+/// or, if the delegation opted into a heartbeat timeout, its validator not having
+/// heartbeated for the timeout since the delegation, as recorded by its validator liveness.
+///
+/// A validator which never heartbeated has no validator liveness, its delegations can then
+/// only be undelegated once marked undelegatable.
+pub(crate) fn require_undelegatable(
+    delegation_metadata_account: &AccountInfo,
+    delegation_metadata: &DelegationMetadata,
+    delegation_record: &DelegationRecord,
+    validator_liveness: Option<&AccountInfo>,
+) -> ProgramResult {
+    if delegation_metadata.is_undelegatable {
+        return Ok(());
+    }
+    let (Some(heartbeat_timeout_slots), Some(validator_liveness)) = (
+        delegation_metadata.heartbeat_timeout_slots,
+        validator_liveness,
+    ) else {
+        log!("delegation metadata indicates the account is not undelegatable : ");
+        pubkey::log(delegation_metadata_account.key());
+        return Err(DlpError::NotUndelegatable.into());
+    };
+
+    require_pda(
+        validator_liveness,
+        &[
+            pda::VALIDATOR_LIVENESS_TAG,
+            delegation_record.authority.as_array(),
+        ],
+        &crate::fast::ID,
+        false,
+        "validator liveness",
+    )?;
+    let liveness = *ValidatorLiveness::try_from_bytes_with_discriminator(
+        &validator_liveness.try_borrow_data()?,
+    )
+    .map_err(to_pinocchio_program_error)?;
+
+    if !liveness.is_stale(
+        delegation_record.delegation_slot,
+        heartbeat_timeout_slots,
+        Clock::get()?.slot,
+    ) {
+        log!(
+            "validator heartbeated at slot {}, within the timeout of {} slots",
+            liveness.last_heartbeat_slot,
+            heartbeat_timeout_slots
+        );
+        return Err(DlpError::NotUndelegatable.into());
+    }
+    Ok(())
+}
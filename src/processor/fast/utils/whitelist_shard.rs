@@ -0,0 +1,43 @@
+use pinocchio::account_info::AccountInfo;
+use pinocchio::pubkey::{pubkey_eq, Pubkey};
+
+use crate::pda::validator_whitelist_shard_pda_from_program_id;
+use crate::state::AccountDiscriminator;
+
+/// Split the validator whitelist shard off the end of the accounts, if passed.
+///
+/// A shard is recognized by its owner and discriminator, its PDA being checked at
+/// [is_whitelisted_by_shard] since other accounts of the delegation program, e.g. the
+/// commit states, hold arbitrary data.
+pub(crate) fn split_whitelist_shard(
+    accounts: &[AccountInfo],
+) -> (&[AccountInfo], Option<&AccountInfo>) {
+    match accounts.split_last() {
+        Some((whitelist_shard, accounts)) if is_whitelist_shard(whitelist_shard) => {
+            (accounts, Some(whitelist_shard))
+        }
+        _ => (accounts, None),
+    }
+}
+
+fn is_whitelist_shard(info: &AccountInfo) -> bool {
+    pubkey_eq(info.owner(), &crate::fast::ID)
+        && info.try_borrow_data().is_ok_and(|data| {
+            data.starts_with(&AccountDiscriminator::ValidatorWhitelistShard.to_bytes())
+        })
+}
+
+/// Whether the shard, if passed, whitelists the validator for the program, which costs a
+/// single PDA derivation whatever the size of the whitelist
+pub(crate) fn is_whitelisted_by_shard(
+    whitelist_shard: Option<&AccountInfo>,
+    program: &Pubkey,
+    validator: &Pubkey,
+) -> bool {
+    whitelist_shard.is_some_and(|whitelist_shard| {
+        let pda =
+            validator_whitelist_shard_pda_from_program_id(&(*program).into(), &(*validator).into());
+        pubkey_eq(pda.as_array(), whitelist_shard.key())
+            && pubkey_eq(whitelist_shard.owner(), &crate::fast::ID)
+    })
+}
@@ -0,0 +1,141 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::error::DlpError::{AlreadyUndelegated, CommitTimeoutNotElapsed};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program, load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::resize_pda;
+use crate::state::{DelegationMetadata, DelegationRecord};
+use crate::{
+    commit_record_seeds_from_delegated_account, delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_force_undelegate]
+pub const FORCE_UNDELEGATE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("payer"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Force the undelegation of a delegated account whose validator stopped committing, without
+/// the admin, see [DelegationRecord::commit_timeout_slots]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the payer, funding the growth of the delegation metadata
+/// 1: `[]`                 the delegated account
+/// 2: `[]`                 the delegation record
+/// 3: `[writable]`         the delegation metadata
+/// 4: `[]`                 the commit record PDA
+/// 5: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - delegated account is owned by the delegation program and is NOT undelegatable
+/// - delegation record and delegation metadata are initialized
+/// - delegation has a commit frequency and its validator did not commit for
+///   [crate::consts::COMMIT_TIMEOUT_FREQUENCY_MULTIPLIER] commit frequencies, since the last
+///   finalized commit or, if none, since the delegation
+/// - commit record is uninitialized, a pending commit being left to be finalized, see
+///   [crate::processor::fast::process_crank_finalize]
+///
+/// Steps:
+///
+/// 1. Mark the account undelegatable and its delegation stale, so that anyone can undelegate it
+///    without the validator or its fees vault, see [crate::processor::fast::process_undelegate],
+///    resizing the delegation metadata to record its [crate::state::DelegationLifecycle::Stale]
+///    status
+///
+/// Usage:
+///
+/// Anyone can call this instruction, protecting the users of a validator gone offline
+/// without waiting for the timelock of [crate::processor::process_schedule_force_undelegate].
+pub fn process_force_undelegate(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [payer, delegated_account, delegation_record_account, delegation_metadata_account, commit_record_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+
+    // A pending commit is left to be finalized, it must not be overwritten by the undelegation
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    let mut delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+
+    if delegation_metadata.is_undelegatable {
+        return Err(AlreadyUndelegated.into());
+    }
+
+    let slot = Clock::get()?.slot;
+    if !delegation_record.is_commit_timed_out(delegation_metadata.last_commit_slot, slot) {
+        msg!(
+            "Last commit at slot {}, the commit timeout is {:?} slots",
+            delegation_metadata
+                .last_commit_slot
+                .unwrap_or(delegation_record.delegation_slot),
+            delegation_record.commit_timeout_slots()
+        );
+        return Err(CommitTimeoutNotElapsed.into());
+    }
+
+    delegation_metadata.is_undelegatable = true;
+    delegation_metadata.is_stale = true;
+    resize_pda(
+        payer,
+        delegation_metadata_account,
+        system_program,
+        delegation_metadata.serialized_size(),
+    )?;
+    let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+    delegation_metadata.to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+
+    Ok(())
+}
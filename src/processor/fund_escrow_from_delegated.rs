@@ -0,0 +1,213 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::FundEscrowFromDelegatedArgs;
+use crate::error::DlpError;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_pda, load_program, load_signer,
+    load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::processor::utils::protocol_stats::record_tvl_change;
+use crate::state::{DelegationMetadata, DelegationRecord};
+use crate::{
+    commit_record_seeds_from_delegated_account, commit_state_seeds_from_delegated_account,
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account, ephemeral_balance_seeds_from_payer,
+};
+
+/// Accounts of [process_fund_escrow_from_delegated]
+pub const FUND_ESCROW_FROM_DELEGATED_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("validator"),
+    AccountSpec::writable("delegated account"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("commit state"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::writable("ephemeral balance"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Fund the ephemeral balance of the rent payer of a delegation with the lamports of the
+/// delegated account above its rent exempt minimum, without undelegating it. The delegation
+/// record follows the lamports taken, so that the next commits of the account settle against
+/// its remaining lamports.
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the validator, authority of the delegation, funding the creation
+///                         of the ephemeral balance
+/// 1: `[writable]` the delegated account
+/// 2: `[writable]` the delegation record of the delegated account
+/// 3: `[]`         the delegation metadata of the delegated account
+/// 4: `[]`         the commit state PDA of the delegated account
+/// 5: `[]`         the commit record PDA of the delegated account
+/// 6: `[writable]` the ephemeral balance of the rent payer to fund
+/// 7: `[]`         the system program
+/// 8: `[writable]` (optional) the protocol stats PDA, subtracting the lamports taken from the
+///                 total value locked
+///
+/// Requirements:
+///
+/// - validator is the authority in the delegation record, the lamports taken on chain
+///   being taken in the ephemeral rollup too
+/// - delegated account is not undelegatable
+/// - there is no pending commit for the delegated account, settled against the lamports of
+///   the delegation record
+/// - the delegation record keeps at least the rent exempt minimum of the delegated account
+/// - the ephemeral balance is the one of the rent payer in the delegation metadata, so that
+///   the validator cannot move the lamports to an escrow of its own
+/// - the ephemeral balance is not the delegated account itself
+///
+/// Steps:
+///
+/// 1. Create the ephemeral balance PDA if it does not exist
+/// 2. Move the lamports from the delegated account to the ephemeral balance
+/// 3. Deduct the lamports from the delegation record and from the total value locked
+pub fn process_fund_escrow_from_delegated(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = FundEscrowFromDelegatedArgs::try_from_slice(data)?;
+
+    let [validator, delegated_account, delegation_record_account, delegation_metadata_account, commit_state_account, commit_record_account, ephemeral_balance_account, system_program, remaining_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let protocol_stats = remaining_accounts.first();
+
+    load_signer(validator, "validator")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation metadata",
+    )?;
+
+    // Make sure there is no pending commit, settled against the lamports of the record
+    load_uninitialized_pda(
+        commit_state_account,
+        commit_state_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit state",
+    )?;
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+
+    let delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    if delegation_metadata.is_undelegatable {
+        msg!(
+            "Delegated account {} is undelegatable",
+            delegated_account.key
+        );
+        return Err(DlpError::AlreadyUndelegated.into());
+    }
+
+    // Only the escrow of the rent payer of the delegation can be funded
+    let rent_payer = delegation_metadata.rent_payer;
+    let bump_ephemeral_balance = load_pda(
+        ephemeral_balance_account,
+        ephemeral_balance_seeds_from_payer!(rent_payer, args.index),
+        &crate::id(),
+        true,
+        "ephemeral balance",
+    )?;
+    if ephemeral_balance_account.key.eq(delegated_account.key) {
+        msg!("A delegated ephemeral balance cannot fund itself");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut delegation_record_data = delegation_record_account.try_borrow_mut_data()?;
+    let mut delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?;
+    if !delegation_record.authority.eq(validator.key) {
+        msg!(
+            "Expected delegation authority to be {}, but got {}",
+            delegation_record.authority,
+            validator.key
+        );
+        return Err(DlpError::InvalidAuthority.into());
+    }
+    if delegated_account.lamports() < delegation_record.lamports {
+        return Err(DlpError::InvalidDelegatedState.into());
+    }
+
+    // Only the lamports above the rent exempt minimum of the delegated account can be taken
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(delegated_account.data_len());
+    let excess_lamports = delegation_record
+        .lamports
+        .saturating_sub(rent_exempt_lamports);
+    if args.amount > excess_lamports {
+        msg!(
+            "Delegated account has {} excess lamports, {} requested",
+            excess_lamports,
+            args.amount
+        );
+        return Err(DlpError::NotEnoughExcessLamports.into());
+    }
+
+    // Create the ephemeral balance PDA if it does not exist
+    if ephemeral_balance_account.owner.eq(&system_program::id()) {
+        create_pda(
+            ephemeral_balance_account,
+            &system_program::id(),
+            0,
+            ephemeral_balance_seeds_from_payer!(rent_payer, args.index),
+            bump_ephemeral_balance,
+            system_program,
+            validator,
+        )?;
+    }
+
+    if args.amount > 0 {
+        **delegated_account.try_borrow_mut_lamports()? = delegated_account
+            .lamports()
+            .checked_sub(args.amount)
+            .ok_or(DlpError::Overflow)?;
+        **ephemeral_balance_account.try_borrow_mut_lamports()? = ephemeral_balance_account
+            .lamports()
+            .checked_add(args.amount)
+            .ok_or(DlpError::Overflow)?;
+    }
+
+    let previous_lamports = delegation_record.lamports;
+    delegation_record.lamports = previous_lamports
+        .checked_sub(args.amount)
+        .ok_or(DlpError::Overflow)?;
+    delegation_record.to_bytes_with_discriminator(&mut delegation_record_data)?;
+    record_tvl_change(
+        protocol_stats,
+        previous_lamports,
+        delegation_record.lamports,
+    )?;
+
+    Ok(())
+}
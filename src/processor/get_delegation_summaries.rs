@@ -0,0 +1,89 @@
+use alloc::vec::Vec;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    program_config_from_program_id,
+};
+use crate::processor::utils::summaries::{read_summary_pda, set_summaries_return_data};
+use crate::state::{DelegationRecord, DelegationSummary};
+
+/// Accounts of [process_get_delegation_summaries]
+pub const GET_DELEGATION_SUMMARIES_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::readonly("account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("program config"),
+];
+
+/// Return the summaries of the delegations of accounts, so that wallets can display them by
+/// simulating the instruction, without decoding the PDAs of the delegation program
+///
+/// Accounts, repeated for each account, up to 7 accounts fitting in the return data:
+///
+/// 0: `[]` the account, delegated or not
+/// 1: `[]` the delegation record of the account, initialized or not
+/// 2: `[]` the delegation metadata of the account, initialized or not
+/// 3: `[]` the program config PDA of the owner program of the account, initialized or not,
+///         unchecked if the account is not delegated
+///
+/// Requirements:
+///
+/// - delegation record and metadata are derived from the account
+/// - program config PDA is derived from the owner program in the delegation record, if any
+///
+/// Steps:
+///
+/// 1. Set the return data to the [DelegationSummary] of each account, see
+///    [DelegationSummary::from_delegation_data]
+pub fn process_get_delegation_summaries(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let groups = accounts.chunks_exact(4);
+    if accounts.is_empty() || !groups.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut summaries = Vec::with_capacity(groups.len());
+    for group in groups {
+        let [delegated_account, delegation_record_account, delegation_metadata_account, program_config_account] =
+            group
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let delegation_record_data = read_summary_pda(
+            delegation_record_account,
+            &delegation_record_pda_from_delegated_account(delegated_account.key),
+        )?;
+        let delegation_metadata_data = read_summary_pda(
+            delegation_metadata_account,
+            &delegation_metadata_pda_from_delegated_account(delegated_account.key),
+        )?;
+
+        // The program config is derived from the owner program in the delegation record
+        let program_config_data = match delegation_record_data.as_deref() {
+            Some(delegation_record_data) => {
+                let delegation_record =
+                    DelegationRecord::try_from_bytes_with_discriminator(delegation_record_data)?;
+                read_summary_pda(
+                    program_config_account,
+                    &program_config_from_program_id(&delegation_record.owner),
+                )?
+            }
+            None => None,
+        };
+
+        summaries.push(DelegationSummary::from_delegation_data(
+            delegated_account.key,
+            delegation_record_data.as_deref().map(|data| &**data),
+            delegation_metadata_data.as_deref().map(|data| &**data),
+            program_config_data.as_deref().map(|data| &**data),
+        )?);
+    }
+    set_summaries_return_data(&summaries)
+}
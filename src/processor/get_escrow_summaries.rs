@@ -0,0 +1,85 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use alloc::vec::Vec;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::args::GetEscrowSummariesArgs;
+use crate::pda::{delegation_record_pda_from_delegated_account, ephemeral_balance_pda_from_payer};
+use crate::processor::utils::summaries::{read_summary_pda, set_summaries_return_data};
+use crate::state::EscrowSummary;
+
+/// Accounts of [process_get_escrow_summaries]
+pub const GET_ESCROW_SUMMARIES_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::readonly("payer"),
+    AccountSpec::readonly("escrow"),
+    AccountSpec::readonly("delegation record"),
+];
+
+/// Return the summaries of escrows, see [crate::pda::ephemeral_balance_pda_from_payer], so
+/// that wallets can display them by simulating the instruction
+///
+/// Accounts, repeated for each escrow, up to 13 escrows fitting in the return data:
+///
+/// 0: `[]` the payer of the escrow
+/// 1: `[]` the escrow, initialized or not
+/// 2: `[]` the delegation record of the escrow, initialized or not
+///
+/// Requirements:
+///
+/// - an index is provided for each escrow
+/// - escrow is derived from the payer and its index
+/// - delegation record is derived from the escrow
+///
+/// Steps:
+///
+/// 1. Set the return data to the [EscrowSummary] of each escrow, see
+///    [EscrowSummary::from_escrow_data]
+pub fn process_get_escrow_summaries(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = GetEscrowSummariesArgs::try_from_slice(data)?;
+
+    let groups = accounts.chunks_exact(3);
+    if accounts.is_empty() || !groups.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    if groups.len() != args.indexes.len() {
+        msg!(
+            "{} indexes provided for {} escrows",
+            args.indexes.len(),
+            groups.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut summaries = Vec::with_capacity(groups.len());
+    for (group, index) in groups.zip(args.indexes) {
+        let [payer, escrow_account, delegation_record_account] = group else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !escrow_account
+            .key
+            .eq(&ephemeral_balance_pda_from_payer(payer.key, index))
+        {
+            msg!("Invalid seeds for account: {}", escrow_account.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let delegation_record_data = read_summary_pda(
+            delegation_record_account,
+            &delegation_record_pda_from_delegated_account(escrow_account.key),
+        )?;
+
+        summaries.push(EscrowSummary::from_escrow_data(
+            payer.key,
+            index,
+            escrow_account.lamports(),
+            delegation_record_data.as_deref().map(|data| &**data),
+        )?);
+    }
+    set_summaries_return_data(&summaries)
+}
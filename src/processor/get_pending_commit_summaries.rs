@@ -0,0 +1,68 @@
+use alloc::vec::Vec;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+};
+use crate::processor::utils::summaries::{read_summary_pda, set_summaries_return_data};
+use crate::state::PendingCommitSummary;
+
+/// Accounts of [process_get_pending_commit_summaries]
+pub const GET_PENDING_COMMIT_SUMMARIES_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("commit state"),
+    AccountSpec::readonly("commit record"),
+];
+
+/// Return the summaries of the commits of delegated accounts pending finalization, so that
+/// wallets can display them by simulating the instruction
+///
+/// Accounts, repeated for each account, up to 7 accounts fitting in the return data:
+///
+/// 0: `[]` the delegated account
+/// 1: `[]` the commit state PDA of the account, initialized or not
+/// 2: `[]` the commit record PDA of the account, initialized or not
+///
+/// Requirements:
+///
+/// - commit state and record are derived from the account
+///
+/// Steps:
+///
+/// 1. Set the return data to the [PendingCommitSummary] of each account, see
+///    [PendingCommitSummary::from_commit_data]
+pub fn process_get_pending_commit_summaries(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let groups = accounts.chunks_exact(3);
+    if accounts.is_empty() || !groups.remainder().is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut summaries = Vec::with_capacity(groups.len());
+    for group in groups {
+        let [delegated_account, commit_state_account, commit_record_account] = group else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let commit_state_data = read_summary_pda(
+            commit_state_account,
+            &commit_state_pda_from_delegated_account(delegated_account.key),
+        )?;
+        let commit_record_data = read_summary_pda(
+            commit_record_account,
+            &commit_record_pda_from_delegated_account(delegated_account.key),
+        )?;
+
+        summaries.push(PendingCommitSummary::from_commit_data(
+            delegated_account.key,
+            commit_record_data.as_deref().map(|data| &**data),
+            commit_state_data.as_deref().map(|data| &**data),
+        )?);
+    }
+    set_summaries_return_data(&summaries)
+}
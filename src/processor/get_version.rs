@@ -0,0 +1,48 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::processor::utils::loaders::load_initialized_pda;
+use crate::program_version_seeds;
+use crate::state::ProgramVersion;
+use solana_program::program::set_return_data;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+/// Accounts of [process_get_version]
+pub const GET_VERSION_ACCOUNTS_SPEC: AccountsSpec = &[AccountSpec::readonly("program version")];
+
+/// Return the version of the deployed delegation program, so that clients can simulate it
+/// and programs can check it via CPI
+///
+/// Accounts:
+///
+/// 0: `[]` program version PDA
+///
+/// Requirements:
+///
+/// - program version PDA is initialized
+///
+/// Steps:
+///
+/// 1. Set the return data to the [ProgramVersion], without its discriminator
+pub fn process_get_version(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [program_version_account] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_initialized_pda(
+        program_version_account,
+        program_version_seeds!(),
+        &crate::id(),
+        false,
+        "program version",
+    )?;
+
+    let program_version_data = program_version_account.try_borrow_data()?;
+    let program_version = ProgramVersion::try_from_bytes_with_discriminator(&program_version_data)?;
+    set_return_data(&borsh::to_vec(&program_version)?);
+
+    Ok(())
+}
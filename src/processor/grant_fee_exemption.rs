@@ -0,0 +1,103 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::GrantFeeExemptionArgs;
+use crate::error::DlpError::Unauthorized;
+use crate::fee_exemption_seeds_from_delegated_account;
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_pda, load_program, load_program_upgrade_authority, load_signer,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::FeeExemption;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_grant_fee_exemption]
+pub const GRANT_FEE_EXEMPTION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("fee exemption"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Exempt a delegated account from the rent fees taken at its undelegation
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account that can grant fee exemptions
+/// 1: `[]`                 the delegated account
+/// 2: `[writable]`         fee exemption PDA of the delegated account
+/// 3: `[]`                 delegation program data
+/// 4: `[]`                 system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - fee exemption PDA is initialized or owned by the system program in
+///   which case it is created
+///
+/// Steps:
+///
+/// 1. Load the fee exemption or create it
+/// 2. Set its expiry slot, zero never expiring
+pub fn process_grant_fee_exemption(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = GrantFeeExemptionArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [admin, delegated_account, fee_exemption_account, delegation_program_data, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    let fee_exemption_bump = load_pda(
+        fee_exemption_account,
+        fee_exemption_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "fee exemption",
+    )?;
+
+    // Create the fee exemption if it doesn't exist
+    if fee_exemption_account.owner.eq(system_program.key) {
+        create_pda(
+            fee_exemption_account,
+            &crate::id(),
+            FeeExemption::size_with_discriminator(),
+            fee_exemption_seeds_from_delegated_account!(delegated_account.key),
+            fee_exemption_bump,
+            system_program,
+            admin,
+        )?;
+    }
+
+    let fee_exemption = FeeExemption {
+        expiry_slot: args.expiry_slot,
+    };
+    let mut fee_exemption_data = fee_exemption_account.try_borrow_mut_data()?;
+    fee_exemption.to_bytes_with_discriminator(&mut fee_exemption_data)?;
+
+    Ok(())
+}
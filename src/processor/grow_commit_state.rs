@@ -0,0 +1,175 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::GrowCommitStateArgs;
+use crate::error::DlpError::{InvalidStreamedCommitState, Overflow};
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_commit_authority, load_initialized_pda, load_owned_pda, load_pda, load_program,
+    load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::{create_pda, resize_pda};
+use crate::state::{DelegationRecord, StreamedCommitState};
+use crate::{
+    commit_record_seeds_from_delegated_account, commit_state_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_grow_commit_state]
+pub const GROW_COMMIT_STATE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("commit state"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Append a chunk of data to a streamed commit state, creating it on the first chunk
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the validator or one of its commit relayers, paying for the growth of
+///                 the commit state
+/// 1: `[]`         the delegated account
+/// 2: `[writable]` the commit state PDA
+/// 3: `[]`         the commit record PDA
+/// 4: `[]`         the delegation record
+/// 5: `[]`         the system program
+/// 6: `[]`         (optional) the validator fees vault of the delegation authority,
+///                 required if a commit relayer signs, see
+///                 [crate::processor::process_register_commit_relayer]
+///
+/// Requirements:
+///
+/// - delegated account is owned by delegation program
+/// - delegation record is initialized
+/// - validator is the delegation authority or one of its approved commit relayers
+/// - commit record is uninitialized
+/// - commit state is uninitialized or a streamed commit state
+///
+/// Steps:
+///
+/// 1. Create the commit state with an empty streamed commit state header recording the
+///    validator as its payer, if needed
+/// 2. Grow the commit state by the length of the chunk
+/// 3. Append the chunk and update the streamed length and the last update slot in the header
+///
+/// Usage:
+///
+/// Once all the chunks are appended, the commit state is passed as the state buffer of
+/// [crate::processor::fast::process_commit_state_from_buffer]. Finalize then copies the
+/// data following the header to the delegated account. An abandoned stream is closed with
+/// [crate::processor::process_close_streamed_commit_state].
+///
+/// NOTE: the account can only grow by 10KiB per instruction, which bounds the length of
+///       a chunk.
+pub fn process_grow_commit_state(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = GrowCommitStateArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [validator, delegated_account, commit_state_account, commit_record_account, delegation_record_account, system_program, relayer_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+
+    // The state can only be streamed while no commit is pending finalization
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    load_commit_authority(
+        validator,
+        &delegation_record.authority,
+        relayer_accounts.first(),
+    )?;
+
+    let commit_state_bump = load_pda(
+        commit_state_account,
+        commit_state_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "commit state",
+    )?;
+    let header_size = StreamedCommitState::size_with_discriminator();
+    let slot = Clock::get()?.slot;
+    if commit_state_account.owner.eq(&system_program::id()) {
+        create_pda(
+            commit_state_account,
+            &crate::id(),
+            header_size,
+            commit_state_seeds_from_delegated_account!(delegated_account.key),
+            commit_state_bump,
+            system_program,
+            validator,
+        )?;
+        let mut commit_state_data = commit_state_account.try_borrow_mut_data()?;
+        StreamedCommitState::new(*validator.key, slot)
+            .to_bytes_with_discriminator(&mut commit_state_data)?;
+    }
+
+    load_owned_pda(commit_state_account, &crate::id(), "commit state")?;
+    let mut streamed_commit_state = {
+        let commit_state_data = commit_state_account.try_borrow_data()?;
+        if StreamedCommitState::streamed_data(&commit_state_data).is_none() {
+            msg!("Commit state {} is not streamed", commit_state_account.key);
+            return Err(InvalidStreamedCommitState.into());
+        }
+        *StreamedCommitState::try_from_bytes_with_discriminator(
+            commit_state_data
+                .get(..header_size)
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )?
+    };
+
+    let start = streamed_commit_state.account_size();
+    let end = start.checked_add(args.data.len()).ok_or(Overflow)?;
+    resize_pda(validator, commit_state_account, system_program, end)?;
+
+    let mut commit_state_data = commit_state_account.try_borrow_mut_data()?;
+    commit_state_data
+        .get_mut(start..end)
+        .ok_or(ProgramError::InvalidAccountData)?
+        .copy_from_slice(&args.data);
+    streamed_commit_state.data_len = streamed_commit_state
+        .data_len
+        .checked_add(args.data.len() as u64)
+        .ok_or(Overflow)?;
+    streamed_commit_state.last_update_slot = slot;
+    streamed_commit_state.to_bytes_with_discriminator(
+        commit_state_data
+            .get_mut(..header_size)
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )?;
+
+    Ok(())
+}
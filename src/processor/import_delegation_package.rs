@@ -0,0 +1,232 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::{to_vec, BorshDeserialize};
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::ImportDelegationPackageArgs;
+use crate::error::DlpError::{InvalidDelegationPackage, Unauthorized};
+use crate::processor::utils::ed25519::ed25519_verified_message;
+use crate::processor::utils::loaders::load_pda;
+use crate::processor::utils::loaders::{
+    load_owned_pda, load_program, load_program_upgrade_authority, load_signer,
+    load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::processor::utils::protocol_stats::record_tvl_change;
+use crate::state::{DelegationMetadata, DelegationPackage, DelegationRecord, ProtocolConfig};
+use crate::{
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account, protocol_config_seeds,
+};
+
+/// Accounts of [process_import_delegation_package]
+pub const IMPORT_DELEGATION_PACKAGE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("instructions sysvar"),
+    AccountSpec::readonly("system program"),
+    AccountSpec::readonly("protocol config"),
+];
+
+/// Recreate a delegation from a [DelegationPackage] exported by
+/// [crate::processor::process_export_delegation_package], on another cluster or under
+/// another program id
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account paying for the delegation PDAs
+/// 1: `[]`                 delegation program data
+/// 2: `[]`                 the delegated account, already migrated
+/// 3: `[writable]`         the delegation record
+/// 4: `[writable]`         the delegation metadata
+/// 5: `[]`                 the instructions sysvar
+/// 6: `[]`                 system program
+/// 7: `[]`                 protocol config PDA, possibly uninitialized
+/// 8: `[writable]`         (optional) the protocol stats PDA, adding the lamports of the
+///                         imported delegation to the total value locked
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - the exporter of the package is the trusted exporter of the protocol config, or the
+///   admin if none is configured, see [ProtocolConfig::trusted_exporter]
+/// - the instruction preceding the import verifies the signature of the package by its
+///   exporter with the Ed25519 program
+/// - package is for the delegated account, and its delegation record and metadata are valid
+/// - delegated account is owned by the delegation program, its data hash matches the
+///   package and it holds the lamports recorded by the delegation record of the package
+/// - delegation record and metadata are uninitialized
+///
+/// Steps:
+///
+/// 1. Create the delegation record and metadata with the data of the package
+/// 2. Add the lamports of the delegation to the total value locked, if the protocol stats
+///    are passed
+pub fn process_import_delegation_package(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = ImportDelegationPackageArgs::try_from_slice(data)?;
+    let package = args.package;
+
+    let [admin, delegation_program_data, delegated_account, delegation_record_account, delegation_metadata_account, instructions_sysvar, system_program, protocol_config_account, remaining_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let protocol_stats = remaining_accounts.first();
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    // Check the package is exported by the trusted exporter, the package declaring its
+    // exporter itself
+    load_pda(
+        protocol_config_account,
+        protocol_config_seeds!(),
+        &crate::id(),
+        false,
+        "protocol config",
+    )?;
+    let trusted_exporter = if protocol_config_account.owner.eq(&crate::id()) {
+        let protocol_config_data = protocol_config_account.try_borrow_data()?;
+        ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_data)?
+            .trusted_exporter(&admin_pubkey)
+    } else {
+        admin_pubkey
+    };
+    if !package.exporter.eq(&trusted_exporter) {
+        msg!(
+            "Delegation package is exported by {}, not the trusted exporter {}",
+            package.exporter,
+            trusted_exporter
+        );
+        return Err(InvalidDelegationPackage.into());
+    }
+
+    // Check the package is signed by its exporter, so that the package the admin imports is
+    // the one exported
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    let signature_ix = current_index
+        .checked_sub(1)
+        .map(|index| load_instruction_at_checked(index as usize, instructions_sysvar))
+        .transpose()?;
+    let package_data = to_vec(&package)?;
+    let is_signed = signature_ix.as_ref().and_then(ed25519_verified_message)
+        == Some((package.exporter, package_data.as_slice()));
+    if !is_signed {
+        msg!(
+            "Delegation package is not signed by its exporter {}",
+            package.exporter
+        );
+        return Err(InvalidDelegationPackage.into());
+    }
+
+    if !package.delegated_account.eq(delegated_account.key) {
+        msg!(
+            "Delegation package is for {}, not {}",
+            package.delegated_account,
+            delegated_account.key
+        );
+        return Err(InvalidDelegationPackage.into());
+    }
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    if DelegationPackage::data_hash(&delegated_account.try_borrow_data()?) != package.data_hash {
+        msg!("Delegated account data does not match the delegation package");
+        return Err(InvalidDelegationPackage.into());
+    }
+    let Ok(delegation_record) =
+        DelegationRecord::try_from_bytes_with_discriminator(&package.delegation_record)
+    else {
+        msg!("Delegation package holds an invalid delegation record");
+        return Err(InvalidDelegationPackage.into());
+    };
+    if DelegationMetadata::try_from_bytes_with_discriminator(&package.delegation_metadata).is_err()
+    {
+        msg!("Delegation package holds an invalid delegation metadata");
+        return Err(InvalidDelegationPackage.into());
+    }
+    // The recorded lamports settle the next commits, they must be the ones migrated
+    let delegated_lamports = delegation_record.lamports;
+    if delegated_lamports != delegated_account.lamports() {
+        msg!(
+            "Delegation package records {} lamports, but the delegated account holds {}",
+            delegated_lamports,
+            delegated_account.lamports()
+        );
+        return Err(InvalidDelegationPackage.into());
+    }
+
+    let delegation_record_bump = load_uninitialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation record",
+    )?;
+    let delegation_metadata_bump = load_uninitialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+
+    create_pda(
+        delegation_record_account,
+        &crate::id(),
+        package.delegation_record.len(),
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        delegation_record_bump,
+        system_program,
+        admin,
+    )?;
+    delegation_record_account
+        .try_borrow_mut_data()?
+        .copy_from_slice(&package.delegation_record);
+
+    create_pda(
+        delegation_metadata_account,
+        &crate::id(),
+        package.delegation_metadata.len(),
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        delegation_metadata_bump,
+        system_program,
+        admin,
+    )?;
+    delegation_metadata_account
+        .try_borrow_mut_data()?
+        .copy_from_slice(&package.delegation_metadata);
+
+    record_tvl_change(protocol_stats, 0, delegated_lamports)?;
+
+    msg!(
+        "Delegation of {} imported from {}",
+        delegated_account.key,
+        package.source_program_id
+    );
+
+    Ok(())
+}
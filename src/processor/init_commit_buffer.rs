@@ -0,0 +1,100 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::InitCommitBufferArgs;
+use crate::commit_buffer_seeds_from_delegated_account_and_authority;
+use crate::processor::utils::loaders::{
+    load_owned_pda, load_program, load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::CommitBuffer;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_init_commit_buffer]
+pub const INIT_COMMIT_BUFFER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("commit buffer"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Initialize a commit buffer, used to commit states too large to fit in one transaction
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the validator, or one of the keys committing on its behalf,
+///                         which becomes the authority of the commit buffer
+/// 1: `[]`                 the delegated account
+/// 2: `[writable]`         the commit buffer PDA of the delegated account and the authority
+/// 3: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - delegated account is owned by delegation program
+/// - the commit buffer is uninitialized
+///
+/// Steps:
+///
+/// 1. Create the commit buffer with an empty data section
+/// 2. Store the authority and the length of the state to stage
+///
+/// Usage:
+///
+/// The state is then written with [crate::processor::process_write_commit_buffer] and the
+/// commit buffer is passed as the state buffer of
+/// [crate::processor::fast::process_commit_state_from_buffer], which commits the state
+/// following its header. The buffer is then closed with
+/// [crate::processor::process_close_commit_buffer].
+pub fn process_init_commit_buffer(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = InitCommitBufferArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, delegated_account, commit_buffer_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let commit_buffer_bump = load_uninitialized_pda(
+        commit_buffer_account,
+        commit_buffer_seeds_from_delegated_account_and_authority!(
+            delegated_account.key,
+            authority.key
+        ),
+        &crate::id(),
+        true,
+        "commit buffer",
+    )?;
+
+    // The data section is allocated as chunks are written, since the account can only
+    // grow by a limited amount in each instruction
+    create_pda(
+        commit_buffer_account,
+        &crate::id(),
+        CommitBuffer::size_with_discriminator(),
+        commit_buffer_seeds_from_delegated_account_and_authority!(
+            delegated_account.key,
+            authority.key
+        ),
+        commit_buffer_bump,
+        system_program,
+        authority,
+    )?;
+
+    let commit_buffer = CommitBuffer {
+        authority: *authority.key,
+        data_len: args.data_len as u64,
+    };
+    let mut commit_buffer_data = commit_buffer_account.try_borrow_mut_data()?;
+    commit_buffer.to_bytes_with_discriminator(&mut commit_buffer_data)?;
+
+    Ok(())
+}
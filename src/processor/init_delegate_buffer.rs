@@ -0,0 +1,90 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::InitDelegateBufferArgs;
+use crate::processor::utils::loaders::{load_program, load_signer, load_uninitialized_pda};
+use crate::processor::utils::pda::create_pda;
+use crate::staged_delegate_buffer_seeds_from_delegated_account;
+use crate::state::StagedDelegateBuffer;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_init_delegate_buffer]
+pub const INIT_DELEGATE_BUFFER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("payer"),
+    AccountSpec::signer("delegated account"),
+    AccountSpec::writable("staged delegate buffer"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Initialize a staged delegate buffer, used to delegate accounts whose data is too large
+/// to be copied into the delegate buffer of their owner program in one transaction
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the payer, which becomes the authority of the staged buffer
+/// 1: `[signer]`   the account to delegate
+/// 2: `[writable]` the staged delegate buffer PDA
+/// 3: `[]`         the system program
+///
+/// Requirements:
+///
+/// - the account to delegate signs, so for a PDA the owner program consents via CPI
+/// - the staged delegate buffer is uninitialized
+///
+/// Steps:
+///
+/// 1. Create the staged delegate buffer with an empty data section
+/// 2. Store the authority and the length of the data to stage
+///
+/// Usage:
+///
+/// The data is then written with [crate::processor::process_write_delegate_buffer_chunk] and the staged
+/// buffer is passed as the last account of [crate::processor::fast::process_delegate],
+/// which copies it instead of the delegate buffer of the owner program.
+pub fn process_init_delegate_buffer(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = InitDelegateBufferArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [payer, delegated_account, staged_buffer_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+    load_signer(delegated_account, "delegated account")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let staged_buffer_bump = load_uninitialized_pda(
+        staged_buffer_account,
+        staged_delegate_buffer_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "staged delegate buffer",
+    )?;
+
+    // The data section is allocated as chunks are written, since the account can only
+    // grow by a limited amount in each instruction
+    create_pda(
+        staged_buffer_account,
+        &crate::id(),
+        StagedDelegateBuffer::size_with_discriminator(),
+        staged_delegate_buffer_seeds_from_delegated_account!(delegated_account.key),
+        staged_buffer_bump,
+        system_program,
+        payer,
+    )?;
+
+    let staged_buffer = StagedDelegateBuffer {
+        authority: *payer.key,
+        data_len: args.data_len as u64,
+    };
+    let mut staged_buffer_data = staged_buffer_account.try_borrow_mut_data()?;
+    staged_buffer.to_bytes_with_discriminator(&mut staged_buffer_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::InitEarningsLedgerPageArgs;
+use crate::earnings_ledger_page_seeds_from_validator;
+use crate::error::DlpError::InvalidEarningsLedgerPage;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_initialized_validator_fees_vault, load_program, load_signer,
+    load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::EarningsLedgerPage;
+
+/// Accounts of [process_init_earnings_ledger_page]
+pub const INIT_EARNINGS_LEDGER_PAGE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("validator identity"),
+    AccountSpec::readonly("validator fees vault"),
+    AccountSpec::writable("earnings ledger page"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Create the next page of the earnings ledger of a validator, see [EarningsLedgerPage]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the validator identity, paying the rent of the page
+/// 1: `[]`                 the validator fees vault
+/// 2: `[writable]`         the earnings ledger page PDA
+/// 3: `[]`                 the system program
+/// 4: `[]`                 the previous page of the ledger, unless the page is the first one
+///
+/// Requirements:
+///
+/// - validator fees vault is initialized, i.e. the validator is whitelisted
+/// - earnings ledger page is uninitialized
+/// - previous page, if any, is initialized and full, so that the pages are filled in order
+///
+/// Steps:
+///
+/// 1. Create the earnings ledger page, with all its entries
+/// 2. Set its header, the page being empty
+pub fn process_init_earnings_ledger_page(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = InitEarningsLedgerPageArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [validator, validator_fees_vault, earnings_ledger_page, system_program, previous_page @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    load_initialized_validator_fees_vault(validator, validator_fees_vault, false)?;
+    let earnings_ledger_page_bump = load_uninitialized_pda(
+        earnings_ledger_page,
+        earnings_ledger_page_seeds_from_validator!(validator.key, args.page),
+        &crate::id(),
+        true,
+        "earnings ledger page",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    if let Some(previous) = args.page.checked_sub(1) {
+        let Some(previous_page) = previous_page.first() else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        load_initialized_pda(
+            previous_page,
+            earnings_ledger_page_seeds_from_validator!(validator.key, previous),
+            &crate::id(),
+            false,
+            "previous earnings ledger page",
+        )?;
+        let previous_page_data = previous_page.try_borrow_data()?;
+        if !EarningsLedgerPage::from_page_data(&previous_page_data)?.is_full() {
+            msg!("previous earnings ledger page {} is not full", previous);
+            return Err(InvalidEarningsLedgerPage.into());
+        }
+    }
+
+    create_pda(
+        earnings_ledger_page,
+        &crate::id(),
+        EarningsLedgerPage::account_size(),
+        earnings_ledger_page_seeds_from_validator!(validator.key, args.page),
+        earnings_ledger_page_bump,
+        system_program,
+        validator,
+    )?;
+
+    let page = EarningsLedgerPage {
+        validator: *validator.key,
+        page: args.page,
+        len: 0,
+    };
+    let mut earnings_ledger_page_data = earnings_ledger_page.try_borrow_mut_data()?;
+    page.to_bytes_with_discriminator(
+        earnings_ledger_page_data
+            .get_mut(..EarningsLedgerPage::size_with_discriminator())
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )?;
+
+    Ok(())
+}
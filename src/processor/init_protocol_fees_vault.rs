@@ -3,10 +3,18 @@ use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
 };
 
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
 use crate::fees_vault_seeds;
 use crate::processor::utils::loaders::{load_program, load_signer, load_uninitialized_pda};
 use crate::processor::utils::pda::create_pda;
 
+/// Accounts of [process_init_protocol_fees_vault]
+pub const INIT_PROTOCOL_FEES_VAULT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("payer"),
+    AccountSpec::writable("protocol fees vault"),
+    AccountSpec::readonly("system program"),
+];
+
 /// Initialize the global fees vault
 ///
 /// Accounts:
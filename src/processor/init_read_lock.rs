@@ -0,0 +1,93 @@
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program, load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::ReadLock;
+use crate::{
+    delegation_record_seeds_from_delegated_account, read_lock_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_init_read_lock]
+pub const INIT_READ_LOCK_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("payer"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::writable("read lock"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Initialize the read lock of a delegated account, locked by the finalizes of the account
+/// for the rest of their slot
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the account paying for the read lock
+/// 1: `[]`         the delegated account
+/// 2: `[]`         the delegation record account
+/// 3: `[writable]` the read lock PDA we are initializing
+/// 4: `[]`         the system program
+///
+/// Requirements:
+///
+/// - delegated account is owned by delegation program
+/// - delegation record is initialized
+/// - read lock is uninitialized
+///
+/// NOTE: this operation is permisionless and can be done by anyone
+///
+/// Steps:
+///
+/// 1. Create the read lock PDA, unlocked
+pub fn process_init_read_lock(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [payer, delegated_account, delegation_record_account, read_lock_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+
+    let read_lock_bump = load_uninitialized_pda(
+        read_lock_account,
+        read_lock_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "read lock",
+    )?;
+
+    // Create the read lock account
+    create_pda(
+        read_lock_account,
+        &crate::id(),
+        ReadLock::size_with_discriminator(),
+        read_lock_seeds_from_delegated_account!(delegated_account.key),
+        read_lock_bump,
+        system_program,
+        payer,
+    )?;
+
+    let mut read_lock_data = read_lock_account.try_borrow_mut_data()?;
+    ReadLock::default().to_bytes_with_discriminator(&mut read_lock_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, pubkey::Pubkey,
+    system_program,
+};
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::InitSessionReportArgs;
+use crate::processor::utils::loaders::{
+    load_pda, load_program, load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::SessionReport;
+use crate::{ephemeral_balance_seeds_from_payer, session_report_seeds_from_escrow};
+
+/// Accounts of [process_init_session_report]
+pub const INIT_SESSION_REPORT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("escrow authority"),
+    AccountSpec::readonly("escrow"),
+    AccountSpec::writable("session report"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Start a session report of an escrow, summarizing what its session costs, see
+/// [SessionReport]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the escrow authority, paying the rent of the report
+/// 1: `[]`                 the ephemeral balance escrow of the authority
+/// 2: `[writable]`         the session report PDA of the escrow
+/// 3: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - escrow is derived from the escrow authority and the escrow index
+/// - session report PDA is uninitialized
+///
+/// Steps:
+///
+/// 1. Create the session report, starting the session at the current slot
+///
+/// Usage:
+///
+/// The report is then passed as the last account of the finalizes of the commits of the
+/// escrow, of the call handlers spending from it and of its undelegation, which freezes it.
+pub fn process_init_session_report(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = InitSessionReportArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [escrow_authority, escrow, session_report_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(escrow_authority, "escrow authority")?;
+    load_pda(
+        escrow,
+        ephemeral_balance_seeds_from_payer!(escrow_authority.key, args.escrow_index),
+        &crate::id(),
+        false,
+        "escrow",
+    )?;
+    let session_report_bump = load_uninitialized_pda(
+        session_report_account,
+        session_report_seeds_from_escrow!(escrow.key),
+        &crate::id(),
+        true,
+        "session report",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    create_pda(
+        session_report_account,
+        &crate::id(),
+        SessionReport::size_with_discriminator(),
+        session_report_seeds_from_escrow!(escrow.key),
+        session_report_bump,
+        system_program,
+        escrow_authority,
+    )?;
+
+    let session_report = SessionReport::new(*escrow_authority.key, *escrow.key, Clock::get()?.slot);
+    let mut session_report_data = session_report_account.try_borrow_mut_data()?;
+    session_report.to_bytes_with_discriminator(&mut session_report_data)?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use solana_program::msg;
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
@@ -11,15 +12,26 @@ use crate::processor::utils::loaders::{
 use crate::processor::utils::pda::create_pda;
 use crate::validator_fees_vault_seeds_from_validator;
 
+/// Accounts of [process_init_validator_fees_vault]
+pub const INIT_VALIDATOR_FEES_VAULT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("payer"),
+    AccountSpec::signer("admin"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("validator identity"),
+    AccountSpec::writable("validator fees vault"),
+    AccountSpec::readonly("system program"),
+];
+
 /// Process the initialization of the validator fees vault
 ///
 /// Accounts:
 ///
-/// 0; `[signer]` payer
-/// 1; `[signer]` admin that controls the vault
-/// 2; `[]`       validator_identity
-/// 3; `[]`       validator_fees_vault_pda
-/// 4; `[]`       system_program
+/// 0: `[signer, writable]` payer
+/// 1: `[signer]`           admin that controls the vault
+/// 2: `[]`                 delegation program data
+/// 3: `[]`                 validator identity
+/// 4: `[writable]`         validator fees vault PDA
+/// 5: `[]`                 system program
 ///
 /// Requirements:
 ///
@@ -0,0 +1,87 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use solana_program::program::set_return_data;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::pda::{program_config_from_program_id, validator_whitelist_shard_pda_from_program_id};
+use crate::state::ValidatorWhitelistStatus;
+
+/// Accounts of [process_is_validator_whitelisted]
+pub const IS_VALIDATOR_WHITELISTED_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::readonly("validator"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program config"),
+];
+
+/// Return whether a validator is whitelisted for a program, so that composing programs can
+/// check it via CPI without parsing the program config
+///
+/// Accounts:
+///
+/// 0: `[]` the validator
+/// 1: `[]` the program
+/// 2: `[]` the program config PDA of the program, initialized or not
+/// 3: `[]` (optional) the validator whitelist shard PDA of the validator for the program,
+///    initialized or not, see [crate::state::ValidatorWhitelistShard]
+///
+/// Requirements:
+///
+/// - program config PDA is derived from the program
+/// - validator whitelist shard, if passed, is derived from the program and the validator
+///
+/// Steps:
+///
+/// 1. Set the return data to the [ValidatorWhitelistStatus] of the validator, see
+///    [ValidatorWhitelistStatus::from_program_config_data], whitelisted as well if its
+///    shard is initialized
+pub fn process_is_validator_whitelisted(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let (validator, program, program_config_account, shard_account) = match accounts {
+        [validator, program, program_config_account] => {
+            (validator, program, program_config_account, None)
+        }
+        [validator, program, program_config_account, shard_account] => (
+            validator,
+            program,
+            program_config_account,
+            Some(shard_account),
+        ),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+
+    if !program_config_account
+        .key
+        .eq(&program_config_from_program_id(program.key))
+    {
+        msg!("Invalid seeds for account: {}", program_config_account.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let program_config_data = program_config_account.try_borrow_data()?;
+    let has_program_config =
+        program_config_account.owner.eq(&crate::id()) && !program_config_data.is_empty();
+    let mut status = ValidatorWhitelistStatus::from_program_config_data(
+        validator.key,
+        has_program_config.then_some(&**program_config_data),
+    )?;
+    if let Some(shard_account) = shard_account {
+        if !shard_account
+            .key
+            .eq(&validator_whitelist_shard_pda_from_program_id(
+                program.key,
+                validator.key,
+            ))
+        {
+            msg!("Invalid seeds for account: {}", shard_account.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        status.is_whitelisted |= shard_account.owner.eq(&crate::id());
+    }
+    set_return_data(&borsh::to_vec(&status)?);
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::delegation_record_seeds_from_delegated_account;
+use crate::processor::utils::loaders::{load_initialized_pda, load_program, load_signer};
+use crate::processor::utils::pda::resize_pda;
+use crate::state::DelegationRecord;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_migrate_delegation_record]
+pub const MIGRATE_DELEGATION_RECORD_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("payer"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Migrate a delegation record with the legacy layout to the current one, see
+/// [DelegationRecord::version]. Anyone can migrate a record, the payer funding the rent of
+/// the added bytes. Migrating a record which already has the current layout is a no-op.
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the payer of the rent of the added bytes
+/// 1: `[]`         the delegated account
+/// 2: `[writable]` the delegation record PDA
+/// 3: `[]`         the system program
+///
+/// Requirements:
+///
+/// - delegation record is initialized
+///
+/// Steps:
+///
+/// 1. Read the delegation record with its current layout
+/// 2. Resize the delegation record to the current layout, the payer funding the rent
+/// 3. Write the delegation record back with [DelegationRecord::VERSION]
+pub fn process_migrate_delegation_record(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [payer, delegated_account, delegation_record_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation record",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        if !DelegationRecord::is_legacy(&delegation_record_data) {
+            return Ok(());
+        }
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+
+    resize_pda(
+        payer,
+        delegation_record_account,
+        system_program,
+        DelegationRecord::size_with_discriminator(),
+    )?;
+
+    let mut delegation_record_data = delegation_record_account.try_borrow_mut_data()?;
+    delegation_record.to_bytes_with_discriminator(&mut delegation_record_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::processor::utils::loaders::{load_initialized_pda, load_pda, load_program, load_signer};
+use crate::processor::utils::pda::{create_pda, resize_pda};
+use crate::processor::whitelist_validator_for_program::validate_authority;
+use crate::processor::whitelist_validator_shard_for_program::refund_freed_rent;
+use crate::state::{ProgramConfig, ValidatorWhitelistShard};
+use crate::{
+    program_config_seeds_from_program_id, validator_whitelist_shard_seeds_from_program_id,
+};
+use solana_program::program::set_return_data;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_migrate_program_config_whitelist]
+pub const MIGRATE_PROGRAM_CONFIG_WHITELIST_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program data"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("program config"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Migrate validators of the `approved_validators` of a program config to their shards,
+/// see [ValidatorWhitelistShard], so that the program config no longer grows with the
+/// whitelist. Large whitelists are migrated over several instructions.
+///
+/// Accounts:
+///
+/// 0: `[signer]`   authority that has rights to whitelist validators
+/// 1: `[]`         program whose whitelist is migrated
+/// 2: `[]`         program data account
+/// 3: `[]`         delegation program data account
+/// 4: `[writable]` program config PDA
+/// 5: `[]`         system program
+/// 6..: `[writable]` the shards of the first `approved_validators`, in their order
+///
+/// Requirements:
+///
+/// - authority is either the ADMIN_PUBKEY or the program upgrade authority
+/// - program config is initialized
+/// - at most as many shards as `approved_validators` are passed
+/// - each shard is derived from the program and its validator
+///
+/// Steps:
+///
+/// 1. Load the authority and validate it
+/// 2. Create the shard of each of the first `approved_validators`, unless it exists, and
+///    remove them from the program config, flagging that the program has whitelist shards
+/// 3. Resize the program config, refunding the freed rent to the authority
+/// 4. Set the number of validators left to migrate as the return data, in u32 little
+///    endian
+pub fn process_migrate_program_config_whitelist(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [authority, program, program_data, delegation_program_data, program_config_account, system_program, shard_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    validate_authority(authority, program, program_data, delegation_program_data)?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_initialized_pda(
+        program_config_account,
+        program_config_seeds_from_program_id!(program.key),
+        &crate::id(),
+        true,
+        "program config",
+    )?;
+
+    let mut program_config = {
+        let program_config_data = program_config_account.try_borrow_data()?;
+        ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)?
+    };
+    if shard_accounts.len() > program_config.approved_validators.len() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    for shard_account in shard_accounts {
+        let Some(validator) = program_config.approved_validators.pop_first() else {
+            return Err(ProgramError::InvalidArgument);
+        };
+        let shard_bump = load_pda(
+            shard_account,
+            validator_whitelist_shard_seeds_from_program_id!(program.key, validator),
+            &crate::id(),
+            true,
+            "validator whitelist shard",
+        )?;
+        if shard_account.owner.eq(&crate::id()) {
+            continue;
+        }
+        create_pda(
+            shard_account,
+            &crate::id(),
+            ValidatorWhitelistShard::size_with_discriminator(),
+            validator_whitelist_shard_seeds_from_program_id!(program.key, validator),
+            shard_bump,
+            system_program,
+            authority,
+        )?;
+        let shard = ValidatorWhitelistShard {
+            program: *program.key,
+            validator,
+        };
+        let mut shard_data = shard_account.try_borrow_mut_data()?;
+        shard.to_bytes_with_discriminator(&mut shard_data)?;
+    }
+
+    program_config.has_whitelist_shards |= !shard_accounts.is_empty();
+
+    let new_size = program_config.size_with_discriminator();
+    resize_pda(authority, program_config_account, system_program, new_size)?;
+    refund_freed_rent(program_config_account, authority, new_size)?;
+
+    let mut program_config_data = program_config_account.try_borrow_mut_data()?;
+    program_config.to_bytes_with_discriminator(&mut program_config_data.as_mut())?;
+
+    set_return_data(&(program_config.approved_validators.len() as u32).to_le_bytes());
+
+    Ok(())
+}
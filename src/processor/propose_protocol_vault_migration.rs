@@ -0,0 +1,124 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::to_vec;
+use solana_program::clock::Clock;
+use solana_program::log::sol_log_data;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::consts::PROTOCOL_VAULT_MIGRATION_TIMELOCK_SLOTS;
+use crate::error::DlpError::Unauthorized;
+use crate::events::{EventDiscriminator, ProtocolVaultMigrationProposedEvent};
+use crate::pda::fees_vault_pda_from_generation;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_program, load_program_upgrade_authority, load_signer,
+};
+use crate::processor::utils::pda::resize_pda;
+use crate::protocol_config_seeds;
+use crate::state::ProtocolConfig;
+
+/// Accounts of [process_propose_protocol_vault_migration]
+pub const PROPOSE_PROTOCOL_VAULT_MIGRATION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::writable("protocol config"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Propose to migrate the protocol fees to a new vault, the vault of the next generation,
+/// see [crate::pda::fees_vault_pda_from_generation]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account proposing the migration
+/// 1: `[writable]`         protocol config PDA
+/// 2: `[]`                 delegation program data
+/// 3: `[]`                 system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - protocol config PDA is initialized
+///
+/// Steps:
+///
+/// 1. Resize the protocol config if it has a previous layout
+/// 2. Record the pending migration, executable once
+///    [PROTOCOL_VAULT_MIGRATION_TIMELOCK_SLOTS] elapsed. A pending migration is proposed
+///    again, restarting its timelock.
+/// 3. Emit a [ProtocolVaultMigrationProposedEvent]
+pub fn process_propose_protocol_vault_migration(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [admin, protocol_config_account, delegation_program_data, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    load_initialized_pda(
+        protocol_config_account,
+        protocol_config_seeds!(),
+        &crate::id(),
+        true,
+        "protocol config",
+    )?;
+    if protocol_config_account.data_len() != ProtocolConfig::size_with_discriminator() {
+        resize_pda(
+            admin,
+            protocol_config_account,
+            system_program,
+            ProtocolConfig::size_with_discriminator(),
+        )?;
+    }
+
+    let mut protocol_config_data = protocol_config_account.try_borrow_mut_data()?;
+    let protocol_config =
+        ProtocolConfig::try_from_bytes_with_discriminator_mut(&mut protocol_config_data)?;
+    let slot = Clock::get()?.slot;
+    protocol_config.pending_fees_vault_generation = protocol_config
+        .fees_vault_generation
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    protocol_config.fees_vault_migration_slot =
+        slot.saturating_add(PROTOCOL_VAULT_MIGRATION_TIMELOCK_SLOTS);
+
+    let event = ProtocolVaultMigrationProposedEvent {
+        admin: *admin.key,
+        current_vault: fees_vault_pda_from_generation(protocol_config.fees_vault_generation),
+        new_vault: fees_vault_pda_from_generation(protocol_config.pending_fees_vault_generation),
+        executable_slot: protocol_config.fees_vault_migration_slot,
+    };
+    msg!(
+        "Migration of the protocol fees vault {} to {} proposed, executable from slot {}",
+        event.current_vault,
+        event.new_vault,
+        event.executable_slot
+    );
+    sol_log_data(&[&[
+        vec![EventDiscriminator::ProtocolVaultMigrationProposed.into()],
+        to_vec(&event)?,
+    ]
+    .concat()]);
+
+    Ok(())
+}
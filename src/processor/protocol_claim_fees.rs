@@ -1,18 +1,27 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
 use crate::error::DlpError::Unauthorized;
+use crate::log::msg;
 use crate::processor::utils::loaders::{
     load_initialized_protocol_fees_vault, load_program_upgrade_authority, load_signer,
 };
-use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::rent::Rent;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
 
+/// Accounts of [process_protocol_claim_fees]
+pub const PROTOCOL_CLAIM_FEES_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::writable("protocol fees vault"),
+    AccountSpec::readonly("delegation program data"),
+];
+
 /// Process request to claim fees from the protocol fees vault
 ///
 /// Accounts:
 ///
-/// 1. `[signer]`   admin account that can claim the fees
-/// 2. `[writable]` protocol fees vault PDA
+/// 0: `[signer, writable]` admin account that can claim the fees
+/// 1: `[writable]`         protocol fees vault PDA
+/// 2: `[]`                 delegation program data
 ///
 /// Requirements:
 ///
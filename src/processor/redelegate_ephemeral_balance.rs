@@ -0,0 +1,150 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::args::RedelegateEphemeralBalanceArgs;
+use crate::error::DlpError;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_initialized_validator_fees_vault, load_signer,
+    load_uninitialized_pda,
+};
+use crate::state::{DelegationMetadata, DelegationRecord};
+use crate::{
+    commit_record_seeds_from_delegated_account, commit_state_seeds_from_delegated_account,
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account, ephemeral_balance_seeds_from_payer,
+};
+
+/// Accounts of [process_redelegate_ephemeral_balance]
+pub const REDELEGATE_EPHEMERAL_BALANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::signer("pubkey"),
+    AccountSpec::readonly("ephemeral balance"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("commit state"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::readonly("new validator"),
+    AccountSpec::readonly("validator fees vault"),
+];
+
+/// Hand a delegated ephemeral balance over to another validator, when its user moves to
+/// another ephemeral rollup, without undelegating and delegating it again
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the validator, current authority of the delegation
+/// 1: `[signer]`   the pubkey the ephemeral balance is derived from
+/// 2: `[]`         the ephemeral balance
+/// 3: `[writable]` the delegation record of the ephemeral balance
+/// 4: `[]`         the delegation metadata of the ephemeral balance
+/// 5: `[]`         the commit state PDA of the ephemeral balance
+/// 6: `[]`         the commit record PDA of the ephemeral balance
+/// 7: `[]`         the new validator
+/// 8: `[]`         the validator fees vault of the new validator
+///
+/// Requirements:
+///
+/// - ephemeral balance is derived from the pubkey and the index, and is delegated
+/// - validator is the authority in the delegation record
+/// - ephemeral balance is not undelegatable
+/// - there is no pending commit for the ephemeral balance, which the new validator could
+///   not finalize
+/// - new validator differs from the current one and its validator fees vault is
+///   initialized, i.e. it is whitelisted
+///
+/// Steps:
+///
+/// 1. Set the new validator as the authority of the delegation, the next commits of the
+///    ephemeral balance being the ones of the new validator
+pub fn process_redelegate_ephemeral_balance(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = RedelegateEphemeralBalanceArgs::try_from_slice(data)?;
+
+    let [validator, pubkey, ephemeral_balance_account, delegation_record_account, delegation_metadata_account, commit_state_account, commit_record_account, new_validator, new_validator_fees_vault] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    load_signer(pubkey, "ephemeral balance pubkey")?;
+    load_initialized_pda(
+        ephemeral_balance_account,
+        ephemeral_balance_seeds_from_payer!(pubkey.key, args.index),
+        &crate::id(),
+        false,
+        "ephemeral balance",
+    )?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(ephemeral_balance_account.key),
+        &crate::id(),
+        true,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(ephemeral_balance_account.key),
+        &crate::id(),
+        false,
+        "delegation metadata",
+    )?;
+    load_initialized_validator_fees_vault(new_validator, new_validator_fees_vault, false)?;
+
+    // Make sure there is no pending commit, which only the current validator can finalize
+    load_uninitialized_pda(
+        commit_state_account,
+        commit_state_seeds_from_delegated_account!(ephemeral_balance_account.key),
+        &crate::id(),
+        false,
+        "commit state",
+    )?;
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(ephemeral_balance_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+
+    let delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    if delegation_metadata.is_undelegatable {
+        msg!(
+            "Ephemeral balance {} is undelegatable",
+            ephemeral_balance_account.key
+        );
+        return Err(DlpError::AlreadyUndelegated.into());
+    }
+
+    let mut delegation_record_data = delegation_record_account.try_borrow_mut_data()?;
+    let mut delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?;
+    if !delegation_record.authority.eq(validator.key) {
+        msg!(
+            "Expected delegation authority to be {}, but got {}",
+            delegation_record.authority,
+            validator.key
+        );
+        return Err(DlpError::InvalidAuthority.into());
+    }
+    if new_validator.key.eq(validator.key) {
+        msg!(
+            "Ephemeral balance is already delegated to {}",
+            validator.key
+        );
+        return Err(DlpError::InvalidAuthority.into());
+    }
+    delegation_record.authority = *new_validator.key;
+    delegation_record.to_bytes_with_discriminator(&mut delegation_record_data)?;
+
+    Ok(())
+}
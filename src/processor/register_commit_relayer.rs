@@ -0,0 +1,101 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::RegisterCommitRelayerArgs;
+use crate::error::DlpError::{InvalidAuthority, TooManyCommitRelayers};
+use crate::processor::utils::loaders::{
+    load_initialized_validator_fees_vault, load_program, load_signer,
+};
+use crate::processor::utils::pda::resize_funded_pda;
+use crate::state::{ValidatorFeesVault, MAX_COMMIT_RELAYERS};
+
+/// Accounts of [process_register_commit_relayer]
+pub const REGISTER_COMMIT_RELAYER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("validator identity"),
+    AccountSpec::writable("validator fees vault"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Approve or revoke a relayer submitting commits on behalf of the validator identity, see
+/// [ValidatorFeesVault]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the validator identity, paying the growth of the vault
+/// 1: `[writable]`         the validator fees vault, storing the commit relayers
+/// 2: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - validator fees vault is initialized, i.e. the validator is whitelisted
+/// - relayer is not the validator identity
+/// - validator approves at most [MAX_COMMIT_RELAYERS] relayers
+///
+/// Steps:
+///
+/// 1. Add the relayer to the commit relayers of the validator fees vault, or remove it
+/// 2. Resize the vault, the validator paying the rent of its growth and being refunded
+///    the rent it frees
+///
+/// The commits submitted by a relayer are attributed to the validator identity, see
+/// [crate::processor::fast::process_commit_state].
+pub fn process_register_commit_relayer(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = RegisterCommitRelayerArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [validator, validator_fees_vault, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    load_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    if args.relayer.eq(validator.key) {
+        msg!("validator cannot be its own commit relayer");
+        return Err(InvalidAuthority.into());
+    }
+
+    let mut vault = {
+        let validator_fees_vault_data = validator_fees_vault.try_borrow_data()?;
+        ValidatorFeesVault::try_from_account_data(&validator_fees_vault_data)?
+    };
+    if args.approved {
+        if !vault.is_approved_relayer(&args.relayer) {
+            vault.commit_relayers.push(args.relayer);
+        }
+        if vault.commit_relayers.len() > MAX_COMMIT_RELAYERS {
+            msg!(
+                "validator can approve at most {} commit relayers",
+                MAX_COMMIT_RELAYERS
+            );
+            return Err(TooManyCommitRelayers.into());
+        }
+    } else {
+        vault
+            .commit_relayers
+            .retain(|relayer| !relayer.eq(&args.relayer));
+    }
+
+    // Resize the vault, exchanging the rent of its growth with the validator
+    resize_funded_pda(
+        validator,
+        validator_fees_vault,
+        system_program,
+        vault.size_with_discriminator(),
+    )?;
+
+    let mut validator_fees_vault_data = validator_fees_vault.try_borrow_mut_data()?;
+    vault.to_bytes_with_discriminator(&mut validator_fees_vault_data.as_mut())?;
+
+    Ok(())
+}
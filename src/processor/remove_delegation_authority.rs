@@ -0,0 +1,123 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::DelegationAuthorityArgs;
+use crate::error::DlpError::InvalidAuthority;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program, load_signer,
+};
+use crate::processor::utils::pda::{close_pda, resize_funded_pda};
+use crate::state::{DelegationAuthorities, DelegationRecord};
+use crate::{
+    delegation_authorities_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_remove_delegation_authority]
+pub const REMOVE_DELEGATION_AUTHORITY_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::writable("delegation authorities"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Remove a validator from the authorities of a delegation, see [DelegationAuthorities]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the authority of the delegation, refunded the rent freed
+/// 1: `[]`                 the delegated account
+/// 2: `[]`                 the delegation record
+/// 3: `[writable]`         the delegation authorities PDA of the delegated account
+/// 4: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - delegated account is owned by the delegation program
+/// - delegation record is initialized
+/// - delegation authorities are initialized
+/// - signer is the authority of the delegation record
+///
+/// Steps:
+///
+/// 1. Remove the validator from the delegation authorities
+/// 2. Close the delegation authorities if no authority of the delegation is left, otherwise
+///    resize them, refunding the signer the rent freed
+pub fn process_remove_delegation_authority(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = DelegationAuthorityArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, delegated_account, delegation_record_account, delegation_authorities_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "delegation authority")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_authorities_account,
+        delegation_authorities_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation authorities",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    if !delegation_record.authority.eq(authority.key) {
+        msg!(
+            "Expected delegation authority to be {}, but got {}",
+            delegation_record.authority,
+            authority.key
+        );
+        return Err(InvalidAuthority.into());
+    }
+
+    let mut delegation_authorities = {
+        let delegation_authorities_data = delegation_authorities_account.try_borrow_data()?;
+        DelegationAuthorities::try_from_bytes_with_discriminator(&delegation_authorities_data)?
+    };
+    delegation_authorities.remove(&args.validator);
+
+    // The authorities of a previous delegation of the account are left to close
+    if delegation_authorities.authorities.is_empty()
+        || delegation_authorities.delegation_slot != delegation_record.delegation_slot
+    {
+        return close_pda(delegation_authorities_account, authority);
+    }
+
+    // Resize the delegation authorities, refunding the signer the rent freed
+    resize_funded_pda(
+        authority,
+        delegation_authorities_account,
+        system_program,
+        delegation_authorities.size_with_discriminator(),
+    )?;
+
+    let mut delegation_authorities_data = delegation_authorities_account.try_borrow_mut_data()?;
+    delegation_authorities
+        .to_bytes_with_discriminator(&mut delegation_authorities_data.as_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::error::DlpError::{AlreadyUndelegated, Unauthorized, UndelegationGracePeriod};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_pda, load_program, load_signer,
+    load_uninitialized_pda,
+};
+use crate::processor::utils::pda::resize_pda;
+use crate::state::{
+    DelegationMetadata, DelegationRecord, ProgramConfig, ProtocolConfig, UndelegationRequest,
+};
+use crate::{
+    commit_record_seeds_from_delegated_account, delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account, program_config_seeds_from_program_id,
+    protocol_config_seeds,
+};
+
+/// Accounts of [process_request_undelegation]
+pub const REQUEST_UNDELEGATION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("requester"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::readonly("protocol config"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Request the undelegation of a delegated account, see [UndelegationRequest]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the requester, funding the growth of the delegation metadata
+/// 1: `[]`                 the delegated account, signing if the owner program requests
+/// 2: `[writable]`         the delegation metadata
+/// 3: `[]`                 the commit record PDA
+/// 4: `[]`                 the protocol config PDA
+/// 5: `[]`                 the system program
+/// 6: `[]`                 (optional) the delegation record
+/// 7: `[]`                 (optional) the program config PDA of the owner program
+///
+/// Requirements:
+///
+/// - requester is the rent payer of the delegation metadata, or the delegated account
+///   signs, which is only possible if the owner program requests via CPI (or if the
+///   delegated account is on curve), unless the delegation outlived the maximum delegation
+///   slots of the program config
+/// - delegated account is owned by the delegation program and is NOT undelegatable
+/// - delegation metadata is initialized
+/// - if the undelegation was already requested, its grace period elapsed and the commit
+///   record is uninitialized
+///
+/// Steps:
+///
+/// 1. If the delegation outlived the maximum delegation slots, see
+///    [crate::processor::process_set_program_max_delegation_slots], mark the account
+///    undelegatable right away, resizing the delegation metadata if necessary: any requester
+///    can force the settlement of the delegation, provided no commit is pending
+/// 2. Else if the undelegation was not requested, record the request in the delegation
+///    metadata, resizing it if necessary. Commits must allow the undelegation once the grace period
+///    of the protocol config elapsed.
+/// 3. Otherwise, mark the account undelegatable so that it can be undelegated without a
+///    commit of the validator
+pub fn process_request_undelegation(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [requester, delegated_account, delegation_metadata_account, commit_record_account, protocol_config_account, system_program, expiry_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(requester, "requester")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+    load_pda(
+        protocol_config_account,
+        protocol_config_seeds!(),
+        &crate::id(),
+        false,
+        "protocol config",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let mut delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+
+    if delegation_metadata.is_undelegatable {
+        return Err(AlreadyUndelegated.into());
+    }
+
+    let slot = Clock::get()?.slot;
+
+    // Anyone can force the settlement of a delegation which outlived its maximum lifetime
+    if let [delegation_record_account, program_config_account, ..] = expiry_accounts {
+        if is_delegation_expired(
+            delegated_account,
+            delegation_record_account,
+            program_config_account,
+            slot,
+        )? {
+            // A pending commit is left to be finalized, it must allow the undelegation
+            load_uninitialized_pda(
+                commit_record_account,
+                commit_record_seeds_from_delegated_account!(delegated_account.key),
+                &crate::id(),
+                false,
+                "commit record",
+            )?;
+            delegation_metadata.is_undelegatable = true;
+            resize_pda(
+                requester,
+                delegation_metadata_account,
+                system_program,
+                delegation_metadata.serialized_size(),
+            )?;
+            let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+            delegation_metadata
+                .to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+            return Ok(());
+        }
+    }
+
+    // Only the rent payer or the owner program can request the undelegation
+    if !delegated_account.is_signer && !delegation_metadata.rent_payer.eq(requester.key) {
+        msg!("requester is not the rent payer and the delegated account did not sign");
+        return Err(Unauthorized.into());
+    }
+
+    match delegation_metadata.undelegation_request {
+        None => {
+            // The grace period is the default one until the protocol config is created
+            let protocol_config = if protocol_config_account.owner.eq(&crate::id()) {
+                let protocol_config_data = protocol_config_account.try_borrow_data()?;
+                *ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_data)?
+            } else {
+                ProtocolConfig::default()
+            };
+            delegation_metadata.undelegation_request = Some(UndelegationRequest {
+                slot,
+                deadline_slot: slot.saturating_add(protocol_config.undelegation_grace_slots()),
+            });
+            resize_pda(
+                requester,
+                delegation_metadata_account,
+                system_program,
+                delegation_metadata.serialized_size(),
+            )?;
+        }
+        Some(undelegation_request) if undelegation_request.is_overdue(slot) => {
+            // A pending commit is left to be finalized, it must allow the undelegation
+            load_uninitialized_pda(
+                commit_record_account,
+                commit_record_seeds_from_delegated_account!(delegated_account.key),
+                &crate::id(),
+                false,
+                "commit record",
+            )?;
+            delegation_metadata.is_undelegatable = true;
+        }
+        Some(_) => return Err(UndelegationGracePeriod.into()),
+    }
+
+    let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+    delegation_metadata.to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+
+    Ok(())
+}
+
+/// Returns true if the delegation outlived the maximum delegation slots of the program config
+/// of its owner program, if any
+fn is_delegation_expired(
+    delegated_account: &AccountInfo,
+    delegation_record_account: &AccountInfo,
+    program_config_account: &AccountInfo,
+    slot: u64,
+) -> Result<bool, ProgramError> {
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    load_pda(
+        program_config_account,
+        program_config_seeds_from_program_id!(delegation_record.owner),
+        &crate::id(),
+        false,
+        "program config",
+    )?;
+    if !program_config_account.owner.eq(&crate::id()) {
+        return Ok(false);
+    }
+    let program_config_data = program_config_account.try_borrow_data()?;
+    let program_config = ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)?;
+    Ok(program_config.is_delegation_expired(delegation_record.delegation_slot, slot))
+}
@@ -0,0 +1,139 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::ResyncProtocolStatsArgs;
+use crate::error::DlpError::{Overflow, Unauthorized};
+use crate::processor::utils::loaders::{
+    load_owned_pda, load_pda, load_program, load_program_upgrade_authority, load_signer,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::protocol_stats_seeds;
+use crate::state::{DelegationRecord, ProtocolStats};
+
+/// Accounts of [process_resync_protocol_stats]
+pub const RESYNC_PROTOCOL_STATS_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::writable("protocol stats"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Resync the total value locked of the protocol stats from the delegation records, if it
+/// drifted from the instructions not passed the protocol stats, see [ProtocolStats]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account that can resync the protocol stats
+/// 1: `[writable]`         protocol stats PDA
+/// 2: `[]`                 delegation program data
+/// 3: `[]`                 system program
+/// 4..: `[]`               the delegation records to sum the lamports of
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - protocol stats PDA is initialized or owned by the system program in which case it
+///   is created
+/// - delegation records are initialized and passed once
+///
+/// Steps:
+///
+/// 1. Load the protocol stats or create them
+/// 2. Sum the lamports recorded by the delegation records
+/// 3. Reset the total value locked to the sum, or add the sum to it to resync over several
+///    transactions, and record the slot of the resync
+pub fn process_resync_protocol_stats(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = ResyncProtocolStatsArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [admin, protocol_stats_account, delegation_program_data, system_program, delegation_records @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    let protocol_stats_bump = load_pda(
+        protocol_stats_account,
+        protocol_stats_seeds!(),
+        &crate::id(),
+        true,
+        "protocol stats",
+    )?;
+
+    // Create the protocol stats if they don't exist
+    if protocol_stats_account.owner.eq(system_program.key) {
+        create_pda(
+            protocol_stats_account,
+            &crate::id(),
+            ProtocolStats::size_with_discriminator(),
+            protocol_stats_seeds!(),
+            protocol_stats_bump,
+            system_program,
+            admin,
+        )?;
+        let mut protocol_stats_data = protocol_stats_account.try_borrow_mut_data()?;
+        ProtocolStats::default().to_bytes_with_discriminator(&mut protocol_stats_data)?;
+    }
+
+    // Sum the lamports of the delegation records, each counted once
+    let mut total_value_locked = 0u64;
+    for (index, delegation_record_account) in delegation_records.iter().enumerate() {
+        if delegation_records
+            .iter()
+            .take(index)
+            .any(|previous| previous.key.eq(delegation_record_account.key))
+        {
+            msg!(
+                "Delegation record {} is passed twice",
+                delegation_record_account.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        load_owned_pda(delegation_record_account, &crate::id(), "delegation record")?;
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        let delegation_record =
+            DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?;
+        total_value_locked = total_value_locked
+            .checked_add(delegation_record.lamports)
+            .ok_or(Overflow)?;
+    }
+
+    let mut protocol_stats_data = protocol_stats_account.try_borrow_mut_data()?;
+    let protocol_stats =
+        ProtocolStats::try_from_bytes_with_discriminator_mut(&mut protocol_stats_data)?;
+    if !args.reset {
+        total_value_locked = total_value_locked
+            .checked_add(protocol_stats.total_value_locked)
+            .ok_or(Overflow)?;
+    }
+    protocol_stats.total_value_locked = total_value_locked;
+    protocol_stats.last_resync_slot = Clock::get()?.slot;
+
+    Ok(())
+}
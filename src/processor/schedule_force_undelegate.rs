@@ -0,0 +1,144 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::to_vec;
+use solana_program::clock::Clock;
+use solana_program::log::sol_log_data;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::consts::FORCE_UNDELEGATE_TIMELOCK_SLOTS;
+use crate::error::DlpError::Unauthorized;
+use crate::events::{EventDiscriminator, ForceUndelegateScheduledEvent};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program, load_program_upgrade_authority,
+    load_signer, load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::{DelegationRecord, ForceUndelegation};
+use crate::{
+    delegation_record_seeds_from_delegated_account, force_undelegation_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_schedule_force_undelegate]
+pub const SCHEDULE_FORCE_UNDELEGATE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::writable("force undelegation"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Schedule the force undelegation of a delegated account, to recover it from a failed
+/// validator, see [ForceUndelegation]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account paying for the force undelegation PDA
+/// 1: `[]`                 the delegated account
+/// 2: `[]`                 the delegation record
+/// 3: `[writable]`         the force undelegation PDA
+/// 4: `[]`                 delegation program data
+/// 5: `[]`                 system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - delegated account is owned by the delegation program
+/// - delegation record is initialized
+/// - force undelegation PDA is uninitialized
+///
+/// Steps:
+///
+/// 1. Create the force undelegation, executable once [FORCE_UNDELEGATE_TIMELOCK_SLOTS]
+///    elapsed
+/// 2. Emit a [ForceUndelegateScheduledEvent]
+pub fn process_schedule_force_undelegate(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [admin, delegated_account, delegation_record_account, force_undelegation_account, delegation_program_data, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    let force_undelegation_bump = load_uninitialized_pda(
+        force_undelegation_account,
+        force_undelegation_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "force undelegation",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+
+    create_pda(
+        force_undelegation_account,
+        &crate::id(),
+        ForceUndelegation::size_with_discriminator(),
+        force_undelegation_seeds_from_delegated_account!(delegated_account.key),
+        force_undelegation_bump,
+        system_program,
+        admin,
+    )?;
+
+    let slot = Clock::get()?.slot;
+    let force_undelegation = ForceUndelegation {
+        admin: *admin.key,
+        scheduled_slot: slot,
+        executable_slot: slot.saturating_add(FORCE_UNDELEGATE_TIMELOCK_SLOTS),
+    };
+    let mut force_undelegation_data = force_undelegation_account.try_borrow_mut_data()?;
+    force_undelegation.to_bytes_with_discriminator(&mut force_undelegation_data)?;
+
+    msg!(
+        "Force undelegation of {} scheduled, executable from slot {}",
+        delegated_account.key,
+        force_undelegation.executable_slot
+    );
+    let event = ForceUndelegateScheduledEvent {
+        delegated_account: *delegated_account.key,
+        validator: delegation_record.authority,
+        admin: *admin.key,
+        executable_slot: force_undelegation.executable_slot,
+    };
+    sol_log_data(&[&[
+        vec![EventDiscriminator::ForceUndelegateScheduled.into()],
+        to_vec(&event)?,
+    ]
+    .concat()]);
+
+    Ok(())
+}
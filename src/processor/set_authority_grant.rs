@@ -0,0 +1,132 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::SetAuthorityGrantArgs;
+use crate::authority_delegation_chain_seeds_from_delegator;
+use crate::error::DlpError::{InvalidAuthority, TooManyAuthorityGrants};
+use crate::processor::utils::loaders::{load_pda, load_program, load_signer};
+use crate::processor::utils::pda::{create_pda, resize_funded_pda};
+use crate::state::{AuthorityDelegationChain, AuthorityGrant, MAX_AUTHORITY_GRANTS};
+
+/// Accounts of [process_set_authority_grant]
+pub const SET_AUTHORITY_GRANT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("delegator"),
+    AccountSpec::writable("authority delegation chain"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Grant or revoke the authority of the delegator to a sub-key, see
+/// [AuthorityDelegationChain]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the delegator, paying the growth of its chain
+/// 1: `[writable]`         the authority delegation chain PDA of the delegator
+/// 2: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - authority delegation chain is initialized or owned by the system program in which
+///   case it is created
+/// - sub-key is not the delegator
+/// - delegator grants at most [MAX_AUTHORITY_GRANTS] sub-keys
+///
+/// Steps:
+///
+/// 1. Load the authority delegation chain or create it
+/// 2. Add the grant of the sub-key, replacing its previous grant, or remove it
+/// 3. Resize the chain, the delegator paying the rent of its growth and being refunded
+///    the rent it frees
+///
+/// Any key can grant its authority, the grants only being followed from the authority of a
+/// delegation and for at most [crate::state::MAX_AUTHORITY_CHAIN_DEPTH] links, see
+/// [crate::processor::fast::process_commit_state].
+pub fn process_set_authority_grant(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetAuthorityGrantArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [delegator, authority_delegation_chain_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(delegator, "delegator")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    let authority_delegation_chain_bump = load_pda(
+        authority_delegation_chain_account,
+        authority_delegation_chain_seeds_from_delegator!(delegator.key),
+        &crate::id(),
+        true,
+        "authority delegation chain",
+    )?;
+
+    if args.key.eq(delegator.key) {
+        msg!("delegator cannot grant its authority to itself");
+        return Err(InvalidAuthority.into());
+    }
+
+    // Create the authority delegation chain if it doesn't exist
+    let mut chain = AuthorityDelegationChain {
+        delegator: *delegator.key,
+        grants: vec![],
+    };
+    if authority_delegation_chain_account
+        .owner
+        .eq(system_program.key)
+    {
+        create_pda(
+            authority_delegation_chain_account,
+            &crate::id(),
+            chain.size_with_discriminator(),
+            authority_delegation_chain_seeds_from_delegator!(delegator.key),
+            authority_delegation_chain_bump,
+            system_program,
+            delegator,
+        )?;
+    } else {
+        let authority_delegation_chain_data =
+            authority_delegation_chain_account.try_borrow_data()?;
+        chain = AuthorityDelegationChain::try_from_bytes_with_discriminator(
+            &authority_delegation_chain_data,
+        )?;
+    }
+
+    if args.approved {
+        chain.set_grant(AuthorityGrant {
+            key: args.key,
+            expiry_slot: args.expiry_slot,
+            scope: args.scope,
+        });
+        if chain.grants.len() > MAX_AUTHORITY_GRANTS {
+            msg!(
+                "delegator can grant its authority to at most {} sub-keys",
+                MAX_AUTHORITY_GRANTS
+            );
+            return Err(TooManyAuthorityGrants.into());
+        }
+    } else {
+        chain.revoke(&args.key);
+    }
+
+    // Resize the chain, exchanging the rent of its growth with the delegator
+    resize_funded_pda(
+        delegator,
+        authority_delegation_chain_account,
+        system_program,
+        chain.size_with_discriminator(),
+    )?;
+
+    let mut authority_delegation_chain_data =
+        authority_delegation_chain_account.try_borrow_mut_data()?;
+    chain.to_bytes_with_discriminator(&mut authority_delegation_chain_data.as_mut())?;
+
+    Ok(())
+}
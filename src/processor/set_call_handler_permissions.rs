@@ -0,0 +1,97 @@
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::{CallHandlerContext, SetCallHandlerPermissionsArgs};
+use crate::processor::utils::loaders::{load_pda, load_program, load_signer};
+use crate::processor::utils::pda::create_pda;
+use crate::state::CallHandlerPermissions;
+use crate::{call_handler_permissions_seeds_from_escrow, ephemeral_balance_seeds_from_payer};
+
+/// Accounts of [process_set_call_handler_permissions]
+pub const SET_CALL_HANDLER_PERMISSIONS_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("escrow authority"),
+    AccountSpec::readonly("escrow"),
+    AccountSpec::writable("call handler permissions"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Set the contexts in which handlers can be called with the signature of an escrow, see
+/// [CallHandlerPermissions]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the escrow authority
+/// 1: `[]`                 the ephemeral balance escrow of the authority
+/// 2: `[writable]`         the call handler permissions PDA of the escrow
+/// 3: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - escrow is derived from the escrow authority and the escrow index
+/// - call handler permissions PDA is initialized or owned by the system program in which
+///   case it is created
+///
+/// Steps:
+///
+/// 1. Load the call handler permissions or create them
+/// 2. Enable or disable every context
+pub fn process_set_call_handler_permissions(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetCallHandlerPermissionsArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [escrow_authority, escrow, call_handler_permissions_account, system_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(escrow_authority, "escrow authority")?;
+    load_pda(
+        escrow,
+        ephemeral_balance_seeds_from_payer!(escrow_authority.key, args.escrow_index),
+        &crate::id(),
+        false,
+        "escrow",
+    )?;
+    let call_handler_permissions_bump = load_pda(
+        call_handler_permissions_account,
+        call_handler_permissions_seeds_from_escrow!(escrow.key),
+        &crate::id(),
+        true,
+        "call handler permissions",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Create the call handler permissions if they don't exist
+    if call_handler_permissions_account
+        .owner
+        .eq(system_program.key)
+    {
+        create_pda(
+            call_handler_permissions_account,
+            &crate::id(),
+            CallHandlerPermissions::size_with_discriminator(),
+            call_handler_permissions_seeds_from_escrow!(escrow.key),
+            call_handler_permissions_bump,
+            system_program,
+            escrow_authority,
+        )?;
+    }
+
+    let mut call_handler_permissions = CallHandlerPermissions::default();
+    call_handler_permissions.set_enabled(CallHandlerContext::Commit, args.allow_commit);
+    call_handler_permissions.set_enabled(CallHandlerContext::Undelegate, args.allow_undelegate);
+    call_handler_permissions.set_enabled(CallHandlerContext::Standalone, args.allow_standalone);
+    let mut call_handler_permissions_data =
+        call_handler_permissions_account.try_borrow_mut_data()?;
+    call_handler_permissions.to_bytes_with_discriminator(&mut call_handler_permissions_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::SetCommitScheduleArgs;
+use crate::error::DlpError::{InvalidCommitSchedule, Unauthorized};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_pda, load_program, load_signer,
+};
+use crate::processor::utils::pda::{create_pda, resize_pda};
+use crate::state::{CommitSchedule, DelegationMetadata};
+use crate::{
+    commit_schedule_seeds_from_delegated_account, delegation_metadata_seeds_from_delegated_account,
+    ephemeral_balance_seeds_from_payer,
+};
+
+/// Accounts of [process_set_commit_schedule]
+pub const SET_COMMIT_SCHEDULE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("rent payer"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::writable("commit schedule"),
+    AccountSpec::readonly("escrow"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Schedule the commits of a delegated account and fund them from an escrow of its rent
+/// payer, see [CommitSchedule]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the rent payer of the delegation, funding the commit schedule
+///                         and the growth of the delegation metadata
+/// 1: `[]`                 the delegated account
+/// 2: `[writable]`         the delegation metadata
+/// 3: `[writable]`         the commit schedule PDA
+/// 4: `[]`                 the ephemeral balance escrow of the rent payer funding the commits
+/// 5: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - rent payer is the one of the delegation metadata
+/// - delegated account is owned by the delegation program
+/// - delegation metadata is initialized
+/// - escrow is derived from the rent payer and the escrow index
+/// - interval and commits per interval are not zero
+/// - commit schedule is initialized or owned by the system program in which case it is
+///   created
+///
+/// Steps:
+///
+/// 1. Load the commit schedule or create it
+/// 2. Set it, its first interval starting at the current slot
+/// 3. Mark the commits of the account as scheduled in the delegation metadata, resizing it
+///    if necessary, so that commits must pass the commit schedule and its escrow
+pub fn process_set_commit_schedule(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetCommitScheduleArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [rent_payer, delegated_account, delegation_metadata_account, commit_schedule_account, escrow, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(rent_payer, "rent payer")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+    let escrow_bump = load_pda(
+        escrow,
+        ephemeral_balance_seeds_from_payer!(rent_payer.key, args.escrow_index),
+        &crate::id(),
+        false,
+        "escrow",
+    )?;
+    let commit_schedule_bump = load_pda(
+        commit_schedule_account,
+        commit_schedule_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "commit schedule",
+    )?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let mut delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    if !delegation_metadata.rent_payer.eq(rent_payer.key) {
+        msg!(
+            "Expected rent payer: {} but got {}",
+            delegation_metadata.rent_payer,
+            rent_payer.key
+        );
+        return Err(Unauthorized.into());
+    }
+    if args.interval_slots == 0 || args.max_commits_per_interval == 0 {
+        msg!("commit schedule must accept commits");
+        return Err(InvalidCommitSchedule.into());
+    }
+
+    // Create the commit schedule if it doesn't exist
+    if commit_schedule_account.owner.eq(system_program.key) {
+        create_pda(
+            commit_schedule_account,
+            &crate::id(),
+            CommitSchedule::size_with_discriminator(),
+            commit_schedule_seeds_from_delegated_account!(delegated_account.key),
+            commit_schedule_bump,
+            system_program,
+            rent_payer,
+        )?;
+    }
+
+    let commit_schedule = CommitSchedule {
+        escrow: *escrow.key,
+        escrow_payer: *rent_payer.key,
+        interval_slots: args.interval_slots,
+        max_commits_per_interval: args.max_commits_per_interval,
+        commit_fee_lamports: args.commit_fee_lamports,
+        interval_start_slot: Clock::get()?.slot,
+        commits_in_interval: 0,
+        escrow_index: args.escrow_index,
+        escrow_bump,
+        padding: [0; 6],
+    };
+    let mut commit_schedule_data = commit_schedule_account.try_borrow_mut_data()?;
+    commit_schedule.to_bytes_with_discriminator(&mut commit_schedule_data)?;
+
+    delegation_metadata.commit_scheduled = true;
+    resize_pda(
+        rent_payer,
+        delegation_metadata_account,
+        system_program,
+        delegation_metadata.serialized_size(),
+    )?;
+    let mut delegation_metadata_data = delegation_metadata_account.try_borrow_mut_data()?;
+    delegation_metadata.to_bytes_with_discriminator(&mut delegation_metadata_data.as_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::SetFeatureGateArgs;
+use crate::error::DlpError::Unauthorized;
+use crate::feature_gates_seeds;
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_pda, load_program, load_program_upgrade_authority, load_signer,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::FeatureGates;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_set_feature_gate]
+pub const SET_FEATURE_GATE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("admin"),
+    AccountSpec::writable("feature gates"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Enable or disable a gated instruction
+///
+/// Accounts:
+///
+/// 0: `[signer]`   admin account that can toggle the feature gates
+/// 1: `[writable]` feature gates PDA
+/// 2: `[]`         delegation program data
+/// 3: `[]`         system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - feature gates PDA is initialized or owned by the system program in
+///   which case it is created with all instructions enabled
+///
+/// Steps:
+///
+/// 1. Load the feature gates or create them
+/// 2. Set or clear the bit of the discriminator
+pub fn process_set_feature_gate(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetFeatureGateArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [admin, feature_gates_account, delegation_program_data, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    let feature_gates_bump = load_pda(
+        feature_gates_account,
+        feature_gates_seeds!(),
+        &crate::id(),
+        true,
+        "feature gates",
+    )?;
+
+    // Create the feature gates if they don't exist, with every instruction enabled
+    if feature_gates_account.owner.eq(system_program.key) {
+        create_pda(
+            feature_gates_account,
+            &crate::id(),
+            FeatureGates::size_with_discriminator(),
+            feature_gates_seeds!(),
+            feature_gates_bump,
+            system_program,
+            admin,
+        )?;
+        let mut feature_gates_data = feature_gates_account.try_borrow_mut_data()?;
+        FeatureGates::default().to_bytes_with_discriminator(&mut feature_gates_data)?;
+    }
+
+    let mut feature_gates_data = feature_gates_account.try_borrow_mut_data()?;
+    let feature_gates =
+        FeatureGates::try_from_bytes_with_discriminator_mut(&mut feature_gates_data)?;
+    feature_gates.set_enabled(args.discriminator, args.enabled);
+
+    Ok(())
+}
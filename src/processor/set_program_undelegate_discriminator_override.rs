@@ -0,0 +1,86 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::SetProgramUndelegateDiscriminatorOverrideArgs;
+use crate::processor::utils::loaders::{load_program, load_signer};
+use crate::processor::utils::pda::resize_pda;
+use crate::processor::whitelist_validator_for_program::{
+    load_or_create_program_config, validate_authority,
+};
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_set_program_undelegate_discriminator_override]
+pub const SET_PROGRAM_UNDELEGATE_DISCRIMINATOR_OVERRIDE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program data"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("program config"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Set whether the delegations of the accounts of the program can override the discriminator
+/// of its external undelegate instruction
+///
+/// Accounts:
+///
+/// 0: `[signer]`   authority that has rights to configure the program
+/// 1: `[]`         program to configure
+/// 2: `[]`         program data account
+/// 3: `[]`         delegation program data account
+/// 4: `[writable]` program config PDA
+/// 5: `[]`         system program
+///
+/// Requirements:
+///
+/// - authority is either the ADMIN_PUBKEY or the program upgrade authority
+/// - program config is initialized or owned by the system program in
+///   which case it is created
+///
+/// Steps:
+///
+/// 1. Load the authority and validate it
+/// 2. Load the program config or create it and replace the
+///    `allow_undelegate_discriminator_override`, resizing the account if necessary
+///
+/// Once set, the delegations passing the program config can carry the discriminator the
+/// external undelegate CPI uses in place of [crate::consts::EXTERNAL_UNDELEGATE_DISCRIMINATOR],
+/// for programs where it collides with an existing instruction, see
+/// [crate::processor::fast::process_delegate]. The opt-in is required as the CPI is signed by
+/// the undelegate buffer and passes the validator as a signer, which must not reach an
+/// arbitrary instruction of a program that did not expect it.
+pub fn process_set_program_undelegate_discriminator_override(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetProgramUndelegateDiscriminatorOverrideArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, program, program_data, delegation_program_data, program_config_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    validate_authority(authority, program, program_data, delegation_program_data)?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let mut program_config =
+        load_or_create_program_config(authority, program, program_config_account, system_program)?;
+    program_config.allow_undelegate_discriminator_override =
+        args.allow_undelegate_discriminator_override;
+    resize_pda(
+        authority,
+        program_config_account,
+        system_program,
+        program_config.size_with_discriminator(),
+    )?;
+    let mut program_config_data = program_config_account.try_borrow_mut_data()?;
+    program_config.to_bytes_with_discriminator(&mut program_config_data.as_mut())?;
+
+    Ok(())
+}
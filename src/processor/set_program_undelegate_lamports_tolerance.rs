@@ -0,0 +1,83 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::SetProgramUndelegateLamportsToleranceArgs;
+use crate::consts::MAX_UNDELEGATE_LAMPORTS_TOLERANCE;
+use crate::error::DlpError;
+use crate::processor::utils::loaders::{load_program, load_signer};
+use crate::processor::utils::pda::resize_pda;
+use crate::processor::whitelist_validator_for_program::{
+    load_or_create_program_config, validate_authority,
+};
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_set_program_undelegate_lamports_tolerance]
+pub const SET_PROGRAM_UNDELEGATE_LAMPORTS_TOLERANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program data"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("program config"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Set the lamports a program can add to the validator in its external undelegate handler
+///
+/// Accounts:
+///
+/// 0: `[signer]`   authority that has rights to configure the program
+/// 1: `[]`         program to configure
+/// 2: `[]`         program data account
+/// 3: `[]`         delegation program data account
+/// 4: `[writable]` program config PDA
+/// 5: `[]`         system program
+///
+/// Requirements:
+///
+/// - authority is either the ADMIN_PUBKEY or the program upgrade authority
+/// - the tolerance is at most MAX_UNDELEGATE_LAMPORTS_TOLERANCE
+/// - program config is initialized or owned by the system program in
+///   which case it is created
+///
+/// Steps:
+///
+/// 1. Load the authority and validate it
+/// 2. Load the program config or create it and replace the `undelegate_lamports_tolerance`,
+///    resizing the account if necessary
+pub fn process_set_program_undelegate_lamports_tolerance(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetProgramUndelegateLamportsToleranceArgs::try_from_slice(data)?;
+    if args.undelegate_lamports_tolerance > MAX_UNDELEGATE_LAMPORTS_TOLERANCE {
+        return Err(DlpError::UndelegateLamportsToleranceTooHigh.into());
+    }
+
+    // Load Accounts
+    let [authority, program, program_data, delegation_program_data, program_config_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    validate_authority(authority, program, program_data, delegation_program_data)?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let mut program_config =
+        load_or_create_program_config(authority, program, program_config_account, system_program)?;
+    program_config.undelegate_lamports_tolerance = args.undelegate_lamports_tolerance;
+    resize_pda(
+        authority,
+        program_config_account,
+        system_program,
+        program_config.size_with_discriminator(),
+    )?;
+    let mut program_config_data = program_config_account.try_borrow_mut_data()?;
+    program_config.to_bytes_with_discriminator(&mut program_config_data.as_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::SetProtocolConfigArgs;
+use crate::error::DlpError::Unauthorized;
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_pda, load_program, load_program_upgrade_authority, load_signer,
+};
+use crate::processor::utils::pda::{create_pda, resize_pda};
+use crate::protocol_config_seeds;
+use crate::state::ProtocolConfig;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_set_protocol_config]
+pub const SET_PROTOCOL_CONFIG_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::writable("protocol config"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Set the protocol wide parameters
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account that can set the protocol config
+/// 1: `[writable]`         protocol config PDA
+/// 2: `[]`                 delegation program data
+/// 3: `[]`                 system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - protocol config PDA is initialized or owned by the system program in
+///   which case it is created
+///
+/// Steps:
+///
+/// 1. Load the protocol config or create it, resizing it if it has a previous layout
+/// 2. Set the escrow dust threshold, the undelegation grace period and the trusted exporter
+///    of delegation packages, keeping the protocol fees vault generation, only changed by a vault migration, see
+///    [crate::processor::process_propose_protocol_vault_migration]
+pub fn process_set_protocol_config(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetProtocolConfigArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [admin, protocol_config_account, delegation_program_data, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    let protocol_config_bump = load_pda(
+        protocol_config_account,
+        protocol_config_seeds!(),
+        &crate::id(),
+        true,
+        "protocol config",
+    )?;
+
+    // Create the protocol config if it doesn't exist
+    let is_created = protocol_config_account.owner.eq(system_program.key);
+    if is_created {
+        create_pda(
+            protocol_config_account,
+            &crate::id(),
+            ProtocolConfig::size_with_discriminator(),
+            protocol_config_seeds!(),
+            protocol_config_bump,
+            system_program,
+            admin,
+        )?;
+    } else if protocol_config_account.data_len() != ProtocolConfig::size_with_discriminator() {
+        resize_pda(
+            admin,
+            protocol_config_account,
+            system_program,
+            ProtocolConfig::size_with_discriminator(),
+        )?;
+    }
+
+    let mut protocol_config_data = protocol_config_account.try_borrow_mut_data()?;
+    let mut protocol_config = if is_created {
+        ProtocolConfig::default()
+    } else {
+        *ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_data)?
+    };
+    protocol_config.escrow_dust_threshold = args.escrow_dust_threshold;
+    protocol_config.undelegation_grace_slots = args.undelegation_grace_slots;
+    protocol_config.trusted_exporter = args.trusted_exporter;
+    protocol_config.to_bytes_with_discriminator(&mut protocol_config_data)?;
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::args::SetValidatorCommitQuotaArgs;
+use crate::error::DlpError::Unauthorized;
+use crate::processor::utils::loaders::{
+    load_initialized_validator_fees_vault, load_program, load_program_upgrade_authority,
+    load_signer,
+};
+use crate::processor::utils::pda::resize_funded_pda;
+use crate::state::ValidatorFeesVault;
+
+/// Accounts of [process_set_validator_commit_quota]
+pub const SET_VALIDATOR_COMMIT_QUOTA_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("validator identity"),
+    AccountSpec::writable("validator fees vault"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Set the commit quota of a validator, bounding the commits and the bytes of committed
+/// state it pushes to the chain per slot, see [crate::state::CommitQuota]
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account, paying the growth of the vault
+/// 1: `[]`                 delegation program data
+/// 2: `[]`                 the validator identity
+/// 3: `[writable]`         the validator fees vault, storing the commit quota
+/// 4: `[]`                 system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - validator fees vault is initialized, i.e. the validator is whitelisted
+///
+/// Steps:
+///
+/// 1. Set the limits of the commit quota, keeping the counters of the current slot
+/// 2. Resize the vault if necessary, the admin paying the rent of its growth
+///
+/// Once a limit is set, the validator fees vault must be writable in the commits of the
+/// validator, see [crate::processor::fast::process_commit_state].
+pub fn process_set_validator_commit_quota(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetValidatorCommitQuotaArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [admin, delegation_program_data, validator, validator_fees_vault, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    load_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
+
+    let mut vault = {
+        let validator_fees_vault_data = validator_fees_vault.try_borrow_data()?;
+        ValidatorFeesVault::try_from_account_data(&validator_fees_vault_data)?
+    };
+    vault.commit_quota.max_commits_per_slot = args.max_commits_per_slot;
+    vault.commit_quota.max_bytes_per_slot = args.max_bytes_per_slot;
+
+    resize_funded_pda(
+        admin,
+        validator_fees_vault,
+        system_program,
+        vault.size_with_discriminator(),
+    )?;
+
+    let mut validator_fees_vault_data = validator_fees_vault.try_borrow_mut_data()?;
+    vault.to_bytes_with_discriminator(&mut validator_fees_vault_data.as_mut())?;
+
+    Ok(())
+}
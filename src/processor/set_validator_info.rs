@@ -0,0 +1,102 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::SetValidatorInfoArgs;
+use crate::error::DlpError::InvalidValidatorInfo;
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_initialized_validator_fees_vault, load_pda, load_program, load_signer,
+};
+use crate::processor::utils::pda::{create_pda, resize_pda};
+use crate::state::ValidatorInfo;
+use crate::validator_info_seeds_from_validator;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_set_validator_info]
+pub const SET_VALIDATOR_INFO_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator identity"),
+    AccountSpec::readonly("validator fees vault"),
+    AccountSpec::writable("validator info"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Set the info of a validator, binding its ephemeral rollup endpoint to its identity
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the validator identity
+/// 1: `[]`         the validator fees vault
+/// 2: `[writable]` the validator info PDA
+/// 3: `[]`         the system program
+///
+/// Requirements:
+///
+/// - validator fees vault is initialized, i.e. the validator is whitelisted
+/// - endpoint is not empty and the fields fit in their maximum lengths
+/// - validator info is initialized or owned by the system program in
+///   which case it is created
+///
+/// Steps:
+///
+/// 1. Load the validator info or create it
+/// 2. Replace the validator info, resizing the account if necessary
+pub fn process_set_validator_info(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetValidatorInfoArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [validator, validator_fees_vault, validator_info_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    load_initialized_validator_fees_vault(validator, validator_fees_vault, false)?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let validator_info = ValidatorInfo {
+        endpoint: args.endpoint,
+        version: args.version,
+        cluster_id: args.cluster_id,
+    };
+    if !validator_info.is_valid() {
+        msg!("Invalid validator info: {:?}", validator_info);
+        return Err(InvalidValidatorInfo.into());
+    }
+
+    let validator_info_bump = load_pda(
+        validator_info_account,
+        validator_info_seeds_from_validator!(validator.key),
+        &crate::id(),
+        true,
+        "validator info",
+    )?;
+
+    // Create the validator info if it doesn't exist
+    if validator_info_account.owner.eq(system_program.key) {
+        create_pda(
+            validator_info_account,
+            &crate::id(),
+            0, // It will be resized later to the proper size
+            validator_info_seeds_from_validator!(validator.key),
+            validator_info_bump,
+            system_program,
+            validator,
+        )?;
+    }
+
+    resize_pda(
+        validator,
+        validator_info_account,
+        system_program,
+        validator_info.size_with_discriminator(),
+    )?;
+    let mut validator_info_data = validator_info_account.try_borrow_mut_data()?;
+    validator_info.to_bytes_with_discriminator(&mut validator_info_data.as_mut())?;
+
+    Ok(())
+}
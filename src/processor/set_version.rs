@@ -0,0 +1,121 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::SetVersionArgs;
+use crate::discriminator::DlpDiscriminator;
+use crate::error::DlpError::{InvalidDiscriminatorRange, Unauthorized};
+use crate::log::msg;
+use crate::processor::utils::loaders::{
+    load_pda, load_program, load_program_upgrade_authority, load_signer,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::program_version_seeds;
+use crate::state::ProgramVersion;
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_set_version]
+pub const SET_VERSION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("admin"),
+    AccountSpec::writable("program version"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Set the version of the deployed delegation program, meant to be called at each deploy
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` admin account that can set the program version
+/// 1: `[writable]`         program version PDA
+/// 2: `[]`                 delegation program data
+/// 3: `[]`                 system program
+///
+/// Requirements:
+///
+/// - admin is the delegation program upgrade authority
+/// - program version PDA is initialized or owned by the system program in
+///   which case it is created
+/// - the range of supported discriminators is not empty and contains valid discriminators
+///
+/// Steps:
+///
+/// 1. Load the program version or create it
+/// 2. Set the version, the git hash, the supported discriminators and the current slot
+pub fn process_set_version(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SetVersionArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [admin, program_version_account, delegation_program_data, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(admin, "admin")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    // Check if the admin is the correct one
+    let admin_pubkey =
+        load_program_upgrade_authority(&crate::ID, delegation_program_data)?.ok_or(Unauthorized)?;
+    if !admin.key.eq(&admin_pubkey) {
+        msg!(
+            "Expected admin pubkey: {} but got {}",
+            admin_pubkey,
+            admin.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    if args.min_discriminator > args.max_discriminator
+        || DlpDiscriminator::try_from(args.min_discriminator).is_err()
+        || DlpDiscriminator::try_from(args.max_discriminator).is_err()
+    {
+        msg!(
+            "Invalid discriminator range: {}..={}",
+            args.min_discriminator,
+            args.max_discriminator
+        );
+        return Err(InvalidDiscriminatorRange.into());
+    }
+
+    let program_version_bump = load_pda(
+        program_version_account,
+        program_version_seeds!(),
+        &crate::id(),
+        true,
+        "program version",
+    )?;
+
+    // Create the program version if it doesn't exist
+    if program_version_account.owner.eq(system_program.key) {
+        create_pda(
+            program_version_account,
+            &crate::id(),
+            ProgramVersion::size_with_discriminator(),
+            program_version_seeds!(),
+            program_version_bump,
+            system_program,
+            admin,
+        )?;
+    }
+
+    let program_version = ProgramVersion {
+        major: args.major,
+        minor: args.minor,
+        patch: args.patch,
+        git_hash: args.git_hash,
+        min_discriminator: args.min_discriminator,
+        max_discriminator: args.max_discriminator,
+        slot: Clock::get()?.slot,
+    };
+    let mut program_version_data = program_version_account.try_borrow_mut_data()?;
+    program_version.to_bytes_with_discriminator(&mut program_version_data.as_mut())?;
+
+    Ok(())
+}
@@ -0,0 +1,322 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use alloc::vec::Vec;
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_instruction,
+    system_program,
+};
+
+use crate::args::{SplitDelegationArgs, MAX_SEEDS};
+use crate::error::DlpError;
+use crate::processor::utils::curve::is_on_curve_fast;
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_program, load_signer, load_uninitialized_account,
+    load_uninitialized_pda,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::processor::utils::protocol_stats::record_tvl_change;
+use crate::state::{DelegationMetadata, DelegationRecord};
+use crate::{
+    commit_record_seeds_from_delegated_account, commit_state_seeds_from_delegated_account,
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_split_delegation]
+pub const SPLIT_DELEGATION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::writable("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("commit state"),
+    AccountSpec::readonly("commit record"),
+    AccountSpec::signer("new delegated account"),
+    AccountSpec::writable("new delegation record"),
+    AccountSpec::writable("new delegation metadata"),
+    AccountSpec::readonly("owner program"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Split a delegated account into two delegated accounts
+///
+/// Accounts:
+///
+///  0: `[signer]`   the validator, authority of the delegation, paying for the new PDAs
+///  1: `[writable]` the delegated account to split
+///  2: `[]`         the delegation record of the delegated account
+///  3: `[]`         the delegation metadata of the delegated account
+///  4: `[]`         the commit state PDA of the delegated account
+///  5: `[]`         the commit record PDA of the delegated account
+///  6: `[signer]`   the new delegated account
+///  7: `[writable]` the delegation record of the new delegated account
+///  8: `[writable]` the delegation metadata of the new delegated account
+///  9: `[]`         the owner program of the delegated account
+/// 10: `[]`         the system program
+/// 11: `[writable]` (optional) the protocol stats PDA, adding the lamports of the new
+///                  delegated account to the total value locked
+///
+/// Requirements:
+///
+/// - validator is the authority in the delegation record
+/// - delegated account is owned by the delegation program and is not undelegatable
+/// - there is no pending commit for the delegated account
+/// - new delegated account is uninitialized and signs, which is only possible if the owner
+///   program approves the split via CPI
+/// - new delegated account is a PDA derived from the seeds and the owner program, an on curve
+///   account signing without the approval of the owner program
+/// - ranges are sorted, non-empty, non-overlapping and within the delegated account data
+///
+/// Steps:
+///
+/// 1. Move the bytes in the given ranges into the new delegated account (owned by the
+///    delegation program)
+/// 2. Create the delegation record and metadata of the new delegated account, inheriting
+///    the authority, owner and commit frequency from the original delegation. The new
+///    delegation has no commit yet, so that its commit timeout starts at the split
+/// 3. Shrink the original delegated account to the remaining bytes
+/// 4. Add the lamports of the new delegated account to the total value locked
+pub fn process_split_delegation(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = SplitDelegationArgs::try_from_slice(data)?;
+
+    let [validator, delegated_account, delegation_record_account, delegation_metadata_account, commit_state_account, commit_record_account, new_delegated_account, new_delegation_record_account, new_delegation_metadata_account, owner_program, system_program, remaining_accounts @ ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let protocol_stats = remaining_accounts.first();
+
+    load_signer(validator, "validator")?;
+    load_signer(new_delegated_account, "new delegated account")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation metadata",
+    )?;
+
+    // Make sure there is no pending commit, which would restore the unsplit data on finalize
+    load_uninitialized_pda(
+        commit_state_account,
+        commit_state_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit state",
+    )?;
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    if !delegation_record.authority.eq(validator.key) {
+        msg!(
+            "Expected delegation authority to be {}, but got {}",
+            delegation_record.authority,
+            validator.key
+        );
+        return Err(DlpError::InvalidAuthority.into());
+    }
+    if !delegation_record.owner.eq(owner_program.key) {
+        msg!(
+            "Expected delegation record owner to be {}, but got {}",
+            delegation_record.owner,
+            owner_program.key
+        );
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    let delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    if delegation_metadata.is_undelegatable {
+        msg!(
+            "Delegated account {} is undelegatable",
+            delegated_account.key
+        );
+        return Err(DlpError::AlreadyUndelegated.into());
+    }
+
+    // Check the new delegation PDAs
+    load_uninitialized_account(new_delegated_account, true, "new delegated account")?;
+    let new_delegation_record_bump = load_uninitialized_pda(
+        new_delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(new_delegated_account.key),
+        &crate::id(),
+        true,
+        "new delegation record",
+    )?;
+    let new_delegation_metadata_bump = load_uninitialized_pda(
+        new_delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(new_delegated_account.key),
+        &crate::id(),
+        true,
+        "new delegation metadata",
+    )?;
+
+    // The new delegated account must be a PDA of the owner program, whose signature is the
+    // approval of the split by the owner program via CPI. An on curve account signs without it
+    if is_on_curve_fast(new_delegated_account.key.as_array()) {
+        msg!(
+            "New delegated account {} must be a PDA of the owner program",
+            new_delegated_account.key
+        );
+        return Err(DlpError::SplitNotApprovedByOwner.into());
+    }
+    if args.seeds.is_empty() || args.seeds.len() > MAX_SEEDS {
+        return Err(DlpError::TooManySeeds.into());
+    }
+    let seeds: Vec<&[u8]> = args.seeds.iter().collect();
+    let derived_pda = Pubkey::try_find_program_address(&seeds, owner_program.key)
+        .ok_or(ProgramError::InvalidSeeds)?
+        .0;
+    if !derived_pda.eq(new_delegated_account.key) {
+        msg!(
+            "Expected new delegated PDA to be {}, but got {}",
+            derived_pda,
+            new_delegated_account.key
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Split the data according to the ranges
+    let Some((new_data, remaining_data)) = args.split(&delegated_account.try_borrow_data()?) else {
+        msg!("Invalid split ranges {:?}", args.ranges);
+        return Err(DlpError::InvalidSplitRanges.into());
+    };
+
+    // Create the new delegated account, owned by the delegation program. The account already
+    // carries the signature, so no signer seeds are needed.
+    let new_account_lamports = Rent::get()?.minimum_balance(new_data.len());
+    if new_delegated_account.lamports() == 0 {
+        invoke(
+            &system_instruction::create_account(
+                validator.key,
+                new_delegated_account.key,
+                new_account_lamports,
+                new_data.len() as u64,
+                &crate::id(),
+            ),
+            &[
+                validator.clone(),
+                new_delegated_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    } else {
+        let rent_exempt_balance =
+            new_account_lamports.saturating_sub(new_delegated_account.lamports());
+        if rent_exempt_balance > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    validator.key,
+                    new_delegated_account.key,
+                    rent_exempt_balance,
+                ),
+                &[
+                    validator.clone(),
+                    new_delegated_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        invoke(
+            &system_instruction::allocate(new_delegated_account.key, new_data.len() as u64),
+            &[new_delegated_account.clone(), system_program.clone()],
+        )?;
+        invoke(
+            &system_instruction::assign(new_delegated_account.key, &crate::id()),
+            &[new_delegated_account.clone(), system_program.clone()],
+        )?;
+    }
+    (*new_delegated_account.try_borrow_mut_data()?).copy_from_slice(&new_data);
+
+    // Initialize the delegation record of the new delegated account
+    create_pda(
+        new_delegation_record_account,
+        &crate::id(),
+        DelegationRecord::size_with_discriminator(),
+        delegation_record_seeds_from_delegated_account!(new_delegated_account.key),
+        new_delegation_record_bump,
+        system_program,
+        validator,
+    )?;
+    let new_delegation_record = DelegationRecord {
+        authority: delegation_record.authority,
+        owner: delegation_record.owner,
+        delegation_slot: Clock::get()?.slot,
+        lamports: new_delegated_account.lamports(),
+        commit_frequency_ms: delegation_record.commit_frequency_ms,
+        version: DelegationRecord::VERSION,
+        padding: [0; 7],
+    };
+    let mut new_delegation_record_data = new_delegation_record_account.try_borrow_mut_data()?;
+    new_delegation_record.to_bytes_with_discriminator(&mut new_delegation_record_data)?;
+
+    // Initialize the delegation metadata of the new delegated account
+    let new_delegation_metadata = DelegationMetadata {
+        last_update_nonce: 0,
+        is_undelegatable: false,
+        seeds: args.seeds,
+        rent_payer: *validator.key,
+        close_destination: None,
+        extended_undelegate_payload: delegation_metadata.extended_undelegate_payload,
+        undelegation_request: None,
+        seed_template: None,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: None,
+        undelegate_discriminator: delegation_metadata.undelegate_discriminator,
+        heartbeat_timeout_slots: delegation_metadata.heartbeat_timeout_slots,
+        max_account_size: delegation_metadata.max_account_size,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: None,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
+    };
+    create_pda(
+        new_delegation_metadata_account,
+        &crate::id(),
+        new_delegation_metadata.serialized_size(),
+        delegation_metadata_seeds_from_delegated_account!(new_delegated_account.key),
+        new_delegation_metadata_bump,
+        system_program,
+        validator,
+    )?;
+    let mut new_delegation_metadata_data = new_delegation_metadata_account.try_borrow_mut_data()?;
+    new_delegation_metadata
+        .to_bytes_with_discriminator(&mut new_delegation_metadata_data.as_mut())?;
+
+    // Shrink the original delegated account to the remaining bytes
+    delegated_account.realloc(remaining_data.len(), false)?;
+    (*delegated_account.try_borrow_mut_data()?).copy_from_slice(&remaining_data);
+
+    record_tvl_change(protocol_stats, 0, new_delegation_record.lamports)
+}
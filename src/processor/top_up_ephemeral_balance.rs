@@ -1,3 +1,4 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
 use crate::args::TopUpEphemeralBalanceArgs;
 use crate::ephemeral_balance_seeds_from_payer;
 use crate::processor::utils::loaders::{load_pda, load_program, load_signer};
@@ -10,6 +11,14 @@ use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
 };
 
+/// Accounts of [process_top_up_ephemeral_balance]
+pub const TOP_UP_EPHEMERAL_BALANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable("payer"),
+    AccountSpec::readonly("pubkey"),
+    AccountSpec::writable("ephemeral balance"),
+    AccountSpec::readonly("system program"),
+];
+
 /// Tops up the ephemeral balance account.
 ///
 /// Accounts:
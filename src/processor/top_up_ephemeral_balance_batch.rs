@@ -0,0 +1,114 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::{
+    EphemeralBalanceIndex, TopUpEphemeralBalanceBatchArgs, MAX_TOP_UP_BATCH_ESCROWS,
+};
+use crate::error::DlpError::TooManyEscrowsInBatch;
+use crate::pda::EPHEMERAL_BALANCE_TAG;
+use crate::processor::utils::loaders::{load_pda, load_program, load_signer};
+use crate::processor::utils::pda::create_pda;
+use borsh::BorshDeserialize;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::system_instruction::transfer;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_top_up_ephemeral_balance_batch]
+pub const TOP_UP_EPHEMERAL_BALANCE_BATCH_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("payer"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Tops up several ephemeral balance accounts from a single payer.
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` payer account who funds the topups
+/// 1: `[]`                 system program
+/// 2..: for every entry of the batch, in order:
+///    - `[]`         pubkey account that the ephemeral balance PDA was derived from
+///    - `[writable]` ephemeral balance account to top up
+///
+/// Requirements:
+///
+/// - at most [MAX_TOP_UP_BATCH_ESCROWS] escrows are topped up
+/// - every ephemeral balance account is derived from its pubkey and the index of its entry,
+///   narrow indices deriving the escrows of [crate::processor::process_top_up_ephemeral_balance]
+/// - the payer account has enough lamports to fund the transfers
+///
+/// Steps:
+///
+/// For every entry:
+///
+/// 1. Create the ephemeral balance PDA if it does not exist
+/// 2. Transfer lamports from payer to ephemeral PDA
+pub fn process_top_up_ephemeral_balance_batch(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args.
+    let args = TopUpEphemeralBalanceBatchArgs::try_from_slice(data)?;
+    if args.entries.len() > MAX_TOP_UP_BATCH_ESCROWS {
+        return Err(TooManyEscrowsInBatch.into());
+    }
+
+    // Load Accounts
+    let [payer, system_program, escrow_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if escrow_accounts.len() != args.entries.len() * 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    load_signer(payer, "payer")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    for (entry, accounts) in args.entries.iter().zip(escrow_accounts.chunks_exact(2)) {
+        let [pubkey, ephemeral_balance_account] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let index = match entry.index {
+            EphemeralBalanceIndex::Narrow(index) => vec![index],
+            EphemeralBalanceIndex::Wide(index) => index.to_le_bytes().to_vec(),
+        };
+        let ephemeral_balance_seeds: &[&[u8]] =
+            &[EPHEMERAL_BALANCE_TAG, pubkey.key.as_ref(), &index];
+        let bump_ephemeral_balance = load_pda(
+            ephemeral_balance_account,
+            ephemeral_balance_seeds,
+            &crate::id(),
+            true,
+            "ephemeral balance",
+        )?;
+
+        // Create the ephemeral balance PDA if it does not exist
+        if ephemeral_balance_account.owner.eq(&system_program::id()) {
+            create_pda(
+                ephemeral_balance_account,
+                &system_program::id(),
+                0,
+                ephemeral_balance_seeds,
+                bump_ephemeral_balance,
+                system_program,
+                payer,
+            )?;
+        }
+
+        // Transfer lamports from payer to ephemeral PDA (with a system program call)
+        if entry.amount > 0 {
+            invoke(
+                &transfer(payer.key, ephemeral_balance_account.key, entry.amount),
+                &[
+                    payer.clone(),
+                    ephemeral_balance_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+    }
+
+    Ok(())
+}
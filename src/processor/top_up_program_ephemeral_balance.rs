@@ -0,0 +1,90 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::TopUpProgramEphemeralBalanceArgs;
+use crate::processor::utils::loaders::{load_pda, load_program, load_signer};
+use crate::processor::utils::pda::create_pda;
+use crate::program_ephemeral_balance_seeds_from_payer;
+use borsh::BorshDeserialize;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::system_instruction::transfer;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_top_up_program_ephemeral_balance]
+pub const TOP_UP_PROGRAM_EPHEMERAL_BALANCE_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable("payer"),
+    AccountSpec::readonly("pubkey"),
+    AccountSpec::writable("program ephemeral balance"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Tops up an ephemeral balance account scoped to a single program.
+///
+/// Accounts:
+///
+/// 0: `[writable]` payer account who funds the topup
+/// 1: `[]` pubkey account that the ephemeral balance PDA was derived from
+/// 2: `[writable]` program ephemeral balance account to top up
+/// 3: `[]` system program
+///
+/// Requirements:
+///
+/// - the payer account has enough lamports to fund the transfer
+///
+/// Steps:
+///
+/// 1. Create the program ephemeral balance PDA if it does not exist
+/// 2. Transfer lamports from payer to the program ephemeral PDA
+pub fn process_top_up_program_ephemeral_balance(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Parse args.
+    let args = TopUpProgramEphemeralBalanceArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [payer, pubkey, ephemeral_balance_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(payer, "payer")?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let bump_ephemeral_balance = load_pda(
+        ephemeral_balance_account,
+        program_ephemeral_balance_seeds_from_payer!(pubkey.key, args.program_id, args.index),
+        &crate::id(),
+        true,
+        "program ephemeral balance",
+    )?;
+
+    // Create the program ephemeral balance PDA if it does not exist
+    if ephemeral_balance_account.owner.eq(&system_program::id()) {
+        create_pda(
+            ephemeral_balance_account,
+            &system_program::id(),
+            0,
+            program_ephemeral_balance_seeds_from_payer!(pubkey.key, args.program_id, args.index),
+            bump_ephemeral_balance,
+            system_program,
+            payer,
+        )?;
+    }
+
+    // Transfer lamports from payer to the program ephemeral PDA (with a system program call)
+    if args.amount > 0 {
+        let transfer_instruction = transfer(payer.key, ephemeral_balance_account.key, args.amount);
+        invoke(
+            &transfer_instruction,
+            &[
+                payer.clone(),
+                ephemeral_balance_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::BorshDeserialize;
+use solana_program::clock::Clock;
+use solana_program::program::invoke_signed;
+use solana_program::program_error::ProgramError;
+use solana_program::stake::{self, state::StakeAuthorize};
+use solana_program::sysvar::{self, Sysvar};
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::args::UndelegateStakeAccountArgs;
+use crate::error::DlpError::{InvalidDelegatedAccount, InvalidStakeState, Unauthorized};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_pda, load_program, load_signer, load_sysvar,
+};
+use crate::processor::utils::pda::close_pda;
+use crate::state::{DelegationMetadata, DelegationRecord, NativeStakeState};
+use crate::{
+    delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account, stake_authority_seeds_from_stake_account,
+};
+
+/// Accounts of [process_undelegate_stake_account]
+pub const UNDELEGATE_STAKE_ACCOUNT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::writable("stake account"),
+    AccountSpec::readonly("stake authority PDA"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::writable("delegation metadata"),
+    AccountSpec::writable("stake authority"),
+    AccountSpec::readonly("stake program"),
+    AccountSpec::readonly("clock sysvar"),
+];
+
+/// Undelegate a native stake account, settling its state in the ephemeral rollup and handing
+/// its authorities back to its stake authority
+///
+/// The stake account being owned by the stake program, its state is not committed but
+/// settled by this instruction, through the stake instructions signed by the stake authority
+/// PDA of the account, see [crate::processor::process_delegate_stake_account].
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the validator of the delegation, or the stake authority once the commit
+///                 timeout of the delegation elapsed
+/// 1: `[writable]` the stake account
+/// 2: `[]`         the stake authority PDA of the stake account
+/// 3: `[writable]` the delegation record
+/// 4: `[writable]` the delegation metadata
+/// 5: `[writable]` the stake authority, the rent payer of the delegation
+/// 6: `[]`         the stake program
+/// 7: `[]`         the clock sysvar
+///
+/// Requirements:
+///
+/// - delegation record and delegation metadata are initialized, the owner of the delegation
+///   being the stake program
+/// - stake authority is the rent payer of the delegation
+/// - signer is the validator of the delegation, or the stake authority if the validator did
+///   not settle the account within the commit timeout of the delegation, see
+///   [DelegationRecord::is_commit_timed_out]
+/// - settled stake state is valid and only deactivates the stake of the account, if it was
+///   not deactivated, see [NativeStakeState::is_settleable_from]
+///
+/// Steps:
+///
+/// 1. Deactivate the stake of the account if the settled state deactivated it
+/// 2. Authorize the stake authority back as the staker and the withdrawer of the account
+/// 3. Close the delegation record and the delegation metadata, refunding the stake authority
+pub fn process_undelegate_stake_account(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = UndelegateStakeAccountArgs::try_from_slice(data)?;
+
+    let [signer, stake_account, stake_authority_pda, delegation_record_account, delegation_metadata_account, authority, stake_program, clock_sysvar] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(signer, "signer")?;
+    load_program(stake_program, stake::program::id(), "stake program")?;
+    load_sysvar(clock_sysvar, sysvar::clock::id())?;
+    load_owned_pda(stake_account, &stake::program::id(), "stake account")?;
+    let stake_authority_seeds: &[&[u8]] =
+        stake_authority_seeds_from_stake_account!(stake_account.key);
+    let stake_authority_bump = load_pda(
+        stake_authority_pda,
+        stake_authority_seeds,
+        &crate::id(),
+        false,
+        "stake authority",
+    )?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(stake_account.key),
+        &crate::id(),
+        true,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(stake_account.key),
+        &crate::id(),
+        true,
+        "delegation metadata",
+    )?;
+
+    let delegation_record = {
+        let delegation_record_data = delegation_record_account.try_borrow_data()?;
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?
+    };
+    if !delegation_record.owner.eq(&stake::program::id()) {
+        msg!("{} is not a delegated stake account", stake_account.key);
+        return Err(InvalidDelegatedAccount.into());
+    }
+    let delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    if !delegation_metadata.rent_payer.eq(authority.key) {
+        msg!(
+            "Expected stake authority to be {}, but got {}",
+            delegation_metadata.rent_payer,
+            authority.key
+        );
+        return Err(Unauthorized.into());
+    }
+
+    // The stake authority can only take the account back from a validator gone offline
+    let clock = Clock::get()?;
+    let is_validator = delegation_record.authority.eq(signer.key);
+    let is_timed_out_authority = authority.key.eq(signer.key)
+        && delegation_record.is_commit_timed_out(delegation_metadata.last_commit_slot, clock.slot);
+    if !is_validator && !is_timed_out_authority {
+        msg!("{} cannot undelegate the stake account", signer.key);
+        return Err(Unauthorized.into());
+    }
+
+    // Check the settled state
+    let deactivates = {
+        let stake_account_data = stake_account.try_borrow_data()?;
+        let (Some(stake_state), Some(settled_stake_state)) = (
+            NativeStakeState::try_from_data(&stake_account_data),
+            NativeStakeState::try_from_data(&args.stake_state),
+        ) else {
+            msg!("Invalid layout of the settled stake state");
+            return Err(InvalidStakeState.into());
+        };
+        if !settled_stake_state.is_settleable_from(stake_state) {
+            msg!("Settled stake state does not only deactivate the stake");
+            return Err(InvalidStakeState.into());
+        }
+        settled_stake_state.deactivates_from(stake_state)
+    };
+
+    let stake_authority_bump_slice: &[u8] = &[stake_authority_bump];
+    let stake_authority_signer_seeds =
+        [stake_authority_seeds, &[stake_authority_bump_slice]].concat();
+    if deactivates {
+        invoke_signed(
+            &stake::instruction::deactivate_stake(stake_account.key, stake_authority_pda.key),
+            &[
+                stake_account.clone(),
+                clock_sysvar.clone(),
+                stake_authority_pda.clone(),
+                stake_program.clone(),
+            ],
+            &[&stake_authority_signer_seeds],
+        )?;
+    }
+
+    // Hand the authorities of the stake account back to the stake authority
+    for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+        invoke_signed(
+            &stake::instruction::authorize(
+                stake_account.key,
+                stake_authority_pda.key,
+                authority.key,
+                stake_authorize,
+                None,
+            ),
+            &[
+                stake_account.clone(),
+                clock_sysvar.clone(),
+                stake_authority_pda.clone(),
+                stake_program.clone(),
+            ],
+            &[&stake_authority_signer_seeds],
+        )?;
+    }
+
+    close_pda(delegation_record_account, authority)?;
+    close_pda(delegation_metadata_account, authority)?;
+
+    Ok(())
+}
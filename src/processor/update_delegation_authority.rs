@@ -0,0 +1,145 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use borsh::{to_vec, BorshDeserialize};
+use solana_program::log::sol_log_data;
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+use crate::args::UpdateDelegationAuthorityArgs;
+use crate::error::DlpError::{AlreadyUndelegated, InvalidAuthority};
+use crate::events::{DelegationAuthorityUpdatedEvent, EventDiscriminator};
+use crate::processor::utils::loaders::{
+    load_initialized_pda, load_owned_pda, load_signer, load_uninitialized_pda,
+};
+use crate::state::{DelegationMetadata, DelegationRecord};
+use crate::{
+    commit_record_seeds_from_delegated_account, delegation_metadata_seeds_from_delegated_account,
+    delegation_record_seeds_from_delegated_account,
+};
+
+/// Accounts of [process_update_delegation_authority]
+pub const UPDATE_DELEGATION_AUTHORITY_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("commit record"),
+];
+
+/// Hand the authority of a delegation to another validator, without undelegating the account
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the authority of the delegation, or any signer if the delegated account
+///                 signs
+/// 1: `[]`         the delegated account, signing if the owner program updates the authority
+/// 2: `[writable]` the delegation record
+/// 3: `[]`         the delegation metadata
+/// 4: `[]`         the commit record PDA
+///
+/// Requirements:
+///
+/// - signer is the authority of the delegation record, or the delegated account signs,
+///   which is only possible if the owner program updates the authority via CPI (or if the
+///   delegated account is on curve)
+/// - delegated account is owned by the delegation program and is NOT undelegatable
+/// - delegation record and delegation metadata are initialized
+/// - commit record is uninitialized, so that the pending commit of the previous authority
+///   is finalized first
+/// - new authority is not the authority of the delegation record
+///
+/// Steps:
+///
+/// 1. Rewrite the authority of the delegation record
+/// 2. Emit a [DelegationAuthorityUpdatedEvent]
+///
+/// The new authority must be whitelisted by the program config of the owner program, if
+/// any, for its commits to be accepted, see [crate::processor::fast::process_commit_state].
+pub fn process_update_delegation_authority(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = UpdateDelegationAuthorityArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, delegated_account, delegation_record_account, delegation_metadata_account, commit_record_account] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "delegation authority")?;
+    load_owned_pda(delegated_account, &crate::id(), "delegated account")?;
+    load_initialized_pda(
+        delegation_record_account,
+        delegation_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "delegation record",
+    )?;
+    load_initialized_pda(
+        delegation_metadata_account,
+        delegation_metadata_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "delegation metadata",
+    )?;
+    load_uninitialized_pda(
+        commit_record_account,
+        commit_record_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        false,
+        "commit record",
+    )?;
+
+    let delegation_metadata = {
+        let delegation_metadata_data = delegation_metadata_account.try_borrow_data()?;
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)?
+    };
+    if delegation_metadata.is_undelegatable {
+        msg!("{} is undelegatable", delegated_account.key);
+        return Err(AlreadyUndelegated.into());
+    }
+
+    let mut delegation_record_data = delegation_record_account.try_borrow_mut_data()?;
+    let mut delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data)?;
+    if !delegated_account.is_signer && !delegation_record.authority.eq(authority.key) {
+        msg!(
+            "Expected delegation authority to be {}, but got {}",
+            delegation_record.authority,
+            authority.key
+        );
+        return Err(InvalidAuthority.into());
+    }
+    if args.new_authority.eq(&delegation_record.authority) {
+        msg!(
+            "{} is already the authority of the delegation",
+            args.new_authority
+        );
+        return Err(InvalidAuthority.into());
+    }
+
+    let event = DelegationAuthorityUpdatedEvent {
+        delegated_account: *delegated_account.key,
+        old_authority: delegation_record.authority,
+        new_authority: args.new_authority,
+    };
+    delegation_record.authority = args.new_authority;
+    delegation_record.to_bytes_with_discriminator(&mut delegation_record_data)?;
+
+    msg!(
+        "Delegation authority of {} updated from {} to {}",
+        event.delegated_account,
+        event.old_authority,
+        event.new_authority
+    );
+    sol_log_data(&[&[
+        vec![EventDiscriminator::DelegationAuthorityUpdated.into()],
+        to_vec(&event)?,
+    ]
+    .concat()]);
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::log::msg;
+use solana_program::instruction::Instruction;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::discriminator::DlpDiscriminator;
+use crate::error::DlpError::InvalidCommitSession;
+
+/// Parse the discriminator of an instruction of the delegation program
+pub(crate) fn dlp_discriminator(ix: &Instruction) -> Option<DlpDiscriminator> {
+    if ix.program_id != crate::id() || ix.data.len() < 8 {
+        return None;
+    }
+    DlpDiscriminator::try_from(*ix.data.first()?).ok()
+}
+
+/// Whether the instruction is a commit which can be part of a commit session
+pub(crate) fn is_session_commit(discriminator: DlpDiscriminator) -> bool {
+    matches!(
+        discriminator,
+        DlpDiscriminator::CommitState
+            | DlpDiscriminator::CommitStateFromBuffer
+            | DlpDiscriminator::CommitDiff
+            | DlpDiscriminator::CommitDiffFromBuffer
+    )
+}
+
+/// Load the account at the given index of a session instruction
+pub(crate) fn session_account(ix: &Instruction, index: usize) -> Result<Pubkey, ProgramError> {
+    match ix.accounts.get(index) {
+        Some(meta) => Ok(meta.pubkey),
+        None => {
+            msg!("Commit session instruction is missing accounts");
+            Err(InvalidCommitSession.into())
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use solana_program::ed25519_program;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+
+/// The offset in the Ed25519 program instruction data of the offsets of its first
+/// signature, after the number of signatures and a padding byte
+const ED25519_OFFSETS_START: usize = 2;
+
+/// The instruction index of the Ed25519 offsets pointing into the instruction itself
+const ED25519_CURRENT_INSTRUCTION: usize = u16::MAX as usize;
+
+/// Returns the public key and the message of the signature verified by an Ed25519 program
+/// instruction, if it verifies a single signature whose public key, signature and message
+/// are all held by the instruction itself.
+///
+/// Since the Ed25519 program fails the transaction on an invalid signature, the message
+/// is signed by the public key once the instruction is in the transaction.
+pub(crate) fn ed25519_verified_message(ix: &Instruction) -> Option<(Pubkey, &[u8])> {
+    if ix.program_id != ed25519_program::id() || ix.data.first() != Some(&1) {
+        return None;
+    }
+    let offset = |index: usize| {
+        let start = ED25519_OFFSETS_START + 2 * index;
+        let bytes = ix.data.get(start..start + 2)?;
+        Some(u16::from_le_bytes(bytes.try_into().ok()?) as usize)
+    };
+    // The offsets are the signature, the public key and the message ones, each followed by
+    // the index of the instruction holding them
+    let signature_ix = offset(1)?;
+    let (public_key_offset, public_key_ix) = (offset(2)?, offset(3)?);
+    let (message_offset, message_len, message_ix) = (offset(4)?, offset(5)?, offset(6)?);
+    if signature_ix != ED25519_CURRENT_INSTRUCTION
+        || public_key_ix != ED25519_CURRENT_INSTRUCTION
+        || message_ix != ED25519_CURRENT_INSTRUCTION
+    {
+        return None;
+    }
+
+    let public_key = ix.data.get(public_key_offset..public_key_offset + 32)?;
+    let message = ix.data.get(message_offset..message_offset + message_len)?;
+    Some((Pubkey::try_from(public_key).ok()?, message))
+}
@@ -1,10 +1,18 @@
-use crate::error::DlpError::InvalidAuthority;
+use crate::consts::FEATURE_GATES_PDA;
+use crate::discriminator::DlpDiscriminator;
+use crate::error::DlpError::{InstructionDisabled, InvalidAuthority, RetiredProtocolFeesVault};
+use crate::log::msg;
 use crate::pda::validator_fees_vault_pda_from_validator;
-use crate::{fees_vault_seeds, validator_fees_vault_seeds_from_validator};
+use crate::state::{
+    fees_vault_generation, FeatureGates, ValidatorFeesVault, RETIRED_FEES_VAULT_GENERATION,
+};
+use crate::{
+    fees_vault_seeds, fees_vault_seeds_from_generation, validator_fees_vault_seeds_from_validator,
+};
 use solana_program::bpf_loader_upgradeable::UpgradeableLoaderState;
 use solana_program::{
-    account_info::AccountInfo, bpf_loader_upgradeable, msg, program_error::ProgramError,
-    pubkey::Pubkey, system_program, sysvar,
+    account_info::AccountInfo, bpf_loader_upgradeable, program_error::ProgramError, pubkey::Pubkey,
+    system_program, sysvar,
 };
 
 /// Errors if:
@@ -226,19 +234,41 @@ pub fn load_program_upgrade_authority(
 }
 
 /// Load fee vault PDA
-/// - Protocol fees vault PDA
+/// - Protocol fees vault PDA must be derived from the generation stored in its data
+/// - Protocol fees vault must not be retired by a migration
+///
+/// Returns the generation of the vault
 pub fn load_initialized_protocol_fees_vault(
     fees_vault: &AccountInfo,
     is_writable: bool,
-) -> Result<(), ProgramError> {
-    load_initialized_pda(
-        fees_vault,
-        fees_vault_seeds!(),
-        &crate::id(),
-        is_writable,
-        "protocol fees vault",
-    )?;
-    Ok(())
+) -> Result<u64, ProgramError> {
+    // A vault not owned by the program is rejected as the vault of generation 0
+    let generation = if fees_vault.owner.eq(&crate::id()) {
+        fees_vault_generation(&fees_vault.try_borrow_data()?)
+    } else {
+        0
+    };
+    if generation == RETIRED_FEES_VAULT_GENERATION {
+        msg!("Protocol fees vault {} was retired", fees_vault.key);
+        return Err(RetiredProtocolFeesVault.into());
+    }
+    match generation {
+        0 => load_initialized_pda(
+            fees_vault,
+            fees_vault_seeds!(),
+            &crate::id(),
+            is_writable,
+            "protocol fees vault",
+        )?,
+        generation => load_initialized_pda(
+            fees_vault,
+            fees_vault_seeds_from_generation!(generation),
+            &crate::id(),
+            is_writable,
+            "protocol fees vault",
+        )?,
+    };
+    Ok(generation)
 }
 
 /// Load validator fee vault PDA
@@ -268,6 +298,81 @@ pub fn load_initialized_validator_fees_vault(
     Ok(())
 }
 
+/// Check that the signer of a commit can commit on behalf of the validator identity
+/// - Signer must be the validator identity, or one of its approved commit relayers in which
+///   case the validator fees vault of the validator identity must be passed, see
+///   [crate::state::ValidatorFeesVault]
+pub fn load_commit_authority(
+    signer: &AccountInfo,
+    validator: &Pubkey,
+    validator_fees_vault: Option<&AccountInfo>,
+) -> Result<(), ProgramError> {
+    load_signer(signer, "validator")?;
+    if signer.key.eq(validator) {
+        return Ok(());
+    }
+    let Some(validator_fees_vault) = validator_fees_vault else {
+        msg!(
+            "Expected delegation authority to be {}, but got {}",
+            validator,
+            signer.key
+        );
+        return Err(InvalidAuthority.into());
+    };
+    let pda = validator_fees_vault_pda_from_validator(validator);
+    if !pda.eq(validator_fees_vault.key) {
+        msg!(
+            "Invalid validator fees vault PDA, expected {} but got {}",
+            pda,
+            validator_fees_vault.key
+        );
+        return Err(InvalidAuthority.into());
+    }
+    load_initialized_pda(
+        validator_fees_vault,
+        validator_fees_vault_seeds_from_validator!(validator),
+        &crate::id(),
+        false,
+        "validator fees vault",
+    )?;
+    let validator_fees_vault_data = validator_fees_vault.try_borrow_data()?;
+    let vault = ValidatorFeesVault::try_from_account_data(&validator_fees_vault_data)?;
+    if !vault.is_approved_relayer(signer.key) {
+        msg!(
+            "{} is neither the delegation authority {} nor one of its commit relayers",
+            signer.key,
+            validator
+        );
+        return Err(InvalidAuthority.into());
+    }
+    Ok(())
+}
+
+/// Load the feature gates PDA and check the instruction is enabled
+/// - The feature gates PDA must be the last account, it is split off the returned accounts
+/// - If the feature gates PDA is initialized, the discriminator must be enabled
+pub fn load_enabled_instruction<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    discriminator: DlpDiscriminator,
+) -> Result<&'a [AccountInfo<'info>], ProgramError> {
+    let Some((feature_gates, accounts)) = accounts.split_last() else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !feature_gates.key.eq(&FEATURE_GATES_PDA) {
+        msg!("Invalid feature gates PDA: {}", feature_gates.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if feature_gates.owner.eq(&crate::id()) {
+        let feature_gates_data = feature_gates.try_borrow_data()?;
+        let feature_gates = FeatureGates::try_from_bytes_with_discriminator(&feature_gates_data)?;
+        if !feature_gates.is_enabled(discriminator as u8) {
+            msg!("Instruction {} is disabled", discriminator.name());
+            return Err(InstructionDisabled.into());
+        }
+    }
+    Ok(accounts)
+}
+
 #[cfg(test)]
 mod tests {
     use solana_program::{account_info::AccountInfo, pubkey::Pubkey, system_program};
@@ -1,3 +1,7 @@
+pub(crate) mod commit_session;
 pub(crate) mod curve;
+pub(crate) mod ed25519;
 pub(crate) mod loaders;
 pub(crate) mod pda;
+pub(crate) mod protocol_stats;
+pub(crate) mod summaries;
@@ -1,3 +1,5 @@
+use core::cmp::Ordering;
+
 use solana_program::program::invoke;
 use solana_program::program_error::ProgramError;
 use solana_program::{
@@ -99,6 +101,67 @@ pub(crate) fn resize_pda<'a, 'info>(
     Ok(())
 }
 
+/// Resize a PDA holding lamports on top of its rent, e.g. the fees of a vault, so that
+/// only the difference of rent is paid by the payer, or refunded to it if the PDA shrinks
+pub(crate) fn resize_funded_pda<'a, 'info>(
+    payer: &'a AccountInfo<'info>,
+    pda: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    new_size: usize,
+) -> Result<(), ProgramError> {
+    let rent = Rent::default();
+    let old_rent = rent.minimum_balance(pda.data_len());
+    let new_rent = rent.minimum_balance(new_size);
+    match new_rent.cmp(&old_rent) {
+        Ordering::Greater => invoke(
+            &system_instruction::transfer(payer.key, pda.key, new_rent.saturating_sub(old_rent)),
+            &[payer.clone(), pda.clone(), system_program.clone()],
+        )?,
+        Ordering::Less => {
+            let refund = old_rent.saturating_sub(new_rent);
+            **pda.try_borrow_mut_lamports()? = pda
+                .lamports()
+                .checked_sub(refund)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            **payer.try_borrow_mut_lamports()? = payer
+                .lamports()
+                .checked_add(refund)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+        Ordering::Equal => {}
+    }
+
+    pda.realloc(new_size, false)?;
+    Ok(())
+}
+
+/// Grow a PDA to the new size if it is smaller, the rent of the additional bytes being moved
+/// from another PDA of the program, e.g. one closed in the same instruction
+pub(crate) fn grow_pda_funded_by_pda<'a, 'info>(
+    pda: &'a AccountInfo<'info>,
+    new_size: usize,
+    funding_pda: &'a AccountInfo<'info>,
+) -> Result<(), ProgramError> {
+    if new_size <= pda.data_len() {
+        return Ok(());
+    }
+
+    let lamports = Rent::default()
+        .minimum_balance(new_size)
+        .saturating_sub(pda.lamports());
+    **funding_pda.try_borrow_mut_lamports()? = funding_pda
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **pda.try_borrow_mut_lamports()? = pda
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    pda.realloc(new_size, false)?;
+    Ok(())
+}
+
 /// Close PDA
 #[inline(always)]
 pub(crate) fn close_pda<'a, 'info>(
@@ -109,7 +172,7 @@ pub(crate) fn close_pda<'a, 'info>(
     let dest_starting_lamports = destination.lamports();
     **destination.lamports.borrow_mut() = dest_starting_lamports
         .checked_add(target_account.lamports())
-        .unwrap();
+        .ok_or(ProgramError::ArithmeticOverflow)?;
     **target_account.lamports.borrow_mut() = 0;
 
     target_account.assign(&solana_program::system_program::ID);
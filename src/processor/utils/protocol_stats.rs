@@ -0,0 +1,33 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::processor::utils::loaders::load_initialized_pda;
+use crate::protocol_stats_seeds;
+use crate::state::ProtocolStats;
+
+/// Apply the change of the lamports recorded by a delegation record to the total value
+/// locked of the protocol stats, if passed, as the fast processors do
+pub(crate) fn record_tvl_change(
+    protocol_stats: Option<&AccountInfo>,
+    previous_lamports: u64,
+    lamports: u64,
+) -> ProgramResult {
+    let Some(protocol_stats) = protocol_stats else {
+        return Ok(());
+    };
+    if previous_lamports == lamports {
+        return Ok(());
+    }
+    load_initialized_pda(
+        protocol_stats,
+        protocol_stats_seeds!(),
+        &crate::id(),
+        true,
+        "protocol stats",
+    )?;
+
+    let mut protocol_stats_data = protocol_stats.try_borrow_mut_data()?;
+    ProtocolStats::try_from_bytes_with_discriminator_mut(&mut protocol_stats_data)?
+        .apply_lamports_change(previous_lamports, lamports);
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use core::cell::Ref;
+
+use crate::log::msg;
+use borsh::BorshSerialize;
+use solana_program::program::{set_return_data, MAX_RETURN_DATA};
+use solana_program::program_error::ProgramError;
+use solana_program::{account_info::AccountInfo, pubkey::Pubkey};
+
+/// Read the data of a PDA of the delegation program passed to a summary instruction, None
+/// if it is not initialized. The PDA must be at the expected address, initialized or not.
+pub(crate) fn read_summary_pda<'a>(
+    info: &'a AccountInfo,
+    expected_key: &Pubkey,
+) -> Result<Option<Ref<'a, &'a mut [u8]>>, ProgramError> {
+    if !info.key.eq(expected_key) {
+        msg!("Invalid seeds for account: {}", info.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !info.owner.eq(&crate::id()) || info.data_is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(info.try_borrow_data()?))
+}
+
+/// Set the return data to the summaries, which must fit in it
+pub(crate) fn set_summaries_return_data<T: BorshSerialize>(
+    summaries: &[T],
+) -> Result<(), ProgramError> {
+    let data = borsh::to_vec(summaries)?;
+    if data.len() > MAX_RETURN_DATA {
+        msg!(
+            "{} summaries exceed the return data, request fewer accounts",
+            summaries.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    set_return_data(&data);
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::log::msg;
+use alloc::vec::Vec;
+use solana_program::clock::Clock;
+use solana_program::program::set_return_data;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+use crate::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+};
+use crate::processor::utils::curve::is_on_curve_fast;
+use crate::state::{CommitRecord, DelegationMetadata, DelegationRecord, DelegationViolations};
+
+/// Accounts of [process_validate_delegation]
+pub const VALIDATE_DELEGATION_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::readonly("delegation record"),
+    AccountSpec::readonly("delegation metadata"),
+    AccountSpec::readonly("commit state"),
+    AccountSpec::readonly("commit record"),
+];
+
+/// Check the consistency of the PDAs of a delegation, so that a stuck delegation can be
+/// diagnosed in a single call. The violations found do not fail the instruction, they are
+/// logged and returned instead.
+///
+/// Accounts:
+///
+/// 0: `[]` the delegated account
+/// 1: `[]` the delegation record of the delegated account
+/// 2: `[]` the delegation metadata of the delegated account
+/// 3: `[]` the commit state PDA of the delegated account
+/// 4: `[]` the commit record PDA of the delegated account
+///
+/// Steps:
+///
+/// 1. Check the owner of the delegated account and that every PDA is derived from it
+/// 2. Check the delegated account against its delegation record and metadata: seeds,
+///    lamports and undelegation request
+/// 3. Check the pending commit, if any: both its PDAs exist, it comes from the delegation
+///    authority and its nonce follows the last finalized one
+/// 4. Log the violations and set the return data to the [DelegationViolations] bitmask
+pub fn process_validate_delegation(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    let [delegated_account, delegation_record_account, delegation_metadata_account, commit_state_account, commit_record_account] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let mut violations = DelegationViolations::default();
+
+    if !delegated_account.owner.eq(&crate::id()) {
+        violations.insert(DelegationViolations::DELEGATED_ACCOUNT_OWNER);
+    }
+
+    let delegation_record = read_delegation_pda(
+        delegation_record_account,
+        &delegation_record_pda_from_delegated_account(delegated_account.key),
+        |data| DelegationRecord::try_from_bytes_with_discriminator(data).ok(),
+    );
+    if delegation_record.is_none() {
+        violations.insert(DelegationViolations::DELEGATION_RECORD);
+    }
+
+    let delegation_metadata = read_delegation_pda(
+        delegation_metadata_account,
+        &delegation_metadata_pda_from_delegated_account(delegated_account.key),
+        |data| DelegationMetadata::try_from_bytes_with_discriminator(data).ok(),
+    );
+    if delegation_metadata.is_none() {
+        violations.insert(DelegationViolations::DELEGATION_METADATA);
+    }
+
+    if !commit_state_account
+        .key
+        .eq(&commit_state_pda_from_delegated_account(
+            delegated_account.key,
+        ))
+    {
+        violations.insert(DelegationViolations::COMMIT_STATE);
+    }
+    let commit_record_pda = commit_record_pda_from_delegated_account(delegated_account.key);
+    if !commit_record_account.key.eq(&commit_record_pda) {
+        violations.insert(DelegationViolations::COMMIT_RECORD);
+    }
+
+    // The commit state and record of a pending commit are created and closed together
+    let is_commit_state_initialized = commit_state_account.owner.eq(&crate::id());
+    let is_commit_record_initialized = commit_record_account.owner.eq(&crate::id());
+    if is_commit_state_initialized != is_commit_record_initialized {
+        violations.insert(DelegationViolations::INCOMPLETE_COMMIT);
+    }
+    let commit_record = if is_commit_record_initialized {
+        let commit_record =
+            read_delegation_pda(commit_record_account, &commit_record_pda, |data| {
+                CommitRecord::try_from_bytes_with_discriminator(data)
+                    .ok()
+                    .copied()
+            });
+        if commit_record.is_none() {
+            violations.insert(DelegationViolations::COMMIT_RECORD);
+        }
+        commit_record
+    } else {
+        None
+    };
+
+    if let Some(delegation_record) = delegation_record {
+        if delegated_account.lamports() < delegation_record.lamports {
+            violations.insert(DelegationViolations::LAMPORTS);
+        }
+        if let Some(commit_record) = commit_record {
+            if !commit_record.account.eq(delegated_account.key)
+                || !commit_record.identity.eq(&delegation_record.authority)
+            {
+                violations.insert(DelegationViolations::COMMIT_IDENTITY);
+            }
+        }
+    }
+
+    if let Some(delegation_metadata) = &delegation_metadata {
+        if let Some(delegation_record) = delegation_record {
+            if !is_derived_from_seeds(
+                delegated_account.key,
+                delegation_metadata,
+                &delegation_record,
+            ) {
+                violations.insert(DelegationViolations::SEEDS);
+            }
+        }
+        if let Some(commit_record) = commit_record {
+            if Some(commit_record.sequence_nonce())
+                != delegation_metadata.last_update_nonce.checked_add(1)
+            {
+                violations.insert(DelegationViolations::COMMIT_NONCE);
+            }
+        }
+        if let Some(undelegation_request) = delegation_metadata.undelegation_request {
+            if !delegation_metadata.is_undelegatable
+                && undelegation_request.is_overdue(Clock::get()?.slot)
+            {
+                violations.insert(DelegationViolations::UNDELEGATION_OVERDUE);
+            }
+        }
+    }
+
+    for name in violations.names() {
+        msg!("Delegation violation: {}", name);
+    }
+    set_return_data(&borsh::to_vec(&violations)?);
+
+    Ok(())
+}
+
+/// Read a PDA of the delegation program, returning None if it is not at the expected
+/// address, not owned by the delegation program or cannot be read
+fn read_delegation_pda<T>(
+    info: &AccountInfo,
+    expected_key: &Pubkey,
+    read: impl FnOnce(&[u8]) -> Option<T>,
+) -> Option<T> {
+    if !info.key.eq(expected_key) || !info.owner.eq(&crate::id()) {
+        return None;
+    }
+    read(&info.try_borrow_data().ok()?)
+}
+
+/// Whether the delegated account is derived from the seeds of its delegation, as checked at
+/// delegation. Accounts on curve are not derived from seeds.
+fn is_derived_from_seeds(
+    delegated_account: &Pubkey,
+    delegation_metadata: &DelegationMetadata,
+    delegation_record: &DelegationRecord,
+) -> bool {
+    if is_on_curve_fast(delegated_account.as_array()) {
+        return true;
+    }
+    let seeds = match delegation_metadata.seed_template {
+        Some(seed_template) => {
+            if !delegation_metadata.seeds.is_empty() {
+                return false;
+            }
+            seed_template.resolve(
+                delegation_metadata.rent_payer.as_array(),
+                delegation_record.owner.as_array(),
+            )
+        }
+        None => delegation_metadata.seeds,
+    };
+    let program_id = if delegation_record.owner.eq(&system_program::id()) {
+        crate::id()
+    } else {
+        delegation_record.owner
+    };
+    let seeds: Vec<&[u8]> = seeds.iter().collect();
+    Pubkey::try_find_program_address(&seeds, &program_id)
+        .is_some_and(|(derived, _)| derived.eq(delegated_account))
+}
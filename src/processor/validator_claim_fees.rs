@@ -1,15 +1,23 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
 use crate::args::ValidatorClaimFeesArgs;
 use crate::consts::PROTOCOL_FEES_PERCENTAGE;
 use crate::error::DlpError;
+use crate::log::msg;
 use crate::processor::utils::loaders::{
     load_initialized_protocol_fees_vault, load_initialized_validator_fees_vault, load_signer,
 };
 use borsh::BorshDeserialize;
-use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::rent::Rent;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
 
+/// Accounts of [process_validator_claim_fees]
+pub const VALIDATOR_CLAIM_FEES_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("validator"),
+    AccountSpec::writable("protocol fees vault"),
+    AccountSpec::writable("validator fees vault"),
+];
+
 /// Process validator request to claim fees from the fees vault
 ///
 /// Accounts:
@@ -42,24 +50,26 @@ pub fn process_validator_claim_fees(
     load_initialized_validator_fees_vault(validator, validator_fees_vault, true)?;
 
     // Calculate the amount to transfer
-    let min_rent = Rent::default().minimum_balance(8);
-    let amount = args
-        .amount
-        .unwrap_or(validator_fees_vault.lamports() - min_rent);
+    let min_rent = Rent::default().minimum_balance(validator_fees_vault.data_len());
+    let claimable = validator_fees_vault.lamports().saturating_sub(min_rent);
+    let amount = args.amount.unwrap_or(claimable);
 
     // Ensure vault has enough lamports
-    if validator_fees_vault.lamports() - min_rent < amount {
+    if claimable < amount {
         msg!(
             "Vault ({}) has insufficient funds: {} < {}",
             validator_fees_vault.key,
-            validator_fees_vault.lamports() - min_rent,
+            claimable,
             amount
         );
         return Err(ProgramError::InsufficientFunds);
     }
 
     // Calculate fees and remaining amount
-    let protocol_fees = (amount * u64::from(PROTOCOL_FEES_PERCENTAGE)) / 100;
+    let protocol_fees = amount
+        .checked_mul(u64::from(PROTOCOL_FEES_PERCENTAGE))
+        .ok_or(DlpError::Overflow)?
+        / 100;
     let remaining_amount = amount.saturating_sub(protocol_fees);
 
     // Transfer fees to fees_vault
@@ -0,0 +1,90 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::processor::utils::loaders::{
+    load_initialized_validator_fees_vault, load_pda, load_program, load_signer,
+};
+use crate::processor::utils::pda::create_pda;
+use crate::state::ValidatorLiveness;
+use crate::validator_liveness_seeds_from_validator;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::sysvar::Sysvar;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_validator_heartbeat]
+pub const VALIDATOR_HEARTBEAT_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("validator identity"),
+    AccountSpec::readonly("validator fees vault"),
+    AccountSpec::writable("validator liveness"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Record a heartbeat of a validator, attesting that it is live
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the validator identity
+/// 1: `[]`         the validator fees vault
+/// 2: `[writable]` the validator liveness PDA
+/// 3: `[]`         the system program
+///
+/// Requirements:
+///
+/// - validator fees vault is initialized, i.e. the validator is whitelisted
+/// - validator liveness is initialized or owned by the system program in
+///   which case it is created
+///
+/// Steps:
+///
+/// 1. Load the validator liveness or create it
+/// 2. Record the current slot as the last heartbeat of the validator
+///
+/// The delegations opting into a heartbeat timeout, see
+/// [crate::args::DelegateArgs::heartbeat_timeout_slots], can be undelegated without a
+/// commit of their validator once it did not heartbeat for the timeout, see
+/// [crate::processor::fast::process_undelegate].
+pub fn process_validator_heartbeat(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    // Load Accounts
+    let [validator, validator_fees_vault, validator_liveness_account, system_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(validator, "validator")?;
+    load_initialized_validator_fees_vault(validator, validator_fees_vault, false)?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let validator_liveness_bump = load_pda(
+        validator_liveness_account,
+        validator_liveness_seeds_from_validator!(validator.key),
+        &crate::id(),
+        true,
+        "validator liveness",
+    )?;
+
+    // Create the validator liveness if it doesn't exist
+    if validator_liveness_account.owner.eq(system_program.key) {
+        create_pda(
+            validator_liveness_account,
+            &crate::id(),
+            ValidatorLiveness::size_with_discriminator(),
+            validator_liveness_seeds_from_validator!(validator.key),
+            validator_liveness_bump,
+            system_program,
+            validator,
+        )?;
+    }
+
+    let validator_liveness = ValidatorLiveness {
+        last_heartbeat_slot: Clock::get()?.slot,
+    };
+    let mut validator_liveness_data = validator_liveness_account.try_borrow_mut_data()?;
+    validator_liveness.to_bytes_with_discriminator(&mut validator_liveness_data)?;
+
+    Ok(())
+}
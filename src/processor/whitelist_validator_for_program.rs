@@ -1,5 +1,7 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
 use crate::args::WhitelistValidatorForProgramArgs;
 use crate::error::DlpError::Unauthorized;
+use crate::log::msg;
 use crate::processor::utils::loaders::{
     load_pda, load_program, load_program_upgrade_authority, load_signer,
 };
@@ -7,12 +9,22 @@ use crate::processor::utils::pda::{create_pda, resize_pda};
 use crate::program_config_seeds_from_program_id;
 use crate::state::ProgramConfig;
 use borsh::BorshDeserialize;
-use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
 };
 
+/// Accounts of [process_whitelist_validator_for_program]
+pub const WHITELIST_VALIDATOR_FOR_PROGRAM_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("validator identity"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program data"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("program config"),
+    AccountSpec::readonly("system program"),
+];
+
 /// Whitelist a validator for a program
 ///
 /// Accounts:
@@ -21,8 +33,9 @@ use solana_program::{
 /// 1: `[]`         validator identity to whitelist
 /// 2: `[]`         program to whitelist the validator for
 /// 3: `[]`         program data account
-/// 4: `[writable]` program config PDA
-/// 5: `[]`         system program
+/// 4: `[]`         delegation program data account
+/// 5: `[writable]` program config PDA
+/// 6: `[]`         system program
 ///
 /// Requirements:
 ///
@@ -53,30 +66,8 @@ pub fn process_whitelist_validator_for_program(
     validate_authority(authority, program, program_data, delegation_program_data)?;
     load_program(system_program, system_program::id(), "system program")?;
 
-    let program_config_bump = load_pda(
-        program_config_account,
-        program_config_seeds_from_program_id!(program.key),
-        &crate::id(),
-        true,
-        "program config",
-    )?;
-
-    // Get the program config. If the account doesn't exist, create it
-    let mut program_config = if program_config_account.owner.eq(system_program.key) {
-        create_pda(
-            program_config_account,
-            &crate::id(),
-            0, // It will be resized later to the proper size
-            program_config_seeds_from_program_id!(program.key),
-            program_config_bump,
-            system_program,
-            authority,
-        )?;
-        ProgramConfig::default()
-    } else {
-        let program_config_data = program_config_account.try_borrow_data()?;
-        ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)?
-    };
+    let mut program_config =
+        load_or_create_program_config(authority, program, program_config_account, system_program)?;
     if args.insert {
         program_config
             .approved_validators
@@ -99,7 +90,7 @@ pub fn process_whitelist_validator_for_program(
 }
 
 /// Authority is valid if either the authority is the ADMIN_PUBKEY or the program upgrade authority
-fn validate_authority(
+pub(crate) fn validate_authority(
     authority: &AccountInfo,
     program: &AccountInfo,
     program_data: &AccountInfo,
@@ -122,3 +113,35 @@ fn validate_authority(
         Err(Unauthorized.into())
     }
 }
+
+/// Load the program config of a program, creating it empty if it does not exist, so that it
+/// must be resized to the size of the updated program config
+pub(crate) fn load_or_create_program_config<'a, 'info>(
+    authority: &'a AccountInfo<'info>,
+    program: &'a AccountInfo<'info>,
+    program_config_account: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+) -> Result<ProgramConfig, ProgramError> {
+    let program_config_bump = load_pda(
+        program_config_account,
+        program_config_seeds_from_program_id!(program.key),
+        &crate::id(),
+        true,
+        "program config",
+    )?;
+    if program_config_account.owner.eq(system_program.key) {
+        create_pda(
+            program_config_account,
+            &crate::id(),
+            0, // It will be resized later to the proper size
+            program_config_seeds_from_program_id!(program.key),
+            program_config_bump,
+            system_program,
+            authority,
+        )?;
+        Ok(ProgramConfig::default())
+    } else {
+        let program_config_data = program_config_account.try_borrow_data()?;
+        ProgramConfig::try_from_bytes_with_discriminator(&program_config_data)
+    }
+}
@@ -0,0 +1,136 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::WhitelistValidatorForProgramArgs;
+use crate::processor::utils::loaders::{load_pda, load_program, load_signer};
+use crate::processor::utils::pda::{close_pda, create_pda, resize_pda};
+use crate::processor::whitelist_validator_for_program::{
+    load_or_create_program_config, validate_authority,
+};
+use crate::state::ValidatorWhitelistShard;
+use crate::validator_whitelist_shard_seeds_from_program_id;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_whitelist_validator_shard_for_program]
+pub const WHITELIST_VALIDATOR_SHARD_FOR_PROGRAM_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("validator identity"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program data"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("program config"),
+    AccountSpec::writable("validator whitelist shard"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Whitelist a validator for a program in its own shard, see [ValidatorWhitelistShard],
+/// so that the whitelist of the program is not bounded by the size of its program config
+///
+/// Accounts:
+///
+/// 0: `[signer]`   authority that has rights to whitelist validators
+/// 1: `[]`         validator identity to whitelist
+/// 2: `[]`         program to whitelist the validator for
+/// 3: `[]`         program data account
+/// 4: `[]`         delegation program data account
+/// 5: `[writable]` program config PDA
+/// 6: `[writable]` validator whitelist shard PDA
+/// 7: `[]`         system program
+///
+/// Requirements:
+///
+/// - authority is either the ADMIN_PUBKEY or the program upgrade authority
+/// - program config is initialized or owned by the system program in which case it is
+///   created, so that the whitelist of the program is enforced
+///
+/// Steps:
+///
+/// 1. Load the authority and validate it
+/// 2. Load the program config or create it and remove the validator from its
+///    `approved_validators`, which migrates it to its shard when inserted, flagging that
+///    the program has whitelist shards, refunding the freed rent to the authority
+/// 3. If inserted, create the shard if it does not exist, otherwise close it, refunding
+///    its rent to the authority
+pub fn process_whitelist_validator_shard_for_program(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = WhitelistValidatorForProgramArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, validator_identity, program, program_data, delegation_program_data, program_config_account, shard_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    validate_authority(authority, program, program_data, delegation_program_data)?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let shard_bump = load_pda(
+        shard_account,
+        validator_whitelist_shard_seeds_from_program_id!(program.key, validator_identity.key),
+        &crate::id(),
+        true,
+        "validator whitelist shard",
+    )?;
+
+    let mut program_config =
+        load_or_create_program_config(authority, program, program_config_account, system_program)?;
+    program_config
+        .approved_validators
+        .remove(validator_identity.key);
+    program_config.has_whitelist_shards |= args.insert;
+
+    let new_size = program_config.size_with_discriminator();
+    resize_pda(authority, program_config_account, system_program, new_size)?;
+    refund_freed_rent(program_config_account, authority, new_size)?;
+    {
+        let mut program_config_data = program_config_account.try_borrow_mut_data()?;
+        program_config.to_bytes_with_discriminator(&mut program_config_data.as_mut())?;
+    }
+
+    let is_shard_initialized = shard_account.owner.eq(&crate::id());
+    if args.insert && !is_shard_initialized {
+        create_pda(
+            shard_account,
+            &crate::id(),
+            ValidatorWhitelistShard::size_with_discriminator(),
+            validator_whitelist_shard_seeds_from_program_id!(program.key, validator_identity.key),
+            shard_bump,
+            system_program,
+            authority,
+        )?;
+        let shard = ValidatorWhitelistShard {
+            program: *program.key,
+            validator: *validator_identity.key,
+        };
+        let mut shard_data = shard_account.try_borrow_mut_data()?;
+        shard.to_bytes_with_discriminator(&mut shard_data)?;
+    } else if !args.insert && is_shard_initialized {
+        close_pda(shard_account, authority)?;
+    }
+
+    Ok(())
+}
+
+/// Refund the authority the rent freed by a program config shrunk to `new_size`
+pub(crate) fn refund_freed_rent(
+    program_config_account: &AccountInfo,
+    authority: &AccountInfo,
+    new_size: usize,
+) -> ProgramResult {
+    let freed_rent = program_config_account
+        .lamports()
+        .saturating_sub(Rent::default().minimum_balance(new_size));
+    if freed_rent > 0 {
+        **program_config_account.try_borrow_mut_lamports()? -= freed_rent;
+        **authority.try_borrow_mut_lamports()? += freed_rent;
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::{WhitelistValidatorsForProgramBatchArgs, MAX_WHITELIST_BATCH_VALIDATORS};
+use crate::error::DlpError::TooManyValidatorsInBatch;
+use crate::processor::utils::loaders::{load_program, load_signer};
+use crate::processor::utils::pda::resize_pda;
+use crate::processor::whitelist_validator_for_program::{
+    load_or_create_program_config, validate_authority,
+};
+use crate::processor::whitelist_validator_shard_for_program::refund_freed_rent;
+use borsh::BorshDeserialize;
+use solana_program::program::set_return_data;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_whitelist_validators_for_program_batch]
+pub const WHITELIST_VALIDATORS_FOR_PROGRAM_BATCH_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("program"),
+    AccountSpec::readonly("program data"),
+    AccountSpec::readonly("delegation program data"),
+    AccountSpec::writable("program config"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Insert and remove validators of the whitelist of a program in a single instruction
+///
+/// Accounts:
+///
+/// 0: `[signer]`   authority that has rights to whitelist validators
+/// 1: `[]`         program to whitelist the validators for
+/// 2: `[]`         program data account
+/// 3: `[]`         delegation program data account
+/// 4: `[writable]` program config PDA
+/// 5: `[]`         system program
+///
+/// Requirements:
+///
+/// - authority is either the ADMIN_PUBKEY or the program upgrade authority
+/// - at most [MAX_WHITELIST_BATCH_VALIDATORS] validators are inserted and removed
+/// - program config is initialized or owned by the system program in
+///   which case it is created
+///
+/// Steps:
+///
+/// 1. Load the authority and validate it
+/// 2. Load the program config or create it, insert the validators into the
+///    `approved_validators` set and then remove the others
+/// 3. Resize the account, the authority pays for the grown rent and is refunded the
+///    freed rent
+/// 4. Set the number of approved validators as the return data, in u32 little endian
+pub fn process_whitelist_validators_for_program_batch(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = WhitelistValidatorsForProgramBatchArgs::try_from_slice(data)?;
+    if args.insert.len() + args.remove.len() > MAX_WHITELIST_BATCH_VALIDATORS {
+        return Err(TooManyValidatorsInBatch.into());
+    }
+
+    // Load Accounts
+    let [authority, program, program_data, delegation_program_data, program_config_account, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    validate_authority(authority, program, program_data, delegation_program_data)?;
+    load_program(system_program, system_program::id(), "system program")?;
+
+    let mut program_config =
+        load_or_create_program_config(authority, program, program_config_account, system_program)?;
+    program_config.approved_validators.extend(args.insert);
+    for validator_identity in args.remove.iter() {
+        program_config
+            .approved_validators
+            .remove(validator_identity);
+    }
+
+    let new_size = program_config.size_with_discriminator();
+    resize_pda(authority, program_config_account, system_program, new_size)?;
+
+    // Refund the rent freed by the removed validators
+    refund_freed_rent(program_config_account, authority, new_size)?;
+
+    let mut program_config_data = program_config_account.try_borrow_mut_data()?;
+    program_config.to_bytes_with_discriminator(&mut program_config_data.as_mut())?;
+
+    set_return_data(&(program_config.approved_validators.len() as u32).to_le_bytes());
+
+    Ok(())
+}
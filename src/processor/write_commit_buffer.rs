@@ -0,0 +1,99 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::WriteCommitBufferArgs;
+use crate::commit_buffer_seeds_from_delegated_account_and_authority;
+use crate::error::DlpError::InvalidCommitBuffer;
+use crate::log::msg;
+use crate::processor::utils::loaders::{load_initialized_pda, load_program, load_signer};
+use crate::processor::utils::pda::resize_pda;
+use crate::state::CommitBuffer;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_write_commit_buffer]
+pub const WRITE_COMMIT_BUFFER_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::writable_signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("commit buffer"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Write a chunk of the state staged in a commit buffer
+///
+/// Accounts:
+///
+/// 0: `[signer, writable]` the authority of the commit buffer, paying for its growth
+/// 1: `[]`                 the delegated account
+/// 2: `[writable]`         the commit buffer PDA of the delegated account and the authority
+/// 3: `[]`                 the system program
+///
+/// Requirements:
+///
+/// - the commit buffer is initialized, its PDA being derived from the authority
+/// - the chunk fits in the data length of the commit buffer
+///
+/// Steps:
+///
+/// 1. Grow the commit buffer up to the end of the chunk, if needed
+/// 2. Copy the chunk at its offset
+///
+/// NOTE: the account can only grow by 10KiB per instruction, so chunks are expected to be
+///       written in order.
+pub fn process_write_commit_buffer(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = WriteCommitBufferArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, delegated_account, commit_buffer_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_initialized_pda(
+        commit_buffer_account,
+        commit_buffer_seeds_from_delegated_account_and_authority!(
+            delegated_account.key,
+            authority.key
+        ),
+        &crate::id(),
+        true,
+        "commit buffer",
+    )?;
+
+    let header_size = CommitBuffer::size_with_discriminator();
+    let commit_buffer = *CommitBuffer::try_from_bytes_with_discriminator(
+        commit_buffer_account
+            .try_borrow_data()?
+            .get(..header_size)
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )?;
+
+    let start = header_size.saturating_add(args.offset as usize);
+    let end = start.saturating_add(args.data.len());
+    if end > commit_buffer.account_size() {
+        msg!(
+            "Chunk ends at {} but the staged data length is {}",
+            end.saturating_sub(header_size),
+            commit_buffer.data_len
+        );
+        return Err(InvalidCommitBuffer.into());
+    }
+
+    if commit_buffer_account.data_len() < end {
+        resize_pda(authority, commit_buffer_account, system_program, end)?;
+    }
+
+    let mut commit_buffer_data = commit_buffer_account.try_borrow_mut_data()?;
+    commit_buffer_data
+        .get_mut(start..end)
+        .ok_or(ProgramError::InvalidAccountData)?
+        .copy_from_slice(&args.data);
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::accounts_spec::{AccountSpec, AccountsSpec};
+use crate::args::WriteDelegateBufferChunkArgs;
+use crate::error::DlpError::{InvalidAuthority, InvalidStagedDelegateBuffer};
+use crate::log::msg;
+use crate::processor::utils::loaders::{load_initialized_pda, load_program, load_signer};
+use crate::processor::utils::pda::resize_pda;
+use crate::staged_delegate_buffer_seeds_from_delegated_account;
+use crate::state::StagedDelegateBuffer;
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey, system_program,
+};
+
+/// Accounts of [process_write_delegate_buffer_chunk]
+pub const WRITE_DELEGATE_BUFFER_CHUNK_ACCOUNTS_SPEC: AccountsSpec = &[
+    AccountSpec::signer("authority"),
+    AccountSpec::readonly("delegated account"),
+    AccountSpec::writable("staged delegate buffer"),
+    AccountSpec::readonly("system program"),
+];
+
+/// Write a chunk of the data staged in a staged delegate buffer
+///
+/// Accounts:
+///
+/// 0: `[signer]`   the authority of the staged buffer, paying for its growth
+/// 1: `[]`         the account to delegate
+/// 2: `[writable]` the staged delegate buffer PDA
+/// 3: `[]`         the system program
+///
+/// Requirements:
+///
+/// - the staged delegate buffer is initialized
+/// - the authority is the one of the staged delegate buffer
+/// - the chunk fits in the data length of the staged delegate buffer
+///
+/// Steps:
+///
+/// 1. Grow the staged delegate buffer up to the end of the chunk, if needed
+/// 2. Copy the chunk at its offset
+///
+/// NOTE: the account can only grow by 10KiB per instruction, so chunks are expected to be
+///       written in order.
+pub fn process_write_delegate_buffer_chunk(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let args = WriteDelegateBufferChunkArgs::try_from_slice(data)?;
+
+    // Load Accounts
+    let [authority, delegated_account, staged_buffer_account, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    load_signer(authority, "authority")?;
+    load_program(system_program, system_program::id(), "system program")?;
+    load_initialized_pda(
+        staged_buffer_account,
+        staged_delegate_buffer_seeds_from_delegated_account!(delegated_account.key),
+        &crate::id(),
+        true,
+        "staged delegate buffer",
+    )?;
+
+    let header_size = StagedDelegateBuffer::size_with_discriminator();
+    let staged_buffer = *StagedDelegateBuffer::try_from_bytes_with_discriminator(
+        staged_buffer_account
+            .try_borrow_data()?
+            .get(..header_size)
+            .ok_or(ProgramError::InvalidAccountData)?,
+    )?;
+    if !staged_buffer.authority.eq(authority.key) {
+        msg!(
+            "Expected staged delegate buffer authority: {} but got {}",
+            staged_buffer.authority,
+            authority.key
+        );
+        return Err(InvalidAuthority.into());
+    }
+
+    let start = header_size.saturating_add(args.offset as usize);
+    let end = start.saturating_add(args.data.len());
+    if end > staged_buffer.account_size() {
+        msg!(
+            "Chunk ends at {} but the staged data length is {}",
+            end - header_size,
+            staged_buffer.data_len
+        );
+        return Err(InvalidStagedDelegateBuffer.into());
+    }
+
+    if staged_buffer_account.data_len() < end {
+        resize_pda(authority, staged_buffer_account, system_program, end)?;
+    }
+
+    let mut staged_buffer_data = staged_buffer_account.try_borrow_mut_data()?;
+    staged_buffer_data
+        .get_mut(start..end)
+        .ok_or(ProgramError::InvalidAccountData)?
+        .copy_from_slice(&args.data);
+
+    Ok(())
+}
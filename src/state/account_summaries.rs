@@ -0,0 +1,428 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::ErBlockHash;
+
+use super::{CommitRecord, DelegationMetadata, DelegationRecord, PendingState, ProgramConfig};
+
+/// The delegation of an account as displayed by wallets, returned by
+/// [crate::processor::process_get_delegation_summaries]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct DelegationSummary {
+    /// The delegated account
+    pub account: Pubkey,
+    /// Whether the account is delegated, the other fields being zeroed otherwise
+    pub is_delegated: bool,
+    /// The validator the account is delegated to
+    pub validator: Pubkey,
+    /// The program owning the account once undelegated
+    pub owner: Pubkey,
+    /// The slot at which the account was delegated
+    pub delegation_slot: u64,
+    /// The slot from which the delegation outlived the maximum delegation slots of the
+    /// program config of the owner program, see [ProgramConfig::is_delegation_expired]
+    pub expiry_slot: Option<u64>,
+    /// The slot from which the commits must undelegate the account, if the undelegation was
+    /// requested, see [super::UndelegationRequest]
+    pub undelegation_deadline_slot: Option<u64>,
+    /// Whether the last commit allowed the undelegation of the account
+    pub is_undelegating: bool,
+    /// The nonce of the last finalized commit
+    pub last_commit_nonce: u64,
+}
+
+impl DelegationSummary {
+    /// Summarize the delegation of the account from the data of its delegation record and
+    /// metadata, None if they do not exist, and of the program config of the owner program,
+    /// if any. Clients can build the same summary from the accounts fetched over RPC.
+    pub fn from_delegation_data(
+        account: &Pubkey,
+        delegation_record_data: Option<&[u8]>,
+        delegation_metadata_data: Option<&[u8]>,
+        program_config_data: Option<&[u8]>,
+    ) -> Result<Self, ProgramError> {
+        let (Some(delegation_record_data), Some(delegation_metadata_data)) =
+            (delegation_record_data, delegation_metadata_data)
+        else {
+            return Ok(Self {
+                account: *account,
+                ..Default::default()
+            });
+        };
+        let delegation_record =
+            DelegationRecord::try_from_bytes_with_discriminator(delegation_record_data)?;
+        let delegation_metadata =
+            DelegationMetadata::try_from_bytes_with_discriminator(delegation_metadata_data)?;
+        let max_delegation_slots = match program_config_data {
+            Some(program_config_data) => {
+                ProgramConfig::try_from_bytes_with_discriminator(program_config_data)?
+                    .max_delegation_slots
+            }
+            None => 0,
+        };
+        Ok(Self {
+            account: *account,
+            is_delegated: true,
+            validator: delegation_record.authority,
+            owner: delegation_record.owner,
+            delegation_slot: delegation_record.delegation_slot,
+            expiry_slot: (max_delegation_slots > 0).then(|| {
+                delegation_record
+                    .delegation_slot
+                    .saturating_add(max_delegation_slots)
+            }),
+            undelegation_deadline_slot: delegation_metadata
+                .undelegation_request
+                .map(|request| request.deadline_slot),
+            is_undelegating: delegation_metadata.is_undelegatable,
+            last_commit_nonce: delegation_metadata.last_update_nonce,
+        })
+    }
+}
+
+impl fmt::Display for DelegationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_delegated {
+            return write!(f, "{} is not delegated", self.account);
+        }
+        write!(
+            f,
+            "{} is delegated to validator {} since slot {}",
+            self.account, self.validator, self.delegation_slot
+        )?;
+        if let Some(expiry_slot) = self.expiry_slot {
+            write!(f, " until slot {}", expiry_slot)?;
+        }
+        if self.is_undelegating {
+            write!(f, ", undelegating")?;
+        } else if let Some(deadline_slot) = self.undelegation_deadline_slot {
+            write!(f, ", undelegation requested by slot {}", deadline_slot)?;
+        }
+        Ok(())
+    }
+}
+
+/// An escrow of a payer as displayed by wallets, see
+/// [crate::pda::ephemeral_balance_pda_from_payer], returned by
+/// [crate::processor::process_get_escrow_summaries]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct EscrowSummary {
+    /// The payer funding the escrow
+    pub payer: Pubkey,
+    /// The index of the escrow of the payer
+    pub index: u8,
+    /// The lamports held by the escrow
+    pub lamports: u64,
+    /// Whether the escrow is delegated, so that its lamports are spent in the ephemeral
+    /// rollup
+    pub is_delegated: bool,
+    /// The validator the escrow is delegated to, zeroed if not delegated
+    pub validator: Pubkey,
+}
+
+impl EscrowSummary {
+    /// Summarize the escrow from its lamports and the data of its delegation record, None if
+    /// the escrow is not delegated
+    pub fn from_escrow_data(
+        payer: &Pubkey,
+        index: u8,
+        lamports: u64,
+        delegation_record_data: Option<&[u8]>,
+    ) -> Result<Self, ProgramError> {
+        let validator = match delegation_record_data {
+            Some(delegation_record_data) => Some(
+                DelegationRecord::try_from_bytes_with_discriminator(delegation_record_data)?
+                    .authority,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            payer: *payer,
+            index,
+            lamports,
+            is_delegated: validator.is_some(),
+            validator: validator.unwrap_or_default(),
+        })
+    }
+}
+
+impl fmt::Display for EscrowSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Escrow {} of {} holds {} SOL",
+            self.index,
+            self.payer,
+            format_sol(self.lamports)
+        )?;
+        if self.is_delegated {
+            write!(f, ", delegated to validator {}", self.validator)?;
+        }
+        Ok(())
+    }
+}
+
+/// The commit of an account pending finalization as displayed by wallets, returned by
+/// [crate::processor::process_get_pending_commit_summaries]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct PendingCommitSummary {
+    /// The delegated account
+    pub account: Pubkey,
+    /// Whether a commit of the account is pending, the other fields being zeroed otherwise
+    pub has_pending_commit: bool,
+    /// The validator the commit is attributed to
+    pub validator: Pubkey,
+    /// The nonce of the commit
+    pub nonce: u64,
+    /// The slot at which the state was committed
+    pub slot: u64,
+    /// The committed lamports
+    pub lamports: u64,
+    /// The length of the committed state
+    pub data_len: u64,
+    /// The hash of the ER block which produced the committed state, if provided
+    pub er_block_hash: Option<ErBlockHash>,
+}
+
+impl PendingCommitSummary {
+    /// Summarize the pending commit of the account from the data of its commit record, None
+    /// if no commit is pending, and of its commit state, read according to the layout
+    /// recorded by the commit
+    pub fn from_commit_data(
+        account: &Pubkey,
+        commit_record_data: Option<&[u8]>,
+        commit_state_data: Option<&[u8]>,
+    ) -> Result<Self, ProgramError> {
+        let Some(commit_record_data) = commit_record_data else {
+            return Ok(Self {
+                account: *account,
+                ..Default::default()
+            });
+        };
+        let commit_record = CommitRecord::try_from_bytes_with_discriminator(commit_record_data)?;
+        let committed_data_len = commit_state_data
+            .zip(commit_record.commit_state_layout())
+            .and_then(|(data, layout)| PendingState::committed_data(data, layout))
+            .map_or(0, <[u8]>::len);
+        Ok(Self {
+            account: *account,
+            has_pending_commit: true,
+            validator: commit_record.identity,
+            nonce: commit_record.nonce,
+            slot: commit_record.slot,
+            lamports: commit_record.lamports,
+            data_len: committed_data_len as u64,
+            er_block_hash: commit_record.er_block_hash(),
+        })
+    }
+}
+
+impl fmt::Display for PendingCommitSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.has_pending_commit {
+            return write!(f, "{} has no pending commit", self.account);
+        }
+        write!(
+            f,
+            "{} has a pending commit {} of {} bytes and {} SOL by validator {} at slot {}",
+            self.account,
+            self.nonce,
+            self.data_len,
+            format_sol(self.lamports),
+            self.validator,
+            self.slot
+        )
+    }
+}
+
+/// Format lamports as SOL, without trailing zeros
+pub fn format_sol(lamports: u64) -> String {
+    let fraction = lamports % LAMPORTS_PER_SOL;
+    if fraction == 0 {
+        return (lamports / LAMPORTS_PER_SOL).to_string();
+    }
+    let fraction = format!("{:09}", fraction);
+    format!(
+        "{}.{}",
+        lamports / LAMPORTS_PER_SOL,
+        fraction.trim_end_matches('0')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::to_vec;
+
+    use super::*;
+    use crate::args::Seeds;
+    use crate::state::UndelegationRequest;
+
+    #[test]
+    fn test_delegation_summary() {
+        let account = Pubkey::new_unique();
+        let summary = DelegationSummary::from_delegation_data(&account, None, None, None).unwrap();
+        assert!(!summary.is_delegated);
+        assert_eq!(summary.to_string(), format!("{} is not delegated", account));
+
+        let delegation_record = DelegationRecord {
+            authority: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            delegation_slot: 100,
+            lamports: 1_000,
+            commit_frequency_ms: 0,
+            version: DelegationRecord::VERSION,
+            padding: [0; 7],
+        };
+        let mut delegation_record_data = vec![0; DelegationRecord::size_with_discriminator()];
+        delegation_record
+            .to_bytes_with_discriminator(&mut delegation_record_data)
+            .unwrap();
+        let delegation_metadata = DelegationMetadata {
+            last_update_nonce: 3,
+            is_undelegatable: false,
+            seeds: Seeds::default(),
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: Some(UndelegationRequest {
+                slot: 150,
+                deadline_slot: 250,
+            }),
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+        let mut delegation_metadata_data = vec![];
+        delegation_metadata
+            .to_bytes_with_discriminator(&mut delegation_metadata_data)
+            .unwrap();
+        let program_config = ProgramConfig {
+            max_delegation_slots: 1_000,
+            ..Default::default()
+        };
+        let mut program_config_data = vec![];
+        program_config
+            .to_bytes_with_discriminator(&mut program_config_data)
+            .unwrap();
+
+        let summary = DelegationSummary::from_delegation_data(
+            &account,
+            Some(&delegation_record_data),
+            Some(&delegation_metadata_data),
+            Some(&program_config_data),
+        )
+        .unwrap();
+        assert_eq!(
+            summary,
+            DelegationSummary {
+                account,
+                is_delegated: true,
+                validator: delegation_record.authority,
+                owner: delegation_record.owner,
+                delegation_slot: 100,
+                expiry_slot: Some(1_100),
+                undelegation_deadline_slot: Some(250),
+                is_undelegating: false,
+                last_commit_nonce: 3,
+            }
+        );
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "{} is delegated to validator {} since slot 100 until slot 1100, undelegation requested by slot 250",
+                account, delegation_record.authority
+            )
+        );
+        assert_eq!(
+            DelegationSummary::try_from_slice(&to_vec(&summary).unwrap()).unwrap(),
+            summary
+        );
+
+        // Without a program config the delegation does not expire
+        let summary = DelegationSummary::from_delegation_data(
+            &account,
+            Some(&delegation_record_data),
+            Some(&delegation_metadata_data),
+            None,
+        )
+        .unwrap();
+        assert_eq!(summary.expiry_slot, None);
+    }
+
+    #[test]
+    fn test_escrow_summary() {
+        let payer = Pubkey::new_unique();
+        let summary = EscrowSummary::from_escrow_data(&payer, 2, 1_500_000_000, None).unwrap();
+        assert!(!summary.is_delegated);
+        assert_eq!(
+            summary.to_string(),
+            format!("Escrow 2 of {} holds 1.5 SOL", payer)
+        );
+    }
+
+    #[test]
+    fn test_pending_commit_summary() {
+        let account = Pubkey::new_unique();
+        let summary = PendingCommitSummary::from_commit_data(&account, None, None).unwrap();
+        assert_eq!(
+            summary.to_string(),
+            format!("{} has no pending commit", account)
+        );
+
+        let commit_record = CommitRecord {
+            identity: Pubkey::new_unique(),
+            account,
+            nonce: 4,
+            lamports: LAMPORTS_PER_SOL,
+            slot: 42,
+            er_block_hash: [0; 32],
+            rent_advanced: 0,
+            escrow: Pubkey::default(),
+            escrow_spend: 0,
+            has_escrow_spend: 0,
+            commit_state_layout: 0,
+            padding: [0; 6],
+        };
+        let mut commit_record_data = vec![0; CommitRecord::size_with_discriminator()];
+        commit_record
+            .to_bytes_with_discriminator(&mut commit_record_data)
+            .unwrap();
+        let summary = PendingCommitSummary::from_commit_data(
+            &account,
+            Some(&commit_record_data),
+            Some(&[7; 10]),
+        )
+        .unwrap();
+        assert!(summary.has_pending_commit);
+        assert_eq!(summary.er_block_hash, None);
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "{} has a pending commit 4 of 10 bytes and 1 SOL by validator {} at slot 42",
+                account, commit_record.identity
+            )
+        );
+    }
+
+    #[test]
+    fn test_format_sol() {
+        assert_eq!(format_sol(0), "0");
+        assert_eq!(format_sol(2 * LAMPORTS_PER_SOL), "2");
+        assert_eq!(format_sol(1), "0.000000001");
+        assert_eq!(format_sol(1_250_000_000), "1.25");
+    }
+}
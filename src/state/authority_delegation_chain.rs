@@ -0,0 +1,138 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::AuthorityScope;
+use crate::{impl_to_bytes_with_discriminator_borsh, impl_try_from_bytes_with_discriminator_borsh};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The maximum number of sub-keys a key can grant its authority to
+pub const MAX_AUTHORITY_GRANTS: usize = 8;
+
+/// The maximum number of links between the delegation authority and the signer of a commit,
+/// e.g. an organization key granting a region key granting a machine key is 2 links deep
+pub const MAX_AUTHORITY_CHAIN_DEPTH: usize = 3;
+
+/// The Authority Delegation Chain of a key holds the sub-keys it granted its commit
+/// authority to, see [crate::processor::process_set_authority_grant]. Starting from the
+/// authority of a delegation, each granted sub-key can grant its own sub-keys in turn, and
+/// any key of a valid chain of at most [MAX_AUTHORITY_CHAIN_DEPTH] links can sign the
+/// commits of the delegation, see [crate::processor::fast::process_commit_state].
+#[derive(BorshSerialize, BorshDeserialize, Default, Debug, PartialEq)]
+pub struct AuthorityDelegationChain {
+    /// The key granting its authority, which the PDA is derived from
+    pub delegator: Pubkey,
+    /// The sub-keys granted the authority of the delegator
+    pub grants: Vec<AuthorityGrant>,
+}
+
+/// A sub-key granted the authority of a delegator until its expiry slot
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AuthorityGrant {
+    /// The granted sub-key
+    pub key: Pubkey,
+    /// The slot from which the grant is expired
+    pub expiry_slot: u64,
+    /// What the sub-key can do on behalf of the delegator
+    pub scope: AuthorityScope,
+}
+
+impl AccountWithDiscriminator for AuthorityDelegationChain {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::AuthorityDelegationChain
+    }
+}
+
+impl AuthorityDelegationChain {
+    pub fn size_with_discriminator(&self) -> usize {
+        8 + 32 + 4 + (32 + 8 + 1) * self.grants.len()
+    }
+
+    /// Returns true if the key is granted the scope at the slot
+    pub fn is_granted(&self, key: &Pubkey, scope: AuthorityScope, slot: u64) -> bool {
+        self.grants
+            .iter()
+            .any(|grant| grant.key.eq(key) && grant.is_valid(scope, slot))
+    }
+
+    /// Add the grant, replacing the previous grant of its key if any
+    pub fn set_grant(&mut self, grant: AuthorityGrant) {
+        self.revoke(&grant.key);
+        self.grants.push(grant);
+    }
+
+    /// Remove the grant of the key, if any
+    pub fn revoke(&mut self, key: &Pubkey) {
+        self.grants.retain(|grant| !grant.key.eq(key));
+    }
+}
+
+impl AuthorityGrant {
+    /// Returns true if the grant covers the scope and is not expired at the slot
+    pub fn is_valid(&self, scope: AuthorityScope, slot: u64) -> bool {
+        slot < self.expiry_slot && self.scope.covers(scope)
+    }
+}
+
+impl_to_bytes_with_discriminator_borsh!(AuthorityDelegationChain);
+impl_try_from_bytes_with_discriminator_borsh!(AuthorityDelegationChain);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_granted() {
+        let key = Pubkey::new_unique();
+        let mut chain = AuthorityDelegationChain {
+            delegator: Pubkey::new_unique(),
+            grants: vec![],
+        };
+        chain.set_grant(AuthorityGrant {
+            key,
+            expiry_slot: 100,
+            scope: AuthorityScope::Commit,
+        });
+        assert!(chain.is_granted(&key, AuthorityScope::Commit, 99));
+        assert!(!chain.is_granted(&key, AuthorityScope::Commit, 100));
+        assert!(!chain.is_granted(&key, AuthorityScope::CommitAndUndelegate, 99));
+        assert!(!chain.is_granted(&Pubkey::new_unique(), AuthorityScope::Commit, 99));
+
+        // A new grant of the key replaces the previous one
+        chain.set_grant(AuthorityGrant {
+            key,
+            expiry_slot: 200,
+            scope: AuthorityScope::CommitAndUndelegate,
+        });
+        assert_eq!(chain.grants.len(), 1);
+        assert!(chain.is_granted(&key, AuthorityScope::Commit, 150));
+        assert!(chain.is_granted(&key, AuthorityScope::CommitAndUndelegate, 150));
+
+        chain.revoke(&key);
+        assert!(!chain.is_granted(&key, AuthorityScope::Commit, 150));
+    }
+
+    #[test]
+    fn test_size_with_discriminator() {
+        let chain = AuthorityDelegationChain {
+            delegator: Pubkey::new_unique(),
+            grants: vec![
+                AuthorityGrant {
+                    key: Pubkey::new_unique(),
+                    expiry_slot: u64::MAX,
+                    scope: AuthorityScope::Commit,
+                };
+                2
+            ],
+        };
+        let mut data = vec![0; chain.size_with_discriminator()];
+        chain
+            .to_bytes_with_discriminator(&mut data.as_mut_slice())
+            .unwrap();
+        assert_eq!(
+            AuthorityDelegationChain::try_from_bytes_with_discriminator(&data).unwrap(),
+            chain
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::args::CallHandlerContext;
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Call Handler Permissions of an ephemeral balance escrow, set by its authority,
+/// scope the contexts in which a validator can call handlers with the signature of the
+/// escrow. Without them, handlers can be called in every context.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct CallHandlerPermissions {
+    /// Bitmask of the enabled contexts, bit `n` is set if the context `n` is enabled, see
+    /// [CallHandlerContext]
+    pub enabled_contexts: u8,
+    pub padding: [u8; 7],
+}
+
+impl AccountWithDiscriminator for CallHandlerPermissions {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::CallHandlerPermissions
+    }
+}
+
+impl Default for CallHandlerPermissions {
+    fn default() -> Self {
+        Self {
+            enabled_contexts: u8::MAX,
+            padding: [0; 7],
+        }
+    }
+}
+
+impl CallHandlerPermissions {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<CallHandlerPermissions>()
+    }
+
+    pub fn is_enabled(&self, context: CallHandlerContext) -> bool {
+        self.enabled_contexts & (1 << context as u8) != 0
+    }
+
+    pub fn set_enabled(&mut self, context: CallHandlerContext, enabled: bool) {
+        let mask = 1 << context as u8;
+        if enabled {
+            self.enabled_contexts |= mask;
+        } else {
+            self.enabled_contexts &= !mask;
+        }
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(CallHandlerPermissions);
+impl_try_from_bytes_with_discriminator_zero_copy!(CallHandlerPermissions);
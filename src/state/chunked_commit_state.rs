@@ -0,0 +1,301 @@
+use alloc::vec::Vec;
+use core::mem::size_of;
+use core::ops::Range;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::hash::hashv;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// Domain separator of the hash of a chunk, so that a leaf can not pass for a node
+const LEAF_PREFIX: &[u8] = &[0];
+
+/// Domain separator of the hash of two nodes
+const NODE_PREFIX: &[u8] = &[1];
+
+/// The header of a Chunked Commit State, holding a state committed by the Merkle root of its
+/// chunks, see [crate::processor::fast::process_commit_state_root]. The data follows the
+/// header, pre-filled with the data of the delegated account, then a bitmap of the chunks
+/// already written with their Merkle proof, see [crate::processor::process_commit_state_chunk].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ChunkedCommitState {
+    /// The Merkle root of the chunks of the state, see [ChunkMerkleTree]
+    pub root: [u8; 32],
+
+    /// The length of the state
+    pub data_len: u64,
+
+    /// The length of every chunk, but the last one which may be shorter
+    pub chunk_size: u32,
+
+    /// The number of chunks written so far
+    pub chunks_written: u32,
+
+    /// The number of chunks changed from the data of the delegated account, to be written
+    /// before finalize. The other chunks keep the data of the delegated account
+    pub chunks_to_write: u32,
+
+    pub padding: [u8; 4],
+}
+
+impl AccountWithDiscriminator for ChunkedCommitState {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ChunkedCommitState
+    }
+}
+
+impl ChunkedCommitState {
+    pub fn new(root: [u8; 32], data_len: u64, chunk_size: u32, chunks_to_write: u32) -> Self {
+        Self {
+            root,
+            data_len,
+            chunk_size,
+            chunks_written: 0,
+            chunks_to_write,
+            padding: [0; 4],
+        }
+    }
+
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ChunkedCommitState>()
+    }
+
+    /// The number of chunks of the state
+    pub fn chunks_count(&self) -> usize {
+        if self.chunk_size == 0 {
+            return 0;
+        }
+        (self.data_len as usize).div_ceil(self.chunk_size as usize)
+    }
+
+    /// The size of the bitmap of the chunks written
+    pub fn bitmap_len(&self) -> usize {
+        self.chunks_count().div_ceil(8)
+    }
+
+    /// The size of the commit state holding the header, the data and the bitmap
+    pub fn account_size(&self) -> usize {
+        Self::size_with_discriminator()
+            .saturating_add(self.data_len as usize)
+            .saturating_add(self.bitmap_len())
+    }
+
+    /// The range of a chunk in the data, or None if the state has no such chunk
+    pub fn chunk_range(&self, index: usize) -> Option<Range<usize>> {
+        if index >= self.chunks_count() {
+            return None;
+        }
+        let start = index.checked_mul(self.chunk_size as usize)?;
+        let end = start
+            .saturating_add(self.chunk_size as usize)
+            .min(self.data_len as usize);
+        Some(start..end)
+    }
+
+    /// Whether every changed chunk of the state was written, each one being proven against
+    /// the root
+    pub fn is_complete(&self) -> bool {
+        self.chunks_written >= self.chunks_to_write
+    }
+
+    /// Split a chunked commit state into its header, its data and the bitmap of the chunks
+    /// written, or None if the account is not a chunked commit state
+    pub fn split(commit_state_data: &[u8]) -> Option<(ChunkedCommitState, &[u8], &[u8])> {
+        let header_size = Self::size_with_discriminator();
+        let header =
+            *Self::try_from_bytes_with_discriminator(commit_state_data.get(..header_size)?).ok()?;
+        if header.account_size() != commit_state_data.len() {
+            return None;
+        }
+        let (data, bitmap) = commit_state_data
+            .get(header_size..)?
+            .split_at_checked(header.data_len as usize)?;
+        Some((header, data, bitmap))
+    }
+
+    /// The data of a chunked commit state, whether all of its chunks were written or not
+    pub fn chunked_data(commit_state_data: &[u8]) -> Option<&[u8]> {
+        Self::split(commit_state_data).map(|(_, data, _)| data)
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ChunkedCommitState);
+impl_try_from_bytes_with_discriminator_zero_copy!(ChunkedCommitState);
+
+/// The hash of a chunk at an index, a leaf of the Merkle tree of a chunked state
+pub fn chunk_leaf_hash(index: u32, chunk: &[u8]) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, &index.to_le_bytes(), chunk]).to_bytes()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Check the Merkle proof of a chunk, the hashes of its siblings from the leaf up, against
+/// the root of a state of `chunks_count` chunks. The last node of a level without a
+/// sibling is carried to the next level and takes no hash of the proof.
+pub fn verify_chunk_proof(
+    root: &[u8; 32],
+    chunks_count: usize,
+    index: u32,
+    chunk: &[u8],
+    proof: &[[u8; 32]],
+) -> bool {
+    let mut hash = chunk_leaf_hash(index, chunk);
+    let mut position = index as usize;
+    let mut level_len = chunks_count;
+    let mut siblings = proof.iter();
+    if position >= level_len {
+        return false;
+    }
+    while level_len > 1 {
+        if position ^ 1 < level_len {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = if position % 2 == 0 {
+                node_hash(&hash, sibling)
+            } else {
+                node_hash(sibling, &hash)
+            };
+        }
+        position /= 2;
+        level_len = level_len.div_ceil(2);
+    }
+    siblings.next().is_none() && hash == *root
+}
+
+/// The Merkle tree of the chunks of a state, built by the validator to commit the root of
+/// the state and the proof of each chunk, see [verify_chunk_proof]
+pub struct ChunkMerkleTree {
+    /// The levels of the tree, from the leaves to the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl ChunkMerkleTree {
+    /// Build the tree of the chunks of `chunk_size` bytes of a non-empty state
+    pub fn new(state: &[u8], chunk_size: usize) -> Self {
+        let leaves = state
+            .chunks(chunk_size.max(1))
+            .enumerate()
+            .map(|(index, chunk)| chunk_leaf_hash(index as u32, chunk))
+            .collect::<Vec<_>>();
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single, ..] => *single,
+                    [] => [0; 32],
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|root| root.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The hashes of the siblings of a chunk from the leaf up
+    pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let mut position = index;
+        let mut proof = Vec::new();
+        let below_root = self
+            .levels
+            .split_last()
+            .map_or(&[][..], |(_, levels)| levels);
+        for level in below_root {
+            if let Some(sibling) = level.get(position ^ 1) {
+                proof.push(*sibling);
+            }
+            position /= 2;
+        }
+        proof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_merkle_proofs() {
+        let state = (0..1_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        // 1, 2 and an odd number of chunks, whose last node is carried up
+        for chunk_size in [1_000, 500, 90] {
+            let tree = ChunkMerkleTree::new(&state, chunk_size);
+            let header =
+                ChunkedCommitState::new(tree.root(), state.len() as u64, chunk_size as u32, 0);
+            for index in 0..header.chunks_count() {
+                let chunk = &state[header.chunk_range(index).unwrap()];
+                let proof = tree.proof(index);
+                assert!(verify_chunk_proof(
+                    &tree.root(),
+                    header.chunks_count(),
+                    index as u32,
+                    chunk,
+                    &proof
+                ));
+
+                // Another chunk, index or proof is rejected
+                let mut other_chunk = chunk.to_vec();
+                other_chunk[0] ^= 1;
+                assert!(!verify_chunk_proof(
+                    &tree.root(),
+                    header.chunks_count(),
+                    index as u32,
+                    &other_chunk,
+                    &proof
+                ));
+                assert!(!verify_chunk_proof(
+                    &tree.root(),
+                    header.chunks_count(),
+                    index as u32 + 1,
+                    chunk,
+                    &proof
+                ));
+                assert!(!verify_chunk_proof(
+                    &tree.root(),
+                    header.chunks_count(),
+                    index as u32,
+                    chunk,
+                    &[proof.as_slice(), &[[0; 32]]].concat()
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunked_commit_state_layout() {
+        let header = ChunkedCommitState::new([7; 32], 10, 4, 1);
+        assert_eq!(header.chunks_count(), 3);
+        assert_eq!(header.bitmap_len(), 1);
+        assert_eq!(header.chunk_range(2), Some(8..10));
+        assert_eq!(header.chunk_range(3), None);
+
+        let mut commit_state_data = vec![0; header.account_size()];
+        header
+            .to_bytes_with_discriminator(
+                &mut commit_state_data[..ChunkedCommitState::size_with_discriminator()],
+            )
+            .unwrap();
+        let (read, data, bitmap) = ChunkedCommitState::split(&commit_state_data).unwrap();
+        assert_eq!(read, header);
+        assert_eq!(data.len(), 10);
+        assert_eq!(bitmap.len(), 1);
+        assert!(!read.is_complete());
+        assert!(ChunkedCommitState::split(&commit_state_data[1..]).is_none());
+    }
+}
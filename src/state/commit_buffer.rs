@@ -0,0 +1,55 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The header of a Commit Buffer, which stages a state too large to be committed in one
+/// transaction, see [crate::processor::process_init_commit_buffer]. The staged data
+/// directly follows the header.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct CommitBuffer {
+    /// The authority allowed to write the staged data and to close the buffer
+    pub authority: Pubkey,
+
+    /// The length of the data to stage
+    pub data_len: u64,
+}
+
+impl AccountWithDiscriminator for CommitBuffer {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::CommitBuffer
+    }
+}
+
+impl CommitBuffer {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<CommitBuffer>()
+    }
+
+    /// The size of the account once all the data is staged
+    pub fn account_size(&self) -> usize {
+        Self::size_with_discriminator().saturating_add(self.data_len as usize)
+    }
+
+    /// The staged data of a commit buffer, or None if the account is not a commit buffer
+    /// or its data is not fully allocated yet
+    pub fn staged_data(commit_buffer_data: &[u8]) -> Option<&[u8]> {
+        let header_size = Self::size_with_discriminator();
+        let header =
+            Self::try_from_bytes_with_discriminator(commit_buffer_data.get(..header_size)?).ok()?;
+        if header.account_size() != commit_buffer_data.len() {
+            return None;
+        }
+        commit_buffer_data.get(header_size..)
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(CommitBuffer);
+impl_try_from_bytes_with_discriminator_zero_copy!(CommitBuffer);
@@ -1,13 +1,16 @@
-use std::mem::size_of;
+use core::mem::size_of;
 
 use bytemuck::{Pod, Zeroable};
 use solana_program::pubkey::Pubkey;
 
+use crate::args::ErBlockHash;
+use crate::consts::OWNER_COMMIT_NONCE_FLAG;
 use crate::{
     impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
 };
 
 use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+use super::CommitStateLayout;
 
 /// The Commit State Record
 #[repr(C)]
@@ -19,11 +22,45 @@ pub struct CommitRecord {
     /// The account for which the state is committed
     pub account: Pubkey,
 
-    /// The external nonce of the commit. This is used to enforce sequential commits
+    /// The external nonce of the commit. This is used to enforce sequential commits, the
+    /// commits pushed by the owner program carrying [OWNER_COMMIT_NONCE_FLAG]
     pub nonce: u64,
 
     /// The account committed lamports
     pub lamports: u64,
+
+    /// The slot at which the state was committed
+    pub slot: u64,
+
+    /// The hash of the ER block which produced the committed state, zeroed if the commit
+    /// did not provide it
+    pub er_block_hash: ErBlockHash,
+
+    /// The rent the validator advanced for the commit state and the commit record, refunded
+    /// to it at finalize. It excludes the committed lamports deposited as collateral and
+    /// the growth of the delegation metadata recording the ER block hash, paid by the
+    /// validator at commit.
+    pub rent_advanced: u64,
+
+    /// The escrow which refunded the rent advanced to the validator at commit, through the
+    /// [crate::state::CommitSchedule] of the account, and to which it is refunded at
+    /// finalize instead. Zeroed if the commit was funded by the validator.
+    pub escrow: Pubkey,
+
+    /// The lamports the committed escrow declared to spend through the
+    /// [crate::processor::process_call_handler] instructions of the finalize transaction,
+    /// only meaningful if [CommitRecord::has_escrow_spend] is set
+    pub escrow_spend: u64,
+
+    /// Whether the commit declared an escrow spend, see [crate::args::CommitStateArgs::escrow_spend]
+    pub has_escrow_spend: u8,
+
+    /// The [CommitStateLayout] of the commit state written by the commit, from which the
+    /// committed data is read. Zeroed, i.e. [CommitStateLayout::Raw], by the commits which
+    /// predate it.
+    pub commit_state_layout: u8,
+
+    pub padding: [u8; 6],
 }
 
 impl AccountWithDiscriminator for CommitRecord {
@@ -36,6 +73,38 @@ impl CommitRecord {
     pub fn size_with_discriminator() -> usize {
         8 + size_of::<CommitRecord>()
     }
+
+    /// The hash of the ER block which produced the committed state, if provided
+    pub fn er_block_hash(&self) -> Option<ErBlockHash> {
+        (self.er_block_hash != ErBlockHash::default()).then_some(self.er_block_hash)
+    }
+
+    /// Whether the commit was pushed by the owner program of the account, see
+    /// [crate::processor::process_commit_from_owner]
+    pub fn is_owner_commit(&self) -> bool {
+        self.nonce & OWNER_COMMIT_NONCE_FLAG != 0
+    }
+
+    /// The nonce of the commit in the sequence of the commits of the account, without the
+    /// owner commit flag
+    pub fn sequence_nonce(&self) -> u64 {
+        self.nonce & !OWNER_COMMIT_NONCE_FLAG
+    }
+
+    /// The escrow which funded the commit, if any
+    pub fn escrow(&self) -> Option<Pubkey> {
+        (self.escrow != Pubkey::default()).then_some(self.escrow)
+    }
+
+    /// The escrow spend declared by the commit, if any
+    pub fn escrow_spend(&self) -> Option<u64> {
+        (self.has_escrow_spend != 0).then_some(self.escrow_spend)
+    }
+
+    /// The layout of the commit state of the commit, or None if unknown
+    pub fn commit_state_layout(&self) -> Option<CommitStateLayout> {
+        CommitStateLayout::try_from(self.commit_state_layout).ok()
+    }
 }
 
 impl_to_bytes_with_discriminator_zero_copy!(CommitRecord);
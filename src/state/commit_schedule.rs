@@ -0,0 +1,93 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Commit Schedule of a delegated account, set by its rent payer, limits the commits
+/// accepted per interval of slots and funds them from an ephemeral balance escrow of the
+/// rent payer: the escrow refunds the validator the rent of the commit PDAs and pays it a
+/// fee at every commit, the rent being refunded to the escrow at finalize
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct CommitSchedule {
+    /// The ephemeral balance escrow funding the commits
+    pub escrow: Pubkey,
+    /// The payer of the escrow, the rent payer of the delegation
+    pub escrow_payer: Pubkey,
+    /// The length of an interval in slots, the intervals starting at the slot the schedule
+    /// was set
+    pub interval_slots: u64,
+    /// The commits accepted per interval
+    pub max_commits_per_interval: u64,
+    /// The lamports paid by the escrow to the validator at every commit
+    pub commit_fee_lamports: u64,
+    /// The first slot of the current interval
+    pub interval_start_slot: u64,
+    /// The commits accepted in the current interval
+    pub commits_in_interval: u64,
+    /// The index of the escrow, see [crate::pda::ephemeral_balance_pda_from_payer]
+    pub escrow_index: u8,
+    /// The bump of the escrow, to sign the transfers out of it
+    pub escrow_bump: u8,
+    pub padding: [u8; 6],
+}
+
+impl AccountWithDiscriminator for CommitSchedule {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::CommitSchedule
+    }
+}
+
+impl CommitSchedule {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<CommitSchedule>()
+    }
+
+    /// Record a commit at the slot, moving to the interval containing it.
+    /// Returns false if the commits of that interval are exhausted.
+    pub fn record_commit(&mut self, slot: u64) -> bool {
+        let elapsed = slot.saturating_sub(self.interval_start_slot);
+        if self.interval_slots > 0 && elapsed >= self.interval_slots {
+            self.interval_start_slot = slot - elapsed % self.interval_slots;
+            self.commits_in_interval = 0;
+        }
+        if self.commits_in_interval >= self.max_commits_per_interval {
+            return false;
+        }
+        self.commits_in_interval += 1;
+        true
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(CommitSchedule);
+impl_try_from_bytes_with_discriminator_zero_copy!(CommitSchedule);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_commit() {
+        let mut schedule = CommitSchedule {
+            interval_slots: 10,
+            max_commits_per_interval: 2,
+            interval_start_slot: 100,
+            ..Default::default()
+        };
+
+        assert!(schedule.record_commit(100));
+        assert!(schedule.record_commit(105));
+        assert!(!schedule.record_commit(109));
+
+        // The next intervals are aligned on the first one
+        assert!(schedule.record_commit(125));
+        assert_eq!(schedule.interval_start_slot, 120);
+        assert_eq!(schedule.commits_in_interval, 1);
+    }
+}
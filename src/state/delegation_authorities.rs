@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::{impl_to_bytes_with_discriminator_borsh, impl_try_from_bytes_with_discriminator_borsh};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The maximum number of validators a delegation authority can add to its delegation
+pub const MAX_DELEGATION_AUTHORITIES: usize = 4;
+
+/// The Delegation Authorities of a delegated account hold the validators which can commit
+/// its state besides the authority of its delegation record, so that the delegation stays
+/// available when that validator is not, see
+/// [crate::processor::process_add_delegation_authority]. A commit signed by one of them is
+/// attributed to it, which then finalizes the commit, see
+/// [crate::processor::fast::process_commit_state].
+///
+/// The authorities only hold for the delegation they were added to, so that the validators
+/// of a previous delegation of the account cannot commit it once redelegated.
+#[derive(BorshSerialize, BorshDeserialize, Default, Debug, PartialEq)]
+pub struct DelegationAuthorities {
+    /// The delegated account, which the PDA is derived from
+    pub delegated_account: Pubkey,
+    /// The delegation slot of the delegation the authorities were added to, see
+    /// [crate::state::DelegationRecord::delegation_slot]
+    pub delegation_slot: u64,
+    /// The validators which can commit the delegated account
+    pub authorities: Vec<Pubkey>,
+}
+
+impl AccountWithDiscriminator for DelegationAuthorities {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::DelegationAuthorities
+    }
+}
+
+impl DelegationAuthorities {
+    pub fn size_with_discriminator(&self) -> usize {
+        8 + 32 + 8 + 4 + 32 * self.authorities.len()
+    }
+
+    /// Returns true if the validator can commit the delegated account
+    pub fn contains(&self, validator: &Pubkey) -> bool {
+        self.authorities.contains(validator)
+    }
+
+    /// Returns true if the validator can commit the delegation started at the delegation slot
+    pub fn is_authority(&self, validator: &Pubkey, delegation_slot: u64) -> bool {
+        self.delegation_slot == delegation_slot && self.contains(validator)
+    }
+
+    /// Add the validator, if not already added
+    pub fn add(&mut self, validator: Pubkey) {
+        if !self.contains(&validator) {
+            self.authorities.push(validator);
+        }
+    }
+
+    /// Remove the validator, if added
+    pub fn remove(&mut self, validator: &Pubkey) {
+        self.authorities
+            .retain(|authority| !authority.eq(validator));
+    }
+}
+
+impl_to_bytes_with_discriminator_borsh!(DelegationAuthorities);
+impl_try_from_bytes_with_discriminator_borsh!(DelegationAuthorities);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delegation_authorities() {
+        let validator = Pubkey::new_unique();
+        let mut authorities = DelegationAuthorities {
+            delegated_account: Pubkey::new_unique(),
+            delegation_slot: 10,
+            authorities: vec![],
+        };
+        authorities.add(validator);
+        authorities.add(validator);
+        assert_eq!(authorities.authorities, vec![validator]);
+        assert!(authorities.contains(&validator));
+        assert!(!authorities.contains(&Pubkey::new_unique()));
+        assert!(authorities.is_authority(&validator, 10));
+        assert!(!authorities.is_authority(&validator, 11));
+
+        let mut data = vec![0; authorities.size_with_discriminator()];
+        authorities
+            .to_bytes_with_discriminator(&mut data.as_mut_slice())
+            .unwrap();
+        assert_eq!(
+            DelegationAuthorities::try_from_bytes_with_discriminator(&data).unwrap(),
+            authorities
+        );
+
+        authorities.remove(&validator);
+        assert!(!authorities.contains(&validator));
+    }
+}
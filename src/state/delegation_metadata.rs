@@ -1,13 +1,16 @@
+use crate::args::{ErBlockHash, MaxAccountSize, RentCoPayer, SeedTemplate, Seeds};
 use crate::{impl_to_bytes_with_discriminator_borsh, impl_try_from_bytes_with_discriminator_borsh};
+use borsh::io::{Read, Write};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
 use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+use super::utils::trailing::deserialize_trailing;
 
 /// The Delegated Metadata includes Account Seeds, max delegation time, seeds
 /// and other meta information about the delegated account.
 /// * Everything necessary at cloning time is instead stored in the delegation record.
-#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct DelegationMetadata {
     /// The last nonce account had during delegation update
     /// Deprecated: The last slot at which the delegation was updated
@@ -15,9 +18,186 @@ pub struct DelegationMetadata {
     /// Whether the account can be undelegated or not
     pub is_undelegatable: bool,
     /// The seeds of the account, used to reopen it on undelegation
-    pub seeds: Vec<Vec<u8>>,
+    pub seeds: Seeds,
     /// The account that paid the rent for the delegation PDAs
     pub rent_payer: Pubkey,
+    /// The account receiving the lamports of the delegated account when it is closed on
+    /// undelegation, set once the owner program approved it
+    pub close_destination: Option<Pubkey>,
+    /// Whether the owner program expects the v2 payload in the external undelegate CPI
+    pub extended_undelegate_payload: bool,
+    /// The undelegation requested by the rent payer or the owner program, if any
+    pub undelegation_request: Option<UndelegationRequest>,
+    /// The template of the seeds of the account when they contain keys of the delegation,
+    /// in which case [DelegationMetadata::seeds] is empty and the seeds are resolved on
+    /// undelegation
+    pub seed_template: Option<SeedTemplate>,
+    /// The ER block hash of the last finalized commit which provided one, that the next
+    /// commit providing one must differ from, see [crate::args::CommitStateArgs::er_block_hash]
+    pub last_er_block_hash: Option<ErBlockHash>,
+    /// Whether the commits of the account are scheduled and funded by an escrow, in which
+    /// case they must pass the [crate::state::CommitSchedule] of the account
+    pub commit_scheduled: bool,
+    /// The hash of the data of the account at delegation, which the first commit may claim
+    /// to start from, see [crate::args::CommitStateArgs::base_state_hash]
+    pub delegated_data_hash: Option<[u8; 32]>,
+    /// The discriminator of the external undelegate instruction of the owner program, in
+    /// place of [crate::consts::EXTERNAL_UNDELEGATE_DISCRIMINATOR], if the program config of
+    /// the owner program allowed the override at delegation, see
+    /// [crate::args::DelegateArgs::undelegate_discriminator]
+    pub undelegate_discriminator: Option<[u8; 8]>,
+    /// The slots the validator can go without a heartbeat before the account is undelegatable
+    /// as if [DelegationMetadata::is_undelegatable] was set, if the delegation opted into it,
+    /// see [crate::state::ValidatorLiveness]
+    pub heartbeat_timeout_slots: Option<u64>,
+    /// The data length cap of the account enforced when its commits are validated and
+    /// finalized, see [crate::args::DelegateArgs::max_account_size]
+    pub max_account_size: Option<MaxAccountSize>,
+    /// The slot at which the last commit of the account was finalized, none until its first
+    /// commit, from which its validator is expected to commit within the commit frequency of
+    /// the delegation, see [crate::processor::process_force_undelegate]
+    pub last_commit_slot: Option<u64>,
+    /// The unix timestamp at which the last commit of the account was finalized, along with
+    /// [DelegationMetadata::last_commit_slot], for off-chain indexers
+    pub last_commit_timestamp: Option<i64>,
+    /// The account that paid a share of the rent of the delegation PDAs along with
+    /// [DelegationMetadata::rent_payer], refunded what it paid on undelegation, see
+    /// [crate::args::DelegateArgs::rent_co_payer]
+    pub rent_co_payer: Option<RentCoPayer>,
+    /// The lamports [DelegationMetadata::rent_co_payer] paid at delegation, refunded to it net
+    /// of its share of the rent fees on undelegation, the rent payer being refunded the rest,
+    /// including any growth of the delegation PDAs. Serialized along with the co-payer.
+    pub rent_co_payer_lamports: u64,
+    /// Whether the account was made undelegatable because its validator stopped committing,
+    /// see [crate::processor::process_force_undelegate], so that anyone can undelegate it
+    /// without the validator or its fees vault. Serialized as the
+    /// [DelegationLifecycle::Stale] status of the delegation, see
+    /// [DelegationMetadata::lifecycle]
+    pub is_stale: bool,
+}
+
+/// The lifecycle status of a delegation, serialized as the last trailing byte of the
+/// delegation metadata once the delegation left the [DelegationLifecycle::Active] status, so
+/// that indexers can tell the status of a delegation from the end of its metadata account.
+/// Existing accounts without it read as [DelegationLifecycle::Active] or, from the
+/// `is_undelegatable` bool, [DelegationLifecycle::Undelegatable].
+///
+/// The status is derived from the delegation metadata, so it is updated by every processor
+/// writing the metadata on a transition of the delegation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum DelegationLifecycle {
+    /// The account is delegated and committed by its validator
+    #[default]
+    Active,
+    /// The account can be undelegated, see [DelegationMetadata::is_undelegatable]
+    Undelegatable,
+    /// The undelegation of the account was requested and is not undelegatable yet, its grace
+    /// period possibly being elapsed, see [DelegationMetadata::undelegation_request]
+    UndelegationRequested,
+    /// The validator of the delegation stopped committing and the account can be undelegated
+    /// without it, see [DelegationMetadata::is_stale]
+    Stale,
+}
+
+/// An undelegation requested by the rent payer or the owner program, see
+/// [crate::processor::process_request_undelegation]
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UndelegationRequest {
+    /// The slot at which the undelegation was requested
+    pub slot: u64,
+    /// The slot from which commits must allow the undelegation and from which the account
+    /// can be made undelegatable without a commit
+    pub deadline_slot: u64,
+}
+
+impl UndelegationRequest {
+    /// Whether the grace period of the request elapsed at the slot
+    pub fn is_overdue(&self, slot: u64) -> bool {
+        slot >= self.deadline_slot
+    }
+}
+
+impl BorshSerialize for DelegationMetadata {
+    fn serialize<W: Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        self.last_update_nonce.serialize(writer)?;
+        self.is_undelegatable.serialize(writer)?;
+        self.seeds.serialize(writer)?;
+        self.rent_payer.serialize(writer)?;
+        let trailing_fields = self.serialized_trailing_fields();
+        if trailing_fields > 0 {
+            self.close_destination.serialize(writer)?;
+        }
+        if trailing_fields > 1 {
+            self.extended_undelegate_payload.serialize(writer)?;
+        }
+        if trailing_fields > 2 {
+            self.undelegation_request.serialize(writer)?;
+        }
+        if trailing_fields > 3 {
+            self.seed_template.serialize(writer)?;
+        }
+        if trailing_fields > 4 {
+            self.last_er_block_hash.serialize(writer)?;
+        }
+        if trailing_fields > 5 {
+            self.commit_scheduled.serialize(writer)?;
+        }
+        if trailing_fields > 6 {
+            self.delegated_data_hash.serialize(writer)?;
+        }
+        if trailing_fields > 7 {
+            self.undelegate_discriminator.serialize(writer)?;
+        }
+        if trailing_fields > 8 {
+            self.heartbeat_timeout_slots.serialize(writer)?;
+        }
+        if trailing_fields > 9 {
+            self.max_account_size.serialize(writer)?;
+        }
+        if trailing_fields > 10 {
+            self.last_commit_slot.serialize(writer)?;
+        }
+        if trailing_fields > 11 {
+            self.last_commit_timestamp.serialize(writer)?;
+        }
+        if trailing_fields > 12 {
+            self.rent_co_payer.serialize(writer)?;
+        }
+        if trailing_fields > 13 {
+            self.rent_co_payer_lamports.serialize(writer)?;
+        }
+        if trailing_fields > 14 {
+            self.lifecycle().serialize(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for DelegationMetadata {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        Ok(Self {
+            last_update_nonce: u64::deserialize_reader(reader)?,
+            is_undelegatable: bool::deserialize_reader(reader)?,
+            seeds: Seeds::deserialize_reader(reader)?,
+            rent_payer: Pubkey::deserialize_reader(reader)?,
+            close_destination: deserialize_trailing(reader)?,
+            extended_undelegate_payload: deserialize_trailing(reader)?,
+            undelegation_request: deserialize_trailing(reader)?,
+            seed_template: deserialize_trailing(reader)?,
+            last_er_block_hash: deserialize_trailing(reader)?,
+            commit_scheduled: deserialize_trailing(reader)?,
+            delegated_data_hash: deserialize_trailing(reader)?,
+            undelegate_discriminator: deserialize_trailing(reader)?,
+            heartbeat_timeout_slots: deserialize_trailing(reader)?,
+            max_account_size: deserialize_trailing(reader)?,
+            last_commit_slot: deserialize_trailing(reader)?,
+            last_commit_timestamp: deserialize_trailing(reader)?,
+            rent_co_payer: deserialize_trailing(reader)?,
+            rent_co_payer_lamports: deserialize_trailing(reader)?,
+            is_stale: deserialize_trailing::<DelegationLifecycle, _>(reader)?
+                == DelegationLifecycle::Stale,
+        })
+    }
 }
 
 impl AccountWithDiscriminator for DelegationMetadata {
@@ -27,12 +207,82 @@ impl AccountWithDiscriminator for DelegationMetadata {
 }
 
 impl DelegationMetadata {
+    /// The lifecycle status of the delegation, serialized as the last trailing field unless
+    /// [DelegationLifecycle::Active]
+    pub fn lifecycle(&self) -> DelegationLifecycle {
+        if self.is_stale {
+            DelegationLifecycle::Stale
+        } else if self.is_undelegatable {
+            DelegationLifecycle::Undelegatable
+        } else if self.undelegation_request.is_some() {
+            DelegationLifecycle::UndelegationRequested
+        } else {
+            DelegationLifecycle::Active
+        }
+    }
+
     pub fn serialized_size(&self) -> usize {
         AccountDiscriminator::SPACE
         + 8 // last_update_nonce (u64) 
         + 1 // is_undelegatable (bool)
         + 32 // rent_payer (Pubkey)
-        + (4 + self.seeds.iter().map(|s| 4 + s.len()).sum::<usize>()) // seeds (Vec<Vec<u8>>)
+        + self.seeds.serialized_size() // seeds (Vec<Vec<u8>>)
+        + [
+            self.close_destination.map_or(1, |_| 1 + 32), // close_destination (Option<Pubkey>)
+            1, // extended_undelegate_payload (bool)
+            self.undelegation_request.map_or(1, |_| 1 + 16), // undelegation_request (Option<UndelegationRequest>)
+            self.seed_template.map_or(1, |t| 1 + t.serialized_size()), // seed_template (Option<SeedTemplate>)
+            self.last_er_block_hash.map_or(1, |_| 1 + 32), // last_er_block_hash (Option<ErBlockHash>)
+            1, // commit_scheduled (bool)
+            self.delegated_data_hash.map_or(1, |_| 1 + 32), // delegated_data_hash (Option<[u8; 32]>)
+            self.undelegate_discriminator.map_or(1, |_| 1 + 8), // undelegate_discriminator (Option<[u8; 8]>)
+            self.heartbeat_timeout_slots.map_or(1, |_| 1 + 8), // heartbeat_timeout_slots (Option<u64>)
+            self.max_account_size.map_or(1, |_| 1 + 4 + 1), // max_account_size (Option<MaxAccountSize>)
+            self.last_commit_slot.map_or(1, |_| 1 + 8), // last_commit_slot (Option<u64>)
+            self.last_commit_timestamp.map_or(1, |_| 1 + 8), // last_commit_timestamp (Option<i64>)
+            self.rent_co_payer.map_or(1, |_| 1 + 32 + 2), // rent_co_payer (Option<RentCoPayer>)
+            8, // rent_co_payer_lamports (u64)
+            1, // lifecycle (DelegationLifecycle)
+        ]
+            .iter()
+            .take(self.serialized_trailing_fields())
+            .sum::<usize>()
+    }
+
+    /// The number of fields appended to the layout which are serialized: up to the last one
+    /// that is set, so that the layout of existing accounts is unchanged
+    fn serialized_trailing_fields(&self) -> usize {
+        if self.lifecycle() != DelegationLifecycle::Active {
+            15
+        } else if self.rent_co_payer.is_some() {
+            14
+        } else if self.last_commit_timestamp.is_some() {
+            12
+        } else if self.last_commit_slot.is_some() {
+            11
+        } else if self.max_account_size.is_some() {
+            10
+        } else if self.heartbeat_timeout_slots.is_some() {
+            9
+        } else if self.undelegate_discriminator.is_some() {
+            8
+        } else if self.delegated_data_hash.is_some() {
+            7
+        } else if self.commit_scheduled {
+            6
+        } else if self.last_er_block_hash.is_some() {
+            5
+        } else if self.seed_template.is_some() {
+            4
+        } else if self.undelegation_request.is_some() {
+            3
+        } else if self.extended_undelegate_payload {
+            2
+        } else if self.close_destination.is_some() {
+            1
+        } else {
+            0
+        }
     }
 }
 
@@ -44,20 +294,37 @@ mod tests {
     use borsh::to_vec;
 
     use super::*;
+    use crate::args::{OversizedStatePolicy, SeedPlaceholder, MAX_SEEDS};
 
     #[test]
     fn test_serialization_without_discriminator() {
         let original = DelegationMetadata {
-            seeds: vec![
+            seeds: Seeds::try_from(vec![
                 vec![],
                 vec![
                     215, 233, 74, 188, 162, 203, 12, 212, 106, 87, 189, 226, 48, 38, 129, 7, 34,
                     82, 254, 106, 161, 35, 74, 146, 30, 211, 164, 97, 139, 136, 136, 77,
                 ],
-            ],
+            ])
+            .unwrap(),
             is_undelegatable: false,
             last_update_nonce: 0,
             rent_payer: Pubkey::default(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
         };
 
         // Serialize
@@ -69,4 +336,653 @@ mod tests {
 
         assert_eq!(deserialized, original);
     }
+
+    #[test]
+    fn test_serialization_with_close_destination() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: true,
+            last_update_nonce: 42,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        // Without a close destination the previous layout is kept
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        metadata.close_destination = Some(Pubkey::new_unique());
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_extended_undelegate_payload() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 7,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: true,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        metadata.close_destination = Some(Pubkey::new_unique());
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_undelegation_request() {
+        let metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 3,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: Some(UndelegationRequest {
+                slot: 10,
+                deadline_slot: 20,
+            }),
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        let request = metadata.undelegation_request.unwrap();
+        assert!(!request.is_overdue(19));
+        assert!(request.is_overdue(20));
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 3,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+        let lifecycle = |metadata: &DelegationMetadata| {
+            let mut data = vec![];
+            metadata.to_bytes_with_discriminator(&mut data).unwrap();
+            assert_eq!(data.len(), metadata.serialized_size());
+            *data.last().unwrap()
+        };
+
+        // An active delegation keeps the layout of the existing accounts
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized)
+                .unwrap()
+                .lifecycle(),
+            DelegationLifecycle::Active
+        );
+
+        // The status of a requested undelegation reads back as not undelegatable
+        metadata.undelegation_request = Some(UndelegationRequest {
+            slot: 10,
+            deadline_slot: 20,
+        });
+        assert_eq!(
+            lifecycle(&metadata),
+            DelegationLifecycle::UndelegationRequested as u8
+        );
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&to_vec(&metadata).unwrap()).unwrap(),
+            metadata
+        );
+
+        metadata.is_undelegatable = true;
+        assert_eq!(
+            lifecycle(&metadata),
+            DelegationLifecycle::Undelegatable as u8
+        );
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&to_vec(&metadata).unwrap()).unwrap(),
+            metadata
+        );
+
+        metadata.is_stale = true;
+        assert_eq!(lifecycle(&metadata), DelegationLifecycle::Stale as u8);
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&to_vec(&metadata).unwrap()).unwrap(),
+            metadata
+        );
+
+        // The bool is kept for the existing decoders, unknown statuses are rejected
+        let mut serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized[8], true as u8);
+        *serialized.last_mut().unwrap() = 4;
+        assert!(DelegationMetadata::try_from_slice(&serialized).is_err());
+
+        // The metadata of an undelegatable delegation without the status reads as is
+        metadata.is_stale = false;
+        metadata.undelegation_request = None;
+        let mut serialized = to_vec(&metadata).unwrap();
+        serialized.pop();
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized)
+                .unwrap()
+                .lifecycle(),
+            DelegationLifecycle::Undelegatable
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_seed_template() {
+        let mut seed_template = SeedTemplate::default();
+        seed_template.push_literal(b"seed").unwrap();
+        seed_template
+            .push_placeholder(SeedPlaceholder::RentPayer)
+            .unwrap();
+        let metadata = DelegationMetadata {
+            seeds: Seeds::default(),
+            is_undelegatable: false,
+            last_update_nonce: 5,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: Some(seed_template),
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_last_er_block_hash() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 5,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: Some([3; 32]),
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        // The previous trailing fields are serialized before the hash
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        metadata.last_er_block_hash = None;
+        assert_eq!(
+            metadata.serialized_size(),
+            to_vec(&metadata).unwrap().len() + 8
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_commit_scheduled() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 5,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: true,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        // Once unscheduled, the previous layout is restored
+        metadata.commit_scheduled = false;
+        assert_eq!(metadata.serialized_size(), 8 + 8 + 1 + 12 + 32);
+        assert_eq!(
+            metadata.serialized_size(),
+            to_vec(&metadata).unwrap().len() + 8
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_delegated_data_hash() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 0,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: Some([4; 32]),
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        // The fields set afterwards keep the layout size
+        let size = metadata.serialized_size();
+        metadata.commit_scheduled = true;
+        metadata.extended_undelegate_payload = true;
+        assert_eq!(metadata.serialized_size(), size);
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&to_vec(&metadata).unwrap()).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_undelegate_discriminator() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 0,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: Some([9; 8]),
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        // Without the override the previous layout is restored
+        metadata.undelegate_discriminator = None;
+        assert_eq!(metadata.serialized_size(), 8 + 8 + 1 + 12 + 32);
+        assert_eq!(
+            metadata.serialized_size(),
+            to_vec(&metadata).unwrap().len() + 8
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_heartbeat_timeout_slots() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 0,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: Some(1_000),
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        metadata.heartbeat_timeout_slots = None;
+        assert_eq!(metadata.serialized_size(), 8 + 8 + 1 + 12 + 32);
+    }
+
+    #[test]
+    fn test_serialization_with_max_account_size() {
+        let metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 0,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: Some(MaxAccountSize {
+                max_len: 1_024,
+                policy: OversizedStatePolicy::Reject,
+            }),
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_last_commit_slot() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 0,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: Some(42),
+            last_commit_timestamp: None,
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        // The trailing fields before it are serialized as unset
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(metadata.serialized_size(), 8 + 8 + 1 + 12 + 32 + 10 + 9);
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        metadata.last_commit_slot = None;
+        assert_eq!(metadata.serialized_size(), 8 + 8 + 1 + 12 + 32);
+    }
+
+    #[test]
+    fn test_serialization_with_last_commit_timestamp() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 0,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: Some(42),
+            last_commit_timestamp: Some(1_700_000_000),
+            rent_co_payer: None,
+            rent_co_payer_lamports: 0,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(metadata.serialized_size(), 8 + 8 + 1 + 12 + 32 + 10 + 9 + 9);
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        // The metadata of accounts finalized before the timestamp was recorded still reads
+        let serialized = &serialized[..serialized.len() - 9];
+        metadata.last_commit_timestamp = None;
+        assert_eq!(
+            DelegationMetadata::try_from_slice(serialized).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_serialization_with_rent_co_payer() {
+        let mut metadata = DelegationMetadata {
+            seeds: Seeds::try_from(vec![b"seed".to_vec()]).unwrap(),
+            is_undelegatable: false,
+            last_update_nonce: 0,
+            rent_payer: Pubkey::new_unique(),
+            close_destination: None,
+            extended_undelegate_payload: false,
+            undelegation_request: None,
+            seed_template: None,
+            last_er_block_hash: None,
+            commit_scheduled: false,
+            delegated_data_hash: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            last_commit_slot: None,
+            last_commit_timestamp: None,
+            rent_co_payer: Some(RentCoPayer {
+                payer: Pubkey::new_unique(),
+                share_bps: 2_500,
+            }),
+            rent_co_payer_lamports: 1_000,
+            is_stale: false,
+        };
+
+        let serialized = to_vec(&metadata).unwrap();
+        assert_eq!(serialized.len() + 8, metadata.serialized_size());
+        assert_eq!(
+            metadata.serialized_size(),
+            8 + 8 + 1 + 12 + 32 + 12 + 35 + 8
+        );
+        assert_eq!(
+            DelegationMetadata::try_from_slice(&serialized).unwrap(),
+            metadata
+        );
+
+        // The metadata of delegations paid by a single payer still reads
+        let serialized = &serialized[..serialized.len() - 12 - 35 - 8];
+        metadata.rent_co_payer = None;
+        metadata.rent_co_payer_lamports = 0;
+        assert_eq!(
+            DelegationMetadata::try_from_slice(serialized).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_seeds_compatible_with_vec() {
+        let seeds = vec![b"seed".to_vec(), vec![7; 32]];
+        let serialized = to_vec(&seeds).unwrap();
+
+        let deserialized = Seeds::try_from_slice(&serialized).unwrap();
+        assert_eq!(deserialized.to_vec(), seeds);
+        assert_eq!(to_vec(&deserialized).unwrap(), serialized);
+        assert_eq!(deserialized.serialized_size(), serialized.len());
+
+        // Too many seeds or a seed too long are rejected
+        assert!(Seeds::try_from_slice(&to_vec(&vec![vec![0u8]; 17]).unwrap()).is_err());
+        assert!(Seeds::try_from_slice(&to_vec(&vec![vec![0u8; 33]]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_seed_template() {
+        let mut template = SeedTemplate::default();
+        template.push_literal(b"vault").unwrap();
+        template
+            .push_placeholder(SeedPlaceholder::RentPayer)
+            .unwrap();
+        template
+            .push_placeholder(SeedPlaceholder::OwnerProgram)
+            .unwrap();
+
+        let serialized = to_vec(&template).unwrap();
+        assert_eq!(serialized.len(), template.serialized_size());
+        assert_eq!(SeedTemplate::try_from_slice(&serialized).unwrap(), template);
+
+        let seeds = template.resolve(&[1; 32], &[2; 32]);
+        assert_eq!(
+            seeds.to_vec(),
+            vec![b"vault".to_vec(), vec![1; 32], vec![2; 32]]
+        );
+
+        // Unknown placeholders and too many seeds are rejected
+        assert!(SeedTemplate::try_from_slice(&[1, 0, 0, 0, 3]).is_err());
+        let mut full = SeedTemplate::default();
+        for _ in 0..MAX_SEEDS {
+            full.push_placeholder(SeedPlaceholder::RentPayer).unwrap();
+        }
+        assert!(full.push_literal(b"seed").is_err());
+    }
 }
@@ -0,0 +1,35 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// The Delegation Package is the portable export of a delegation, produced by
+/// [crate::processor::process_export_delegation_package] and consumed by
+/// [crate::processor::process_import_delegation_package] to recreate the delegation on
+/// another cluster or under another program id.
+///
+/// The package is signed off-chain by its exporter, the signature being verified at
+/// import.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct DelegationPackage {
+    /// The delegation program which exported the delegation
+    pub source_program_id: Pubkey,
+    /// The admin which exported the delegation and signs the package
+    pub exporter: Pubkey,
+    /// The slot at which the delegation was exported
+    pub export_slot: u64,
+    /// The delegated account
+    pub delegated_account: Pubkey,
+    /// The data of the delegation record, discriminator included
+    pub delegation_record: Vec<u8>,
+    /// The data of the delegation metadata, discriminator included
+    pub delegation_metadata: Vec<u8>,
+    /// The hash of the data of the delegated account, see [DelegationPackage::data_hash]
+    pub data_hash: [u8; 32],
+}
+
+impl DelegationPackage {
+    /// The hash of the data of a delegated account, which must be unchanged at import
+    pub fn data_hash(data: &[u8]) -> [u8; 32] {
+        solana_program::hash::hash(data).to_bytes()
+    }
+}
@@ -1,15 +1,20 @@
-use std::mem::size_of;
+use core::mem::size_of;
 
-use crate::impl_to_bytes_with_discriminator_zero_copy;
-use crate::impl_try_from_bytes_with_discriminator_zero_copy;
 use bytemuck::{Pod, Zeroable};
+use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 
+use crate::consts::{COMMIT_TIMEOUT_FREQUENCY_MULTIPLIER, MS_PER_SLOT};
+
 use super::discriminator::AccountDiscriminator;
 use super::discriminator::AccountWithDiscriminator;
 
 /// The Delegation Record stores information such as the authority, the owner and the commit frequency.
 /// This is used by the ephemeral validator to update the state of the delegated account.
+///
+/// The records created before [DelegationRecord::version] was added have the legacy layout,
+/// a prefix of the current one. They are read and written transparently in their layout
+/// until [crate::processor::process_migrate_delegation_record] upgrades them in place.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
 pub struct DelegationRecord {
@@ -27,6 +32,37 @@ pub struct DelegationRecord {
 
     /// The state update frequency in milliseconds
     pub commit_frequency_ms: u64,
+
+    /// The version of the layout of the record, [DelegationRecord::LEGACY_VERSION] when read
+    /// from a record with the legacy layout
+    pub version: u8,
+
+    pub padding: [u8; 7],
+}
+
+/// The layout of the delegation records created before the version was added
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct LegacyDelegationRecord {
+    authority: Pubkey,
+    owner: Pubkey,
+    delegation_slot: u64,
+    lamports: u64,
+    commit_frequency_ms: u64,
+}
+
+impl From<LegacyDelegationRecord> for DelegationRecord {
+    fn from(record: LegacyDelegationRecord) -> Self {
+        Self {
+            authority: record.authority,
+            owner: record.owner,
+            delegation_slot: record.delegation_slot,
+            lamports: record.lamports,
+            commit_frequency_ms: record.commit_frequency_ms,
+            version: Self::LEGACY_VERSION,
+            padding: [0; 7],
+        }
+    }
 }
 
 impl AccountWithDiscriminator for DelegationRecord {
@@ -36,10 +72,164 @@ impl AccountWithDiscriminator for DelegationRecord {
 }
 
 impl DelegationRecord {
+    /// The version of the legacy layout, which has no version field
+    pub const LEGACY_VERSION: u8 = 1;
+
+    /// The version of the current layout
+    pub const VERSION: u8 = 2;
+
     pub fn size_with_discriminator() -> usize {
         8 + size_of::<DelegationRecord>()
     }
+
+    /// The size of a record with the legacy layout
+    pub fn legacy_size_with_discriminator() -> usize {
+        8 + size_of::<LegacyDelegationRecord>()
+    }
+
+    /// The slots the validator can go without committing, a multiple of the commit frequency,
+    /// or None if the delegation has no commit frequency
+    pub fn commit_timeout_slots(&self) -> Option<u64> {
+        if self.commit_frequency_ms == 0 {
+            return None;
+        }
+        Some(
+            self.commit_frequency_ms
+                .saturating_mul(COMMIT_TIMEOUT_FREQUENCY_MULTIPLIER)
+                .div_ceil(MS_PER_SLOT),
+        )
+    }
+
+    /// Whether the validator went without committing for the commit timeout at the slot,
+    /// since the last commit or, if none, since the delegation
+    pub fn is_commit_timed_out(&self, last_commit_slot: Option<u64>, slot: u64) -> bool {
+        let Some(commit_timeout_slots) = self.commit_timeout_slots() else {
+            return false;
+        };
+        let last_commit_slot = last_commit_slot.unwrap_or(self.delegation_slot);
+        slot >= last_commit_slot.saturating_add(commit_timeout_slots)
+    }
+
+    /// Whether the record data has the legacy layout, i.e. needs to be migrated
+    pub fn is_legacy(data: &[u8]) -> bool {
+        data.len() == Self::legacy_size_with_discriminator()
+    }
+
+    /// Reads a record with either layout, the layout being told by the length of the data
+    pub fn try_from_bytes_with_discriminator(data: &[u8]) -> Result<Self, ProgramError> {
+        let Some((discriminator, data)) = data.split_first_chunk::<8>() else {
+            return Err(ProgramError::InvalidAccountData);
+        };
+        if Self::discriminator().to_bytes().ne(discriminator) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if let Ok(record) = bytemuck::try_pod_read_unaligned::<LegacyDelegationRecord>(data) {
+            return Ok(record.into());
+        }
+        let record = bytemuck::try_pod_read_unaligned::<Self>(data)
+            .or(Err(ProgramError::InvalidAccountData))?;
+        if record.version != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(record)
+    }
+
+    /// Writes the record in the layout of the data, so that a record not migrated yet keeps
+    /// its legacy layout. The current layout is always written with [DelegationRecord::VERSION].
+    pub fn to_bytes_with_discriminator(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let Some((discriminator, data)) = data.split_first_chunk_mut::<8>() else {
+            return Err(ProgramError::InvalidAccountData);
+        };
+        let record = Self {
+            version: Self::VERSION,
+            ..*self
+        };
+        // The legacy layout is a prefix of the current one
+        let bytes = bytemuck::bytes_of(&record);
+        if data.len() != bytes.len() && data.len() != size_of::<LegacyDelegationRecord>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let Some(bytes) = bytes.get(..data.len()) else {
+            return Err(ProgramError::InvalidAccountData);
+        };
+        *discriminator = Self::discriminator().to_bytes();
+        data.copy_from_slice(bytes);
+        Ok(())
+    }
 }
 
-impl_to_bytes_with_discriminator_zero_copy!(DelegationRecord);
-impl_try_from_bytes_with_discriminator_zero_copy!(DelegationRecord);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delegation_record() -> DelegationRecord {
+        DelegationRecord {
+            authority: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            delegation_slot: 10,
+            lamports: 1_000,
+            commit_frequency_ms: 30_000,
+            version: DelegationRecord::VERSION,
+            padding: [0; 7],
+        }
+    }
+
+    #[test]
+    fn test_delegation_record_round_trip() {
+        let record = delegation_record();
+        let mut data = vec![0; DelegationRecord::size_with_discriminator()];
+        record.to_bytes_with_discriminator(&mut data).unwrap();
+        assert!(!DelegationRecord::is_legacy(&data));
+        assert_eq!(
+            DelegationRecord::try_from_bytes_with_discriminator(&data).unwrap(),
+            record
+        );
+
+        // A record of an unknown version is rejected
+        data[8 + size_of::<LegacyDelegationRecord>()] = DelegationRecord::VERSION + 1;
+        assert!(DelegationRecord::try_from_bytes_with_discriminator(&data).is_err());
+    }
+
+    #[test]
+    fn test_commit_timeout() {
+        // 30s between commits, timing out after 10 frequencies of 750 slots
+        let record = delegation_record();
+        assert_eq!(record.commit_timeout_slots(), Some(750));
+        assert!(!record.is_commit_timed_out(None, 759));
+        assert!(record.is_commit_timed_out(None, 760));
+        assert!(!record.is_commit_timed_out(Some(500), 1_249));
+        assert!(record.is_commit_timed_out(Some(500), 1_250));
+
+        // A delegation without commit frequency never times out
+        let record = DelegationRecord {
+            commit_frequency_ms: 0,
+            ..record
+        };
+        assert_eq!(record.commit_timeout_slots(), None);
+        assert!(!record.is_commit_timed_out(None, u64::MAX));
+    }
+
+    #[test]
+    fn test_legacy_delegation_record() {
+        let record = delegation_record();
+        let mut data = vec![0; DelegationRecord::legacy_size_with_discriminator()];
+        record.to_bytes_with_discriminator(&mut data).unwrap();
+        assert!(DelegationRecord::is_legacy(&data));
+
+        let legacy = DelegationRecord::try_from_bytes_with_discriminator(&data).unwrap();
+        assert_eq!(legacy.version, DelegationRecord::LEGACY_VERSION);
+        assert_eq!(
+            legacy,
+            DelegationRecord {
+                version: DelegationRecord::LEGACY_VERSION,
+                ..record
+            }
+        );
+
+        // Other lengths are neither layout
+        let mut data = vec![0; DelegationRecord::size_with_discriminator() + 1];
+        assert!(record.to_bytes_with_discriminator(&mut data).is_err());
+        data[..8].copy_from_slice(&AccountDiscriminator::DelegationRecord.to_bytes());
+        assert!(DelegationRecord::try_from_bytes_with_discriminator(&data).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// The inconsistencies found between the PDAs of a delegation, returned as a bitmask by
+/// [crate::processor::process_validate_delegation]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct DelegationViolations(pub u32);
+
+impl DelegationViolations {
+    /// The delegated account is not owned by the delegation program
+    pub const DELEGATED_ACCOUNT_OWNER: u32 = 1 << 0;
+    /// The delegation record is not the PDA of the delegated account, or is not initialized
+    pub const DELEGATION_RECORD: u32 = 1 << 1;
+    /// The delegation metadata is not the PDA of the delegated account, or is not initialized
+    pub const DELEGATION_METADATA: u32 = 1 << 2;
+    /// The commit state account is not the PDA of the delegated account
+    pub const COMMIT_STATE: u32 = 1 << 3;
+    /// The commit record is not the PDA of the delegated account, or cannot be read while
+    /// initialized
+    pub const COMMIT_RECORD: u32 = 1 << 4;
+    /// The seeds in the delegation metadata do not derive the delegated account, or both
+    /// the seeds and a seed template are set
+    pub const SEEDS: u32 = 1 << 5;
+    /// The delegated account holds fewer lamports than recorded in the delegation record
+    pub const LAMPORTS: u32 = 1 << 6;
+    /// Only one of the commit state and the commit record of a pending commit exists
+    pub const INCOMPLETE_COMMIT: u32 = 1 << 7;
+    /// The pending commit is not for the delegated account or not from its authority
+    pub const COMMIT_IDENTITY: u32 = 1 << 8;
+    /// The nonce of the pending commit does not follow the last finalized one
+    pub const COMMIT_NONCE: u32 = 1 << 9;
+    /// The undelegation request is overdue while the account is still not undelegatable
+    pub const UNDELEGATION_OVERDUE: u32 = 1 << 10;
+
+    /// The name of every violation, in the order of their bits
+    pub const NAMES: [&'static str; 11] = [
+        "delegated account owner",
+        "delegation record",
+        "delegation metadata",
+        "commit state",
+        "commit record",
+        "seeds",
+        "lamports",
+        "incomplete commit",
+        "commit identity",
+        "commit nonce",
+        "undelegation overdue",
+    ];
+
+    pub fn insert(&mut self, violation: u32) {
+        self.0 |= violation;
+    }
+
+    pub fn contains(&self, violation: u32) -> bool {
+        self.0 & violation == violation
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The names of the violations found
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        Self::NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violation_names() {
+        let mut violations = DelegationViolations::default();
+        assert!(violations.is_empty());
+        violations.insert(DelegationViolations::SEEDS);
+        violations.insert(DelegationViolations::UNDELEGATION_OVERDUE);
+        assert!(violations.contains(DelegationViolations::SEEDS));
+        assert!(!violations.contains(DelegationViolations::LAMPORTS));
+        assert_eq!(
+            violations.names().collect::<Vec<_>>(),
+            ["seeds", "undelegation overdue"]
+        );
+    }
+}
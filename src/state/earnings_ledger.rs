@@ -0,0 +1,246 @@
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::pda::earnings_ledger_page_pda_from_validator;
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The entries of an earnings ledger page, keeping it creatable by CPI
+pub const EARNINGS_LEDGER_PAGE_ENTRIES: usize = 128;
+
+/// How the validator fees vault earned the lamports of an [EarningsEntry]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+pub enum EarningsKind {
+    /// The lamports the delegated account lost in the ephemeral rollup, settled at finalize
+    Settlement = 0,
+    /// The share of the rent of the delegation PDAs charged at undelegation
+    RentFees = 1,
+}
+
+/// An entry of the earnings ledger of a validator
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct EarningsEntry {
+    /// The slot the validator fees vault earned the lamports
+    pub slot: u64,
+    /// The delegated account the lamports come from
+    pub delegated_account: Pubkey,
+    /// The lamports earned
+    pub amount: u64,
+    /// The [EarningsKind] of the entry
+    pub kind: u8,
+    pub padding: [u8; 7],
+}
+
+impl EarningsEntry {
+    pub fn kind(&self) -> Option<EarningsKind> {
+        EarningsKind::try_from(self.kind).ok()
+    }
+}
+
+/// The header of an Earnings Ledger Page of a validator. The ledger records what the
+/// validator fees vault earns, so that the income of the validator can be reconstructed
+/// from on-chain data alone. It is optional: the validator creates its pages in order with
+/// [crate::processor::process_init_earnings_ledger_page] and passes the last one to the
+/// fee-paying instructions, which append their entries to it. The entries directly follow
+/// the header.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct EarningsLedgerPage {
+    /// The validator whose fees vault earnings are recorded
+    pub validator: Pubkey,
+    /// The index of the page in the ledger
+    pub page: u32,
+    /// The entries appended to the page so far
+    pub len: u32,
+}
+
+impl AccountWithDiscriminator for EarningsLedgerPage {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::EarningsLedgerPage
+    }
+}
+
+impl EarningsLedgerPage {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<EarningsLedgerPage>()
+    }
+
+    /// The size of the account of a page, holding all its entries
+    pub fn account_size() -> usize {
+        Self::size_with_discriminator() + EARNINGS_LEDGER_PAGE_ENTRIES * size_of::<EarningsEntry>()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len as usize >= EARNINGS_LEDGER_PAGE_ENTRIES
+    }
+
+    /// The header of a page
+    pub fn from_page_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() != Self::account_size() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::try_from_bytes_with_discriminator(
+            data.get(..Self::size_with_discriminator())
+                .ok_or(ProgramError::InvalidAccountData)?,
+        )
+    }
+
+    /// Append an entry to a page.
+    /// Returns false if the page is full.
+    pub fn append(data: &mut [u8], entry: &EarningsEntry) -> Result<bool, ProgramError> {
+        if data.len() != Self::account_size() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, entries) = data.split_at_mut(Self::size_with_discriminator());
+        let header = Self::try_from_bytes_with_discriminator_mut(header)?;
+        if header.is_full() {
+            return Ok(false);
+        }
+        let offset = header.len as usize * size_of::<EarningsEntry>();
+        entries
+            .get_mut(offset..offset + size_of::<EarningsEntry>())
+            .ok_or(ProgramError::InvalidAccountData)?
+            .copy_from_slice(bytemuck::bytes_of(entry));
+        header.len += 1;
+        Ok(true)
+    }
+
+    /// The entries appended to a page, from any buffer the account data was fetched into
+    pub fn entries(data: &[u8]) -> Result<Vec<EarningsEntry>, ProgramError> {
+        let len = Self::from_page_data(data)?.len as usize;
+        Ok(data
+            .get(Self::size_with_discriminator()..)
+            .ok_or(ProgramError::InvalidAccountData)?
+            .chunks_exact(size_of::<EarningsEntry>())
+            .take(len)
+            .map(bytemuck::pod_read_unaligned)
+            .collect())
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(EarningsLedgerPage);
+impl_try_from_bytes_with_discriminator_zero_copy!(EarningsLedgerPage);
+
+/// The position of the next entry to read in the earnings ledger of a validator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EarningsLedgerCursor {
+    pub page: u32,
+    pub index: u32,
+}
+
+/// The entries read from a page of an earnings ledger, see [EarningsLedgerCursor::read]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EarningsLedgerRead {
+    pub entries: Vec<EarningsEntry>,
+    /// The cursor to resume reading from, moved to the next page once the page is read to
+    /// its end
+    pub next: EarningsLedgerCursor,
+}
+
+impl EarningsLedgerCursor {
+    /// The page to fetch to read from the cursor
+    pub fn page_pda(&self, validator: &Pubkey) -> Pubkey {
+        earnings_ledger_page_pda_from_validator(validator, self.page)
+    }
+
+    /// Read up to `limit` entries of the page the cursor points to, from the data of its
+    /// account. Clients read the whole ledger by fetching the page of the returned cursor
+    /// until no entry is returned, or the page does not exist yet.
+    pub fn read(&self, page_data: &[u8], limit: usize) -> Result<EarningsLedgerRead, ProgramError> {
+        let page = EarningsLedgerPage::from_page_data(page_data)?;
+        if page.page != self.page {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let page_full = page.is_full();
+        let entries: Vec<EarningsEntry> = EarningsLedgerPage::entries(page_data)?
+            .into_iter()
+            .skip(self.index as usize)
+            .take(limit)
+            .collect();
+        let index = self.index.saturating_add(entries.len() as u32);
+        let next = if page_full && index as usize >= EARNINGS_LEDGER_PAGE_ENTRIES {
+            EarningsLedgerCursor {
+                page: self.page.saturating_add(1),
+                index: 0,
+            }
+        } else {
+            EarningsLedgerCursor {
+                page: self.page,
+                index,
+            }
+        };
+        Ok(EarningsLedgerRead { entries, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_data(page: u32, len: u32) -> Vec<u8> {
+        let mut data = vec![0; EarningsLedgerPage::account_size()];
+        EarningsLedgerPage {
+            validator: Pubkey::new_unique(),
+            page,
+            len: 0,
+        }
+        .to_bytes_with_discriminator(&mut data[..EarningsLedgerPage::size_with_discriminator()])
+        .unwrap();
+        for slot in 0..len {
+            let entry = EarningsEntry {
+                slot: slot as u64,
+                amount: 10,
+                kind: EarningsKind::RentFees.into(),
+                ..Default::default()
+            };
+            assert!(EarningsLedgerPage::append(&mut data, &entry).unwrap());
+        }
+        data
+    }
+
+    #[test]
+    fn test_append_until_full() {
+        let mut data = page_data(0, EARNINGS_LEDGER_PAGE_ENTRIES as u32);
+        assert!(!EarningsLedgerPage::append(&mut data, &EarningsEntry::default()).unwrap());
+
+        let entries = EarningsLedgerPage::entries(&data).unwrap();
+        assert_eq!(entries.len(), EARNINGS_LEDGER_PAGE_ENTRIES);
+        assert_eq!(entries[5].slot, 5);
+        assert_eq!(entries[5].kind(), Some(EarningsKind::RentFees));
+    }
+
+    #[test]
+    fn test_read_with_cursor() {
+        // A partially filled page is read from where the cursor stopped
+        let data = page_data(0, 3);
+        let read = EarningsLedgerCursor::default().read(&data, 2).unwrap();
+        assert_eq!(read.entries.len(), 2);
+        assert_eq!(read.next, EarningsLedgerCursor { page: 0, index: 2 });
+        let read = read.next.read(&data, 10).unwrap();
+        assert_eq!(read.entries[0].slot, 2);
+        assert_eq!(read.next, EarningsLedgerCursor { page: 0, index: 3 });
+
+        // A full page read to its end moves the cursor to the next page
+        let data = page_data(1, EARNINGS_LEDGER_PAGE_ENTRIES as u32);
+        let cursor = EarningsLedgerCursor {
+            page: 1,
+            index: EARNINGS_LEDGER_PAGE_ENTRIES as u32 - 1,
+        };
+        let read = cursor.read(&data, 10).unwrap();
+        assert_eq!(read.entries.len(), 1);
+        assert_eq!(read.next, EarningsLedgerCursor { page: 2, index: 0 });
+
+        // The cursor must point to the page read
+        assert!(EarningsLedgerCursor::default().read(&data, 10).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Feature Gates store which instruction discriminators are enabled.
+/// Gated instructions are rejected by the entrypoint when their bit is cleared. The
+/// instructions validators sent before they were gated are only rejected when passed the
+/// feature gates, see [crate::processor::fast::require_enabled_legacy_instruction].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct FeatureGates {
+    /// Bitmask of the enabled discriminators, bit `n` is set if discriminator `n` is enabled
+    pub enabled: [u8; 32],
+}
+
+impl AccountWithDiscriminator for FeatureGates {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::FeatureGates
+    }
+}
+
+impl Default for FeatureGates {
+    fn default() -> Self {
+        Self {
+            enabled: [u8::MAX; 32],
+        }
+    }
+}
+
+impl FeatureGates {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<FeatureGates>()
+    }
+
+    pub fn is_enabled(&self, discriminator: u8) -> bool {
+        self.enabled
+            .get(discriminator as usize / 8)
+            .is_some_and(|byte| byte & (1 << (discriminator % 8)) != 0)
+    }
+
+    pub fn set_enabled(&mut self, discriminator: u8, enabled: bool) {
+        let mask = 1 << (discriminator % 8);
+        // Every u8 discriminator has its bit in the 32 bytes
+        let Some(byte) = self.enabled.get_mut(discriminator as usize / 8) else {
+            return;
+        };
+        if enabled {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(FeatureGates);
+impl_try_from_bytes_with_discriminator_zero_copy!(FeatureGates);
@@ -0,0 +1,38 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Fee Exemption of a delegated account, granted by the admin, refunds the whole rent
+/// of the delegation PDAs to the rent payer at undelegation instead of taking the rent fees
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct FeeExemption {
+    /// The slot from which the exemption no longer applies. Zero never expires.
+    pub expiry_slot: u64,
+}
+
+impl AccountWithDiscriminator for FeeExemption {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::FeeExemption
+    }
+}
+
+impl FeeExemption {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<FeeExemption>()
+    }
+
+    /// Whether the exemption applies at the slot
+    pub fn is_active(&self, slot: u64) -> bool {
+        self.expiry_slot == 0 || slot < self.expiry_slot
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(FeeExemption);
+impl_try_from_bytes_with_discriminator_zero_copy!(FeeExemption);
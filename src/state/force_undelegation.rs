@@ -0,0 +1,44 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Force Undelegation of a delegated account, scheduled by the admin to recover it
+/// from a failed validator: once its timelock elapsed, the admin can mark the account
+/// undelegatable without a commit of the validator
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ForceUndelegation {
+    /// The admin who scheduled the force undelegation, refunded its rent
+    pub admin: Pubkey,
+    /// The slot the force undelegation was scheduled at
+    pub scheduled_slot: u64,
+    /// The slot from which the force undelegation can be executed
+    pub executable_slot: u64,
+}
+
+impl AccountWithDiscriminator for ForceUndelegation {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ForceUndelegation
+    }
+}
+
+impl ForceUndelegation {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ForceUndelegation>()
+    }
+
+    /// Whether the timelock elapsed at the slot
+    pub fn is_executable(&self, slot: u64) -> bool {
+        slot >= self.executable_slot
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ForceUndelegation);
+impl_try_from_bytes_with_discriminator_zero_copy!(ForceUndelegation);
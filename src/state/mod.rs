@@ -1,11 +1,70 @@
+mod account_summaries;
+mod authority_delegation_chain;
+mod call_handler_permissions;
+mod chunked_commit_state;
+mod commit_buffer;
 mod commit_record;
+mod commit_schedule;
+mod delegation_authorities;
 mod delegation_metadata;
+mod delegation_package;
 mod delegation_record;
+mod delegation_violations;
+mod earnings_ledger;
+mod feature_gates;
+mod fee_exemption;
+mod force_undelegation;
+mod native_stake_state;
+mod parked_undelegation;
+mod pending_state;
 mod program_config;
+mod program_version;
+mod protocol_config;
+mod protocol_stats;
+mod read_lock;
+mod session_report;
+mod staged_delegate_buffer;
+mod streamed_commit_state;
+mod undelegate_progress;
 mod utils;
+mod validator_fees_vault;
+mod validator_info;
+mod validator_liveness;
+mod validator_whitelist_shard;
+mod validator_whitelist_status;
 
+pub use account_summaries::*;
+pub use authority_delegation_chain::*;
+pub use call_handler_permissions::*;
+pub use chunked_commit_state::*;
+pub use commit_buffer::*;
 pub use commit_record::*;
+pub use commit_schedule::*;
+pub use delegation_authorities::*;
 pub use delegation_metadata::*;
+pub use delegation_package::*;
 pub use delegation_record::*;
+pub use delegation_violations::*;
+pub use earnings_ledger::*;
+pub use feature_gates::*;
+pub use fee_exemption::*;
+pub use force_undelegation::*;
+pub use native_stake_state::*;
+pub use parked_undelegation::*;
+pub use pending_state::*;
 pub use program_config::*;
+pub use program_version::*;
+pub use protocol_config::*;
+pub use protocol_stats::*;
+pub use read_lock::*;
+pub use session_report::*;
+pub use staged_delegate_buffer::*;
+pub use streamed_commit_state::*;
+pub use undelegate_progress::*;
+pub use utils::discriminator::AccountDiscriminator;
 pub use utils::*;
+pub use validator_fees_vault::*;
+pub use validator_info::*;
+pub use validator_liveness::*;
+pub use validator_whitelist_shard::*;
+pub use validator_whitelist_status::*;
@@ -0,0 +1,131 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+use static_assertions::const_assert_eq;
+
+/// The data length of a native stake account
+pub const NATIVE_STAKE_STATE_LEN: usize = 200;
+
+const TAG_INITIALIZED: u32 = 1;
+const TAG_STAKE: u32 = 2;
+
+/// A view of the data of a native stake account, the bincode layout of the `StakeStateV2`
+/// of the stake program, read without depending on the stake program types
+///
+/// Only the initialized and the staked states are accepted, the other ones cannot be
+/// delegated, see [crate::processor::process_delegate_stake_account].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct NativeStakeState {
+    tag: [u8; 4],
+    rent_exempt_reserve: [u8; 8],
+    staker: [u8; 32],
+    withdrawer: [u8; 32],
+    lockup_unix_timestamp: [u8; 8],
+    lockup_epoch: [u8; 8],
+    custodian: [u8; 32],
+    voter: [u8; 32],
+    stake: [u8; 8],
+    activation_epoch: [u8; 8],
+    deactivation_epoch: [u8; 8],
+    /// The warmup cooldown rate, the credits observed and the flags of the stake, maintained
+    /// by the stake program
+    rewards: [u8; 20],
+}
+
+const_assert_eq!(size_of::<NativeStakeState>(), NATIVE_STAKE_STATE_LEN);
+
+impl NativeStakeState {
+    pub fn try_from_data(data: &[u8]) -> Option<&Self> {
+        let state: &Self = bytemuck::try_from_bytes(data).ok()?;
+        matches!(u32::from_le_bytes(state.tag), TAG_INITIALIZED | TAG_STAKE).then_some(state)
+    }
+
+    /// The authority which can delegate and deactivate the stake
+    pub fn staker(&self) -> Pubkey {
+        Pubkey::new_from_array(self.staker)
+    }
+
+    /// The authority which can withdraw the lamports and change the authorities
+    pub fn withdrawer(&self) -> Pubkey {
+        Pubkey::new_from_array(self.withdrawer)
+    }
+
+    /// Whether the lockup of the account is in force at the unix timestamp and epoch, in which
+    /// case the withdrawer can only be changed with the signature of the custodian
+    pub fn is_lockup_in_force(&self, unix_timestamp: i64, epoch: u64) -> bool {
+        i64::from_le_bytes(self.lockup_unix_timestamp) > unix_timestamp
+            || u64::from_le_bytes(self.lockup_epoch) > epoch
+    }
+
+    /// The epoch at which the stake is deactivated, none if the account is not staked or its
+    /// stake is not deactivated
+    pub fn deactivation_epoch(&self) -> Option<u64> {
+        if u32::from_le_bytes(self.tag) != TAG_STAKE {
+            return None;
+        }
+        Some(u64::from_le_bytes(self.deactivation_epoch)).filter(|epoch| *epoch != u64::MAX)
+    }
+
+    /// Whether the state is reachable from the state `from` through the stake transitions
+    /// the delegation settles, i.e. the deactivation of the stake, the meta of the account,
+    /// its authorities and its stake being otherwise unchanged. The rewards fields are left
+    /// to the stake program.
+    pub fn is_settleable_from(&self, from: &NativeStakeState) -> bool {
+        let unchanged = |state: &NativeStakeState| NativeStakeState {
+            deactivation_epoch: [0; 8],
+            rewards: [0; 20],
+            ..*state
+        };
+        unchanged(self) == unchanged(from)
+            && (self.deactivation_epoch == from.deactivation_epoch
+                || from.deactivation_epoch().is_none())
+    }
+
+    /// Whether settling the state from the state `from` deactivates the stake
+    pub fn deactivates_from(&self, from: &NativeStakeState) -> bool {
+        from.deactivation_epoch().is_none() && self.deactivation_epoch().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake_state(tag: u32, authority: Pubkey, deactivation_epoch: u64) -> NativeStakeState {
+        NativeStakeState {
+            tag: tag.to_le_bytes(),
+            staker: authority.to_bytes(),
+            withdrawer: authority.to_bytes(),
+            deactivation_epoch: deactivation_epoch.to_le_bytes(),
+            ..Zeroable::zeroed()
+        }
+    }
+
+    #[test]
+    fn test_native_stake_state() {
+        let authority = Pubkey::new_unique();
+        let active = stake_state(TAG_STAKE, authority, u64::MAX);
+        let deactivated = stake_state(TAG_STAKE, authority, 10);
+        let active = NativeStakeState::try_from_data(bytemuck::bytes_of(&active)).unwrap();
+        assert_eq!(active.staker(), authority);
+        assert_eq!(active.withdrawer(), authority);
+        assert_eq!(active.deactivation_epoch(), None);
+        assert_eq!(deactivated.deactivation_epoch(), Some(10));
+        assert!(!active.is_lockup_in_force(0, 0));
+
+        // Only the deactivation of the stake can be settled
+        assert!(active.is_settleable_from(active));
+        assert!(deactivated.is_settleable_from(active));
+        assert!(deactivated.deactivates_from(active));
+        assert!(!active.is_settleable_from(&deactivated));
+        let other_authority = stake_state(TAG_STAKE, Pubkey::new_unique(), u64::MAX);
+        assert!(!other_authority.is_settleable_from(active));
+
+        // Uninitialized accounts and truncated data are rejected
+        let uninitialized = stake_state(0, authority, 0);
+        assert!(NativeStakeState::try_from_data(bytemuck::bytes_of(&uninitialized)).is_none());
+        assert!(NativeStakeState::try_from_data(&[1, 0, 0, 0]).is_none());
+    }
+}
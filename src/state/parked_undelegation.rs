@@ -0,0 +1,53 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The header of a Parked Undelegation, holding the state of an account undelegated without
+/// the CPI to its owner program, see [crate::args::UndelegateMode::ToBuffer]. The parked data
+/// directly follows the header, and the PDA holds the lamports of the undelegated account
+/// until its rent payer claims them, see [crate::processor::process_claim_parked_undelegation].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ParkedUndelegation {
+    /// The rent payer of the delegation, which alone can claim the parked undelegation
+    pub rent_payer: Pubkey,
+
+    /// The owner program the account was delegated from
+    pub owner: Pubkey,
+
+    /// The length of the parked data
+    pub data_len: u64,
+}
+
+impl AccountWithDiscriminator for ParkedUndelegation {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ParkedUndelegation
+    }
+}
+
+impl ParkedUndelegation {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ParkedUndelegation>()
+    }
+
+    /// The parked data of a parked undelegation, or None if the account is not a parked
+    /// undelegation
+    pub fn parked_data(parked_undelegation_data: &[u8]) -> Option<&[u8]> {
+        let header_size = Self::size_with_discriminator();
+        let header =
+            Self::try_from_bytes_with_discriminator(parked_undelegation_data.get(..header_size)?)
+                .ok()?;
+        parked_undelegation_data
+            .get(header_size..header_size.checked_add(header.data_len as usize)?)
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ParkedUndelegation);
+impl_try_from_bytes_with_discriminator_zero_copy!(ParkedUndelegation);
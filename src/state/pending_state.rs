@@ -0,0 +1,271 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use solana_program::pubkey::Pubkey;
+
+use crate::pda::commit_state_pda_from_delegated_account;
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+use super::ChunkedCommitState;
+
+/// The version of the [PendingState] header written by the program
+pub const PENDING_STATE_VERSION: u8 = 1;
+
+/// Flag of a pending state whose commit allows the undelegation of the account
+pub const PENDING_STATE_ALLOWS_UNDELEGATION: u8 = 1 << 0;
+
+/// Flag of a pending state pushed by the owner program, see
+/// [crate::processor::process_commit_from_owner]
+pub const PENDING_STATE_FROM_OWNER: u8 = 1 << 1;
+
+/// The layout of a commit state holding a committed state, recorded by the commit in
+/// [crate::state::CommitRecord::commit_state_layout]. The commit state is read according to
+/// the layout recorded by the program rather than guessed from its data, which the committed
+/// data of a state committed before the header could mimic.
+///
+/// A commit state being streamed has no commit record yet, see [super::StreamedCommitState].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+pub enum CommitStateLayout {
+    /// The raw committed data, as committed before the [PendingState] header
+    Raw = 0,
+    /// A [PendingState] header followed by the committed data
+    Pending = 1,
+    /// A [ChunkedCommitState] header followed by the data and the bitmap of its chunks
+    Chunked = 2,
+}
+
+/// The header of a commit state, holding the pending state of a delegated account: committed
+/// but not finalized yet. The committed data directly follows the header.
+///
+/// The header is versioned so that the programs composing with a delegated account and the
+/// indexers can read its pending state, see [PendingState::read]. A later version only
+/// appends fields, the data starting after [PendingState::header_len] bytes whichever the
+/// version. The pending state is optimistic: it is only applied to the delegated account
+/// once finalized, and readers must check that the account is the commit state PDA of the
+/// delegated account, see [crate::pda::commit_state_pda_from_delegated_account], owned by
+/// the delegation program.
+///
+/// The header changed the layout of the commit state PDA, which held the raw committed data
+/// before: readers of the PDA must read it according to the [CommitStateLayout] recorded in
+/// the commit record, see [PendingState::committed_data].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct PendingState {
+    /// The program owning the delegated account, which interprets the data
+    pub owner: Pubkey,
+
+    /// The nonce of the commit, see [crate::state::CommitRecord::nonce]
+    pub nonce: u64,
+
+    /// The length of the committed data following the header
+    pub data_len: u64,
+
+    /// The size of the header, discriminator included, after which the data starts
+    pub header_len: u16,
+
+    /// The version of the header, see [PENDING_STATE_VERSION]
+    pub version: u8,
+
+    /// The flags of the commit, see [PENDING_STATE_ALLOWS_UNDELEGATION] and
+    /// [PENDING_STATE_FROM_OWNER]
+    pub flags: u8,
+
+    pub padding: [u8; 4],
+}
+
+impl AccountWithDiscriminator for PendingState {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::PendingState
+    }
+}
+
+impl PendingState {
+    pub fn new(owner: Pubkey, nonce: u64, data_len: usize, flags: u8) -> Self {
+        Self {
+            owner,
+            nonce,
+            data_len: data_len as u64,
+            header_len: Self::size_with_discriminator() as u16,
+            version: PENDING_STATE_VERSION,
+            flags,
+            padding: [0; 4],
+        }
+    }
+
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<PendingState>()
+    }
+
+    /// The size of the commit state holding the header and the data
+    pub fn account_size(&self) -> usize {
+        (self.header_len as usize).saturating_add(self.data_len as usize)
+    }
+
+    pub fn allows_undelegation(&self) -> bool {
+        self.flags & PENDING_STATE_ALLOWS_UNDELEGATION != 0
+    }
+
+    pub fn is_from_owner(&self) -> bool {
+        self.flags & PENDING_STATE_FROM_OWNER != 0
+    }
+
+    /// Read the header and the data of a commit state, or None if it does not hold a pending
+    /// state, e.g. a state being streamed
+    pub fn read(commit_state_data: &[u8]) -> Option<(PendingState, &[u8])> {
+        let header = *Self::try_from_bytes_with_discriminator(
+            commit_state_data.get(..Self::size_with_discriminator())?,
+        )
+        .ok()?;
+        if (header.header_len as usize) < Self::size_with_discriminator()
+            || header.account_size() != commit_state_data.len()
+        {
+            return None;
+        }
+        Some((header, commit_state_data.get(header.header_len as usize..)?))
+    }
+
+    /// Read the pending state of a delegated account from an account passed as its commit
+    /// state, as a composing program or an indexer would, checking that the account is the
+    /// commit state PDA of the delegated account owned by the delegation program. Returns
+    /// None if the account holds no pending state. The commit record of the delegated
+    /// account tells whether its commit state has this layout, see [CommitStateLayout].
+    pub fn read_account<'a>(
+        delegated_account: &Pubkey,
+        commit_state: &Pubkey,
+        commit_state_owner: &Pubkey,
+        commit_state_data: &'a [u8],
+    ) -> Option<(PendingState, &'a [u8])> {
+        if !commit_state_owner.eq(&crate::id())
+            || !commit_state.eq(&commit_state_pda_from_delegated_account(delegated_account))
+        {
+            return None;
+        }
+        Self::read(commit_state_data)
+    }
+
+    /// The committed data of a commit state of the layout recorded by its commit: following
+    /// its pending state header, the data of a state committed by its Merkle root, or the
+    /// whole data of a commit state committed before the header. Returns None if the commit
+    /// state does not have the layout.
+    pub fn committed_data(commit_state_data: &[u8], layout: CommitStateLayout) -> Option<&[u8]> {
+        match layout {
+            CommitStateLayout::Raw => Some(commit_state_data),
+            CommitStateLayout::Pending => Self::read(commit_state_data).map(|(_, data)| data),
+            CommitStateLayout::Chunked => ChunkedCommitState::chunked_data(commit_state_data),
+        }
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(PendingState);
+impl_try_from_bytes_with_discriminator_zero_copy!(PendingState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_state_read() {
+        let owner = Pubkey::new_unique();
+        let header = PendingState::new(owner, 7, 3, PENDING_STATE_ALLOWS_UNDELEGATION);
+        let mut commit_state_data = vec![0; header.account_size()];
+        header
+            .to_bytes_with_discriminator(
+                &mut commit_state_data[..PendingState::size_with_discriminator()],
+            )
+            .unwrap();
+        commit_state_data[PendingState::size_with_discriminator()..].copy_from_slice(&[1, 2, 3]);
+
+        let (read, data) = PendingState::read(&commit_state_data).unwrap();
+        assert_eq!(read, header);
+        assert_eq!(read.owner, owner);
+        assert!(read.allows_undelegation());
+        assert!(!read.is_from_owner());
+        assert_eq!(data, &[1, 2, 3]);
+        assert_eq!(
+            PendingState::committed_data(&commit_state_data, CommitStateLayout::Pending),
+            Some(&[1, 2, 3][..])
+        );
+
+        // The data must have the length of the header
+        assert!(PendingState::read(&commit_state_data[..commit_state_data.len() - 1]).is_none());
+
+        // A commit state committed before the header is read as a whole
+        assert!(PendingState::read(&[1, 2, 3]).is_none());
+        assert_eq!(
+            PendingState::committed_data(&[1, 2, 3], CommitStateLayout::Raw),
+            Some(&[1, 2, 3][..])
+        );
+        assert_eq!(
+            PendingState::committed_data(&[1, 2, 3], CommitStateLayout::Pending),
+            None
+        );
+    }
+
+    #[test]
+    fn test_committed_data_follows_the_recorded_layout() {
+        // The data committed before the header holding a pending state header is read as a
+        // whole, as recorded by its commit, rather than as the pending state it mimics
+        let header = PendingState::new(Pubkey::new_unique(), 1, 2, 0);
+        let mut commit_state_data = vec![0; header.account_size()];
+        header
+            .to_bytes_with_discriminator(
+                &mut commit_state_data[..PendingState::size_with_discriminator()],
+            )
+            .unwrap();
+        assert_eq!(
+            PendingState::committed_data(&commit_state_data, CommitStateLayout::Raw),
+            Some(commit_state_data.as_slice())
+        );
+        assert_eq!(
+            PendingState::committed_data(&commit_state_data, CommitStateLayout::Chunked),
+            None
+        );
+
+        // A chunked commit state is only read as such if recorded by its commit
+        let chunked_commit_state = ChunkedCommitState::new([1; 32], 4, 2, 0);
+        let mut commit_state_data = vec![5; chunked_commit_state.account_size()];
+        chunked_commit_state
+            .to_bytes_with_discriminator(
+                &mut commit_state_data[..ChunkedCommitState::size_with_discriminator()],
+            )
+            .unwrap();
+        let data_start = ChunkedCommitState::size_with_discriminator();
+        assert_eq!(
+            PendingState::committed_data(&commit_state_data, CommitStateLayout::Chunked),
+            Some(&commit_state_data[data_start..data_start + 4])
+        );
+        assert_eq!(
+            PendingState::committed_data(&commit_state_data, CommitStateLayout::Pending),
+            None
+        );
+        assert_eq!(
+            PendingState::committed_data(&commit_state_data, CommitStateLayout::Raw),
+            Some(commit_state_data.as_slice())
+        );
+    }
+
+    #[test]
+    fn test_pending_state_later_version() {
+        // A later version appends fields to the header, skipped by the readers of this one
+        let mut header = PendingState::new(Pubkey::new_unique(), 1, 2, 0);
+        header.version = PENDING_STATE_VERSION + 1;
+        header.header_len += 8;
+        let mut commit_state_data = vec![0; header.account_size()];
+        header
+            .to_bytes_with_discriminator(
+                &mut commit_state_data[..PendingState::size_with_discriminator()],
+            )
+            .unwrap();
+        let data_start = header.header_len as usize;
+        commit_state_data[data_start..].copy_from_slice(&[4, 5]);
+
+        let (read, data) = PendingState::read(&commit_state_data).unwrap();
+        assert_eq!(read.version, PENDING_STATE_VERSION + 1);
+        assert_eq!(data, &[4, 5]);
+    }
+}
@@ -1,14 +1,53 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use borsh::io::Read;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
-use std::collections::BTreeSet;
 
 use crate::{impl_to_bytes_with_discriminator_borsh, impl_try_from_bytes_with_discriminator_borsh};
 
 use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+use super::utils::trailing::deserialize_trailing;
 
-#[derive(BorshSerialize, BorshDeserialize, Default, Debug)]
+#[derive(BorshSerialize, Default, Debug)]
 pub struct ProgramConfig {
+    /// The whitelisted validators, large whitelists holding them in shards instead, see
+    /// [super::ValidatorWhitelistShard]. Any validator can commit if the program has no
+    /// whitelist, see [ProgramConfig::has_whitelist].
     pub approved_validators: BTreeSet<Pubkey>,
+    /// The data lengths the accounts of the program can have, if empty any length is allowed
+    pub allowed_data_lens: Vec<u32>,
+    /// The lamports the program can add to the validator in its external undelegate handler,
+    /// on top of the rent of the reopened account, see
+    /// [crate::consts::MAX_UNDELEGATE_LAMPORTS_TOLERANCE]
+    pub undelegate_lamports_tolerance: u64,
+    /// The slots a delegation of an account of the program can last before it must be
+    /// settled, if zero the delegations are unlimited
+    pub max_delegation_slots: u64,
+    /// Whether the delegations of the accounts of the program are validated by the program,
+    /// see [crate::consts::EXTERNAL_VALIDATE_DELEGATION_DISCRIMINATOR]
+    pub validate_delegations: bool,
+    /// Whether the delegations of the accounts of the program can override the discriminator
+    /// of its external undelegate instruction, see
+    /// [crate::args::DelegateArgs::undelegate_discriminator]
+    pub allow_undelegate_discriminator_override: bool,
+    /// Whether validators were whitelisted in shards, so that the whitelist is enforced once
+    /// the `approved_validators` are migrated to them
+    pub has_whitelist_shards: bool,
+}
+
+impl BorshDeserialize for ProgramConfig {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        Ok(Self {
+            approved_validators: BTreeSet::deserialize_reader(reader)?,
+            allowed_data_lens: deserialize_trailing(reader)?,
+            undelegate_lamports_tolerance: deserialize_trailing(reader)?,
+            max_delegation_slots: deserialize_trailing(reader)?,
+            validate_delegations: deserialize_trailing(reader)?,
+            allow_undelegate_discriminator_override: deserialize_trailing(reader)?,
+            has_whitelist_shards: deserialize_trailing(reader)?,
+        })
+    }
 }
 
 impl AccountWithDiscriminator for ProgramConfig {
@@ -19,9 +58,125 @@ impl AccountWithDiscriminator for ProgramConfig {
 
 impl ProgramConfig {
     pub fn size_with_discriminator(&self) -> usize {
-        8 + 4 + 32 * self.approved_validators.len()
+        8 + 4
+            + 32 * self.approved_validators.len()
+            + 4
+            + 4 * self.allowed_data_lens.len()
+            + 8
+            + 8
+            + 1
+            + 1
+            + 1
+    }
+
+    /// Returns true if only the whitelisted validators can commit the accounts of the
+    /// program, a program config created for its other settings whitelisting none
+    pub fn has_whitelist(&self) -> bool {
+        !self.approved_validators.is_empty() || self.has_whitelist_shards
+    }
+
+    /// Returns true if an account of the program can hold `data_len` bytes
+    pub fn is_allowed_data_len(&self, data_len: usize) -> bool {
+        self.allowed_data_lens.is_empty()
+            || self
+                .allowed_data_lens
+                .iter()
+                .any(|allowed| *allowed as usize == data_len)
+    }
+
+    /// Returns true if a delegation started at `delegation_slot` outlived the maximum
+    /// delegation slots at `slot`
+    pub fn is_delegation_expired(&self, delegation_slot: u64, slot: u64) -> bool {
+        self.max_delegation_slots > 0
+            && slot >= delegation_slot.saturating_add(self.max_delegation_slots)
     }
 }
 
 impl_to_bytes_with_discriminator_borsh!(ProgramConfig);
 impl_try_from_bytes_with_discriminator_borsh!(ProgramConfig);
+
+#[cfg(test)]
+mod tests {
+    use borsh::to_vec;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_previous_layout() {
+        let approved_validators = BTreeSet::from([Pubkey::new_unique()]);
+        let serialized = to_vec(&approved_validators).unwrap();
+
+        let program_config = ProgramConfig::try_from_slice(&serialized).unwrap();
+        assert_eq!(program_config.approved_validators, approved_validators);
+        assert!(program_config.allowed_data_lens.is_empty());
+        assert!(program_config.is_allowed_data_len(42));
+        assert_eq!(program_config.undelegate_lamports_tolerance, 0);
+        assert!(!program_config.is_delegation_expired(0, u64::MAX));
+        assert!(!program_config.validate_delegations);
+        assert!(!program_config.allow_undelegate_discriminator_override);
+        assert!(!program_config.has_whitelist_shards);
+        assert!(program_config.has_whitelist());
+    }
+
+    #[test]
+    fn test_allowed_data_lens() {
+        let program_config = ProgramConfig {
+            approved_validators: BTreeSet::new(),
+            allowed_data_lens: vec![8, 100],
+            undelegate_lamports_tolerance: 1_000,
+            max_delegation_slots: 0,
+            validate_delegations: true,
+            allow_undelegate_discriminator_override: true,
+            has_whitelist_shards: false,
+        };
+        let serialized = to_vec(&program_config).unwrap();
+        assert_eq!(
+            serialized.len() + 8,
+            program_config.size_with_discriminator()
+        );
+
+        let program_config = ProgramConfig::try_from_slice(&serialized).unwrap();
+        assert_eq!(program_config.undelegate_lamports_tolerance, 1_000);
+        assert!(program_config.validate_delegations);
+        assert!(program_config.allow_undelegate_discriminator_override);
+        assert!(program_config.is_allowed_data_len(100));
+        assert!(!program_config.is_allowed_data_len(42));
+        assert!(!program_config.has_whitelist());
+    }
+
+    #[test]
+    fn test_whitelist_shards() {
+        let program_config = ProgramConfig {
+            has_whitelist_shards: true,
+            ..Default::default()
+        };
+        let serialized = to_vec(&program_config).unwrap();
+        assert_eq!(
+            serialized.len() + 8,
+            program_config.size_with_discriminator()
+        );
+
+        let program_config = ProgramConfig::try_from_slice(&serialized).unwrap();
+        assert!(program_config.approved_validators.is_empty());
+        assert!(program_config.has_whitelist());
+    }
+
+    #[test]
+    fn test_max_delegation_slots() {
+        let program_config = ProgramConfig {
+            max_delegation_slots: 100,
+            ..Default::default()
+        };
+        let serialized = to_vec(&program_config).unwrap();
+        assert_eq!(
+            serialized.len() + 8,
+            program_config.size_with_discriminator()
+        );
+
+        let program_config = ProgramConfig::try_from_slice(&serialized).unwrap();
+        assert_eq!(program_config.max_delegation_slots, 100);
+        assert!(!program_config.is_delegation_expired(50, 149));
+        assert!(program_config.is_delegation_expired(50, 150));
+        assert!(!program_config.is_delegation_expired(u64::MAX, u64::MAX - 1));
+    }
+}
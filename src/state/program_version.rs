@@ -0,0 +1,83 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[cfg(not(feature = "sdk"))]
+use crate::discriminator::DlpDiscriminator;
+use crate::{impl_to_bytes_with_discriminator_borsh, impl_try_from_bytes_with_discriminator_borsh};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Program Version lets clients detect the capabilities of the delegation program
+/// deployed on a cluster. It is set by the admin at each deploy, see
+/// [crate::processor::process_set_version].
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct ProgramVersion {
+    /// The semver major version of the deployed program
+    pub major: u16,
+    /// The semver minor version of the deployed program
+    pub minor: u16,
+    /// The semver patch version of the deployed program
+    pub patch: u16,
+    /// The git commit hash the deployed program was built from
+    pub git_hash: [u8; 20],
+    /// The lowest instruction discriminator supported by the deployed program
+    pub min_discriminator: u8,
+    /// The highest instruction discriminator supported by the deployed program
+    pub max_discriminator: u8,
+    /// The slot at which the version was set
+    pub slot: u64,
+}
+
+impl AccountWithDiscriminator for ProgramVersion {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ProgramVersion
+    }
+}
+
+impl ProgramVersion {
+    pub const fn size_with_discriminator() -> usize {
+        AccountDiscriminator::SPACE + 2 + 2 + 2 + 20 + 1 + 1 + 8
+    }
+
+    /// Whether the deployed program is at least the given version
+    pub fn is_at_least(&self, major: u16, minor: u16, patch: u16) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+
+    /// Whether the deployed program supports the instruction
+    #[cfg(not(feature = "sdk"))]
+    pub fn supports(&self, discriminator: DlpDiscriminator) -> bool {
+        (self.min_discriminator..=self.max_discriminator).contains(&(discriminator as u8))
+    }
+}
+
+impl_to_bytes_with_discriminator_borsh!(ProgramVersion);
+impl_try_from_bytes_with_discriminator_borsh!(ProgramVersion);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_version() {
+        let version = ProgramVersion {
+            major: 1,
+            minor: 2,
+            patch: 3,
+            min_discriminator: 0,
+            max_discriminator: DlpDiscriminator::RequestUndelegation as u8,
+            ..Default::default()
+        };
+        assert_eq!(
+            borsh::to_vec(&version).unwrap().len() + AccountDiscriminator::SPACE,
+            ProgramVersion::size_with_discriminator()
+        );
+
+        assert!(version.is_at_least(1, 2, 3));
+        assert!(version.is_at_least(0, 9, 9));
+        assert!(!version.is_at_least(1, 3, 0));
+
+        assert!(version.supports(DlpDiscriminator::Delegate));
+        assert!(version.supports(DlpDiscriminator::RequestUndelegation));
+        assert!(!version.supports(DlpDiscriminator::CommitDiffShadow));
+    }
+}
@@ -0,0 +1,89 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::consts::DEFAULT_UNDELEGATION_GRACE_SLOTS;
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Protocol Config stores the protocol wide parameters set by the admin
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ProtocolConfig {
+    /// Ephemeral balance escrows holding less lamports than the threshold are closed at
+    /// undelegation, refunding their rent payer. Zero disables the auto-close.
+    pub escrow_dust_threshold: u64,
+    /// The number of slots after an undelegation request from which commits must allow the
+    /// undelegation. Zero uses [DEFAULT_UNDELEGATION_GRACE_SLOTS].
+    pub undelegation_grace_slots: u64,
+    /// The generation of the protocol fees vault collecting the fees, see
+    /// [crate::pda::fees_vault_pda_from_generation]. Only changed by a vault migration.
+    pub fees_vault_generation: u64,
+    /// The generation of the vault a proposed migration moves the fees to, zero if no
+    /// migration is pending
+    pub pending_fees_vault_generation: u64,
+    /// The slot from which the pending migration can be executed
+    pub fees_vault_migration_slot: u64,
+    /// The key trusted to export the delegation packages imported on this cluster, see
+    /// [crate::processor::process_import_delegation_package]. The default pubkey trusts the
+    /// admin alone.
+    pub trusted_exporter: Pubkey,
+}
+
+/// The generation stored in the data of a protocol fees vault retired by a migration, which
+/// no longer collects fees
+pub const RETIRED_FEES_VAULT_GENERATION: u64 = u64::MAX;
+
+/// The generation of a protocol fees vault, stored in the first 8 bytes of its data. The
+/// vault created at bootstrap holds zeroes or no data, being generation 0.
+pub fn fees_vault_generation(data: &[u8]) -> u64 {
+    data.get(..8)
+        .and_then(|generation| generation.try_into().ok())
+        .map_or(0, u64::from_le_bytes)
+}
+
+impl AccountWithDiscriminator for ProtocolConfig {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ProtocolConfig
+    }
+}
+
+impl ProtocolConfig {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ProtocolConfig>()
+    }
+
+    /// Whether an escrow holding the lamports is dust and should be closed
+    pub fn is_escrow_dust(&self, lamports: u64) -> bool {
+        lamports < self.escrow_dust_threshold
+    }
+
+    /// The grace period of the undelegation requests, in slots
+    pub fn undelegation_grace_slots(&self) -> u64 {
+        match self.undelegation_grace_slots {
+            0 => DEFAULT_UNDELEGATION_GRACE_SLOTS,
+            grace_slots => grace_slots,
+        }
+    }
+
+    /// The key trusted to export delegation packages, the admin unless configured
+    pub fn trusted_exporter(&self, admin: &Pubkey) -> Pubkey {
+        if self.trusted_exporter == Pubkey::default() {
+            *admin
+        } else {
+            self.trusted_exporter
+        }
+    }
+
+    /// Whether a vault migration is pending and its timelock elapsed at the slot
+    pub fn is_fees_vault_migration_executable(&self, slot: u64) -> bool {
+        self.pending_fees_vault_generation != 0 && slot >= self.fees_vault_migration_slot
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ProtocolConfig);
+impl_try_from_bytes_with_discriminator_zero_copy!(ProtocolConfig);
@@ -0,0 +1,75 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Protocol Stats aggregate metrics of the delegations on chain, so that they can be
+/// read without scanning all the delegated accounts.
+///
+/// They are maintained by the delegate, finalize, undelegate and fund escrow from delegated
+/// instructions passed the protocol stats, and resynced by the admin if they drift, see
+/// [crate::processor::process_resync_protocol_stats].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ProtocolStats {
+    /// The total value locked, i.e. the lamports of the delegated accounts as recorded by
+    /// their delegation records
+    pub total_value_locked: u64,
+    /// The slot of the last resync
+    pub last_resync_slot: u64,
+}
+
+impl AccountWithDiscriminator for ProtocolStats {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ProtocolStats
+    }
+}
+
+impl ProtocolStats {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ProtocolStats>()
+    }
+
+    /// Apply the change of the recorded lamports of a delegated account to the total value
+    /// locked. It saturates rather than failing, so that a drift never blocks a delegation.
+    pub fn apply_lamports_change(&mut self, previous_lamports: u64, lamports: u64) {
+        self.total_value_locked = if lamports >= previous_lamports {
+            self.total_value_locked
+                .saturating_add(lamports - previous_lamports)
+        } else {
+            self.total_value_locked
+                .saturating_sub(previous_lamports - lamports)
+        };
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ProtocolStats);
+impl_try_from_bytes_with_discriminator_zero_copy!(ProtocolStats);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_lamports_change() {
+        let mut stats = ProtocolStats::default();
+
+        stats.apply_lamports_change(0, 1_000);
+        stats.apply_lamports_change(1_000, 1_500);
+        assert_eq!(stats.total_value_locked, 1_500);
+        stats.apply_lamports_change(1_500, 0);
+        assert_eq!(stats.total_value_locked, 0);
+
+        // A drift saturates instead of overflowing
+        stats.apply_lamports_change(100, 0);
+        assert_eq!(stats.total_value_locked, 0);
+        stats.apply_lamports_change(0, u64::MAX);
+        stats.apply_lamports_change(0, 1);
+        assert_eq!(stats.total_value_locked, u64::MAX);
+    }
+}
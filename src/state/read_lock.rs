@@ -0,0 +1,69 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use crate::pda::read_lock_pda_from_delegated_account;
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Read Lock marks the slot in which a delegated account was last finalized.
+/// Finalizes are bundled, so in that slot the delegated accounts of the bundle can be
+/// partially settled: programs reading several delegated accounts can check the read lock
+/// to avoid acting on a mix of old and new states. The lock is released at the next slot.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ReadLock {
+    /// The slot of the last finalize of the delegated account
+    pub slot: u64,
+    /// The nonce of the last finalized commit
+    pub nonce: u64,
+}
+
+impl AccountWithDiscriminator for ReadLock {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ReadLock
+    }
+}
+
+impl ReadLock {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ReadLock>()
+    }
+
+    /// Whether the delegated account was finalized in the slot
+    pub fn is_locked(&self, slot: u64) -> bool {
+        self.slot == slot
+    }
+
+    /// Whether the delegated account was finalized in the current slot, for programs reading
+    /// it. Errors if the read lock is not the PDA of the delegated account, an uninitialized
+    /// read lock is never locked.
+    pub fn is_read_locked(
+        delegated_account: &Pubkey,
+        read_lock: &AccountInfo,
+    ) -> Result<bool, ProgramError> {
+        if read_lock
+            .key
+            .ne(&read_lock_pda_from_delegated_account(delegated_account))
+        {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if read_lock.owner.ne(&crate::id()) {
+            return Ok(false);
+        }
+        let read_lock_data = read_lock.try_borrow_data()?;
+        let read_lock = ReadLock::try_from_bytes_with_discriminator(&read_lock_data)?;
+        Ok(read_lock.is_locked(Clock::get()?.slot))
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ReadLock);
+impl_try_from_bytes_with_discriminator_zero_copy!(ReadLock);
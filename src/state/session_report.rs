@@ -0,0 +1,127 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Session Report of an escrow summarizes what a session cost its authority, so that
+/// wallets can render a receipt of the session verifiable on chain.
+///
+/// It is created by the escrow authority, see [crate::processor::process_init_session_report],
+/// accumulated by the finalizes of the commits of the escrow and by the call handlers
+/// spending from it, and frozen by the undelegation of the escrow. Like the protocol stats,
+/// it is passed as an optional trailing account and never blocks the instruction recording
+/// into it, a frozen report being left untouched.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct SessionReport {
+    /// The escrow authority, which created the report and alone can close it
+    pub authority: Pubkey,
+    /// The escrow the report summarizes the session of
+    pub escrow: Pubkey,
+    /// The identity of the validator which finalized the last commit of the escrow
+    pub validator: Pubkey,
+    /// The lamports spent by the escrow, through its commits and its call handlers
+    pub total_spent: u64,
+    /// The number of commits of the escrow finalized
+    pub commits: u64,
+    /// The slot the report was created at
+    pub start_slot: u64,
+    /// The slot of the last record, or of the undelegation of the escrow once frozen
+    pub end_slot: u64,
+    /// Whether the escrow was undelegated, ending the session
+    pub frozen: u8,
+    pub padding: [u8; 7],
+}
+
+impl AccountWithDiscriminator for SessionReport {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::SessionReport
+    }
+}
+
+impl SessionReport {
+    pub fn new(authority: Pubkey, escrow: Pubkey, slot: u64) -> Self {
+        Self {
+            authority,
+            escrow,
+            start_slot: slot,
+            end_slot: slot,
+            ..Default::default()
+        }
+    }
+
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<SessionReport>()
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen != 0
+    }
+
+    /// Record a finalized commit of the escrow, which spent `spent` lamports in the session
+    pub fn record_commit(&mut self, validator: Pubkey, spent: u64, slot: u64) {
+        if self.is_frozen() {
+            return;
+        }
+        self.validator = validator;
+        self.commits = self.commits.saturating_add(1);
+        self.record_spend(spent, slot);
+    }
+
+    /// Record lamports spent by the escrow outside of a commit, e.g. by a call handler
+    pub fn record_spend(&mut self, spent: u64, slot: u64) {
+        if self.is_frozen() {
+            return;
+        }
+        self.total_spent = self.total_spent.saturating_add(spent);
+        self.end_slot = self.end_slot.max(slot);
+    }
+
+    /// End the session at the undelegation of the escrow
+    pub fn freeze(&mut self, slot: u64) {
+        if self.is_frozen() {
+            return;
+        }
+        self.end_slot = self.end_slot.max(slot);
+        self.frozen = 1;
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(SessionReport);
+impl_try_from_bytes_with_discriminator_zero_copy!(SessionReport);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_report_freeze() {
+        let validator = Pubkey::new_unique();
+        let mut report = SessionReport::new(Pubkey::new_unique(), Pubkey::new_unique(), 10);
+
+        report.record_commit(validator, 1_000, 12);
+        report.record_spend(500, 15);
+        report.record_commit(validator, 0, 20);
+        assert_eq!(report.validator, validator);
+        assert_eq!(report.total_spent, 1_500);
+        assert_eq!(report.commits, 2);
+        assert_eq!((report.start_slot, report.end_slot), (10, 20));
+
+        // Once frozen, the report is left untouched
+        report.freeze(25);
+        report.record_commit(Pubkey::new_unique(), 1_000, 30);
+        report.record_spend(1_000, 30);
+        report.freeze(30);
+        assert!(report.is_frozen());
+        assert_eq!(report.validator, validator);
+        assert_eq!(report.total_spent, 1_500);
+        assert_eq!(report.commits, 2);
+        assert_eq!(report.end_slot, 25);
+    }
+}
@@ -0,0 +1,43 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The header of a Staged Delegate Buffer, which stages the initial data of an account
+/// too large to be copied into the delegate buffer of its owner program in one transaction.
+/// The staged data directly follows the header.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct StagedDelegateBuffer {
+    /// The authority allowed to write the staged data and to delegate from it
+    pub authority: Pubkey,
+
+    /// The length of the data to stage
+    pub data_len: u64,
+}
+
+impl AccountWithDiscriminator for StagedDelegateBuffer {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::StagedDelegateBuffer
+    }
+}
+
+impl StagedDelegateBuffer {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<StagedDelegateBuffer>()
+    }
+
+    /// The size of the account once all the data is staged
+    pub fn account_size(&self) -> usize {
+        Self::size_with_discriminator().saturating_add(self.data_len as usize)
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(StagedDelegateBuffer);
+impl_try_from_bytes_with_discriminator_zero_copy!(StagedDelegateBuffer);
@@ -0,0 +1,72 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The header of a Streamed Commit State, a commit state created small and grown chunk by
+/// chunk with [crate::processor::process_grow_commit_state] when the size of the committed
+/// state is not known upfront. The streamed data directly follows the header. A stream
+/// abandoned before being committed is closed with
+/// [crate::processor::process_close_streamed_commit_state].
+///
+/// A streamed commit state has no commit record, the instructions reading it requiring the
+/// commit record to be uninitialized. Once committed, it is rewritten as a
+/// [crate::state::PendingState], see [crate::state::CommitStateLayout].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct StreamedCommitState {
+    /// The length of the data streamed so far
+    pub data_len: u64,
+
+    /// The signer which created the commit state, refunded its rent when the stream is closed
+    pub payer: Pubkey,
+
+    /// The slot of the last chunk streamed, from which the commit timeout of the delegation
+    /// runs before anyone can close the stream, see [crate::state::DelegationRecord]
+    pub last_update_slot: u64,
+}
+
+impl AccountWithDiscriminator for StreamedCommitState {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::StreamedCommitState
+    }
+}
+
+impl StreamedCommitState {
+    pub fn new(payer: Pubkey, slot: u64) -> Self {
+        Self {
+            data_len: 0,
+            payer,
+            last_update_slot: slot,
+        }
+    }
+
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<StreamedCommitState>()
+    }
+
+    /// The size of the account holding the streamed data
+    pub fn account_size(&self) -> usize {
+        Self::size_with_discriminator().saturating_add(self.data_len as usize)
+    }
+
+    /// The streamed data of a commit state, or None if the commit state holds a full state
+    pub fn streamed_data(commit_state_data: &[u8]) -> Option<&[u8]> {
+        let header_size = Self::size_with_discriminator();
+        let header =
+            Self::try_from_bytes_with_discriminator(commit_state_data.get(..header_size)?).ok()?;
+        if header.account_size() != commit_state_data.len() {
+            return None;
+        }
+        commit_state_data.get(header_size..)
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(StreamedCommitState);
+impl_try_from_bytes_with_discriminator_zero_copy!(StreamedCommitState);
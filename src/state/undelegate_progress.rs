@@ -0,0 +1,42 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Undelegate Progress links the two stages of a staged undelegation, see
+/// [crate::processor::fast::process_undelegate_stage1]. It exists while the delegated
+/// account is given back to its owner program but its delegation PDAs are not closed yet,
+/// which blocks a new delegation of the account until the second stage cleans them up.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct UndelegateProgress {
+    /// The validator which ran the first stage, paying the rent of the progress and of the
+    /// undelegate buffer, and which alone can run the second stage until the timeout, see
+    /// [crate::consts::UNDELEGATE_STAGE2_TIMEOUT_SLOTS]
+    pub validator: Pubkey,
+    /// The owner program the delegated account was given back to
+    pub owner: Pubkey,
+    /// The slot of the first stage, from which the timeout of the second stage runs
+    pub slot: u64,
+}
+
+impl AccountWithDiscriminator for UndelegateProgress {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::UndelegateProgress
+    }
+}
+
+impl UndelegateProgress {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<UndelegateProgress>()
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(UndelegateProgress);
+impl_try_from_bytes_with_discriminator_zero_copy!(UndelegateProgress);
@@ -7,6 +7,30 @@ pub enum AccountDiscriminator {
     DelegationMetadata = 102,
     CommitRecord = 101,
     ProgramConfig = 103,
+    FeatureGates = 104,
+    StagedDelegateBuffer = 105,
+    ValidatorInfo = 106,
+    StreamedCommitState = 107,
+    ProtocolConfig = 108,
+    ReadLock = 109,
+    ProgramVersion = 110,
+    FeeExemption = 111,
+    CommitSchedule = 112,
+    EarningsLedgerPage = 113,
+    ForceUndelegation = 114,
+    ProtocolStats = 115,
+    CallHandlerPermissions = 116,
+    ValidatorFeesVault = 117,
+    ValidatorWhitelistShard = 118,
+    UndelegateProgress = 119,
+    ValidatorLiveness = 120,
+    AuthorityDelegationChain = 121,
+    CommitBuffer = 122,
+    ParkedUndelegation = 123,
+    SessionReport = 124,
+    DelegationAuthorities = 125,
+    PendingState = 126,
+    ChunkedCommitState = 127,
 }
 
 impl AccountDiscriminator {
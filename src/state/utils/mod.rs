@@ -1,3 +1,4 @@
 pub mod discriminator;
 pub mod to_bytes;
+pub mod trailing;
 pub mod try_from_bytes;
@@ -5,12 +5,17 @@ macro_rules! impl_to_bytes_with_discriminator_zero_copy {
             pub fn to_bytes_with_discriminator(
                 &self,
                 data: &mut [u8],
-            ) -> Result<(), ::solana_program::program_error::ProgramError> {
-                if data.len() < 8 {
+            ) -> ::core::result::Result<(), ::solana_program::program_error::ProgramError> {
+                // The data must fit the discriminator and the struct exactly, as copying into
+                // a slice of another length panics
+                let Some((discriminator, data)) = data.split_first_chunk_mut::<8>() else {
+                    return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
+                };
+                if data.len() != ::core::mem::size_of::<Self>() {
                     return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
                 }
-                data[..8].copy_from_slice(&Self::discriminator().to_bytes());
-                data[8..].copy_from_slice(bytemuck::bytes_of(self));
+                *discriminator = Self::discriminator().to_bytes();
+                data.copy_from_slice(bytemuck::bytes_of(self));
                 Ok(())
             }
         }
@@ -21,10 +26,10 @@ macro_rules! impl_to_bytes_with_discriminator_zero_copy {
 macro_rules! impl_to_bytes_with_discriminator_borsh {
     ($struct_name:ident) => {
         impl $struct_name {
-            pub fn to_bytes_with_discriminator<W: std::io::Write>(
+            pub fn to_bytes_with_discriminator<W: borsh::io::Write>(
                 &self,
                 writer: &mut W,
-            ) -> Result<(), ::solana_program::program_error::ProgramError> {
+            ) -> ::core::result::Result<(), ::solana_program::program_error::ProgramError> {
                 writer.write_all(&Self::discriminator().to_bytes())?;
                 self.serialize(writer)?;
                 Ok(())
@@ -0,0 +1,15 @@
+use borsh::io::{ErrorKind, Read, Result};
+
+use borsh::BorshDeserialize;
+
+/// Deserializes a field appended to an account layout after accounts were already created,
+/// falling back to its default value if the reader is exhausted, i.e. the account was
+/// serialized with the previous layout.
+pub fn deserialize_trailing<T: BorshDeserialize + Default, R: Read>(reader: &mut R) -> Result<T> {
+    let mut first = [0u8; 1];
+    match reader.read_exact(&mut first) {
+        Ok(()) => T::deserialize_reader(&mut first.as_slice().chain(reader)),
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(T::default()),
+        Err(err) => Err(err),
+    }
+}
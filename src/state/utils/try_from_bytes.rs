@@ -4,27 +4,28 @@ macro_rules! impl_try_from_bytes_with_discriminator_zero_copy {
         impl $struct_name {
             pub fn try_from_bytes_with_discriminator(
                 data: &[u8],
-            ) -> Result<&Self, ::solana_program::program_error::ProgramError> {
-                if data.len() < 8 {
+            ) -> ::core::result::Result<&Self, ::solana_program::program_error::ProgramError> {
+                let Some((discriminator, data)) = data.split_first_chunk::<8>() else {
                     return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
-                }
-                if Self::discriminator().to_bytes().ne(&data[..8]) {
+                };
+                if Self::discriminator().to_bytes().ne(discriminator) {
                     return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
                 }
-                bytemuck::try_from_bytes::<Self>(&data[8..]).or(Err(
+                bytemuck::try_from_bytes::<Self>(data).or(Err(
                     ::solana_program::program_error::ProgramError::InvalidAccountData,
                 ))
             }
             pub fn try_from_bytes_with_discriminator_mut(
                 data: &mut [u8],
-            ) -> Result<&mut Self, ::solana_program::program_error::ProgramError> {
-                if data.len() < 8 {
+            ) -> ::core::result::Result<&mut Self, ::solana_program::program_error::ProgramError>
+            {
+                let Some((discriminator, data)) = data.split_first_chunk_mut::<8>() else {
                     return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
-                }
-                if Self::discriminator().to_bytes().ne(&data[..8]) {
+                };
+                if Self::discriminator().to_bytes().ne(discriminator) {
                     return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
                 }
-                bytemuck::try_from_bytes_mut::<Self>(&mut data[8..]).or(Err(
+                bytemuck::try_from_bytes_mut::<Self>(data).or(Err(
                     ::solana_program::program_error::ProgramError::InvalidAccountData,
                 ))
             }
@@ -38,14 +39,14 @@ macro_rules! impl_try_from_bytes_with_discriminator_borsh {
         impl $struct_name {
             pub fn try_from_bytes_with_discriminator(
                 data: &[u8],
-            ) -> Result<Self, ::solana_program::program_error::ProgramError> {
-                if data.len() < 8 {
+            ) -> ::core::result::Result<Self, ::solana_program::program_error::ProgramError> {
+                let Some((discriminator, data)) = data.split_first_chunk::<8>() else {
                     return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
-                }
-                if Self::discriminator().to_bytes().ne(&data[..8]) {
+                };
+                if Self::discriminator().to_bytes().ne(discriminator) {
                     return Err(::solana_program::program_error::ProgramError::InvalidAccountData);
                 }
-                Self::try_from_slice(&data[8..]).or(Err(
+                Self::try_from_slice(data).or(Err(
                     ::solana_program::program_error::ProgramError::InvalidAccountData,
                 ))
             }
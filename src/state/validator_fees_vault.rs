@@ -0,0 +1,168 @@
+use alloc::vec::Vec;
+use borsh::io::Read;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::{impl_to_bytes_with_discriminator_borsh, impl_try_from_bytes_with_discriminator_borsh};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+use super::utils::trailing::deserialize_trailing;
+
+/// The maximum number of commit relayers a validator can approve
+pub const MAX_COMMIT_RELAYERS: usize = 8;
+
+/// The Validator Fees Vault holds the fees of a validator, and its existence proves the
+/// validator is whitelisted. Its data is empty until the validator registers a commit
+/// relayer or the admin sets its commit quota.
+#[derive(BorshSerialize, Default, Debug, PartialEq)]
+pub struct ValidatorFeesVault {
+    /// The keys the validator approved to submit commits on behalf of its identity, which
+    /// can then stay offline
+    pub commit_relayers: Vec<Pubkey>,
+    /// The commits the validator can submit per slot, see [CommitQuota]
+    pub commit_quota: CommitQuota,
+}
+
+/// The Commit Quota of a validator, set by the admin, bounds the commits and the bytes of
+/// committed state a validator pushes to the chain per slot, so that it cannot monopolize
+/// the bandwidth. The counters of the current slot are updated by every commit.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct CommitQuota {
+    /// The commits accepted per slot, if zero the commits are unlimited
+    pub max_commits_per_slot: u64,
+    /// The bytes of committed state accepted per slot, if zero the bytes are unlimited
+    pub max_bytes_per_slot: u64,
+    /// The slot the counters are for
+    pub slot: u64,
+    /// The commits accepted in the slot
+    pub commits_in_slot: u64,
+    /// The bytes of committed state accepted in the slot
+    pub bytes_in_slot: u64,
+}
+
+impl BorshDeserialize for ValidatorFeesVault {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        Ok(Self {
+            commit_relayers: Vec::deserialize_reader(reader)?,
+            commit_quota: deserialize_trailing(reader)?,
+        })
+    }
+}
+
+impl AccountWithDiscriminator for ValidatorFeesVault {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ValidatorFeesVault
+    }
+}
+
+impl ValidatorFeesVault {
+    pub fn size_with_discriminator(&self) -> usize {
+        8 + 4 + 32 * self.commit_relayers.len() + 8 * 5
+    }
+
+    /// Read the data of a validator fees vault, a vault whose data was never set holding
+    /// the defaults
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.iter().all(|byte| *byte == 0) {
+            return Ok(Self::default());
+        }
+        Self::try_from_bytes_with_discriminator(data)
+    }
+
+    /// Returns true if the relayer can commit on behalf of the validator
+    pub fn is_approved_relayer(&self, relayer: &Pubkey) -> bool {
+        self.commit_relayers.contains(relayer)
+    }
+}
+
+impl CommitQuota {
+    /// Returns true if the commits or their bytes are limited
+    pub fn is_limited(&self) -> bool {
+        self.max_commits_per_slot > 0 || self.max_bytes_per_slot > 0
+    }
+
+    /// Record a commit of `bytes` bytes at the slot, resetting the counters on a new slot.
+    /// Returns false if the commit exceeds the quota of the slot.
+    pub fn record_commit(&mut self, slot: u64, bytes: u64) -> bool {
+        if slot != self.slot {
+            self.slot = slot;
+            self.commits_in_slot = 0;
+            self.bytes_in_slot = 0;
+        }
+        let commits = self.commits_in_slot.saturating_add(1);
+        let bytes = self.bytes_in_slot.saturating_add(bytes);
+        if (self.max_commits_per_slot > 0 && commits > self.max_commits_per_slot)
+            || (self.max_bytes_per_slot > 0 && bytes > self.max_bytes_per_slot)
+        {
+            return false;
+        }
+        self.commits_in_slot = commits;
+        self.bytes_in_slot = bytes;
+        true
+    }
+}
+
+impl_to_bytes_with_discriminator_borsh!(ValidatorFeesVault);
+impl_try_from_bytes_with_discriminator_borsh!(ValidatorFeesVault);
+
+#[cfg(test)]
+mod tests {
+    use borsh::to_vec;
+
+    use super::*;
+
+    #[test]
+    fn test_try_from_account_data() {
+        // A vault whose data was never set holds a zeroed header
+        let vault = ValidatorFeesVault::try_from_account_data(&[0; 8]).unwrap();
+        assert_eq!(vault, ValidatorFeesVault::default());
+
+        let relayer = Pubkey::new_unique();
+        let vault = ValidatorFeesVault {
+            commit_relayers: vec![relayer],
+            ..Default::default()
+        };
+        let mut data = vec![0; vault.size_with_discriminator()];
+        vault
+            .to_bytes_with_discriminator(&mut data.as_mut_slice())
+            .unwrap();
+        let vault = ValidatorFeesVault::try_from_account_data(&data).unwrap();
+        assert!(vault.is_approved_relayer(&relayer));
+        assert!(!vault.is_approved_relayer(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_deserialize_without_commit_quota() {
+        let relayer = Pubkey::new_unique();
+        let data = [
+            AccountDiscriminator::ValidatorFeesVault.to_bytes().to_vec(),
+            to_vec(&vec![relayer]).unwrap(),
+        ]
+        .concat();
+        let vault = ValidatorFeesVault::try_from_account_data(&data).unwrap();
+        assert_eq!(vault.commit_relayers, vec![relayer]);
+        assert_eq!(vault.commit_quota, CommitQuota::default());
+    }
+
+    #[test]
+    fn test_record_commit() {
+        let mut quota = CommitQuota {
+            max_commits_per_slot: 2,
+            max_bytes_per_slot: 100,
+            ..Default::default()
+        };
+        assert!(quota.is_limited());
+
+        assert!(quota.record_commit(10, 60));
+        assert!(!quota.record_commit(10, 50));
+        assert!(quota.record_commit(10, 40));
+        assert!(!quota.record_commit(10, 0));
+
+        // The counters roll over at the next slot
+        assert!(quota.record_commit(11, 100));
+        assert_eq!(quota.commits_in_slot, 1);
+        assert_eq!(quota.bytes_in_slot, 100);
+        assert!(!CommitQuota::default().is_limited());
+    }
+}
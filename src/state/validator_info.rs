@@ -0,0 +1,47 @@
+use alloc::string::String;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{impl_to_bytes_with_discriminator_borsh, impl_try_from_bytes_with_discriminator_borsh};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The maximum length of the endpoint of a validator
+pub const MAX_VALIDATOR_ENDPOINT_LEN: usize = 256;
+
+/// The maximum length of the version and cluster id of a validator
+pub const MAX_VALIDATOR_TAG_LEN: usize = 32;
+
+/// The Validator Info lets clients discover how to reach the ephemeral rollup operated by a
+/// validator from on-chain data alone. It is set by the validator identity itself.
+#[derive(BorshSerialize, BorshDeserialize, Default, Debug, PartialEq)]
+pub struct ValidatorInfo {
+    /// The RPC endpoint of the ephemeral rollup
+    pub endpoint: String,
+    /// The version of the validator software
+    pub version: String,
+    /// The identifier of the cluster the validator belongs to
+    pub cluster_id: String,
+}
+
+impl AccountWithDiscriminator for ValidatorInfo {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ValidatorInfo
+    }
+}
+
+impl ValidatorInfo {
+    pub fn size_with_discriminator(&self) -> usize {
+        8 + 4 + self.endpoint.len() + 4 + self.version.len() + 4 + self.cluster_id.len()
+    }
+
+    /// Returns true if every field fits in its maximum length
+    pub fn is_valid(&self) -> bool {
+        !self.endpoint.is_empty()
+            && self.endpoint.len() <= MAX_VALIDATOR_ENDPOINT_LEN
+            && self.version.len() <= MAX_VALIDATOR_TAG_LEN
+            && self.cluster_id.len() <= MAX_VALIDATOR_TAG_LEN
+    }
+}
+
+impl_to_bytes_with_discriminator_borsh!(ValidatorInfo);
+impl_try_from_bytes_with_discriminator_borsh!(ValidatorInfo);
@@ -0,0 +1,64 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// The Validator Liveness records the last heartbeat of a validator, see
+/// [crate::processor::process_validator_heartbeat]. The delegations opting into a heartbeat
+/// timeout can be undelegated without a commit of their validator once it stopped
+/// heartbeating, see [crate::state::DelegationMetadata::heartbeat_timeout_slots].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ValidatorLiveness {
+    /// The slot of the last heartbeat of the validator
+    pub last_heartbeat_slot: u64,
+}
+
+impl AccountWithDiscriminator for ValidatorLiveness {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ValidatorLiveness
+    }
+}
+
+impl ValidatorLiveness {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ValidatorLiveness>()
+    }
+
+    /// Returns true if the validator did not heartbeat for `timeout_slots` at `slot`, counting
+    /// from `since_slot` if the validator last heartbeated before it, e.g. before the delegation
+    pub fn is_stale(&self, since_slot: u64, timeout_slots: u64, slot: u64) -> bool {
+        slot >= self
+            .last_heartbeat_slot
+            .max(since_slot)
+            .saturating_add(timeout_slots)
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ValidatorLiveness);
+impl_try_from_bytes_with_discriminator_zero_copy!(ValidatorLiveness);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stale() {
+        let liveness = ValidatorLiveness {
+            last_heartbeat_slot: 100,
+        };
+        assert!(!liveness.is_stale(0, 50, 149));
+        assert!(liveness.is_stale(0, 50, 150));
+
+        // A heartbeat older than the delegation counts from the delegation
+        assert!(!liveness.is_stale(200, 50, 249));
+        assert!(liveness.is_stale(200, 50, 250));
+
+        assert!(!liveness.is_stale(0, u64::MAX, u64::MAX - 1));
+    }
+}
@@ -0,0 +1,41 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    impl_to_bytes_with_discriminator_zero_copy, impl_try_from_bytes_with_discriminator_zero_copy,
+};
+
+use super::discriminator::{AccountDiscriminator, AccountWithDiscriminator};
+
+/// A Validator Whitelist Shard whitelists a single validator for a program, see
+/// [crate::pda::validator_whitelist_shard_pda_from_program_id], so that programs can
+/// whitelist more validators than the `approved_validators` of their
+/// [super::ProgramConfig] can hold. A validator is whitelisted if it is in either.
+///
+/// Its existence is the membership, the fields only let clients list the shards of a
+/// program.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ValidatorWhitelistShard {
+    /// The program the validator is whitelisted for
+    pub program: Pubkey,
+    /// The whitelisted validator identity
+    pub validator: Pubkey,
+}
+
+impl AccountWithDiscriminator for ValidatorWhitelistShard {
+    fn discriminator() -> AccountDiscriminator {
+        AccountDiscriminator::ValidatorWhitelistShard
+    }
+}
+
+impl ValidatorWhitelistShard {
+    pub fn size_with_discriminator() -> usize {
+        8 + size_of::<ValidatorWhitelistShard>()
+    }
+}
+
+impl_to_bytes_with_discriminator_zero_copy!(ValidatorWhitelistShard);
+impl_try_from_bytes_with_discriminator_zero_copy!(ValidatorWhitelistShard);
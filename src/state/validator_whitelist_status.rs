@@ -0,0 +1,100 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use super::ProgramConfig;
+
+/// Whether a validator is whitelisted for a program, returned by
+/// [crate::processor::process_is_validator_whitelisted]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct ValidatorWhitelistStatus {
+    /// Whether the validator can commit the accounts of the program, any validator can if
+    /// the program has no program config or no whitelist. Only the `approved_validators` of the program
+    /// config are read here, see [super::ValidatorWhitelistShard] for the others.
+    pub is_whitelisted: bool,
+    /// The slots a delegation of an account of the program can last, see
+    /// [ProgramConfig::max_delegation_slots]
+    pub max_delegation_slots: u64,
+    /// Whether the program has a program config
+    pub has_program_config: bool,
+}
+
+impl ValidatorWhitelistStatus {
+    /// Read the status of the validator from the data of the program config of the program,
+    /// None if it does not exist. Clients can run the same check as the instruction on the
+    /// program config account fetched over RPC.
+    pub fn from_program_config_data(
+        validator: &Pubkey,
+        program_config_data: Option<&[u8]>,
+    ) -> Result<Self, ProgramError> {
+        let Some(program_config_data) = program_config_data else {
+            return Ok(Self {
+                is_whitelisted: true,
+                ..Default::default()
+            });
+        };
+        let program_config = ProgramConfig::try_from_bytes_with_discriminator(program_config_data)?;
+        Ok(Self {
+            is_whitelisted: !program_config.has_whitelist()
+                || program_config.approved_validators.contains(validator),
+            max_delegation_slots: program_config.max_delegation_slots,
+            has_program_config: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeSet;
+
+    use super::*;
+
+    #[test]
+    fn test_from_program_config_data() {
+        let validator = Pubkey::new_unique();
+        assert_eq!(
+            ValidatorWhitelistStatus::from_program_config_data(&validator, None).unwrap(),
+            ValidatorWhitelistStatus {
+                is_whitelisted: true,
+                max_delegation_slots: 0,
+                has_program_config: false,
+            }
+        );
+
+        let program_config = ProgramConfig {
+            approved_validators: BTreeSet::from([validator]),
+            max_delegation_slots: 100,
+            ..Default::default()
+        };
+        let mut data = vec![0; program_config.size_with_discriminator()];
+        program_config
+            .to_bytes_with_discriminator(&mut data.as_mut_slice())
+            .unwrap();
+        assert_eq!(
+            ValidatorWhitelistStatus::from_program_config_data(&validator, Some(&data)).unwrap(),
+            ValidatorWhitelistStatus {
+                is_whitelisted: true,
+                max_delegation_slots: 100,
+                has_program_config: true,
+            }
+        );
+        let status =
+            ValidatorWhitelistStatus::from_program_config_data(&Pubkey::new_unique(), Some(&data))
+                .unwrap();
+        assert!(!status.is_whitelisted);
+        assert!(status.has_program_config);
+
+        // A program config whitelisting no validator does not restrict them
+        let program_config = ProgramConfig {
+            max_delegation_slots: 100,
+            ..Default::default()
+        };
+        let mut data = vec![0; program_config.size_with_discriminator()];
+        program_config
+            .to_bytes_with_discriminator(&mut data.as_mut_slice())
+            .unwrap();
+        let status =
+            ValidatorWhitelistStatus::from_program_config_data(&validator, Some(&data)).unwrap();
+        assert!(status.is_whitelisted);
+    }
+}
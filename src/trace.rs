@@ -0,0 +1,172 @@
+//! Execution tracing of the delegation program.
+//!
+//! With the `trace` feature, the processors of the delegated account lifecycle (delegate,
+//! commit, finalize and undelegate) log a line at their entry, at their successful exit and
+//! at their key decision points:
+//!
+//! `DLP-TRACE <correlation id> <instruction> <event>`
+//!
+//! The correlation id is derived from the delegated account and the nonce being
+//! processed, see [correlation_id], so that the interleaved logs of a bundle can be split
+//! into per-account timelines with [account_timelines].
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use solana_program::pubkey::Pubkey;
+
+pub const TRACE_LOG_PREFIX: &str = "DLP-TRACE";
+
+/// The first 4 bytes of the delegated account in hex, identifying it in the trace logs
+pub fn account_prefix(delegated_account: &[u8; 32]) -> String {
+    delegated_account[..4]
+        .iter()
+        .fold(String::new(), |mut prefix, byte| {
+            let _ = write!(prefix, "{:02x}", byte);
+            prefix
+        })
+}
+
+/// Compact id correlating the trace logs of a delegated account at a nonce
+pub fn correlation_id(delegated_account: &[u8; 32], nonce: u64) -> String {
+    format!("{}-{}", account_prefix(delegated_account), nonce)
+}
+
+/// A trace event logged by a processor
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub account_prefix: String,
+    pub nonce: u64,
+    pub instruction: String,
+    pub event: String,
+}
+
+impl TraceEvent {
+    /// Parse a trace event from a log line, with or without the `Program log: ` prefix.
+    /// Returns None if the line is not a trace log.
+    pub fn try_from_log(log: &str) -> Option<Self> {
+        let log = log.strip_prefix("Program log: ").unwrap_or(log);
+        let mut parts = log.split(' ');
+        if parts.next()? != TRACE_LOG_PREFIX {
+            return None;
+        }
+        let (account_prefix, nonce) = parts.next()?.split_once('-')?;
+        let instruction = parts.next()?;
+        let event = parts.next()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            account_prefix: account_prefix.to_string(),
+            nonce: nonce.parse().ok()?,
+            instruction: instruction.to_string(),
+            event: event.to_string(),
+        })
+    }
+
+    /// Whether the event was logged for the delegated account
+    pub fn is_for(&self, delegated_account: &Pubkey) -> bool {
+        self.account_prefix == account_prefix(&delegated_account.to_bytes())
+    }
+}
+
+/// Parse the trace events of the logs of one or more transactions, in order of execution
+pub fn parse_trace_logs<S: AsRef<str>>(logs: &[S]) -> Vec<TraceEvent> {
+    logs.iter()
+        .filter_map(|log| TraceEvent::try_from_log(log.as_ref()))
+        .collect()
+}
+
+/// Group the trace events of the logs per delegated account prefix, each timeline in
+/// order of execution
+pub fn account_timelines<S: AsRef<str>>(logs: &[S]) -> BTreeMap<String, Vec<TraceEvent>> {
+    let mut timelines: BTreeMap<String, Vec<TraceEvent>> = BTreeMap::new();
+    for event in parse_trace_logs(logs) {
+        timelines
+            .entry(event.account_prefix.clone())
+            .or_default()
+            .push(event);
+    }
+    timelines
+}
+
+#[cfg(all(feature = "trace", not(feature = "sdk")))]
+pub(crate) fn log(delegated_account: &[u8; 32], nonce: u64, instruction: &str, event: &str) {
+    solana_program::msg!(
+        "{} {} {} {}",
+        TRACE_LOG_PREFIX,
+        correlation_id(delegated_account, nonce),
+        instruction,
+        event
+    );
+}
+
+/// Log a trace event, compiled out without the `trace` feature
+#[cfg(not(feature = "sdk"))]
+macro_rules! trace {
+    ($delegated_account:expr, $nonce:expr, $instruction:literal, $event:literal) => {
+        #[cfg(feature = "trace")]
+        $crate::trace::log($delegated_account, $nonce, $instruction, $event);
+        #[cfg(not(feature = "trace"))]
+        let _ = ($delegated_account, $nonce);
+    };
+}
+
+#[cfg(not(feature = "sdk"))]
+pub(crate) use trace;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_timelines() {
+        let account = Pubkey::new_from_array([1; 32]);
+        let other = Pubkey::new_from_array([2; 32]);
+        let logs = [
+            format!(
+                "Program log: {} {} commit enter",
+                TRACE_LOG_PREFIX,
+                correlation_id(&account.to_bytes(), 1)
+            ),
+            "Program log: unrelated".to_string(),
+            format!(
+                "Program log: {} {} commit enter",
+                TRACE_LOG_PREFIX,
+                correlation_id(&other.to_bytes(), 4)
+            ),
+            format!(
+                "Program log: {} {} finalize exit",
+                TRACE_LOG_PREFIX,
+                correlation_id(&account.to_bytes(), 1)
+            ),
+        ];
+
+        let timelines = account_timelines(&logs);
+        assert_eq!(timelines.len(), 2);
+        let timeline = &timelines[&account_prefix(&account.to_bytes())];
+        assert!(timeline.iter().all(|event| event.is_for(&account)));
+        assert_eq!(
+            timeline
+                .iter()
+                .map(|event| (
+                    event.nonce,
+                    event.instruction.as_str(),
+                    event.event.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [(1, "commit", "enter"), (1, "finalize", "exit")]
+        );
+    }
+
+    #[test]
+    fn test_try_from_log_rejects_malformed_lines() {
+        assert!(TraceEvent::try_from_log("Program log: DLP-TRACE 00000000 commit enter").is_none());
+        assert!(
+            TraceEvent::try_from_log("Program log: DLP-TRACE 00000000-x commit enter").is_none()
+        );
+        assert!(TraceEvent::try_from_log("Program log: DLP-TRACE 00000000-1 commit").is_none());
+    }
+}
@@ -1,4 +1,7 @@
-use dlp::state::{CommitRecord, DelegationMetadata, DelegationRecord, ProgramConfig};
+use dlp::args::{commit_state_hash, MaxAccountSize, RentCoPayer, SeedTemplate, Seeds};
+use dlp::state::{
+    CommitRecord, CommitStateLayout, DelegationMetadata, DelegationRecord, ProgramConfig,
+};
 use solana_program::native_token::LAMPORTS_PER_SOL;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
@@ -55,6 +58,14 @@ pub fn get_delegation_record_data(authority: Pubkey, last_update_lamports: Optio
     create_delegation_record_data(authority, DELEGATED_PDA_OWNER_ID, last_update_lamports)
 }
 
+/// A delegation record with the layout of the records created before it was versioned
+#[allow(dead_code)]
+pub fn get_legacy_delegation_record_data(authority: Pubkey) -> Vec<u8> {
+    let mut bytes = get_delegation_record_data(authority, None);
+    bytes.truncate(DelegationRecord::legacy_size_with_discriminator());
+    bytes
+}
+
 #[allow(dead_code)]
 pub fn get_delegation_record_on_curve_data(
     authority: Pubkey,
@@ -75,6 +86,8 @@ pub fn create_delegation_record_data(
         delegation_slot: DEFAULT_DELEGATION_SLOT,
         commit_frequency_ms: DEFAULT_COMMIT_FREQUENCY_MS,
         lamports: last_update_lamports.unwrap_or(Rent::default().minimum_balance(500)),
+        version: DelegationRecord::VERSION,
+        padding: [0; 7],
     };
     let mut bytes = vec![0u8; DelegationRecord::size_with_discriminator()];
     delegation_record
@@ -109,12 +122,173 @@ pub fn create_delegation_metadata_data(
     seeds: &[&[u8]],
     is_undelegatable: bool,
 ) -> Vec<u8> {
-    let delegation_metadata = DelegationMetadata {
+    serialize_delegation_metadata(DelegationMetadata {
         last_update_nonce: DEFAULT_LAST_UPDATE_EXTERNAL_SLOT,
         is_undelegatable,
-        seeds: seeds.iter().map(|s| s.to_vec()).collect(),
+        seeds: Seeds::try_from(seeds).unwrap(),
         rent_payer,
-    };
+        close_destination: None,
+        extended_undelegate_payload: false,
+        undelegation_request: None,
+        seed_template: None,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: None,
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: None,
+        max_account_size: None,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: None,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
+    })
+}
+
+#[allow(dead_code)]
+pub fn create_delegation_metadata_data_with_seed_template(
+    rent_payer: Pubkey,
+    seed_template: SeedTemplate,
+    is_undelegatable: bool,
+) -> Vec<u8> {
+    serialize_delegation_metadata(DelegationMetadata {
+        last_update_nonce: DEFAULT_LAST_UPDATE_EXTERNAL_SLOT,
+        is_undelegatable,
+        seeds: Seeds::default(),
+        rent_payer,
+        close_destination: None,
+        extended_undelegate_payload: false,
+        undelegation_request: None,
+        seed_template: Some(seed_template),
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: None,
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: None,
+        max_account_size: None,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: None,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
+    })
+}
+
+#[allow(dead_code)]
+pub fn create_delegation_metadata_data_with_heartbeat_timeout(
+    rent_payer: Pubkey,
+    heartbeat_timeout_slots: u64,
+) -> Vec<u8> {
+    serialize_delegation_metadata(DelegationMetadata {
+        last_update_nonce: DEFAULT_LAST_UPDATE_EXTERNAL_SLOT,
+        is_undelegatable: false,
+        seeds: Seeds::try_from(DEFAULT_SEEDS).unwrap(),
+        rent_payer,
+        close_destination: None,
+        extended_undelegate_payload: false,
+        undelegation_request: None,
+        seed_template: None,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: None,
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: Some(heartbeat_timeout_slots),
+        max_account_size: None,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: None,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
+    })
+}
+
+#[allow(dead_code)]
+pub fn create_delegation_metadata_data_with_max_account_size(
+    rent_payer: Pubkey,
+    max_account_size: MaxAccountSize,
+) -> Vec<u8> {
+    serialize_delegation_metadata(DelegationMetadata {
+        last_update_nonce: DEFAULT_LAST_UPDATE_EXTERNAL_SLOT,
+        is_undelegatable: DEFAULT_IS_UNDELEGATABLE,
+        seeds: Seeds::try_from(DEFAULT_SEEDS).unwrap(),
+        rent_payer,
+        close_destination: None,
+        extended_undelegate_payload: false,
+        undelegation_request: None,
+        seed_template: None,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: None,
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: None,
+        max_account_size: Some(max_account_size),
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: None,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
+    })
+}
+
+#[allow(dead_code)]
+pub fn create_delegation_metadata_data_on_curve_with_rent_co_payer(
+    rent_payer: Pubkey,
+    rent_co_payer: RentCoPayer,
+    rent_co_payer_lamports: u64,
+    is_undelegatable: bool,
+) -> Vec<u8> {
+    serialize_delegation_metadata(DelegationMetadata {
+        last_update_nonce: DEFAULT_LAST_UPDATE_EXTERNAL_SLOT,
+        is_undelegatable,
+        seeds: Seeds::default(),
+        rent_payer,
+        close_destination: None,
+        extended_undelegate_payload: false,
+        undelegation_request: None,
+        seed_template: None,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: None,
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: None,
+        max_account_size: None,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: Some(rent_co_payer),
+        rent_co_payer_lamports,
+        is_stale: false,
+    })
+}
+
+#[allow(dead_code)]
+pub fn get_delegation_metadata_data_with_data_hash(
+    rent_payer: Pubkey,
+    delegated_data: &[u8],
+) -> Vec<u8> {
+    serialize_delegation_metadata(DelegationMetadata {
+        last_update_nonce: DEFAULT_LAST_UPDATE_EXTERNAL_SLOT,
+        is_undelegatable: DEFAULT_IS_UNDELEGATABLE,
+        seeds: Seeds::try_from(DEFAULT_SEEDS).unwrap(),
+        rent_payer,
+        close_destination: None,
+        extended_undelegate_payload: false,
+        undelegation_request: None,
+        seed_template: None,
+        last_er_block_hash: None,
+        commit_scheduled: false,
+        delegated_data_hash: Some(commit_state_hash(delegated_data)),
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: None,
+        max_account_size: None,
+        last_commit_slot: None,
+        last_commit_timestamp: None,
+        rent_co_payer: None,
+        rent_co_payer_lamports: 0,
+        is_stale: false,
+    })
+}
+
+fn serialize_delegation_metadata(delegation_metadata: DelegationMetadata) -> Vec<u8> {
     let mut bytes = vec![];
     delegation_metadata
         .to_bytes_with_discriminator(&mut bytes)
@@ -124,11 +298,43 @@ pub fn create_delegation_metadata_data(
 
 #[allow(dead_code)]
 pub fn get_commit_record_account_data(authority: Pubkey) -> Vec<u8> {
+    create_commit_record_data_with_er_block_hash(authority, [0; 32])
+}
+
+#[allow(dead_code)]
+pub fn create_commit_record_data_with_er_block_hash(
+    authority: Pubkey,
+    er_block_hash: [u8; 32],
+) -> Vec<u8> {
+    create_commit_record_data(authority, er_block_hash, None)
+}
+
+#[allow(dead_code)]
+pub fn create_commit_record_data_with_escrow_spend(
+    authority: Pubkey,
+    escrow_spend: u64,
+) -> Vec<u8> {
+    create_commit_record_data(authority, [0; 32], Some(escrow_spend))
+}
+
+fn create_commit_record_data(
+    authority: Pubkey,
+    er_block_hash: [u8; 32],
+    escrow_spend: Option<u64>,
+) -> Vec<u8> {
     let commit_record = CommitRecord {
         nonce: 100,
         identity: authority,
         account: DELEGATED_PDA_ID,
         lamports: LAMPORTS_PER_SOL,
+        slot: 0,
+        er_block_hash,
+        rent_advanced: 0,
+        escrow: Pubkey::default(),
+        escrow_spend: escrow_spend.unwrap_or_default(),
+        has_escrow_spend: escrow_spend.is_some().into(),
+        commit_state_layout: CommitStateLayout::Raw.into(),
+        padding: [0; 6],
     };
     let mut bytes = vec![0u8; CommitRecord::size_with_discriminator()];
     commit_record
@@ -139,8 +345,17 @@ pub fn get_commit_record_account_data(authority: Pubkey) -> Vec<u8> {
 
 #[allow(dead_code)]
 pub fn create_program_config_data(approved_validator: Pubkey) -> Vec<u8> {
+    create_program_config_data_with_data_lens(approved_validator, vec![])
+}
+
+#[allow(dead_code)]
+pub fn create_program_config_data_with_data_lens(
+    approved_validator: Pubkey,
+    allowed_data_lens: Vec<u32>,
+) -> Vec<u8> {
     let mut program_config = ProgramConfig {
-        approved_validators: Default::default(),
+        allowed_data_lens,
+        ..Default::default()
     };
     program_config
         .approved_validators
@@ -151,3 +366,16 @@ pub fn create_program_config_data(approved_validator: Pubkey) -> Vec<u8> {
         .unwrap();
     bytes
 }
+
+#[allow(dead_code)]
+pub fn create_program_config_data_without_whitelist(allowed_data_lens: Vec<u32>) -> Vec<u8> {
+    let program_config = ProgramConfig {
+        allowed_data_lens,
+        ..Default::default()
+    };
+    let mut bytes = vec![];
+    program_config
+        .to_bytes_with_discriminator(&mut bytes)
+        .unwrap();
+    bytes
+}
@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use ephemeral_rollups_sdk::anchor::{delegate, ephemeral};
+use ephemeral_rollups_sdk::cpi::DelegateConfig;
+
+declare_id!("GFcBozGicy4asXHn7mRVNQ9HE9iqmMgueyztgr2vQ5B5");
+
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// A token escrow whose release is decided in the ephemeral rollup.
+///
+/// The escrow state is delegated while the tokens stay in a vault owned by the escrow
+/// PDA on the base layer, so they can only be withdrawn once the state with the release
+/// is committed and the escrow undelegated.
+#[ephemeral]
+#[program]
+pub mod test_escrow {
+    use super::*;
+
+    /// Create the escrow of the maker and deposit the tokens in its vault
+    pub fn initialize(ctx: Context<Initialize>, taker: Pubkey, amount: u64) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.maker = ctx.accounts.maker.key();
+        escrow.taker = taker;
+        escrow.mint = ctx.accounts.mint.key();
+        escrow.amount = amount;
+        escrow.released = false;
+        escrow.bump = ctx.bumps.escrow;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.maker_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.maker.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    /// Release the escrow to the taker
+    pub fn release(ctx: Context<Release>) -> Result<()> {
+        ctx.accounts.escrow.released = true;
+        Ok(())
+    }
+
+    /// Delegate the escrow state to the delegation program
+    pub fn delegate(ctx: Context<DelegateEscrow>, validator: Option<Pubkey>) -> Result<()> {
+        let maker = ctx.accounts.payer.key();
+        ctx.accounts.delegate_escrow(
+            &ctx.accounts.payer,
+            &[ESCROW_SEED, maker.as_ref()],
+            DelegateConfig {
+                commit_frequency_ms: u32::MAX,
+                validator,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Transfer the tokens of a released escrow to the taker
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(escrow.released, EscrowError::NotReleased);
+
+        let maker = escrow.maker;
+        let seeds: &[&[u8]] = &[ESCROW_SEED, maker.as_ref(), &[escrow.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.taker_token_account.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                &[seeds],
+            ),
+            escrow.amount,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [ESCROW_SEED, maker.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, token::mint = mint, token::authority = maker)]
+    pub maker_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = maker,
+        associated_token::mint = mint,
+        associated_token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Release<'info> {
+    pub maker: Signer<'info>,
+    #[account(mut, seeds = [ESCROW_SEED, maker.key().as_ref()], bump = escrow.bump, has_one = maker)]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateEscrow<'info> {
+    pub payer: Signer<'info>,
+    /// CHECK: The escrow to delegate
+    #[account(mut, del, seeds = [ESCROW_SEED, payer.key().as_ref()], bump)]
+    pub escrow: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub taker: Signer<'info>,
+    #[account(
+        seeds = [ESCROW_SEED, escrow.maker.as_ref()],
+        bump = escrow.bump,
+        has_one = taker,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        associated_token::mint = escrow.mint,
+        associated_token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = escrow.mint, token::authority = taker)]
+    pub taker_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub released: bool,
+    pub bump: u8,
+}
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("The escrow was not released")]
+    NotReleased,
+}
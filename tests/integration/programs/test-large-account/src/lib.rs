@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use ephemeral_rollups_sdk::anchor::ephemeral;
+use ephemeral_rollups_sdk::types::DelegateAccountArgs;
+
+declare_id!("7Wfr5saLRFWKjrxn1WbEPaiPdPtguJyVQsCCyQJYhtGW");
+
+pub const LARGE_ACCOUNT_SEED: &[u8] = b"large-account";
+
+/// The largest account that can be created, and re-created on undelegation, through CPI
+pub const LARGE_ACCOUNT_SIZE: usize = 10_240;
+
+/// Delegation program discriminators of the instructions called without the sdk
+const DLP_DELEGATE: u8 = 0;
+const DLP_INIT_DELEGATE_BUFFER: u8 = 26;
+
+#[ephemeral]
+#[program]
+pub mod test_large_account {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        ctx.accounts.large_account.load_init()?;
+        Ok(())
+    }
+
+    /// Write bytes into the large account
+    pub fn write(ctx: Context<WriteLargeAccount>, offset: u32, bytes: Vec<u8>) -> Result<()> {
+        let mut large_account = ctx.accounts.large_account.load_mut()?;
+        write_bytes(&mut large_account.data, offset, &bytes)
+    }
+
+    /// Write bytes into a commit buffer owned by this program, standing in for the
+    /// buffers a validator writes before committing from them
+    pub fn write_buffer(ctx: Context<WriteBuffer>, offset: u32, bytes: Vec<u8>) -> Result<()> {
+        let mut data = ctx.accounts.buffer.try_borrow_mut_data()?;
+        write_bytes(&mut data, offset, &bytes)
+    }
+
+    /// Create the staged delegate buffer of the large account, the payer becoming its
+    /// authority and writing the data in chunks
+    pub fn stage_delegation(ctx: Context<StageDelegation>) -> Result<()> {
+        let mut data = vec![DLP_INIT_DELEGATE_BUFFER, 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(&(LARGE_ACCOUNT_SIZE as u32).to_le_bytes());
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.delegation_program.key(),
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.payer.key(), true),
+                    AccountMeta::new_readonly(ctx.accounts.large_account.key(), true),
+                    AccountMeta::new(ctx.accounts.staged_buffer.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                ],
+                data,
+            },
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.large_account.to_account_info(),
+                ctx.accounts.staged_buffer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.delegation_program.to_account_info(),
+            ],
+            &[&[LARGE_ACCOUNT_SEED, &[ctx.bumps.large_account]]],
+        )?;
+        Ok(())
+    }
+
+    /// Delegate the large account, its data being copied from the complete staged buffer
+    pub fn delegate_staged(ctx: Context<DelegateStaged>, validator: Option<Pubkey>) -> Result<()> {
+        let large_account = ctx.accounts.large_account.to_account_info();
+        large_account.try_borrow_mut_data()?.fill(0);
+        large_account.assign(&ctx.accounts.delegation_program.key());
+
+        let args = DelegateAccountArgs {
+            commit_frequency_ms: u32::MAX,
+            seeds: vec![LARGE_ACCOUNT_SEED.to_vec()],
+            validator,
+        };
+        let mut data = vec![DLP_DELEGATE, 0, 0, 0, 0, 0, 0, 0];
+        args.serialize(&mut data)?;
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.delegation_program.key(),
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.payer.key(), true),
+                    AccountMeta::new(large_account.key(), true),
+                    AccountMeta::new_readonly(crate::ID, false),
+                    AccountMeta::new(ctx.accounts.delegate_buffer.key(), false),
+                    AccountMeta::new(ctx.accounts.delegation_record.key(), false),
+                    AccountMeta::new(ctx.accounts.delegation_metadata.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                    AccountMeta::new(ctx.accounts.staged_buffer.key(), false),
+                ],
+                data,
+            },
+            &[
+                ctx.accounts.payer.to_account_info(),
+                large_account,
+                ctx.accounts.owner_program.to_account_info(),
+                ctx.accounts.delegate_buffer.to_account_info(),
+                ctx.accounts.delegation_record.to_account_info(),
+                ctx.accounts.delegation_metadata.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.staged_buffer.to_account_info(),
+                ctx.accounts.delegation_program.to_account_info(),
+            ],
+            &[&[LARGE_ACCOUNT_SEED, &[ctx.bumps.large_account]]],
+        )?;
+        Ok(())
+    }
+}
+
+fn write_bytes(data: &mut [u8], offset: u32, bytes: &[u8]) -> Result<()> {
+    let start = offset as usize;
+    let end = start
+        .checked_add(bytes.len())
+        .filter(|end| *end <= data.len())
+        .ok_or(LargeAccountError::OutOfBounds)?;
+    data[start..end].copy_from_slice(bytes);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(init, payer = payer, space = LARGE_ACCOUNT_SIZE, seeds = [LARGE_ACCOUNT_SEED], bump)]
+    pub large_account: AccountLoader<'info, LargeAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WriteLargeAccount<'info> {
+    #[account(mut, seeds = [LARGE_ACCOUNT_SEED], bump)]
+    pub large_account: AccountLoader<'info, LargeAccount>,
+}
+
+#[derive(Accounts)]
+pub struct WriteBuffer<'info> {
+    pub authority: Signer<'info>,
+    /// CHECK: A commit buffer created by the client with this program as owner
+    #[account(mut, owner = crate::ID)]
+    pub buffer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StageDelegation<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The large account to delegate, signing for the staged buffer
+    #[account(seeds = [LARGE_ACCOUNT_SEED], bump)]
+    pub large_account: AccountInfo<'info>,
+    /// CHECK: Checked by the delegation program
+    #[account(mut)]
+    pub staged_buffer: UncheckedAccount<'info>,
+    /// CHECK: The delegation program
+    #[account(address = delegation_program_utils::ID)]
+    pub delegation_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateStaged<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The large account to delegate
+    #[account(mut, seeds = [LARGE_ACCOUNT_SEED], bump, owner = crate::ID)]
+    pub large_account: AccountInfo<'info>,
+    /// CHECK: This program
+    #[account(address = crate::ID)]
+    pub owner_program: UncheckedAccount<'info>,
+    /// CHECK: Checked by the delegation program, unused when delegating from the staged buffer
+    #[account(mut)]
+    pub delegate_buffer: UncheckedAccount<'info>,
+    /// CHECK: Checked by the delegation program
+    #[account(mut)]
+    pub delegation_record: UncheckedAccount<'info>,
+    /// CHECK: Checked by the delegation program
+    #[account(mut)]
+    pub delegation_metadata: UncheckedAccount<'info>,
+    /// CHECK: Checked by the delegation program
+    #[account(mut)]
+    pub staged_buffer: UncheckedAccount<'info>,
+    /// CHECK: The delegation program
+    #[account(address = delegation_program_utils::ID)]
+    pub delegation_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account(zero_copy)]
+pub struct LargeAccount {
+    pub data: [u8; LARGE_ACCOUNT_SIZE - 8],
+}
+
+#[error_code]
+pub enum LargeAccountError {
+    #[msg("The write is out of the bounds of the account")]
+    OutOfBounds,
+}
+
+mod delegation_program_utils {
+    use anchor_lang::prelude::*;
+
+    declare_id!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+}
@@ -0,0 +1,680 @@
+//! A native (no Anchor) program delegating a counter PDA to the delegation program.
+//!
+//! The CPIs into the delegation program are built from the `sdk` feature of
+//! `magicblock-delegation-program`, which exposes the args, PDAs and consts without the
+//! program itself. The [instruction] module builds the instructions of this program for
+//! clients.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use dlp::args::{
+    ApproveUndelegateAndCloseArgs, CommitFromOwnerArgs, DelegateArgs, Seeds, SplitDelegationArgs,
+};
+use dlp::consts::{DELEGATION_PROGRAM_ID, EXTERNAL_UNDELEGATE_DISCRIMINATOR};
+use solana_program::{
+    account_info::AccountInfo,
+    declare_id,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+
+declare_id!("6YbkuaEsNwWVWaaokQEPGL89KznJ7boGAeUkgfqV5oa");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub const COUNTER_SEED: &[u8] = b"native-counter";
+pub const LABEL_SEED: &[u8] = b"native-label";
+
+/// Delegation program discriminators used by this program, the discriminator enum is not
+/// part of the `sdk` feature
+const DLP_DELEGATE: u8 = 0;
+const DLP_SPLIT_DELEGATION: u8 = 18;
+const DLP_APPROVE_UNDELEGATE_AND_CLOSE: u8 = 29;
+const DLP_COMMIT_FROM_OWNER: u8 = 76;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct Counter {
+    pub authority: Pubkey,
+    pub count: u64,
+    pub label: [u8; 32],
+}
+
+impl Counter {
+    pub const SIZE: usize = 32 + 8 + 32;
+    /// The offset of the label, which is moved to its own account by [NativeInstruction::SplitLabel]
+    pub const LABEL_OFFSET: u32 = 32 + 8;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum NativeInstruction {
+    /// Create the counter of the authority
+    ///
+    /// 0: `[signer, writable]` the authority
+    /// 1: `[writable]`         the counter PDA
+    /// 2: `[]`                 the system program
+    Initialize { label: [u8; 32] },
+    /// Increment the counter, only while it is not delegated
+    ///
+    /// 0: `[writable]` the counter PDA
+    Increment,
+    /// Delegate the counter to the delegation program
+    ///
+    /// 0: `[signer, writable]` the authority, paying for the delegation PDAs
+    /// 1: `[writable]`         the counter PDA
+    /// 2: `[]`                 this program
+    /// 3: `[writable]`         the delegate buffer PDA
+    /// 4: `[writable]`         the delegation record PDA
+    /// 5: `[writable]`         the delegation metadata PDA
+    /// 6: `[]`                 the delegation program
+    /// 7: `[]`                 the system program
+    Delegate { validator: Option<Pubkey> },
+    /// Approve closing the counter to the destination when it is undelegated
+    ///
+    /// 0: `[signer, writable]` the authority, paying for the delegation metadata growth
+    /// 1: `[]`                 the counter PDA
+    /// 2: `[writable]`         the delegation metadata PDA
+    /// 3: `[]`                 the delegation program
+    /// 4: `[]`                 the system program
+    ApproveUndelegateAndClose { close_destination: Pubkey },
+    /// Move the label of a delegated counter to its own delegated account
+    ///
+    /// 0: `[signer, writable]` the validator
+    /// 1: `[writable]`         the counter PDA
+    /// 2: `[]`                 the delegation record of the counter
+    /// 3: `[]`                 the delegation metadata of the counter
+    /// 4: `[]`                 the commit state PDA of the counter
+    /// 5: `[]`                 the commit record PDA of the counter
+    /// 6: `[writable]`         the label PDA
+    /// 7: `[writable]`         the delegation record of the label
+    /// 8: `[writable]`         the delegation metadata of the label
+    /// 9: `[]`                 this program
+    /// 10: `[]`                the system program
+    /// 11: `[]`                the feature gates PDA
+    /// 12: `[]`                the delegation program
+    SplitLabel { authority: Pubkey },
+    /// Action handler called by the delegation program `CallHandler`, transferring from the
+    /// escrow which signs
+    ///
+    /// 0: `[writable]`         the destination
+    /// 1: `[]`                 the system program
+    /// 2: `[]`                 the escrow authority
+    /// 3: `[signer, writable]` the escrow
+    EscrowTransfer { amount: u64 },
+    /// Push a corrected count to the delegated counter, finalized by the validator
+    ///
+    /// 0: `[signer, writable]` the authority, paying for the commit PDAs
+    /// 1: `[]`                 the counter PDA
+    /// 2: `[writable]`         the commit state PDA of the counter
+    /// 3: `[writable]`         the commit record PDA of the counter
+    /// 4: `[]`                 the delegation record of the counter
+    /// 5: `[]`                 the delegation metadata of the counter
+    /// 6: `[]`                 the system program
+    /// 7: `[]`                 the delegation program
+    CorrectCount { count: u64, nonce: u64 },
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // The delegation program gives the account back with its own discriminator
+    if let Some(seeds) = data.strip_prefix(&EXTERNAL_UNDELEGATE_DISCRIMINATOR) {
+        let seeds = Vec::<Vec<u8>>::try_from_slice(seeds)?;
+        return process_undelegate(program_id, accounts, seeds);
+    }
+
+    match NativeInstruction::try_from_slice(data)? {
+        NativeInstruction::Initialize { label } => process_initialize(program_id, accounts, label),
+        NativeInstruction::Increment => process_increment(program_id, accounts),
+        NativeInstruction::Delegate { validator } => {
+            process_delegate(program_id, accounts, validator)
+        }
+        NativeInstruction::ApproveUndelegateAndClose { close_destination } => {
+            process_approve_undelegate_and_close(accounts, close_destination)
+        }
+        NativeInstruction::SplitLabel { authority } => process_split_label(accounts, authority),
+        NativeInstruction::EscrowTransfer { amount } => process_escrow_transfer(accounts, amount),
+        NativeInstruction::CorrectCount { count, nonce } => {
+            process_correct_count(accounts, count, nonce)
+        }
+    }
+}
+
+fn process_initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    label: [u8; 32],
+) -> ProgramResult {
+    let [authority, counter, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let bump = require_pda(counter, &[COUNTER_SEED, authority.key.as_ref()], program_id)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            counter.key,
+            Rent::get()?.minimum_balance(Counter::SIZE),
+            Counter::SIZE as u64,
+            program_id,
+        ),
+        &[authority.clone(), counter.clone(), system_program.clone()],
+        &[&[COUNTER_SEED, authority.key.as_ref(), &[bump]]],
+    )?;
+
+    let state = Counter {
+        authority: *authority.key,
+        count: 0,
+        label,
+    };
+    state.serialize(&mut &mut counter.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+fn process_increment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [counter] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if counter.owner != program_id {
+        msg!("Counter is delegated");
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut state = Counter::try_from_slice(&counter.try_borrow_data()?)?;
+    state.count += 1;
+    state.serialize(&mut &mut counter.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+fn process_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    validator: Option<Pubkey>,
+) -> ProgramResult {
+    let [authority, counter, owner_program, delegate_buffer, delegation_record, delegation_metadata, delegation_program, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let counter_seeds: &[&[u8]] = &[COUNTER_SEED, authority.key.as_ref()];
+    let counter_bump = require_pda(counter, counter_seeds, program_id)?;
+    let buffer_seeds: &[&[u8]] = &[dlp::pda::DELEGATE_BUFFER_TAG, counter.key.as_ref()];
+    let buffer_bump = require_pda(delegate_buffer, buffer_seeds, program_id)?;
+
+    // Copy the counter to the delegate buffer
+    let data_len = counter.data_len();
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            delegate_buffer.key,
+            Rent::get()?.minimum_balance(data_len),
+            data_len as u64,
+            program_id,
+        ),
+        &[
+            authority.clone(),
+            delegate_buffer.clone(),
+            system_program.clone(),
+        ],
+        &[&[buffer_seeds[0], buffer_seeds[1], &[buffer_bump]]],
+    )?;
+    delegate_buffer
+        .try_borrow_mut_data()?
+        .copy_from_slice(&counter.try_borrow_data()?);
+
+    // Clear the counter and give it to the delegation program
+    counter.try_borrow_mut_data()?.fill(0);
+    counter.assign(&DELEGATION_PROGRAM_ID);
+
+    let args = DelegateArgs {
+        commit_frequency_ms: u32::MAX,
+        seeds: Seeds::try_from(counter_seeds).map_err(|_| ProgramError::InvalidSeeds)?,
+        validator,
+        extended_undelegate_payload: false,
+        seed_template: None,
+        undelegate_discriminator: None,
+        heartbeat_timeout_slots: None,
+        max_account_size: None,
+        rent_co_payer: None,
+    };
+    invoke_signed(
+        &Instruction {
+            program_id: DELEGATION_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*authority.key, true),
+                AccountMeta::new(*counter.key, true),
+                AccountMeta::new_readonly(*owner_program.key, false),
+                AccountMeta::new(*delegate_buffer.key, false),
+                AccountMeta::new(*delegation_record.key, false),
+                AccountMeta::new(*delegation_metadata.key, false),
+                AccountMeta::new_readonly(*system_program.key, false),
+            ],
+            data: dlp_instruction_data(DLP_DELEGATE, &args)?,
+        },
+        &[
+            authority.clone(),
+            counter.clone(),
+            owner_program.clone(),
+            delegate_buffer.clone(),
+            delegation_record.clone(),
+            delegation_metadata.clone(),
+            system_program.clone(),
+            delegation_program.clone(),
+        ],
+        &[&[counter_seeds[0], counter_seeds[1], &[counter_bump]]],
+    )?;
+
+    // Close the delegate buffer
+    let buffer_lamports = delegate_buffer.lamports();
+    **authority.try_borrow_mut_lamports()? += buffer_lamports;
+    **delegate_buffer.try_borrow_mut_lamports()? = 0;
+    delegate_buffer.realloc(0, false)?;
+    delegate_buffer.assign(&system_program::ID);
+    Ok(())
+}
+
+fn process_approve_undelegate_and_close(
+    accounts: &[AccountInfo],
+    close_destination: Pubkey,
+) -> ProgramResult {
+    let [authority, counter, delegation_metadata, delegation_program, system_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let counter_seeds: &[&[u8]] = &[COUNTER_SEED, authority.key.as_ref()];
+    let counter_bump = require_pda(counter, counter_seeds, &crate::ID)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let args = ApproveUndelegateAndCloseArgs { close_destination };
+    invoke_signed(
+        &Instruction {
+            program_id: DELEGATION_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*authority.key, true),
+                AccountMeta::new_readonly(*counter.key, true),
+                AccountMeta::new(*delegation_metadata.key, false),
+                AccountMeta::new_readonly(*system_program.key, false),
+            ],
+            data: dlp_instruction_data(DLP_APPROVE_UNDELEGATE_AND_CLOSE, &args)?,
+        },
+        &[
+            authority.clone(),
+            counter.clone(),
+            delegation_metadata.clone(),
+            system_program.clone(),
+            delegation_program.clone(),
+        ],
+        &[&[counter_seeds[0], counter_seeds[1], &[counter_bump]]],
+    )
+}
+
+fn process_split_label(accounts: &[AccountInfo], authority: Pubkey) -> ProgramResult {
+    let [validator, counter, delegation_record, delegation_metadata, commit_state, commit_record, label, label_delegation_record, label_delegation_metadata, owner_program, system_program, feature_gates, delegation_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    require_pda(counter, &[COUNTER_SEED, authority.as_ref()], &crate::ID)?;
+    let label_seeds: &[&[u8]] = &[LABEL_SEED, authority.as_ref()];
+    let label_bump = require_pda(label, label_seeds, &crate::ID)?;
+
+    let args = SplitDelegationArgs {
+        ranges: vec![(Counter::LABEL_OFFSET, Counter::SIZE as u32)],
+        seeds: Seeds::try_from(label_seeds).map_err(|_| ProgramError::InvalidSeeds)?,
+    };
+    invoke_signed(
+        &Instruction {
+            program_id: DELEGATION_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*validator.key, true),
+                AccountMeta::new(*counter.key, false),
+                AccountMeta::new_readonly(*delegation_record.key, false),
+                AccountMeta::new_readonly(*delegation_metadata.key, false),
+                AccountMeta::new_readonly(*commit_state.key, false),
+                AccountMeta::new_readonly(*commit_record.key, false),
+                AccountMeta::new(*label.key, true),
+                AccountMeta::new(*label_delegation_record.key, false),
+                AccountMeta::new(*label_delegation_metadata.key, false),
+                AccountMeta::new_readonly(*owner_program.key, false),
+                AccountMeta::new_readonly(*system_program.key, false),
+                AccountMeta::new_readonly(*feature_gates.key, false),
+            ],
+            data: dlp_instruction_data(DLP_SPLIT_DELEGATION, &args)?,
+        },
+        accounts,
+        &[&[label_seeds[0], label_seeds[1], &[label_bump]]],
+    )
+}
+
+fn process_escrow_transfer(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let [destination, system_program, _escrow_authority, escrow] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !escrow.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    invoke(
+        &system_instruction::transfer(escrow.key, destination.key, amount),
+        &[escrow.clone(), destination.clone(), system_program.clone()],
+    )
+}
+
+fn process_correct_count(accounts: &[AccountInfo], count: u64, nonce: u64) -> ProgramResult {
+    let [authority, counter, commit_state, commit_record, delegation_record, delegation_metadata, system_program, delegation_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let counter_seeds: &[&[u8]] = &[COUNTER_SEED, authority.key.as_ref()];
+    let counter_bump = require_pda(counter, counter_seeds, &crate::ID)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // The delegated counter keeps its last finalized state, corrected here
+    let mut state = Counter::try_from_slice(&counter.try_borrow_data()?)?;
+    state.count = count;
+    let args = CommitFromOwnerArgs {
+        nonce,
+        data: borsh::to_vec(&state)?,
+    };
+    invoke_signed(
+        &Instruction {
+            program_id: DELEGATION_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*authority.key, true),
+                AccountMeta::new_readonly(*counter.key, true),
+                AccountMeta::new(*commit_state.key, false),
+                AccountMeta::new(*commit_record.key, false),
+                AccountMeta::new_readonly(*delegation_record.key, false),
+                AccountMeta::new_readonly(*delegation_metadata.key, false),
+                AccountMeta::new_readonly(*system_program.key, false),
+            ],
+            data: dlp_instruction_data(DLP_COMMIT_FROM_OWNER, &args)?,
+        },
+        &[
+            authority.clone(),
+            counter.clone(),
+            commit_state.clone(),
+            commit_record.clone(),
+            delegation_record.clone(),
+            delegation_metadata.clone(),
+            system_program.clone(),
+            delegation_program.clone(),
+        ],
+        &[&[counter_seeds[0], counter_seeds[1], &[counter_bump]]],
+    )
+}
+
+/// Re-open the undelegated account with the state committed by the validator
+fn process_undelegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    seeds: Vec<Vec<u8>>,
+) -> ProgramResult {
+    let [delegated_account, undelegate_buffer, payer, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    // Only the delegation program can sign for the undelegate buffer
+    if !undelegate_buffer.is_signer || undelegate_buffer.owner != &DELEGATION_PROGRAM_ID {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    let bump = require_pda(delegated_account, &seeds, program_id)?;
+    let bump = [bump];
+    let signer_seeds = [seeds.as_slice(), &[&bump]].concat();
+
+    let data_len = undelegate_buffer.data_len();
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            delegated_account.key,
+            Rent::get()?.minimum_balance(data_len),
+            data_len as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            delegated_account.clone(),
+            system_program.clone(),
+        ],
+        &[&signer_seeds],
+    )?;
+    delegated_account
+        .try_borrow_mut_data()?
+        .copy_from_slice(&undelegate_buffer.try_borrow_data()?);
+    Ok(())
+}
+
+fn require_pda(
+    account: &AccountInfo,
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+    if account.key != &pda {
+        msg!("Invalid PDA {}, expected {}", account.key, pda);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(bump)
+}
+
+fn dlp_instruction_data(
+    discriminator: u8,
+    args: &impl BorshSerialize,
+) -> Result<Vec<u8>, ProgramError> {
+    let mut data = vec![discriminator, 0, 0, 0, 0, 0, 0, 0];
+    args.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// Builders of the instructions of this program, for clients
+#[cfg(not(target_os = "solana"))]
+pub mod instruction {
+    use dlp::pda::{
+        commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+        delegate_buffer_pda_from_delegated_account_and_owner_program,
+        delegation_metadata_pda_from_delegated_account,
+        delegation_record_pda_from_delegated_account,
+    };
+
+    use super::*;
+
+    /// The counter PDA of an authority
+    ///
+    /// ```
+    /// use solana_program::pubkey::Pubkey;
+    ///
+    /// let authority = Pubkey::new_unique();
+    /// let counter = test_native::instruction::counter_pda(&authority);
+    /// assert!(!counter.is_on_curve());
+    /// assert_ne!(counter, test_native::instruction::label_pda(&authority));
+    /// ```
+    pub fn counter_pda(authority: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[COUNTER_SEED, authority.as_ref()], &crate::ID).0
+    }
+
+    /// The PDA the label of the counter is split into
+    pub fn label_pda(authority: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[LABEL_SEED, authority.as_ref()], &crate::ID).0
+    }
+
+    /// ```
+    /// use borsh::BorshDeserialize;
+    /// use solana_program::pubkey::Pubkey;
+    /// use test_native::NativeInstruction;
+    ///
+    /// let ix = test_native::instruction::initialize(Pubkey::new_unique(), [7; 32]);
+    /// assert!(matches!(
+    ///     NativeInstruction::try_from_slice(&ix.data).unwrap(),
+    ///     NativeInstruction::Initialize { label } if label == [7; 32]
+    /// ));
+    /// ```
+    pub fn initialize(authority: Pubkey, label: [u8; 32]) -> Instruction {
+        Instruction {
+            program_id: crate::ID,
+            accounts: vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(counter_pda(&authority), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: borsh::to_vec(&NativeInstruction::Initialize { label }).unwrap(),
+        }
+    }
+
+    pub fn increment(authority: Pubkey) -> Instruction {
+        Instruction {
+            program_id: crate::ID,
+            accounts: vec![AccountMeta::new(counter_pda(&authority), false)],
+            data: borsh::to_vec(&NativeInstruction::Increment).unwrap(),
+        }
+    }
+
+    /// ```
+    /// use solana_program::pubkey::Pubkey;
+    ///
+    /// let authority = Pubkey::new_unique();
+    /// let ix = test_native::instruction::delegate(authority, None);
+    /// assert_eq!(ix.accounts.len(), 8);
+    /// assert_eq!(
+    ///     ix.accounts[4].pubkey,
+    ///     dlp::pda::delegation_record_pda_from_delegated_account(
+    ///         &test_native::instruction::counter_pda(&authority)
+    ///     )
+    /// );
+    /// ```
+    pub fn delegate(authority: Pubkey, validator: Option<Pubkey>) -> Instruction {
+        let counter = counter_pda(&authority);
+        Instruction {
+            program_id: crate::ID,
+            accounts: vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(counter, false),
+                AccountMeta::new_readonly(crate::ID, false),
+                AccountMeta::new(
+                    delegate_buffer_pda_from_delegated_account_and_owner_program(
+                        &counter,
+                        &crate::ID,
+                    ),
+                    false,
+                ),
+                AccountMeta::new(
+                    delegation_record_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new(
+                    delegation_metadata_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new_readonly(DELEGATION_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: borsh::to_vec(&NativeInstruction::Delegate { validator }).unwrap(),
+        }
+    }
+
+    pub fn approve_undelegate_and_close(
+        authority: Pubkey,
+        close_destination: Pubkey,
+    ) -> Instruction {
+        let counter = counter_pda(&authority);
+        Instruction {
+            program_id: crate::ID,
+            accounts: vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(counter, false),
+                AccountMeta::new(
+                    delegation_metadata_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new_readonly(DELEGATION_PROGRAM_ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: borsh::to_vec(&NativeInstruction::ApproveUndelegateAndClose {
+                close_destination,
+            })
+            .unwrap(),
+        }
+    }
+
+    /// ```
+    /// use solana_program::pubkey::Pubkey;
+    ///
+    /// let ix = test_native::instruction::split_label(Pubkey::new_unique(), Pubkey::new_unique());
+    /// assert_eq!(ix.accounts.len(), 13);
+    /// assert_eq!(ix.accounts[11].pubkey, dlp::consts::FEATURE_GATES_PDA);
+    /// ```
+    pub fn split_label(validator: Pubkey, authority: Pubkey) -> Instruction {
+        let counter = counter_pda(&authority);
+        let label = label_pda(&authority);
+        Instruction {
+            program_id: crate::ID,
+            accounts: vec![
+                AccountMeta::new(validator, true),
+                AccountMeta::new(counter, false),
+                AccountMeta::new_readonly(
+                    delegation_record_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new_readonly(
+                    delegation_metadata_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new_readonly(commit_state_pda_from_delegated_account(&counter), false),
+                AccountMeta::new_readonly(
+                    commit_record_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new(label, false),
+                AccountMeta::new(delegation_record_pda_from_delegated_account(&label), false),
+                AccountMeta::new(
+                    delegation_metadata_pda_from_delegated_account(&label),
+                    false,
+                ),
+                AccountMeta::new_readonly(crate::ID, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(dlp::consts::FEATURE_GATES_PDA, false),
+                AccountMeta::new_readonly(DELEGATION_PROGRAM_ID, false),
+            ],
+            data: borsh::to_vec(&NativeInstruction::SplitLabel { authority }).unwrap(),
+        }
+    }
+
+    pub fn correct_count(authority: Pubkey, count: u64, nonce: u64) -> Instruction {
+        let counter = counter_pda(&authority);
+        Instruction {
+            program_id: crate::ID,
+            accounts: vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(counter, false),
+                AccountMeta::new(commit_state_pda_from_delegated_account(&counter), false),
+                AccountMeta::new(commit_record_pda_from_delegated_account(&counter), false),
+                AccountMeta::new_readonly(
+                    delegation_record_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new_readonly(
+                    delegation_metadata_pda_from_delegated_account(&counter),
+                    false,
+                ),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(DELEGATION_PROGRAM_ID, false),
+            ],
+            data: borsh::to_vec(&NativeInstruction::CorrectCount { count, nonce }).unwrap(),
+        }
+    }
+
+    /// The instruction data of the action handler, to be passed to `CallHandler`
+    pub fn escrow_transfer_data(amount: u64) -> Vec<u8> {
+        borsh::to_vec(&NativeInstruction::EscrowTransfer { amount }).unwrap()
+    }
+}
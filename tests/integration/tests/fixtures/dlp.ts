@@ -0,0 +1,2428 @@
+import * as anchor from "@coral-xyz/anchor";
+import { web3 } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { createHash } from "crypto";
+
+/// Instruction builders of the delegation program, mirroring src/instruction_builder
+
+export const DELEGATION_PROGRAM_ID = new web3.PublicKey(
+  "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+);
+export const FEATURE_GATES_PDA = new web3.PublicKey(
+  "Ha6KfEbUduu6NwHojViYnp5XEokt5PEuMRdwadvBv9SG"
+);
+export const BPF_LOADER = new web3.PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+export const EXTERNAL_UNDELEGATE_DISCRIMINATOR = [
+  196, 28, 41, 206, 48, 37, 51, 167,
+];
+
+export enum DlpDiscriminator {
+  Delegate = 0,
+  CommitState = 1,
+  Finalize = 2,
+  Undelegate = 3,
+  InitProtocolFeesVault = 5,
+  InitValidatorFeesVault = 6,
+  ValidatorClaimFees = 7,
+  WhitelistValidatorForProgram = 8,
+  TopUpEphemeralBalance = 9,
+  DelegateEphemeralBalance = 10,
+  CloseEphemeralBalance = 11,
+  ProtocolClaimFees = 12,
+  CommitStateFromBuffer = 13,
+  CloseValidatorFeesVault = 14,
+  CallHandler = 15,
+  CommitDiff = 16,
+  CommitDiffFromBuffer = 17,
+  SplitDelegation = 18,
+  TopUpProgramEphemeralBalance = 19,
+  DelegateProgramEphemeralBalance = 20,
+  CloseProgramEphemeralBalance = 21,
+  SetFeatureGate = 22,
+  SetProgramAllowedDataLens = 23,
+  CrankFinalize = 24,
+  CommitFinalize = 25,
+  InitDelegateBuffer = 26,
+  WriteDelegateBufferChunk = 27,
+  SetValidatorInfo = 28,
+  ApproveUndelegateAndClose = 29,
+  UndelegateAndClose = 30,
+  CommitSessionBegin = 31,
+  CommitSessionEnd = 32,
+  GrowCommitState = 33,
+  SetProtocolConfig = 34,
+  BootstrapProtocol = 35,
+  InitReadLock = 36,
+  WhitelistValidatorsForProgramBatch = 37,
+  RequestUndelegation = 38,
+  CommitDiffShadow = 39,
+  SetVersion = 40,
+  GetVersion = 41,
+  GrantFeeExemption = 42,
+  ValidateDelegation = 43,
+  SetProgramUndelegateLamportsTolerance = 44,
+  SetCommitSchedule = 45,
+  CloseCommitSchedule = 46,
+  SetProgramMaxDelegationSlots = 48,
+  InitEarningsLedgerPage = 49,
+  ScheduleForceUndelegate = 50,
+  ExecuteForceUndelegate = 51,
+  SetProgramValidateDelegations = 52,
+  ResyncProtocolStats = 53,
+  SetCallHandlerPermissions = 54,
+  TopUpEphemeralBalanceBatch = 55,
+  RegisterCommitRelayer = 56,
+  SetValidatorCommitQuota = 57,
+  RedelegateEphemeralBalance = 58,
+  IsValidatorWhitelisted = 59,
+  ExportDelegationPackage = 60,
+  ImportDelegationPackage = 61,
+  ProposeProtocolVaultMigration = 62,
+  ExecuteProtocolVaultMigration = 63,
+  GetDelegationSummaries = 64,
+  GetEscrowSummaries = 65,
+  GetPendingCommitSummaries = 66,
+  WhitelistValidatorShardForProgram = 67,
+  MigrateProgramConfigWhitelist = 68,
+  UndelegateStage1 = 69,
+  UndelegateStage2 = 70,
+  SetProgramUndelegateDiscriminatorOverride = 71,
+  ValidatorHeartbeat = 72,
+  MigrateDelegationRecord = 73,
+  FundEscrowFromDelegated = 74,
+  CloseProgramConfig = 75,
+  CommitFromOwner = 76,
+  SetAuthorityGrant = 77,
+  InitCommitBuffer = 78,
+  WriteCommitBuffer = 79,
+  CloseCommitBuffer = 80,
+  ClaimParkedUndelegation = 81,
+  InitSessionReport = 82,
+  CloseSessionReport = 83,
+  ForceUndelegate = 84,
+  DelegateStakeAccount = 85,
+  UndelegateStakeAccount = 86,
+  AddDelegationAuthority = 87,
+  RemoveDelegationAuthority = 88,
+  UpdateDelegationAuthority = 89,
+}
+
+export enum UndelegateMode {
+  Owner = 0,
+  ToBuffer = 1,
+}
+
+export enum AuthorityScope {
+  Commit = 0,
+  CommitAndUndelegate = 1,
+}
+
+export enum CallHandlerContext {
+  Commit = 0,
+  Undelegate = 1,
+  Standalone = 2,
+}
+
+export enum DlpError {
+  NonceOutOfOrder = 12,
+  CrankFinalizeTooEarly = 41,
+  ForceUndelegationTimelock = 65,
+  InvalidDelegationPackage = 70,
+  ProtocolVaultMigrationTimelock = 73,
+  InvalidOwnerCommit = 82,
+  CommitTimeoutNotElapsed = 87,
+  InvalidStakeState = 88,
+}
+
+/// PDAs
+
+function findPda(
+  seeds: (Buffer | Uint8Array)[],
+  programId = DELEGATION_PROGRAM_ID
+) {
+  return web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+export function delegationRecordPda(delegatedAccount: web3.PublicKey) {
+  return findPda([Buffer.from("delegation"), delegatedAccount.toBuffer()]);
+}
+
+export function delegationMetadataPda(delegatedAccount: web3.PublicKey) {
+  return findPda([
+    Buffer.from("delegation-metadata"),
+    delegatedAccount.toBuffer(),
+  ]);
+}
+
+export function commitStatePda(delegatedAccount: web3.PublicKey) {
+  return findPda([Buffer.from("state-diff"), delegatedAccount.toBuffer()]);
+}
+
+export function commitRecordPda(delegatedAccount: web3.PublicKey) {
+  return findPda([
+    Buffer.from("commit-state-record"),
+    delegatedAccount.toBuffer(),
+  ]);
+}
+
+export function delegateBufferPda(
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey
+) {
+  return findPda(
+    [Buffer.from("buffer"), delegatedAccount.toBuffer()],
+    ownerProgram
+  );
+}
+
+export function undelegateBufferPda(delegatedAccount: web3.PublicKey) {
+  return findPda([
+    Buffer.from("undelegate-buffer"),
+    delegatedAccount.toBuffer(),
+  ]);
+}
+
+export function undelegateProgressPda(delegatedAccount: web3.PublicKey) {
+  return findPda([
+    Buffer.from("undelegate-progress"),
+    delegatedAccount.toBuffer(),
+  ]);
+}
+
+export function stagedDelegateBufferPda(delegatedAccount: web3.PublicKey) {
+  return findPda([Buffer.from("staged-buffer"), delegatedAccount.toBuffer()]);
+}
+
+export function parkedUndelegationPda(delegatedAccount: web3.PublicKey) {
+  return findPda([
+    Buffer.from("parked-undelegation"),
+    delegatedAccount.toBuffer(),
+  ]);
+}
+
+export function commitBufferPda(
+  delegatedAccount: web3.PublicKey,
+  authority: web3.PublicKey
+) {
+  return findPda([
+    Buffer.from("commit-buffer"),
+    delegatedAccount.toBuffer(),
+    authority.toBuffer(),
+  ]);
+}
+
+export function commitSchedulePda(delegatedAccount: web3.PublicKey) {
+  return findPda([Buffer.from("commit-schedule"), delegatedAccount.toBuffer()]);
+}
+
+export function forceUndelegationPda(delegatedAccount: web3.PublicKey) {
+  return findPda([
+    Buffer.from("force-undelegation"),
+    delegatedAccount.toBuffer(),
+  ]);
+}
+
+export function feesVaultPda(generation = 0) {
+  if (generation === 0) {
+    return findPda([Buffer.from("fees-vault")]);
+  }
+  return findPda([
+    Buffer.from("fees-vault"),
+    new anchor.BN(generation).toArrayLike(Buffer, "le", 8),
+  ]);
+}
+
+export function feeExemptionPda(delegatedAccount: web3.PublicKey) {
+  return findPda([Buffer.from("fee-exemption"), delegatedAccount.toBuffer()]);
+}
+
+export function protocolConfigPda() {
+  return findPda([Buffer.from("protocol-config")]);
+}
+
+export function protocolStatsPda() {
+  return findPda([Buffer.from("protocol-stats")]);
+}
+
+export function programVersionPda() {
+  return findPda([Buffer.from("program-version")]);
+}
+
+export function readLockPda(delegatedAccount: web3.PublicKey) {
+  return findPda([Buffer.from("read-lock"), delegatedAccount.toBuffer()]);
+}
+
+export function validatorFeesVaultPda(validator: web3.PublicKey) {
+  return findPda([Buffer.from("v-fees-vault"), validator.toBuffer()]);
+}
+
+export function validatorInfoPda(validator: web3.PublicKey) {
+  return findPda([Buffer.from("validator-info"), validator.toBuffer()]);
+}
+
+export function validatorLivenessPda(validator: web3.PublicKey) {
+  return findPda([Buffer.from("validator-liveness"), validator.toBuffer()]);
+}
+
+export function authorityDelegationChainPda(delegator: web3.PublicKey) {
+  return findPda([
+    Buffer.from("authority-delegation-chain"),
+    delegator.toBuffer(),
+  ]);
+}
+
+export function earningsLedgerPagePda(validator: web3.PublicKey, page: number) {
+  const pageBytes = Buffer.alloc(4);
+  pageBytes.writeUInt32LE(page);
+  return findPda([
+    Buffer.from("earnings-ledger"),
+    validator.toBuffer(),
+    pageBytes,
+  ]);
+}
+
+export function programConfigPda(programId: web3.PublicKey) {
+  return findPda([Buffer.from("p-conf"), programId.toBuffer()]);
+}
+
+export function validatorWhitelistShardPda(
+  programId: web3.PublicKey,
+  validator: web3.PublicKey
+) {
+  return findPda([
+    Buffer.from("p-conf"),
+    programId.toBuffer(),
+    validator.toBuffer(),
+  ]);
+}
+
+export function callHandlerPermissionsPda(escrow: web3.PublicKey) {
+  return findPda([Buffer.from("call-handler-permissions"), escrow.toBuffer()]);
+}
+
+export function sessionReportPda(escrow: web3.PublicKey) {
+  return findPda([Buffer.from("session-report"), escrow.toBuffer()]);
+}
+
+export function stakeAuthorityPda(stakeAccount: web3.PublicKey) {
+  return findPda([Buffer.from("stake-authority"), stakeAccount.toBuffer()]);
+}
+
+export function delegationAuthoritiesPda(delegatedAccount: web3.PublicKey) {
+  return findPda([
+    Buffer.from("delegation-authorities"),
+    delegatedAccount.toBuffer(),
+  ]);
+}
+
+export function ephemeralBalancePda(payer: web3.PublicKey, index: number) {
+  return findPda([
+    Buffer.from("balance"),
+    payer.toBuffer(),
+    Buffer.from([index]),
+  ]);
+}
+
+export function wideEphemeralBalancePda(payer: web3.PublicKey, index: number) {
+  const indexBuffer = Buffer.alloc(4);
+  indexBuffer.writeUInt32LE(index);
+  return findPda([Buffer.from("balance"), payer.toBuffer(), indexBuffer]);
+}
+
+export function programEphemeralBalancePda(
+  payer: web3.PublicKey,
+  programId: web3.PublicKey,
+  index: number
+) {
+  return findPda([
+    Buffer.from("program-balance"),
+    payer.toBuffer(),
+    programId.toBuffer(),
+    Buffer.from([index]),
+  ]);
+}
+
+export function programDataPda(programId: web3.PublicKey) {
+  return findPda([programId.toBuffer()], BPF_LOADER);
+}
+
+/// Borsh serialization of the instruction args
+
+export class BorshWriter {
+  private parts: Buffer[] = [];
+
+  u8(value: number) {
+    this.parts.push(Buffer.from([value]));
+    return this;
+  }
+
+  bool(value: boolean) {
+    return this.u8(value ? 1 : 0);
+  }
+
+  u16(value: number) {
+    const buffer = Buffer.alloc(2);
+    buffer.writeUInt16LE(value);
+    this.parts.push(buffer);
+    return this;
+  }
+
+  u32(value: number) {
+    const buffer = Buffer.alloc(4);
+    buffer.writeUInt32LE(value);
+    this.parts.push(buffer);
+    return this;
+  }
+
+  u64(value: number | anchor.BN) {
+    this.parts.push(new anchor.BN(value).toArrayLike(Buffer, "le", 8));
+    return this;
+  }
+
+  pubkey(value: web3.PublicKey) {
+    this.parts.push(value.toBuffer());
+    return this;
+  }
+
+  array(value: Uint8Array) {
+    this.parts.push(Buffer.from(value));
+    return this;
+  }
+
+  bytes(value: Uint8Array) {
+    return this.u32(value.length).array(value);
+  }
+
+  string(value: string) {
+    return this.bytes(Buffer.from(value, "utf8"));
+  }
+
+  option<T>(value: T | null | undefined, write: (value: T) => void) {
+    if (value === null || value === undefined) {
+      return this.u8(0);
+    }
+    this.u8(1);
+    write(value);
+    return this;
+  }
+
+  vec<T>(values: T[], write: (value: T) => void) {
+    this.u32(values.length);
+    values.forEach(write);
+    return this;
+  }
+
+  toBuffer() {
+    return Buffer.concat(this.parts);
+  }
+}
+
+function instructionData(
+  discriminator: DlpDiscriminator,
+  args?: (writer: BorshWriter) => void
+) {
+  const writer = new BorshWriter().u64(discriminator);
+  if (args) {
+    args(writer);
+  }
+  return writer.toBuffer();
+}
+
+function dlpInstruction(
+  keys: web3.AccountMeta[],
+  discriminator: DlpDiscriminator,
+  args?: (writer: BorshWriter) => void
+) {
+  return new web3.TransactionInstruction({
+    programId: DELEGATION_PROGRAM_ID,
+    keys,
+    data: instructionData(discriminator, args),
+  });
+}
+
+function writable(
+  pubkey: web3.PublicKey,
+  isSigner = false
+): web3.AccountMeta {
+  return { pubkey, isSigner, isWritable: true };
+}
+
+function readonly(
+  pubkey: web3.PublicKey,
+  isSigner = false
+): web3.AccountMeta {
+  return { pubkey, isSigner, isWritable: false };
+}
+
+const SYSTEM_PROGRAM = web3.SystemProgram.programId;
+
+export interface DelegateArgs {
+  commitFrequencyMs: number;
+  seeds: Buffer[];
+  validator?: web3.PublicKey;
+  /// Only read by the delegate instruction, as a trailing flag
+  extendedUndelegatePayload?: boolean;
+}
+
+function writeDelegateArgs(writer: BorshWriter, args: DelegateArgs) {
+  writer
+    .u32(args.commitFrequencyMs)
+    .vec(args.seeds, (seed) => writer.bytes(seed))
+    .option(args.validator, (validator) => writer.pubkey(validator));
+}
+
+export interface CommitArgs {
+  nonce: number;
+  lamports: number | anchor.BN;
+  allowUndelegation: boolean;
+  /// The hash of the ER block which produced the committed state, which must change
+  /// between successive commits of the account
+  erBlockHash?: Uint8Array;
+}
+
+/// The ER block hash trails the commit args, only when set
+function writeErBlockHash(writer: BorshWriter, args: CommitArgs) {
+  if (args.erBlockHash) {
+    writer.option(args.erBlockHash, (hash) => writer.array(hash));
+  }
+}
+
+function commitKeys(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  args: CommitArgs,
+  buffer?: web3.PublicKey
+) {
+  // The delegation metadata is only written by commits allowing the undelegation
+  const delegationMetadata = delegationMetadataPda(delegatedAccount);
+  return [
+    readonly(validator, true),
+    readonly(delegatedAccount),
+    writable(commitStatePda(delegatedAccount)),
+    writable(commitRecordPda(delegatedAccount)),
+    readonly(delegationRecordPda(delegatedAccount)),
+    args.allowUndelegation
+      ? writable(delegationMetadata)
+      : readonly(delegationMetadata),
+    ...(buffer ? [readonly(buffer)] : []),
+    readonly(validatorFeesVaultPda(validator)),
+    readonly(programConfigPda(ownerProgram)),
+    readonly(SYSTEM_PROGRAM),
+  ];
+}
+
+export function delegate(
+  payer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  args: DelegateArgs
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      writable(delegatedAccount, true),
+      readonly(ownerProgram),
+      writable(delegateBufferPda(delegatedAccount, ownerProgram)),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.Delegate,
+    (writer) => {
+      writeDelegateArgs(writer, args);
+      if (args.extendedUndelegatePayload) {
+        writer.bool(true);
+      }
+    }
+  );
+}
+
+export function commitState(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  args: CommitArgs & { data: Uint8Array }
+) {
+  return dlpInstruction(
+    commitKeys(validator, delegatedAccount, ownerProgram, args),
+    DlpDiscriminator.CommitState,
+    (writer) => {
+      writer
+        .u64(args.nonce)
+        .u64(args.lamports)
+        .bool(args.allowUndelegation)
+        .bytes(args.data);
+      writeErBlockHash(writer, args);
+    }
+  );
+}
+
+export function commitStateFromBuffer(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  buffer: web3.PublicKey,
+  args: CommitArgs
+) {
+  return dlpInstruction(
+    commitKeys(validator, delegatedAccount, ownerProgram, args, buffer),
+    DlpDiscriminator.CommitStateFromBuffer,
+    (writer) => {
+      writer.u64(args.nonce).u64(args.lamports).bool(args.allowUndelegation);
+      writeErBlockHash(writer, args);
+    }
+  );
+}
+
+export function commitDiff(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  args: CommitArgs & { diff: Uint8Array }
+) {
+  return dlpInstruction(
+    [
+      ...commitKeys(validator, delegatedAccount, ownerProgram, args),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.CommitDiff,
+    (writer) => {
+      writer
+        .bytes(args.diff)
+        .u64(args.nonce)
+        .u64(args.lamports)
+        .bool(args.allowUndelegation);
+      writeErBlockHash(writer, args);
+    }
+  );
+}
+
+/// Commits the diff verifying that it produces the expected state
+export function commitDiffShadow(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  args: CommitArgs & { diff: Uint8Array; expectedState: Uint8Array }
+) {
+  return dlpInstruction(
+    [
+      ...commitKeys(validator, delegatedAccount, ownerProgram, args),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.CommitDiffShadow,
+    (writer) =>
+      writer
+        .bytes(args.diff)
+        .u64(args.nonce)
+        .u64(args.lamports)
+        .bool(args.allowUndelegation)
+        .array(createHash("sha256").update(args.expectedState).digest())
+  );
+}
+
+export function commitDiffFromBuffer(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  buffer: web3.PublicKey,
+  args: CommitArgs
+) {
+  return dlpInstruction(
+    [
+      ...commitKeys(validator, delegatedAccount, ownerProgram, args, buffer),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.CommitDiffFromBuffer,
+    (writer) => {
+      writer.u64(args.nonce).u64(args.lamports).bool(args.allowUndelegation);
+      writeErBlockHash(writer, args);
+    }
+  );
+}
+
+export function commitFinalize(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  args: CommitArgs & { data: Uint8Array }
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(delegatedAccount),
+      readonly(commitRecordPda(delegatedAccount)),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(programConfigPda(ownerProgram)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.CommitFinalize,
+    (writer) => {
+      writer
+        .u64(args.nonce)
+        .u64(args.lamports)
+        .bool(args.allowUndelegation)
+        .bytes(args.data);
+      writeErBlockHash(writer, args);
+    }
+  );
+}
+
+export function finalize(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      readonly(validator, true),
+      writable(delegatedAccount),
+      writable(commitStatePda(delegatedAccount)),
+      writable(commitRecordPda(delegatedAccount)),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.Finalize
+  );
+}
+
+/// Finalizes a commit which did not decrease the lamports of the delegated account,
+/// passing the validator fees vault read-only
+export function finalizeSettled(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  const ix = finalize(validator, delegatedAccount);
+  ix.keys[6] = readonly(validatorFeesVaultPda(validator));
+  return ix;
+}
+
+export function finalizeWithReadLock(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  const ix = finalize(validator, delegatedAccount);
+  ix.keys.push(writable(readLockPda(delegatedAccount)));
+  return ix;
+}
+
+/// Finalizes a commit pushed by the owner program, refunding its payer the rent of the
+/// commit PDAs
+export function finalizeOwnerCommit(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  payer: web3.PublicKey
+) {
+  const ix = finalize(validator, delegatedAccount);
+  ix.keys.push(writable(payer));
+  return ix;
+}
+
+/// Pushes a state to a delegated PDA, the owner program signing for it in a CPI
+export function commitFromOwner(
+  payer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  nonce: number | anchor.BN,
+  data: Uint8Array
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(delegatedAccount, true),
+      writable(commitStatePda(delegatedAccount)),
+      writable(commitRecordPda(delegatedAccount)),
+      readonly(delegationRecordPda(delegatedAccount)),
+      readonly(delegationMetadataPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.CommitFromOwner,
+    (writer) => writer.u64(nonce).bytes(data)
+  );
+}
+
+export function initReadLock(
+  payer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      writable(readLockPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.InitReadLock
+  );
+}
+
+export function commitSessionBegin(validator: web3.PublicKey) {
+  return dlpInstruction(
+    [readonly(validator, true), readonly(web3.SYSVAR_INSTRUCTIONS_PUBKEY)],
+    DlpDiscriminator.CommitSessionBegin
+  );
+}
+
+export function commitSessionEnd(validator: web3.PublicKey) {
+  return dlpInstruction(
+    [readonly(validator, true), readonly(web3.SYSVAR_INSTRUCTIONS_PUBKEY)],
+    DlpDiscriminator.CommitSessionEnd
+  );
+}
+
+export function crankFinalize(
+  cranker: web3.PublicKey,
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(cranker, true),
+      writable(validator),
+      writable(delegatedAccount),
+      writable(commitStatePda(delegatedAccount)),
+      writable(commitRecordPda(delegatedAccount)),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.CrankFinalize
+  );
+}
+
+export function undelegate(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  rentReimbursement: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(delegatedAccount),
+      readonly(ownerProgram),
+      writable(undelegateBufferPda(delegatedAccount)),
+      readonly(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(rentReimbursement),
+      writable(feesVaultPda()),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(protocolConfigPda()),
+      readonly(feeExemptionPda(delegatedAccount)),
+      readonly(programConfigPda(ownerProgram)),
+    ],
+    DlpDiscriminator.Undelegate
+  );
+}
+
+/// Undelegate parking the state and the lamports of the account for its rent payer, without
+/// the CPI to the owner program
+export function undelegateToBuffer(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  rentPayer: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(delegatedAccount),
+      readonly(ownerProgram),
+      writable(parkedUndelegationPda(delegatedAccount)),
+      readonly(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(rentPayer, true),
+      writable(feesVaultPda()),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(protocolConfigPda()),
+      readonly(feeExemptionPda(delegatedAccount)),
+      readonly(programConfigPda(ownerProgram)),
+    ],
+    DlpDiscriminator.Undelegate,
+    (writer) => writer.u8(UndelegateMode.ToBuffer)
+  );
+}
+
+export function claimParkedUndelegation(
+  rentPayer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(rentPayer, true),
+      readonly(delegatedAccount),
+      writable(parkedUndelegationPda(delegatedAccount)),
+    ],
+    DlpDiscriminator.ClaimParkedUndelegation
+  );
+}
+
+/// The first stage of a staged undelegation, giving the account back to its owner program
+export function undelegateStage1(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(delegatedAccount),
+      readonly(ownerProgram),
+      writable(undelegateBufferPda(delegatedAccount)),
+      readonly(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      readonly(delegationRecordPda(delegatedAccount)),
+      readonly(delegationMetadataPda(delegatedAccount)),
+      writable(undelegateProgressPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(programConfigPda(ownerProgram)),
+    ],
+    DlpDiscriminator.UndelegateStage1
+  );
+}
+
+/// The second stage of a staged undelegation, closing the delegation PDAs
+export function undelegateStage2(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  rentReimbursement: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      readonly(delegatedAccount),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(undelegateProgressPda(delegatedAccount)),
+      writable(rentReimbursement),
+      writable(feesVaultPda()),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(feeExemptionPda(delegatedAccount)),
+    ],
+    DlpDiscriminator.UndelegateStage2
+  );
+}
+
+export function undelegateAndClose(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  closeDestination: web3.PublicKey,
+  rentReimbursement: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(delegatedAccount),
+      readonly(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(closeDestination),
+      writable(rentReimbursement),
+      writable(feesVaultPda()),
+      writable(validatorFeesVaultPda(validator)),
+    ],
+    DlpDiscriminator.UndelegateAndClose
+  );
+}
+
+export function initValidatorFeesVault(
+  payer: web3.PublicKey,
+  admin: web3.PublicKey,
+  validator: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      writable(admin, true),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(validator),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.InitValidatorFeesVault
+  );
+}
+
+export function closeValidatorFeesVault(
+  payer: web3.PublicKey,
+  admin: web3.PublicKey,
+  validator: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      writable(admin, true),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(validator),
+      writable(validatorFeesVaultPda(validator)),
+    ],
+    DlpDiscriminator.CloseValidatorFeesVault
+  );
+}
+
+export function setValidatorInfo(
+  validator: web3.PublicKey,
+  args: { endpoint: string; version: string; clusterId: string }
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      readonly(validatorFeesVaultPda(validator)),
+      writable(validatorInfoPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetValidatorInfo,
+    (writer) =>
+      writer.string(args.endpoint).string(args.version).string(args.clusterId)
+  );
+}
+
+export function validatorHeartbeat(validator: web3.PublicKey) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      readonly(validatorFeesVaultPda(validator)),
+      writable(validatorLivenessPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.ValidatorHeartbeat
+  );
+}
+
+export function migrateDelegationRecord(
+  payer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(delegatedAccount),
+      writable(delegationRecordPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.MigrateDelegationRecord
+  );
+}
+
+export function registerCommitRelayer(
+  validator: web3.PublicKey,
+  relayer: web3.PublicKey,
+  approved: boolean
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.RegisterCommitRelayer,
+    (writer) => writer.pubkey(relayer).bool(approved)
+  );
+}
+
+export function setAuthorityGrant(
+  delegator: web3.PublicKey,
+  args: {
+    key: web3.PublicKey;
+    expirySlot: number | anchor.BN;
+    scope: AuthorityScope;
+    approved: boolean;
+  }
+) {
+  return dlpInstruction(
+    [
+      writable(delegator, true),
+      writable(authorityDelegationChainPda(delegator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetAuthorityGrant,
+    (writer) =>
+      writer
+        .pubkey(args.key)
+        .u64(args.expirySlot)
+        .u8(args.scope)
+        .bool(args.approved)
+  );
+}
+
+export function setValidatorCommitQuota(
+  admin: web3.PublicKey,
+  validator: web3.PublicKey,
+  args: { maxCommitsPerSlot: number; maxBytesPerSlot: number }
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(validator),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetValidatorCommitQuota,
+    (writer) => writer.u64(args.maxCommitsPerSlot).u64(args.maxBytesPerSlot)
+  );
+}
+
+export function initEarningsLedgerPage(
+  validator: web3.PublicKey,
+  page: number
+) {
+  const accounts = [
+    writable(validator, true),
+    readonly(validatorFeesVaultPda(validator)),
+    writable(earningsLedgerPagePda(validator, page)),
+    readonly(SYSTEM_PROGRAM),
+  ];
+  if (page > 0) {
+    accounts.push(readonly(earningsLedgerPagePda(validator, page - 1)));
+  }
+  return dlpInstruction(
+    accounts,
+    DlpDiscriminator.InitEarningsLedgerPage,
+    (writer) => writer.u32(page)
+  );
+}
+
+export function setFeatureGate(
+  admin: web3.PublicKey,
+  discriminator: DlpDiscriminator,
+  enabled: boolean
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      writable(FEATURE_GATES_PDA),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetFeatureGate,
+    (writer) => writer.u8(discriminator).bool(enabled)
+  );
+}
+
+export function setProtocolConfig(
+  admin: web3.PublicKey,
+  escrowDustThreshold: number,
+  undelegationGraceSlots = 0,
+  trustedExporter = web3.PublicKey.default
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      writable(protocolConfigPda()),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetProtocolConfig,
+    (writer) =>
+      writer
+        .u64(escrowDustThreshold)
+        .u64(undelegationGraceSlots)
+        .pubkey(trustedExporter)
+  );
+}
+
+/// Without reset, the lamports of the delegation records are added to the total value locked
+export function resyncProtocolStats(
+  admin: web3.PublicKey,
+  delegatedAccounts: web3.PublicKey[],
+  reset = true
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      writable(protocolStatsPda()),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+      ...delegatedAccounts.map((delegatedAccount) =>
+        readonly(delegationRecordPda(delegatedAccount))
+      ),
+    ],
+    DlpDiscriminator.ResyncProtocolStats,
+    (writer) => writer.bool(reset)
+  );
+}
+
+/// An expiry slot of zero never expires
+export function grantFeeExemption(
+  admin: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  expirySlot = 0
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      readonly(delegatedAccount),
+      writable(feeExemptionPda(delegatedAccount)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.GrantFeeExemption,
+    (writer) => writer.u64(expirySlot)
+  );
+}
+
+export function scheduleForceUndelegate(
+  admin: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      readonly(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      writable(forceUndelegationPda(delegatedAccount)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.ScheduleForceUndelegate
+  );
+}
+
+export function executeForceUndelegate(
+  admin: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      readonly(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      writable(forceUndelegationPda(delegatedAccount)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+    ],
+    DlpDiscriminator.ExecuteForceUndelegate
+  );
+}
+
+export function forceUndelegate(
+  payer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.ForceUndelegate
+  );
+}
+
+export function delegateStakeAccount(
+  authority: web3.PublicKey,
+  stakeAccount: web3.PublicKey,
+  commitFrequencyMs: number,
+  validator?: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      writable(stakeAccount),
+      readonly(stakeAuthorityPda(stakeAccount)),
+      writable(delegationRecordPda(stakeAccount)),
+      writable(delegationMetadataPda(stakeAccount)),
+      readonly(web3.StakeProgram.programId),
+      readonly(web3.SYSVAR_CLOCK_PUBKEY),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.DelegateStakeAccount,
+    (writer) =>
+      writer
+        .u32(commitFrequencyMs)
+        .option(validator, (validator) => writer.pubkey(validator))
+  );
+}
+
+export function undelegateStakeAccount(
+  signer: web3.PublicKey,
+  stakeAccount: web3.PublicKey,
+  authority: web3.PublicKey,
+  stakeState: Buffer
+) {
+  return dlpInstruction(
+    [
+      readonly(signer, true),
+      writable(stakeAccount),
+      readonly(stakeAuthorityPda(stakeAccount)),
+      writable(delegationRecordPda(stakeAccount)),
+      writable(delegationMetadataPda(stakeAccount)),
+      writable(authority),
+      readonly(web3.StakeProgram.programId),
+      readonly(web3.SYSVAR_CLOCK_PUBKEY),
+    ],
+    DlpDiscriminator.UndelegateStakeAccount,
+    (writer) => writer.bytes(stakeState)
+  );
+}
+
+function delegationAuthorityInstruction(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  validator: web3.PublicKey,
+  discriminator: DlpDiscriminator
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      writable(delegationAuthoritiesPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    discriminator,
+    (writer) => writer.pubkey(validator)
+  );
+}
+
+export function addDelegationAuthority(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  validator: web3.PublicKey
+) {
+  return delegationAuthorityInstruction(
+    authority,
+    delegatedAccount,
+    validator,
+    DlpDiscriminator.AddDelegationAuthority
+  );
+}
+
+export function removeDelegationAuthority(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  validator: web3.PublicKey
+) {
+  return delegationAuthorityInstruction(
+    authority,
+    delegatedAccount,
+    validator,
+    DlpDiscriminator.RemoveDelegationAuthority
+  );
+}
+
+export function updateDelegationAuthority(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  newAuthority: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      readonly(authority, true),
+      readonly(delegatedAccount),
+      writable(delegationRecordPda(delegatedAccount)),
+      readonly(delegationMetadataPda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+    ],
+    DlpDiscriminator.UpdateDelegationAuthority,
+    (writer) => writer.pubkey(newAuthority)
+  );
+}
+
+export function proposeProtocolVaultMigration(admin: web3.PublicKey) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      writable(protocolConfigPda()),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.ProposeProtocolVaultMigration
+  );
+}
+
+export function executeProtocolVaultMigration(
+  admin: web3.PublicKey,
+  currentGeneration: number
+) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      writable(protocolConfigPda()),
+      writable(feesVaultPda(currentGeneration)),
+      writable(feesVaultPda(currentGeneration + 1)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.ExecuteProtocolVaultMigration
+  );
+}
+
+/// Logs and returns the bitmask of the inconsistencies between the PDAs of a delegation
+export function validateDelegation(delegatedAccount: web3.PublicKey) {
+  return dlpInstruction(
+    [
+      readonly(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      readonly(delegationMetadataPda(delegatedAccount)),
+      readonly(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+    ],
+    DlpDiscriminator.ValidateDelegation
+  );
+}
+
+export function isValidatorWhitelisted(
+  validator: web3.PublicKey,
+  program: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      readonly(validator),
+      readonly(program),
+      readonly(programConfigPda(program)),
+      readonly(validatorWhitelistShardPda(program, validator)),
+    ],
+    DlpDiscriminator.IsValidatorWhitelisted
+  );
+}
+
+export interface ValidatorWhitelistStatus {
+  isWhitelisted: boolean;
+  maxDelegationSlots: bigint;
+  hasProgramConfig: boolean;
+}
+
+/// Decode the status returned by isValidatorWhitelisted
+export function decodeValidatorWhitelistStatus(
+  data: Buffer
+): ValidatorWhitelistStatus {
+  return {
+    isWhitelisted: data.readUInt8(0) === 1,
+    maxDelegationSlots: data.readBigUInt64LE(1),
+    hasProgramConfig: data.readUInt8(9) === 1,
+  };
+}
+
+/// Returns the borsh encoded delegation summaries of the accounts, owned by their programs
+export function getDelegationSummaries(
+  accounts: { account: web3.PublicKey; ownerProgram: web3.PublicKey }[]
+) {
+  return dlpInstruction(
+    accounts.flatMap(({ account, ownerProgram }) => [
+      readonly(account),
+      readonly(delegationRecordPda(account)),
+      readonly(delegationMetadataPda(account)),
+      readonly(programConfigPda(ownerProgram)),
+    ]),
+    DlpDiscriminator.GetDelegationSummaries
+  );
+}
+
+/// Returns the borsh encoded summaries of the escrows of the payers
+export function getEscrowSummaries(
+  escrows: { payer: web3.PublicKey; index: number }[]
+) {
+  return dlpInstruction(
+    escrows.flatMap(({ payer, index }) => {
+      const escrow = ephemeralBalancePda(payer, index);
+      return [
+        readonly(payer),
+        readonly(escrow),
+        readonly(delegationRecordPda(escrow)),
+      ];
+    }),
+    DlpDiscriminator.GetEscrowSummaries,
+    (writer) => {
+      writer.bytes(Uint8Array.from(escrows.map(({ index }) => index)));
+    }
+  );
+}
+
+/// Returns the borsh encoded pending commit summaries of the accounts
+export function getPendingCommitSummaries(accounts: web3.PublicKey[]) {
+  return dlpInstruction(
+    accounts.flatMap((account) => [
+      readonly(account),
+      readonly(commitStatePda(account)),
+      readonly(commitRecordPda(account)),
+    ]),
+    DlpDiscriminator.GetPendingCommitSummaries
+  );
+}
+
+export interface DelegationPackage {
+  sourceProgramId: web3.PublicKey;
+  exporter: web3.PublicKey;
+  exportSlot: number;
+  delegatedAccount: web3.PublicKey;
+  /// The data of the delegation record and metadata, discriminators included
+  delegationRecord: Buffer;
+  delegationMetadata: Buffer;
+  dataHash: Uint8Array;
+}
+
+export function exportDelegationPackage(
+  admin: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      readonly(admin, true),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      readonly(delegationMetadataPda(delegatedAccount)),
+    ],
+    DlpDiscriminator.ExportDelegationPackage
+  );
+}
+
+/// The import must follow an Ed25519 program instruction verifying the signature of the
+/// borsh serialized package by the admin
+export function importDelegationPackage(
+  admin: web3.PublicKey,
+  delegationPackage: DelegationPackage
+) {
+  const delegatedAccount = delegationPackage.delegatedAccount;
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(delegatedAccount),
+      writable(delegationRecordPda(delegatedAccount)),
+      writable(delegationMetadataPda(delegatedAccount)),
+      readonly(web3.SYSVAR_INSTRUCTIONS_PUBKEY),
+      readonly(SYSTEM_PROGRAM),
+      readonly(protocolConfigPda()),
+    ],
+    DlpDiscriminator.ImportDelegationPackage,
+    (writer) =>
+      writer
+        .pubkey(delegationPackage.sourceProgramId)
+        .pubkey(delegationPackage.exporter)
+        .u64(delegationPackage.exportSlot)
+        .pubkey(delegatedAccount)
+        .bytes(delegationPackage.delegationRecord)
+        .bytes(delegationPackage.delegationMetadata)
+        .array(delegationPackage.dataHash)
+  );
+}
+
+export interface ProgramVersion {
+  major: number;
+  minor: number;
+  patch: number;
+  /// The 20 bytes of the git commit hash
+  gitHash: Uint8Array;
+  minDiscriminator: number;
+  maxDiscriminator: number;
+}
+
+export function setVersion(admin: web3.PublicKey, version: ProgramVersion) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      writable(programVersionPda()),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetVersion,
+    (writer) =>
+      writer
+        .u16(version.major)
+        .u16(version.minor)
+        .u16(version.patch)
+        .array(version.gitHash)
+        .u8(version.minDiscriminator)
+        .u8(version.maxDiscriminator)
+  );
+}
+
+export function getVersion() {
+  return dlpInstruction(
+    [readonly(programVersionPda())],
+    DlpDiscriminator.GetVersion
+  );
+}
+
+/// Decode the program version returned by getVersion, or stored in the program version
+/// PDA once its 8 bytes discriminator are skipped
+export function decodeProgramVersion(data: Buffer): ProgramVersion {
+  return {
+    major: data.readUInt16LE(0),
+    minor: data.readUInt16LE(2),
+    patch: data.readUInt16LE(4),
+    gitHash: data.subarray(6, 26),
+    minDiscriminator: data[26],
+    maxDiscriminator: data[27],
+  };
+}
+
+export function bootstrapProtocol(admin: web3.PublicKey) {
+  return dlpInstruction(
+    [
+      writable(admin, true),
+      writable(feesVaultPda()),
+      writable(FEATURE_GATES_PDA),
+      writable(protocolConfigPda()),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.BootstrapProtocol
+  );
+}
+
+export function whitelistValidatorsForProgramBatch(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  insert: web3.PublicKey[],
+  remove: web3.PublicKey[]
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.WhitelistValidatorsForProgramBatch,
+    (writer) => {
+      writer.vec(insert, (validator) => writer.pubkey(validator));
+      writer.vec(remove, (validator) => writer.pubkey(validator));
+    }
+  );
+}
+
+export function whitelistValidatorShardForProgram(
+  authority: web3.PublicKey,
+  validator: web3.PublicKey,
+  program: web3.PublicKey,
+  insert: boolean
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(validator),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      writable(validatorWhitelistShardPda(program, validator)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.WhitelistValidatorShardForProgram,
+    (writer) => {
+      writer.bool(insert);
+    }
+  );
+}
+
+/// Migrates the validators to their shards, which must be the first approved validators of
+/// the program config, sorted by their bytes
+export function migrateProgramConfigWhitelist(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  validators: web3.PublicKey[]
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      readonly(SYSTEM_PROGRAM),
+      ...validators.map((validator) =>
+        writable(validatorWhitelistShardPda(program, validator))
+      ),
+    ],
+    DlpDiscriminator.MigrateProgramConfigWhitelist
+  );
+}
+
+export function setProgramAllowedDataLens(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  allowedDataLens: number[]
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetProgramAllowedDataLens,
+    (writer) => writer.vec(allowedDataLens, (len) => writer.u32(len))
+  );
+}
+
+export function setProgramUndelegateLamportsTolerance(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  undelegateLamportsTolerance: number | anchor.BN
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetProgramUndelegateLamportsTolerance,
+    (writer) => writer.u64(undelegateLamportsTolerance)
+  );
+}
+
+export function setProgramMaxDelegationSlots(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  maxDelegationSlots: number | anchor.BN
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetProgramMaxDelegationSlots,
+    (writer) => writer.u64(maxDelegationSlots)
+  );
+}
+
+export function closeProgramConfig(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  noActiveDelegations: boolean
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+    ],
+    DlpDiscriminator.CloseProgramConfig,
+    (writer) => writer.bool(noActiveDelegations)
+  );
+}
+
+export function setProgramValidateDelegations(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  validateDelegations: boolean
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetProgramValidateDelegations,
+    (writer) => writer.bool(validateDelegations)
+  );
+}
+
+export function setProgramUndelegateDiscriminatorOverride(
+  authority: web3.PublicKey,
+  program: web3.PublicKey,
+  allowUndelegateDiscriminatorOverride: boolean
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(program),
+      readonly(programDataPda(program)),
+      readonly(programDataPda(DELEGATION_PROGRAM_ID)),
+      writable(programConfigPda(program)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetProgramUndelegateDiscriminatorOverride,
+    (writer) => writer.bool(allowUndelegateDiscriminatorOverride)
+  );
+}
+
+export function setCommitSchedule(
+  rentPayer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  args: {
+    escrowIndex: number;
+    intervalSlots: number | anchor.BN;
+    maxCommitsPerInterval: number | anchor.BN;
+    commitFeeLamports: number | anchor.BN;
+  }
+) {
+  return dlpInstruction(
+    [
+      writable(rentPayer, true),
+      readonly(delegatedAccount),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(commitSchedulePda(delegatedAccount)),
+      readonly(ephemeralBalancePda(rentPayer, args.escrowIndex)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetCommitSchedule,
+    (writer) =>
+      writer
+        .u8(args.escrowIndex)
+        .u64(args.intervalSlots)
+        .u64(args.maxCommitsPerInterval)
+        .u64(args.commitFeeLamports)
+  );
+}
+
+export function closeCommitSchedule(
+  escrowPayer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(escrowPayer, true),
+      readonly(delegatedAccount),
+      writable(delegationMetadataPda(delegatedAccount)),
+      writable(commitSchedulePda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.CloseCommitSchedule
+  );
+}
+
+export function callHandler(
+  validator: web3.PublicKey,
+  destinationProgram: web3.PublicKey,
+  escrowAuthority: web3.PublicKey,
+  otherAccounts: web3.AccountMeta[],
+  args: {
+    escrowIndex: number;
+    data: Uint8Array;
+    escrowSpend?: number;
+    context?: CallHandlerContext;
+  }
+) {
+  const escrow = ephemeralBalancePda(escrowAuthority, args.escrowIndex);
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(validatorFeesVaultPda(validator)),
+      readonly(destinationProgram),
+      writable(escrowAuthority),
+      writable(escrow),
+      readonly(web3.SYSVAR_INSTRUCTIONS_PUBKEY),
+      readonly(callHandlerPermissionsPda(escrow)),
+      ...otherAccounts,
+    ],
+    DlpDiscriminator.CallHandler,
+    (writer) => {
+      writer.u8(args.escrowIndex).bytes(args.data);
+      // The escrow spend and the context trail the args, only when set
+      if (args.escrowSpend !== undefined || args.context !== undefined) {
+        writer.option(args.escrowSpend, (spend) => writer.u64(spend));
+      }
+      if (args.context !== undefined) {
+        writer.option(args.context, (context) => writer.u8(context));
+      }
+    }
+  );
+}
+
+export function setCallHandlerPermissions(
+  escrowAuthority: web3.PublicKey,
+  args: {
+    escrowIndex: number;
+    allowCommit: boolean;
+    allowUndelegate: boolean;
+    allowStandalone: boolean;
+  }
+) {
+  const escrow = ephemeralBalancePda(escrowAuthority, args.escrowIndex);
+  return dlpInstruction(
+    [
+      writable(escrowAuthority, true),
+      readonly(escrow),
+      writable(callHandlerPermissionsPda(escrow)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.SetCallHandlerPermissions,
+    (writer) =>
+      writer
+        .u8(args.escrowIndex)
+        .bool(args.allowCommit)
+        .bool(args.allowUndelegate)
+        .bool(args.allowStandalone)
+  );
+}
+
+export function initSessionReport(
+  escrowAuthority: web3.PublicKey,
+  escrowIndex: number
+) {
+  const escrow = ephemeralBalancePda(escrowAuthority, escrowIndex);
+  return dlpInstruction(
+    [
+      writable(escrowAuthority, true),
+      readonly(escrow),
+      writable(sessionReportPda(escrow)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.InitSessionReport,
+    (writer) => writer.u8(escrowIndex)
+  );
+}
+
+export function closeSessionReport(
+  escrowAuthority: web3.PublicKey,
+  escrowIndex: number
+) {
+  const escrow = ephemeralBalancePda(escrowAuthority, escrowIndex);
+  return dlpInstruction(
+    [
+      writable(escrowAuthority, true),
+      readonly(escrow),
+      writable(sessionReportPda(escrow)),
+    ],
+    DlpDiscriminator.CloseSessionReport
+  );
+}
+
+export function splitDelegation(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  newDelegatedAccount: web3.PublicKey,
+  ownerProgram: web3.PublicKey,
+  args: { ranges: [number, number][]; seeds: Buffer[] }
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(delegatedAccount),
+      readonly(delegationRecordPda(delegatedAccount)),
+      readonly(delegationMetadataPda(delegatedAccount)),
+      readonly(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      writable(newDelegatedAccount, true),
+      writable(delegationRecordPda(newDelegatedAccount)),
+      writable(delegationMetadataPda(newDelegatedAccount)),
+      readonly(ownerProgram),
+      readonly(SYSTEM_PROGRAM),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.SplitDelegation,
+    (writer) =>
+      writer
+        .vec(args.ranges, ([start, end]) => writer.u32(start).u32(end))
+        .vec(args.seeds, (seed) => writer.bytes(seed))
+  );
+}
+
+export function initDelegateBuffer(
+  payer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  dataLen: number
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(delegatedAccount, true),
+      writable(stagedDelegateBufferPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.InitDelegateBuffer,
+    (writer) => writer.u32(dataLen)
+  );
+}
+
+export function writeDelegateBufferChunk(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  offset: number,
+  data: Uint8Array
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(delegatedAccount),
+      writable(stagedDelegateBufferPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.WriteDelegateBufferChunk,
+    (writer) => writer.u32(offset).bytes(data)
+  );
+}
+
+export function initCommitBuffer(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  dataLen: number
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(delegatedAccount),
+      writable(commitBufferPda(delegatedAccount, authority)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.InitCommitBuffer,
+    (writer) => writer.u32(dataLen)
+  );
+}
+
+export function writeCommitBuffer(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  offset: number,
+  data: Uint8Array
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(delegatedAccount),
+      writable(commitBufferPda(delegatedAccount, authority)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.WriteCommitBuffer,
+    (writer) => writer.u32(offset).bytes(data)
+  );
+}
+
+export function closeCommitBuffer(
+  authority: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(authority, true),
+      readonly(delegatedAccount),
+      writable(commitBufferPda(delegatedAccount, authority)),
+    ],
+    DlpDiscriminator.CloseCommitBuffer
+  );
+}
+
+export function growCommitState(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  data: Uint8Array
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      readonly(delegatedAccount),
+      writable(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      readonly(delegationRecordPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.GrowCommitState,
+    (writer) => writer.bytes(data)
+  );
+}
+
+export function approveUndelegateAndClose(
+  payer: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  closeDestination: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(delegatedAccount, true),
+      writable(delegationMetadataPda(delegatedAccount)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.ApproveUndelegateAndClose,
+    (writer) => writer.pubkey(closeDestination)
+  );
+}
+
+export function requestUndelegation(
+  requester: web3.PublicKey,
+  delegatedAccount: web3.PublicKey
+) {
+  return dlpInstruction(
+    [
+      writable(requester, true),
+      readonly(delegatedAccount),
+      writable(delegationMetadataPda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      readonly(protocolConfigPda()),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.RequestUndelegation
+  );
+}
+
+export function topUpEphemeralBalance(
+  payer: web3.PublicKey,
+  pubkey: web3.PublicKey,
+  amount: number,
+  index: number
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(pubkey),
+      writable(ephemeralBalancePda(pubkey, index)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.TopUpEphemeralBalance,
+    (writer) => writer.u64(amount).u8(index)
+  );
+}
+
+export function fundEscrowFromDelegated(
+  validator: web3.PublicKey,
+  delegatedAccount: web3.PublicKey,
+  pubkey: web3.PublicKey,
+  amount: number,
+  index: number
+) {
+  return dlpInstruction(
+    [
+      writable(validator, true),
+      writable(delegatedAccount),
+      writable(delegationRecordPda(delegatedAccount)),
+      readonly(delegationMetadataPda(delegatedAccount)),
+      readonly(commitStatePda(delegatedAccount)),
+      readonly(commitRecordPda(delegatedAccount)),
+      readonly(pubkey),
+      writable(ephemeralBalancePda(pubkey, index)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.FundEscrowFromDelegated,
+    (writer) => writer.u64(amount).u8(index)
+  );
+}
+
+export type EphemeralBalanceIndex =
+  | { narrow: number }
+  | { wide: number };
+
+export type TopUpEphemeralBalanceBatchEntry = {
+  pubkey: web3.PublicKey;
+  index: EphemeralBalanceIndex;
+  amount: number;
+};
+
+export function escrowPda(pubkey: web3.PublicKey, index: EphemeralBalanceIndex) {
+  return "narrow" in index
+    ? ephemeralBalancePda(pubkey, index.narrow)
+    : wideEphemeralBalancePda(pubkey, index.wide);
+}
+
+export function topUpEphemeralBalanceBatch(
+  payer: web3.PublicKey,
+  entries: TopUpEphemeralBalanceBatchEntry[]
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(SYSTEM_PROGRAM),
+      ...entries.flatMap(({ pubkey, index }) => [
+        readonly(pubkey),
+        writable(escrowPda(pubkey, index)),
+      ]),
+    ],
+    DlpDiscriminator.TopUpEphemeralBalanceBatch,
+    (writer) =>
+      writer.vec(entries, ({ index, amount }) => {
+        if ("narrow" in index) {
+          writer.u8(0).u8(index.narrow);
+        } else {
+          writer.u8(1).u32(index.wide);
+        }
+        writer.u64(amount);
+      })
+  );
+}
+
+export function delegateEphemeralBalance(
+  payer: web3.PublicKey,
+  pubkey: web3.PublicKey,
+  index: number,
+  delegateArgs: DelegateArgs
+) {
+  const balance = ephemeralBalancePda(pubkey, index);
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(pubkey, true),
+      writable(balance),
+      writable(delegateBufferPda(balance, SYSTEM_PROGRAM)),
+      writable(delegationRecordPda(balance)),
+      writable(delegationMetadataPda(balance)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(DELEGATION_PROGRAM_ID),
+    ],
+    DlpDiscriminator.DelegateEphemeralBalance,
+    (writer) => {
+      writeDelegateArgs(writer, delegateArgs);
+      writer.u8(index);
+    }
+  );
+}
+
+export function redelegateEphemeralBalance(
+  validator: web3.PublicKey,
+  pubkey: web3.PublicKey,
+  index: number,
+  newValidator: web3.PublicKey
+) {
+  const balance = ephemeralBalancePda(pubkey, index);
+  return dlpInstruction(
+    [
+      readonly(validator, true),
+      readonly(pubkey, true),
+      readonly(balance),
+      writable(delegationRecordPda(balance)),
+      readonly(delegationMetadataPda(balance)),
+      readonly(commitStatePda(balance)),
+      readonly(commitRecordPda(balance)),
+      readonly(newValidator),
+      readonly(validatorFeesVaultPda(newValidator)),
+    ],
+    DlpDiscriminator.RedelegateEphemeralBalance,
+    (writer) => writer.u8(index)
+  );
+}
+
+export function closeEphemeralBalance(payer: web3.PublicKey, index: number) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      writable(ephemeralBalancePda(payer, index)),
+      readonly(SYSTEM_PROGRAM),
+    ],
+    DlpDiscriminator.CloseEphemeralBalance,
+    (writer) => writer.u8(index)
+  );
+}
+
+export function topUpProgramEphemeralBalance(
+  payer: web3.PublicKey,
+  pubkey: web3.PublicKey,
+  programId: web3.PublicKey,
+  amount: number,
+  index: number
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(pubkey),
+      writable(programEphemeralBalancePda(pubkey, programId, index)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.TopUpProgramEphemeralBalance,
+    (writer) => writer.u64(amount).pubkey(programId).u8(index)
+  );
+}
+
+export function delegateProgramEphemeralBalance(
+  payer: web3.PublicKey,
+  pubkey: web3.PublicKey,
+  programId: web3.PublicKey,
+  index: number,
+  delegateArgs: DelegateArgs
+) {
+  const balance = programEphemeralBalancePda(pubkey, programId, index);
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      readonly(pubkey, true),
+      writable(balance),
+      writable(delegateBufferPda(balance, SYSTEM_PROGRAM)),
+      writable(delegationRecordPda(balance)),
+      writable(delegationMetadataPda(balance)),
+      readonly(programConfigPda(programId)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(DELEGATION_PROGRAM_ID),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.DelegateProgramEphemeralBalance,
+    (writer) => {
+      writeDelegateArgs(writer, delegateArgs);
+      writer.pubkey(programId).u8(index);
+    }
+  );
+}
+
+export function closeProgramEphemeralBalance(
+  payer: web3.PublicKey,
+  programId: web3.PublicKey,
+  index: number
+) {
+  return dlpInstruction(
+    [
+      writable(payer, true),
+      writable(programEphemeralBalancePda(payer, programId, index)),
+      readonly(SYSTEM_PROGRAM),
+      readonly(FEATURE_GATES_PDA),
+    ],
+    DlpDiscriminator.CloseProgramEphemeralBalance,
+    (writer) => writer.pubkey(programId).u8(index)
+  );
+}
+
+/// Diff of two account states, in the format of src/diff/algorithm.rs
+export function computeDiff(original: Uint8Array, changed: Uint8Array) {
+  const segments: [number, Uint8Array][] = [];
+  const minLen = Math.min(original.length, changed.length);
+  let i = 0;
+  while (i < minLen) {
+    if (original[i] !== changed[i]) {
+      const start = i;
+      while (i < minLen && original[i] !== changed[i]) {
+        i++;
+      }
+      segments.push([start, changed.slice(start, i)]);
+    } else {
+      i++;
+    }
+  }
+  if (changed.length > original.length) {
+    segments.push([original.length, changed.slice(original.length)]);
+  }
+
+  const writer = new BorshWriter().u32(changed.length).u32(segments.length);
+  let offsetInDiff = 0;
+  for (const [offsetInData, segment] of segments) {
+    writer.u32(offsetInDiff).u32(offsetInData);
+    offsetInDiff += segment.length;
+  }
+  return Buffer.concat([
+    writer.toBuffer(),
+    ...segments.map(([, segment]) => Buffer.from(segment)),
+  ]);
+}
+
+/// Coverage of the delegation program instructions across the integration suite
+
+const exercised = new Set<DlpDiscriminator>();
+
+export function exercisedDiscriminators() {
+  return exercised;
+}
+
+async function fetchTransaction(
+  connection: web3.Connection,
+  signature: string
+) {
+  for (let i = 0; i < 10; i++) {
+    const tx = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    if (tx) return tx;
+    await new Promise((r) => setTimeout(r, 500));
+  }
+  throw new Error("Transaction not found after waiting");
+}
+
+/// Record the delegation program instructions of a transaction, including the CPIs
+export async function recordCoverage(
+  connection: web3.Connection,
+  signature: string
+) {
+  const tx = await fetchTransaction(connection, signature);
+  const keys = tx.transaction.message.staticAccountKeys;
+  const record = (programIdIndex: number, data: Uint8Array) => {
+    if (keys[programIdIndex].equals(DELEGATION_PROGRAM_ID) && data.length) {
+      exercised.add(data[0]);
+    }
+  };
+  for (const ix of tx.transaction.message.compiledInstructions) {
+    record(ix.programIdIndex, ix.data);
+  }
+  for (const inner of tx.meta.innerInstructions ?? []) {
+    for (const ix of inner.instructions) {
+      record(ix.programIdIndex, anchor.utils.bytes.bs58.decode(ix.data));
+    }
+  }
+  return tx;
+}
+
+/// Send the instructions and record the delegation program instructions they executed
+export async function processInstructions(
+  provider: anchor.AnchorProvider,
+  ixs: web3.TransactionInstruction[],
+  signers: web3.Signer[] = []
+) {
+  const tx = new web3.Transaction().add(...ixs);
+  const signature = await provider.sendAndConfirm(tx, signers, {
+    commitment: "confirmed",
+  });
+  await recordCoverage(provider.connection, signature);
+  return signature;
+}
+
+/// Send the instructions, expecting a delegation program error. The instruction counts
+/// as exercised when it fails with the expected error, since it reached the processor.
+export async function expectDlpError(
+  provider: anchor.AnchorProvider,
+  ixs: web3.TransactionInstruction[],
+  error: DlpError,
+  signers: web3.Signer[] = []
+) {
+  const tx = new web3.Transaction().add(...ixs);
+  tx.feePayer = provider.wallet.publicKey;
+  tx.recentBlockhash = (
+    await provider.connection.getLatestBlockhash()
+  ).blockhash;
+  if (signers.length) {
+    tx.partialSign(...signers);
+  }
+  const signed = await provider.wallet.signTransaction(tx);
+  const signature = await provider.connection.sendRawTransaction(
+    signed.serialize(),
+    { skipPreflight: true }
+  );
+  await provider.connection.confirmTransaction(signature, "confirmed");
+  const result = await fetchTransaction(provider.connection, signature);
+  assert.deepEqual(result.meta.err, {
+    InstructionError: [ixs.length - 1, { Custom: error }],
+  });
+  const ix = ixs[ixs.length - 1];
+  if (ix.programId.equals(DELEGATION_PROGRAM_ID)) {
+    exercised.add(ix.data[0]);
+  }
+}
+
+/// Assert that the transaction fails, without reaching the delegation program
+export async function expectFailure(
+  provider: anchor.AnchorProvider,
+  ixs: web3.TransactionInstruction[],
+  signers: web3.Signer[] = []
+) {
+  const tx = new web3.Transaction().add(...ixs);
+  let failed = false;
+  try {
+    await provider.sendAndConfirm(tx, signers, { commitment: "confirmed" });
+  } catch (e) {
+    failed = true;
+  }
+  assert.isTrue(failed, "Expected the transaction to fail");
+}
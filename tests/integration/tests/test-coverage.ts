@@ -0,0 +1,727 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, web3 } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { TestDelegation } from "../target/types/test_delegation";
+import { TestEscrow } from "../target/types/test_escrow";
+import { ON_CURVE_ACCOUNT } from "./fixtures/consts";
+import * as dlp from "./fixtures/dlp";
+
+/// Runs last: exercises the admin instructions and checks that the suite executed every
+/// instruction of the delegation program
+describe("TestCoverage", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const testEscrow = anchor.workspace.TestEscrow as Program<TestEscrow>;
+  const testDelegation = anchor.workspace
+    .TestDelegation as Program<TestDelegation>;
+  const admin = provider.wallet.publicKey;
+  const validator = provider.wallet.publicKey;
+
+  it("Set the validator info", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.setValidatorInfo(validator, {
+        endpoint: "http://127.0.0.1:8899",
+        version: "0.1.4",
+        clusterId: "localnet",
+      }),
+    ]);
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(dlp.validatorInfoPda(validator))
+    );
+  });
+
+  it("Record a heartbeat of the validator", async () => {
+    await dlp.processInstructions(provider, [dlp.validatorHeartbeat(validator)]);
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.validatorLivenessPda(validator)
+      )
+    );
+  });
+
+  it("Register a commit relayer of the validator", async () => {
+    const relayer = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.registerCommitRelayer(validator, relayer, true),
+    ]);
+    const vault = await provider.connection.getAccountInfo(
+      dlp.validatorFeesVaultPda(validator)
+    );
+    // The relayers follow the discriminator and their count
+    assert.equal(vault.data.readUInt32LE(8), 1);
+    assert.isTrue(new web3.PublicKey(vault.data.subarray(12, 44)).equals(relayer));
+  });
+
+  it("Grant the authority of the validator to a sub-key", async () => {
+    const subKey = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.setAuthorityGrant(validator, {
+        key: subKey,
+        expirySlot: new anchor.BN("18446744073709551615"),
+        scope: dlp.AuthorityScope.Commit,
+        approved: true,
+      }),
+    ]);
+    const chain = await provider.connection.getAccountInfo(
+      dlp.authorityDelegationChainPda(validator)
+    );
+    // The grants follow the discriminator, the delegator and their count
+    assert.equal(chain.data.readUInt32LE(40), 1);
+    assert.isTrue(new web3.PublicKey(chain.data.subarray(44, 76)).equals(subKey));
+  });
+
+  it("Set the commit quota of the validator", async () => {
+    // The commits of the other tests are left unlimited
+    await dlp.processInstructions(provider, [
+      dlp.setValidatorCommitQuota(admin, validator, {
+        maxCommitsPerSlot: 0,
+        maxBytesPerSlot: 0,
+      }),
+    ]);
+    const vault = await provider.connection.getAccountInfo(
+      dlp.validatorFeesVaultPda(validator)
+    );
+    // The commit quota follows the discriminator and the commit relayers
+    assert.equal(vault.data.length, 8 + 4 + 32 * vault.data.readUInt32LE(8) + 40);
+  });
+
+  it("Init the first page of the earnings ledger of the validator", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.initEarningsLedgerPage(validator, 0),
+    ]);
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.earningsLedgerPagePda(validator, 0)
+      )
+    );
+  });
+
+  it("Init and close the fees vault of a validator", async () => {
+    const other = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.initValidatorFeesVault(admin, admin, other),
+      dlp.closeValidatorFeesVault(admin, admin, other),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(dlp.validatorFeesVaultPda(other))
+    );
+  });
+
+  it("Redelegate an ephemeral balance to another validator", async () => {
+    const other = web3.Keypair.generate().publicKey;
+    const balance = dlp.ephemeralBalancePda(admin, 13);
+    await dlp.processInstructions(provider, [
+      dlp.initValidatorFeesVault(admin, admin, other),
+      dlp.topUpEphemeralBalance(admin, admin, 100_000_000, 13),
+      dlp.delegateEphemeralBalance(admin, admin, 13, {
+        commitFrequencyMs: 0,
+        seeds: [],
+        validator,
+      }),
+      dlp.redelegateEphemeralBalance(validator, admin, 13, other),
+    ]);
+    const record = await provider.connection.getAccountInfo(
+      dlp.delegationRecordPda(balance)
+    );
+    // The authority follows the discriminator
+    assert.isTrue(new web3.PublicKey(record.data.subarray(8, 40)).equals(other));
+  });
+
+  it("Fund an escrow from the excess lamports of a delegated account", async () => {
+    const delegated = dlp.ephemeralBalancePda(admin, 14);
+    await dlp.processInstructions(provider, [
+      dlp.topUpEphemeralBalance(admin, admin, 100_000_000, 14),
+      dlp.delegateEphemeralBalance(admin, admin, 14, {
+        commitFrequencyMs: 0,
+        seeds: [],
+        validator,
+      }),
+      dlp.fundEscrowFromDelegated(validator, delegated, admin, 10_000_000, 15),
+    ]);
+    const escrow = await provider.connection.getAccountInfo(
+      dlp.ephemeralBalancePda(admin, 15)
+    );
+    assert.isAtLeast(escrow.lamports, 10_000_000);
+  });
+
+  it("Undelegate an escrow to a parked undelegation and claim it", async () => {
+    const escrow = dlp.ephemeralBalancePda(admin, 16);
+    await dlp.processInstructions(provider, [
+      dlp.topUpEphemeralBalance(admin, admin, 100_000_000, 16),
+      dlp.delegateEphemeralBalance(admin, admin, 16, {
+        commitFrequencyMs: 0,
+        seeds: [],
+        validator,
+      }),
+    ]);
+    const lamports = (await provider.connection.getAccountInfo(escrow)).lamports;
+    await dlp.processInstructions(provider, [
+      dlp.commitState(validator, escrow, web3.SystemProgram.programId, {
+        nonce: 1,
+        lamports,
+        allowUndelegation: true,
+        data: new Uint8Array(),
+      }),
+      dlp.finalize(validator, escrow),
+      dlp.undelegateToBuffer(
+        validator,
+        escrow,
+        web3.SystemProgram.programId,
+        admin
+      ),
+    ]);
+    const parked = await provider.connection.getAccountInfo(
+      dlp.parkedUndelegationPda(escrow)
+    );
+    // The rent payer follows the discriminator
+    assert.isTrue(new web3.PublicKey(parked.data.subarray(8, 40)).equals(admin));
+    await dlp.processInstructions(provider, [
+      dlp.claimParkedUndelegation(admin, escrow),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(dlp.parkedUndelegationPda(escrow))
+    );
+  });
+
+  it("Export a delegation and reject an unsigned import", async () => {
+    // Redelegated to another validator above
+    const balance = dlp.ephemeralBalancePda(admin, 13);
+    await dlp.processInstructions(provider, [
+      dlp.exportDelegationPackage(admin, balance),
+    ]);
+    await dlp.expectDlpError(
+      provider,
+      [
+        dlp.importDelegationPackage(admin, {
+          sourceProgramId: dlp.DELEGATION_PROGRAM_ID,
+          exporter: admin,
+          exportSlot: 0,
+          delegatedAccount: balance,
+          delegationRecord: Buffer.alloc(0),
+          delegationMetadata: Buffer.alloc(0),
+          dataHash: new Uint8Array(32),
+        }),
+      ],
+      dlp.DlpError.InvalidDelegationPackage
+    );
+  });
+
+  it("Bootstrap the protocol", async () => {
+    await dlp.processInstructions(provider, [dlp.bootstrapProtocol(admin)]);
+    for (const pda of [
+      dlp.feesVaultPda(),
+      dlp.FEATURE_GATES_PDA,
+      dlp.protocolConfigPda(),
+    ]) {
+      assert.isNotNull(await provider.connection.getAccountInfo(pda));
+    }
+  });
+
+  it("Set and get the program version", async () => {
+    const version = {
+      major: 1,
+      minor: 2,
+      patch: 3,
+      gitHash: new Uint8Array(20).fill(7),
+      minDiscriminator: 0,
+      maxDiscriminator: dlp.DlpDiscriminator.GetVersion,
+    };
+    await dlp.processInstructions(provider, [
+      dlp.setVersion(admin, version),
+      dlp.getVersion(),
+    ]);
+    const account = await provider.connection.getAccountInfo(
+      dlp.programVersionPda()
+    );
+    const stored = dlp.decodeProgramVersion(account.data.subarray(8));
+    assert.equal(stored.minor, version.minor);
+    assert.equal(stored.maxDiscriminator, dlp.DlpDiscriminator.GetVersion);
+  });
+
+  it("Grant a fee exemption", async () => {
+    const delegatedAccount = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.grantFeeExemption(admin, delegatedAccount),
+      dlp.grantFeeExemption(admin, delegatedAccount, 1_000_000),
+    ]);
+    const account = await provider.connection.getAccountInfo(
+      dlp.feeExemptionPda(delegatedAccount)
+    );
+    assert.equal(account.data.readBigUInt64LE(8), BigInt(1_000_000));
+  });
+
+  it("Validate a delegation", async () => {
+    // The violations of an account which is not delegated do not fail the instruction
+    const delegatedAccount = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.validateDelegation(delegatedAccount),
+    ]);
+  });
+
+  it("Get the summaries of accounts and escrows for wallets", async () => {
+    // Accounts which are not delegated are summarized as such
+    const account = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.getDelegationSummaries([
+        { account, ownerProgram: testEscrow.programId },
+      ]),
+      dlp.getEscrowSummaries([{ payer: admin, index: 13 }]),
+      dlp.getPendingCommitSummaries([account]),
+    ]);
+  });
+
+  it("Toggle a feature gate", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.setFeatureGate(admin, dlp.DlpDiscriminator.CommitDiff, false),
+      dlp.setFeatureGate(admin, dlp.DlpDiscriminator.CommitDiff, true),
+    ]);
+  });
+
+  it("Set the allowed data lens of a program", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.setProgramAllowedDataLens(admin, testEscrow.programId, [114]),
+    ]);
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.programConfigPda(testEscrow.programId)
+      )
+    );
+  });
+
+  it("Check whether the validator is whitelisted for a program", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.isValidatorWhitelisted(validator, testEscrow.programId),
+    ]);
+  });
+
+  it("Set the undelegate lamports tolerance of a program", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.setProgramUndelegateLamportsTolerance(
+        admin,
+        testEscrow.programId,
+        1_000
+      ),
+      dlp.setProgramUndelegateLamportsTolerance(admin, testEscrow.programId, 0),
+    ]);
+  });
+
+  it("Set the maximum delegation slots of a program", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.setProgramMaxDelegationSlots(admin, testEscrow.programId, 1_000),
+      dlp.setProgramMaxDelegationSlots(admin, testEscrow.programId, 0),
+    ]);
+  });
+
+  it("Set whether the delegations of a program are validated", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.setProgramValidateDelegations(admin, testEscrow.programId, true),
+      dlp.setProgramValidateDelegations(admin, testEscrow.programId, false),
+    ]);
+  });
+
+  it("Allow overriding the undelegate discriminator", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.setProgramUndelegateDiscriminatorOverride(
+        admin,
+        testEscrow.programId,
+        true
+      ),
+      dlp.setProgramUndelegateDiscriminatorOverride(
+        admin,
+        testEscrow.programId,
+        false
+      ),
+    ]);
+  });
+
+  it("Stage a state in a commit buffer and close it", async () => {
+    const delegatedAccount = ON_CURVE_ACCOUNT.publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.initCommitBuffer(validator, delegatedAccount, 4),
+      dlp.writeCommitBuffer(validator, delegatedAccount, 0, Buffer.from([1, 2])),
+      dlp.writeCommitBuffer(validator, delegatedAccount, 2, Buffer.from([3, 4])),
+    ]);
+    const buffer = await provider.connection.getAccountInfo(
+      dlp.commitBufferPda(delegatedAccount, validator)
+    );
+    // The state follows the discriminator, the authority and its length
+    assert.deepEqual([...buffer.data.subarray(48)], [1, 2, 3, 4]);
+    await dlp.processInstructions(provider, [
+      dlp.closeCommitBuffer(validator, delegatedAccount),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(
+        dlp.commitBufferPda(delegatedAccount, validator)
+      )
+    );
+  });
+
+  it("Set and close the commit schedule of a delegated account", async () => {
+    // Delegated by the wallet in test-delegation, closed in the same transaction so
+    // that its commits are not scheduled
+    const delegatedAccount = ON_CURVE_ACCOUNT.publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.setCommitSchedule(admin, delegatedAccount, {
+        escrowIndex: 0,
+        intervalSlots: 10,
+        maxCommitsPerInterval: 1,
+        commitFeeLamports: 0,
+      }),
+      dlp.closeCommitSchedule(admin, delegatedAccount),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(
+        dlp.commitSchedulePda(delegatedAccount)
+      )
+    );
+  });
+
+  it("Schedule the force undelegation of a delegated account", async () => {
+    // Delegated by the wallet in test-delegation, the timelock does not elapse in the suite
+    const delegatedAccount = ON_CURVE_ACCOUNT.publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.scheduleForceUndelegate(admin, delegatedAccount),
+    ]);
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.forceUndelegationPda(delegatedAccount)
+      )
+    );
+    await dlp.expectDlpError(
+      provider,
+      [dlp.executeForceUndelegate(admin, delegatedAccount)],
+      dlp.DlpError.ForceUndelegationTimelock
+    );
+  });
+
+  it("Force the undelegation of an account whose validator stopped committing", async () => {
+    // Delegated by the wallet in test-delegation, its commit timeout does not elapse in the
+    // suite
+    await dlp.expectDlpError(
+      provider,
+      [dlp.forceUndelegate(admin, ON_CURVE_ACCOUNT.publicKey)],
+      dlp.DlpError.CommitTimeoutNotElapsed
+    );
+  });
+
+  it("Delegate and undelegate a native stake account", async () => {
+    const stakeAccount = web3.Keypair.generate();
+    await dlp.processInstructions(
+      provider,
+      web3.StakeProgram.createAccount({
+        fromPubkey: admin,
+        stakePubkey: stakeAccount.publicKey,
+        authorized: new web3.Authorized(admin, admin),
+        lamports: web3.LAMPORTS_PER_SOL,
+      }).instructions,
+      [stakeAccount]
+    );
+    await dlp.processInstructions(provider, [
+      dlp.delegateStakeAccount(admin, stakeAccount.publicKey, 30_000, validator),
+    ]);
+
+    // A settled state changing the authorities is rejected
+    const stakeState = (
+      await provider.connection.getAccountInfo(stakeAccount.publicKey)
+    ).data;
+    const invalidStakeState = Buffer.from(stakeState);
+    validator.toBuffer().copy(invalidStakeState, 44);
+    await dlp.expectDlpError(
+      provider,
+      [
+        dlp.undelegateStakeAccount(
+          validator,
+          stakeAccount.publicKey,
+          admin,
+          invalidStakeState
+        ),
+      ],
+      dlp.DlpError.InvalidStakeState
+    );
+
+    await dlp.processInstructions(provider, [
+      dlp.undelegateStakeAccount(
+        validator,
+        stakeAccount.publicKey,
+        admin,
+        stakeState
+      ),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(
+        dlp.delegationRecordPda(stakeAccount.publicKey)
+      )
+    );
+  });
+
+  it("Add and remove a delegation authority of a delegated account", async () => {
+    // Delegated by the wallet in test-delegation, the validator being its authority
+    const delegatedAccount = ON_CURVE_ACCOUNT.publicKey;
+    const otherValidator = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.addDelegationAuthority(validator, delegatedAccount, otherValidator),
+    ]);
+    const authorities = await provider.connection.getAccountInfo(
+      dlp.delegationAuthoritiesPda(delegatedAccount)
+    );
+    // The authorities follow the discriminator, the delegated account, the delegation slot
+    // and their count
+    assert.equal(authorities.data.readUInt32LE(48), 1);
+    assert.isTrue(
+      new web3.PublicKey(authorities.data.subarray(52, 84)).equals(otherValidator)
+    );
+    await dlp.processInstructions(provider, [
+      dlp.removeDelegationAuthority(validator, delegatedAccount, otherValidator),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(
+        dlp.delegationAuthoritiesPda(delegatedAccount)
+      )
+    );
+  });
+
+  it("Hand the authority of a delegation to another validator and back", async () => {
+    // Delegated by the wallet in test-delegation, the validator being its authority
+    const delegatedAccount = ON_CURVE_ACCOUNT.publicKey;
+    const otherValidator = web3.Keypair.generate();
+    await dlp.processInstructions(provider, [
+      dlp.updateDelegationAuthority(
+        validator,
+        delegatedAccount,
+        otherValidator.publicKey
+      ),
+    ]);
+    // The authority follows the discriminator
+    const record = await provider.connection.getAccountInfo(
+      dlp.delegationRecordPda(delegatedAccount)
+    );
+    assert.isTrue(
+      new web3.PublicKey(record.data.subarray(8, 40)).equals(
+        otherValidator.publicKey
+      )
+    );
+    await dlp.processInstructions(
+      provider,
+      [
+        dlp.updateDelegationAuthority(
+          otherValidator.publicKey,
+          delegatedAccount,
+          validator
+        ),
+      ],
+      [otherValidator]
+    );
+  });
+
+  it("Propose a protocol fees vault migration", async () => {
+    await dlp.processInstructions(provider, [
+      dlp.proposeProtocolVaultMigration(admin),
+    ]);
+    await dlp.expectDlpError(
+      provider,
+      [dlp.executeProtocolVaultMigration(admin, 0)],
+      dlp.DlpError.ProtocolVaultMigrationTimelock
+    );
+  });
+
+  it("Resync the total value locked of the protocol stats", async () => {
+    // Delegated by the wallet in test-delegation
+    const delegatedAccount = ON_CURVE_ACCOUNT.publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.resyncProtocolStats(admin, [delegatedAccount]),
+    ]);
+    const record = await provider.connection.getAccountInfo(
+      dlp.delegationRecordPda(delegatedAccount)
+    );
+    const stats = await provider.connection.getAccountInfo(
+      dlp.protocolStatsPda()
+    );
+    // The lamports follow the authority, the owner and the delegation slot in the record
+    assert.equal(
+      stats.data.readBigUInt64LE(8),
+      record.data.readBigUInt64LE(8 + 32 + 32 + 8)
+    );
+  });
+
+  it("Set the call handler permissions of an escrow", async () => {
+    const escrowIndex = 7;
+    await dlp.processInstructions(provider, [
+      dlp.setCallHandlerPermissions(admin, {
+        escrowIndex,
+        allowCommit: true,
+        allowUndelegate: true,
+        allowStandalone: false,
+      }),
+    ]);
+    const account = await provider.connection.getAccountInfo(
+      dlp.callHandlerPermissionsPda(dlp.ephemeralBalancePda(admin, escrowIndex))
+    );
+    assert.equal(account.data[8], 0b011);
+  });
+
+  it("Start and close the session report of an escrow", async () => {
+    const escrowIndex = 7;
+    const escrow = dlp.ephemeralBalancePda(admin, escrowIndex);
+    await dlp.processInstructions(provider, [
+      dlp.initSessionReport(admin, escrowIndex),
+    ]);
+    const account = await provider.connection.getAccountInfo(
+      dlp.sessionReportPda(escrow)
+    );
+    // The escrow follows the discriminator and the authority
+    assert.isTrue(account.data.subarray(40, 72).equals(escrow.toBuffer()));
+    await dlp.processInstructions(provider, [
+      dlp.closeSessionReport(admin, escrowIndex),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(dlp.sessionReportPda(escrow))
+    );
+  });
+
+  it("Top up several escrows in a batch", async () => {
+    const pubkey = web3.Keypair.generate().publicKey;
+    const entries: dlp.TopUpEphemeralBalanceBatchEntry[] = [
+      { pubkey: admin, index: { narrow: 9 }, amount: 1_000 },
+      { pubkey, index: { wide: 70_000 }, amount: 2_000 },
+    ];
+    await dlp.processInstructions(provider, [
+      dlp.topUpEphemeralBalanceBatch(admin, entries),
+    ]);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(0);
+    for (const { pubkey, index, amount } of entries) {
+      const balance = await provider.connection.getBalance(
+        dlp.escrowPda(pubkey, index)
+      );
+      assert.equal(balance, rent + amount);
+    }
+  });
+
+  it("Undelegate an account in two stages", async () => {
+    // Delegated along the test PDA in test-delegation, with the wallet as rent payer
+    const [account] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("test-pda-other")],
+      testDelegation.programId
+    );
+    const { data, lamports } = await provider.connection.getAccountInfo(
+      account
+    );
+    await dlp.processInstructions(provider, [
+      dlp.commitState(validator, account, testDelegation.programId, {
+        nonce: 1,
+        lamports,
+        allowUndelegation: true,
+        data,
+      }),
+      dlp.finalize(validator, account),
+      dlp.undelegateStage1(validator, account, testDelegation.programId),
+    ]);
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.undelegateProgressPda(account)
+      )
+    );
+
+    await dlp.processInstructions(provider, [
+      dlp.undelegateStage2(validator, account, admin),
+    ]);
+    const accountInfo = await provider.connection.getAccountInfo(account);
+    assert.isTrue(accountInfo.owner.equals(testDelegation.programId));
+    assert.isTrue(accountInfo.data.equals(data));
+    assert.isNull(
+      await provider.connection.getAccountInfo(dlp.delegationRecordPda(account))
+    );
+  });
+
+  it("Whitelist validators for a program in a batch", async () => {
+    const others = [0, 1, 2].map(() => web3.Keypair.generate().publicKey);
+    await dlp.processInstructions(provider, [
+      dlp.whitelistValidatorsForProgramBatch(
+        admin,
+        testEscrow.programId,
+        others,
+        []
+      ),
+      dlp.whitelistValidatorsForProgramBatch(
+        admin,
+        testEscrow.programId,
+        [],
+        others
+      ),
+    ]);
+  });
+
+  it("Whitelist validators for a program in shards", async () => {
+    // A program of its own, as its migrated validators can only commit with their shard
+    const program = web3.Keypair.generate().publicKey;
+    const others = [0, 1]
+      .map(() => web3.Keypair.generate().publicKey)
+      .sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+    await dlp.processInstructions(provider, [
+      dlp.whitelistValidatorsForProgramBatch(admin, program, others, []),
+      dlp.migrateProgramConfigWhitelist(admin, program, others),
+      dlp.whitelistValidatorShardForProgram(admin, others[0], program, false),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(
+        dlp.validatorWhitelistShardPda(program, others[0])
+      )
+    );
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.validatorWhitelistShardPda(program, others[1])
+      )
+    );
+  });
+
+  it("Close the program config of a program without delegations", async () => {
+    const program = web3.Keypair.generate().publicKey;
+    await dlp.processInstructions(provider, [
+      dlp.setProgramMaxDelegationSlots(admin, program, 1_000),
+      dlp.closeProgramConfig(admin, program, true),
+    ]);
+    assert.isNull(
+      await provider.connection.getAccountInfo(dlp.programConfigPda(program))
+    );
+  });
+
+  it("Migrate a delegation record already in the current layout", async () => {
+    // Delegated by the wallet in test-delegation, with the current layout
+    const delegatedAccount = ON_CURVE_ACCOUNT.publicKey;
+    const before = await provider.connection.getAccountInfo(
+      dlp.delegationRecordPda(delegatedAccount)
+    );
+    await dlp.processInstructions(provider, [
+      dlp.migrateDelegationRecord(admin, delegatedAccount),
+    ]);
+    const after = await provider.connection.getAccountInfo(
+      dlp.delegationRecordPda(delegatedAccount)
+    );
+    assert.deepEqual(after.data, before.data);
+  });
+
+  it("Reject a commit from the owner of an on curve account", async () => {
+    // Delegated by the wallet in test-delegation, an on curve account has no owner program
+    await dlp.expectDlpError(
+      provider,
+      [
+        dlp.commitFromOwner(
+          admin,
+          ON_CURVE_ACCOUNT.publicKey,
+          new anchor.BN(1).shln(63).addn(1),
+          new Uint8Array()
+        ),
+      ],
+      dlp.DlpError.InvalidOwnerCommit,
+      [ON_CURVE_ACCOUNT]
+    );
+  });
+
+  it("Every instruction was exercised", () => {
+    const exercised = dlp.exercisedDiscriminators();
+    const missing = Object.keys(dlp.DlpDiscriminator)
+      .filter((key) => isNaN(Number(key)))
+      .filter((key) => !exercised.has(dlp.DlpDiscriminator[key]));
+    assert.deepEqual(missing, [], "instructions not exercised by the suite");
+  });
+});
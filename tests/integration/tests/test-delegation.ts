@@ -9,6 +9,7 @@ import {
   DELEGATION_PROGRAM_ID,
 } from "@magicblock-labs/ephemeral-rollups-sdk";
 import { ON_CURVE_ACCOUNT } from "./fixtures/consts";
+import { recordCoverage } from "./fixtures/dlp";
 import { assert } from "chai";
 
 const SEED_TEST_PDA = "test-pda";
@@ -328,6 +329,7 @@ describe("TestDelegation", () => {
     ).blockhash;
     tx.feePayer = provider.wallet.publicKey;
     const txId = await provider.sendAndConfirm(tx, [], { skipPreflight: true });
+    await recordCoverage(provider.connection, txId);
     return txId;
   }
 
@@ -0,0 +1,140 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, web3 } from "@coral-xyz/anchor";
+import {
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { TestEscrow } from "../target/types/test_escrow";
+import * as dlp from "./fixtures/dlp";
+
+/// Offset of the released flag in the escrow, after the discriminator, the maker, the
+/// taker, the mint and the amount
+const RELEASED_OFFSET = 8 + 32 * 3 + 8;
+const AMOUNT = 500;
+
+describe("TestEscrow", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const testEscrow = anchor.workspace.TestEscrow as Program<TestEscrow>;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+  const maker = provider.wallet.publicKey;
+  const validator = provider.wallet.publicKey;
+  const taker = web3.Keypair.generate();
+  const [escrow] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("escrow"), maker.toBuffer()],
+    testEscrow.programId
+  );
+  let mint: web3.PublicKey;
+  let vault: web3.PublicKey;
+  let takerTokenAccount: web3.PublicKey;
+
+  async function withdraw() {
+    return testEscrow.methods
+      .withdraw()
+      .accountsPartial({
+        taker: taker.publicKey,
+        escrow,
+        vault,
+        takerTokenAccount,
+      })
+      .signers([taker])
+      .rpc({ commitment: "confirmed" });
+  }
+
+  it("Initialize the escrow", async () => {
+    mint = await createMint(provider.connection, payer, maker, null, 0);
+    const makerTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      mint,
+      maker
+    );
+    await mintTo(
+      provider.connection,
+      payer,
+      mint,
+      makerTokenAccount.address,
+      maker,
+      AMOUNT
+    );
+    takerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        payer,
+        mint,
+        taker.publicKey
+      )
+    ).address;
+    vault = getAssociatedTokenAddressSync(mint, escrow, true);
+
+    await testEscrow.methods
+      .initialize(taker.publicKey, new anchor.BN(AMOUNT))
+      .accountsPartial({
+        maker,
+        mint,
+        escrow,
+        makerTokenAccount: makerTokenAccount.address,
+        vault,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    assert.strictEqual(
+      Number((await getAccount(provider.connection, vault)).amount),
+      AMOUNT
+    );
+  });
+
+  it("Delegate the escrow", async () => {
+    const signature = await testEscrow.methods
+      .delegate(validator)
+      .accounts({ payer: maker })
+      .rpc({ commitment: "confirmed" });
+    await dlp.recordCoverage(provider.connection, signature);
+
+    const account = await provider.connection.getAccountInfo(escrow);
+    assert.isTrue(account.owner.equals(dlp.DELEGATION_PROGRAM_ID));
+  });
+
+  it("Withdraw fails while the escrow is delegated", async () => {
+    let failed = false;
+    try {
+      await withdraw();
+    } catch (e) {
+      failed = true;
+    }
+    assert.isTrue(failed);
+  });
+
+  it("Commit the release as a diff and undelegate", async () => {
+    const account = await provider.connection.getAccountInfo(escrow);
+    const released = Buffer.from(account.data);
+    released[RELEASED_OFFSET] = 1;
+
+    await dlp.processInstructions(provider, [
+      dlp.commitDiff(validator, escrow, testEscrow.programId, {
+        nonce: 1,
+        lamports: account.lamports,
+        allowUndelegation: true,
+        diff: dlp.computeDiff(account.data, released),
+      }),
+      dlp.finalize(validator, escrow),
+      dlp.undelegate(validator, escrow, testEscrow.programId, maker),
+    ]);
+
+    const escrowAccount = await testEscrow.account.escrow.fetch(escrow);
+    assert.isTrue(escrowAccount.released);
+  });
+
+  it("Withdraw the released tokens", async () => {
+    await withdraw();
+    assert.strictEqual(
+      Number((await getAccount(provider.connection, takerTokenAccount)).amount),
+      AMOUNT
+    );
+  });
+});
@@ -0,0 +1,240 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, web3 } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { TestLargeAccount } from "../target/types/test_large_account";
+import * as dlp from "./fixtures/dlp";
+
+const LARGE_ACCOUNT_SIZE = 10_240;
+const CHUNK_SIZE = 900;
+
+describe("TestLargeAccount", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const testLargeAccount = anchor.workspace
+    .TestLargeAccount as Program<TestLargeAccount>;
+  const payer = provider.wallet.publicKey;
+  const validator = provider.wallet.publicKey;
+  const [largeAccount] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("large-account")],
+    testLargeAccount.programId
+  );
+
+  function chunks(data: Buffer) {
+    const result: [number, Buffer][] = [];
+    for (let offset = 0; offset < data.length; offset += CHUNK_SIZE) {
+      result.push([offset, data.subarray(offset, offset + CHUNK_SIZE)]);
+    }
+    return result;
+  }
+
+  async function largeAccountData() {
+    return (await provider.connection.getAccountInfo(largeAccount)).data;
+  }
+
+  /// Create a commit buffer owned by the program and fill it with the data
+  async function createCommitBuffer(data: Buffer) {
+    const buffer = web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new web3.Transaction().add(
+        web3.SystemProgram.createAccount({
+          fromPubkey: payer,
+          newAccountPubkey: buffer.publicKey,
+          lamports:
+            await provider.connection.getMinimumBalanceForRentExemption(
+              data.length
+            ),
+          space: data.length,
+          programId: testLargeAccount.programId,
+        })
+      ),
+      [buffer]
+    );
+    for (const [offset, chunk] of chunks(data)) {
+      await testLargeAccount.methods
+        .writeBuffer(offset, chunk)
+        .accounts({ authority: payer, buffer: buffer.publicKey })
+        .rpc({ commitment: "confirmed" });
+    }
+    return buffer.publicKey;
+  }
+
+  it("Initialize and write the large account", async () => {
+    await testLargeAccount.methods
+      .initialize()
+      .accounts({ payer })
+      .rpc({ commitment: "confirmed" });
+    await testLargeAccount.methods
+      .write(LARGE_ACCOUNT_SIZE - 8 - 4, Buffer.from([1, 2, 3, 4]))
+      .accounts({})
+      .rpc({ commitment: "confirmed" });
+
+    const data = await largeAccountData();
+    assert.strictEqual(data.length, LARGE_ACCOUNT_SIZE);
+    assert.deepEqual([...data.subarray(LARGE_ACCOUNT_SIZE - 4)], [1, 2, 3, 4]);
+  });
+
+  it("Delegate the large account from a staged buffer", async () => {
+    const data = await largeAccountData();
+
+    let signature = await testLargeAccount.methods
+      .stageDelegation()
+      .accountsPartial({
+        payer,
+        largeAccount,
+        stagedBuffer: dlp.stagedDelegateBufferPda(largeAccount),
+        delegationProgram: dlp.DELEGATION_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" });
+    await dlp.recordCoverage(provider.connection, signature);
+
+    for (const [offset, chunk] of chunks(data)) {
+      await dlp.processInstructions(provider, [
+        dlp.writeDelegateBufferChunk(payer, largeAccount, offset, chunk),
+      ]);
+    }
+
+    signature = await testLargeAccount.methods
+      .delegateStaged(validator)
+      .accountsPartial({
+        payer,
+        largeAccount,
+        ownerProgram: testLargeAccount.programId,
+        delegateBuffer: dlp.delegateBufferPda(
+          largeAccount,
+          testLargeAccount.programId
+        ),
+        delegationRecord: dlp.delegationRecordPda(largeAccount),
+        delegationMetadata: dlp.delegationMetadataPda(largeAccount),
+        stagedBuffer: dlp.stagedDelegateBufferPda(largeAccount),
+        delegationProgram: dlp.DELEGATION_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" });
+    await dlp.recordCoverage(provider.connection, signature);
+
+    const account = await provider.connection.getAccountInfo(largeAccount);
+    assert.isTrue(account.owner.equals(dlp.DELEGATION_PROGRAM_ID));
+    assert.isTrue(account.data.equals(data));
+    assert.isNull(
+      await provider.connection.getAccountInfo(
+        dlp.stagedDelegateBufferPda(largeAccount)
+      )
+    );
+  });
+
+  it("Commit the state from a buffer in a session and finalize", async () => {
+    const data = Buffer.from(await largeAccountData());
+    data.fill(9, 0, 4096);
+    const buffer = await createCommitBuffer(data);
+
+    await dlp.processInstructions(provider, [
+      dlp.commitSessionBegin(validator),
+      dlp.commitStateFromBuffer(
+        validator,
+        largeAccount,
+        testLargeAccount.programId,
+        buffer,
+        {
+          nonce: 1,
+          lamports: (await provider.connection.getAccountInfo(largeAccount))
+            .lamports,
+          allowUndelegation: false,
+        }
+      ),
+      dlp.commitSessionEnd(validator),
+      dlp.finalize(validator, largeAccount),
+    ]);
+    assert.isTrue((await largeAccountData()).equals(data));
+  });
+
+  it("Commit a diff from a buffer, finalize fails early for a crank", async () => {
+    const original = await largeAccountData();
+    const changed = Buffer.from(original);
+    changed.fill(5, 100, 200);
+    changed.fill(6, 8000, 8100);
+    const buffer = await createCommitBuffer(dlp.computeDiff(original, changed));
+
+    await dlp.processInstructions(provider, [
+      dlp.commitDiffFromBuffer(
+        validator,
+        largeAccount,
+        testLargeAccount.programId,
+        buffer,
+        {
+          nonce: 2,
+          lamports: (await provider.connection.getAccountInfo(largeAccount))
+            .lamports,
+          allowUndelegation: false,
+        }
+      ),
+    ]);
+
+    // The commit was just made, a crank cannot finalize it yet
+    await dlp.expectDlpError(
+      provider,
+      [dlp.crankFinalize(payer, validator, largeAccount)],
+      dlp.DlpError.CrankFinalizeTooEarly
+    );
+
+    await dlp.processInstructions(provider, [
+      dlp.finalize(validator, largeAccount),
+    ]);
+    assert.isTrue((await largeAccountData()).equals(changed));
+  });
+
+  it("Stream the state into a growing commit state and finalize", async () => {
+    // The streamed state is larger than the account, its size is only known at the end
+    const data = Buffer.concat([
+      await largeAccountData(),
+      Buffer.alloc(CHUNK_SIZE, 7),
+    ]);
+    for (const [, chunk] of chunks(data)) {
+      await dlp.processInstructions(provider, [
+        dlp.growCommitState(validator, largeAccount, chunk),
+      ]);
+    }
+
+    await dlp.processInstructions(provider, [
+      dlp.commitStateFromBuffer(
+        validator,
+        largeAccount,
+        testLargeAccount.programId,
+        dlp.commitStatePda(largeAccount),
+        {
+          nonce: 3,
+          lamports:
+            await provider.connection.getMinimumBalanceForRentExemption(
+              data.length
+            ),
+          allowUndelegation: false,
+        }
+      ),
+      dlp.finalize(validator, largeAccount),
+    ]);
+    assert.isTrue((await largeAccountData()).equals(data));
+  });
+
+  it("Undelegate the large account", async () => {
+    const data = await largeAccountData();
+    await dlp.processInstructions(provider, [
+      dlp.commitDiff(validator, largeAccount, testLargeAccount.programId, {
+        nonce: 4,
+        lamports: (await provider.connection.getAccountInfo(largeAccount))
+          .lamports,
+        allowUndelegation: true,
+        diff: dlp.computeDiff(data, data),
+      }),
+      dlp.finalize(validator, largeAccount),
+      dlp.undelegate(
+        validator,
+        largeAccount,
+        testLargeAccount.programId,
+        payer
+      ),
+    ]);
+
+    const account = await provider.connection.getAccountInfo(largeAccount);
+    assert.isTrue(account.owner.equals(testLargeAccount.programId));
+    assert.isTrue(account.data.equals(data));
+  });
+});
@@ -0,0 +1,503 @@
+import * as anchor from "@coral-xyz/anchor";
+import { web3 } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import * as dlp from "./fixtures/dlp";
+
+const NATIVE_PROGRAM_ID = new web3.PublicKey(
+  "6YbkuaEsNwWVWaaokQEPGL89KznJ7boGAeUkgfqV5oa"
+);
+
+/// Offset of the label in the counter, moved to its own account by the split
+const LABEL_OFFSET = 40;
+const COUNTER_SIZE = 72;
+
+enum NativeInstruction {
+  Initialize = 0,
+  Increment = 1,
+  Delegate = 2,
+  ApproveUndelegateAndClose = 3,
+  SplitLabel = 4,
+  EscrowTransfer = 5,
+  CorrectCount = 6,
+}
+
+describe("TestNative", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const authority = provider.wallet.publicKey;
+  const validator = provider.wallet.publicKey;
+  const label = Buffer.alloc(32, 7);
+  const [counter] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("native-counter"), authority.toBuffer()],
+    NATIVE_PROGRAM_ID
+  );
+  const [labelPda] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("native-label"), authority.toBuffer()],
+    NATIVE_PROGRAM_ID
+  );
+  const escrowAuthority = web3.Keypair.generate();
+  const closeDestination = web3.Keypair.generate().publicKey;
+
+  function nativeInstruction(
+    keys: web3.AccountMeta[],
+    instruction: NativeInstruction,
+    args?: (writer: dlp.BorshWriter) => void
+  ) {
+    const writer = new dlp.BorshWriter().u8(instruction);
+    if (args) {
+      args(writer);
+    }
+    return new web3.TransactionInstruction({
+      programId: NATIVE_PROGRAM_ID,
+      keys,
+      data: writer.toBuffer(),
+    });
+  }
+
+  function incrementInstruction() {
+    return nativeInstruction(
+      [{ pubkey: counter, isSigner: false, isWritable: true }],
+      NativeInstruction.Increment
+    );
+  }
+
+  async function counterData() {
+    return (await provider.connection.getAccountInfo(counter)).data;
+  }
+
+  function withCount(data: Buffer, count: number) {
+    const changed = Buffer.from(data);
+    changed.writeUInt32LE(count, 32);
+    return changed;
+  }
+
+  async function lamportsOf(pubkey: web3.PublicKey) {
+    return (await provider.connection.getAccountInfo(pubkey)).lamports;
+  }
+
+  it("Initialize and increment the counter", async () => {
+    await dlp.processInstructions(provider, [
+      nativeInstruction(
+        [
+          { pubkey: authority, isSigner: true, isWritable: true },
+          { pubkey: counter, isSigner: false, isWritable: true },
+          {
+            pubkey: web3.SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+        ],
+        NativeInstruction.Initialize,
+        (writer) => label.forEach((byte) => writer.u8(byte))
+      ),
+      incrementInstruction(),
+    ]);
+
+    const data = await counterData();
+    assert.strictEqual(data.length, COUNTER_SIZE);
+    assert.isTrue(data.subarray(0, 32).equals(authority.toBuffer()));
+    assert.strictEqual(data.readUInt32LE(32), 1);
+  });
+
+  it("Delegate the counter without Anchor", async () => {
+    await dlp.processInstructions(provider, [
+      nativeInstruction(
+        [
+          { pubkey: authority, isSigner: true, isWritable: true },
+          { pubkey: counter, isSigner: false, isWritable: true },
+          { pubkey: NATIVE_PROGRAM_ID, isSigner: false, isWritable: false },
+          {
+            pubkey: dlp.delegateBufferPda(counter, NATIVE_PROGRAM_ID),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: dlp.delegationRecordPda(counter),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: dlp.delegationMetadataPda(counter),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: dlp.DELEGATION_PROGRAM_ID,
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: web3.SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+        ],
+        NativeInstruction.Delegate,
+        (writer) => writer.option(validator, (key) => writer.pubkey(key))
+      ),
+    ]);
+
+    const account = await provider.connection.getAccountInfo(counter);
+    assert.isTrue(account.owner.equals(dlp.DELEGATION_PROGRAM_ID));
+    assert.strictEqual(account.data.readUInt32LE(32), 1);
+  });
+
+  it("Increment fails while delegated", async () => {
+    await dlp.expectFailure(provider, [incrementInstruction()]);
+  });
+
+  it("Commit the full state and finalize", async () => {
+    const data = withCount(await counterData(), 2);
+    await dlp.processInstructions(provider, [
+      dlp.commitState(validator, counter, NATIVE_PROGRAM_ID, {
+        nonce: 1,
+        lamports: await lamportsOf(counter),
+        allowUndelegation: false,
+        data,
+      }),
+      dlp.finalizeSettled(validator, counter),
+    ]);
+    assert.isTrue((await counterData()).equals(data));
+  });
+
+  it("Commit a diff in shadow mode and finalize locking the reads", async () => {
+    const original = await counterData();
+    const changed = withCount(original, 3);
+    await dlp.processInstructions(provider, [
+      dlp.initReadLock(validator, counter),
+      dlp.commitDiffShadow(validator, counter, NATIVE_PROGRAM_ID, {
+        nonce: 2,
+        lamports: await lamportsOf(counter),
+        allowUndelegation: false,
+        diff: dlp.computeDiff(original, changed),
+        expectedState: changed,
+      }),
+      dlp.finalizeWithReadLock(validator, counter),
+    ]);
+    assert.isTrue((await counterData()).equals(changed));
+
+    // The read lock holds the slot and the nonce of the finalize
+    const readLock = await provider.connection.getAccountInfo(
+      dlp.readLockPda(counter)
+    );
+    assert.isAbove(readLock.data.readUInt32LE(8), 0);
+    assert.strictEqual(readLock.data.readUInt32LE(16), 2);
+  });
+
+  it("Commit and finalize in one instruction", async () => {
+    const data = withCount(await counterData(), 4);
+    await dlp.processInstructions(provider, [
+      dlp.commitFinalize(validator, counter, NATIVE_PROGRAM_ID, {
+        nonce: 3,
+        lamports: await lamportsOf(counter),
+        allowUndelegation: false,
+        data,
+      }),
+    ]);
+    assert.isTrue((await counterData()).equals(data));
+  });
+
+  it("Push a corrected count from the owner program", async () => {
+    // The owner commit takes the next nonce, flagged as pushed by the owner
+    const nonce = new anchor.BN(1).shln(63).addn(4);
+    const data = withCount(await counterData(), 10);
+    await dlp.processInstructions(provider, [
+      nativeInstruction(
+        [
+          { pubkey: authority, isSigner: true, isWritable: true },
+          { pubkey: counter, isSigner: false, isWritable: false },
+          {
+            pubkey: dlp.commitStatePda(counter),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: dlp.commitRecordPda(counter),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: dlp.delegationRecordPda(counter),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: dlp.delegationMetadataPda(counter),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: web3.SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: dlp.DELEGATION_PROGRAM_ID,
+            isSigner: false,
+            isWritable: false,
+          },
+        ],
+        NativeInstruction.CorrectCount,
+        (writer) => writer.u64(10).u64(nonce)
+      ),
+      dlp.finalizeOwnerCommit(validator, counter, authority),
+    ]);
+    assert.isTrue((await counterData()).equals(data));
+
+    // A commit of the validator which did not fold the correction in is rejected
+    await dlp.expectDlpError(
+      provider,
+      [
+        dlp.commitState(validator, counter, NATIVE_PROGRAM_ID, {
+          nonce: 4,
+          lamports: await lamportsOf(counter),
+          allowUndelegation: false,
+          data: withCount(data, 5),
+        }),
+      ],
+      dlp.DlpError.NonceOutOfOrder
+    );
+  });
+
+  it("Split the label into its own delegated account", async () => {
+    await dlp.processInstructions(provider, [
+      nativeInstruction(
+        [
+          { pubkey: validator, isSigner: true, isWritable: true },
+          { pubkey: counter, isSigner: false, isWritable: true },
+          {
+            pubkey: dlp.delegationRecordPda(counter),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: dlp.delegationMetadataPda(counter),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: dlp.commitStatePda(counter),
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: dlp.commitRecordPda(counter),
+            isSigner: false,
+            isWritable: false,
+          },
+          { pubkey: labelPda, isSigner: false, isWritable: true },
+          {
+            pubkey: dlp.delegationRecordPda(labelPda),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: dlp.delegationMetadataPda(labelPda),
+            isSigner: false,
+            isWritable: true,
+          },
+          { pubkey: NATIVE_PROGRAM_ID, isSigner: false, isWritable: false },
+          {
+            pubkey: web3.SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+          { pubkey: dlp.FEATURE_GATES_PDA, isSigner: false, isWritable: false },
+          {
+            pubkey: dlp.DELEGATION_PROGRAM_ID,
+            isSigner: false,
+            isWritable: false,
+          },
+        ],
+        NativeInstruction.SplitLabel,
+        (writer) => writer.pubkey(authority)
+      ),
+    ]);
+
+    assert.strictEqual((await counterData()).length, LABEL_OFFSET);
+    const labelAccount = await provider.connection.getAccountInfo(labelPda);
+    assert.isTrue(labelAccount.owner.equals(dlp.DELEGATION_PROGRAM_ID));
+    assert.isTrue(labelAccount.data.equals(label));
+  });
+
+  it("Call an action handler with an escrow", async () => {
+    const destination = web3.Keypair.generate().publicKey;
+    const amount = web3.LAMPORTS_PER_SOL / 10;
+    await dlp.processInstructions(provider, [
+      dlp.topUpEphemeralBalance(
+        authority,
+        escrowAuthority.publicKey,
+        web3.LAMPORTS_PER_SOL,
+        0
+      ),
+      dlp.callHandler(
+        validator,
+        NATIVE_PROGRAM_ID,
+        escrowAuthority.publicKey,
+        [
+          { pubkey: destination, isSigner: false, isWritable: true },
+          {
+            pubkey: web3.SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+        ],
+        {
+          escrowIndex: 0,
+          data: new dlp.BorshWriter()
+            .u8(NativeInstruction.EscrowTransfer)
+            .u64(amount)
+            .toBuffer(),
+        }
+      ),
+    ]);
+    assert.strictEqual(await lamportsOf(destination), amount);
+  });
+
+  it("Top up, close and delegate ephemeral balances", async () => {
+    const payer = escrowAuthority.publicKey;
+    const delegateArgs = {
+      commitFrequencyMs: 0xffffffff,
+      seeds: [],
+      validator,
+    };
+    await dlp.processInstructions(
+      provider,
+      [
+        dlp.topUpEphemeralBalance(authority, payer, 100_000_000, 1),
+        dlp.closeEphemeralBalance(payer, 1),
+        dlp.topUpEphemeralBalance(authority, payer, 100_000_000, 2),
+        dlp.delegateEphemeralBalance(authority, payer, 2, delegateArgs),
+      ],
+      [escrowAuthority]
+    );
+    assert.isNull(
+      await provider.connection.getAccountInfo(dlp.ephemeralBalancePda(payer, 1))
+    );
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.delegationRecordPda(dlp.ephemeralBalancePda(payer, 2))
+      )
+    );
+
+    await dlp.processInstructions(
+      provider,
+      [
+        dlp.topUpProgramEphemeralBalance(
+          authority,
+          payer,
+          NATIVE_PROGRAM_ID,
+          100_000_000,
+          0
+        ),
+        dlp.closeProgramEphemeralBalance(payer, NATIVE_PROGRAM_ID, 0),
+        dlp.topUpProgramEphemeralBalance(
+          authority,
+          payer,
+          NATIVE_PROGRAM_ID,
+          100_000_000,
+          1
+        ),
+        dlp.delegateProgramEphemeralBalance(
+          authority,
+          payer,
+          NATIVE_PROGRAM_ID,
+          1,
+          delegateArgs
+        ),
+      ],
+      [escrowAuthority]
+    );
+    const programBalance = dlp.programEphemeralBalancePda(
+      payer,
+      NATIVE_PROGRAM_ID,
+      1
+    );
+    assert.isNotNull(
+      await provider.connection.getAccountInfo(
+        dlp.delegationRecordPda(programBalance)
+      )
+    );
+  });
+
+  it("Close a dust escrow at undelegation", async () => {
+    const escrow = dlp.ephemeralBalancePda(escrowAuthority.publicKey, 2);
+    const lamports = await lamportsOf(escrow);
+    await dlp.processInstructions(provider, [
+      dlp.setProtocolConfig(authority, lamports + 1),
+      dlp.commitState(validator, escrow, web3.SystemProgram.programId, {
+        nonce: 1,
+        lamports,
+        allowUndelegation: true,
+        data: new Uint8Array(),
+      }),
+      dlp.finalize(validator, escrow),
+      dlp.undelegate(validator, escrow, web3.SystemProgram.programId, authority),
+      dlp.setProtocolConfig(authority, 0),
+    ]);
+    assert.isNull(await provider.connection.getAccountInfo(escrow));
+  });
+
+  it("Undelegate the label back to the native program", async () => {
+    const data = (await provider.connection.getAccountInfo(labelPda)).data;
+    await dlp.processInstructions(provider, [
+      dlp.requestUndelegation(authority, labelPda),
+      dlp.commitState(validator, labelPda, NATIVE_PROGRAM_ID, {
+        nonce: 1,
+        lamports: await lamportsOf(labelPda),
+        allowUndelegation: true,
+        data,
+      }),
+      dlp.finalize(validator, labelPda),
+      dlp.undelegate(validator, labelPda, NATIVE_PROGRAM_ID, authority),
+    ]);
+
+    const labelAccount = await provider.connection.getAccountInfo(labelPda);
+    assert.isTrue(labelAccount.owner.equals(NATIVE_PROGRAM_ID));
+    assert.isTrue(labelAccount.data.equals(label));
+  });
+
+  it("Undelegate and close the counter to an approved destination", async () => {
+    await dlp.processInstructions(provider, [
+      nativeInstruction(
+        [
+          { pubkey: authority, isSigner: true, isWritable: true },
+          { pubkey: counter, isSigner: false, isWritable: false },
+          {
+            pubkey: dlp.delegationMetadataPda(counter),
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: dlp.DELEGATION_PROGRAM_ID,
+            isSigner: false,
+            isWritable: false,
+          },
+          {
+            pubkey: web3.SystemProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+        ],
+        NativeInstruction.ApproveUndelegateAndClose,
+        (writer) => writer.pubkey(closeDestination)
+      ),
+    ]);
+
+    const lamports = await lamportsOf(counter);
+    await dlp.processInstructions(provider, [
+      dlp.commitState(validator, counter, NATIVE_PROGRAM_ID, {
+        nonce: 5,
+        lamports,
+        allowUndelegation: true,
+        data: await counterData(),
+      }),
+      dlp.finalize(validator, counter),
+      dlp.undelegateAndClose(validator, counter, closeDestination, authority),
+    ]);
+
+    assert.isNull(await provider.connection.getAccountInfo(counter));
+    assert.strictEqual(await lamportsOf(closeDestination), lamports);
+  });
+});
@@ -0,0 +1,194 @@
+use borsh::BorshDeserialize;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    ephemeral_balance_pda_from_payer,
+};
+use dlp::state::{DelegationSummary, EscrowSummary, PendingCommitSummary};
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::fixtures::{
+    get_commit_record_account_data, get_delegation_metadata_data, get_delegation_record_data,
+    COMMIT_NEW_STATE_ACCOUNT_DATA, DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_get_delegation_summaries() {
+    // Setup
+    let (banks, payer, validator, blockhash) = setup_program_test_env().await;
+    let undelegated_account = Pubkey::new_unique();
+
+    let ix = dlp::instruction_builder::get_delegation_summaries(&[
+        (DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID),
+        (undelegated_account, DELEGATED_PDA_OWNER_ID),
+    ]);
+    let summaries: Vec<DelegationSummary> = simulate(&banks, &payer, ix, blockhash).await.unwrap();
+    assert_eq!(summaries.len(), 2);
+    assert!(summaries[0].is_delegated);
+    assert_eq!(summaries[0].account, DELEGATED_PDA_ID);
+    assert_eq!(summaries[0].validator, validator);
+    assert_eq!(summaries[0].owner, DELEGATED_PDA_OWNER_ID);
+    assert_eq!(summaries[0].expiry_slot, None);
+    assert_eq!(
+        summaries[1],
+        DelegationSummary {
+            account: undelegated_account,
+            ..Default::default()
+        }
+    );
+
+    // The program config must be the one of the owner program of a delegated account
+    let ix = dlp::instruction_builder::get_delegation_summaries(&[(
+        DELEGATED_PDA_ID,
+        Pubkey::new_unique(),
+    )]);
+    assert!(simulate::<DelegationSummary>(&banks, &payer, ix, blockhash)
+        .await
+        .is_none());
+
+    // The summaries must fit in the return data
+    let ix = dlp::instruction_builder::get_delegation_summaries(
+        &[(DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID); 8],
+    );
+    assert!(simulate::<DelegationSummary>(&banks, &payer, ix, blockhash)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_get_escrow_summaries() {
+    // Setup
+    let (banks, payer, _, blockhash) = setup_program_test_env().await;
+
+    let ix =
+        dlp::instruction_builder::get_escrow_summaries(&[(payer.pubkey(), 0), (payer.pubkey(), 1)]);
+    let summaries: Vec<EscrowSummary> = simulate(&banks, &payer, ix, blockhash).await.unwrap();
+    assert_eq!(
+        summaries,
+        vec![
+            EscrowSummary {
+                payer: payer.pubkey(),
+                index: 0,
+                lamports: LAMPORTS_PER_SOL,
+                ..Default::default()
+            },
+            EscrowSummary {
+                payer: payer.pubkey(),
+                index: 1,
+                ..Default::default()
+            },
+        ]
+    );
+
+    // An index is required for each escrow
+    let mut ix = dlp::instruction_builder::get_escrow_summaries(&[(payer.pubkey(), 0)]);
+    ix.accounts.extend(ix.accounts.clone());
+    assert!(simulate::<EscrowSummary>(&banks, &payer, ix, blockhash)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_get_pending_commit_summaries() {
+    // Setup
+    let (banks, payer, validator, blockhash) = setup_program_test_env().await;
+    let undelegated_account = Pubkey::new_unique();
+
+    let ix = dlp::instruction_builder::get_pending_commit_summaries(&[
+        DELEGATED_PDA_ID,
+        undelegated_account,
+    ]);
+    let summaries: Vec<PendingCommitSummary> =
+        simulate(&banks, &payer, ix, blockhash).await.unwrap();
+    assert!(summaries[0].has_pending_commit);
+    assert_eq!(summaries[0].validator, validator);
+    assert_eq!(
+        summaries[0].data_len,
+        COMMIT_NEW_STATE_ACCOUNT_DATA.len() as u64
+    );
+    assert!(!summaries[1].has_pending_commit);
+}
+
+/// Simulate the summary instruction, returning the summaries or None if it failed
+async fn simulate<T: BorshDeserialize>(
+    banks: &BanksClient,
+    payer: &Keypair,
+    ix: Instruction,
+    blockhash: Hash,
+) -> Option<Vec<T>> {
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    let res = banks.simulate_transaction(tx).await.unwrap();
+    res.result.unwrap().ok()?;
+    let return_data = res.simulation_details.unwrap().return_data.unwrap();
+    Some(Vec::<T>::try_from_slice(&return_data.data).unwrap())
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Pubkey, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap().pubkey();
+
+    // Setup a delegated account with a pending commit
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    for (pubkey, data) in [
+        (
+            delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            get_delegation_record_data(validator, None),
+        ),
+        (
+            delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            get_delegation_metadata_data(validator, None),
+        ),
+        (
+            commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            COMMIT_NEW_STATE_ACCOUNT_DATA.to_vec(),
+        ),
+        (
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            get_commit_record_account_data(validator),
+        ),
+    ] {
+        program_test.add_account(
+            pubkey,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let (banks, payer, blockhash) = program_test.start().await;
+
+    // Fund the escrow of the payer
+    let ix = solana_program::system_instruction::transfer(
+        &payer.pubkey(),
+        &ephemeral_balance_pda_from_payer(&payer.pubkey(), 0),
+        LAMPORTS_PER_SOL,
+    );
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+    banks.process_transaction(tx).await.unwrap();
+    (banks, payer, validator, blockhash)
+}
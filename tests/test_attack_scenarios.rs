@@ -0,0 +1,1179 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use dlp::args::{CommitStateArgs, DelegateArgs, Encoding};
+use dlp::consts::OWNER_COMMIT_NONCE_FLAG;
+use dlp::discriminator::DlpDiscriminator;
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    ephemeral_balance_pda_from_payer, fees_vault_pda, fees_vault_pda_from_generation,
+    program_config_from_program_id, undelegate_buffer_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{
+    CommitRecord, CommitStateLayout, DelegationRecord, EarningsLedgerPage, PendingState,
+    ProtocolStats, RETIRED_FEES_VAULT_GENERATION,
+};
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{read_file, BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    create_program_config_data, get_commit_record_account_data, get_delegation_metadata_data,
+    get_delegation_record_data, get_legacy_delegation_record_data, COMMIT_NEW_STATE_ACCOUNT_DATA,
+    DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+/// The classes of attacks the instructions are reviewed against
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AttackClass {
+    /// An account is substituted by another account of the instruction or an unrelated one
+    WrongPda,
+    /// A PDA the instruction creates is funded beforehand, to make its creation fail
+    PrefundedPda,
+    /// An instruction meant to be invoked by the owner program is invoked directly
+    CpiOriginSpoofing,
+    /// A commit is submitted again with the nonce of a finalized commit
+    NonceReplay,
+    /// A fees vault is substituted by another vault
+    FeeVaultSubstitution,
+    /// The undelegate buffer is initialized with tampered data before the undelegation
+    UndelegateBufferTampering,
+    /// A delegated account, owned by the delegation program and holding the data committed
+    /// by its validator, is passed in the role of a PDA of the delegation program
+    DelegatedAccountRoleConfusion,
+}
+
+type ScenarioFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// An attack scenario against an instruction, failing if the attack succeeds
+struct Scenario {
+    instruction: DlpDiscriminator,
+    class: AttackClass,
+    run: fn() -> ScenarioFuture,
+}
+
+/// The attack scenarios, run by [test_attack_scenarios].
+/// New instructions must add their scenarios here, or to [WITHOUT_SCENARIO].
+const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        instruction: DlpDiscriminator::Delegate,
+        class: AttackClass::CpiOriginSpoofing,
+        run: || Box::pin(delegate_without_owner_program_cpi()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitState,
+        class: AttackClass::WrongPda,
+        run: || Box::pin(commit_state_wrong_pda()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitState,
+        class: AttackClass::PrefundedPda,
+        run: || Box::pin(commit_state_prefunded_pdas()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitState,
+        class: AttackClass::NonceReplay,
+        run: || Box::pin(commit_state_nonce_replay()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitState,
+        class: AttackClass::DelegatedAccountRoleConfusion,
+        run: || Box::pin(commit_state_delegated_account_role_confusion()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::Finalize,
+        class: AttackClass::WrongPda,
+        run: || Box::pin(finalize_wrong_pda()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::Finalize,
+        class: AttackClass::DelegatedAccountRoleConfusion,
+        run: || Box::pin(finalize_delegated_account_role_confusion()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::Undelegate,
+        class: AttackClass::WrongPda,
+        run: || Box::pin(undelegate_wrong_pda()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::Undelegate,
+        class: AttackClass::FeeVaultSubstitution,
+        run: || Box::pin(undelegate_fee_vault_substitution()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::Undelegate,
+        class: AttackClass::PrefundedPda,
+        run: || Box::pin(undelegate_prefunded_buffer()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::Undelegate,
+        class: AttackClass::UndelegateBufferTampering,
+        run: || Box::pin(undelegate_tampered_buffer()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::Undelegate,
+        class: AttackClass::DelegatedAccountRoleConfusion,
+        run: || Box::pin(undelegate_delegated_account_role_confusion()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitFinalize,
+        class: AttackClass::WrongPda,
+        run: || Box::pin(commit_finalize_wrong_pda()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitFinalize,
+        class: AttackClass::NonceReplay,
+        run: || Box::pin(commit_finalize_nonce_replay()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::MigrateDelegationRecord,
+        class: AttackClass::WrongPda,
+        run: || Box::pin(migrate_delegation_record_wrong_pda()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::FundEscrowFromDelegated,
+        class: AttackClass::WrongPda,
+        run: || Box::pin(fund_escrow_from_delegated_wrong_pda()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::FundEscrowFromDelegated,
+        class: AttackClass::PrefundedPda,
+        run: || Box::pin(fund_escrow_from_delegated_prefunded_escrow()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitFromOwner,
+        class: AttackClass::CpiOriginSpoofing,
+        run: || Box::pin(commit_from_owner_without_owner_program_cpi()),
+    },
+    Scenario {
+        instruction: DlpDiscriminator::CommitFromOwner,
+        class: AttackClass::NonceReplay,
+        run: || Box::pin(commit_from_owner_nonce_replay()),
+    },
+];
+
+const ADMIN_ONLY: &str = "gated by the upgrade authority of the delegation program";
+const PROGRAM_AUTHORITY_ONLY: &str = "gated by the upgrade authority of the owner program";
+const READ_ONLY: &str = "does not write any account";
+const NOT_COVERED: &str = "no scenario yet";
+
+/// The instructions without an attack scenario, with the reason why
+const WITHOUT_SCENARIO: &[(DlpDiscriminator, &str)] = &[
+    (DlpDiscriminator::InitProtocolFeesVault, NOT_COVERED),
+    (DlpDiscriminator::InitValidatorFeesVault, ADMIN_ONLY),
+    (DlpDiscriminator::ValidatorClaimFees, NOT_COVERED),
+    (
+        DlpDiscriminator::WhitelistValidatorForProgram,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (DlpDiscriminator::TopUpEphemeralBalance, NOT_COVERED),
+    (DlpDiscriminator::DelegateEphemeralBalance, NOT_COVERED),
+    (DlpDiscriminator::CloseEphemeralBalance, NOT_COVERED),
+    (DlpDiscriminator::ProtocolClaimFees, ADMIN_ONLY),
+    (DlpDiscriminator::CommitStateFromBuffer, NOT_COVERED),
+    (DlpDiscriminator::CloseValidatorFeesVault, ADMIN_ONLY),
+    (DlpDiscriminator::CallHandler, NOT_COVERED),
+    (DlpDiscriminator::CommitDiff, NOT_COVERED),
+    (DlpDiscriminator::CommitDiffFromBuffer, NOT_COVERED),
+    (DlpDiscriminator::SplitDelegation, NOT_COVERED),
+    (DlpDiscriminator::TopUpProgramEphemeralBalance, NOT_COVERED),
+    (
+        DlpDiscriminator::DelegateProgramEphemeralBalance,
+        NOT_COVERED,
+    ),
+    (DlpDiscriminator::CloseProgramEphemeralBalance, NOT_COVERED),
+    (DlpDiscriminator::SetFeatureGate, ADMIN_ONLY),
+    (
+        DlpDiscriminator::SetProgramAllowedDataLens,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (DlpDiscriminator::CrankFinalize, NOT_COVERED),
+    (DlpDiscriminator::InitDelegateBuffer, NOT_COVERED),
+    (DlpDiscriminator::WriteDelegateBufferChunk, NOT_COVERED),
+    (DlpDiscriminator::SetValidatorInfo, NOT_COVERED),
+    (DlpDiscriminator::ApproveUndelegateAndClose, NOT_COVERED),
+    (DlpDiscriminator::UndelegateAndClose, NOT_COVERED),
+    (DlpDiscriminator::CommitSessionBegin, NOT_COVERED),
+    (DlpDiscriminator::CommitSessionEnd, NOT_COVERED),
+    (DlpDiscriminator::GrowCommitState, NOT_COVERED),
+    (DlpDiscriminator::SetProtocolConfig, ADMIN_ONLY),
+    (DlpDiscriminator::BootstrapProtocol, ADMIN_ONLY),
+    (DlpDiscriminator::InitReadLock, NOT_COVERED),
+    (
+        DlpDiscriminator::WhitelistValidatorsForProgramBatch,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (DlpDiscriminator::RequestUndelegation, NOT_COVERED),
+    (DlpDiscriminator::CommitDiffShadow, NOT_COVERED),
+    (DlpDiscriminator::SetVersion, ADMIN_ONLY),
+    (DlpDiscriminator::GetVersion, READ_ONLY),
+    (DlpDiscriminator::GrantFeeExemption, ADMIN_ONLY),
+    (DlpDiscriminator::ValidateDelegation, NOT_COVERED),
+    (
+        DlpDiscriminator::SetProgramUndelegateLamportsTolerance,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (DlpDiscriminator::SetCommitSchedule, NOT_COVERED),
+    (DlpDiscriminator::CloseCommitSchedule, NOT_COVERED),
+    (
+        DlpDiscriminator::SetProgramMaxDelegationSlots,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (DlpDiscriminator::InitEarningsLedgerPage, NOT_COVERED),
+    (DlpDiscriminator::ScheduleForceUndelegate, ADMIN_ONLY),
+    (DlpDiscriminator::ExecuteForceUndelegate, ADMIN_ONLY),
+    (
+        DlpDiscriminator::SetProgramValidateDelegations,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (
+        DlpDiscriminator::SetProgramUndelegateDiscriminatorOverride,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (DlpDiscriminator::ValidatorHeartbeat, NOT_COVERED),
+    (DlpDiscriminator::ResyncProtocolStats, ADMIN_ONLY),
+    (DlpDiscriminator::SetCallHandlerPermissions, NOT_COVERED),
+    (DlpDiscriminator::TopUpEphemeralBalanceBatch, NOT_COVERED),
+    (DlpDiscriminator::RegisterCommitRelayer, NOT_COVERED),
+    (DlpDiscriminator::SetValidatorCommitQuota, ADMIN_ONLY),
+    (DlpDiscriminator::RedelegateEphemeralBalance, NOT_COVERED),
+    (DlpDiscriminator::IsValidatorWhitelisted, READ_ONLY),
+    (DlpDiscriminator::ExportDelegationPackage, ADMIN_ONLY),
+    (DlpDiscriminator::ImportDelegationPackage, ADMIN_ONLY),
+    (DlpDiscriminator::ProposeProtocolVaultMigration, ADMIN_ONLY),
+    (DlpDiscriminator::ExecuteProtocolVaultMigration, ADMIN_ONLY),
+    (DlpDiscriminator::GetDelegationSummaries, READ_ONLY),
+    (DlpDiscriminator::GetEscrowSummaries, READ_ONLY),
+    (DlpDiscriminator::GetPendingCommitSummaries, READ_ONLY),
+    (
+        DlpDiscriminator::WhitelistValidatorShardForProgram,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (
+        DlpDiscriminator::MigrateProgramConfigWhitelist,
+        PROGRAM_AUTHORITY_ONLY,
+    ),
+    (DlpDiscriminator::UndelegateStage1, NOT_COVERED),
+    (DlpDiscriminator::UndelegateStage2, NOT_COVERED),
+    (DlpDiscriminator::CloseProgramConfig, PROGRAM_AUTHORITY_ONLY),
+    (DlpDiscriminator::SetAuthorityGrant, NOT_COVERED),
+    (DlpDiscriminator::InitCommitBuffer, NOT_COVERED),
+    (DlpDiscriminator::WriteCommitBuffer, NOT_COVERED),
+    (DlpDiscriminator::CloseCommitBuffer, NOT_COVERED),
+    (DlpDiscriminator::ClaimParkedUndelegation, NOT_COVERED),
+    (DlpDiscriminator::InitSessionReport, NOT_COVERED),
+    (DlpDiscriminator::CloseSessionReport, NOT_COVERED),
+    (DlpDiscriminator::ForceUndelegate, NOT_COVERED),
+    (DlpDiscriminator::DelegateStakeAccount, NOT_COVERED),
+    (DlpDiscriminator::UndelegateStakeAccount, NOT_COVERED),
+    (DlpDiscriminator::AddDelegationAuthority, NOT_COVERED),
+    (DlpDiscriminator::RemoveDelegationAuthority, NOT_COVERED),
+    (DlpDiscriminator::UpdateDelegationAuthority, NOT_COVERED),
+    (DlpDiscriminator::CommitStateRoot, NOT_COVERED),
+    (DlpDiscriminator::CommitStateChunk, NOT_COVERED),
+    (DlpDiscriminator::CloseStreamedCommitState, NOT_COVERED),
+];
+
+#[tokio::test]
+async fn test_attack_scenarios() {
+    for scenario in SCENARIOS {
+        println!(
+            "Attack scenario: {:?} against {}",
+            scenario.class,
+            scenario.instruction.name()
+        );
+        (scenario.run)().await;
+    }
+}
+
+#[test]
+fn test_attack_scenarios_checklist() {
+    // Every instruction has a scenario, or is listed without one
+    for discriminator in (0..=u8::MAX).filter_map(|tag| DlpDiscriminator::try_from(tag).ok()) {
+        let has_scenario = SCENARIOS
+            .iter()
+            .any(|scenario| scenario.instruction == discriminator);
+        let without_scenario = WITHOUT_SCENARIO
+            .iter()
+            .any(|(instruction, _)| *instruction == discriminator);
+        assert!(
+            has_scenario != without_scenario,
+            "{} must either have attack scenarios or be listed without one",
+            discriminator.name()
+        );
+    }
+}
+
+/// The delegated account is signed for by the owner program in its delegate CPI, so that a
+/// direct invocation cannot delegate an account of another program
+async fn delegate_without_owner_program_cpi() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    // The account assigned to the delegation program, as the owner program does before the CPI
+    add_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![0; 10],
+        dlp::id(),
+    );
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let mut ix = dlp::instruction_builder::delegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        Some(DELEGATED_PDA_OWNER_ID),
+        DelegateArgs::default(),
+    );
+    ix.accounts[1].is_signer = false;
+    let res = process(&banks, &validator, blockhash, &[ix]).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+async fn commit_state_wrong_pda() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix = commit_state(&validator, 1, vec![1; 10]);
+    assert_substitutions_fail(&banks, &validator, blockhash, &ix, ix.accounts.len()).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+}
+
+/// Funding the commit PDAs before the commit does not prevent their creation
+async fn commit_state_prefunded_pdas() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    for pda in [
+        commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+    ] {
+        add_account(&mut program_test, pda, 1_000, vec![], system_program::id());
+    }
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix = commit_state(&validator, 1, vec![1; 10]);
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+    let commit_state_account = banks
+        .get_account(commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(commit_state_account.owner, dlp::id());
+    assert_eq!(
+        PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+            .unwrap(),
+        vec![1; 10]
+    );
+}
+
+/// A finalized commit cannot be submitted again, even with another state
+async fn commit_state_nonce_replay() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ixs = [
+        commit_state(&validator, 1, vec![1; 10]),
+        dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID),
+    ];
+    process(&banks, &validator, blockhash, &ixs).await.unwrap();
+
+    let ix = commit_state(&validator, 1, vec![2; 10]);
+    let res = process(&banks, &validator, blockhash, &[ix]).await;
+    assert_dlp_error(res, DlpError::NonceOutOfOrder);
+}
+
+/// The validator fees vault, the program config and the commit PDAs are checked against
+/// their PDA, which a delegated account holding their data can not stand in for
+async fn commit_state_delegated_account_role_confusion() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let ix = commit_state(&validator, 1, vec![1; 10]);
+    let mimics = [
+        (
+            validator_fees_vault_pda_from_validator(&validator.pubkey()),
+            vec![],
+        ),
+        (
+            program_config_from_program_id(&DELEGATED_PDA_OWNER_ID),
+            create_program_config_data(validator.pubkey()),
+        ),
+        (
+            commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            vec![],
+        ),
+        (
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            vec![],
+        ),
+    ]
+    .map(|(role, data)| {
+        (
+            account_index(&ix, &role),
+            add_delegated_mimic(&mut program_test, data),
+        )
+    });
+    let (banks, _, blockhash) = program_test.start().await;
+
+    assert_role_confusions_fail(&banks, &validator, blockhash, &ix, &mimics).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+}
+
+async fn finalize_wrong_pda() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_committed_delegation(&mut program_test, &validator);
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    assert_substitutions_fail(&banks, &validator, blockhash, &ix, ix.accounts.len()).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+}
+
+/// The commit PDAs are checked against their PDA before their data is read
+async fn finalize_delegated_account_role_confusion() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_committed_delegation(&mut program_test, &validator);
+    let ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    let mimics = [
+        (
+            commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            vec![2; COMMIT_NEW_STATE_ACCOUNT_DATA.len()],
+        ),
+        (
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            get_commit_record_account_data(validator.pubkey()),
+        ),
+        (
+            validator_fees_vault_pda_from_validator(&validator.pubkey()),
+            vec![],
+        ),
+    ]
+    .map(|(role, data)| {
+        (
+            account_index(&ix, &role),
+            add_delegated_mimic(&mut program_test, data),
+        )
+    });
+    let (banks, _, blockhash) = program_test.start().await;
+
+    assert_role_confusions_fail(&banks, &validator, blockhash, &ix, &mimics).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+}
+
+async fn undelegate_wrong_pda() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_committed_delegation(&mut program_test, &validator);
+    let (banks, _, blockhash) = program_test.start().await;
+    finalize(&banks, &validator, blockhash).await;
+
+    // The trailing optional accounts are only read when they apply to the delegation
+    let ix = undelegate(&validator);
+    assert_substitutions_fail(&banks, &validator, blockhash, &ix, 12).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+}
+
+/// The fees are paid to the live protocol fees vault and the vault of the validator only
+async fn undelegate_fee_vault_substitution() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_committed_delegation(&mut program_test, &validator);
+
+    // The protocol fees were migrated to the vault of generation 1
+    add_account(
+        &mut program_test,
+        fees_vault_pda(),
+        Rent::default().minimum_balance(8),
+        RETIRED_FEES_VAULT_GENERATION.to_le_bytes().to_vec(),
+        dlp::id(),
+    );
+    add_account(
+        &mut program_test,
+        fees_vault_pda_from_generation(1),
+        Rent::default().minimum_balance(8),
+        1u64.to_le_bytes().to_vec(),
+        dlp::id(),
+    );
+    // A vault forged at another address, claiming the live generation
+    let forged_vault = Pubkey::new_unique();
+    add_account(
+        &mut program_test,
+        forged_vault,
+        Rent::default().minimum_balance(8),
+        1u64.to_le_bytes().to_vec(),
+        dlp::id(),
+    );
+    // The vault of another validator
+    let other_validator_fees_vault = validator_fees_vault_pda_from_validator(&Pubkey::new_unique());
+    add_account(
+        &mut program_test,
+        other_validator_fees_vault,
+        LAMPORTS_PER_SOL,
+        vec![],
+        dlp::id(),
+    );
+    let (banks, _, blockhash) = program_test.start().await;
+    finalize(&banks, &validator, blockhash).await;
+
+    let ix = undelegate(&validator);
+    let fees_vault_index = account_index(&ix, &fees_vault_pda());
+    let validator_fees_vault_index = account_index(
+        &ix,
+        &validator_fees_vault_pda_from_validator(&validator.pubkey()),
+    );
+
+    // The retired vault
+    let res = process(&banks, &validator, blockhash, &[ix.clone()]).await;
+    assert_dlp_error(res, DlpError::RetiredProtocolFeesVault);
+
+    for (index, vault) in [
+        (fees_vault_index, forged_vault),
+        (fees_vault_index, fees_vault_pda_from_generation(2)),
+        (
+            fees_vault_index,
+            validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        ),
+        (validator_fees_vault_index, other_validator_fees_vault),
+        (
+            validator_fees_vault_index,
+            fees_vault_pda_from_generation(1),
+        ),
+    ] {
+        let mut attack = ix.clone();
+        attack.accounts[fees_vault_index].pubkey = fees_vault_pda_from_generation(1);
+        attack.accounts[index].pubkey = vault;
+        let res = process(&banks, &validator, blockhash, &[attack]).await;
+        assert!(res.is_err(), "vault {} was accepted", vault);
+    }
+
+    let mut ix = ix;
+    ix.accounts[fees_vault_index].pubkey = fees_vault_pda_from_generation(1);
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+}
+
+/// Funding the undelegate buffer before the undelegation does not prevent its creation
+async fn undelegate_prefunded_buffer() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_committed_delegation(&mut program_test, &validator);
+    add_account(
+        &mut program_test,
+        undelegate_buffer_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        1_000,
+        vec![],
+        system_program::id(),
+    );
+    let (banks, _, blockhash) = program_test.start().await;
+    finalize(&banks, &validator, blockhash).await;
+
+    process(&banks, &validator, blockhash, &[undelegate(&validator)])
+        .await
+        .unwrap();
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.owner, DELEGATED_PDA_OWNER_ID);
+    assert_eq!(delegated_account.data, COMMIT_NEW_STATE_ACCOUNT_DATA);
+}
+
+/// An undelegate buffer holding data before the undelegation is never handed to the owner
+/// program
+async fn undelegate_tampered_buffer() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_committed_delegation(&mut program_test, &validator);
+    add_account(
+        &mut program_test,
+        undelegate_buffer_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        LAMPORTS_PER_SOL,
+        vec![0xAB; COMMIT_NEW_STATE_ACCOUNT_DATA.len()],
+        dlp::id(),
+    );
+    let (banks, _, blockhash) = program_test.start().await;
+    finalize(&banks, &validator, blockhash).await;
+
+    let res = process(&banks, &validator, blockhash, &[undelegate(&validator)]).await;
+    assert!(res.is_err());
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.owner, dlp::id());
+    assert!(banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID
+        ))
+        .await
+        .unwrap()
+        .is_some());
+}
+
+/// The optional protocol stats and earnings ledger page are recognized by their owner and
+/// discriminator, which the data committed to another delegated account can mimic. The fees
+/// vaults, the program config and the commit PDAs are checked against their PDA.
+async fn undelegate_delegated_account_role_confusion() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_committed_delegation(&mut program_test, &validator);
+    let ix = undelegate(&validator);
+    let vault_mimics = [
+        (fees_vault_pda(), 1u64.to_le_bytes().to_vec()),
+        (
+            validator_fees_vault_pda_from_validator(&validator.pubkey()),
+            vec![],
+        ),
+        (
+            program_config_from_program_id(&DELEGATED_PDA_OWNER_ID),
+            create_program_config_data(validator.pubkey()),
+        ),
+        (
+            commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            vec![],
+        ),
+        (
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            vec![],
+        ),
+    ]
+    .map(|(role, data)| {
+        (
+            account_index(&ix, &role),
+            add_delegated_mimic(&mut program_test, data),
+        )
+    });
+
+    // Delegated accounts holding the data of the protocol stats and of an earnings ledger
+    // page of the validator
+    let mut protocol_stats_data = vec![0; ProtocolStats::size_with_discriminator()];
+    ProtocolStats {
+        total_value_locked: LAMPORTS_PER_SOL,
+        last_resync_slot: 0,
+    }
+    .to_bytes_with_discriminator(&mut protocol_stats_data)
+    .unwrap();
+    let mut earnings_ledger_page_data = vec![0; EarningsLedgerPage::account_size()];
+    EarningsLedgerPage {
+        validator: validator.pubkey(),
+        page: 0,
+        len: 0,
+    }
+    .to_bytes_with_discriminator(
+        &mut earnings_ledger_page_data[..EarningsLedgerPage::size_with_discriminator()],
+    )
+    .unwrap();
+    let mimics = [protocol_stats_data, earnings_ledger_page_data].map(|data| {
+        let delegated_account = Pubkey::new_unique();
+        add_account(
+            &mut program_test,
+            delegated_account,
+            Rent::default().minimum_balance(data.len()),
+            data,
+            dlp::id(),
+        );
+        delegated_account
+    });
+    let (banks, _, blockhash) = program_test.start().await;
+    finalize(&banks, &validator, blockhash).await;
+
+    assert_role_confusions_fail(&banks, &validator, blockhash, &ix, &vault_mimics).await;
+    for mimic in mimics {
+        let mut ix = undelegate(&validator);
+        ix.accounts.push(AccountMeta::new(mimic, false));
+        let res = process(&banks, &validator, blockhash, &[ix]).await;
+        assert_eq!(
+            res.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds),
+            "delegated account {} was accepted",
+            mimic
+        );
+    }
+    process(&banks, &validator, blockhash, &[undelegate(&validator)])
+        .await
+        .unwrap();
+}
+
+async fn commit_finalize_wrong_pda() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let (banks, _, blockhash) = program_test.start().await;
+
+    // The trailing feature gates PDA is checked before the processor runs
+    let ix = commit_finalize(&validator, 1, vec![1; 10]);
+    assert_substitutions_fail(&banks, &validator, blockhash, &ix, ix.accounts.len() - 1).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+}
+
+/// A commit finalized in place cannot be submitted again, finalized in place or not
+async fn commit_finalize_nonce_replay() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix = commit_finalize(&validator, 1, vec![1; 10]);
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+
+    let ix = commit_finalize(&validator, 1, vec![2; 10]);
+    let res = process(&banks, &validator, blockhash, &[ix]).await;
+    assert_dlp_error(res, DlpError::NonceOutOfOrder);
+    let ix = commit_state(&validator, 1, vec![2; 10]);
+    let res = process(&banks, &validator, blockhash, &[ix]).await;
+    assert_dlp_error(res, DlpError::NonceOutOfOrder);
+}
+
+/// Anyone can migrate a record, but only the record of the delegated account passed
+async fn migrate_delegation_record_wrong_pda() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![],
+        dlp::id(),
+    );
+    let delegation_record_data = get_legacy_delegation_record_data(validator.pubkey());
+    add_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+        dlp::id(),
+    );
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix =
+        dlp::instruction_builder::migrate_delegation_record(validator.pubkey(), DELEGATED_PDA_ID);
+    assert_substitutions_fail(&banks, &validator, blockhash, &ix, ix.accounts.len()).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+    let delegation_record_account = banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.authority, validator.pubkey());
+    assert_eq!(delegation_record.version, DelegationRecord::VERSION);
+}
+
+/// The lamports of the delegated account only go to the escrow of the rent payer of its
+/// delegation, the record following the lamports taken
+async fn fund_escrow_from_delegated_wrong_pda() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix = dlp::instruction_builder::fund_escrow_from_delegated(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        validator.pubkey(),
+        1_000,
+        0,
+    );
+    assert_substitutions_fail(&banks, &validator, blockhash, &ix, ix.accounts.len()).await;
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.lamports, LAMPORTS_PER_SOL - 1_000);
+}
+
+/// Funding the escrow before it is funded from the delegated account does not prevent its
+/// creation
+async fn fund_escrow_from_delegated_prefunded_escrow() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let escrow = ephemeral_balance_pda_from_payer(&validator.pubkey(), 0);
+    add_account(
+        &mut program_test,
+        escrow,
+        1_000,
+        vec![],
+        system_program::id(),
+    );
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix = dlp::instruction_builder::fund_escrow_from_delegated(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        validator.pubkey(),
+        1_000,
+        0,
+    );
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+    let escrow_account = banks.get_account(escrow).await.unwrap().unwrap();
+    assert_eq!(escrow_account.owner, system_program::id());
+    assert_eq!(
+        escrow_account.lamports,
+        Rent::default().minimum_balance(0) + 1_000
+    );
+}
+
+/// A state is only pushed to a delegated PDA by its owner program, signing for the PDA in
+/// its CPI
+async fn commit_from_owner_without_owner_program_cpi() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let mut ix = dlp::instruction_builder::commit_from_owner(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        OWNER_COMMIT_NONCE_FLAG | 1,
+        vec![1; 10],
+    );
+    ix.accounts[1].is_signer = false;
+    let res = process(&banks, &validator, blockhash, &[ix]).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+    assert!(banks
+        .get_account(commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// Once a pushed state is finalized, its nonce cannot be committed again by the validator,
+/// whether with or without the flag of the owner commits
+async fn commit_from_owner_nonce_replay() {
+    let mut program_test = program_test();
+    let validator = add_validator(&mut program_test);
+    add_delegation(&mut program_test, &validator, false);
+
+    // A state pushed by the owner program, the payer advancing the rent of the commit PDAs
+    let payer = Pubkey::new_unique();
+    add_account(
+        &mut program_test,
+        payer,
+        LAMPORTS_PER_SOL,
+        vec![],
+        system_program::id(),
+    );
+    let commit_state_lamports =
+        Rent::default().minimum_balance(COMMIT_NEW_STATE_ACCOUNT_DATA.len());
+    add_account(
+        &mut program_test,
+        commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        commit_state_lamports,
+        COMMIT_NEW_STATE_ACCOUNT_DATA.into(),
+        dlp::id(),
+    );
+    let commit_record_lamports =
+        Rent::default().minimum_balance(CommitRecord::size_with_discriminator());
+    let mut commit_record_data = vec![0; CommitRecord::size_with_discriminator()];
+    CommitRecord {
+        identity: validator.pubkey(),
+        account: DELEGATED_PDA_ID,
+        nonce: OWNER_COMMIT_NONCE_FLAG | 1,
+        lamports: Rent::default().minimum_balance(500),
+        slot: 0,
+        er_block_hash: [0; 32],
+        rent_advanced: commit_state_lamports + commit_record_lamports,
+        escrow: payer,
+        escrow_spend: 0,
+        has_escrow_spend: 0,
+        commit_state_layout: CommitStateLayout::Raw.into(),
+        padding: [0; 6],
+    }
+    .to_bytes_with_discriminator(&mut commit_record_data)
+    .unwrap();
+    add_account(
+        &mut program_test,
+        commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        commit_record_lamports,
+        commit_record_data,
+        dlp::id(),
+    );
+    let (banks, _, blockhash) = program_test.start().await;
+
+    let ix = dlp::instruction_builder::finalize_owner_commit(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        payer,
+    );
+    process(&banks, &validator, blockhash, &[ix]).await.unwrap();
+
+    for nonce in [1, OWNER_COMMIT_NONCE_FLAG | 1] {
+        let ix = commit_state(&validator, nonce, vec![2; 10]);
+        let res = process(&banks, &validator, blockhash, &[ix]).await;
+        assert_dlp_error(res, DlpError::NonceOutOfOrder);
+    }
+}
+
+/// Substitutes every account of the instruction, up to `accounts_len`, but the signers and
+/// the system program, by the other accounts of the instruction and an unrelated account.
+/// Every substitution must be rejected.
+async fn assert_substitutions_fail(
+    banks: &BanksClient,
+    signer: &Keypair,
+    blockhash: Hash,
+    ix: &Instruction,
+    accounts_len: usize,
+) {
+    let replacements: Vec<Pubkey> = ix
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .chain([Pubkey::new_unique()])
+        .collect();
+    for (index, meta) in ix.accounts.iter().enumerate().take(accounts_len) {
+        if meta.is_signer || meta.pubkey == system_program::id() {
+            continue;
+        }
+        for replacement in &replacements {
+            if *replacement == meta.pubkey {
+                continue;
+            }
+            let mut attack = ix.clone();
+            attack.accounts[index].pubkey = *replacement;
+            let res = process(banks, signer, blockhash, &[attack]).await;
+            assert!(
+                res.is_err(),
+                "account {} substituted by {} was accepted",
+                index,
+                replacement
+            );
+        }
+    }
+}
+
+/// A delegated account, owned by the delegation program, holding the data of the role it
+/// stands in for
+fn add_delegated_mimic(program_test: &mut ProgramTest, data: Vec<u8>) -> Pubkey {
+    let delegated_account = Pubkey::new_unique();
+    add_account(
+        program_test,
+        delegated_account,
+        Rent::default().minimum_balance(data.len()),
+        data,
+        dlp::id(),
+    );
+    delegated_account
+}
+
+/// Substitutes the account at each index by the delegated account standing in for it.
+/// Every substitution must be rejected.
+async fn assert_role_confusions_fail(
+    banks: &BanksClient,
+    signer: &Keypair,
+    blockhash: Hash,
+    ix: &Instruction,
+    mimics: &[(usize, Pubkey)],
+) {
+    for (index, mimic) in mimics {
+        let mut attack = ix.clone();
+        attack.accounts[*index].pubkey = *mimic;
+        let res = process(banks, signer, blockhash, &[attack]).await;
+        assert!(
+            res.is_err(),
+            "delegated account {} was accepted as account {}",
+            mimic,
+            index
+        );
+    }
+}
+
+fn account_index(ix: &Instruction, pubkey: &Pubkey) -> usize {
+    ix.accounts
+        .iter()
+        .position(|meta| meta.pubkey == *pubkey)
+        .unwrap()
+}
+
+fn commit_state(validator: &Keypair, nonce: u64, data: Vec<u8>) -> Instruction {
+    dlp::instruction_builder::commit_state(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_args(nonce, data),
+    )
+}
+
+fn commit_finalize(validator: &Keypair, nonce: u64, data: Vec<u8>) -> Instruction {
+    dlp::instruction_builder::commit_finalize(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_args(nonce, data),
+    )
+}
+
+fn commit_args(nonce: u64, data: Vec<u8>) -> CommitStateArgs {
+    CommitStateArgs {
+        nonce,
+        lamports: LAMPORTS_PER_SOL,
+        allow_undelegation: false,
+        data,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
+    }
+}
+
+fn undelegate(validator: &Keypair) -> Instruction {
+    dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    )
+}
+
+async fn finalize(banks: &BanksClient, validator: &Keypair, blockhash: Hash) {
+    let ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    process(banks, validator, blockhash, &[ix]).await.unwrap();
+}
+
+async fn process(
+    banks: &BanksClient,
+    signer: &Keypair,
+    blockhash: Hash,
+    ixs: &[Instruction],
+) -> Result<(), BanksClientError> {
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    banks.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    // Setup program to test undelegation
+    let data = read_file("tests/buffers/test_delegation.so");
+    add_account(
+        &mut program_test,
+        DELEGATED_PDA_OWNER_ID,
+        Rent::default().minimum_balance(data.len()),
+        data,
+        solana_sdk::bpf_loader::id(),
+    );
+    program_test
+}
+
+fn add_account(
+    program_test: &mut ProgramTest,
+    pubkey: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner,
+            executable: owner == solana_sdk::bpf_loader::id(),
+            rent_epoch: 0,
+        },
+    );
+}
+
+/// The validator, with its fees vault, paying for the transactions
+fn add_validator(program_test: &mut ProgramTest) -> Keypair {
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    add_account(
+        program_test,
+        validator.pubkey(),
+        10 * LAMPORTS_PER_SOL,
+        vec![],
+        system_program::id(),
+    );
+    add_account(
+        program_test,
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        LAMPORTS_PER_SOL,
+        vec![],
+        dlp::id(),
+    );
+    validator
+}
+
+/// An account delegated to the validator, without a pending commit
+fn add_delegation(program_test: &mut ProgramTest, validator: &Keypair, is_undelegatable: bool) {
+    add_account(
+        program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![],
+        dlp::id(),
+    );
+    let delegation_metadata_data =
+        get_delegation_metadata_data(validator.pubkey(), Some(is_undelegatable));
+    add_account(
+        program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+        dlp::id(),
+    );
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    add_account(
+        program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+        dlp::id(),
+    );
+}
+
+/// An undelegatable account delegated to the validator, with a commit pending finalization,
+/// and the protocol fees vault
+fn add_committed_delegation(program_test: &mut ProgramTest, validator: &Keypair) {
+    add_delegation(program_test, validator, true);
+    add_account(
+        program_test,
+        commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        LAMPORTS_PER_SOL,
+        COMMIT_NEW_STATE_ACCOUNT_DATA.into(),
+        dlp::id(),
+    );
+    let commit_record_data = get_commit_record_account_data(validator.pubkey());
+    add_account(
+        program_test,
+        commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(commit_record_data.len()),
+        commit_record_data,
+        dlp::id(),
+    );
+    add_account(
+        program_test,
+        fees_vault_pda(),
+        Rent::default().minimum_balance(0),
+        vec![],
+        dlp::id(),
+    );
+}
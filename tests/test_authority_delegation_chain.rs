@@ -0,0 +1,329 @@
+use dlp::args::{AuthorityScope, CommitStateArgs, Encoding, SetAuthorityGrantArgs};
+use dlp::error::DlpError;
+use dlp::pda::{
+    authority_delegation_chain_pda_from_delegator, commit_record_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{AuthorityDelegationChain, CommitRecord};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_instruction, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_commit_from_sub_key() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env().await;
+    let region = Keypair::new();
+    let machine = Keypair::new();
+
+    // The validator grants a region key, which grants a machine key in turn
+    let ix = set_authority_grant(
+        &validator,
+        region.pubkey(),
+        u64::MAX,
+        AuthorityScope::Commit,
+    );
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+    let ixs = [
+        system_instruction::transfer(&validator.pubkey(), &region.pubkey(), LAMPORTS_PER_SOL),
+        set_authority_grant(&region, machine.pubkey(), u64::MAX, AuthorityScope::Commit),
+    ];
+    process(&mut context, &ixs, &validator, &[&region])
+        .await
+        .unwrap();
+    let chain_account = context
+        .banks_client
+        .get_account(authority_delegation_chain_pda_from_delegator(
+            &region.pubkey(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let chain =
+        AuthorityDelegationChain::try_from_bytes_with_discriminator(&chain_account.data).unwrap();
+    assert_eq!(chain.delegator, region.pubkey());
+    assert!(chain.is_granted(&machine.pubkey(), AuthorityScope::Commit, 0));
+    assert!(Rent::default().is_exempt(chain_account.lamports, chain_account.data.len()));
+
+    // Without the chain, the machine key is not an authority of the delegation
+    let mut ix = commit_from_sub_key(&machine, &validator, &[region.pubkey()], false);
+    ix.accounts.truncate(9);
+    let res = process(&mut context, &[ix], &validator, &[&machine]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+
+    // A chain skipping the region key does not grant the machine key
+    let ix = commit_from_sub_key(&machine, &validator, &[], false);
+    let res = process(&mut context, &[ix], &validator, &[&machine]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthorityChain);
+
+    // The commit of the machine key is attributed to the validator identity
+    let ix = commit_from_sub_key(&machine, &validator, &[region.pubkey()], false);
+    process(&mut context, &[ix], &validator, &[&machine])
+        .await
+        .unwrap();
+    let commit_record_account = context
+        .banks_client
+        .get_account(commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+    assert_eq!(commit_record.identity, validator.pubkey());
+}
+
+#[tokio::test]
+async fn test_commit_from_sub_key_scope() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env().await;
+    let region = Keypair::new();
+    let machine = Keypair::new();
+
+    // The region key can undelegate, but it only granted the machine key to commit
+    let ixs = [
+        set_authority_grant(
+            &validator,
+            region.pubkey(),
+            u64::MAX,
+            AuthorityScope::CommitAndUndelegate,
+        ),
+        system_instruction::transfer(&validator.pubkey(), &region.pubkey(), LAMPORTS_PER_SOL),
+        set_authority_grant(&region, machine.pubkey(), u64::MAX, AuthorityScope::Commit),
+    ];
+    process(&mut context, &ixs, &validator, &[&region])
+        .await
+        .unwrap();
+
+    // The machine key cannot commit allowing the undelegation
+    let ix = commit_from_sub_key(&machine, &validator, &[region.pubkey()], true);
+    let res = process(&mut context, &[ix], &validator, &[&machine]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthorityChain);
+
+    // Once granted the undelegation, it can
+    let ix = set_authority_grant(
+        &region,
+        machine.pubkey(),
+        u64::MAX,
+        AuthorityScope::CommitAndUndelegate,
+    );
+    process(&mut context, &[ix], &validator, &[&region])
+        .await
+        .unwrap();
+    let ix = commit_from_sub_key(&machine, &validator, &[region.pubkey()], true);
+    process(&mut context, &[ix], &validator, &[&machine])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_commit_from_expired_sub_key() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env().await;
+    let sub_key = Keypair::new();
+
+    // The grant expires at slot 100
+    let ix = set_authority_grant(&validator, sub_key.pubkey(), 100, AuthorityScope::Commit);
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+
+    context.warp_to_slot(100).unwrap();
+    let ix = commit_from_sub_key(&sub_key, &validator, &[], false);
+    let res = process(&mut context, &[ix], &validator, &[&sub_key]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthorityChain);
+
+    // Revoking the grant refunds the rent of its entry
+    let ix = dlp::instruction_builder::set_authority_grant(
+        validator.pubkey(),
+        SetAuthorityGrantArgs {
+            key: sub_key.pubkey(),
+            expiry_slot: 0,
+            scope: AuthorityScope::Commit,
+            approved: false,
+        },
+    );
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+    let chain_account = context
+        .banks_client
+        .get_account(authority_delegation_chain_pda_from_delegator(
+            &validator.pubkey(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let chain =
+        AuthorityDelegationChain::try_from_bytes_with_discriminator(&chain_account.data).unwrap();
+    assert!(chain.grants.is_empty());
+    assert_eq!(
+        chain_account.lamports,
+        Rent::default().minimum_balance(chain.size_with_discriminator())
+    );
+}
+
+#[tokio::test]
+async fn test_authority_chain_depth() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env().await;
+    let keys: Vec<Keypair> = (0..dlp::state::MAX_AUTHORITY_CHAIN_DEPTH + 1)
+        .map(|_| Keypair::new())
+        .collect();
+
+    // Every key grants the next one, the chain being one link too deep
+    let ix = set_authority_grant(
+        &validator,
+        keys[0].pubkey(),
+        u64::MAX,
+        AuthorityScope::Commit,
+    );
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+    for pair in keys.windows(2) {
+        let ixs = [
+            system_instruction::transfer(&validator.pubkey(), &pair[0].pubkey(), LAMPORTS_PER_SOL),
+            set_authority_grant(&pair[0], pair[1].pubkey(), u64::MAX, AuthorityScope::Commit),
+        ];
+        process(&mut context, &ixs, &validator, &[&pair[0]])
+            .await
+            .unwrap();
+    }
+
+    let (signer, grantors) = keys.split_last().unwrap();
+    let grantors: Vec<Pubkey> = grantors.iter().map(|key| key.pubkey()).collect();
+    let ix = commit_from_sub_key(signer, &validator, &grantors, false);
+    let res = process(&mut context, &[ix], &validator, &[signer]).await;
+    assert!(res.is_err());
+}
+
+fn set_authority_grant(
+    delegator: &Keypair,
+    key: Pubkey,
+    expiry_slot: u64,
+    scope: AuthorityScope,
+) -> Instruction {
+    dlp::instruction_builder::set_authority_grant(
+        delegator.pubkey(),
+        SetAuthorityGrantArgs {
+            key,
+            expiry_slot,
+            scope,
+            approved: true,
+        },
+    )
+}
+
+fn commit_from_sub_key(
+    sub_key: &Keypair,
+    validator: &Keypair,
+    grantors: &[Pubkey],
+    allow_undelegation: bool,
+) -> Instruction {
+    dlp::instruction_builder::commit_state_from_sub_key(
+        sub_key.pubkey(),
+        validator.pubkey(),
+        grantors,
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![1, 2, 3],
+            nonce: 1,
+            allow_undelegation,
+            lamports: Rent::default().minimum_balance(500),
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let signers = [&[payer], signers].concat();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA, its delegation PDAs and the validator fees vault
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+    );
+    let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+    );
+    add_dlp_account(
+        &mut program_test,
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, validator)
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
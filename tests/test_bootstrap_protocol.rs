@@ -0,0 +1,149 @@
+use crate::fixtures::TEST_AUTHORITY;
+use dlp::pda::{feature_gates_pda, fees_vault_pda, protocol_config_pda};
+use dlp::state::{FeatureGates, ProtocolConfig};
+use solana_program::instruction::InstructionError;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_bootstrap_protocol() {
+    // Setup
+    let (banks, admin, blockhash) = setup_program_test_env(None).await;
+
+    let ix = dlp::instruction_builder::bootstrap_protocol(admin.pubkey());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the fees vault was created
+    let fees_vault_account = banks.get_account(fees_vault_pda()).await.unwrap().unwrap();
+    assert_eq!(fees_vault_account.owner, dlp::id());
+
+    // Assert every instruction is enabled
+    let feature_gates_account = banks
+        .get_account(feature_gates_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    let feature_gates =
+        FeatureGates::try_from_bytes_with_discriminator(&feature_gates_account.data).unwrap();
+    assert_eq!(*feature_gates, FeatureGates::default());
+
+    // Assert the default protocol config was created
+    let protocol_config_account = banks
+        .get_account(protocol_config_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    let protocol_config =
+        ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_account.data).unwrap();
+    assert_eq!(protocol_config.escrow_dust_threshold, 0);
+}
+
+#[tokio::test]
+async fn test_bootstrap_protocol_keeps_initialized_pdas() {
+    // Setup with an existing protocol config
+    let (banks, admin, blockhash) = setup_program_test_env(Some(42)).await;
+
+    let ix = dlp::instruction_builder::bootstrap_protocol(admin.pubkey());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the protocol config was left untouched
+    let protocol_config_account = banks
+        .get_account(protocol_config_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    let protocol_config =
+        ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_account.data).unwrap();
+    assert_eq!(protocol_config.escrow_dust_threshold, 42);
+
+    // Assert the missing PDAs were created
+    assert!(banks.get_account(fees_vault_pda()).await.unwrap().is_some());
+    assert!(banks
+        .get_account(feature_gates_pda())
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_bootstrap_protocol_unauthorized() {
+    // Setup
+    let (banks, payer, blockhash) = setup_program_test_env(None).await;
+    let other = Keypair::new();
+
+    let ix = dlp::instruction_builder::bootstrap_protocol(other.pubkey());
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &other],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(dlp::error::DlpError::Unauthorized as u32)
+        )
+    );
+    assert!(banks
+        .get_account(protocol_config_pda())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn setup_program_test_env(
+    escrow_dust_threshold: Option<u64>,
+) -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the protocol config PDA
+    if let Some(escrow_dust_threshold) = escrow_dust_threshold {
+        let mut data = vec![0; ProtocolConfig::size_with_discriminator()];
+        ProtocolConfig {
+            escrow_dust_threshold,
+            ..Default::default()
+        }
+        .to_bytes_with_discriminator(&mut data)
+        .unwrap();
+        program_test.add_account(
+            protocol_config_pda(),
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, admin, blockhash)
+}
@@ -1,25 +1,29 @@
 use crate::fixtures::{
-    create_delegation_metadata_data, create_delegation_record_data, get_commit_record_account_data,
-    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
-    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+    create_commit_record_data_with_escrow_spend, create_delegation_metadata_data,
+    create_delegation_record_data, get_commit_record_account_data, get_delegation_metadata_data,
+    get_delegation_record_data, DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
 };
 use borsh::{to_vec, BorshDeserialize, BorshSerialize};
-use dlp::args::CallHandlerArgs;
+use dlp::args::{CallHandlerArgs, CallHandlerContext, SetCallHandlerPermissionsArgs};
 use dlp::ephemeral_balance_seeds_from_payer;
+use dlp::error::DlpError;
 use dlp::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
-    ephemeral_balance_pda_from_payer, fees_vault_pda, validator_fees_vault_pda_from_validator,
+    ephemeral_balance_pda_from_payer, fees_vault_pda, session_report_pda_from_escrow,
+    validator_fees_vault_pda_from_validator,
 };
-use solana_program::instruction::AccountMeta;
+use dlp::state::SessionReport;
+use solana_program::instruction::{AccountMeta, InstructionError};
 use solana_program::rent::Rent;
+use solana_program::system_instruction;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{read_file, BanksClient, ProgramTest};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::{
     account::Account,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 mod fixtures;
@@ -74,7 +78,11 @@ async fn setup_delegated_pda(program_test: &mut ProgramTest, authority_pubkey: &
     );
 }
 
-async fn setup_commit_state(program_test: &mut ProgramTest, authority_pubkey: &Pubkey) {
+async fn setup_commit_state(
+    program_test: &mut ProgramTest,
+    authority_pubkey: &Pubkey,
+    escrow_spend: Option<u64>,
+) {
     // Setup the commit state PDA
     let commit_state = to_vec(&Counter { count: 101 }).unwrap();
     program_test.add_account(
@@ -88,7 +96,12 @@ async fn setup_commit_state(program_test: &mut ProgramTest, authority_pubkey: &P
         },
     );
 
-    let commit_record_data = get_commit_record_account_data(*authority_pubkey);
+    let commit_record_data = match escrow_spend {
+        Some(escrow_spend) => {
+            create_commit_record_data_with_escrow_spend(*authority_pubkey, escrow_spend)
+        }
+        None => get_commit_record_account_data(*authority_pubkey),
+    };
     program_test.add_account(
         commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
         Account {
@@ -219,6 +232,13 @@ async fn setup_ephemeral_balance(program_test: &mut ProgramTest, payer: &Keypair
 }
 
 async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    setup_program_test_env_with_escrow_spend(None).await
+}
+
+/// Setup the accounts, the commit of the delegated account declaring the escrow spend if set
+async fn setup_program_test_env_with_escrow_spend(
+    escrow_spend: Option<u64>,
+) -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
 
@@ -239,7 +259,7 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
 
     // Setup necessary accounts
     setup_delegated_pda(&mut program_test, &validator.pubkey()).await;
-    setup_commit_state(&mut program_test, &validator.pubkey()).await;
+    setup_commit_state(&mut program_test, &validator.pubkey(), escrow_spend).await;
     setup_invalid_escrow_account(&mut program_test, &validator.pubkey()).await;
     setup_delegated_ephemeral_balance(&mut program_test, &validator, &payer).await;
     setup_ephemeral_balance(&mut program_test, &payer).await;
@@ -308,6 +328,8 @@ async fn test_finalize_call_handler() {
                 to_vec(&PRIZE).unwrap(),
             ]
             .concat(),
+            context: None,
+            escrow_spend: None,
         },
     );
 
@@ -329,7 +351,7 @@ async fn test_finalize_call_handler() {
     assert_eq!(transfer_destination.lamports, PRIZE);
 }
 
-/// Test call_handler in finalize context
+/// Test call_handler in undelegate context
 #[tokio::test]
 async fn test_undelegate_call_handler() {
     const PRIZE: u64 = LAMPORTS_PER_SOL / 1000;
@@ -360,6 +382,8 @@ async fn test_undelegate_call_handler() {
                 to_vec(&PRIZE).unwrap(),
             ]
             .concat(),
+            context: None,
+            escrow_spend: None,
         },
     );
 
@@ -408,6 +432,8 @@ async fn test_finalize_invalid_escrow_call_handler() {
         CallHandlerArgs {
             escrow_index: 0,
             data: COMMIT_HANDLER_DISCRIMINATOR.to_vec(),
+            context: None,
+            escrow_spend: None,
         },
     );
     let tx = Transaction::new_signed_with_payer(
@@ -440,6 +466,8 @@ async fn test_undelegate_invalid_escow_call_handler() {
         CallHandlerArgs {
             escrow_index: 0,
             data: UNDELEGATE_HANDLER_DISCRIMINATOR.to_vec(),
+            context: None,
+            escrow_spend: None,
         },
     );
 
@@ -461,6 +489,8 @@ async fn test_undelegate_invalid_escow_call_handler() {
                 to_vec(&PRIZE).unwrap(),
             ]
             .concat(),
+            context: None,
+            escrow_spend: None,
         },
     );
     let tx = Transaction::new_signed_with_payer(
@@ -480,3 +510,521 @@ async fn test_undelegate_invalid_escow_call_handler() {
         .to_string()
         .contains("Invalid account owner"));
 }
+
+/// Test call_handler in a context disabled by the escrow authority
+#[tokio::test]
+async fn test_call_handler_disabled_context() {
+    const PRIZE: u64 = LAMPORTS_PER_SOL / 1000;
+
+    let (banks, payer, validator, blockhash) = setup_program_test_env().await;
+
+    // The escrow authority only allows the handlers called along an undelegation
+    let fund_ix =
+        system_instruction::transfer(&validator.pubkey(), &payer.pubkey(), LAMPORTS_PER_SOL / 10);
+    let set_permissions_ix = dlp::instruction_builder::set_call_handler_permissions(
+        payer.pubkey(),
+        SetCallHandlerPermissionsArgs {
+            escrow_index: 2,
+            allow_commit: false,
+            allow_undelegate: true,
+            allow_standalone: false,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[fund_ix, set_permissions_ix],
+        Some(&validator.pubkey()),
+        &[&validator, &payer],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+
+    // A handler called along a commit is rejected
+    let transfer_destination = Keypair::new();
+    let finalize_ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    let call_handler_ix = dlp::instruction_builder::call_handler(
+        validator.pubkey(),
+        DELEGATED_PDA_OWNER_ID, // destination program
+        payer.pubkey(),         // escrow authority
+        vec![
+            AccountMeta::new(transfer_destination.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        CallHandlerArgs {
+            escrow_index: 2, // undelegated escrow index,
+            data: [
+                COMMIT_HANDLER_DISCRIMINATOR.to_vec(),
+                to_vec(&PRIZE).unwrap(),
+            ]
+            .concat(),
+            context: None,
+            escrow_spend: None,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix, call_handler_ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DlpError::CallHandlerContextDisabled as u32)
+        )
+    );
+}
+
+/// Test call_handler rejecting a validator omitting the call handler permissions of the
+/// escrow, so that it cannot call a handler in a context disabled by the escrow authority
+#[tokio::test]
+async fn test_call_handler_without_permissions() {
+    const PRIZE: u64 = LAMPORTS_PER_SOL / 1000;
+
+    let (banks, payer, validator, blockhash) = setup_program_test_env().await;
+
+    // The escrow authority only allows the handlers called along an undelegation
+    let fund_ix =
+        system_instruction::transfer(&validator.pubkey(), &payer.pubkey(), LAMPORTS_PER_SOL / 10);
+    let set_permissions_ix = dlp::instruction_builder::set_call_handler_permissions(
+        payer.pubkey(),
+        SetCallHandlerPermissionsArgs {
+            escrow_index: 2,
+            allow_commit: false,
+            allow_undelegate: true,
+            allow_standalone: false,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[fund_ix, set_permissions_ix],
+        Some(&validator.pubkey()),
+        &[&validator, &payer],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+
+    // A handler called along a commit without the instructions sysvar and the call handler
+    // permissions is rejected
+    let transfer_destination = Keypair::new();
+    let finalize_ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    let mut call_handler_ix = dlp::instruction_builder::call_handler(
+        validator.pubkey(),
+        DELEGATED_PDA_OWNER_ID, // destination program
+        payer.pubkey(),         // escrow authority
+        vec![
+            AccountMeta::new(transfer_destination.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        CallHandlerArgs {
+            escrow_index: 2, // undelegated escrow index,
+            data: [
+                COMMIT_HANDLER_DISCRIMINATOR.to_vec(),
+                to_vec(&PRIZE).unwrap(),
+            ]
+            .concat(),
+            context: None,
+            escrow_spend: None,
+        },
+    );
+    call_handler_ix.accounts.drain(5..7);
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix, call_handler_ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(1, InstructionError::UnsupportedSysvar)
+    );
+    let transfer_destination = banks
+        .get_account(transfer_destination.pubkey())
+        .await
+        .unwrap();
+    assert!(transfer_destination.is_none());
+}
+
+/// Test call_handler deriving its context from the transaction, regardless of the context
+/// declared by the validator
+#[tokio::test]
+async fn test_call_handler_derived_context() {
+    const PRIZE: u64 = LAMPORTS_PER_SOL / 1000;
+
+    let (banks, payer, validator, blockhash) = setup_program_test_env().await;
+
+    // The escrow authority only allows the handlers called along a commit
+    let fund_ix =
+        system_instruction::transfer(&validator.pubkey(), &payer.pubkey(), LAMPORTS_PER_SOL / 10);
+    let set_permissions_ix = dlp::instruction_builder::set_call_handler_permissions(
+        payer.pubkey(),
+        SetCallHandlerPermissionsArgs {
+            escrow_index: 2,
+            allow_commit: true,
+            allow_undelegate: false,
+            allow_standalone: false,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[fund_ix, set_permissions_ix],
+        Some(&validator.pubkey()),
+        &[&validator, &payer],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+
+    let transfer_destination = Keypair::new();
+    let call_handler_ix = || {
+        dlp::instruction_builder::call_handler(
+            validator.pubkey(),
+            DELEGATED_PDA_OWNER_ID, // destination program
+            payer.pubkey(),         // escrow authority
+            vec![
+                AccountMeta::new(transfer_destination.pubkey(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            CallHandlerArgs {
+                escrow_index: 2, // undelegated escrow index,
+                data: [
+                    COMMIT_HANDLER_DISCRIMINATOR.to_vec(),
+                    to_vec(&PRIZE).unwrap(),
+                ]
+                .concat(),
+                context: Some(CallHandlerContext::Commit),
+                escrow_spend: None,
+            },
+        )
+    };
+
+    // A handler declared along a commit but called along an undelegation is rejected
+    let finalize_ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    let undelegate_ix = dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix, undelegate_ix, call_handler_ix()],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(DlpError::CallHandlerContextDisabled as u32)
+        )
+    );
+
+    // A handler declared along a commit but called on its own is rejected
+    let tx = Transaction::new_signed_with_payer(
+        &[call_handler_ix()],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::CallHandlerContextMismatch as u32)
+        )
+    );
+
+    // A handler called along a commit is allowed
+    let finalize_ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix, call_handler_ix()],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    let transfer_destination = banks
+        .get_account(transfer_destination.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(transfer_destination.lamports, PRIZE);
+}
+
+/// Test call_handler verifying the lamports spent by the escrow against its declared spend
+#[tokio::test]
+async fn test_call_handler_escrow_spend() {
+    const PRIZE: u64 = LAMPORTS_PER_SOL / 1000;
+
+    let (banks, payer, validator, blockhash) = setup_program_test_env().await;
+
+    let transfer_destination = Keypair::new();
+    let call_handler_ix = |escrow_spend: u64| {
+        dlp::instruction_builder::call_handler(
+            validator.pubkey(),
+            DELEGATED_PDA_OWNER_ID, // destination program
+            payer.pubkey(),         // escrow authority
+            vec![
+                AccountMeta::new(transfer_destination.pubkey(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            CallHandlerArgs {
+                escrow_index: 2, // undelegated escrow index,
+                data: [
+                    COMMIT_HANDLER_DISCRIMINATOR.to_vec(),
+                    to_vec(&PRIZE).unwrap(),
+                ]
+                .concat(),
+                context: None,
+                escrow_spend: Some(escrow_spend),
+            },
+        )
+    };
+
+    // The escrow spent more than declared
+    let tx = Transaction::new_signed_with_payer(
+        &[call_handler_ix(PRIZE - 1)],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::EscrowSpendMismatch as u32)
+        )
+    );
+
+    // The escrow spent the declared lamports
+    let tx = Transaction::new_signed_with_payer(
+        &[call_handler_ix(PRIZE)],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    let escrow = banks
+        .get_account(ephemeral_balance_pda_from_payer(&payer.pubkey(), 2))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(escrow.lamports, LAMPORTS_PER_SOL - PRIZE);
+}
+
+/// Test finalize cross-checking the escrow spend declared by the commit against the call
+/// handlers of the transaction
+#[tokio::test]
+async fn test_finalize_escrow_spend() {
+    const PRIZE: u64 = LAMPORTS_PER_SOL / 1000;
+
+    let (banks, payer, validator, blockhash) =
+        setup_program_test_env_with_escrow_spend(Some(PRIZE)).await;
+
+    let transfer_destination = Keypair::new();
+    let call_handler_ix = dlp::instruction_builder::call_handler(
+        validator.pubkey(),
+        DELEGATED_PDA_OWNER_ID, // destination program
+        payer.pubkey(),         // escrow authority
+        vec![
+            AccountMeta::new(transfer_destination.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        CallHandlerArgs {
+            escrow_index: 2, // undelegated escrow index,
+            data: [
+                COMMIT_HANDLER_DISCRIMINATOR.to_vec(),
+                to_vec(&PRIZE).unwrap(),
+            ]
+            .concat(),
+            context: None,
+            escrow_spend: Some(PRIZE),
+        },
+    );
+
+    // The instructions sysvar is required to verify the declared spend
+    let finalize_ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::EscrowSpendMismatch as u32)
+        )
+    );
+
+    // The spend of another escrow is not accounted for the committed account
+    let finalize_ix =
+        dlp::instruction_builder::finalize_with_escrow_spend(validator.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix, call_handler_ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::EscrowSpendMismatch as u32)
+        )
+    );
+
+    // A commit declaring no spend is finalized without call handlers
+    let (banks, _, validator, blockhash) = setup_program_test_env_with_escrow_spend(Some(0)).await;
+    let finalize_ix =
+        dlp::instruction_builder::finalize_with_escrow_spend(validator.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    assert!(banks
+        .get_account(commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// Test the session report of an escrow recording the spends of its call handlers, and
+/// frozen by the undelegation of the escrow
+#[tokio::test]
+async fn test_call_handler_session_report() {
+    const PRIZE: u64 = LAMPORTS_PER_SOL / 1000;
+
+    let (banks, payer, validator, blockhash) = setup_program_test_env().await;
+    let escrow = ephemeral_balance_pda_from_payer(&payer.pubkey(), 2);
+    let delegated_escrow = ephemeral_balance_pda_from_payer(&payer.pubkey(), 1);
+
+    // The escrow authority starts the reports of both escrows
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &validator.pubkey(),
+                &payer.pubkey(),
+                LAMPORTS_PER_SOL / 10,
+            ),
+            dlp::instruction_builder::init_session_report(payer.pubkey(), 2),
+            dlp::instruction_builder::init_session_report(payer.pubkey(), 1),
+        ],
+        Some(&validator.pubkey()),
+        &[&validator, &payer],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+
+    // The call handler records its spend, the report not being forwarded to the handler
+    let transfer_destination = Keypair::new();
+    let call_handler_ix = dlp::instruction_builder::call_handler(
+        validator.pubkey(),
+        DELEGATED_PDA_OWNER_ID, // destination program
+        payer.pubkey(),         // escrow authority
+        vec![
+            AccountMeta::new(transfer_destination.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        CallHandlerArgs {
+            escrow_index: 2, // undelegated escrow index,
+            data: [
+                COMMIT_HANDLER_DISCRIMINATOR.to_vec(),
+                to_vec(&PRIZE).unwrap(),
+            ]
+            .concat(),
+            context: None,
+            escrow_spend: None,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[dlp::instruction_builder::with_session_report(
+            call_handler_ix,
+            &escrow,
+        )],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    let session_report_account = banks
+        .get_account(session_report_pda_from_escrow(&escrow))
+        .await
+        .unwrap()
+        .unwrap();
+    let session_report =
+        SessionReport::try_from_bytes_with_discriminator(&session_report_account.data).unwrap();
+    assert_eq!(session_report.authority, payer.pubkey());
+    assert_eq!(session_report.escrow, escrow);
+    assert_eq!(session_report.total_spent, PRIZE);
+    assert_eq!(session_report.commits, 0);
+    assert!(!session_report.is_frozen());
+
+    // The undelegation of the delegated escrow freezes its report
+    let undelegate_ix = dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        delegated_escrow,
+        system_program::id(),
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[dlp::instruction_builder::with_session_report(
+            undelegate_ix,
+            &delegated_escrow,
+        )],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    let session_report_account = banks
+        .get_account(session_report_pda_from_escrow(&delegated_escrow))
+        .await
+        .unwrap()
+        .unwrap();
+    let session_report =
+        SessionReport::try_from_bytes_with_discriminator(&session_report_account.data).unwrap();
+    assert!(session_report.is_frozen());
+
+    // Only the escrow authority closes a report
+    let mut close_ix = dlp::instruction_builder::close_session_report(payer.pubkey(), 2);
+    close_ix.accounts[0].pubkey = validator.pubkey();
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::Unauthorized as u32)
+        )
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[dlp::instruction_builder::close_session_report(
+            payer.pubkey(),
+            2,
+        )],
+        Some(&validator.pubkey()),
+        &[&validator, &payer],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    assert!(banks
+        .get_account(session_report_pda_from_escrow(&escrow))
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,148 @@
+use crate::fixtures::{DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY};
+use dlp::error::DlpError;
+use dlp::pda::program_config_from_program_id;
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{read_file, BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_close_program_config() {
+    // Setup
+    let (banks, authority, blockhash) = setup_program_test_env().await;
+    let program_config_pda = program_config_from_program_id(&DELEGATED_PDA_OWNER_ID);
+
+    // Create the program config
+    let ix = dlp::instruction_builder::set_program_max_delegation_slots(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        1_000,
+    );
+    let res = process(&banks, &[ix], &authority, blockhash).await;
+    assert!(res.is_ok());
+    let program_config_lamports = banks
+        .get_account(program_config_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    let authority_lamports = banks.get_balance(authority.pubkey()).await.unwrap();
+
+    // Close the program config
+    let ix = dlp::instruction_builder::close_program_config(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        true,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let fee = banks
+        .get_fee_for_message(tx.message.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // The program config is closed and its rent refunded to the authority
+    assert!(banks
+        .get_account(program_config_pda)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        banks.get_balance(authority.pubkey()).await.unwrap(),
+        authority_lamports + program_config_lamports - fee
+    );
+}
+
+#[tokio::test]
+async fn test_close_program_config_rejections() {
+    // Setup
+    let (banks, authority, blockhash) = setup_program_test_env().await;
+
+    // A program config which does not exist cannot be closed
+    let ix = dlp::instruction_builder::close_program_config(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        true,
+    );
+    let res = process(&banks, &[ix], &authority, blockhash).await;
+    assert!(res.is_err());
+
+    // The absence of active delegations must be attested
+    let ixs = [
+        dlp::instruction_builder::set_program_max_delegation_slots(
+            authority.pubkey(),
+            DELEGATED_PDA_OWNER_ID,
+            1_000,
+        ),
+        dlp::instruction_builder::close_program_config(
+            authority.pubkey(),
+            DELEGATED_PDA_OWNER_ID,
+            false,
+        ),
+    ];
+    let res = process(&banks, &ixs, &authority, blockhash).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DlpError::ActiveDelegationsNotAttested as u32)
+        )
+    );
+}
+
+async fn process(
+    banks: &BanksClient,
+    ixs: &[Instruction],
+    signer: &Keypair,
+    blockhash: Hash,
+) -> Result<(), BanksClientError> {
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    banks.process_transaction(tx).await
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let authority = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        authority.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the program the config belongs to
+    let data = read_file("tests/buffers/test_delegation.so");
+    program_test.add_account(
+        DELEGATED_PDA_OWNER_ID,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: solana_sdk::bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, authority, blockhash)
+}
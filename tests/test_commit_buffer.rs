@@ -0,0 +1,242 @@
+use dlp::args::{CommitStateFromBufferArgs, Encoding};
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_buffer_pda_from_delegated_account_and_authority,
+    commit_state_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account, validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{CommitBuffer, CommitStateLayout, PendingState};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+/// Larger than the data of a single transaction
+const STATE_LEN: usize = 3000;
+const CHUNK_LEN: usize = 900;
+
+#[tokio::test]
+async fn test_commit_state_from_commit_buffer() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env().await;
+    let state: Vec<u8> = (0..STATE_LEN).map(|i| i as u8).collect();
+    let commit_buffer_pda = commit_buffer_pda_from_delegated_account_and_authority(
+        &DELEGATED_PDA_ID,
+        &validator.pubkey(),
+    );
+
+    // Stage the state in chunks, each in its own transaction
+    let ix = dlp::instruction_builder::init_commit_buffer(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        STATE_LEN as u32,
+    );
+    process(&mut context, &[ix], &validator).await.unwrap();
+    for (i, chunk) in state.chunks(CHUNK_LEN).enumerate() {
+        let ix = dlp::instruction_builder::write_commit_buffer(
+            validator.pubkey(),
+            DELEGATED_PDA_ID,
+            (i * CHUNK_LEN) as u32,
+            chunk.to_vec(),
+        );
+        process(&mut context, &[ix], &validator).await.unwrap();
+    }
+
+    let commit_buffer_account = context
+        .banks_client
+        .get_account(commit_buffer_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        CommitBuffer::staged_data(&commit_buffer_account.data),
+        Some(state.as_slice())
+    );
+    assert!(Rent::default().is_exempt(
+        commit_buffer_account.lamports,
+        commit_buffer_account.data.len()
+    ));
+
+    // The commit state holds the staged state, without the header of the buffer
+    let ix = commit_state_from_buffer(&validator, commit_buffer_pda);
+    process(&mut context, &[ix], &validator).await.unwrap();
+    let commit_state_account = context
+        .banks_client
+        .get_account(commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+            .unwrap(),
+        state
+    );
+
+    // Closing the buffer refunds its rent to the authority
+    let balance_before = context
+        .banks_client
+        .get_balance(validator.pubkey())
+        .await
+        .unwrap();
+    let ix = dlp::instruction_builder::close_commit_buffer(validator.pubkey(), DELEGATED_PDA_ID);
+    process(&mut context, &[ix], &validator).await.unwrap();
+    assert!(context
+        .banks_client
+        .get_account(commit_buffer_pda)
+        .await
+        .unwrap()
+        .is_none());
+    let balance_after = context
+        .banks_client
+        .get_balance(validator.pubkey())
+        .await
+        .unwrap();
+    assert!(balance_after > balance_before);
+}
+
+#[tokio::test]
+async fn test_commit_state_from_incomplete_commit_buffer() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env().await;
+    let commit_buffer_pda = commit_buffer_pda_from_delegated_account_and_authority(
+        &DELEGATED_PDA_ID,
+        &validator.pubkey(),
+    );
+
+    // Only the first chunk of the state is staged
+    let ixs = [
+        dlp::instruction_builder::init_commit_buffer(
+            validator.pubkey(),
+            DELEGATED_PDA_ID,
+            STATE_LEN as u32,
+        ),
+        dlp::instruction_builder::write_commit_buffer(
+            validator.pubkey(),
+            DELEGATED_PDA_ID,
+            0,
+            vec![1; CHUNK_LEN],
+        ),
+    ];
+    process(&mut context, &ixs, &validator).await.unwrap();
+
+    let ix = commit_state_from_buffer(&validator, commit_buffer_pda);
+    let res = process(&mut context, &[ix], &validator).await;
+    assert_dlp_error(res, DlpError::InvalidCommitBuffer);
+
+    // A chunk overflowing the length of the state is rejected
+    let ix = dlp::instruction_builder::write_commit_buffer(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        (STATE_LEN - 1) as u32,
+        vec![1, 2],
+    );
+    let res = process(&mut context, &[ix], &validator).await;
+    assert_dlp_error(res, DlpError::InvalidCommitBuffer);
+}
+
+fn commit_state_from_buffer(validator: &Keypair, commit_buffer: Pubkey) -> Instruction {
+    dlp::instruction_builder::commit_state_from_buffer(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_buffer,
+        CommitStateFromBufferArgs {
+            nonce: 1,
+            allow_undelegation: false,
+            lamports: LAMPORTS_PER_SOL,
+            er_block_hash: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    payer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &[payer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA, its delegation PDAs and the validator fees vault
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+    );
+    let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+    );
+    add_dlp_account(
+        &mut program_test,
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, validator)
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
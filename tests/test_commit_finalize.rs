@@ -0,0 +1,427 @@
+use dlp::args::{CommitStateArgs, Encoding, MaxAccountSize, OversizedStatePolicy};
+use dlp::consts::FEATURE_GATES_PDA;
+use dlp::discriminator::DlpDiscriminator;
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    read_lock_pda_from_delegated_account, validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{DelegationMetadata, DelegationRecord, ReadLock};
+use solana_program::clock::Clock;
+use solana_program::instruction::InstructionError;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    create_delegation_metadata_data_with_max_account_size, get_commit_record_account_data,
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_commit_finalize() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env(false, None).await;
+    let new_state = vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9];
+
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let delegation_record_account = banks
+        .get_account(delegation_record_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let previous_lamports =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap()
+            .lamports;
+
+    let new_account_balance = 1_000_000;
+    let commit_args = CommitStateArgs {
+        data: new_state.clone(),
+        nonce: 1,
+        allow_undelegation: true,
+        lamports: new_account_balance,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
+    };
+
+    // Commit and finalize the state for the delegated account
+    let ix = dlp::instruction_builder::commit_finalize(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_args,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Assert no commit PDA was created
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    assert!(banks.get_account(commit_state_pda).await.unwrap().is_none());
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    assert!(banks
+        .get_account(commit_record_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Assert the delegated account contains the new state
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.data, new_state);
+
+    // Assert the extra lamports were settled to the validator fees vault
+    let validator_fees_vault = banks
+        .get_account(validator_fees_vault_pda_from_validator(&authority.pubkey()))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        validator_fees_vault.lamports,
+        LAMPORTS_PER_SOL + previous_lamports - new_account_balance
+    );
+
+    // Assert the delegation record tracks the delegated account lamports
+    let delegation_record_account = banks
+        .get_account(delegation_record_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.lamports, delegated_account.lamports);
+
+    // Assert the delegation metadata was updated
+    let delegation_metadata_pda = delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let delegation_metadata_account = banks
+        .get_account(delegation_metadata_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert_eq!(delegation_metadata.last_update_nonce, 1);
+    assert!(delegation_metadata.is_undelegatable);
+}
+
+#[tokio::test]
+async fn test_commit_finalize_with_read_lock() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env(false, None).await;
+
+    let commit_args = CommitStateArgs {
+        data: vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9],
+        nonce: 1,
+        allow_undelegation: false,
+        lamports: 1_000_000,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
+    };
+
+    // Initialize the read lock and commit and finalize locking the reads of the delegated
+    // account, as a finalize does
+    let ixs = [
+        dlp::instruction_builder::init_read_lock(authority.pubkey(), DELEGATED_PDA_ID),
+        dlp::instruction_builder::commit_finalize_with_read_lock(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            DELEGATED_PDA_OWNER_ID,
+            commit_args,
+        ),
+    ];
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the reads are locked for the slot of the commit
+    let read_lock_pda = read_lock_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let read_lock_account = banks.get_account(read_lock_pda).await.unwrap().unwrap();
+    let read_lock = ReadLock::try_from_bytes_with_discriminator(&read_lock_account.data).unwrap();
+    let clock = banks.get_sysvar::<Clock>().await.unwrap();
+    assert!(read_lock.is_locked(clock.slot));
+    assert_eq!(read_lock.nonce, 1);
+}
+
+#[tokio::test]
+async fn test_commit_finalize_with_pending_commit() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env(true, None).await;
+
+    let commit_args = CommitStateArgs {
+        data: vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9],
+        nonce: 1,
+        allow_undelegation: false,
+        lamports: 1_000_000,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
+    };
+
+    // The pending commit must be finalized first
+    let ix = dlp::instruction_builder::commit_finalize(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_args,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_commit_finalize_without_feature_gates() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env(false, None).await;
+    let commit_finalize = |nonce: u64| {
+        dlp::instruction_builder::commit_finalize(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            DELEGATED_PDA_OWNER_ID,
+            CommitStateArgs {
+                data: vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9],
+                nonce,
+                allow_undelegation: false,
+                lamports: 1_000_000,
+                er_block_hash: None,
+                base_state_hash: None,
+                escrow_spend: None,
+                encoding: Encoding::Raw,
+            },
+        )
+    };
+
+    // Disable the instruction, rejected when the feature gates PDA is passed
+    let ix = dlp::instruction_builder::set_feature_gate(
+        authority.pubkey(),
+        DlpDiscriminator::CommitFinalize as u8,
+        false,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    banks.process_transaction(tx).await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[commit_finalize(1)],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InstructionDisabled as u32)
+        )
+    );
+
+    // The validator clients sending the instruction before it was gated omit the feature
+    // gates PDA, the instruction being enabled for them
+    let mut ix = commit_finalize(1);
+    assert_eq!(ix.accounts.pop().unwrap().pubkey, FEATURE_GATES_PDA);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+}
+
+#[tokio::test]
+async fn test_commit_finalize_with_max_account_size() {
+    let new_state = vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9];
+    let commit_finalize = |authority: &Keypair, blockhash: Hash| {
+        let ix = dlp::instruction_builder::commit_finalize(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            DELEGATED_PDA_OWNER_ID,
+            CommitStateArgs {
+                data: new_state.clone(),
+                nonce: 1,
+                allow_undelegation: false,
+                lamports: 1_000_000,
+                er_block_hash: None,
+                base_state_hash: None,
+                escrow_spend: None,
+                encoding: Encoding::Raw,
+            },
+        );
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&authority.pubkey()),
+            &[authority],
+            blockhash,
+        )
+    };
+
+    // A state longer than the cap is rejected
+    let (banks, _, authority, blockhash) = setup_program_test_env(
+        false,
+        Some(MaxAccountSize {
+            max_len: 4,
+            policy: OversizedStatePolicy::Reject,
+        }),
+    )
+    .await;
+    let err = banks
+        .process_transaction(commit_finalize(&authority, blockhash))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::CommittedDataTooLarge as u32)
+        )
+    );
+
+    // Or truncated to the cap
+    let (banks, _, authority, blockhash) = setup_program_test_env(
+        false,
+        Some(MaxAccountSize {
+            max_len: 4,
+            policy: OversizedStatePolicy::Truncate,
+        }),
+    )
+    .await;
+    banks
+        .process_transaction(commit_finalize(&authority, blockhash))
+        .await
+        .unwrap();
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.data, new_state[..4]);
+}
+
+async fn setup_program_test_env(
+    with_pending_commit: bool,
+    max_account_size: Option<MaxAccountSize>,
+) -> (BanksClient, Keypair, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let validator_keypair = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator_keypair.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account metadata PDA
+    let delegation_metadata_data = match max_account_size {
+        Some(max_account_size) => create_delegation_metadata_data_with_max_account_size(
+            validator_keypair.pubkey(),
+            max_account_size,
+        ),
+        None => get_delegation_metadata_data(validator_keypair.pubkey(), None),
+    };
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA
+    let delegation_record_data = get_delegation_record_data(validator_keypair.pubkey(), None);
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator_keypair.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a pending commit record
+    if with_pending_commit {
+        let commit_record_data = get_commit_record_account_data(validator_keypair.pubkey());
+        program_test.add_account(
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Account {
+                lamports: Rent::default().minimum_balance(commit_record_data.len()),
+                data: commit_record_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let (banks, payer, blockhash) = program_test.start().await;
+    (banks, payer, validator_keypair, blockhash)
+}
@@ -0,0 +1,288 @@
+use dlp::args::{CommitStateArgs, Encoding};
+use dlp::consts::OWNER_COMMIT_NONCE_FLAG;
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{CommitRecord, CommitStateLayout, DelegationMetadata, PendingState};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_metadata_data_on_curve,
+    get_delegation_record_data, get_delegation_record_on_curve_data, COMMIT_NEW_STATE_ACCOUNT_DATA,
+    DELEGATED_PDA, DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, ON_CURVE_KEYPAIR, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_finalize_owner_commit() {
+    // Setup
+    let (mut context, validator, payer) = setup_program_test_env().await;
+    let payer_lamports = get_lamports(&mut context, payer.pubkey()).await;
+    let rent_advanced = owner_commit_rent();
+
+    // Finalize the commit pushed by the owner program, refunding its payer
+    let ix = dlp::instruction_builder::finalize_owner_commit(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        payer.pubkey(),
+    );
+    let res = process(&mut context, &[ix], &validator).await;
+    assert!(res.is_ok());
+
+    // The pushed state is applied as is
+    let delegated_account = context
+        .banks_client
+        .get_account(DELEGATED_PDA_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegated_account.data, COMMIT_NEW_STATE_ACCOUNT_DATA);
+    assert_eq!(
+        get_lamports(&mut context, payer.pubkey()).await,
+        payer_lamports + rent_advanced
+    );
+
+    // The owner commit took the next nonce of the sequence
+    let delegation_metadata_account = context
+        .banks_client
+        .get_account(delegation_metadata_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert_eq!(delegation_metadata.last_update_nonce, 1);
+
+    // A commit of the validator which did not fold the pushed state in is rejected, as well
+    // as a validator commit with a nonce reserved to the owner commits
+    for nonce in [1, OWNER_COMMIT_NONCE_FLAG | 2] {
+        let ix = commit_state(&validator, nonce);
+        let res = process(&mut context, &[ix], &validator).await;
+        assert_dlp_error(res, DlpError::NonceOutOfOrder);
+    }
+
+    // The next commit of the validator acknowledges the pushed state
+    let ix = commit_state(&validator, 2);
+    let res = process(&mut context, &[ix], &validator).await;
+    assert!(res.is_ok());
+}
+
+#[tokio::test]
+async fn test_finalize_owner_commit_without_payer() {
+    // Setup
+    let (mut context, validator, _) = setup_program_test_env().await;
+
+    // The payer of the owner commit must be refunded
+    let ix = dlp::instruction_builder::finalize(validator.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix], &validator).await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_commit_from_owner_of_on_curve_account() {
+    // Setup
+    let (mut context, validator, _) = setup_program_test_env().await;
+    let on_curve = Keypair::from_bytes(&ON_CURVE_KEYPAIR).unwrap();
+
+    // An on curve account signing for itself has no owner program
+    let ix = dlp::instruction_builder::commit_from_owner(
+        validator.pubkey(),
+        on_curve.pubkey(),
+        OWNER_COMMIT_NONCE_FLAG | 1,
+        vec![],
+    );
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator, &on_curve],
+        blockhash,
+    );
+    let res = context.banks_client.process_transaction(tx).await;
+    assert_dlp_error(res, DlpError::InvalidOwnerCommit);
+}
+
+fn commit_state(validator: &Keypair, nonce: u64) -> Instruction {
+    dlp::instruction_builder::commit_state(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: DELEGATED_PDA.to_vec(),
+            nonce,
+            allow_undelegation: false,
+            lamports: LAMPORTS_PER_SOL,
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+/// The rent of the commit PDAs advanced by the payer of the owner commit
+fn owner_commit_rent() -> u64 {
+    Rent::default().minimum_balance(
+        PendingState::size_with_discriminator() + COMMIT_NEW_STATE_ACCOUNT_DATA.len(),
+    ) + Rent::default().minimum_balance(CommitRecord::size_with_discriminator())
+}
+
+async fn get_lamports(context: &mut ProgramTestContext, pubkey: Pubkey) -> u64 {
+    context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let payer = Keypair::new();
+
+    for pubkey in [validator.pubkey(), payer.pubkey()] {
+        program_test.add_account(
+            pubkey,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Setup a delegated PDA, its lamports all recorded by its delegation record
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        DELEGATED_PDA.to_vec(),
+    );
+    let delegation_record_data =
+        get_delegation_record_data(validator.pubkey(), Some(LAMPORTS_PER_SOL));
+    add_rent_exempt_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        delegation_record_data,
+    );
+    let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), None);
+    add_rent_exempt_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        delegation_metadata_data,
+    );
+    add_dlp_account(
+        &mut program_test,
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+
+    // Setup a commit pushed by the owner program, the payer advancing the rent of its PDAs
+    add_rent_exempt_dlp_account(
+        &mut program_test,
+        commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        COMMIT_NEW_STATE_ACCOUNT_DATA.to_vec(),
+    );
+    let commit_record = CommitRecord {
+        identity: validator.pubkey(),
+        account: DELEGATED_PDA_ID,
+        nonce: OWNER_COMMIT_NONCE_FLAG | 1,
+        lamports: LAMPORTS_PER_SOL,
+        slot: 0,
+        er_block_hash: [0; 32],
+        rent_advanced: owner_commit_rent(),
+        escrow: payer.pubkey(),
+        escrow_spend: 0,
+        has_escrow_spend: 0,
+        commit_state_layout: CommitStateLayout::Raw.into(),
+        padding: [0; 6],
+    };
+    let mut commit_record_data = vec![0u8; CommitRecord::size_with_discriminator()];
+    commit_record
+        .to_bytes_with_discriminator(&mut commit_record_data)
+        .unwrap();
+    add_rent_exempt_dlp_account(
+        &mut program_test,
+        commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        commit_record_data,
+    );
+
+    // Setup a delegated on curve account
+    let on_curve = Keypair::from_bytes(&ON_CURVE_KEYPAIR).unwrap();
+    add_dlp_account(
+        &mut program_test,
+        on_curve.pubkey(),
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+    add_rent_exempt_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&on_curve.pubkey()),
+        get_delegation_record_on_curve_data(validator.pubkey(), Some(LAMPORTS_PER_SOL)),
+    );
+    add_rent_exempt_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&on_curve.pubkey()),
+        get_delegation_metadata_data_on_curve(validator.pubkey(), None),
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, validator, payer)
+}
+
+fn add_rent_exempt_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, data: Vec<u8>) {
+    let lamports = Rent::default().minimum_balance(data.len());
+    add_dlp_account(program_test, pubkey, lamports, data);
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
@@ -1,10 +1,10 @@
-use dlp::args::CommitStateArgs;
+use dlp::args::{CommitStateArgs, Encoding};
 use dlp::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
     validator_fees_vault_pda_from_validator,
 };
-use dlp::state::{CommitRecord, DelegationMetadata};
+use dlp::state::{CommitRecord, CommitStateLayout, DelegationMetadata, PendingState};
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{BanksClient, ProgramTest};
@@ -32,6 +32,10 @@ async fn test_commit_on_curve() {
         nonce: 1,
         allow_undelegation: true,
         lamports: new_account_balance,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
     };
 
     // Commit the state for the delegated account
@@ -54,7 +58,11 @@ async fn test_commit_on_curve() {
     // Assert the state commitment was created and contains the new state
     let commit_state_pda = commit_state_pda_from_delegated_account(&payer_delegated.pubkey());
     let commit_state_account = banks.get_account(commit_state_pda).await.unwrap().unwrap();
-    assert!(commit_state_account.data.is_empty());
+    assert!(
+        PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+            .unwrap()
+            .is_empty()
+    );
 
     // Assert the record about the commitment exists
     let commit_record_pda = commit_record_pda_from_delegated_account(&payer_delegated.pubkey());
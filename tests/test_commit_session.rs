@@ -0,0 +1,249 @@
+use dlp::args::{CommitStateArgs, Encoding};
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::CommitRecord;
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+const OTHER_DELEGATED_PDA_ID: Pubkey =
+    solana_program::pubkey!("9FMBeNYZfGfBB8CZV8fwpMiKxUK6BWrrnE5WAUNVDrQR");
+
+#[tokio::test]
+async fn test_commit_session() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    // Commit both delegated accounts in a session, the builder sorts the commits
+    let ixs = dlp::instruction_builder::commit_session(
+        authority.pubkey(),
+        vec![
+            commit_ix(&authority, DELEGATED_PDA_ID),
+            commit_ix(&authority, OTHER_DELEGATED_PDA_ID),
+        ],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the commits of both accounts were created
+    for delegated_account in [DELEGATED_PDA_ID, OTHER_DELEGATED_PDA_ID] {
+        let commit_record_pda = commit_record_pda_from_delegated_account(&delegated_account);
+        let commit_record_account = banks.get_account(commit_record_pda).await.unwrap().unwrap();
+        let commit_record =
+            CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+        assert_eq!(commit_record.account, delegated_account);
+        assert_eq!(commit_record.identity, authority.pubkey());
+    }
+}
+
+#[tokio::test]
+async fn test_commit_session_unordered_commits() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let mut commits = vec![
+        commit_ix(&authority, DELEGATED_PDA_ID),
+        commit_ix(&authority, OTHER_DELEGATED_PDA_ID),
+    ];
+    commits.sort_by_key(|ix| std::cmp::Reverse(ix.accounts[1].pubkey));
+    let mut ixs = vec![dlp::instruction_builder::commit_session_begin(
+        authority.pubkey(),
+    )];
+    ixs.extend(commits);
+    ixs.push(dlp::instruction_builder::commit_session_end(
+        authority.pubkey(),
+    ));
+
+    let res = process(&banks, &authority, &ixs, blockhash).await;
+    assert_invalid_commit_session(res, 0);
+    assert_no_commit(&banks).await;
+}
+
+#[tokio::test]
+async fn test_commit_session_not_closed() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ixs = [
+        dlp::instruction_builder::commit_session_begin(authority.pubkey()),
+        commit_ix(&authority, DELEGATED_PDA_ID),
+    ];
+
+    let res = process(&banks, &authority, &ixs, blockhash).await;
+    assert_invalid_commit_session(res, 0);
+    assert_no_commit(&banks).await;
+}
+
+#[tokio::test]
+async fn test_commit_session_rejects_finalize() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ixs = [
+        dlp::instruction_builder::commit_session_begin(authority.pubkey()),
+        commit_ix(&authority, DELEGATED_PDA_ID),
+        dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID),
+        dlp::instruction_builder::commit_session_end(authority.pubkey()),
+    ];
+
+    let res = process(&banks, &authority, &ixs, blockhash).await;
+    assert_invalid_commit_session(res, 0);
+    assert_no_commit(&banks).await;
+}
+
+#[tokio::test]
+async fn test_commit_session_end_without_begin() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ixs = [
+        commit_ix(&authority, DELEGATED_PDA_ID),
+        dlp::instruction_builder::commit_session_end(authority.pubkey()),
+    ];
+
+    let res = process(&banks, &authority, &ixs, blockhash).await;
+    assert_invalid_commit_session(res, 1);
+    assert_no_commit(&banks).await;
+}
+
+fn commit_ix(authority: &Keypair, delegated_account: Pubkey) -> Instruction {
+    dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        delegated_account,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9],
+            nonce: 1,
+            allow_undelegation: false,
+            lamports: LAMPORTS_PER_SOL,
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+async fn process(
+    banks: &BanksClient,
+    authority: &Keypair,
+    ixs: &[Instruction],
+    blockhash: Hash,
+) -> Result<(), BanksClientError> {
+    let tx =
+        Transaction::new_signed_with_payer(ixs, Some(&authority.pubkey()), &[authority], blockhash);
+    banks.process_transaction(tx).await
+}
+
+fn assert_invalid_commit_session(res: Result<(), BanksClientError>, index: u8) {
+    let err = res.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            index,
+            InstructionError::Custom(dlp::error::DlpError::InvalidCommitSession as u32)
+        )
+    );
+}
+
+async fn assert_no_commit(banks: &BanksClient) {
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    assert!(banks.get_account(commit_state_pda).await.unwrap().is_none());
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let validator_keypair = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator_keypair.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    for delegated_account in [DELEGATED_PDA_ID, OTHER_DELEGATED_PDA_ID] {
+        // Setup a delegated PDA
+        program_test.add_account(
+            delegated_account,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        // Setup the delegated account metadata PDA
+        let delegation_metadata_data =
+            get_delegation_metadata_data(validator_keypair.pubkey(), None);
+        program_test.add_account(
+            delegation_metadata_pda_from_delegated_account(&delegated_account),
+            Account {
+                lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+                data: delegation_metadata_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        // Setup the delegated record PDA
+        let delegation_record_data = get_delegation_record_data(validator_keypair.pubkey(), None);
+        program_test.add_account(
+            delegation_record_pda_from_delegated_account(&delegated_account),
+            Account {
+                lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+                data: delegation_record_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator_keypair.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, payer, blockhash) = program_test.start().await;
+    (banks, payer, validator_keypair, blockhash)
+}
@@ -1,22 +1,34 @@
-use dlp::args::CommitStateArgs;
+use dlp::args::{
+    commit_state_hash, CommitStateArgs, Encoding, ErBlockHash, SetCommitScheduleArgs,
+    SetValidatorCommitQuotaArgs,
+};
+use dlp::error::DlpError;
+use dlp::instruction_builder::compress_commit_state_args;
 use dlp::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
-    validator_fees_vault_pda_from_validator,
+    ephemeral_balance_pda_from_payer, validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{
+    CommitRecord, CommitStateLayout, DelegationMetadata, PendingState, ValidatorFeesVault,
+    PENDING_STATE_VERSION,
 };
-use dlp::state::{CommitRecord, DelegationMetadata};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
-use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
-use solana_program_test::{BanksClient, ProgramTest};
+use solana_program::{
+    hash::Hash, native_token::LAMPORTS_PER_SOL, system_instruction, system_program,
+};
+use solana_program_test::{BanksClient, BanksClientError, ProgramTest};
 use solana_sdk::{
     account::Account,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 use crate::fixtures::{
-    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
-    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+    get_delegation_metadata_data, get_delegation_metadata_data_with_data_hash,
+    get_delegation_record_data, DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
 };
 
 mod fixtures;
@@ -33,6 +45,10 @@ async fn test_commit_new_state() {
         nonce: 1,
         allow_undelegation: true,
         lamports: new_account_balance,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
     };
 
     // Commit the state for the delegated account
@@ -55,7 +71,33 @@ async fn test_commit_new_state() {
     // Assert the state commitment was created and contains the new state
     let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
     let commit_state_account = banks.get_account(commit_state_pda).await.unwrap().unwrap();
-    assert_eq!(commit_state_account.data, new_state.clone());
+    assert_eq!(
+        PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+            .unwrap(),
+        new_state.clone()
+    );
+
+    // The pending state header describes the committed state to the composing programs
+    let (pending_state, data) = PendingState::read_account(
+        &DELEGATED_PDA_ID,
+        &commit_state_pda,
+        &commit_state_account.owner,
+        &commit_state_account.data,
+    )
+    .unwrap();
+    assert_eq!(data, new_state.as_slice());
+    assert_eq!(pending_state.owner, DELEGATED_PDA_OWNER_ID);
+    assert_eq!(pending_state.nonce, 1);
+    assert_eq!(pending_state.version, PENDING_STATE_VERSION);
+    assert!(pending_state.allows_undelegation());
+    assert!(!pending_state.is_from_owner());
+    assert!(PendingState::read_account(
+        &DELEGATED_PDA_ID,
+        &DELEGATED_PDA_ID,
+        &commit_state_account.owner,
+        &commit_state_account.data,
+    )
+    .is_none());
 
     // Check that the commit has enough collateral to finalize the proposed state diff
     let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
@@ -97,6 +139,10 @@ async fn test_commit_out_of_order() {
         nonce: 101,
         allow_undelegation: true,
         lamports: new_account_balance,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
     };
 
     // Commit the state for the delegated account
@@ -119,7 +165,562 @@ async fn test_commit_out_of_order() {
     );
 }
 
+#[tokio::test]
+async fn test_commit_with_er_block_hash() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    // Commit and finalize a state produced by the first ER block
+    let ix = commit_with_er_block_hash(&authority, 1, [1; 32]);
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_ok());
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_record_account = banks.get_account(commit_record_pda).await.unwrap().unwrap();
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+    assert_eq!(commit_record.er_block_hash(), Some([1; 32]));
+
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_ok());
+    let delegation_metadata_pda = delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let delegation_metadata_account = banks
+        .get_account(delegation_metadata_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert_eq!(delegation_metadata.last_er_block_hash, Some([1; 32]));
+
+    // The next commit cannot be bound to the same ER block
+    let ix = commit_with_er_block_hash(&authority, 2, [1; 32]);
+    let err = process(&banks, &authority, &[ix], blockhash)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::ErBlockHashNotChanged as u32)
+        )
+    );
+    let ix = commit_with_er_block_hash(&authority, 2, [2; 32]);
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_ok());
+}
+
+#[tokio::test]
+async fn test_commit_with_base_state_hash() {
+    // Setup, the delegated account being empty at delegation
+    let authority = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let (banks, _, authority, blockhash) = setup_program_test_env_with_metadata(
+        get_delegation_metadata_data_with_data_hash(authority.pubkey(), &[]),
+    )
+    .await;
+
+    // The validator did not start from the delegated data
+    let ix = commit_with_base_state_hash(&authority, 1, commit_state_hash(&[0]));
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert_base_state_hash_mismatch(res);
+
+    // The first commit starts from the delegated data
+    let ix = commit_with_base_state_hash(&authority, 1, commit_state_hash(&[]));
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_ok());
+
+    // The next commits cannot claim a base state
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_ok());
+    let ix = commit_with_base_state_hash(&authority, 2, commit_state_hash(&[1, 2, 3]));
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert_base_state_hash_mismatch(res);
+}
+
+#[tokio::test]
+async fn test_commit_with_base_state_hash_not_recorded() {
+    // Setup, the delegation did not record the hash of the delegated data
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ix = commit_with_base_state_hash(&authority, 1, commit_state_hash(&[]));
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert_base_state_hash_mismatch(res);
+}
+
+fn assert_base_state_hash_mismatch(res: Result<(), BanksClientError>) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::BaseStateHashMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_commit_compressed_state() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let new_state = [vec![7; 600], (0..200).map(|i| i as u8).collect()].concat();
+    let commit_args = |data: Vec<u8>, encoding: Encoding| CommitStateArgs {
+        data,
+        nonce: 1,
+        allow_undelegation: false,
+        lamports: Rent::default().minimum_balance(new_state.len()),
+        encoding,
+        ..Default::default()
+    };
+
+    let compressed = compress_commit_state_args(commit_args(new_state.clone(), Encoding::Raw));
+    assert_eq!(compressed.encoding, Encoding::Lz4);
+    assert!(compressed.data.len() < new_state.len() / 2);
+
+    // The compressed state must decompress to the length it declares
+    let mut malformed = compressed.data.clone();
+    malformed[..4].copy_from_slice(&(new_state.len() as u32 + 1).to_le_bytes());
+    let ix = dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_args(malformed, Encoding::Lz4),
+    );
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InvalidCompressedState as u32)
+        )
+    );
+
+    let ix = dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        compressed,
+    );
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_ok());
+
+    // The commit state holds the decompressed state, finalized as is
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_state_account = banks.get_account(commit_state_pda).await.unwrap().unwrap();
+    assert_eq!(
+        PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+            .unwrap(),
+        new_state
+    );
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_ok());
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.data, new_state);
+}
+
+#[tokio::test]
+async fn test_commit_rent_refunded_at_finalize() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let validator_lamports = get_lamports(&banks, authority.pubkey()).await;
+    let delegation_metadata_pda = delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let metadata_lamports = get_lamports(&banks, delegation_metadata_pda).await;
+    let mut fees = 0;
+
+    // Commit without an ER block hash: the validator advances the rent of the commit PDAs
+    let ix = dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![1, 2, 3],
+            nonce: 1,
+            allow_undelegation: false,
+            lamports: Rent::default().minimum_balance(500),
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    );
+    fees += process_with_fee(&banks, &authority, ix, blockhash).await;
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_record_account = banks.get_account(commit_record_pda).await.unwrap().unwrap();
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+    assert_eq!(
+        commit_record.rent_advanced,
+        Rent::default().minimum_balance(PendingState::size_with_discriminator() + 3)
+            + Rent::default().minimum_balance(CommitRecord::size_with_discriminator())
+    );
+
+    // Finalize refunds it exactly
+    let ix = dlp::instruction_builder::finalize_settled(authority.pubkey(), DELEGATED_PDA_ID);
+    fees += process_with_fee(&banks, &authority, ix, blockhash).await;
+    assert_eq!(
+        get_lamports(&banks, authority.pubkey()).await,
+        validator_lamports - fees
+    );
+
+    // A commit with an ER block hash also pays the growth of the delegation metadata
+    let ix = commit_with_er_block_hash(&authority, 2, [1; 32]);
+    fees += process_with_fee(&banks, &authority, ix, blockhash).await;
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    fees += process_with_fee(&banks, &authority, ix, blockhash).await;
+    let metadata_growth = get_lamports(&banks, delegation_metadata_pda).await - metadata_lamports;
+    assert!(metadata_growth > 0);
+    assert_eq!(
+        get_lamports(&banks, authority.pubkey()).await,
+        validator_lamports - fees - metadata_growth
+    );
+}
+
+#[tokio::test]
+async fn test_commit_schedule_funded_by_escrow() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let escrow = ephemeral_balance_pda_from_payer(&authority.pubkey(), 0);
+    let commit_fee_lamports = 1_000;
+
+    // The rent payer funds its escrow and schedules the commits
+    let ixs = [
+        system_instruction::transfer(&authority.pubkey(), &escrow, LAMPORTS_PER_SOL),
+        dlp::instruction_builder::set_commit_schedule(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            SetCommitScheduleArgs {
+                escrow_index: 0,
+                interval_slots: 1_000_000,
+                max_commits_per_interval: 1,
+                commit_fee_lamports,
+            },
+        ),
+    ];
+    process(&banks, &authority, &ixs, blockhash).await.unwrap();
+    let delegation_metadata_pda = delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let delegation_metadata_account = banks
+        .get_account(delegation_metadata_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert!(delegation_metadata.commit_scheduled);
+
+    // Commits must pass the commit schedule
+    let ix = commit_with_nonce(&authority, 1);
+    let err = process(&banks, &authority, &[ix.clone()], blockhash)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InvalidCommitSchedule as u32)
+        )
+    );
+
+    // The escrow refunds the validator the rent of the commit and pays it the fee
+    let escrow_lamports = get_lamports(&banks, escrow).await;
+    let ix = dlp::instruction_builder::with_commit_schedule(ix, DELEGATED_PDA_ID, escrow);
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_record_account = banks.get_account(commit_record_pda).await.unwrap().unwrap();
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+    assert_eq!(commit_record.escrow(), Some(escrow));
+    assert_eq!(
+        get_lamports(&banks, escrow).await,
+        escrow_lamports - commit_record.rent_advanced - commit_fee_lamports
+    );
+
+    // Finalize refunds the rent to the escrow
+    let ix = dlp::instruction_builder::with_commit_escrow(
+        dlp::instruction_builder::finalize_settled(authority.pubkey(), DELEGATED_PDA_ID),
+        escrow,
+    );
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+    assert_eq!(
+        get_lamports(&banks, escrow).await,
+        escrow_lamports - commit_fee_lamports
+    );
+
+    // The commits of the interval are exhausted
+    let ix = dlp::instruction_builder::with_commit_schedule(
+        commit_with_nonce(&authority, 2),
+        DELEGATED_PDA_ID,
+        escrow,
+    );
+    let err = process(&banks, &authority, &[ix], blockhash)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::CommitScheduleExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_commit_from_relayer() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let relayer = Keypair::new();
+    let ix = system_instruction::transfer(&authority.pubkey(), &relayer.pubkey(), LAMPORTS_PER_SOL);
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+    let commit_from_relayer = |nonce| {
+        dlp::instruction_builder::commit_state_from_relayer(
+            relayer.pubkey(),
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            DELEGATED_PDA_OWNER_ID,
+            CommitStateArgs {
+                data: vec![1, 2, 3],
+                nonce,
+                allow_undelegation: false,
+                lamports: Rent::default().minimum_balance(500),
+                er_block_hash: None,
+                base_state_hash: None,
+                escrow_spend: None,
+                encoding: Encoding::Raw,
+            },
+        )
+    };
+
+    // The relayer is not approved yet
+    let err = process(&banks, &relayer, &[commit_from_relayer(1)], blockhash)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InvalidAuthority as u32)
+        )
+    );
+
+    // Approve the relayer, which is stored in the validator fees vault
+    let ix = dlp::instruction_builder::register_commit_relayer(
+        authority.pubkey(),
+        relayer.pubkey(),
+        true,
+    );
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&authority.pubkey());
+    let validator_fees_vault = banks
+        .get_account(validator_fees_vault_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let vault = ValidatorFeesVault::try_from_account_data(&validator_fees_vault.data).unwrap();
+    assert_eq!(vault.commit_relayers, vec![relayer.pubkey()]);
+
+    // The commit of the relayer is attributed to the validator identity
+    process(&banks, &relayer, &[commit_from_relayer(1)], blockhash)
+        .await
+        .unwrap();
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_record_account = banks.get_account(commit_record_pda).await.unwrap().unwrap();
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+    assert_eq!(commit_record.identity, authority.pubkey());
+
+    // Revoke the relayer, refunding the rent of its entry
+    let ix = dlp::instruction_builder::register_commit_relayer(
+        authority.pubkey(),
+        relayer.pubkey(),
+        false,
+    );
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+    let validator_fees_vault = banks
+        .get_account(validator_fees_vault_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let vault = ValidatorFeesVault::try_from_account_data(&validator_fees_vault.data).unwrap();
+    assert!(vault.commit_relayers.is_empty());
+    let rent = Rent::default();
+    assert_eq!(
+        validator_fees_vault.lamports,
+        LAMPORTS_PER_SOL + rent.minimum_balance(validator_fees_vault.data.len())
+            - rent.minimum_balance(0)
+    );
+}
+
+#[tokio::test]
+async fn test_commit_quota() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    // The admin limits the validator to one commit per slot
+    let ix = dlp::instruction_builder::set_validator_commit_quota(
+        authority.pubkey(),
+        authority.pubkey(),
+        SetValidatorCommitQuotaArgs {
+            max_commits_per_slot: 1,
+            max_bytes_per_slot: 0,
+        },
+    );
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+
+    // The commits must update the quota in the validator fees vault
+    let ix = commit_with_nonce(&authority, 1);
+    let res = process(&banks, &authority, &[ix], blockhash).await;
+    assert!(res.is_err());
+    let ix = dlp::instruction_builder::with_commit_quota(
+        commit_with_nonce(&authority, 1),
+        authority.pubkey(),
+    );
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&authority.pubkey());
+    let validator_fees_vault = banks
+        .get_account(validator_fees_vault_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let vault = ValidatorFeesVault::try_from_account_data(&validator_fees_vault.data).unwrap();
+    assert_eq!(vault.commit_quota.commits_in_slot, 1);
+    assert_eq!(vault.commit_quota.bytes_in_slot, 3);
+
+    // The quota of the slot is exhausted
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    process(&banks, &authority, &[ix], blockhash).await.unwrap();
+    let ix = dlp::instruction_builder::with_commit_quota(
+        commit_with_nonce(&authority, 2),
+        authority.pubkey(),
+    );
+    let err = process(&banks, &authority, &[ix], blockhash)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::CommitQuotaExceeded as u32)
+        )
+    );
+}
+
+fn commit_with_nonce(authority: &Keypair, nonce: u64) -> Instruction {
+    dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![1, 2, 3],
+            nonce,
+            allow_undelegation: false,
+            lamports: Rent::default().minimum_balance(500),
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+fn commit_with_base_state_hash(
+    authority: &Keypair,
+    nonce: u64,
+    base_state_hash: [u8; 32],
+) -> Instruction {
+    dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![1, 2, 3],
+            nonce,
+            allow_undelegation: false,
+            lamports: Rent::default().minimum_balance(500),
+            er_block_hash: None,
+            base_state_hash: Some(base_state_hash),
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+fn commit_with_er_block_hash(
+    authority: &Keypair,
+    nonce: u64,
+    er_block_hash: ErBlockHash,
+) -> Instruction {
+    dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![1, 2, 3],
+            nonce,
+            allow_undelegation: false,
+            lamports: Rent::default().minimum_balance(500),
+            er_block_hash: Some(er_block_hash),
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+async fn process(
+    banks: &BanksClient,
+    authority: &Keypair,
+    ixs: &[Instruction],
+    blockhash: Hash,
+) -> Result<(), BanksClientError> {
+    let tx =
+        Transaction::new_signed_with_payer(ixs, Some(&authority.pubkey()), &[authority], blockhash);
+    banks.process_transaction(tx).await
+}
+
+/// Process the instruction signed by the authority, returning the fee it paid
+async fn process_with_fee(
+    banks: &BanksClient,
+    authority: &Keypair,
+    ix: Instruction,
+    blockhash: Hash,
+) -> u64 {
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[authority],
+        blockhash,
+    );
+    let fee = banks
+        .get_fee_for_message(tx.message.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    banks.process_transaction(tx).await.unwrap();
+    fee
+}
+
+async fn get_lamports(banks: &BanksClient, pubkey: Pubkey) -> u64 {
+    banks.get_account(pubkey).await.unwrap().unwrap().lamports
+}
+
 async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    let validator_keypair = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    setup_program_test_env_with_metadata(get_delegation_metadata_data(
+        validator_keypair.pubkey(),
+        None,
+    ))
+    .await
+}
+
+async fn setup_program_test_env_with_metadata(
+    delegation_metadata_data: Vec<u8>,
+) -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
 
@@ -149,7 +750,6 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
     );
 
     // Setup the delegated account metadata PDA
-    let delegation_metadata_data = get_delegation_metadata_data(validator_keypair.pubkey(), None);
     program_test.add_account(
         delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
         Account {
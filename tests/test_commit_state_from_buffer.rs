@@ -2,13 +2,13 @@ use crate::fixtures::{
     get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
     DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
 };
-use dlp::args::CommitStateFromBufferArgs;
+use dlp::args::{CommitStateFromBufferArgs, Encoding};
 use dlp::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
     validator_fees_vault_pda_from_validator,
 };
-use dlp::state::{CommitRecord, DelegationMetadata};
+use dlp::state::{CommitRecord, CommitStateLayout, DelegationMetadata, PendingState};
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{BanksClient, ProgramTest};
@@ -34,6 +34,8 @@ async fn test_commit_new_state_from_buffer() {
         nonce: 1,
         allow_undelegation: true,
         lamports: new_account_balance,
+        er_block_hash: None,
+        encoding: Encoding::Raw,
     };
 
     // Commit the state for the delegated account
@@ -57,7 +59,11 @@ async fn test_commit_new_state_from_buffer() {
     // Assert the state commitment was created and contains the new state
     let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
     let commit_state_account = banks.get_account(commit_state_pda).await.unwrap().unwrap();
-    assert_eq!(commit_state_account.data, NEW_STATE.to_vec());
+    assert_eq!(
+        PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+            .unwrap(),
+        NEW_STATE.to_vec()
+    );
 
     // Check that the commit has enough collateral to finalize the proposed state diff
     let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
@@ -0,0 +1,408 @@
+use dlp::args::{CommitStateChunkArgs, CommitStateRootArgs};
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{ChunkMerkleTree, ChunkedCommitState, PendingState};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+const CHUNK_SIZE: u32 = 900;
+
+#[tokio::test]
+async fn test_commit_state_root_and_finalize() {
+    // Setup
+    let (mut context, authority) = setup_program_test_env(vec![]).await;
+    let state = (0..5_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+
+    // Commit the root of the chunks of the state
+    let ix = commit_state_root(&authority, &[], &state);
+    let res = process(&mut context, &authority, ix).await;
+    assert!(res.is_ok());
+
+    // Assert the commit state awaits the chunks of the root
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_state_account = context
+        .banks_client
+        .get_account(commit_state_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let (chunked_commit_state, _, _) =
+        ChunkedCommitState::split(&commit_state_account.data).unwrap();
+    assert_eq!(
+        chunked_commit_state.root,
+        ChunkMerkleTree::new(&state, CHUNK_SIZE as usize).root()
+    );
+    assert_eq!(chunked_commit_state.chunks_count(), 6);
+    assert_eq!(chunked_commit_state.chunks_to_write, 6);
+    assert_eq!(chunked_commit_state.chunks_written, 0);
+    assert!(PendingState::read(&commit_state_account.data).is_none());
+
+    // Write all the chunks but the last one
+    let chunk_ixs = dlp::instruction_builder::commit_state_chunks(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        &[],
+        &state,
+        CHUNK_SIZE,
+    );
+    let (last_chunk_ix, chunk_ixs) = chunk_ixs.split_last().unwrap();
+    for ix in chunk_ixs {
+        let res = process(&mut context, &authority, ix.clone()).await;
+        assert!(res.is_ok());
+    }
+
+    // The state cannot be finalized until all of its chunks are written
+    let finalize_ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &authority, finalize_ix.clone()).await;
+    assert_dlp_error(res, DlpError::InvalidChunkedCommitState);
+
+    let res = process(&mut context, &authority, last_chunk_ix.clone()).await;
+    assert!(res.is_ok());
+    let commit_state_account = context
+        .banks_client
+        .get_account(commit_state_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let (chunked_commit_state, data, _) =
+        ChunkedCommitState::split(&commit_state_account.data).unwrap();
+    assert!(chunked_commit_state.is_complete());
+    assert_eq!(data, state.as_slice());
+
+    let res = process(&mut context, &authority, finalize_ix).await;
+    assert!(res.is_ok());
+
+    // Assert the delegated account holds the state of the root
+    let delegated_account = context
+        .banks_client
+        .get_account(DELEGATED_PDA_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegated_account.data, state);
+
+    // Assert the commit PDAs were closed
+    assert!(context
+        .banks_client
+        .get_account(commit_state_pda)
+        .await
+        .unwrap()
+        .is_none());
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    assert!(context
+        .banks_client
+        .get_account(commit_record_pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_commit_state_chunk_invalid_proof() {
+    // Setup
+    let (mut context, authority) = setup_program_test_env(vec![]).await;
+    let payer = context.payer.insecure_clone();
+    let state = vec![7; 2_000];
+    let tree = ChunkMerkleTree::new(&state, CHUNK_SIZE as usize);
+
+    let ix = commit_state_root(&authority, &[], &state);
+    let res = process(&mut context, &authority, ix).await;
+    assert!(res.is_ok());
+
+    // A chunk which is not the one of the root
+    let ix = dlp::instruction_builder::commit_state_chunk(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        CommitStateChunkArgs {
+            index: 0,
+            data: vec![8; CHUNK_SIZE as usize],
+            proof: tree.proof(0),
+        },
+    );
+    let res = process(&mut context, &authority, ix).await;
+    assert_dlp_error(res, DlpError::InvalidChunkProof);
+
+    // The chunk of another index, of the same data
+    let ix = dlp::instruction_builder::commit_state_chunk(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        CommitStateChunkArgs {
+            index: 1,
+            data: state[..CHUNK_SIZE as usize].to_vec(),
+            proof: tree.proof(0),
+        },
+    );
+    let res = process(&mut context, &authority, ix).await;
+    assert_dlp_error(res, DlpError::InvalidChunkProof);
+
+    // A chunk past the end of the state
+    let ix = dlp::instruction_builder::commit_state_chunk(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        CommitStateChunkArgs {
+            index: 3,
+            data: vec![7; 200],
+            proof: tree.proof(2),
+        },
+    );
+    let res = process(&mut context, &authority, ix).await;
+    assert_dlp_error(res, DlpError::InvalidChunkProof);
+
+    // Only the validator of the commit can write the chunks
+    let ix = dlp::instruction_builder::commit_state_chunk(
+        payer.pubkey(),
+        DELEGATED_PDA_ID,
+        CommitStateChunkArgs {
+            index: 0,
+            data: state[..CHUNK_SIZE as usize].to_vec(),
+            proof: tree.proof(0),
+        },
+    );
+    let res = process(&mut context, &payer, ix).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+
+    // No chunk was written
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_state_account = context
+        .banks_client
+        .get_account(commit_state_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let (chunked_commit_state, _, bitmap) =
+        ChunkedCommitState::split(&commit_state_account.data).unwrap();
+    assert_eq!(chunked_commit_state.chunks_written, 0);
+    assert_eq!(bitmap, &[0]);
+}
+
+#[tokio::test]
+async fn test_commit_state_root_unchanged_chunks() {
+    // Setup a delegated account holding a state of which only the third chunk changes
+    let delegated_data = (0..5_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let (mut context, authority) = setup_program_test_env(delegated_data.clone()).await;
+    let mut state = delegated_data.clone();
+    state[2_000] ^= 1;
+    state.extend_from_slice(&[9; 100]);
+
+    // Commit the root of the chunks of the state
+    let ix = commit_state_root(&authority, &delegated_data, &state);
+    let res = process(&mut context, &authority, ix).await;
+    assert!(res.is_ok());
+
+    // Assert the commit state is pre-filled with the data of the delegated account
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_state_account = context
+        .banks_client
+        .get_account(commit_state_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let (chunked_commit_state, data, _) =
+        ChunkedCommitState::split(&commit_state_account.data).unwrap();
+    assert_eq!(chunked_commit_state.chunks_count(), 6);
+    assert_eq!(chunked_commit_state.chunks_to_write, 2);
+    assert_eq!(&data[..delegated_data.len()], delegated_data.as_slice());
+    assert_eq!(&data[delegated_data.len()..], &[0; 100]);
+
+    // Only the changed chunk and the last one, grown past the delegated data, are written
+    let chunk_ixs = dlp::instruction_builder::commit_state_chunks(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        &delegated_data,
+        &state,
+        CHUNK_SIZE,
+    );
+    assert_eq!(chunk_ixs.len(), 2);
+    let (last_chunk_ix, chunk_ixs) = chunk_ixs.split_last().unwrap();
+    for ix in chunk_ixs {
+        let res = process(&mut context, &authority, ix.clone()).await;
+        assert!(res.is_ok());
+    }
+
+    // The state cannot be finalized until its changed chunks are written
+    let finalize_ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &authority, finalize_ix.clone()).await;
+    assert_dlp_error(res, DlpError::InvalidChunkedCommitState);
+
+    let res = process(&mut context, &authority, last_chunk_ix.clone()).await;
+    assert!(res.is_ok());
+    let res = process(&mut context, &authority, finalize_ix).await;
+    assert!(res.is_ok());
+
+    // Assert the delegated account holds the state of the root
+    let delegated_account = context
+        .banks_client
+        .get_account(DELEGATED_PDA_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegated_account.data, state);
+}
+
+#[tokio::test]
+async fn test_commit_state_root_empty_chunks() {
+    // Setup
+    let (mut context, authority) = setup_program_test_env(vec![]).await;
+
+    let ix = dlp::instruction_builder::commit_state_root(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateRootArgs {
+            nonce: 1,
+            lamports: LAMPORTS_PER_SOL,
+            data_len: 1_000,
+            chunk_size: 0,
+            ..Default::default()
+        },
+    );
+    let res = process(&mut context, &authority, ix).await;
+    assert_dlp_error(res, DlpError::InvalidChunkedCommitState);
+}
+
+#[tokio::test]
+async fn test_commit_state_root_too_many_chunks_to_write() {
+    // Setup
+    let (mut context, authority) = setup_program_test_env(vec![]).await;
+    let state = vec![7; 2_000];
+
+    let mut ix = commit_state_root(&authority, &[], &state);
+    let mut args = CommitStateRootArgs::try_from_instruction_data(&ix.data[8..]).unwrap();
+    args.chunks_to_write = 4;
+    ix.data = [&ix.data[..8], &args.to_instruction_data()[..]].concat();
+    let res = process(&mut context, &authority, ix).await;
+    assert_dlp_error(res, DlpError::InvalidChunkedCommitState);
+}
+
+fn commit_state_root(authority: &Keypair, delegated_data: &[u8], state: &[u8]) -> Instruction {
+    let chunks_to_write = dlp::instruction_builder::commit_state_chunks(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        delegated_data,
+        state,
+        CHUNK_SIZE,
+    )
+    .len() as u32;
+    dlp::instruction_builder::commit_state_root(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateRootArgs {
+            nonce: 1,
+            lamports: LAMPORTS_PER_SOL,
+            allow_undelegation: false,
+            root: ChunkMerkleTree::new(state, CHUNK_SIZE as usize).root(),
+            data_len: state.len() as u64,
+            chunk_size: CHUNK_SIZE,
+            chunks_to_write,
+            er_block_hash: None,
+        },
+    )
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    signer: &Keypair,
+    ix: Instruction,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env(delegated_data: Vec<u8>) -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let validator_keypair = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator_keypair.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: delegated_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account metadata PDA
+    let delegation_metadata_data = get_delegation_metadata_data(validator_keypair.pubkey(), None);
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA
+    let delegation_record_data = get_delegation_record_data(validator_keypair.pubkey(), None);
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator_keypair.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, validator_keypair)
+}
@@ -1,18 +1,25 @@
-use dlp::args::CommitStateArgs;
+use dlp::args::{CommitStateArgs, Encoding};
+use dlp::error::DlpError;
 use dlp::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
     program_config_from_program_id, validator_fees_vault_pda_from_validator,
+    validator_whitelist_shard_pda_from_program_id,
 };
-use dlp::state::{CommitRecord, DelegationMetadata};
-use fixtures::create_program_config_data;
+use dlp::state::{
+    CommitRecord, CommitStateLayout, DelegationMetadata, PendingState, ValidatorWhitelistShard,
+};
+use fixtures::{
+    create_program_config_data_with_data_lens, create_program_config_data_without_whitelist,
+};
+use solana_program::instruction::InstructionError;
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{BanksClient, ProgramTest};
 use solana_sdk::{
     account::Account,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 use crate::fixtures::{
@@ -24,17 +31,119 @@ mod fixtures;
 
 #[tokio::test]
 async fn test_commit_new_state_valid_config() {
-    test_commit_new_state(true).await
+    test_commit_new_state(true, vec![]).await
 }
 
 #[tokio::test]
 async fn test_commit_new_state_invalid_config() {
-    test_commit_new_state(false).await
+    test_commit_new_state(false, vec![]).await
+}
+
+#[tokio::test]
+async fn test_commit_new_state_allowed_data_len() {
+    test_commit_new_state(true, vec![4, 10]).await
+}
+
+#[tokio::test]
+async fn test_commit_new_state_disallowed_data_len() {
+    test_commit_new_state(false, vec![4, 32]).await
+}
+
+#[tokio::test]
+async fn test_commit_new_state_whitelisted_by_shard() {
+    // Setup, the program config whitelisting another validator
+    let (banks, _, authority, blockhash) = setup_program_test_env(false, vec![], true, true).await;
+    let commit_args = || CommitStateArgs {
+        data: vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9],
+        nonce: 1,
+        allow_undelegation: false,
+        lamports: 1_000_000,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
+    };
+
+    // The validator is not whitelisted without its shard
+    let ix = dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_args(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InvalidWhitelistProgramConfig as u32)
+        )
+    );
+
+    let ix = dlp::instruction_builder::commit_state_with_whitelist_shard(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_args(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_record_account = banks.get_account(commit_record_pda).await.unwrap().unwrap();
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+    assert_eq!(commit_record.identity, authority.pubkey());
 }
 
-async fn test_commit_new_state(valid_config: bool) {
+#[tokio::test]
+async fn test_commit_new_state_without_whitelist() {
+    // Setup, the program config created for its other settings whitelisting no validator
+    let (banks, _, authority, blockhash) =
+        setup_program_test_env(false, vec![], false, false).await;
+
+    // Any validator can commit
+    let ix = dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9],
+            nonce: 1,
+            allow_undelegation: false,
+            lamports: 1_000_000,
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+}
+
+async fn test_commit_new_state(valid_config: bool, allowed_data_lens: Vec<u32>) {
     // Setup
-    let (banks, _, authority, blockhash) = setup_program_test_env(valid_config).await;
+    let (banks, _, authority, blockhash) =
+        setup_program_test_env(valid_config, allowed_data_lens, false, true).await;
     let new_state = vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9];
 
     let new_account_balance = 1_000_000;
@@ -43,6 +152,10 @@ async fn test_commit_new_state(valid_config: bool) {
         nonce: 1,
         allow_undelegation: true,
         lamports: new_account_balance,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
     };
 
     // Commit the state for the delegated account
@@ -68,7 +181,11 @@ async fn test_commit_new_state(valid_config: bool) {
         // Assert the state commitment was created and contains the new state
         let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
         let commit_state_account = banks.get_account(commit_state_pda).await.unwrap().unwrap();
-        assert_eq!(commit_state_account.data, new_state.clone());
+        assert_eq!(
+            PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+                .unwrap(),
+            new_state.clone()
+        );
 
         // Check that the commit has enough collateral to finalize the proposed state diff
         let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
@@ -98,7 +215,12 @@ async fn test_commit_new_state(valid_config: bool) {
     }
 }
 
-async fn setup_program_test_env(valid_config: bool) -> (BanksClient, Keypair, Keypair, Hash) {
+async fn setup_program_test_env(
+    valid_config: bool,
+    allowed_data_lens: Vec<u32>,
+    whitelist_shard: bool,
+    whitelist: bool,
+) -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
 
@@ -166,11 +288,16 @@ async fn setup_program_test_env(valid_config: bool) -> (BanksClient, Keypair, Ke
     );
 
     // Setup the program config
-    let program_config_data = create_program_config_data(if valid_config {
+    let approved_validator = if valid_config || !allowed_data_lens.is_empty() {
         validator_keypair.pubkey()
     } else {
         Keypair::new().pubkey()
-    });
+    };
+    let program_config_data = if whitelist {
+        create_program_config_data_with_data_lens(approved_validator, allowed_data_lens)
+    } else {
+        create_program_config_data_without_whitelist(allowed_data_lens)
+    };
     program_test.add_account(
         program_config_from_program_id(&DELEGATED_PDA_OWNER_ID),
         Account {
@@ -182,6 +309,29 @@ async fn setup_program_test_env(valid_config: bool) -> (BanksClient, Keypair, Ke
         },
     );
 
+    // Setup the whitelist shard of the validator
+    if whitelist_shard {
+        let shard = ValidatorWhitelistShard {
+            program: DELEGATED_PDA_OWNER_ID,
+            validator: validator_keypair.pubkey(),
+        };
+        let mut shard_data = vec![0; ValidatorWhitelistShard::size_with_discriminator()];
+        shard.to_bytes_with_discriminator(&mut shard_data).unwrap();
+        program_test.add_account(
+            validator_whitelist_shard_pda_from_program_id(
+                &DELEGATED_PDA_OWNER_ID,
+                &validator_keypair.pubkey(),
+            ),
+            Account {
+                lamports: Rent::default().minimum_balance(shard_data.len()),
+                data: shard_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
     let (banks, payer, blockhash) = program_test.start().await;
     (banks, payer, validator_keypair, blockhash)
 }
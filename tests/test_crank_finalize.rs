@@ -0,0 +1,188 @@
+use crate::fixtures::{
+    get_commit_record_account_data, get_delegation_metadata_data, get_delegation_record_data,
+    COMMIT_NEW_STATE_ACCOUNT_DATA, DELEGATED_PDA_ID, TEST_AUTHORITY,
+};
+use dlp::consts::{CRANK_FINALIZE_BOUNTY_LAMPORTS, CRANK_FINALIZE_DELAY_SLOTS};
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_crank_finalize() {
+    // Setup
+    let (mut context, validator, cranker) = setup_program_test_env().await;
+
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&validator.pubkey());
+
+    // Cranking before the finalize delay elapsed fails
+    let ix = dlp::instruction_builder::crank_finalize(
+        cranker.pubkey(),
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix.clone()],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &cranker],
+        context.last_blockhash,
+    );
+    let res = context.banks_client.process_transaction(tx).await;
+    assert!(res.is_err());
+
+    // Cranking after the finalize delay elapsed succeeds
+    context
+        .warp_to_slot(CRANK_FINALIZE_DELAY_SLOTS + 1)
+        .unwrap();
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &cranker],
+        blockhash,
+    );
+    let res = context.banks_client.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Assert the commit state and commit record were closed
+    let banks = &mut context.banks_client;
+    assert!(banks.get_account(commit_state_pda).await.unwrap().is_none());
+    assert!(banks
+        .get_account(commit_record_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Assert the delegated account contains the data from the new state
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(pda_account.data, COMMIT_NEW_STATE_ACCOUNT_DATA);
+
+    // Assert the cranker received the bounty from the validator fees vault
+    let cranker_account = banks.get_account(cranker.pubkey()).await.unwrap().unwrap();
+    assert_eq!(
+        cranker_account.lamports,
+        LAMPORTS_PER_SOL + CRANK_FINALIZE_BOUNTY_LAMPORTS
+    );
+    let validator_fees_vault = banks
+        .get_account(validator_fees_vault_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        validator_fees_vault.lamports,
+        LAMPORTS_PER_SOL - CRANK_FINALIZE_BOUNTY_LAMPORTS
+    );
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let cranker = Keypair::new();
+
+    for account in [validator.pubkey(), cranker.pubkey()] {
+        program_test.add_account(
+            account,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegation record PDA
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account metadata PDA
+    let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), None);
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the commit state PDA
+    program_test.add_account(
+        commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: COMMIT_NEW_STATE_ACCOUNT_DATA.into(),
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the commit record PDA, committed at slot 0
+    let commit_record_data = get_commit_record_account_data(validator.pubkey());
+    program_test.add_account(
+        commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(commit_record_data.len()),
+            data: commit_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, validator, cranker)
+}
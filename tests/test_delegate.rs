@@ -9,11 +9,12 @@ use solana_sdk::{
     transaction::Transaction,
 };
 
+use dlp::args::commit_state_hash;
 use dlp::pda::{
     delegate_buffer_pda_from_delegated_account_and_owner_program,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
 };
-use dlp::state::DelegationRecord;
+use dlp::state::{DelegationMetadata, DelegationRecord};
 
 use crate::fixtures::{
     DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, EXTERNAL_DELEGATE_INSTRUCTION_DISCRIMINATOR,
@@ -63,6 +64,15 @@ async fn test_delegate() {
         .unwrap();
     assert!(delegation_metadata_account.owner.eq(&dlp::id()));
 
+    // Assert that the metadata records the hash of the delegated data
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert_eq!(
+        delegation_metadata.delegated_data_hash,
+        Some(commit_state_hash(&pda_data_before_delegation))
+    );
+
     // Assert that the delegation record exists and can be parsed
     let delegation_record = banks
         .get_account(delegation_record_pda_from_delegated_account(
@@ -7,12 +7,16 @@ use solana_sdk::{
 };
 
 use crate::fixtures::ON_CURVE_KEYPAIR;
-use dlp::args::DelegateArgs;
+use dlp::args::{DelegateArgs, Seeds};
+use dlp::error::DlpError;
 use dlp::pda::{
     delegate_buffer_pda_from_delegated_account_and_owner_program,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    program_config_from_program_id,
 };
-use dlp::state::{DelegationMetadata, DelegationRecord};
+use dlp::state::{DelegationMetadata, DelegationRecord, ProgramConfig};
+use solana_program::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
 
 mod fixtures;
 
@@ -54,8 +58,14 @@ async fn test_delegate_on_curve() {
         None,
         DelegateArgs {
             commit_frequency_ms: u32::MAX,
-            seeds: vec![],
+            seeds: Seeds::default(),
             validator: Some(alt_payer.pubkey()),
+            extended_undelegate_payload: false,
+            seed_template: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            rent_co_payer: None,
         },
     );
 
@@ -120,6 +130,76 @@ async fn test_delegate_on_curve() {
     assert!(!delegation_metadata.is_undelegatable);
 }
 
+#[tokio::test]
+async fn test_delegate_on_curve_with_undelegate_discriminator() {
+    // Setup
+    let (banks, payer, alt_payer, blockhash) = setup_program_test_env().await;
+    let delegated_account = alt_payer.pubkey();
+
+    let change_owner_ix =
+        solana_program::system_instruction::assign(&alt_payer.pubkey(), &dlp::id());
+    let change_owner_tx = Transaction::new_signed_with_payer(
+        &[change_owner_ix],
+        Some(&alt_payer.pubkey()),
+        &[&alt_payer],
+        blockhash,
+    );
+    assert!(banks.process_transaction(change_owner_tx).await.is_ok());
+
+    let args = || DelegateArgs {
+        commit_frequency_ms: u32::MAX,
+        validator: Some(alt_payer.pubkey()),
+        undelegate_discriminator: Some([1, 2, 3, 4, 5, 6, 7, 8]),
+        ..Default::default()
+    };
+
+    // Without the program config allowing it, the override is rejected
+    let ix = dlp::instruction_builder::delegate(payer.pubkey(), delegated_account, None, args());
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &alt_payer],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::UndelegateDiscriminatorOverrideNotAllowed as u32)
+        )
+    );
+
+    // With the program config allowing it, the override is recorded in the metadata
+    let ix = dlp::instruction_builder::delegate_with_program_config(
+        payer.pubkey(),
+        delegated_account,
+        system_program::id(),
+        args(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &alt_payer],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+
+    let delegation_metadata = banks
+        .get_account(delegation_metadata_pda_from_delegated_account(
+            &delegated_account,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata.data).unwrap();
+    assert_eq!(
+        delegation_metadata.undelegate_discriminator,
+        Some([1, 2, 3, 4, 5, 6, 7, 8])
+    );
+}
+
 async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
@@ -136,6 +216,26 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
         },
     );
 
+    // Program config of the system program, allowing the undelegate discriminator override
+    let program_config = ProgramConfig {
+        allow_undelegate_discriminator_override: true,
+        ..Default::default()
+    };
+    let mut program_config_data = vec![];
+    program_config
+        .to_bytes_with_discriminator(&mut program_config_data)
+        .unwrap();
+    program_test.add_account(
+        program_config_from_program_id(&system_program::id()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: program_config_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
     let (banks, payer, blockhash) = program_test.start().await;
     (banks, payer, payer_alt, blockhash)
 }
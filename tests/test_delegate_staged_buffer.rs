@@ -0,0 +1,173 @@
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::fixtures::ON_CURVE_KEYPAIR;
+use dlp::args::{DelegateArgs, Seeds};
+use dlp::pda::{
+    delegation_record_pda_from_delegated_account, staged_delegate_buffer_pda_from_delegated_account,
+};
+
+mod fixtures;
+
+const DATA_LEN: usize = 2_000;
+const CHUNK_LEN: usize = 1_000;
+
+#[tokio::test]
+async fn test_delegate_from_staged_buffer() {
+    // Setup
+    let (banks, payer, delegated, blockhash) = setup_program_test_env().await;
+    let data: Vec<u8> = (0..DATA_LEN).map(|i| i as u8).collect();
+
+    stage_data(&banks, &payer, &delegated, &data, blockhash).await;
+
+    // Submit the delegate tx
+    let ix = dlp::instruction_builder::delegate_from_staged_buffer(
+        payer.pubkey(),
+        delegated.pubkey(),
+        None,
+        DelegateArgs {
+            commit_frequency_ms: u32::MAX,
+            seeds: Seeds::default(),
+            validator: Some(delegated.pubkey()),
+            extended_undelegate_payload: false,
+            seed_template: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            rent_co_payer: None,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &delegated],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Assert the delegated account contains the staged data
+    let delegated_account = banks
+        .get_account(delegated.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegated_account.data, data);
+
+    // Assert the delegation record exists
+    let delegation_record = banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &delegated.pubkey(),
+        ))
+        .await
+        .unwrap();
+    assert!(delegation_record.is_some());
+
+    // Assert the staged buffer was closed
+    let staged_buffer = banks
+        .get_account(staged_delegate_buffer_pda_from_delegated_account(
+            &delegated.pubkey(),
+        ))
+        .await
+        .unwrap();
+    assert!(staged_buffer.is_none());
+}
+
+#[tokio::test]
+async fn test_delegate_from_incomplete_staged_buffer() {
+    // Setup
+    let (banks, payer, delegated, blockhash) = setup_program_test_env().await;
+    let data: Vec<u8> = (0..DATA_LEN).map(|i| i as u8).collect();
+
+    // Only stage the first chunk
+    stage_data(&banks, &payer, &delegated, &data[..CHUNK_LEN], blockhash).await;
+
+    let ix = dlp::instruction_builder::delegate_from_staged_buffer(
+        payer.pubkey(),
+        delegated.pubkey(),
+        None,
+        DelegateArgs {
+            commit_frequency_ms: u32::MAX,
+            seeds: Seeds::default(),
+            validator: Some(delegated.pubkey()),
+            extended_undelegate_payload: false,
+            seed_template: None,
+            undelegate_discriminator: None,
+            heartbeat_timeout_slots: None,
+            max_account_size: None,
+            rent_co_payer: None,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &delegated],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_err());
+}
+
+async fn stage_data(
+    banks: &BanksClient,
+    payer: &Keypair,
+    delegated: &Keypair,
+    data: &[u8],
+    blockhash: Hash,
+) {
+    let ix = dlp::instruction_builder::init_delegate_buffer(
+        payer.pubkey(),
+        delegated.pubkey(),
+        DATA_LEN as u32,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[payer, delegated],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    for (i, chunk) in data.chunks(CHUNK_LEN).enumerate() {
+        let ix = dlp::instruction_builder::write_delegate_buffer_chunk(
+            payer.pubkey(),
+            delegated.pubkey(),
+            (i * CHUNK_LEN) as u32,
+            chunk.to_vec(),
+        );
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+        let res = banks.process_transaction(tx).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+    }
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let delegated = Keypair::from_bytes(&ON_CURVE_KEYPAIR).unwrap();
+
+    // Setup an account already owned by the delegation program, with its data cleared
+    program_test.add_account(
+        delegated.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![0; DATA_LEN],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, payer, blockhash) = program_test.start().await;
+    (banks, payer, delegated, blockhash)
+}
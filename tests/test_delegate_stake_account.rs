@@ -0,0 +1,220 @@
+use dlp::args::{DelegateStakeAccountArgs, UndelegateStakeAccountArgs};
+use dlp::error::DlpError;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    stake_authority_pda_from_stake_account,
+};
+use dlp::state::{DelegationRecord, NativeStakeState};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::stake::{
+    self,
+    state::{Authorized, Lockup},
+};
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::TEST_AUTHORITY;
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_delegate_and_undelegate_stake_account() {
+    // Setup
+    let (mut context, authority, stake_account) = setup_program_test_env().await;
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let stake_authority_pda = stake_authority_pda_from_stake_account(&stake_account.pubkey());
+
+    // Delegate the stake account, handing its authorities to the stake authority PDA
+    let ix = delegate_stake_account(&authority, &stake_account, &validator);
+    process(&mut context, &[ix], &authority).await.unwrap();
+    let stake_state = fetch_stake_state(&mut context, &stake_account.pubkey()).await;
+    let state = NativeStakeState::try_from_data(&stake_state).unwrap();
+    assert_eq!(state.staker(), stake_authority_pda);
+    assert_eq!(state.withdrawer(), stake_authority_pda);
+    let delegation_record_account = context
+        .banks_client
+        .get_account(delegation_record_pda_from_delegated_account(
+            &stake_account.pubkey(),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.owner, stake::program::id());
+    assert_eq!(delegation_record.authority, validator.pubkey());
+
+    // The stake authority cannot take the account back before the commit timeout
+    let ix = undelegate_stake_account(&authority, &stake_account, &authority, stake_state.clone());
+    let res = process(&mut context, &[ix], &authority).await;
+    assert_dlp_error(res, DlpError::Unauthorized);
+
+    // The validator settles the stake account, handing the authorities back
+    let ix = undelegate_stake_account(&validator, &stake_account, &authority, stake_state);
+    process_with_payer(&mut context, &[ix], &authority, &validator)
+        .await
+        .unwrap();
+    let stake_state = fetch_stake_state(&mut context, &stake_account.pubkey()).await;
+    let state = NativeStakeState::try_from_data(&stake_state).unwrap();
+    assert_eq!(state.staker(), authority.pubkey());
+    assert_eq!(state.withdrawer(), authority.pubkey());
+    for pda in [
+        delegation_record_pda_from_delegated_account(&stake_account.pubkey()),
+        delegation_metadata_pda_from_delegated_account(&stake_account.pubkey()),
+    ] {
+        assert!(context
+            .banks_client
+            .get_account(pda)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_undelegate_stake_account_invalid_state() {
+    // Setup
+    let (mut context, authority, stake_account) = setup_program_test_env().await;
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let ix = delegate_stake_account(&authority, &stake_account, &validator);
+    process(&mut context, &[ix], &authority).await.unwrap();
+
+    // A settled state changing the authorities is rejected
+    let mut stake_state = fetch_stake_state(&mut context, &stake_account.pubkey()).await;
+    stake_state[12..44].copy_from_slice(validator.pubkey().as_ref());
+    let ix = undelegate_stake_account(&validator, &stake_account, &authority, stake_state);
+    let res = process_with_payer(&mut context, &[ix], &authority, &validator).await;
+    assert_dlp_error(res, DlpError::InvalidStakeState);
+
+    // So is a truncated state
+    let ix = undelegate_stake_account(&validator, &stake_account, &authority, vec![1, 0, 0, 0]);
+    let res = process_with_payer(&mut context, &[ix], &authority, &validator).await;
+    assert_dlp_error(res, DlpError::InvalidStakeState);
+}
+
+#[tokio::test]
+async fn test_delegate_stake_account_not_authority() {
+    // Setup
+    let (mut context, _, stake_account) = setup_program_test_env().await;
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    // Only the staker and withdrawer can delegate the stake account
+    let ix = delegate_stake_account(&validator, &stake_account, &validator);
+    let res = process(&mut context, &[ix], &validator).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+fn delegate_stake_account(
+    authority: &Keypair,
+    stake_account: &Keypair,
+    validator: &Keypair,
+) -> Instruction {
+    dlp::instruction_builder::delegate_stake_account(
+        authority.pubkey(),
+        stake_account.pubkey(),
+        DelegateStakeAccountArgs {
+            commit_frequency_ms: 30_000,
+            validator: Some(validator.pubkey()),
+        },
+    )
+}
+
+fn undelegate_stake_account(
+    signer: &Keypair,
+    stake_account: &Keypair,
+    authority: &Keypair,
+    stake_state: Vec<u8>,
+) -> Instruction {
+    dlp::instruction_builder::undelegate_stake_account(
+        signer.pubkey(),
+        stake_account.pubkey(),
+        authority.pubkey(),
+        UndelegateStakeAccountArgs { stake_state },
+    )
+}
+
+async fn fetch_stake_state(context: &mut ProgramTestContext, stake_account: &Pubkey) -> Vec<u8> {
+    context
+        .banks_client
+        .get_account(*stake_account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+async fn process_with_payer(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    payer: &Keypair,
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx =
+        Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &[payer, signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+/// Setup a stake account initialized with the authority as staker and withdrawer
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let authority = Keypair::new();
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    for key in [authority.pubkey(), validator.pubkey()] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 10 * LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let mut context = program_test.start_with_context().await;
+
+    let stake_account = Keypair::new();
+    let ixs = stake::instruction::create_account(
+        &authority.pubkey(),
+        &stake_account.pubkey(),
+        &Authorized::auto(&authority.pubkey()),
+        &Lockup::default(),
+        LAMPORTS_PER_SOL,
+    );
+    process_with_payer(&mut context, &ixs, &authority, &stake_account)
+        .await
+        .unwrap();
+
+    (context, authority, stake_account)
+}
@@ -0,0 +1,274 @@
+use borsh::to_vec;
+use dlp::args::{CommitStateArgs, DelegationAuthorityArgs, Encoding};
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, delegation_authorities_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{
+    AccountDiscriminator, CommitRecord, DelegationAuthorities, MAX_DELEGATION_AUTHORITIES,
+};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_commit_from_delegation_authority() {
+    // Setup
+    let (mut context, validator, other_validator) = setup_program_test_env(None).await;
+
+    // The other validator is not an authority of the delegation yet
+    let ix = commit_from_delegation_authority(&other_validator);
+    let res = process(&mut context, &[ix], &validator, &[&other_validator]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+
+    // Once added by the validator, its commit is attributed to itself
+    let ix = delegation_authority_ix(&validator, other_validator.pubkey(), true);
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+    let authorities = delegation_authorities(&mut context).await.unwrap();
+    assert_eq!(authorities.authorities, vec![other_validator.pubkey()]);
+
+    let ix = commit_from_delegation_authority(&other_validator);
+    process(&mut context, &[ix], &validator, &[&other_validator])
+        .await
+        .unwrap();
+    let commit_record_account = context
+        .banks_client
+        .get_account(commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator(&commit_record_account.data).unwrap();
+    assert_eq!(commit_record.identity, other_validator.pubkey());
+}
+
+#[tokio::test]
+async fn test_delegation_authorities_management() {
+    // Setup
+    let (mut context, validator, other_validator) = setup_program_test_env(None).await;
+
+    // Only the authority of the delegation can add authorities
+    let ix = delegation_authority_ix(&other_validator, Pubkey::new_unique(), true);
+    let res = process(&mut context, &[ix], &validator, &[&other_validator]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+
+    // The delegation holds at most MAX_DELEGATION_AUTHORITIES authorities
+    let keys: Vec<Pubkey> = (0..MAX_DELEGATION_AUTHORITIES + 1)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    let (last, keys) = keys.split_last().unwrap();
+    let ixs: Vec<Instruction> = keys
+        .iter()
+        .map(|key| delegation_authority_ix(&validator, *key, true))
+        .collect();
+    process(&mut context, &ixs, &validator, &[]).await.unwrap();
+    let authorities = delegation_authorities(&mut context).await.unwrap();
+    assert_eq!(authorities.authorities, keys);
+    let ix = delegation_authority_ix(&validator, *last, true);
+    let res = process(&mut context, &[ix], &validator, &[]).await;
+    assert_dlp_error(res, DlpError::TooManyDelegationAuthorities);
+
+    // The removal of an authority is persisted
+    let (first, keys) = keys.split_first().unwrap();
+    let ix = delegation_authority_ix(&validator, *first, false);
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+    let authorities = delegation_authorities(&mut context).await.unwrap();
+    assert_eq!(authorities.authorities, keys);
+
+    // Removing the last authority closes the delegation authorities
+    let ixs: Vec<Instruction> = keys
+        .iter()
+        .map(|key| delegation_authority_ix(&validator, *key, false))
+        .collect();
+    process(&mut context, &ixs, &validator, &[]).await.unwrap();
+    assert!(delegation_authorities(&mut context).await.is_none());
+}
+
+#[tokio::test]
+async fn test_commit_from_previous_delegation_authority() {
+    // Setup the other validator as an authority of a previous delegation of the account
+    let (mut context, validator, other_validator) = setup_program_test_env(Some(100)).await;
+
+    let ix = commit_from_delegation_authority(&other_validator);
+    let res = process(&mut context, &[ix], &validator, &[&other_validator]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+
+    // Adding an authority clears the ones of the previous delegation
+    let ix = delegation_authority_ix(&validator, Pubkey::new_unique(), true);
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+    let authorities = delegation_authorities(&mut context).await.unwrap();
+    assert_eq!(authorities.delegation_slot, 0);
+    assert!(!authorities.contains(&other_validator.pubkey()));
+}
+
+fn delegation_authority_ix(authority: &Keypair, validator: Pubkey, add: bool) -> Instruction {
+    let args = DelegationAuthorityArgs { validator };
+    if add {
+        dlp::instruction_builder::add_delegation_authority(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            args,
+        )
+    } else {
+        dlp::instruction_builder::remove_delegation_authority(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            args,
+        )
+    }
+}
+
+fn commit_from_delegation_authority(validator: &Keypair) -> Instruction {
+    dlp::instruction_builder::commit_state_from_delegation_authority(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![1, 2, 3],
+            nonce: 1,
+            allow_undelegation: false,
+            lamports: Rent::default().minimum_balance(500),
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+async fn delegation_authorities(context: &mut ProgramTestContext) -> Option<DelegationAuthorities> {
+    context
+        .banks_client
+        .get_account(delegation_authorities_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .map(|account| {
+            DelegationAuthorities::try_from_bytes_with_discriminator(&account.data).unwrap()
+        })
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let signers = [&[payer], signers].concat();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+/// Setup a delegated PDA of the validator, and the delegation authorities of a previous
+/// delegation of the account, started at `previous_delegation_slot`, granting the other
+/// validator, if any
+async fn setup_program_test_env(
+    previous_delegation_slot: Option<u64>,
+) -> (ProgramTestContext, Keypair, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let other_validator = Keypair::new();
+
+    for key in [validator.pubkey(), other_validator.pubkey()] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 10 * LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        add_dlp_account(
+            &mut program_test,
+            validator_fees_vault_pda_from_validator(&key),
+            LAMPORTS_PER_SOL,
+            vec![],
+        );
+    }
+
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+    );
+    let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+    );
+
+    if let Some(delegation_slot) = previous_delegation_slot {
+        let delegation_authorities = DelegationAuthorities {
+            delegated_account: DELEGATED_PDA_ID,
+            delegation_slot,
+            authorities: vec![other_validator.pubkey()],
+        };
+        let data = [
+            AccountDiscriminator::DelegationAuthorities
+                .to_bytes()
+                .to_vec(),
+            to_vec(&delegation_authorities).unwrap(),
+        ]
+        .concat();
+        add_dlp_account(
+            &mut program_test,
+            delegation_authorities_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Rent::default().minimum_balance(data.len()),
+            data,
+        );
+    }
+
+    let context = program_test.start_with_context().await;
+    (context, validator, other_validator)
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
@@ -0,0 +1,365 @@
+use borsh::{to_vec, BorshDeserialize};
+use dlp::error::DlpError;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    protocol_config_pda, protocol_stats_pda,
+};
+use dlp::state::{DelegationPackage, DelegationRecord, ProtocolConfig, ProtocolStats};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+const DELEGATED_DATA: [u8; 4] = [1, 2, 3, 4];
+
+#[tokio::test]
+async fn test_export_and_import_delegation_package() {
+    // Export the delegation from the source cluster
+    let (mut source, admin) = setup_program_test_env(true, None).await;
+    let ix = dlp::instruction_builder::export_delegation_package(admin.pubkey(), DELEGATED_PDA_ID);
+    let blockhash = source.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let res = source
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(res.result.is_ok());
+    let return_data = res.metadata.unwrap().return_data.unwrap();
+    let package = DelegationPackage::try_from_slice(&return_data.data).unwrap();
+    assert_eq!(package.delegated_account, DELEGATED_PDA_ID);
+    assert_eq!(package.exporter, admin.pubkey());
+    assert_eq!(
+        package.data_hash,
+        DelegationPackage::data_hash(&DELEGATED_DATA)
+    );
+
+    // Import it on the destination cluster, where only the delegated account was migrated
+    let (mut destination, _) = setup_program_test_env(false, None).await;
+    let signature = admin.sign_message(&to_vec(&package).unwrap());
+    let mut ixs = dlp::instruction_builder::import_delegation_package(
+        admin.pubkey(),
+        package.clone(),
+        signature.as_ref().try_into().unwrap(),
+    );
+    ixs[1] = dlp::instruction_builder::with_protocol_stats(ixs[1].clone());
+    assert!(process(&mut destination, &ixs, &admin).await.is_ok());
+
+    let delegation_record_account = destination
+        .banks_client
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegation_record_account.owner, dlp::id());
+    assert_eq!(delegation_record_account.data, package.delegation_record);
+    DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data).unwrap();
+    let delegation_metadata_account = destination
+        .banks_client
+        .get_account(delegation_metadata_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        delegation_metadata_account.data,
+        package.delegation_metadata
+    );
+
+    // The imported delegation is added to the total value locked
+    let protocol_stats_account = destination
+        .banks_client
+        .get_account(protocol_stats_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    let protocol_stats =
+        ProtocolStats::try_from_bytes_with_discriminator(&protocol_stats_account.data).unwrap();
+    assert_eq!(protocol_stats.total_value_locked, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_import_delegation_package_with_mismatched_lamports() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env(false, None).await;
+
+    // The package records more lamports than the delegated account holds
+    let package = DelegationPackage {
+        delegation_record: get_delegation_record_data(admin.pubkey(), Some(2 * LAMPORTS_PER_SOL)),
+        ..delegation_package(admin.pubkey())
+    };
+    let signature = admin.sign_message(&to_vec(&package).unwrap());
+    let ixs = dlp::instruction_builder::import_delegation_package(
+        admin.pubkey(),
+        package,
+        signature.as_ref().try_into().unwrap(),
+    );
+    let res = process(&mut context, &ixs, &admin).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DlpError::InvalidDelegationPackage as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_import_tampered_delegation_package() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env(false, None).await;
+    let package = delegation_package(admin.pubkey());
+    let signature = admin.sign_message(&to_vec(&package).unwrap());
+
+    // The signature does not cover the tampered package
+    let mut ixs = dlp::instruction_builder::import_delegation_package(
+        admin.pubkey(),
+        package.clone(),
+        signature.as_ref().try_into().unwrap(),
+    );
+    let tampered = DelegationPackage {
+        delegation_record: get_delegation_record_data(Keypair::new().pubkey(), None),
+        ..package
+    };
+    ixs[1] = dlp::instruction_builder::import_delegation_package(
+        admin.pubkey(),
+        tampered,
+        signature.as_ref().try_into().unwrap(),
+    )
+    .remove(1);
+    let res = process(&mut context, &ixs, &admin).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DlpError::InvalidDelegationPackage as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_import_delegation_package_of_another_exporter() {
+    // Setup, the exporter being trusted by the protocol config
+    let exporter = Keypair::new();
+    let (mut context, admin) = setup_program_test_env(false, Some(exporter.pubkey())).await;
+    let package = DelegationPackage {
+        exporter: exporter.pubkey(),
+        ..delegation_package(admin.pubkey())
+    };
+
+    // The package is signed by its exporter and imported by the admin
+    let signature = exporter.sign_message(&to_vec(&package).unwrap());
+    let ixs = dlp::instruction_builder::import_delegation_package(
+        admin.pubkey(),
+        package.clone(),
+        signature.as_ref().try_into().unwrap(),
+    );
+    assert!(process(&mut context, &ixs, &admin).await.is_ok());
+
+    let delegation_record_account = context
+        .banks_client
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegation_record_account.data, package.delegation_record);
+}
+
+#[tokio::test]
+async fn test_import_delegation_package_of_an_untrusted_exporter() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env(false, None).await;
+    let exporter = Keypair::new();
+    let package = DelegationPackage {
+        exporter: exporter.pubkey(),
+        ..delegation_package(admin.pubkey())
+    };
+
+    // The package is signed by an unrelated key declaring itself as its exporter
+    let signature = exporter.sign_message(&to_vec(&package).unwrap());
+    let ixs = dlp::instruction_builder::import_delegation_package(
+        admin.pubkey(),
+        package,
+        signature.as_ref().try_into().unwrap(),
+    );
+    let res = process(&mut context, &ixs, &admin).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DlpError::InvalidDelegationPackage as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_import_delegation_package_not_signed_by_its_exporter() {
+    // Setup
+    let exporter = Keypair::new();
+    let (mut context, admin) = setup_program_test_env(false, Some(exporter.pubkey())).await;
+    let package = DelegationPackage {
+        exporter: exporter.pubkey(),
+        ..delegation_package(admin.pubkey())
+    };
+
+    // The admin signs the package in place of its exporter
+    let signature = admin.sign_message(&to_vec(&package).unwrap());
+    let mut ixs = dlp::instruction_builder::import_delegation_package(
+        admin.pubkey(),
+        package.clone(),
+        signature.as_ref().try_into().unwrap(),
+    );
+    // The Ed25519 instruction verifies the signature against the admin, following its header
+    ixs[0].data[16..48].copy_from_slice(admin.pubkey().as_ref());
+    let res = process(&mut context, &ixs, &admin).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DlpError::InvalidDelegationPackage as u32)
+        )
+    );
+}
+
+fn delegation_package(authority: Pubkey) -> DelegationPackage {
+    DelegationPackage {
+        source_program_id: dlp::id(),
+        exporter: authority,
+        export_slot: 0,
+        delegated_account: DELEGATED_PDA_ID,
+        delegation_record: get_delegation_record_data(authority, Some(LAMPORTS_PER_SOL)),
+        delegation_metadata: get_delegation_metadata_data(authority, None),
+        data_hash: DelegationPackage::data_hash(&DELEGATED_DATA),
+    }
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+async fn setup_program_test_env(
+    with_delegation: bool,
+    trusted_exporter: Option<Pubkey>,
+) -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    // The admin is the upgrade authority of the delegation program
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let validator = Keypair::new();
+
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: DELEGATED_DATA.to_vec(),
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    if with_delegation {
+        // Setup the delegated account metadata PDA
+        let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), None);
+        program_test.add_account(
+            delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Account {
+                lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+                data: delegation_metadata_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        // Setup the delegated record PDA
+        let delegation_record_data =
+            get_delegation_record_data(validator.pubkey(), Some(LAMPORTS_PER_SOL));
+        program_test.add_account(
+            delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Account {
+                lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+                data: delegation_record_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Setup the protocol stats PDA
+    let mut protocol_stats_data = vec![0; ProtocolStats::size_with_discriminator()];
+    ProtocolStats::default()
+        .to_bytes_with_discriminator(&mut protocol_stats_data)
+        .unwrap();
+    program_test.add_account(
+        protocol_stats_pda(),
+        Account {
+            lamports: Rent::default().minimum_balance(protocol_stats_data.len()),
+            data: protocol_stats_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the protocol config PDA
+    if let Some(trusted_exporter) = trusted_exporter {
+        let mut data = vec![0; ProtocolConfig::size_with_discriminator()];
+        ProtocolConfig {
+            trusted_exporter,
+            ..Default::default()
+        }
+        .to_bytes_with_discriminator(&mut data)
+        .unwrap();
+        program_test.add_account(
+            protocol_config_pda(),
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let context = program_test.start_with_context().await;
+    (context, admin)
+}
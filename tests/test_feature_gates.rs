@@ -0,0 +1,146 @@
+use crate::fixtures::TEST_AUTHORITY;
+use dlp::consts::FEATURE_GATES_PDA;
+use dlp::pda::feature_gates_pda;
+use dlp::state::FeatureGates;
+use solana_program::instruction::InstructionError;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+mod fixtures;
+
+const TOP_UP_PROGRAM_EPHEMERAL_BALANCE: u8 = 19;
+
+#[test]
+fn test_feature_gates_pda() {
+    assert_eq!(feature_gates_pda(), FEATURE_GATES_PDA);
+}
+
+#[tokio::test]
+async fn test_disable_and_enable_instruction() {
+    // Setup
+    let (banks, admin, blockhash) = setup_program_test_env().await;
+    let program_id = Pubkey::new_unique();
+
+    // Disable the top-up program ephemeral balance instruction
+    let ix = dlp::instruction_builder::set_feature_gate(
+        admin.pubkey(),
+        TOP_UP_PROGRAM_EPHEMERAL_BALANCE,
+        false,
+    );
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    let feature_gates_account = banks.get_account(FEATURE_GATES_PDA).await.unwrap().unwrap();
+    let feature_gates =
+        FeatureGates::try_from_bytes_with_discriminator(&feature_gates_account.data).unwrap();
+    assert!(!feature_gates.is_enabled(TOP_UP_PROGRAM_EPHEMERAL_BALANCE));
+    assert!(feature_gates.is_enabled(TOP_UP_PROGRAM_EPHEMERAL_BALANCE + 1));
+
+    // The gated instruction is rejected
+    let ix = dlp::instruction_builder::top_up_program_ephemeral_balance(
+        admin.pubkey(),
+        admin.pubkey(),
+        program_id,
+        None,
+        None,
+    );
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert_instruction_disabled(res);
+
+    // Enable the instruction again
+    let ix = dlp::instruction_builder::set_feature_gate(
+        admin.pubkey(),
+        TOP_UP_PROGRAM_EPHEMERAL_BALANCE,
+        true,
+    );
+    let top_up_ix = dlp::instruction_builder::top_up_program_ephemeral_balance(
+        admin.pubkey(),
+        admin.pubkey(),
+        program_id,
+        Some(20000),
+        None,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix, top_up_ix],
+        Some(&admin.pubkey()),
+        &[&admin],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_feature_gate_unauthorized() {
+    // Setup
+    let (banks, _, blockhash) = setup_program_test_env().await;
+    let unauthorized = Keypair::new();
+    banks_airdrop(&banks, &unauthorized, blockhash).await;
+
+    let ix = dlp::instruction_builder::set_feature_gate(
+        unauthorized.pubkey(),
+        TOP_UP_PROGRAM_EPHEMERAL_BALANCE,
+        false,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&unauthorized.pubkey()),
+        &[&unauthorized],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_err());
+
+    let feature_gates_account = banks.get_account(FEATURE_GATES_PDA).await.unwrap();
+    assert!(feature_gates_account.is_none());
+}
+
+fn assert_instruction_disabled(res: Result<(), BanksClientError>) {
+    let err = res.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(dlp::error::DlpError::InstructionDisabled as u32)
+        )
+    );
+}
+
+async fn banks_airdrop(banks: &BanksClient, to: &Keypair, blockhash: Hash) {
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let ix = solana_program::system_instruction::transfer(
+        &admin.pubkey(),
+        &to.pubkey(),
+        LAMPORTS_PER_SOL / 10,
+    );
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    banks.process_transaction(tx).await.unwrap();
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, admin, blockhash)
+}
@@ -2,19 +2,26 @@ use crate::fixtures::{
     get_commit_record_account_data, get_delegation_metadata_data, get_delegation_record_data,
     COMMIT_NEW_STATE_ACCOUNT_DATA, DELEGATED_PDA_ID, TEST_AUTHORITY,
 };
+use dlp::consts::MAX_ACCOUNT_DATA_LEN;
+use dlp::error::DlpError;
 use dlp::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
-    validator_fees_vault_pda_from_validator,
+    read_lock_pda_from_delegated_account, validator_fees_vault_pda_from_validator,
 };
-use dlp::state::{CommitRecord, DelegationMetadata};
+use dlp::state::{
+    CommitRecord, CommitStateLayout, DelegationMetadata, DelegationRecord, PendingState, ReadLock,
+};
+use solana_program::clock::Clock;
+use solana_program::instruction::InstructionError;
+use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{BanksClient, ProgramTest};
 use solana_sdk::{
     account::Account,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 mod fixtures;
@@ -80,9 +87,250 @@ async fn test_finalize() {
         DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
             .unwrap();
     assert_eq!(commit_record.nonce, delegation_metadata.last_update_nonce);
+    assert!(delegation_metadata.last_commit_slot.is_some());
+    assert!(delegation_metadata.last_commit_timestamp.is_some());
+}
+
+#[tokio::test]
+async fn test_finalize_settled() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&authority.pubkey());
+
+    // The commit increased the lamports of the delegated account, the validator fees vault
+    // can be read-only
+    let ix = dlp::instruction_builder::finalize_settled(authority.pubkey(), DELEGATED_PDA_ID);
+    assert!(!ix.accounts[6].is_writable);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the commit was finalized without touching the validator fees vault
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(pda_account.data, COMMIT_NEW_STATE_ACCOUNT_DATA);
+    let validator_fees_vault = banks
+        .get_account(validator_fees_vault_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(validator_fees_vault.lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_finalize_with_read_lock() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    // Initialize the read lock and finalize locking the reads of the delegated account
+    let ixs = [
+        dlp::instruction_builder::init_read_lock(authority.pubkey(), DELEGATED_PDA_ID),
+        dlp::instruction_builder::finalize_with_read_lock(authority.pubkey(), DELEGATED_PDA_ID),
+    ];
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the reads are locked for the slot of the finalize
+    let read_lock_pda = read_lock_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let read_lock_account = banks.get_account(read_lock_pda).await.unwrap().unwrap();
+    let read_lock = ReadLock::try_from_bytes_with_discriminator(&read_lock_account.data).unwrap();
+    let clock = banks.get_sysvar::<Clock>().await.unwrap();
+    assert!(read_lock.is_locked(clock.slot));
+    assert!(!read_lock.is_locked(clock.slot + 1));
+
+    let delegation_metadata_pda = delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let delegation_metadata_account = banks
+        .get_account(delegation_metadata_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert_eq!(read_lock.nonce, delegation_metadata.last_update_nonce);
+}
+
+#[tokio::test]
+async fn test_finalize_funds_rent_exemption() {
+    // Setup a delegated account whose lamports cannot hold the committed data
+    let (banks, _, authority, blockhash) =
+        setup_program_test_env_with_lamports(1_000, Some(LAMPORTS_PER_SOL)).await;
+
+    let ix = dlp::instruction_builder::finalize_settled(authority.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the shortfall was taken from the commit PDAs refunded to the validator
+    let minimum_balance = Rent::default().minimum_balance(COMMIT_NEW_STATE_ACCOUNT_DATA.len());
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(pda_account.data, COMMIT_NEW_STATE_ACCOUNT_DATA);
+    assert_eq!(pda_account.lamports, minimum_balance);
+
+    // Assert the delegation record tracks the funded lamports
+    let delegation_record_account = banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.lamports, minimum_balance);
+}
+
+#[tokio::test]
+async fn test_finalize_max_account_size() {
+    // Setup a delegated account and a commit state both at the maximum account size, the
+    // delegated account holding the lamports committed
+    let lamports = Rent::default().minimum_balance(MAX_ACCOUNT_DATA_LEN);
+    let committed_data: Vec<u8> = (0..MAX_ACCOUNT_DATA_LEN).map(|i| (i % 251) as u8).collect();
+    let (banks, _, authority, blockhash) = setup_program_test_env_with_data(
+        lamports,
+        Some(lamports),
+        vec![0; MAX_ACCOUNT_DATA_LEN],
+        committed_data.clone(),
+    )
+    .await;
+
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the committed data was copied entirely, including its first and last bytes
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(pda_account.data.len(), MAX_ACCOUNT_DATA_LEN);
+    assert_eq!(pda_account.data.first(), committed_data.first());
+    assert_eq!(pda_account.data.last(), committed_data.last());
+    assert!(pda_account.data == committed_data);
+    assert_eq!(pda_account.lamports, lamports);
+}
+
+#[tokio::test]
+async fn test_finalize_raw_commit_state_mimicking_a_pending_state() {
+    // Setup a commit state committed before the pending state header, whose raw data holds
+    // a pending state header followed by data
+    let header = PendingState::new(Pubkey::new_unique(), 1, 3, 0);
+    let mut committed_data = vec![7; header.account_size()];
+    header
+        .to_bytes_with_discriminator(&mut committed_data[..PendingState::size_with_discriminator()])
+        .unwrap();
+    let (banks, _, authority, blockhash) =
+        setup_program_test_env_with_data(LAMPORTS_PER_SOL, None, vec![], committed_data.clone())
+            .await;
+
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // The commit record recorded a raw commit state, applied as a whole
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(pda_account.data, committed_data);
+}
+
+#[tokio::test]
+async fn test_finalize_commit_state_without_its_recorded_layout() {
+    // Setup a commit record recording a pending state header the commit state lacks
+    let (banks, _, authority, blockhash) = setup_program_test_env_with_layout(
+        LAMPORTS_PER_SOL,
+        None,
+        vec![],
+        COMMIT_NEW_STATE_ACCOUNT_DATA.into(),
+        CommitStateLayout::Pending,
+    )
+    .await;
+
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InvalidCommitStateLayout as u32)
+        )
+    );
 }
 
 async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    setup_program_test_env_with_lamports(LAMPORTS_PER_SOL, None).await
+}
+
+/// Setup with the lamports of the delegated account and the ones recorded at the last
+/// update in its delegation record
+async fn setup_program_test_env_with_lamports(
+    delegated_lamports: u64,
+    last_update_lamports: Option<u64>,
+) -> (BanksClient, Keypair, Keypair, Hash) {
+    setup_program_test_env_with_data(
+        delegated_lamports,
+        last_update_lamports,
+        vec![],
+        COMMIT_NEW_STATE_ACCOUNT_DATA.into(),
+    )
+    .await
+}
+
+/// Setup with the data of the delegated account and of its raw commit state, the commit
+/// record committing the lamports of the last update if any
+async fn setup_program_test_env_with_data(
+    delegated_lamports: u64,
+    last_update_lamports: Option<u64>,
+    delegated_data: Vec<u8>,
+    committed_data: Vec<u8>,
+) -> (BanksClient, Keypair, Keypair, Hash) {
+    setup_program_test_env_with_layout(
+        delegated_lamports,
+        last_update_lamports,
+        delegated_data,
+        committed_data,
+        CommitStateLayout::Raw,
+    )
+    .await
+}
+
+/// Setup with the data of the commit state and the layout recorded by its commit record
+async fn setup_program_test_env_with_layout(
+    delegated_lamports: u64,
+    last_update_lamports: Option<u64>,
+    delegated_data: Vec<u8>,
+    committed_data: Vec<u8>,
+    commit_state_layout: CommitStateLayout,
+) -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
 
@@ -103,8 +351,8 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
     program_test.add_account(
         DELEGATED_PDA_ID,
         Account {
-            lamports: LAMPORTS_PER_SOL,
-            data: vec![],
+            lamports: delegated_lamports,
+            data: delegated_data,
             owner: dlp::id(),
             executable: false,
             rent_epoch: 0,
@@ -112,7 +360,8 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
     );
 
     // Setup the delegation record PDA
-    let delegation_record_data = get_delegation_record_data(authority.pubkey(), None);
+    let delegation_record_data =
+        get_delegation_record_data(authority.pubkey(), last_update_lamports);
     program_test.add_account(
         delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
         Account {
@@ -142,14 +391,20 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
         commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
         Account {
             lamports: LAMPORTS_PER_SOL,
-            data: COMMIT_NEW_STATE_ACCOUNT_DATA.into(),
+            data: committed_data,
             owner: dlp::id(),
             executable: false,
             rent_epoch: 0,
         },
     );
 
-    let commit_record_data = get_commit_record_account_data(authority.pubkey());
+    let mut commit_record_data = get_commit_record_account_data(authority.pubkey());
+    let commit_record =
+        CommitRecord::try_from_bytes_with_discriminator_mut(&mut commit_record_data).unwrap();
+    if let Some(last_update_lamports) = last_update_lamports {
+        commit_record.lamports = last_update_lamports;
+    }
+    commit_record.commit_state_layout = commit_state_layout.into();
     program_test.add_account(
         commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
         Account {
@@ -0,0 +1,330 @@
+use dlp::consts::FORCE_UNDELEGATE_TIMELOCK_SLOTS;
+use dlp::error::DlpError;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fees_vault_pda, force_undelegation_pda_from_delegated_account,
+};
+use dlp::state::{DelegationLifecycle, DelegationMetadata, DelegationRecord, ForceUndelegation};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_force_undelegate() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env().await;
+    let force_undelegation_pda = force_undelegation_pda_from_delegated_account(&DELEGATED_PDA_ID);
+
+    // Schedule the force undelegation
+    let ix = dlp::instruction_builder::schedule_force_undelegate(admin.pubkey(), DELEGATED_PDA_ID);
+    assert!(process(&mut context, &[ix], &admin).await.is_ok());
+    let force_undelegation_account = context
+        .banks_client
+        .get_account(force_undelegation_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let force_undelegation =
+        ForceUndelegation::try_from_bytes_with_discriminator(&force_undelegation_account.data)
+            .unwrap();
+    assert_eq!(force_undelegation.admin, admin.pubkey());
+    assert_eq!(
+        force_undelegation.executable_slot,
+        force_undelegation.scheduled_slot + FORCE_UNDELEGATE_TIMELOCK_SLOTS
+    );
+
+    // Executing it before the timelock elapsed fails
+    let ix = dlp::instruction_builder::execute_force_undelegate(admin.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix.clone()], &admin).await;
+    assert_dlp_error(res, DlpError::ForceUndelegationTimelock);
+
+    // Executing it after the timelock elapsed marks the account undelegatable
+    context
+        .warp_to_slot(force_undelegation.executable_slot + 1)
+        .unwrap();
+    assert!(process(&mut context, &[ix], &admin).await.is_ok());
+    let delegation_metadata_account = context
+        .banks_client
+        .get_account(delegation_metadata_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert!(delegation_metadata.is_undelegatable);
+    assert_eq!(
+        delegation_metadata.lifecycle(),
+        DelegationLifecycle::Undelegatable
+    );
+    assert!(context
+        .banks_client
+        .get_account(force_undelegation_pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_schedule_force_undelegate_unauthorized() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env().await;
+    let other = Keypair::new();
+    fund(&mut context, &admin, &other).await;
+
+    let ix = dlp::instruction_builder::schedule_force_undelegate(other.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix], &other).await;
+    assert_dlp_error(res, DlpError::Unauthorized);
+}
+
+#[tokio::test]
+async fn test_force_undelegate_after_commit_timeout() {
+    // Setup, the validator committing every 4s, i.e. timing out after 100 slots
+    let (mut context, admin) = setup_program_test_env_with_commit_timeout(4_000, None).await;
+
+    // Anyone can force the undelegation, once the commit timeout elapsed
+    let ix = dlp::instruction_builder::force_undelegate(admin.pubkey(), DELEGATED_PDA_ID);
+    context.warp_to_slot(99).unwrap();
+    let res = process(&mut context, &[ix.clone()], &admin).await;
+    assert_dlp_error(res, DlpError::CommitTimeoutNotElapsed);
+
+    context.warp_to_slot(100).unwrap();
+    assert!(process(&mut context, &[ix.clone()], &admin).await.is_ok());
+    let delegation_metadata = delegation_metadata(&mut context).await;
+    assert!(delegation_metadata.is_undelegatable);
+    assert_eq!(delegation_metadata.lifecycle(), DelegationLifecycle::Stale);
+    let res = process(&mut context, &[ix], &admin).await;
+    assert_dlp_error(res, DlpError::AlreadyUndelegated);
+}
+
+#[tokio::test]
+async fn test_undelegate_stale_delegation() {
+    // Setup, the validator committing every 4s and going offline without a fees vault
+    let (mut context, admin) = setup_program_test_env_with_commit_timeout(4_000, None).await;
+    let rent_payer = delegation_metadata(&mut context).await.rent_payer;
+    let other = Keypair::new();
+    fund(&mut context, &admin, &other).await;
+
+    // The delegation is not stale yet, undelegating requires the validator
+    let undelegate_ix = dlp::instruction_builder::undelegate(
+        other.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        rent_payer,
+    );
+    context.warp_to_slot(100).unwrap();
+    let res = process(&mut context, &[undelegate_ix.clone()], &other).await;
+    assert!(res.is_err());
+
+    // Once force undelegated, anyone can undelegate it, the rent fees going to the protocol
+    let ix = dlp::instruction_builder::force_undelegate(other.pubkey(), DELEGATED_PDA_ID);
+    assert!(process(&mut context, &[ix], &other).await.is_ok());
+    let fees_vault_lamports = get_lamports(&mut context, fees_vault_pda()).await;
+    assert!(process(&mut context, &[undelegate_ix], &other)
+        .await
+        .is_ok());
+
+    let delegated_account = context
+        .banks_client
+        .get_account(DELEGATED_PDA_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegated_account.owner, DELEGATED_PDA_OWNER_ID);
+    assert!(context
+        .banks_client
+        .get_account(delegation_metadata_pda_from_delegated_account(
+            &DELEGATED_PDA_ID
+        ))
+        .await
+        .unwrap()
+        .is_none());
+    assert!(get_lamports(&mut context, fees_vault_pda()).await > fees_vault_lamports);
+}
+
+#[tokio::test]
+async fn test_force_undelegate_since_last_commit() {
+    // Setup, the last commit being finalized at slot 200
+    let (mut context, admin) = setup_program_test_env_with_commit_timeout(4_000, Some(200)).await;
+
+    let ix = dlp::instruction_builder::force_undelegate(admin.pubkey(), DELEGATED_PDA_ID);
+    context.warp_to_slot(299).unwrap();
+    let res = process(&mut context, &[ix.clone()], &admin).await;
+    assert_dlp_error(res, DlpError::CommitTimeoutNotElapsed);
+
+    context.warp_to_slot(300).unwrap();
+    assert!(process(&mut context, &[ix], &admin).await.is_ok());
+    assert!(delegation_metadata(&mut context).await.is_undelegatable);
+}
+
+#[tokio::test]
+async fn test_force_undelegate_without_commit_frequency() {
+    // Setup, a delegation without commit frequency never times out
+    let (mut context, admin) = setup_program_test_env().await;
+
+    context.warp_to_slot(1_000_000).unwrap();
+    let ix = dlp::instruction_builder::force_undelegate(admin.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix], &admin).await;
+    assert_dlp_error(res, DlpError::CommitTimeoutNotElapsed);
+}
+
+async fn delegation_metadata(context: &mut ProgramTestContext) -> DelegationMetadata {
+    let delegation_metadata_account = context
+        .banks_client
+        .get_account(delegation_metadata_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+        .unwrap()
+}
+
+async fn get_lamports(context: &mut ProgramTestContext, pubkey: Pubkey) -> u64 {
+    context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports
+}
+
+async fn fund(context: &mut ProgramTestContext, payer: &Keypair, account: &Keypair) {
+    let ix = solana_sdk::system_instruction::transfer(
+        &payer.pubkey(),
+        &account.pubkey(),
+        LAMPORTS_PER_SOL,
+    );
+    process(context, &[ix], payer).await.unwrap();
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair) {
+    setup_program_test_env_with_commit_timeout(0, None).await
+}
+
+/// Setup the accounts, the delegation committing at the commit frequency and its last commit
+/// finalized at the slot, if set
+async fn setup_program_test_env_with_commit_timeout(
+    commit_frequency_ms: u64,
+    last_commit_slot: Option<u64>,
+) -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    // The admin is the upgrade authority of the delegation program
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let validator = Keypair::new();
+
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account metadata PDA
+    let mut delegation_metadata = DelegationMetadata::try_from_bytes_with_discriminator(
+        &get_delegation_metadata_data(validator.pubkey(), None),
+    )
+    .unwrap();
+    delegation_metadata.last_commit_slot = last_commit_slot;
+    let mut delegation_metadata_data = vec![];
+    delegation_metadata
+        .to_bytes_with_discriminator(&mut delegation_metadata_data)
+        .unwrap();
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA, delegated to the failed validator
+    let mut delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    let mut delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data).unwrap();
+    delegation_record.commit_frequency_ms = commit_frequency_ms;
+    delegation_record
+        .to_bytes_with_discriminator(&mut delegation_record_data)
+        .unwrap();
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the protocol fees vault
+    program_test.add_account(
+        fees_vault_pda(),
+        Account {
+            lamports: Rent::default().minimum_balance(0),
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, admin)
+}
@@ -0,0 +1,267 @@
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account, ephemeral_balance_pda_from_payer,
+    protocol_stats_pda,
+};
+use dlp::state::{DelegationRecord, ProtocolStats};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_instruction, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_commit_record_account_data, get_delegation_metadata_data, get_delegation_record_data,
+    DELEGATED_PDA, DELEGATED_PDA_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+const DELEGATED_LAMPORTS: u64 = LAMPORTS_PER_SOL;
+const RENT_PAYER: Pubkey = Pubkey::new_from_array([7; 32]);
+
+#[tokio::test]
+async fn test_fund_escrow_from_delegated() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env(false).await;
+    let amount = LAMPORTS_PER_SOL / 2;
+
+    let ix = dlp::instruction_builder::with_protocol_stats(
+        dlp::instruction_builder::fund_escrow_from_delegated(
+            validator.pubkey(),
+            DELEGATED_PDA_ID,
+            RENT_PAYER,
+            amount,
+            3,
+        ),
+    );
+    let res = process(&mut context, &[ix], &validator).await;
+    assert!(res.is_ok());
+
+    // The escrow of the rent payer is created and funded
+    let escrow = context
+        .banks_client
+        .get_account(ephemeral_balance_pda_from_payer(&RENT_PAYER, 3))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(escrow.owner, system_program::id());
+    assert_eq!(escrow.lamports, Rent::default().minimum_balance(0) + amount);
+
+    // The delegated account and its record lost the lamports
+    let delegated_account = context
+        .banks_client
+        .get_account(DELEGATED_PDA_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegated_account.lamports, DELEGATED_LAMPORTS - amount);
+    let delegation_record_account = context
+        .banks_client
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.lamports, DELEGATED_LAMPORTS - amount);
+
+    // The lamports taken are subtracted from the total value locked
+    let protocol_stats_account = context
+        .banks_client
+        .get_account(protocol_stats_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    let protocol_stats =
+        ProtocolStats::try_from_bytes_with_discriminator(&protocol_stats_account.data).unwrap();
+    assert_eq!(
+        protocol_stats.total_value_locked,
+        DELEGATED_LAMPORTS - amount
+    );
+}
+
+#[tokio::test]
+async fn test_fund_escrow_from_delegated_rejections() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env(false).await;
+
+    // Only the escrow of the rent payer can be funded, not one of the validator
+    let ix = dlp::instruction_builder::fund_escrow_from_delegated(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        validator.pubkey(),
+        1,
+        0,
+    );
+    let res = process(&mut context, &[ix], &validator).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+
+    // The rent exempt minimum of the delegated account cannot be taken
+    let excess_lamports = DELEGATED_LAMPORTS - Rent::default().minimum_balance(DELEGATED_PDA.len());
+    let ix = dlp::instruction_builder::fund_escrow_from_delegated(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        RENT_PAYER,
+        excess_lamports + 1,
+        0,
+    );
+    let res = process(&mut context, &[ix], &validator).await;
+    assert_dlp_error(res, DlpError::NotEnoughExcessLamports);
+
+    // Only the authority of the delegation can take the lamports
+    let other = Keypair::new();
+    let ix = dlp::instruction_builder::fund_escrow_from_delegated(
+        other.pubkey(),
+        DELEGATED_PDA_ID,
+        RENT_PAYER,
+        1,
+        0,
+    );
+    let ixs = [
+        system_instruction::transfer(&validator.pubkey(), &other.pubkey(), LAMPORTS_PER_SOL / 10),
+        ix,
+    ];
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&validator.pubkey()),
+        &[&validator, &other],
+        blockhash,
+    );
+    let res = context.banks_client.process_transaction(tx).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(DlpError::InvalidAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_fund_escrow_from_delegated_with_pending_commit() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env(true).await;
+
+    // A pending commit is settled against the lamports of the record
+    let ix = dlp::instruction_builder::fund_escrow_from_delegated(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        RENT_PAYER,
+        1,
+        0,
+    );
+    let res = process(&mut context, &[ix], &validator).await;
+    assert!(res.is_err());
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env(pending_commit: bool) -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA, its lamports all recorded by its delegation record
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        DELEGATED_LAMPORTS,
+        DELEGATED_PDA.to_vec(),
+    );
+    let delegation_record_data =
+        get_delegation_record_data(validator.pubkey(), Some(DELEGATED_LAMPORTS));
+    add_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+    );
+    let delegation_metadata_data = get_delegation_metadata_data(RENT_PAYER, None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+    );
+
+    // The total value locked counts the lamports of the delegated account
+    let mut protocol_stats_data = vec![0; ProtocolStats::size_with_discriminator()];
+    ProtocolStats {
+        total_value_locked: DELEGATED_LAMPORTS,
+        last_resync_slot: 0,
+    }
+    .to_bytes_with_discriminator(&mut protocol_stats_data)
+    .unwrap();
+    add_dlp_account(
+        &mut program_test,
+        protocol_stats_pda(),
+        Rent::default().minimum_balance(protocol_stats_data.len()),
+        protocol_stats_data,
+    );
+
+    if pending_commit {
+        let commit_record_data = get_commit_record_account_data(validator.pubkey());
+        add_dlp_account(
+            &mut program_test,
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Rent::default().minimum_balance(commit_record_data.len()),
+            commit_record_data,
+        );
+    }
+
+    let context = program_test.start_with_context().await;
+    (context, validator)
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
@@ -0,0 +1,99 @@
+use dlp::args::GrantFeeExemptionArgs;
+use dlp::error::DlpError;
+use dlp::pda::fee_exemption_pda_from_delegated_account;
+use dlp::state::FeeExemption;
+use solana_program::instruction::InstructionError;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{DELEGATED_PDA_ID, TEST_AUTHORITY};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_grant_fee_exemption() {
+    // Setup
+    let (banks, admin, blockhash) = setup_program_test_env().await;
+
+    // Grant an exemption, then update its expiry
+    for expiry_slot in [0, 100] {
+        let ix = dlp::instruction_builder::grant_fee_exemption(
+            admin.pubkey(),
+            DELEGATED_PDA_ID,
+            GrantFeeExemptionArgs { expiry_slot },
+        );
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+        let res = banks.process_transaction(tx).await;
+        assert!(res.is_ok());
+
+        // Assert the fee exemption holds the expiry slot
+        let fee_exemption_account = banks
+            .get_account(fee_exemption_pda_from_delegated_account(&DELEGATED_PDA_ID))
+            .await
+            .unwrap()
+            .unwrap();
+        let fee_exemption =
+            FeeExemption::try_from_bytes_with_discriminator(&fee_exemption_account.data).unwrap();
+        assert_eq!(fee_exemption.expiry_slot, expiry_slot);
+        assert!(fee_exemption.is_active(99));
+        assert_eq!(fee_exemption.is_active(100), expiry_slot == 0);
+    }
+}
+
+#[tokio::test]
+async fn test_grant_fee_exemption_unauthorized() {
+    // Setup
+    let (banks, payer, blockhash) = setup_program_test_env().await;
+    let other = Keypair::new();
+
+    let ix = dlp::instruction_builder::grant_fee_exemption(
+        other.pubkey(),
+        DELEGATED_PDA_ID,
+        GrantFeeExemptionArgs { expiry_slot: 0 },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &other],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::Unauthorized as u32)
+        )
+    );
+    assert!(banks
+        .get_account(fee_exemption_pda_from_delegated_account(&DELEGATED_PDA_ID))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, admin, blockhash)
+}
@@ -0,0 +1,329 @@
+use dlp::args::{CommitStateFromBufferArgs, Encoding};
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{DelegationRecord, PendingState, StreamedCommitState};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_grow_commit_state_and_finalize() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let chunks = [vec![1; 100], vec![2; 50], vec![3; 10]];
+
+    // Stream the chunks in the commit state
+    for chunk in chunks.iter() {
+        let ix = dlp::instruction_builder::grow_commit_state(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            chunk.clone(),
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&authority.pubkey()),
+            &[&authority],
+            blockhash,
+        );
+        let res = banks.process_transaction(tx).await;
+        assert!(res.is_ok());
+    }
+
+    // Assert the header holds the streamed length
+    let streamed_data = chunks.concat();
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_state_account = banks.get_account(commit_state_pda).await.unwrap().unwrap();
+    assert_eq!(
+        StreamedCommitState::streamed_data(&commit_state_account.data),
+        Some(streamed_data.as_slice())
+    );
+
+    // Commit the streamed state and finalize it
+    let commit_ix = dlp::instruction_builder::commit_state_from_buffer(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        commit_state_pda,
+        CommitStateFromBufferArgs {
+            nonce: 1,
+            lamports: LAMPORTS_PER_SOL,
+            allow_undelegation: false,
+            er_block_hash: None,
+            encoding: Encoding::Raw,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[commit_ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the streamed state was moved after a pending state header
+    let commit_state_account = banks.get_account(commit_state_pda).await.unwrap().unwrap();
+    let (pending_state, data) = PendingState::read(&commit_state_account.data).unwrap();
+    assert_eq!(data, streamed_data.as_slice());
+    assert_eq!(pending_state.nonce, 1);
+    assert_eq!(pending_state.owner, DELEGATED_PDA_OWNER_ID);
+    assert!(!pending_state.allows_undelegation());
+    assert!(StreamedCommitState::streamed_data(&commit_state_account.data).is_none());
+    assert!(
+        commit_state_account.lamports
+            >= Rent::default().minimum_balance(commit_state_account.data.len())
+    );
+
+    let finalize_ix = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(
+        &[finalize_ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the delegated account holds the streamed data without the header
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.data, streamed_data);
+
+    // Assert the commit PDAs were closed
+    assert!(banks.get_account(commit_state_pda).await.unwrap().is_none());
+    let commit_record_pda = commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    assert!(banks
+        .get_account(commit_record_pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_grow_commit_state_invalid_authority() {
+    // Setup
+    let (banks, payer, _, blockhash) = setup_program_test_env().await;
+
+    let ix =
+        dlp::instruction_builder::grow_commit_state(payer.pubkey(), DELEGATED_PDA_ID, vec![1; 100]);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(dlp::error::DlpError::InvalidAuthority as u32)
+        )
+    );
+
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    assert!(banks.get_account(commit_state_pda).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_close_abandoned_streamed_commit_state() {
+    // Setup, the validator committing every 4s, i.e. timing out after 100 slots
+    let (program_test, authority) = program_test_env(4_000);
+    let mut context = program_test.start_with_context().await;
+    let other = Keypair::new();
+    let ix = solana_sdk::system_instruction::transfer(
+        &authority.pubkey(),
+        &other.pubkey(),
+        LAMPORTS_PER_SOL,
+    );
+    assert!(process(&mut context, &[ix], &authority).await.is_ok());
+
+    // The validator streams a chunk and goes offline
+    let ix = dlp::instruction_builder::grow_commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        vec![1; 100],
+    );
+    assert!(process(&mut context, &[ix], &authority).await.is_ok());
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let commit_state_lamports = get_lamports(&mut context, commit_state_pda).await;
+
+    // Anyone else can only close it once the commit timeout elapsed
+    let ix = dlp::instruction_builder::close_streamed_commit_state(
+        other.pubkey(),
+        DELEGATED_PDA_ID,
+        authority.pubkey(),
+    );
+    context.warp_to_slot(50).unwrap();
+    let res = process(&mut context, &[ix.clone()], &other).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+
+    // The rent is refunded to the payer of the stream only
+    context.warp_to_slot(200).unwrap();
+    let wrong_payer_ix = dlp::instruction_builder::close_streamed_commit_state(
+        other.pubkey(),
+        DELEGATED_PDA_ID,
+        other.pubkey(),
+    );
+    let res = process(&mut context, &[wrong_payer_ix], &other).await;
+    assert_dlp_error(res, DlpError::InvalidStreamedCommitState);
+
+    let authority_lamports = get_lamports(&mut context, authority.pubkey()).await;
+    assert!(process(&mut context, &[ix], &other).await.is_ok());
+    assert!(context
+        .banks_client
+        .get_account(commit_state_pda)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_lamports(&mut context, authority.pubkey()).await,
+        authority_lamports + commit_state_lamports
+    );
+}
+
+#[tokio::test]
+async fn test_close_streamed_commit_state_by_authority() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let grow_ix =
+        dlp::instruction_builder::grow_commit_state(authority.pubkey(), DELEGATED_PDA_ID, vec![1]);
+    let close_ix = dlp::instruction_builder::close_streamed_commit_state(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[grow_ix, close_ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+
+    let commit_state_pda = commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    assert!(banks.get_account(commit_state_pda).await.unwrap().is_none());
+}
+
+async fn get_lamports(context: &mut ProgramTestContext, pubkey: Pubkey) -> u64 {
+    context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    let (program_test, validator_keypair) = program_test_env(0);
+    let (banks, payer, blockhash) = program_test.start().await;
+    (banks, payer, validator_keypair, blockhash)
+}
+
+/// The accounts of a delegation committing at the commit frequency
+fn program_test_env(commit_frequency_ms: u64) -> (ProgramTest, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let validator_keypair = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator_keypair.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account metadata PDA
+    let delegation_metadata_data = get_delegation_metadata_data(validator_keypair.pubkey(), None);
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA
+    let mut delegation_record_data = get_delegation_record_data(validator_keypair.pubkey(), None);
+    let mut delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_data).unwrap();
+    delegation_record.commit_frequency_ms = commit_frequency_ms;
+    delegation_record
+        .to_bytes_with_discriminator(&mut delegation_record_data)
+        .unwrap();
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator_keypair.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    (program_test, validator_keypair)
+}
@@ -0,0 +1,485 @@
+use crate::fixtures::{
+    get_delegation_metadata_data_on_curve, get_delegation_record_on_curve_data, TEST_AUTHORITY,
+};
+use dlp::args::{CommitStateArgs, Encoding};
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    ephemeral_balance_pda_from_payer, fees_vault_pda, undelegate_buffer_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{DelegationMetadata, DelegationRecord};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_instruction, system_program};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+mod fixtures;
+
+/// The seeds of the generated scenarios, each one replayable on its own
+const SEEDS: [u64; 4] = [1, 7, 42, 1337];
+const STEPS: usize = 32;
+const DELEGATED_ACCOUNTS: usize = 3;
+const ESCROWS: u8 = 2;
+
+#[tokio::test]
+async fn test_lamport_conservation_across_random_interleavings() {
+    for seed in SEEDS {
+        run_scenario(seed).await;
+    }
+}
+
+/// Run a random sequence of valid instructions, checking the invariants after every step
+async fn run_scenario(seed: u64) {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut scenario = Scenario::new(&mut rng).await;
+    let total_lamports = scenario.total_lamports().await;
+    scenario.check_invariants(seed, total_lamports).await;
+
+    for step_index in 0..STEPS {
+        let step = scenario.generate_step(&mut rng).await;
+        scenario.execute_step(seed, step_index, &step).await;
+        scenario.check_invariants(seed, total_lamports).await;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DelegationState {
+    Delegated,
+    Committed { allow_undelegation: bool },
+    Undelegatable,
+    Undelegated,
+}
+
+#[derive(Debug)]
+enum Step {
+    Commit {
+        account: usize,
+        lamports: u64,
+        allow_undelegation: bool,
+    },
+    Finalize {
+        account: usize,
+    },
+    Undelegate {
+        account: usize,
+    },
+    /// Lamports sent to a delegated account on chain, outside of the ephemeral rollup
+    TransferToDelegated {
+        account: usize,
+        amount: u64,
+    },
+    TopUpEscrow {
+        index: u8,
+        amount: u64,
+    },
+}
+
+struct Scenario {
+    context: ProgramTestContext,
+    validator: Keypair,
+    user: Keypair,
+    delegated_accounts: Vec<(Pubkey, DelegationState)>,
+    /// The lamports each escrow of the user is expected to hold
+    escrows: Vec<u64>,
+    /// The transaction fees paid so far, leaving the tracked accounts
+    fees: u64,
+}
+
+impl Scenario {
+    async fn new(rng: &mut SmallRng) -> Self {
+        let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+        program_test.prefer_bpf(true);
+        let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+        let user = Keypair::new();
+
+        for wallet in [validator.pubkey(), user.pubkey()] {
+            add_account(
+                &mut program_test,
+                wallet,
+                10 * LAMPORTS_PER_SOL,
+                system_program::id(),
+                vec![],
+            );
+        }
+
+        // Setup the protocol and validator fees vaults
+        for vault in [
+            fees_vault_pda(),
+            validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        ] {
+            add_account(
+                &mut program_test,
+                vault,
+                Rent::default().minimum_balance(0),
+                dlp::id(),
+                vec![],
+            );
+        }
+
+        // Setup the delegated on curve accounts, with their records and metadata
+        let mut delegated_accounts = vec![];
+        for _ in 0..DELEGATED_ACCOUNTS {
+            let delegated_account = Keypair::new().pubkey();
+            let lamports = rng.gen_range(LAMPORTS_PER_SOL / 2..=2 * LAMPORTS_PER_SOL);
+            add_account(
+                &mut program_test,
+                delegated_account,
+                lamports,
+                dlp::id(),
+                vec![],
+            );
+            let delegation_record_data =
+                get_delegation_record_on_curve_data(validator.pubkey(), Some(lamports));
+            add_account(
+                &mut program_test,
+                delegation_record_pda_from_delegated_account(&delegated_account),
+                Rent::default().minimum_balance(delegation_record_data.len()),
+                dlp::id(),
+                delegation_record_data,
+            );
+            let delegation_metadata_data =
+                get_delegation_metadata_data_on_curve(validator.pubkey(), Some(false));
+            add_account(
+                &mut program_test,
+                delegation_metadata_pda_from_delegated_account(&delegated_account),
+                Rent::default().minimum_balance(delegation_metadata_data.len()),
+                dlp::id(),
+                delegation_metadata_data,
+            );
+            delegated_accounts.push((delegated_account, DelegationState::Delegated));
+        }
+
+        let context = program_test.start_with_context().await;
+        Self {
+            context,
+            validator,
+            user,
+            delegated_accounts,
+            escrows: vec![0; ESCROWS as usize],
+            fees: 0,
+        }
+    }
+
+    /// Generate a step which is valid in the current state of the delegated accounts
+    async fn generate_step(&mut self, rng: &mut SmallRng) -> Step {
+        let mut candidates = vec![Step::TopUpEscrow {
+            index: rng.gen_range(0..ESCROWS),
+            amount: rng.gen_range(1..=LAMPORTS_PER_SOL / 10),
+        }];
+        for (account, (delegated_account, state)) in
+            self.delegated_accounts.clone().into_iter().enumerate()
+        {
+            if state == DelegationState::Undelegated {
+                continue;
+            }
+            candidates.push(Step::TransferToDelegated {
+                account,
+                amount: rng.gen_range(1..=LAMPORTS_PER_SOL / 100),
+            });
+            match state {
+                DelegationState::Delegated => {
+                    // Commit lamports from half of the recorded ones to a bit more
+                    let record_lamports = self.delegation_record(&delegated_account).await.lamports;
+                    let minimum = 2 * Rent::default().minimum_balance(0);
+                    let lamports = rng.gen_range(
+                        (record_lamports / 2).max(minimum)
+                            ..=record_lamports + LAMPORTS_PER_SOL / 100,
+                    );
+                    candidates.push(Step::Commit {
+                        account,
+                        lamports,
+                        allow_undelegation: rng.gen_bool(0.25),
+                    });
+                }
+                DelegationState::Committed { .. } => candidates.push(Step::Finalize { account }),
+                DelegationState::Undelegatable => candidates.push(Step::Undelegate { account }),
+                DelegationState::Undelegated => {}
+            }
+        }
+        candidates.swap_remove(rng.gen_range(0..candidates.len()))
+    }
+
+    async fn execute_step(&mut self, seed: u64, step_index: usize, step: &Step) {
+        let validator = self.validator.pubkey();
+        let (ixs, signer) = match *step {
+            Step::Commit {
+                account,
+                lamports,
+                allow_undelegation,
+            } => {
+                let delegated_account = self.delegated_accounts[account].0;
+                let nonce = self
+                    .delegation_metadata(&delegated_account)
+                    .await
+                    .last_update_nonce
+                    + 1;
+                let ix = dlp::instruction_builder::commit_state(
+                    validator,
+                    delegated_account,
+                    system_program::id(),
+                    CommitStateArgs {
+                        data: vec![],
+                        nonce,
+                        allow_undelegation,
+                        lamports,
+                        er_block_hash: None,
+                        base_state_hash: None,
+                        escrow_spend: None,
+                        encoding: Encoding::Raw,
+                    },
+                );
+                self.delegated_accounts[account].1 =
+                    DelegationState::Committed { allow_undelegation };
+                (vec![ix], &self.validator)
+            }
+            Step::Finalize { account } => {
+                let (delegated_account, state) = self.delegated_accounts[account];
+                let ix = dlp::instruction_builder::finalize(validator, delegated_account);
+                self.delegated_accounts[account].1 = match state {
+                    DelegationState::Committed {
+                        allow_undelegation: true,
+                    } => DelegationState::Undelegatable,
+                    _ => DelegationState::Delegated,
+                };
+                (vec![ix], &self.validator)
+            }
+            Step::Undelegate { account } => {
+                let delegated_account = self.delegated_accounts[account].0;
+                let ix = dlp::instruction_builder::undelegate(
+                    validator,
+                    delegated_account,
+                    system_program::id(),
+                    validator,
+                );
+                self.delegated_accounts[account].1 = DelegationState::Undelegated;
+                (vec![ix], &self.validator)
+            }
+            Step::TransferToDelegated { account, amount } => {
+                let delegated_account = self.delegated_accounts[account].0;
+                let ix =
+                    system_instruction::transfer(&self.user.pubkey(), &delegated_account, amount);
+                (vec![ix], &self.user)
+            }
+            Step::TopUpEscrow { index, amount } => {
+                let ix = dlp::instruction_builder::top_up_ephemeral_balance(
+                    self.user.pubkey(),
+                    self.user.pubkey(),
+                    Some(amount),
+                    Some(index),
+                );
+                let escrow = &mut self.escrows[index as usize];
+                if *escrow == 0 {
+                    *escrow = Rent::default().minimum_balance(0);
+                }
+                *escrow += amount;
+                (vec![ix], &self.user)
+            }
+        };
+
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let tx =
+            Transaction::new_signed_with_payer(&ixs, Some(&signer.pubkey()), &[signer], blockhash);
+        let fee = self
+            .context
+            .banks_client
+            .get_fee_for_message(tx.message.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        let res = self.context.banks_client.process_transaction(tx).await;
+        assert!(
+            res.is_ok(),
+            "seed {} step {} {:?} failed: {:?}",
+            seed,
+            step_index,
+            step,
+            res
+        );
+        self.fees += fee;
+
+        // A finalized commit settles the lamports of the delegated account in its record
+        if let Step::Finalize { account } = *step {
+            let delegated_account = self.delegated_accounts[account].0;
+            assert_eq!(
+                self.lamports(&delegated_account).await,
+                self.delegation_record(&delegated_account).await.lamports,
+                "seed {} step {}: finalize did not settle the lamports",
+                seed,
+                step_index
+            );
+        }
+    }
+
+    async fn check_invariants(&mut self, seed: u64, total_lamports: u64) {
+        // The lamports only move between the tracked accounts, or pay the transaction fees
+        assert_eq!(
+            self.total_lamports().await + self.fees,
+            total_lamports,
+            "seed {}: lamports are not conserved",
+            seed
+        );
+
+        for vault in [
+            fees_vault_pda(),
+            validator_fees_vault_pda_from_validator(&self.validator.pubkey()),
+        ] {
+            assert!(self.lamports(&vault).await >= Rent::default().minimum_balance(0));
+        }
+
+        for index in 0..ESCROWS {
+            let escrow = ephemeral_balance_pda_from_payer(&self.user.pubkey(), index);
+            assert_eq!(
+                self.lamports(&escrow).await,
+                self.escrows[index as usize],
+                "seed {}: escrow {} holds unexpected lamports",
+                seed,
+                index
+            );
+        }
+
+        for (delegated_account, state) in self.delegated_accounts.clone() {
+            let account = self.account(&delegated_account).await.unwrap();
+            let commit_record = self
+                .account(&commit_record_pda_from_delegated_account(
+                    &delegated_account,
+                ))
+                .await;
+            if state == DelegationState::Undelegated {
+                assert_eq!(account.owner, system_program::id(), "seed {}", seed);
+                assert!(self
+                    .account(&delegation_record_pda_from_delegated_account(
+                        &delegated_account
+                    ))
+                    .await
+                    .is_none());
+                assert!(self
+                    .account(&delegation_metadata_pda_from_delegated_account(
+                        &delegated_account
+                    ))
+                    .await
+                    .is_none());
+                assert!(commit_record.is_none());
+                continue;
+            }
+
+            // The delegated account holds at least the lamports of its record and stays
+            // rent exempt
+            assert_eq!(account.owner, dlp::id(), "seed {}", seed);
+            let delegation_record = self.delegation_record(&delegated_account).await;
+            assert!(
+                account.lamports >= delegation_record.lamports,
+                "seed {}: delegated account {} holds {} lamports, {} recorded",
+                seed,
+                delegated_account,
+                account.lamports,
+                delegation_record.lamports
+            );
+            assert!(Rent::default().is_exempt(account.lamports, account.data.len()));
+
+            // A commit is pending until finalized, and allows the undelegation if requested
+            let is_committed = matches!(state, DelegationState::Committed { .. });
+            assert_eq!(commit_record.is_some(), is_committed, "seed {}", seed);
+            let delegation_metadata = self.delegation_metadata(&delegated_account).await;
+            assert_eq!(
+                delegation_metadata.is_undelegatable,
+                matches!(
+                    state,
+                    DelegationState::Undelegatable
+                        | DelegationState::Committed {
+                            allow_undelegation: true
+                        }
+                ),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    /// The lamports of every account the steps can move lamports between
+    async fn total_lamports(&mut self) -> u64 {
+        let mut accounts = vec![
+            self.validator.pubkey(),
+            self.user.pubkey(),
+            fees_vault_pda(),
+            validator_fees_vault_pda_from_validator(&self.validator.pubkey()),
+        ];
+        accounts.extend(
+            (0..ESCROWS).map(|index| ephemeral_balance_pda_from_payer(&self.user.pubkey(), index)),
+        );
+        for (delegated_account, _) in &self.delegated_accounts {
+            accounts.extend([
+                *delegated_account,
+                delegation_record_pda_from_delegated_account(delegated_account),
+                delegation_metadata_pda_from_delegated_account(delegated_account),
+                commit_state_pda_from_delegated_account(delegated_account),
+                commit_record_pda_from_delegated_account(delegated_account),
+                undelegate_buffer_pda_from_delegated_account(delegated_account),
+            ]);
+        }
+        let mut total = 0;
+        for account in accounts {
+            total += self.lamports(&account).await;
+        }
+        total
+    }
+
+    async fn account(&mut self, pubkey: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*pubkey)
+            .await
+            .unwrap()
+    }
+
+    async fn lamports(&mut self, pubkey: &Pubkey) -> u64 {
+        self.account(pubkey)
+            .await
+            .map_or(0, |account| account.lamports)
+    }
+
+    async fn delegation_record(&mut self, delegated_account: &Pubkey) -> DelegationRecord {
+        let account = self
+            .account(&delegation_record_pda_from_delegated_account(
+                delegated_account,
+            ))
+            .await
+            .unwrap();
+        DelegationRecord::try_from_bytes_with_discriminator(&account.data).unwrap()
+    }
+
+    async fn delegation_metadata(&mut self, delegated_account: &Pubkey) -> DelegationMetadata {
+        let account = self
+            .account(&delegation_metadata_pda_from_delegated_account(
+                delegated_account,
+            ))
+            .await
+            .unwrap();
+        DelegationMetadata::try_from_bytes_with_discriminator(&account.data).unwrap()
+    }
+}
+
+fn add_account(
+    program_test: &mut ProgramTest,
+    pubkey: Pubkey,
+    lamports: u64,
+    owner: Pubkey,
+    data: Vec<u8>,
+) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
@@ -3,13 +3,14 @@ use crate::fixtures::{
     get_delegation_metadata_data_on_curve, COMMIT_NEW_STATE_ACCOUNT_DATA, DELEGATED_PDA_ID,
     DELEGATED_PDA_OWNER_ID, ON_CURVE_KEYPAIR, TEST_AUTHORITY,
 };
-use dlp::args::CommitStateArgs;
+use dlp::args::{CommitStateArgs, Encoding};
 use dlp::pda::{
     commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
     fees_vault_pda, validator_fees_vault_pda_from_validator,
 };
-use dlp::state::{CommitRecord, DelegationMetadata};
+use dlp::state::{CommitRecord, CommitStateLayout, DelegationMetadata, PendingState};
+use solana_program::instruction::InstructionError;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
@@ -17,7 +18,7 @@ use solana_program_test::{read_file, BanksClient, ProgramTest};
 use solana_sdk::{
     account::Account,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 mod fixtures;
@@ -309,6 +310,127 @@ async fn test_commit_system_account_after_balance_increase_and_increase_mainchai
     assert!(validator_vault.lamports >= Rent::default().minimum_balance(0));
 }
 
+#[tokio::test]
+async fn test_commit_unsettleable_lamports() {
+    // Setup
+    let (delegated_account, owner_program) = get_delegated_account_and_owner(true);
+    let (banks, _, authority, blockhash) =
+        setup_program_for_commit_test_env(SetupProgramCommitTestEnvArgs {
+            delegated_account_init_lamports: LAMPORTS_PER_SOL,
+            delegated_account_current_lamports: LAMPORTS_PER_SOL,
+            validator_vault_init_lamports: Rent::default().minimum_balance(0),
+            delegated_account,
+            owner_program,
+        })
+        .await;
+
+    // The delegated account would not be rent exempt once the commit is finalized
+    let ix = dlp::instruction_builder::commit_state(
+        authority.pubkey(),
+        delegated_account,
+        owner_program,
+        CommitStateArgs {
+            data: COMMIT_NEW_STATE_ACCOUNT_DATA.to_vec(),
+            nonce: 1,
+            allow_undelegation: true,
+            lamports: 1,
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(dlp::error::DlpError::UnsettleableCommit as u32)
+        )
+    );
+
+    // Assert the commit was rejected
+    let commit_state_pda = commit_state_pda_from_delegated_account(&delegated_account);
+    assert!(banks.get_account(commit_state_pda).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_commit_lamports_below_rent_exemption() {
+    // Setup, the delegated account received lamports while delegated so that the commits
+    // below can always be settled
+    let (delegated_account, owner_program) = get_delegated_account_and_owner(true);
+    let (banks, _, authority, blockhash) =
+        setup_program_for_commit_test_env(SetupProgramCommitTestEnvArgs {
+            delegated_account_init_lamports: LAMPORTS_PER_SOL,
+            delegated_account_current_lamports: 2 * LAMPORTS_PER_SOL,
+            validator_vault_init_lamports: Rent::default().minimum_balance(0),
+            delegated_account,
+            owner_program,
+        })
+        .await;
+    let commit = |nonce: u64, data_len: usize, lamports: u64| {
+        let ix = dlp::instruction_builder::commit_state(
+            authority.pubkey(),
+            delegated_account,
+            owner_program,
+            CommitStateArgs {
+                data: vec![1; data_len],
+                nonce,
+                allow_undelegation: false,
+                lamports,
+                er_block_hash: None,
+                base_state_hash: None,
+                escrow_spend: None,
+                encoding: Encoding::Raw,
+            },
+        );
+        Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&authority.pubkey()),
+            &[&authority],
+            blockhash,
+        )
+    };
+    let not_rent_exempt = TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(dlp::error::DlpError::CommittedLamportsNotRentExempt as u32),
+    );
+
+    // Growing the account requires the committed lamports to cover the grown data
+    let grown_rent = Rent::default().minimum_balance(1_000);
+    let res = banks
+        .process_transaction(commit(1, 1_000, grown_rent - 1))
+        .await;
+    assert_eq!(res.unwrap_err().unwrap(), not_rent_exempt);
+    let res = banks
+        .process_transaction(commit(1, 1_000, grown_rent))
+        .await;
+    assert!(res.is_ok());
+    let ix = dlp::instruction_builder::finalize(authority.pubkey(), delegated_account);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+
+    // Shrinking it only requires the committed lamports to cover the shrunk data
+    let shrunk_rent = Rent::default().minimum_balance(11);
+    let res = banks
+        .process_transaction(commit(2, 11, shrunk_rent - 1))
+        .await;
+    assert_eq!(res.unwrap_err().unwrap(), not_rent_exempt);
+    let res = banks.process_transaction(commit(2, 11, shrunk_rent)).await;
+    assert!(res.is_ok());
+}
+
 fn get_delegated_account_and_owner(is_pda: bool) -> (Pubkey, Pubkey) {
     let (delegated_account, owner_program) = if is_pda {
         (DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID)
@@ -450,6 +572,10 @@ async fn commit_new_state(args: CommitNewStateArgs<'_>) {
         nonce: 1,
         allow_undelegation: true,
         lamports: args.new_delegated_account_lamports,
+        er_block_hash: None,
+        base_state_hash: None,
+        escrow_spend: None,
+        encoding: Encoding::Raw,
     };
 
     // Commit the state for the delegated account
@@ -477,7 +603,11 @@ async fn commit_new_state(args: CommitNewStateArgs<'_>) {
         .await
         .unwrap()
         .unwrap();
-    assert_eq!(commit_state_account.data, data);
+    assert_eq!(
+        PendingState::committed_data(&commit_state_account.data, CommitStateLayout::Pending)
+            .unwrap(),
+        data
+    );
 
     // Check that the commit has enough collateral to finalize the proposed state diff
     let delegated_account = args
@@ -0,0 +1,105 @@
+use crate::fixtures::TEST_AUTHORITY;
+use dlp::discriminator::DlpDiscriminator;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_malformed_instructions_do_not_panic() {
+    // Setup
+    let (banks, admin, blockhash) = setup_program_test_env().await;
+    let mut rng = SmallRng::seed_from_u64(42);
+
+    // Every instruction, and one which does not exist
+    let discriminators: Vec<u8> = (0..=u8::MAX)
+        .filter(|&discriminator| DlpDiscriminator::try_from(discriminator).is_ok())
+        .chain([u8::MAX])
+        .collect();
+
+    for discriminator in discriminators {
+        let tag = (discriminator as u64).to_le_bytes().to_vec();
+        let random: Vec<u8> = (0..200).map(|_| rng.gen()).collect();
+        let payloads = [
+            tag[..1].to_vec(),
+            tag.clone(),
+            [tag.clone(), vec![u8::MAX]].concat(),
+            [tag.clone(), vec![u8::MAX; 40]].concat(),
+            [tag.clone(), random].concat(),
+        ];
+        let account_sets = [
+            vec![],
+            vec![AccountMeta::new(admin.pubkey(), true)],
+            [AccountMeta::new(admin.pubkey(), true)]
+                .into_iter()
+                .chain((0..12).map(|i| {
+                    if i % 2 == 0 {
+                        AccountMeta::new(Pubkey::new_unique(), false)
+                    } else {
+                        AccountMeta::new_readonly(Pubkey::new_unique(), false)
+                    }
+                }))
+                .chain([AccountMeta::new_readonly(system_program::id(), false)])
+                .collect(),
+        ];
+
+        for data in &payloads {
+            for accounts in &account_sets {
+                let ix = Instruction {
+                    program_id: dlp::id(),
+                    accounts: accounts.clone(),
+                    data: data.clone(),
+                };
+                let tx = Transaction::new_signed_with_payer(
+                    &[ix],
+                    Some(&admin.pubkey()),
+                    &[&admin],
+                    blockhash,
+                );
+                // Rejecting the instruction is fine, panicking is not
+                if let Err(err) = banks.process_transaction(tx).await {
+                    assert_ne!(
+                        err.unwrap(),
+                        TransactionError::InstructionError(
+                            0,
+                            InstructionError::ProgramFailedToComplete
+                        ),
+                        "instruction {} with {} bytes of data and {} accounts panicked",
+                        discriminator,
+                        data.len(),
+                        accounts.len()
+                    );
+                }
+            }
+        }
+    }
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let admin_keypair = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        admin_keypair.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, admin_keypair, blockhash)
+}
@@ -0,0 +1,130 @@
+use dlp::pda::delegation_record_pda_from_delegated_account;
+use dlp::state::DelegationRecord;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::fixtures::{get_legacy_delegation_record_data, DELEGATED_PDA_ID, TEST_AUTHORITY};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_migrate_delegation_record() {
+    // Setup
+    let (mut context, payer) = setup_program_test_env().await;
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    // The legacy record is read transparently
+    let legacy_account = context
+        .banks_client
+        .get_account(delegation_record_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let legacy_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&legacy_account.data).unwrap();
+    assert_eq!(legacy_record.version, DelegationRecord::LEGACY_VERSION);
+    assert_eq!(legacy_record.authority, validator.pubkey());
+
+    // Migrate the record
+    let ix = dlp::instruction_builder::migrate_delegation_record(payer.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix.clone()], &payer).await;
+    assert!(res.is_ok());
+
+    // The record has the current layout, its fields kept
+    let account = context
+        .banks_client
+        .get_account(delegation_record_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        account.data.len(),
+        DelegationRecord::size_with_discriminator()
+    );
+    assert!(Rent::default().is_exempt(account.lamports, account.data.len()));
+    let record = DelegationRecord::try_from_bytes_with_discriminator(&account.data).unwrap();
+    assert_eq!(record.version, DelegationRecord::VERSION);
+    assert_eq!(
+        record,
+        DelegationRecord {
+            version: DelegationRecord::VERSION,
+            ..legacy_record
+        }
+    );
+
+    // Migrating the record again is a no-op
+    let res = process(&mut context, &[ix], &payer).await;
+    assert!(res.is_ok());
+    let migrated_account = context
+        .banks_client
+        .get_account(delegation_record_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(migrated_account, account);
+}
+
+#[tokio::test]
+async fn test_migrate_delegation_record_of_undelegated_account() {
+    // Setup
+    let (mut context, payer) = setup_program_test_env().await;
+
+    // An account without a delegation record cannot be migrated
+    let ix =
+        dlp::instruction_builder::migrate_delegation_record(payer.pubkey(), Pubkey::new_unique());
+    let res = process(&mut context, &[ix], &payer).await;
+    assert!(res.is_err());
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let payer = Keypair::new();
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        payer.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegation record created before the record was versioned
+    let delegation_record_data = get_legacy_delegation_record_data(validator.pubkey());
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, payer)
+}
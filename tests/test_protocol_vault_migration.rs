@@ -0,0 +1,203 @@
+use dlp::args::SetProtocolConfigArgs;
+use dlp::consts::PROTOCOL_VAULT_MIGRATION_TIMELOCK_SLOTS;
+use dlp::error::DlpError;
+use dlp::pda::{fees_vault_pda, fees_vault_pda_from_generation, protocol_config_pda};
+use dlp::state::{ProtocolConfig, RETIRED_FEES_VAULT_GENERATION};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::TEST_AUTHORITY;
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_protocol_vault_migration() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env().await;
+    let new_vault = fees_vault_pda_from_generation(1);
+
+    // Propose the migration
+    let ix = dlp::instruction_builder::propose_protocol_vault_migration(admin.pubkey());
+    assert!(process(&mut context, &[ix], &admin).await.is_ok());
+    let protocol_config = get_protocol_config(&mut context).await;
+    assert_eq!(protocol_config.fees_vault_generation, 0);
+    assert_eq!(protocol_config.pending_fees_vault_generation, 1);
+    let slot = context.banks_client.get_root_slot().await.unwrap();
+    assert!(
+        protocol_config.fees_vault_migration_slot >= slot + PROTOCOL_VAULT_MIGRATION_TIMELOCK_SLOTS
+    );
+
+    // Executing it before the timelock elapsed fails
+    let ix = dlp::instruction_builder::execute_protocol_vault_migration(admin.pubkey(), 0);
+    let res = process(&mut context, &[ix.clone()], &admin).await;
+    assert_dlp_error(res, DlpError::ProtocolVaultMigrationTimelock);
+
+    // Executing it after the timelock elapsed moves the fees to the new vault
+    context
+        .warp_to_slot(protocol_config.fees_vault_migration_slot + 1)
+        .unwrap();
+    assert!(process(&mut context, &[ix], &admin).await.is_ok());
+
+    let rent = Rent::default();
+    let old_vault_account = get_account(&mut context, fees_vault_pda()).await;
+    assert_eq!(old_vault_account.lamports, rent.minimum_balance(8));
+    assert_eq!(
+        old_vault_account.data,
+        RETIRED_FEES_VAULT_GENERATION.to_le_bytes()
+    );
+    let new_vault_account = get_account(&mut context, new_vault).await;
+    assert_eq!(new_vault_account.owner, dlp::id());
+    assert_eq!(new_vault_account.data, 1u64.to_le_bytes());
+    assert_eq!(
+        new_vault_account.lamports,
+        LAMPORTS_PER_SOL + rent.minimum_balance(8) - rent.minimum_balance(0)
+    );
+
+    let protocol_config = get_protocol_config(&mut context).await;
+    assert_eq!(protocol_config.fees_vault_generation, 1);
+    assert_eq!(protocol_config.pending_fees_vault_generation, 0);
+
+    // The retired vault no longer collects nor pays out fees
+    let ix = dlp::instruction_builder::protocol_claim_fees(admin.pubkey());
+    let res = process(&mut context, &[ix.clone()], &admin).await;
+    assert_dlp_error(res, DlpError::RetiredProtocolFeesVault);
+
+    // The fees are claimed from the new vault
+    let mut ix = ix;
+    ix.accounts[1].pubkey = new_vault;
+    assert!(process(&mut context, &[ix], &admin).await.is_ok());
+    let new_vault_account = get_account(&mut context, new_vault).await;
+    assert_eq!(new_vault_account.lamports, rent.minimum_balance(8));
+
+    // Setting the protocol config keeps the vault generation
+    let ix = dlp::instruction_builder::set_protocol_config(
+        admin.pubkey(),
+        SetProtocolConfigArgs {
+            escrow_dust_threshold: 1,
+            undelegation_grace_slots: 0,
+            trusted_exporter: Pubkey::default(),
+        },
+    );
+    assert!(process(&mut context, &[ix], &admin).await.is_ok());
+    let protocol_config = get_protocol_config(&mut context).await;
+    assert_eq!(protocol_config.escrow_dust_threshold, 1);
+    assert_eq!(protocol_config.fees_vault_generation, 1);
+}
+
+#[tokio::test]
+async fn test_execute_protocol_vault_migration_not_proposed() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env().await;
+
+    let ix = dlp::instruction_builder::execute_protocol_vault_migration(admin.pubkey(), 0);
+    let res = process(&mut context, &[ix], &admin).await;
+    assert_dlp_error(res, DlpError::NoPendingProtocolVaultMigration);
+}
+
+#[tokio::test]
+async fn test_propose_protocol_vault_migration_unauthorized() {
+    // Setup
+    let (mut context, admin) = setup_program_test_env().await;
+    let other = Keypair::new();
+    let ix = solana_sdk::system_instruction::transfer(
+        &admin.pubkey(),
+        &other.pubkey(),
+        LAMPORTS_PER_SOL,
+    );
+    process(&mut context, &[ix], &admin).await.unwrap();
+
+    let ix = dlp::instruction_builder::propose_protocol_vault_migration(other.pubkey());
+    let res = process(&mut context, &[ix], &other).await;
+    assert_dlp_error(res, DlpError::Unauthorized);
+}
+
+async fn get_account(
+    context: &mut ProgramTestContext,
+    pubkey: solana_program::pubkey::Pubkey,
+) -> Account {
+    context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+async fn get_protocol_config(context: &mut ProgramTestContext) -> ProtocolConfig {
+    let protocol_config_account = get_account(context, protocol_config_pda()).await;
+    *ProtocolConfig::try_from_bytes_with_discriminator(&protocol_config_account.data).unwrap()
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    // The admin is the upgrade authority of the delegation program
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the protocol fees vault created at bootstrap, holding the fees
+    program_test.add_account(
+        fees_vault_pda(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the protocol config PDA
+    let mut data = vec![0; ProtocolConfig::size_with_discriminator()];
+    ProtocolConfig::default()
+        .to_bytes_with_discriminator(&mut data)
+        .unwrap();
+    program_test.add_account(
+        protocol_config_pda(),
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, admin)
+}
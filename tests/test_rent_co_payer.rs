@@ -0,0 +1,351 @@
+use dlp::args::{DelegateArgs, RentCoPayer};
+use dlp::consts::RENT_FEES_PERCENTAGE;
+use dlp::error::DlpError;
+use dlp::instruction_builder::with_rent_co_payer;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fees_vault_pda, validator_fees_vault_pda_from_validator,
+};
+use dlp::state::DelegationMetadata;
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    create_delegation_metadata_data_on_curve_with_rent_co_payer,
+    get_delegation_record_on_curve_data, ON_CURVE_KEYPAIR, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_delegate_with_rent_co_payer() {
+    // Setup
+    let (mut context, accounts) = setup_program_test_env(false, false).await;
+    let rent_co_payer = RentCoPayer {
+        payer: accounts.rent_co_payer.pubkey(),
+        share_bps: 3_333,
+    };
+
+    // Both payers must pay a share of the rent
+    let ix = delegate(
+        &accounts,
+        RentCoPayer {
+            share_bps: 0,
+            ..rent_co_payer
+        },
+    );
+    let res = process(&mut context, &[ix], &accounts.delegate_signers()).await;
+    assert_dlp_error(res, DlpError::InvalidRentCoPayer);
+
+    // The co-payer passed must be the one of the args
+    let mut ix = delegate(&accounts, rent_co_payer);
+    ix.accounts.last_mut().unwrap().pubkey = accounts.rent_payer.pubkey();
+    let res = process(
+        &mut context,
+        &[ix],
+        &[&accounts.rent_payer, &accounts.delegated_account],
+    )
+    .await;
+    assert_dlp_error(res, DlpError::InvalidRentCoPayer);
+
+    let ix = delegate(&accounts, rent_co_payer);
+    process(&mut context, &[ix], &accounts.delegate_signers())
+        .await
+        .unwrap();
+
+    // The co-payer paid its share of the rent of each delegation PDA, rounded down
+    let record_lamports = lamports(&mut context, &accounts.delegation_record()).await;
+    let metadata_lamports = lamports(&mut context, &accounts.delegation_metadata()).await;
+    let share = rent_co_payer.share_of(record_lamports) + rent_co_payer.share_of(metadata_lamports);
+    assert!(share > 0);
+    assert_eq!(
+        lamports(&mut context, &accounts.rent_co_payer.pubkey()).await,
+        LAMPORTS_PER_SOL - share
+    );
+    assert_eq!(
+        lamports(&mut context, &accounts.rent_payer.pubkey()).await,
+        LAMPORTS_PER_SOL - (record_lamports + metadata_lamports - share)
+    );
+
+    let delegation_metadata = delegation_metadata(&mut context, &accounts).await;
+    assert_eq!(delegation_metadata.rent_co_payer, Some(rent_co_payer));
+    assert_eq!(delegation_metadata.rent_co_payer_lamports, share);
+}
+
+#[tokio::test]
+async fn test_undelegate_with_rent_co_payer() {
+    assert_undelegate_refunds(false).await;
+}
+
+#[tokio::test]
+async fn test_undelegate_with_rent_co_payer_after_metadata_growth() {
+    assert_undelegate_refunds(true).await;
+}
+
+/// Undelegate an account delegated with a rent co-payer, its delegation metadata having grown
+/// since delegation if `grown`, and check the refunds to the lamport
+async fn assert_undelegate_refunds(grown: bool) {
+    // Setup
+    let (mut context, accounts) = setup_program_test_env(true, grown).await;
+
+    // The co-payer recorded at delegation must be refunded
+    let ix = undelegate(&accounts);
+    let res = process(&mut context, &[ix], &[&accounts.validator]).await;
+    assert_dlp_error(res, DlpError::InvalidRentCoPayer);
+
+    let record_lamports = lamports(&mut context, &accounts.delegation_record()).await;
+    let metadata_lamports = lamports(&mut context, &accounts.delegation_metadata()).await;
+    let paid = delegation_metadata(&mut context, &accounts)
+        .await
+        .rent_co_payer_lamports;
+    let fees_before = lamports(&mut context, &fees_vault_pda()).await
+        + lamports(&mut context, &accounts.validator_fees_vault()).await;
+
+    let ix = with_rent_co_payer(undelegate(&accounts), accounts.rent_co_payer.pubkey());
+    process(&mut context, &[ix], &[&accounts.validator])
+        .await
+        .unwrap();
+
+    // The co-payer is refunded the rent it paid net of its share of the rent fees, rounded
+    // up, the rent payer being refunded the rest, including the growth of the metadata
+    let fees = lamports(&mut context, &fees_vault_pda()).await
+        + lamports(&mut context, &accounts.validator_fees_vault()).await
+        - fees_before;
+    assert_eq!(
+        fees,
+        record_lamports * RENT_FEES_PERCENTAGE as u64 / 100
+            + metadata_lamports * RENT_FEES_PERCENTAGE as u64 / 100
+    );
+    let co_refund = paid - (paid * RENT_FEES_PERCENTAGE as u64).div_ceil(100);
+    let refund = record_lamports + metadata_lamports - fees - co_refund;
+    assert_eq!(
+        lamports(&mut context, &accounts.rent_co_payer.pubkey()).await,
+        LAMPORTS_PER_SOL + co_refund
+    );
+    assert_eq!(
+        lamports(&mut context, &accounts.rent_payer.pubkey()).await,
+        LAMPORTS_PER_SOL + refund
+    );
+    assert!(context
+        .banks_client
+        .get_account(accounts.delegation_metadata())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn delegation_metadata(
+    context: &mut ProgramTestContext,
+    accounts: &TestAccounts,
+) -> DelegationMetadata {
+    let delegation_metadata_account = context
+        .banks_client
+        .get_account(accounts.delegation_metadata())
+        .await
+        .unwrap()
+        .unwrap();
+    DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+        .unwrap()
+}
+
+struct TestAccounts {
+    validator: Keypair,
+    delegated_account: Keypair,
+    rent_payer: Keypair,
+    rent_co_payer: Keypair,
+}
+
+impl TestAccounts {
+    fn delegation_record(&self) -> Pubkey {
+        delegation_record_pda_from_delegated_account(&self.delegated_account.pubkey())
+    }
+
+    fn delegation_metadata(&self) -> Pubkey {
+        delegation_metadata_pda_from_delegated_account(&self.delegated_account.pubkey())
+    }
+
+    fn validator_fees_vault(&self) -> Pubkey {
+        validator_fees_vault_pda_from_validator(&self.validator.pubkey())
+    }
+
+    fn delegate_signers(&self) -> [&Keypair; 3] {
+        [
+            &self.rent_payer,
+            &self.delegated_account,
+            &self.rent_co_payer,
+        ]
+    }
+}
+
+fn delegate(accounts: &TestAccounts, rent_co_payer: RentCoPayer) -> Instruction {
+    let ix = dlp::instruction_builder::delegate(
+        accounts.rent_payer.pubkey(),
+        accounts.delegated_account.pubkey(),
+        None,
+        DelegateArgs {
+            commit_frequency_ms: u32::MAX,
+            validator: Some(accounts.validator.pubkey()),
+            rent_co_payer: Some(rent_co_payer),
+            ..Default::default()
+        },
+    );
+    with_rent_co_payer(ix, accounts.rent_co_payer.pubkey())
+}
+
+fn undelegate(accounts: &TestAccounts) -> Instruction {
+    dlp::instruction_builder::undelegate(
+        accounts.validator.pubkey(),
+        accounts.delegated_account.pubkey(),
+        system_program::id(),
+        accounts.rent_payer.pubkey(),
+    )
+}
+
+async fn lamports(context: &mut ProgramTestContext, pubkey: &Pubkey) -> u64 {
+    context
+        .banks_client
+        .get_account(*pubkey)
+        .await
+        .unwrap()
+        .map_or(0, |account| account.lamports)
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let signers = [&[&context.payer], signers].concat();
+    let tx =
+        Transaction::new_signed_with_payer(ixs, Some(&context.payer.pubkey()), &signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+/// Setup an on curve account owned by the delegation program, its payers and the fees vaults,
+/// already delegated with a rent co-payer and undelegatable if `delegated`, its delegation
+/// metadata having grown since delegation if `grown`
+async fn setup_program_test_env(
+    delegated: bool,
+    grown: bool,
+) -> (ProgramTestContext, TestAccounts) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let accounts = TestAccounts {
+        validator: Keypair::from_bytes(&TEST_AUTHORITY).unwrap(),
+        delegated_account: Keypair::from_bytes(&ON_CURVE_KEYPAIR).unwrap(),
+        rent_payer: Keypair::new(),
+        rent_co_payer: Keypair::new(),
+    };
+
+    for key in [
+        accounts.validator.pubkey(),
+        accounts.rent_payer.pubkey(),
+        accounts.rent_co_payer.pubkey(),
+    ] {
+        add_account(&mut program_test, key, system_program::id(), vec![]);
+    }
+    add_account(
+        &mut program_test,
+        accounts.delegated_account.pubkey(),
+        dlp::id(),
+        vec![],
+    );
+    add_account(&mut program_test, fees_vault_pda(), dlp::id(), vec![]);
+    add_account(
+        &mut program_test,
+        accounts.validator_fees_vault(),
+        dlp::id(),
+        vec![],
+    );
+
+    if delegated {
+        let rent_co_payer = RentCoPayer {
+            payer: accounts.rent_co_payer.pubkey(),
+            share_bps: 3_333,
+        };
+        let delegation_record_data = get_delegation_record_on_curve_data(
+            accounts.validator.pubkey(),
+            Some(LAMPORTS_PER_SOL),
+        );
+        let delegation_metadata_data = |rent_co_payer_lamports| {
+            create_delegation_metadata_data_on_curve_with_rent_co_payer(
+                accounts.rent_payer.pubkey(),
+                rent_co_payer,
+                rent_co_payer_lamports,
+                true,
+            )
+        };
+
+        // The co-payer paid its share of the rent of the delegation PDAs at delegation
+        let rent = Rent::default();
+        let rent_co_payer_lamports = rent_co_payer
+            .share_of(rent.minimum_balance(delegation_record_data.len()))
+            + rent_co_payer.share_of(rent.minimum_balance(delegation_metadata_data(0).len()));
+        let mut delegation_metadata_data = delegation_metadata_data(rent_co_payer_lamports);
+
+        // The finalized commits grew the metadata, funded by the validator
+        if grown {
+            let mut delegation_metadata =
+                DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_data)
+                    .unwrap();
+            delegation_metadata.last_er_block_hash = Some([7; 32]);
+            delegation_metadata.last_commit_slot = Some(1);
+            delegation_metadata.last_commit_timestamp = Some(1);
+            delegation_metadata_data.clear();
+            delegation_metadata
+                .to_bytes_with_discriminator(&mut delegation_metadata_data)
+                .unwrap();
+        }
+
+        add_account(
+            &mut program_test,
+            accounts.delegation_record(),
+            dlp::id(),
+            delegation_record_data,
+        );
+        add_account(
+            &mut program_test,
+            accounts.delegation_metadata(),
+            dlp::id(),
+            delegation_metadata_data,
+        );
+    }
+
+    let context = program_test.start_with_context().await;
+    (context, accounts)
+}
+
+/// Add an account holding the rent of its data, or a SOL if it has no data
+fn add_account(program_test: &mut ProgramTest, pubkey: Pubkey, owner: Pubkey, data: Vec<u8>) {
+    let lamports = if data.is_empty() {
+        LAMPORTS_PER_SOL
+    } else {
+        Rent::default().minimum_balance(data.len())
+    };
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
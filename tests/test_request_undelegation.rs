@@ -0,0 +1,274 @@
+use dlp::args::{CommitStateArgs, Encoding};
+use dlp::consts::DEFAULT_UNDELEGATION_GRACE_SLOTS;
+use dlp::error::DlpError;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    program_config_from_program_id, validator_fees_vault_pda_from_validator,
+};
+use dlp::state::{DelegationMetadata, ProgramConfig};
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_request_undelegation() {
+    // Setup
+    let (mut context, validator, rent_payer) = setup_program_test_env().await;
+
+    // The rent payer requests the undelegation
+    let ix = dlp::instruction_builder::request_undelegation(rent_payer.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix.clone()], &rent_payer).await;
+    assert!(res.is_ok());
+
+    let metadata = delegation_metadata(&mut context).await;
+    let undelegation_request = metadata.undelegation_request.unwrap();
+    assert_eq!(
+        undelegation_request.deadline_slot,
+        undelegation_request.slot + DEFAULT_UNDELEGATION_GRACE_SLOTS
+    );
+    assert!(!metadata.is_undelegatable);
+
+    // Requesting again during the grace period fails
+    let res = process(&mut context, &[ix.clone()], &rent_payer).await;
+    assert_dlp_error(res, DlpError::UndelegationGracePeriod);
+
+    // Once overdue, commits must allow the undelegation
+    context
+        .warp_to_slot(undelegation_request.deadline_slot + 1)
+        .unwrap();
+    let res = process(&mut context, &[commit_ix(&validator, false)], &validator).await;
+    assert_dlp_error(res, DlpError::UndelegationRequired);
+
+    // Without a commit of the validator, the account is made undelegatable
+    let res = process(&mut context, &[ix], &rent_payer).await;
+    assert!(res.is_ok());
+    assert!(delegation_metadata(&mut context).await.is_undelegatable);
+}
+
+#[tokio::test]
+async fn test_request_undelegation_unauthorized() {
+    // Setup
+    let (mut context, validator, _) = setup_program_test_env().await;
+
+    // The validator is not the rent payer of the delegated account
+    let ix = dlp::instruction_builder::request_undelegation(validator.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix], &validator).await;
+    assert_dlp_error(res, DlpError::Unauthorized);
+    assert!(delegation_metadata(&mut context)
+        .await
+        .undelegation_request
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_request_expired_undelegation() {
+    // Setup, the delegations of the program lasting 100 slots
+    let (mut context, validator, _) = setup_program_test_env_with_max_delegation_slots(100).await;
+    let requester = Keypair::new();
+    fund(&mut context, &validator, &requester).await;
+    let ix = dlp::instruction_builder::request_expired_undelegation(
+        requester.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+    );
+
+    // Before the delegation expires, only the rent payer can request the undelegation
+    let res = process(&mut context, &[ix.clone()], &requester).await;
+    assert_dlp_error(res, DlpError::Unauthorized);
+
+    // Once expired, commits must allow the undelegation
+    context.warp_to_slot(100).unwrap();
+    let res = process(&mut context, &[commit_ix(&validator, false)], &validator).await;
+    assert_dlp_error(res, DlpError::DelegationExpired);
+
+    // Anyone can force the settlement of the delegation
+    let res = process(&mut context, &[ix], &requester).await;
+    assert!(res.is_ok());
+    assert!(delegation_metadata(&mut context).await.is_undelegatable);
+}
+
+#[tokio::test]
+async fn test_commit_undelegating_expired_delegation() {
+    // Setup, the delegations of the program lasting 100 slots
+    let (mut context, validator, _) = setup_program_test_env_with_max_delegation_slots(100).await;
+
+    // Commits undelegating the account are still accepted
+    context.warp_to_slot(100).unwrap();
+    let res = process(&mut context, &[commit_ix(&validator, true)], &validator).await;
+    assert!(res.is_ok());
+}
+
+async fn fund(context: &mut ProgramTestContext, payer: &Keypair, account: &Keypair) {
+    let ix = solana_sdk::system_instruction::transfer(
+        &payer.pubkey(),
+        &account.pubkey(),
+        LAMPORTS_PER_SOL,
+    );
+    process(context, &[ix], payer).await.unwrap();
+}
+
+fn commit_ix(validator: &Keypair, allow_undelegation: bool) -> Instruction {
+    dlp::instruction_builder::commit_state(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        CommitStateArgs {
+            data: vec![0, 1, 2, 9, 9, 9, 6, 7, 8, 9],
+            nonce: 1,
+            allow_undelegation,
+            lamports: LAMPORTS_PER_SOL,
+            er_block_hash: None,
+            base_state_hash: None,
+            escrow_spend: None,
+            encoding: Encoding::Raw,
+        },
+    )
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn delegation_metadata(context: &mut ProgramTestContext) -> DelegationMetadata {
+    let delegation_metadata_account = context
+        .banks_client
+        .get_account(delegation_metadata_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+        .unwrap()
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair, Keypair) {
+    setup_program_test_env_with_max_delegation_slots(0).await
+}
+
+async fn setup_program_test_env_with_max_delegation_slots(
+    max_delegation_slots: u64,
+) -> (ProgramTestContext, Keypair, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let rent_payer = Keypair::new();
+
+    for account in [validator.pubkey(), rent_payer.pubkey()] {
+        program_test.add_account(
+            account,
+            Account {
+                lamports: 10 * LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account metadata PDA, paid by the rent payer
+    let delegation_metadata_data = get_delegation_metadata_data(rent_payer.pubkey(), None);
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the program config of the owner program, limiting the delegations
+    if max_delegation_slots > 0 {
+        let mut program_config = ProgramConfig {
+            max_delegation_slots,
+            ..Default::default()
+        };
+        program_config
+            .approved_validators
+            .insert(validator.pubkey());
+        let mut program_config_data = vec![];
+        program_config
+            .to_bytes_with_discriminator(&mut program_config_data)
+            .unwrap();
+        program_test.add_account(
+            program_config_from_program_id(&DELEGATED_PDA_OWNER_ID),
+            Account {
+                lamports: Rent::default().minimum_balance(program_config_data.len()),
+                data: program_config_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let context = program_test.start_with_context().await;
+    (context, validator, rent_payer)
+}
@@ -0,0 +1,131 @@
+use crate::fixtures::TEST_AUTHORITY;
+use dlp::args::SetValidatorInfoArgs;
+use dlp::pda::{validator_fees_vault_pda_from_validator, validator_info_pda_from_validator};
+use dlp::state::ValidatorInfo;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_set_validator_info() {
+    // Setup
+    let (banks, validator, blockhash) = setup_program_test_env().await;
+
+    let args = SetValidatorInfoArgs {
+        endpoint: "https://devnet.magicblock.app".to_string(),
+        version: "0.1.0".to_string(),
+        cluster_id: "devnet".to_string(),
+    };
+    let ix = dlp::instruction_builder::set_validator_info(validator.pubkey(), args);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Check that the validator info is set
+    let validator_info_account = banks
+        .get_account(validator_info_pda_from_validator(&validator.pubkey()))
+        .await
+        .unwrap()
+        .unwrap();
+    let validator_info =
+        ValidatorInfo::try_from_bytes_with_discriminator(&validator_info_account.data).unwrap();
+    assert_eq!(validator_info.endpoint, "https://devnet.magicblock.app");
+    assert_eq!(validator_info.version, "0.1.0");
+    assert_eq!(validator_info.cluster_id, "devnet");
+
+    // Update the validator info with a shorter endpoint
+    let args = SetValidatorInfoArgs {
+        endpoint: "https://er.local".to_string(),
+        version: "0.2.0".to_string(),
+        cluster_id: "devnet".to_string(),
+    };
+    let ix = dlp::instruction_builder::set_validator_info(validator.pubkey(), args);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    let validator_info_account = banks
+        .get_account(validator_info_pda_from_validator(&validator.pubkey()))
+        .await
+        .unwrap()
+        .unwrap();
+    let validator_info =
+        ValidatorInfo::try_from_bytes_with_discriminator(&validator_info_account.data).unwrap();
+    assert_eq!(validator_info.endpoint, "https://er.local");
+    assert_eq!(validator_info.version, "0.2.0");
+    assert_eq!(
+        validator_info_account.data.len(),
+        validator_info.size_with_discriminator()
+    );
+}
+
+#[tokio::test]
+async fn test_set_validator_info_empty_endpoint() {
+    // Setup
+    let (banks, validator, blockhash) = setup_program_test_env().await;
+
+    let args = SetValidatorInfoArgs {
+        endpoint: String::new(),
+        version: "0.1.0".to_string(),
+        cluster_id: "devnet".to_string(),
+    };
+    let ix = dlp::instruction_builder::set_validator_info(validator.pubkey(), args);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_err());
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, validator, blockhash)
+}
@@ -0,0 +1,135 @@
+use borsh::BorshDeserialize;
+use dlp::args::SetVersionArgs;
+use dlp::discriminator::DlpDiscriminator;
+use dlp::error::DlpError;
+use dlp::pda::program_version_pda;
+use dlp::state::ProgramVersion;
+use solana_program::instruction::InstructionError;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::TEST_AUTHORITY;
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_set_and_get_version() {
+    // Setup
+    let (banks, admin, blockhash) = setup_program_test_env().await;
+
+    let ix = dlp::instruction_builder::set_version(
+        admin.pubkey(),
+        version_args(DlpDiscriminator::GetVersion as u8),
+    );
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the program version was created
+    let program_version_account = banks
+        .get_account(program_version_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    let program_version =
+        ProgramVersion::try_from_bytes_with_discriminator(&program_version_account.data).unwrap();
+    assert!(program_version.is_at_least(1, 2, 3));
+    assert!(program_version.supports(DlpDiscriminator::SetVersion));
+    assert_eq!(program_version.git_hash, [7; 20]);
+
+    // Assert the getter returns the program version
+    let ix = dlp::instruction_builder::get_version();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let res = banks.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(res.result.is_ok());
+    let return_data = res.metadata.unwrap().return_data.unwrap();
+    assert_eq!(
+        ProgramVersion::try_from_slice(&return_data.data).unwrap(),
+        program_version
+    );
+}
+
+#[tokio::test]
+async fn test_set_version_invalid_discriminator_range() {
+    // Setup
+    let (banks, admin, blockhash) = setup_program_test_env().await;
+
+    // The discriminator 4 is unused
+    let ix = dlp::instruction_builder::set_version(admin.pubkey(), version_args(4));
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&admin.pubkey()), &[&admin], blockhash);
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InvalidDiscriminatorRange as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_set_version_unauthorized() {
+    // Setup
+    let (banks, payer, blockhash) = setup_program_test_env().await;
+    let other = Keypair::new();
+
+    let ix = dlp::instruction_builder::set_version(
+        other.pubkey(),
+        version_args(DlpDiscriminator::GetVersion as u8),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &other],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::Unauthorized as u32)
+        )
+    );
+    assert!(banks
+        .get_account(program_version_pda())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+fn version_args(max_discriminator: u8) -> SetVersionArgs {
+    SetVersionArgs {
+        major: 1,
+        minor: 2,
+        patch: 3,
+        git_hash: [7; 20],
+        min_discriminator: 0,
+        max_discriminator,
+    }
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let admin = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        admin.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, admin, blockhash)
+}
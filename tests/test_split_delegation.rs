@@ -0,0 +1,125 @@
+use dlp::args::{Seeds, SplitDelegationArgs};
+use dlp::error::DlpError;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+};
+use solana_program::instruction::InstructionError;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, ON_CURVE_KEYPAIR, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+/// The split is approved by the owner program signing for a PDA of its own, see the split of
+/// the label in tests/integration/tests/test-native.ts. An on curve account signs without it.
+#[tokio::test]
+async fn test_split_delegation_on_curve_without_approval() {
+    // Setup
+    let (banks, validator, blockhash) = setup_program_test_env().await;
+    let new_delegated_account = Keypair::from_bytes(&ON_CURVE_KEYPAIR).unwrap();
+
+    // Submit the split delegation tx, signed by the on curve account only
+    let ix = dlp::instruction_builder::split_delegation(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        new_delegated_account.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        SplitDelegationArgs {
+            ranges: vec![(0, 4), (10, 12)],
+            seeds: Seeds::default(),
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator, &new_delegated_account],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::SplitNotApprovedByOwner as u32)
+        )
+    );
+
+    // Assert the delegated account is untouched and the new delegation was not created
+    let delegated_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert_eq!(delegated_account.data, DELEGATED_PDA.to_vec());
+    assert!(banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &new_delegated_account.pubkey(),
+        ))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: DELEGATED_PDA.into(),
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated metadata PDA
+    let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), None);
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, validator, blockhash)
+}
@@ -1,13 +1,14 @@
 use crate::fixtures::{
     create_delegation_metadata_data, create_delegation_record_data, TEST_AUTHORITY,
 };
-use dlp::args::DelegateEphemeralBalanceArgs;
+use dlp::args::{DelegateArgs, DelegateEphemeralBalanceArgs, EphemeralBalanceIndex};
 use dlp::ephemeral_balance_seeds_from_payer;
 use dlp::pda::{
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
-    ephemeral_balance_pda_from_payer, fees_vault_pda, validator_fees_vault_pda_from_validator,
+    ephemeral_balance_pda_from_payer, fees_vault_pda, protocol_config_pda,
+    validator_fees_vault_pda_from_validator, wide_ephemeral_balance_pda_from_payer,
 };
-use dlp::state::DelegationRecord;
+use dlp::state::{DelegationRecord, ProtocolConfig};
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{BanksClient, ProgramTest};
@@ -70,6 +71,57 @@ async fn test_top_up_ephemeral_balance_for_pubkey() {
     assert!(balance_account.lamports > 0);
 }
 
+#[tokio::test]
+async fn test_top_up_ephemeral_balance_batch() {
+    // Setup
+    let (banks, payer, _, blockhash) = setup_program_test_env().await;
+
+    let pubkey = Keypair::new().pubkey();
+    let escrows = [
+        (payer.pubkey(), EphemeralBalanceIndex::Narrow(0), 1_000),
+        (payer.pubkey(), EphemeralBalanceIndex::Wide(70_000), 2_000),
+        (pubkey, EphemeralBalanceIndex::Wide(0), 3_000),
+    ];
+    let ix = dlp::instruction_builder::top_up_ephemeral_balance_batch(payer.pubkey(), &escrows);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Narrow indices keep deriving the original escrows, wide ones are distinct from them
+    assert_eq!(
+        EphemeralBalanceIndex::Narrow(0).pda(&payer.pubkey()),
+        ephemeral_balance_pda_from_payer(&payer.pubkey(), 0)
+    );
+    assert_ne!(
+        EphemeralBalanceIndex::Wide(0).pda(&pubkey),
+        ephemeral_balance_pda_from_payer(&pubkey, 0)
+    );
+
+    // Check every escrow exists, is owned by the system program and was funded
+    let rent = Rent::default().minimum_balance(0);
+    for (pubkey, index, amount) in escrows {
+        let balance_account = banks
+            .get_account(index.pda(&pubkey))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(balance_account.owner, system_program::id());
+        assert_eq!(balance_account.lamports, rent + amount);
+    }
+
+    // Close the wide escrow of the payer
+    let wide_ephemeral_balance_pda = wide_ephemeral_balance_pda_from_payer(&payer.pubkey(), 70_000);
+    let ix = dlp::instruction_builder::close_wide_ephemeral_balance(payer.pubkey(), 70_000);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+    assert!(banks
+        .get_account(wide_ephemeral_balance_pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
 #[tokio::test]
 async fn test_top_up_ephemeral_balance_and_delegate() {
     // Setup
@@ -161,6 +213,91 @@ async fn test_top_up_ephemeral_balance_and_delegate_for_pubkey() {
     assert!(balance_account.lamports > 0);
 }
 
+#[tokio::test]
+async fn test_redelegate_ephemeral_balance() {
+    // Setup
+    let (banks, payer, _, blockhash) = setup_program_test_env().await;
+    let new_validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    let key = Keypair::new();
+    let pubkey = key.pubkey();
+
+    // Top-up and delegate the ephemeral balance to the payer, as the current validator
+    let ix = dlp::instruction_builder::top_up_ephemeral_balance(payer.pubkey(), pubkey, None, None);
+    let delegate_ix = dlp::instruction_builder::delegate_ephemeral_balance(
+        payer.pubkey(),
+        pubkey,
+        DelegateEphemeralBalanceArgs {
+            delegate_args: DelegateArgs {
+                validator: Some(payer.pubkey()),
+                ..Default::default()
+            },
+            index: 0,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix, delegate_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &key],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Redelegate the ephemeral balance to the new validator
+    let ix = dlp::instruction_builder::redelegate_ephemeral_balance(
+        payer.pubkey(),
+        pubkey,
+        0,
+        new_validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &key],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Check the ephemeral balance is still delegated, now to the new validator
+    let ephemeral_balance_pda = ephemeral_balance_pda_from_payer(&pubkey, 0);
+    let balance_account = banks
+        .get_account(ephemeral_balance_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(balance_account.owner, dlp::id());
+    let delegation_record_account = banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &ephemeral_balance_pda,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.authority, new_validator.pubkey());
+
+    // The previous validator is no longer the authority of the delegation
+    let ix = dlp::instruction_builder::redelegate_ephemeral_balance(
+        payer.pubkey(),
+        pubkey,
+        0,
+        new_validator.pubkey(),
+    );
+    let blockhash = banks.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &[&payer, &key],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_err());
+}
+
 #[tokio::test]
 async fn test_undelegate() {
     // Setup
@@ -260,7 +397,69 @@ async fn test_undelegate_and_close() {
     );
 }
 
+#[tokio::test]
+async fn test_undelegate_dust_escrow() {
+    // Setup
+    let (banks, _, payer_alt, blockhash) =
+        setup_program_test_env_with_protocol_config(Some(ProtocolConfig {
+            escrow_dust_threshold: 2 * LAMPORTS_PER_SOL,
+            ..Default::default()
+        }))
+        .await;
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    let ephemeral_balance_pda = ephemeral_balance_pda_from_payer(&payer_alt.pubkey(), 0);
+    let ephemeral_balance_lamports = banks
+        .get_account(ephemeral_balance_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    let prev_validator_lamports = banks
+        .get_account(validator.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+
+    // Undelegate ephemeral balance Ix, the validator is the rent payer of the delegation
+    let ix = dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        ephemeral_balance_pda,
+        system_program::id(),
+        validator.pubkey(),
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert that the ephemeral balance account below the dust threshold is closed
+    let ephemeral_balance_account = banks.get_account(ephemeral_balance_pda).await.unwrap();
+    assert!(ephemeral_balance_account.is_none());
+
+    // Assert that the rent payer was refunded the escrowed lamports, net of the tx fee
+    let validator_lamports = banks
+        .get_account(validator.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    assert!(validator_lamports > prev_validator_lamports + ephemeral_balance_lamports - 10_000);
+}
+
 async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    setup_program_test_env_with_protocol_config(None).await
+}
+
+async fn setup_program_test_env_with_protocol_config(
+    protocol_config: Option<ProtocolConfig>,
+) -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
 
@@ -316,6 +515,24 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
         },
     );
 
+    // Setup the protocol config
+    if let Some(protocol_config) = protocol_config {
+        let mut protocol_config_data = vec![0; ProtocolConfig::size_with_discriminator()];
+        protocol_config
+            .to_bytes_with_discriminator(&mut protocol_config_data)
+            .unwrap();
+        program_test.add_account(
+            protocol_config_pda(),
+            Account {
+                lamports: Rent::default().minimum_balance(protocol_config_data.len()),
+                data: protocol_config_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
     let (banks, payer, blockhash) = program_test.start().await;
     (banks, payer, payer_alt, blockhash)
 }
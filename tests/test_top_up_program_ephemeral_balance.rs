@@ -0,0 +1,233 @@
+use crate::fixtures::{create_program_config_data, DELEGATED_PDA_OWNER_ID};
+use dlp::args::{DelegateArgs, DelegateProgramEphemeralBalanceArgs};
+use dlp::pda::{
+    delegation_record_pda_from_delegated_account, ephemeral_balance_pda_from_payer,
+    program_config_from_program_id, program_ephemeral_balance_pda_from_payer,
+};
+use dlp::state::DelegationRecord;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_top_up_program_ephemeral_balance() {
+    // Setup
+    let (banks, payer, _, blockhash) = setup_program_test_env().await;
+    let program_id = Pubkey::new_unique();
+
+    let ix = dlp::instruction_builder::top_up_program_ephemeral_balance(
+        payer.pubkey(),
+        payer.pubkey(),
+        program_id,
+        None,
+        None,
+    );
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Check account exists, it's owned by the system program and is distinct from the
+    // global ephemeral balance of the payer
+    let ephemeral_balance_pda =
+        program_ephemeral_balance_pda_from_payer(&payer.pubkey(), &program_id, 0);
+    assert_ne!(
+        ephemeral_balance_pda,
+        ephemeral_balance_pda_from_payer(&payer.pubkey(), 0)
+    );
+    let balance_account = banks
+        .get_account(ephemeral_balance_pda)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(balance_account.owner, system_program::id());
+    assert!(balance_account.lamports > 0);
+}
+
+#[tokio::test]
+async fn test_top_up_program_ephemeral_balance_and_delegate() {
+    // Setup
+    let (banks, payer, _, blockhash) = setup_program_test_env().await;
+    let program_id = Pubkey::new_unique();
+
+    // Top-up Ix
+    let ix = dlp::instruction_builder::top_up_program_ephemeral_balance(
+        payer.pubkey(),
+        payer.pubkey(),
+        program_id,
+        None,
+        None,
+    );
+    // Delegate program ephemeral balance Ix
+    let delegate_ix = dlp::instruction_builder::delegate_program_ephemeral_balance(
+        payer.pubkey(),
+        payer.pubkey(),
+        DelegateProgramEphemeralBalanceArgs {
+            program_id,
+            ..Default::default()
+        },
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix, delegate_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Check account exists and it's owned by the delegation program (delegated)
+    let ephemeral_balance_pda =
+        program_ephemeral_balance_pda_from_payer(&payer.pubkey(), &program_id, 0);
+    let balance_account = banks
+        .get_account(ephemeral_balance_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(balance_account.owner, dlp::id());
+
+    // Check the delegation record PDA has system program as owner
+    let delegation_record_account = banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &ephemeral_balance_pda,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.owner, system_program::id());
+}
+
+#[tokio::test]
+async fn test_delegate_program_ephemeral_balance_to_non_whitelisted_validator() {
+    // Setup
+    let (banks, payer, approved_validator, blockhash) = setup_program_test_env().await;
+
+    // Top-up Ix
+    let ix = dlp::instruction_builder::top_up_program_ephemeral_balance(
+        payer.pubkey(),
+        payer.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        None,
+        None,
+    );
+    // Delegate to a validator which is not whitelisted for the program
+    let delegate_ix = dlp::instruction_builder::delegate_program_ephemeral_balance(
+        payer.pubkey(),
+        payer.pubkey(),
+        DelegateProgramEphemeralBalanceArgs {
+            delegate_args: DelegateArgs {
+                validator: Some(Pubkey::new_unique()),
+                ..Default::default()
+            },
+            program_id: DELEGATED_PDA_OWNER_ID,
+            index: 0,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix.clone(), delegate_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_err());
+
+    // Delegating to the whitelisted validator succeeds
+    let delegate_ix = dlp::instruction_builder::delegate_program_ephemeral_balance(
+        payer.pubkey(),
+        payer.pubkey(),
+        DelegateProgramEphemeralBalanceArgs {
+            delegate_args: DelegateArgs {
+                validator: Some(approved_validator),
+                ..Default::default()
+            },
+            program_id: DELEGATED_PDA_OWNER_ID,
+            index: 0,
+        },
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix, delegate_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+}
+
+#[tokio::test]
+async fn test_top_up_and_close_program_ephemeral_balance() {
+    // Setup
+    let (banks, payer, _, blockhash) = setup_program_test_env().await;
+    let program_id = Pubkey::new_unique();
+
+    let ix = dlp::instruction_builder::top_up_program_ephemeral_balance(
+        payer.pubkey(),
+        payer.pubkey(),
+        program_id,
+        None,
+        None,
+    );
+    let ix_close =
+        dlp::instruction_builder::close_program_ephemeral_balance(payer.pubkey(), program_id, 0);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix, ix_close],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert that the program ephemeral balance account is closed
+    let ephemeral_balance_pda =
+        program_ephemeral_balance_pda_from_payer(&payer.pubkey(), &program_id, 0);
+    let ephemeral_balance_account = banks.get_account(ephemeral_balance_pda).await.unwrap();
+    assert!(ephemeral_balance_account.is_none());
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Pubkey, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+
+    // Setup a program config whitelisting a single validator
+    let approved_validator = Pubkey::new_unique();
+    let program_config_data = create_program_config_data(approved_validator);
+    program_test.add_account(
+        program_config_from_program_id(&DELEGATED_PDA_OWNER_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(program_config_data.len()),
+            data: program_config_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let payer = Keypair::new();
+    program_test.add_account(
+        payer.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, payer, approved_validator, blockhash)
+}
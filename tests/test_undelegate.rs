@@ -3,6 +3,7 @@ use dlp::pda::{
     delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
     fees_vault_pda, validator_fees_vault_pda_from_validator,
 };
+use dlp::state::{EarningsKind, EarningsLedgerCursor};
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{read_file, BanksClient, ProgramTest};
@@ -75,6 +76,66 @@ async fn test_finalize_and_undelegate() {
     assert_eq!(new_state_data_before_finalize, pda_account.data);
 }
 
+#[tokio::test]
+async fn test_finalize_and_undelegate_with_earnings_ledger() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&authority.pubkey());
+    let vault_lamports_before = banks.get_balance(validator_fees_vault_pda).await.unwrap();
+
+    // Create the first page of the earnings ledger and pass it to the fee-paying instructions
+    let ix_init = dlp::instruction_builder::init_earnings_ledger_page(authority.pubkey(), 0);
+    let ix_finalize = dlp::instruction_builder::with_earnings_ledger_page(
+        dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID),
+        authority.pubkey(),
+        0,
+    );
+    let ix_undelegate = dlp::instruction_builder::with_earnings_ledger_page(
+        dlp::instruction_builder::undelegate(
+            authority.pubkey(),
+            DELEGATED_PDA_ID,
+            DELEGATED_PDA_OWNER_ID,
+            authority.pubkey(),
+        ),
+        authority.pubkey(),
+        0,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_init, ix_finalize, ix_undelegate],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // The ledger records everything the validator fees vault earned
+    let cursor = EarningsLedgerCursor::default();
+    let page = banks
+        .get_account(cursor.page_pda(&authority.pubkey()))
+        .await
+        .unwrap()
+        .unwrap();
+    let read = cursor.read(&page.data, 10).unwrap();
+    let rent_fees = read.entries.last().unwrap();
+    assert_eq!(rent_fees.kind(), Some(EarningsKind::RentFees));
+    assert_eq!(rent_fees.delegated_account, DELEGATED_PDA_ID);
+    assert!(rent_fees.amount > 0);
+
+    let vault_lamports_after = banks.get_balance(validator_fees_vault_pda).await.unwrap();
+    assert_eq!(
+        read.entries.iter().map(|entry| entry.amount).sum::<u64>(),
+        vault_lamports_after - vault_lamports_before
+    );
+    assert_eq!(
+        read.next,
+        EarningsLedgerCursor {
+            page: 0,
+            index: read.entries.len() as u32
+        }
+    );
+}
+
 async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
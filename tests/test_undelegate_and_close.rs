@@ -0,0 +1,211 @@
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fees_vault_pda, validator_fees_vault_pda_from_validator,
+};
+use dlp::state::DelegationMetadata;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data_on_curve, get_delegation_record_on_curve_data, ON_CURVE_KEYPAIR,
+    TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_undelegate_and_close() {
+    // Setup
+    let (banks, validator, delegated_on_curve, blockhash) = setup_program_test_env().await;
+    let close_destination = Keypair::new();
+
+    // Approve the close, signed by the delegated account
+    let ix = dlp::instruction_builder::approve_undelegate_and_close(
+        validator.pubkey(),
+        delegated_on_curve.pubkey(),
+        close_destination.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator, &delegated_on_curve],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Assert the close destination is set in the delegation metadata
+    let delegation_metadata_pda =
+        delegation_metadata_pda_from_delegated_account(&delegated_on_curve.pubkey());
+    let delegation_metadata_account = banks
+        .get_account(delegation_metadata_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_metadata =
+        DelegationMetadata::try_from_bytes_with_discriminator(&delegation_metadata_account.data)
+            .unwrap();
+    assert_eq!(
+        delegation_metadata.close_destination,
+        Some(close_destination.pubkey())
+    );
+
+    // Submit the undelegate and close tx
+    let ix = dlp::instruction_builder::undelegate_and_close(
+        validator.pubkey(),
+        delegated_on_curve.pubkey(),
+        close_destination.pubkey(),
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Assert the delegation record and metadata were closed
+    let delegation_record_pda =
+        delegation_record_pda_from_delegated_account(&delegated_on_curve.pubkey());
+    assert!(banks
+        .get_account(delegation_record_pda)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks
+        .get_account(delegation_metadata_pda)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Assert the delegated account was closed and its lamports sent to the destination
+    assert!(banks
+        .get_account(delegated_on_curve.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+    let close_destination_account = banks
+        .get_account(close_destination.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(close_destination_account.lamports, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_undelegate_and_close_without_approval() {
+    // Setup
+    let (banks, validator, delegated_on_curve, blockhash) = setup_program_test_env().await;
+    let close_destination = Keypair::new();
+
+    let ix = dlp::instruction_builder::undelegate_and_close(
+        validator.pubkey(),
+        delegated_on_curve.pubkey(),
+        close_destination.pubkey(),
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_err());
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let delegated_on_curve = Keypair::from_bytes(&ON_CURVE_KEYPAIR).unwrap();
+
+    // Setup a delegated on curve account with some data
+    program_test.add_account(
+        delegated_on_curve.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![1; 100],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA
+    let delegation_record_data =
+        get_delegation_record_on_curve_data(delegated_on_curve.pubkey(), Some(LAMPORTS_PER_SOL));
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&delegated_on_curve.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account metadata PDA
+    let delegation_metadata_data =
+        get_delegation_metadata_data_on_curve(validator.pubkey(), Some(true));
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&delegated_on_curve.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator keypair
+    program_test.add_account(
+        validator.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the protocol fees vault
+    program_test.add_account(
+        fees_vault_pda(),
+        Account {
+            lamports: Rent::default().minimum_balance(0),
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, validator, delegated_on_curve, blockhash)
+}
@@ -0,0 +1,385 @@
+use dlp::consts::UNDELEGATE_STAGE2_TIMEOUT_SLOTS;
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, commit_state_pda_from_delegated_account,
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fees_vault_pda, undelegate_progress_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::UndelegateProgress;
+use solana_program::instruction::InstructionError;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{read_file, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_commit_record_account_data, get_delegation_metadata_data, get_delegation_record_data,
+    COMMIT_NEW_STATE_ACCOUNT_DATA, DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_finalize_and_undelegate_staged() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let delegation_record_pda = delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let delegation_metadata_pda = delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID);
+    let undelegate_progress_pda = undelegate_progress_pda_from_delegated_account(&DELEGATED_PDA_ID);
+
+    // Finalize and give the account back to its owner program
+    let ix_finalize = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let ix_stage1 = dlp::instruction_builder::undelegate_stage1(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_finalize, ix_stage1],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Assert the account is back to its owner program with the committed state
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert!(pda_account.owner.eq(&DELEGATED_PDA_OWNER_ID));
+    assert_eq!(pda_account.data, COMMIT_NEW_STATE_ACCOUNT_DATA);
+
+    // Assert the progress links the stages and the delegation PDAs are still open
+    let undelegate_progress_account = banks
+        .get_account(undelegate_progress_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let undelegate_progress =
+        UndelegateProgress::try_from_bytes_with_discriminator(&undelegate_progress_account.data)
+            .unwrap();
+    assert_eq!(undelegate_progress.validator, authority.pubkey());
+    assert_eq!(undelegate_progress.owner, DELEGATED_PDA_OWNER_ID);
+    assert!(banks
+        .get_account(delegation_record_pda)
+        .await
+        .unwrap()
+        .is_some());
+
+    // The account cannot be undelegated again in between
+    let ix_undelegate = dlp::instruction_builder::undelegate(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_undelegate],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountOwner)
+    );
+
+    // Close the delegation PDAs
+    let ix_stage2 = dlp::instruction_builder::undelegate_stage2(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_stage2],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Assert the delegation PDAs and the progress were closed
+    for pda in [
+        delegation_record_pda,
+        delegation_metadata_pda,
+        undelegate_progress_pda,
+    ] {
+        assert!(banks.get_account(pda).await.unwrap().is_none());
+    }
+
+    // Assert the account kept the committed state
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert!(pda_account.owner.eq(&DELEGATED_PDA_OWNER_ID));
+    assert_eq!(pda_account.data, COMMIT_NEW_STATE_ACCOUNT_DATA);
+}
+
+#[tokio::test]
+async fn test_undelegate_stage2_without_stage1() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    // The cleanup cannot run before the account is given back to its owner program
+    let ix_finalize = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let ix_stage2 = dlp::instruction_builder::undelegate_stage2(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_finalize, ix_stage2],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(1, InstructionError::InvalidAccountOwner)
+    );
+
+    // Assert the account is still delegated
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert!(pda_account.owner.eq(&dlp::id()));
+}
+
+#[tokio::test]
+async fn test_undelegate_stage2_after_timeout() {
+    // Setup
+    let (mut context, authority) = setup_program_test_context().await;
+    let payer = context.payer.insecure_clone();
+    let validator_fees_vault_pda = validator_fees_vault_pda_from_validator(&authority.pubkey());
+    let undelegate_progress_pda = undelegate_progress_pda_from_delegated_account(&DELEGATED_PDA_ID);
+
+    // Finalize and give the account back to its owner program
+    let ix_finalize = dlp::instruction_builder::finalize(authority.pubkey(), DELEGATED_PDA_ID);
+    let ix_stage1 = dlp::instruction_builder::undelegate_stage1(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_finalize, ix_stage1],
+        Some(&authority.pubkey()),
+        &[&authority],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_ok());
+    let undelegate_progress_account = context
+        .banks_client
+        .get_account(undelegate_progress_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let undelegate_progress =
+        UndelegateProgress::try_from_bytes_with_discriminator(&undelegate_progress_account.data)
+            .unwrap();
+
+    // Another payer cannot run the second stage before the timeout
+    let ix_stage2 = dlp::instruction_builder::undelegate_stage2_after_timeout(
+        payer.pubkey(),
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        authority.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_stage2.clone()],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    let err = context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::InvalidUndelegateProgress as u32)
+        )
+    );
+
+    // After the timeout, it runs it, the validator fees vault collecting the rent of the
+    // progress
+    context
+        .warp_to_slot(undelegate_progress.slot + UNDELEGATE_STAGE2_TIMEOUT_SLOTS)
+        .unwrap();
+    let validator_fees_vault_lamports = context
+        .banks_client
+        .get_account(validator_fees_vault_pda)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix_stage2],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_ok());
+
+    for pda in [
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        undelegate_progress_pda,
+    ] {
+        assert!(context
+            .banks_client
+            .get_account(pda)
+            .await
+            .unwrap()
+            .is_none());
+    }
+    let validator_fees_vault = context
+        .banks_client
+        .get_account(validator_fees_vault_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        validator_fees_vault.lamports
+            >= validator_fees_vault_lamports + undelegate_progress_account.lamports
+    );
+}
+
+async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+    let (program_test, authority) = setup_program_test();
+    let (banks, payer, blockhash) = program_test.start().await;
+    (banks, payer, authority, blockhash)
+}
+
+async fn setup_program_test_context() -> (ProgramTestContext, Keypair) {
+    let (program_test, authority) = setup_program_test();
+    (program_test.start_with_context().await, authority)
+}
+
+fn setup_program_test() -> (ProgramTest, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let authority = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        authority.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated record PDA
+    let delegation_record_data = get_delegation_record_data(authority.pubkey(), None);
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated metadata PDA
+    let delegation_metadata_data = get_delegation_metadata_data(authority.pubkey(), Some(true));
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the committed state PDA
+    program_test.add_account(
+        commit_state_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: COMMIT_NEW_STATE_ACCOUNT_DATA.into(),
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the commit state record PDA
+    let commit_record_data = get_commit_record_account_data(authority.pubkey());
+    program_test.add_account(
+        commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(commit_record_data.len()),
+            data: commit_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup program to test undelegation
+    let data = read_file("tests/buffers/test_delegation.so");
+    program_test.add_account(
+        DELEGATED_PDA_OWNER_ID,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: solana_sdk::bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the protocol fees vault
+    program_test.add_account(
+        fees_vault_pda(),
+        Account {
+            lamports: Rent::default().minimum_balance(0),
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the validator fees vault
+    program_test.add_account(
+        validator_fees_vault_pda_from_validator(&authority.pubkey()),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    (program_test, authority)
+}
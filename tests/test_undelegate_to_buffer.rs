@@ -0,0 +1,218 @@
+use dlp::error::DlpError;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fees_vault_pda, parked_undelegation_pda_from_delegated_account,
+    validator_fees_vault_pda_from_validator,
+};
+use dlp::state::ParkedUndelegation;
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
+    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+const DELEGATED_DATA: [u8; 12] = [7; 12];
+
+#[tokio::test]
+async fn test_undelegate_to_buffer_and_claim() {
+    // Setup
+    let (mut context, validator, rent_payer) = setup_program_test_env().await;
+    let parked_undelegation_pda = parked_undelegation_pda_from_delegated_account(&DELEGATED_PDA_ID);
+
+    // The owner program is not deployed, it is not invoked when parking the state
+    let ix = dlp::instruction_builder::undelegate_to_buffer(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        rent_payer.pubkey(),
+    );
+    process(&mut context, &[ix], &validator, &[&rent_payer])
+        .await
+        .unwrap();
+
+    // The delegated account and its delegation PDAs are closed
+    for closed in [
+        DELEGATED_PDA_ID,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+    ] {
+        assert!(context
+            .banks_client
+            .get_account(closed)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    // The parked undelegation holds the data and the lamports of the delegated account
+    let parked_account = context
+        .banks_client
+        .get_account(parked_undelegation_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let header_size = ParkedUndelegation::size_with_discriminator();
+    let parked =
+        ParkedUndelegation::try_from_bytes_with_discriminator(&parked_account.data[..header_size])
+            .unwrap();
+    assert_eq!(parked.rent_payer, rent_payer.pubkey());
+    assert_eq!(parked.owner, DELEGATED_PDA_OWNER_ID);
+    assert_eq!(
+        ParkedUndelegation::parked_data(&parked_account.data),
+        Some(DELEGATED_DATA.as_slice())
+    );
+    assert_eq!(
+        parked_account.lamports,
+        LAMPORTS_PER_SOL + Rent::default().minimum_balance(parked_account.data.len())
+    );
+
+    // Only the rent payer can claim it
+    let ix =
+        dlp::instruction_builder::claim_parked_undelegation(validator.pubkey(), DELEGATED_PDA_ID);
+    let res = process(&mut context, &[ix], &validator, &[]).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::Unauthorized as u32)
+        )
+    );
+
+    let balance_before = context
+        .banks_client
+        .get_balance(rent_payer.pubkey())
+        .await
+        .unwrap();
+    let ix =
+        dlp::instruction_builder::claim_parked_undelegation(rent_payer.pubkey(), DELEGATED_PDA_ID);
+    process(&mut context, &[ix], &validator, &[&rent_payer])
+        .await
+        .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(parked_undelegation_pda)
+        .await
+        .unwrap()
+        .is_none());
+    let balance_after = context
+        .banks_client
+        .get_balance(rent_payer.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(balance_after, balance_before + parked_account.lamports);
+}
+
+#[tokio::test]
+async fn test_undelegate_to_buffer_requires_rent_payer_signature() {
+    // Setup
+    let (mut context, validator, rent_payer) = setup_program_test_env().await;
+
+    let mut ix = dlp::instruction_builder::undelegate_to_buffer(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        rent_payer.pubkey(),
+    );
+    ix.accounts[8].is_signer = false;
+    let res = process(&mut context, &[ix], &validator, &[]).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let signers = [&[payer], signers].concat();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let rent_payer = Keypair::new();
+
+    for signer in [&validator, &rent_payer] {
+        program_test.add_account(
+            signer.pubkey(),
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Setup an undelegatable delegated PDA, without pending commit, and its delegation PDAs
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        DELEGATED_DATA.into(),
+    );
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+    );
+    let delegation_metadata_data = get_delegation_metadata_data(rent_payer.pubkey(), Some(true));
+    add_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+    );
+
+    // Setup the protocol and validator fees vaults
+    add_dlp_account(
+        &mut program_test,
+        fees_vault_pda(),
+        Rent::default().minimum_balance(0),
+        vec![],
+    );
+    add_dlp_account(
+        &mut program_test,
+        validator_fees_vault_pda_from_validator(&validator.pubkey()),
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, validator, rent_payer)
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
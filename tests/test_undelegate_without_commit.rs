@@ -1,20 +1,24 @@
+use dlp::args::{SeedPlaceholder, SeedTemplate};
 use dlp::pda::{
     commit_state_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
-    delegation_record_pda_from_delegated_account, fees_vault_pda,
-    validator_fees_vault_pda_from_validator,
+    delegation_record_pda_from_delegated_account, fee_exemption_pda_from_delegated_account,
+    fees_vault_pda, protocol_stats_pda, validator_fees_vault_pda_from_validator,
 };
+use dlp::state::{FeeExemption, ProtocolStats};
+use solana_program::instruction::InstructionError;
+use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{read_file, BanksClient, ProgramTest};
 use solana_sdk::{
     account::Account,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 use crate::fixtures::{
-    get_delegation_metadata_data, get_delegation_record_data, DELEGATED_PDA_ID,
-    DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+    create_delegation_metadata_data_with_seed_template, get_delegation_metadata_data,
+    get_delegation_record_data, DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
 };
 
 mod fixtures;
@@ -22,7 +26,7 @@ mod fixtures;
 #[tokio::test]
 async fn test_undelegate_without_commit() {
     // Setup
-    let (banks, _, validator, blockhash) = setup_program_test_env().await;
+    let (banks, _, validator, blockhash) = setup_program_test_env(None, None).await;
 
     // Retrieve the accounts
     let delegation_record_pda = delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID);
@@ -75,7 +79,205 @@ async fn test_undelegate_without_commit() {
     assert_eq!(new_state_data_before_finalize, pda_account.data);
 }
 
-async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
+#[tokio::test]
+async fn test_undelegate_with_seed_template() {
+    // The seeds of the delegated PDA, as a template
+    let mut seed_template = SeedTemplate::default();
+    seed_template.push_literal(b"test-pda").unwrap();
+    let (banks, _, validator, blockhash) = setup_program_test_env(Some(seed_template), None).await;
+
+    let ix = dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    let pda_account = banks.get_account(DELEGATED_PDA_ID).await.unwrap().unwrap();
+    assert!(pda_account.owner.eq(&DELEGATED_PDA_OWNER_ID));
+}
+
+#[tokio::test]
+async fn test_undelegate_with_invalid_seed_template() {
+    // The resolved seeds contain the rent payer, not deriving the delegated PDA
+    let mut seed_template = SeedTemplate::default();
+    seed_template.push_literal(b"test-pda").unwrap();
+    seed_template
+        .push_placeholder(SeedPlaceholder::RentPayer)
+        .unwrap();
+    let (banks, _, validator, blockhash) = setup_program_test_env(Some(seed_template), None).await;
+
+    let ix = dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
+
+#[tokio::test]
+async fn test_undelegate_with_fee_exemption() {
+    let (banks, _, validator, blockhash) =
+        setup_program_test_env(None, Some(FeeExemption { expiry_slot: 0 })).await;
+
+    let fees_vault_lamports = get_lamports(&banks, fees_vault_pda()).await;
+    let validator_fees_vault = validator_fees_vault_pda_from_validator(&validator.pubkey());
+    let validator_fees_vault_lamports = get_lamports(&banks, validator_fees_vault).await;
+
+    let ix = dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert no rent fees were taken
+    assert_eq!(
+        get_lamports(&banks, fees_vault_pda()).await,
+        fees_vault_lamports
+    );
+    assert_eq!(
+        get_lamports(&banks, validator_fees_vault).await,
+        validator_fees_vault_lamports
+    );
+    let delegation_record_account = banks
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap();
+    assert!(delegation_record_account.is_none());
+}
+
+#[tokio::test]
+async fn test_undelegate_with_expired_fee_exemption() {
+    // The exemption expired at the first slot
+    let (banks, _, validator, blockhash) =
+        setup_program_test_env(None, Some(FeeExemption { expiry_slot: 1 })).await;
+
+    let fees_vault_lamports = get_lamports(&banks, fees_vault_pda()).await;
+
+    let ix = dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Assert the rent fees were taken
+    assert!(get_lamports(&banks, fees_vault_pda()).await > fees_vault_lamports);
+}
+
+#[tokio::test]
+async fn test_undelegate_with_protocol_stats() {
+    // Setup, the validator being the admin
+    let (banks, _, validator, blockhash) = setup_program_test_env(None, None).await;
+    let recorded_lamports = Rent::default().minimum_balance(500);
+
+    // Resync the total value locked from the delegation record
+    let ix = dlp::instruction_builder::resync_protocol_stats(
+        validator.pubkey(),
+        &[DELEGATED_PDA_ID],
+        true,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    assert_eq!(get_total_value_locked(&banks).await, recorded_lamports);
+
+    // A delegation record cannot be counted twice
+    let ix = dlp::instruction_builder::resync_protocol_stats(
+        validator.pubkey(),
+        &[DELEGATED_PDA_ID, DELEGATED_PDA_ID],
+        false,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+
+    // Undelegating subtracts the lamports of the account from the total value locked
+    let ix = dlp::instruction_builder::with_protocol_stats(dlp::instruction_builder::undelegate(
+        validator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    ));
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    assert!(banks.process_transaction(tx).await.is_ok());
+    assert_eq!(get_total_value_locked(&banks).await, 0);
+}
+
+async fn get_total_value_locked(banks: &BanksClient) -> u64 {
+    let protocol_stats_account = banks
+        .get_account(protocol_stats_pda())
+        .await
+        .unwrap()
+        .unwrap();
+    ProtocolStats::try_from_bytes_with_discriminator(&protocol_stats_account.data)
+        .unwrap()
+        .total_value_locked
+}
+
+async fn get_lamports(banks: &BanksClient, pubkey: Pubkey) -> u64 {
+    banks.get_account(pubkey).await.unwrap().unwrap().lamports
+}
+
+async fn setup_program_test_env(
+    seed_template: Option<SeedTemplate>,
+    fee_exemption: Option<FeeExemption>,
+) -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
     let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
@@ -117,7 +319,14 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
     );
 
     // Setup the delegated account metadata PDA
-    let delegation_metadata_data = get_delegation_metadata_data(validator.pubkey(), Some(true));
+    let delegation_metadata_data = match seed_template {
+        Some(seed_template) => create_delegation_metadata_data_with_seed_template(
+            validator.pubkey(),
+            seed_template,
+            true,
+        ),
+        None => get_delegation_metadata_data(validator.pubkey(), Some(true)),
+    };
     program_test.add_account(
         delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
         Account {
@@ -166,6 +375,24 @@ async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
         },
     );
 
+    // Setup the fee exemption of the delegated PDA
+    if let Some(fee_exemption) = fee_exemption {
+        let mut fee_exemption_data = vec![0; FeeExemption::size_with_discriminator()];
+        fee_exemption
+            .to_bytes_with_discriminator(&mut fee_exemption_data)
+            .unwrap();
+        program_test.add_account(
+            fee_exemption_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Account {
+                lamports: Rent::default().minimum_balance(fee_exemption_data.len()),
+                data: fee_exemption_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
     let (banks, payer, blockhash) = program_test.start().await;
     (banks, payer, validator, blockhash)
 }
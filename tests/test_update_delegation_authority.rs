@@ -0,0 +1,176 @@
+use dlp::args::UpdateDelegationAuthorityArgs;
+use dlp::error::DlpError;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account,
+};
+use dlp::state::DelegationRecord;
+use solana_program::instruction::{Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    get_commit_record_account_data, get_delegation_metadata_data, get_delegation_record_data,
+    DELEGATED_PDA_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_update_delegation_authority() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env(false, false).await;
+    let new_validator = Keypair::new();
+
+    // Only the authority of the delegation can hand it over
+    let ix = update_delegation_authority(&new_validator, new_validator.pubkey());
+    let res = process(&mut context, &[ix], &validator, &[&new_validator]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+
+    let ix = update_delegation_authority(&validator, new_validator.pubkey());
+    process(&mut context, &[ix], &validator, &[]).await.unwrap();
+    let delegation_record_account = context
+        .banks_client
+        .get_account(delegation_record_pda_from_delegated_account(
+            &DELEGATED_PDA_ID,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let delegation_record =
+        DelegationRecord::try_from_bytes_with_discriminator(&delegation_record_account.data)
+            .unwrap();
+    assert_eq!(delegation_record.authority, new_validator.pubkey());
+
+    // The previous authority can no longer update it
+    let ix = update_delegation_authority(&validator, validator.pubkey());
+    let res = process(&mut context, &[ix], &validator, &[]).await;
+    assert_dlp_error(res, DlpError::InvalidAuthority);
+}
+
+#[tokio::test]
+async fn test_update_delegation_authority_with_pending_commit() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env(true, false).await;
+
+    // The pending commit of the authority must be finalized first
+    let ix = update_delegation_authority(&validator, Pubkey::new_unique());
+    let res = process(&mut context, &[ix], &validator, &[]).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountOwner)
+    );
+}
+
+#[tokio::test]
+async fn test_update_undelegatable_delegation_authority() {
+    // Setup
+    let (mut context, validator) = setup_program_test_env(false, true).await;
+
+    let ix = update_delegation_authority(&validator, Pubkey::new_unique());
+    let res = process(&mut context, &[ix], &validator, &[]).await;
+    assert_dlp_error(res, DlpError::AlreadyUndelegated);
+}
+
+fn update_delegation_authority(authority: &Keypair, new_authority: Pubkey) -> Instruction {
+    dlp::instruction_builder::update_delegation_authority(
+        authority.pubkey(),
+        DELEGATED_PDA_ID,
+        UpdateDelegationAuthorityArgs { new_authority },
+    )
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let signers = [&[payer], signers].concat();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&payer.pubkey()), &signers, blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+/// Setup a delegated PDA of the validator, with a pending commit if `pending_commit`, and
+/// marked undelegatable if `is_undelegatable`
+async fn setup_program_test_env(
+    pending_commit: bool,
+    is_undelegatable: bool,
+) -> (ProgramTestContext, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        validator.pubkey(),
+        Account {
+            lamports: 10 * LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+    );
+    let delegation_metadata_data =
+        get_delegation_metadata_data(validator.pubkey(), Some(is_undelegatable));
+    add_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+    );
+    if pending_commit {
+        let commit_record_data = get_commit_record_account_data(validator.pubkey());
+        add_dlp_account(
+            &mut program_test,
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Rent::default().minimum_balance(commit_record_data.len()),
+            commit_record_data,
+        );
+    }
+
+    let context = program_test.start_with_context().await;
+    (context, validator)
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
@@ -0,0 +1,158 @@
+use borsh::BorshDeserialize;
+use dlp::pda::{
+    commit_record_pda_from_delegated_account, delegation_metadata_pda_from_delegated_account,
+    delegation_record_pda_from_delegated_account,
+};
+use dlp::state::DelegationViolations;
+use solana_program::rent::Rent;
+use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::fixtures::{
+    get_commit_record_account_data, get_delegation_metadata_data, get_delegation_record_data,
+    DELEGATED_PDA, DELEGATED_PDA_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn test_validate_consistent_delegation() {
+    // Setup
+    let (banks, payer, blockhash) =
+        setup_program_test_env(Rent::default().minimum_balance(500), false).await;
+
+    let violations = validate_delegation(&banks, &payer, blockhash).await;
+    assert!(violations.is_empty(), "{:?}", violations);
+}
+
+#[tokio::test]
+async fn test_validate_inconsistent_delegation() {
+    // Setup a delegated account missing lamports, with a pending commit missing its
+    // commit state and not following the last finalized nonce
+    let (banks, payer, blockhash) =
+        setup_program_test_env(LAMPORTS_PER_SOL / 1_000_000, true).await;
+
+    let violations = validate_delegation(&banks, &payer, blockhash).await;
+    assert_eq!(
+        violations,
+        DelegationViolations(
+            DelegationViolations::LAMPORTS
+                | DelegationViolations::INCOMPLETE_COMMIT
+                | DelegationViolations::COMMIT_NONCE
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_validate_undelegated_account() {
+    // Setup
+    let (banks, payer, blockhash) =
+        setup_program_test_env(Rent::default().minimum_balance(500), false).await;
+
+    // An account which is not delegated has none of the delegation PDAs
+    let ix = dlp::instruction_builder::validate_delegation(payer.pubkey());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+    let res = banks.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(res.result.is_ok());
+    let return_data = res.metadata.unwrap().return_data.unwrap();
+    assert_eq!(
+        DelegationViolations::try_from_slice(&return_data.data).unwrap(),
+        DelegationViolations(
+            DelegationViolations::DELEGATED_ACCOUNT_OWNER
+                | DelegationViolations::DELEGATION_RECORD
+                | DelegationViolations::DELEGATION_METADATA
+        )
+    );
+}
+
+async fn validate_delegation(
+    banks: &BanksClient,
+    payer: &Keypair,
+    blockhash: Hash,
+) -> DelegationViolations {
+    let ix = dlp::instruction_builder::validate_delegation(DELEGATED_PDA_ID);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    let res = banks.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(res.result.is_ok());
+    let return_data = res.metadata.unwrap().return_data.unwrap();
+    DelegationViolations::try_from_slice(&return_data.data).unwrap()
+}
+
+async fn setup_program_test_env(
+    delegated_account_lamports: u64,
+    pending_commit_record: bool,
+) -> (BanksClient, Keypair, Hash) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let authority = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+
+    program_test.add_account(
+        authority.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: system_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup a delegated PDA
+    program_test.add_account(
+        DELEGATED_PDA_ID,
+        Account {
+            lamports: delegated_account_lamports,
+            data: DELEGATED_PDA.into(),
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    // Setup the delegated account PDAs
+    let delegation_record_data = get_delegation_record_data(authority.pubkey(), None);
+    program_test.add_account(
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_record_data.len()),
+            data: delegation_record_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let delegation_metadata_data = get_delegation_metadata_data(authority.pubkey(), None);
+    program_test.add_account(
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(delegation_metadata_data.len()),
+            data: delegation_metadata_data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    if pending_commit_record {
+        let commit_record_data = get_commit_record_account_data(authority.pubkey());
+        program_test.add_account(
+            commit_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+            Account {
+                lamports: Rent::default().minimum_balance(commit_record_data.len()),
+                data: commit_record_data,
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let (banks, _, blockhash) = program_test.start().await;
+    (banks, authority, blockhash)
+}
@@ -0,0 +1,249 @@
+use dlp::error::DlpError;
+use dlp::pda::{
+    delegation_metadata_pda_from_delegated_account, delegation_record_pda_from_delegated_account,
+    fees_vault_pda, validator_fees_vault_pda_from_validator, validator_liveness_pda_from_validator,
+};
+use dlp::state::ValidatorLiveness;
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::{native_token::LAMPORTS_PER_SOL, system_program};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::fixtures::{
+    create_delegation_metadata_data_with_heartbeat_timeout, get_delegation_record_data,
+    DELEGATED_PDA_ID, DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY,
+};
+
+mod fixtures;
+
+const HEARTBEAT_TIMEOUT_SLOTS: u64 = 100;
+
+#[tokio::test]
+async fn test_undelegate_on_stale_heartbeat() {
+    // Setup
+    let (mut context, validator, undelegator) = setup_program_test_env().await;
+
+    // The validator heartbeats
+    let heartbeat_ix = dlp::instruction_builder::validator_heartbeat(validator.pubkey());
+    let res = process(&mut context, &[heartbeat_ix.clone()], &validator).await;
+    assert!(res.is_ok());
+    let first_heartbeat_slot = validator_liveness(&mut context, &validator).await;
+
+    // While the validator heartbeats, the account is not undelegatable
+    let res = process(
+        &mut context,
+        &[undelegate_ix(&validator, &undelegator, true)],
+        &undelegator,
+    )
+    .await;
+    assert_dlp_error(res, DlpError::NotUndelegatable);
+
+    // Once the validator stopped heartbeating, the account is undelegatable
+    context
+        .warp_to_slot(first_heartbeat_slot + HEARTBEAT_TIMEOUT_SLOTS)
+        .unwrap();
+
+    // A new heartbeat restarts the timeout
+    let res = process(&mut context, &[heartbeat_ix], &validator).await;
+    assert!(res.is_ok());
+    let last_heartbeat_slot = validator_liveness(&mut context, &validator).await;
+    assert!(last_heartbeat_slot > first_heartbeat_slot);
+    let res = process(
+        &mut context,
+        &[undelegate_ix(&validator, &undelegator, true)],
+        &undelegator,
+    )
+    .await;
+    assert_dlp_error(res, DlpError::NotUndelegatable);
+
+    context
+        .warp_to_slot(last_heartbeat_slot + HEARTBEAT_TIMEOUT_SLOTS)
+        .unwrap();
+
+    // The validator liveness is required to prove the heartbeat stale
+    let res = process(
+        &mut context,
+        &[undelegate_ix(&validator, &undelegator, false)],
+        &undelegator,
+    )
+    .await;
+    assert_dlp_error(res, DlpError::NotUndelegatable);
+
+    // Any validator can then undelegate the account without a commit
+    let res = process(
+        &mut context,
+        &[undelegate_ix(&validator, &undelegator, true)],
+        &undelegator,
+    )
+    .await;
+    assert!(res.is_ok());
+    let delegated_account = context
+        .banks_client
+        .get_account(DELEGATED_PDA_ID)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegated_account.owner, DELEGATED_PDA_OWNER_ID);
+}
+
+#[tokio::test]
+async fn test_undelegate_with_liveness_of_another_validator() {
+    // Setup
+    let (mut context, validator, undelegator) = setup_program_test_env().await;
+
+    // The undelegator heartbeated long ago, but it is not the validator of the delegation
+    let res = process(
+        &mut context,
+        &[dlp::instruction_builder::validator_heartbeat(
+            undelegator.pubkey(),
+        )],
+        &undelegator,
+    )
+    .await;
+    assert!(res.is_ok());
+    context.warp_to_slot(HEARTBEAT_TIMEOUT_SLOTS * 2).unwrap();
+
+    let mut ix = undelegate_ix(&validator, &undelegator, false);
+    ix.accounts.push(AccountMeta::new_readonly(
+        validator_liveness_pda_from_validator(&undelegator.pubkey()),
+        false,
+    ));
+    let res = process(&mut context, &[ix], &undelegator).await;
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
+
+fn undelegate_ix(validator: &Keypair, undelegator: &Keypair, with_liveness: bool) -> Instruction {
+    let mut ix = dlp::instruction_builder::undelegate(
+        undelegator.pubkey(),
+        DELEGATED_PDA_ID,
+        DELEGATED_PDA_OWNER_ID,
+        validator.pubkey(),
+    );
+    if with_liveness {
+        ix.accounts.push(AccountMeta::new_readonly(
+            validator_liveness_pda_from_validator(&validator.pubkey()),
+            false,
+        ));
+    }
+    ix
+}
+
+async fn validator_liveness(context: &mut ProgramTestContext, validator: &Keypair) -> u64 {
+    let validator_liveness_account = context
+        .banks_client
+        .get_account(validator_liveness_pda_from_validator(&validator.pubkey()))
+        .await
+        .unwrap()
+        .unwrap();
+    ValidatorLiveness::try_from_bytes_with_discriminator(&validator_liveness_account.data)
+        .unwrap()
+        .last_heartbeat_slot
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    ixs: &[Instruction],
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_dlp_error(res: Result<(), BanksClientError>, error: DlpError) {
+    assert_eq!(
+        res.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+async fn setup_program_test_env() -> (ProgramTestContext, Keypair, Keypair) {
+    let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
+    program_test.prefer_bpf(true);
+    let validator = Keypair::from_bytes(&TEST_AUTHORITY).unwrap();
+    let undelegator = Keypair::new();
+
+    // Setup the validators and their fees vaults
+    for validator in [&validator, &undelegator] {
+        program_test.add_account(
+            validator.pubkey(),
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        program_test.add_account(
+            validator_fees_vault_pda_from_validator(&validator.pubkey()),
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: vec![],
+                owner: dlp::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    // Setup a delegated PDA without data, undelegated without a CPI
+    add_dlp_account(
+        &mut program_test,
+        DELEGATED_PDA_ID,
+        LAMPORTS_PER_SOL,
+        vec![],
+    );
+
+    // Setup the delegation record and the metadata, opting into the heartbeat timeout
+    let delegation_record_data = get_delegation_record_data(validator.pubkey(), None);
+    add_dlp_account(
+        &mut program_test,
+        delegation_record_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_record_data.len()),
+        delegation_record_data,
+    );
+    let delegation_metadata_data = create_delegation_metadata_data_with_heartbeat_timeout(
+        validator.pubkey(),
+        HEARTBEAT_TIMEOUT_SLOTS,
+    );
+    add_dlp_account(
+        &mut program_test,
+        delegation_metadata_pda_from_delegated_account(&DELEGATED_PDA_ID),
+        Rent::default().minimum_balance(delegation_metadata_data.len()),
+        delegation_metadata_data,
+    );
+
+    // Setup the protocol fees vault
+    add_dlp_account(
+        &mut program_test,
+        fees_vault_pda(),
+        Rent::default().minimum_balance(0),
+        vec![],
+    );
+
+    let context = program_test.start_with_context().await;
+    (context, validator, undelegator)
+}
+
+fn add_dlp_account(program_test: &mut ProgramTest, pubkey: Pubkey, lamports: u64, data: Vec<u8>) {
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports,
+            data,
+            owner: dlp::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
@@ -1,13 +1,21 @@
+use std::collections::BTreeSet;
+
 use crate::fixtures::{DELEGATED_PDA_OWNER_ID, TEST_AUTHORITY};
-use dlp::pda::program_config_from_program_id;
-use dlp::state::ProgramConfig;
+use borsh::BorshDeserialize;
+use dlp::args::MAX_WHITELIST_BATCH_VALIDATORS;
+use dlp::consts::MAX_UNDELEGATE_LAMPORTS_TOLERANCE;
+use dlp::error::DlpError;
+use dlp::pda::{program_config_from_program_id, validator_whitelist_shard_pda_from_program_id};
+use dlp::state::{ProgramConfig, ValidatorWhitelistShard, ValidatorWhitelistStatus};
+use solana_program::instruction::InstructionError;
 use solana_program::rent::Rent;
 use solana_program::{hash::Hash, native_token::LAMPORTS_PER_SOL, system_program};
 use solana_program_test::{read_file, BanksClient, ProgramTest};
 use solana_sdk::{
     account::Account,
+    pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 mod fixtures;
@@ -46,6 +54,75 @@ async fn test_whitelist_validator_for_program() {
         .contains(&validator.pubkey()));
 }
 
+#[tokio::test]
+async fn test_is_validator_whitelisted() {
+    // Setup
+    let (banks, _, validator, blockhash) = setup_program_test_env().await;
+
+    // Any validator is whitelisted for a program without program config
+    let status =
+        is_validator_whitelisted(&banks, &validator, Pubkey::new_unique(), blockhash).await;
+    assert_eq!(
+        status,
+        ValidatorWhitelistStatus {
+            is_whitelisted: true,
+            max_delegation_slots: 0,
+            has_program_config: false,
+        }
+    );
+
+    let ix = dlp::instruction_builder::whitelist_validator_for_program(
+        validator.pubkey(),
+        validator.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        true,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&validator.pubkey()),
+        &[&validator],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    let status = is_validator_whitelisted(&banks, &validator, validator.pubkey(), blockhash).await;
+    assert!(status.is_whitelisted);
+    assert!(status.has_program_config);
+    let status =
+        is_validator_whitelisted(&banks, &validator, Pubkey::new_unique(), blockhash).await;
+    assert!(!status.is_whitelisted);
+
+    // The same check runs on the fetched program config
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        ValidatorWhitelistStatus::from_program_config_data(
+            &validator.pubkey(),
+            Some(&program_config_account.data)
+        )
+        .unwrap(),
+        is_validator_whitelisted(&banks, &validator, validator.pubkey(), blockhash).await
+    );
+}
+
+async fn is_validator_whitelisted(
+    banks: &BanksClient,
+    payer: &Keypair,
+    validator: Pubkey,
+    blockhash: Hash,
+) -> ValidatorWhitelistStatus {
+    let ix = dlp::instruction_builder::is_validator_whitelisted(validator, DELEGATED_PDA_OWNER_ID);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    let res = banks.simulate_transaction(tx).await.unwrap();
+    assert!(res.result.unwrap().is_ok());
+    let return_data = res.simulation_details.unwrap().return_data.unwrap();
+    ValidatorWhitelistStatus::try_from_slice(&return_data.data).unwrap()
+}
+
 #[tokio::test]
 async fn test_remove_validator_for_program() {
     // Setup
@@ -97,6 +174,377 @@ async fn test_remove_validator_for_program() {
         .contains(&validator.pubkey()));
 }
 
+#[tokio::test]
+async fn test_set_program_allowed_data_lens() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ix = dlp::instruction_builder::set_program_allowed_data_lens(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        vec![8, 128],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    println!("{:?}", res);
+    assert!(res.is_ok());
+
+    // Check that the allowed data lengths are set
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await;
+    let program_config = ProgramConfig::try_from_bytes_with_discriminator(
+        &program_config_account.unwrap().unwrap().data,
+    )
+    .unwrap();
+    assert_eq!(program_config.allowed_data_lens, vec![8, 128]);
+    assert!(program_config.approved_validators.is_empty());
+}
+
+#[tokio::test]
+async fn test_set_program_undelegate_lamports_tolerance() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    // A tolerance above the maximum is rejected
+    let ix = dlp::instruction_builder::set_program_undelegate_lamports_tolerance(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        MAX_UNDELEGATE_LAMPORTS_TOLERANCE + 1,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::UndelegateLamportsToleranceTooHigh as u32)
+        )
+    );
+
+    let ix = dlp::instruction_builder::set_program_undelegate_lamports_tolerance(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        5_000,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Check that the tolerance is set
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await;
+    let program_config = ProgramConfig::try_from_bytes_with_discriminator(
+        &program_config_account.unwrap().unwrap().data,
+    )
+    .unwrap();
+    assert_eq!(program_config.undelegate_lamports_tolerance, 5_000);
+    assert!(program_config.allowed_data_lens.is_empty());
+}
+
+#[tokio::test]
+async fn test_set_program_max_delegation_slots() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ix = dlp::instruction_builder::set_program_max_delegation_slots(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        1_000,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Check that the maximum delegation slots are set
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await;
+    let program_config = ProgramConfig::try_from_bytes_with_discriminator(
+        &program_config_account.unwrap().unwrap().data,
+    )
+    .unwrap();
+    assert_eq!(program_config.max_delegation_slots, 1_000);
+    assert!(program_config.approved_validators.is_empty());
+}
+
+#[tokio::test]
+async fn test_set_program_validate_delegations() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ix = dlp::instruction_builder::set_program_validate_delegations(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        true,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // Check that the delegations of the program are validated
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await;
+    let program_config = ProgramConfig::try_from_bytes_with_discriminator(
+        &program_config_account.unwrap().unwrap().data,
+    )
+    .unwrap();
+    assert!(program_config.validate_delegations);
+    assert!(program_config.approved_validators.is_empty());
+}
+
+#[tokio::test]
+async fn test_whitelist_validators_for_program_batch() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let validators: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+    // Insert the validators in a single instruction
+    let ix = dlp::instruction_builder::whitelist_validators_for_program_batch(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        validators.clone(),
+        vec![],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(res.result.is_ok());
+    let return_data = res.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.data, 3u32.to_le_bytes());
+
+    // Remove two of them, the freed rent is refunded to the authority
+    let ix = dlp::instruction_builder::whitelist_validators_for_program_batch(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        vec![],
+        validators[1..].to_vec(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    let program_config =
+        ProgramConfig::try_from_bytes_with_discriminator(&program_config_account.data).unwrap();
+    assert_eq!(
+        program_config.approved_validators,
+        BTreeSet::from([validators[0]])
+    );
+    assert_eq!(
+        program_config_account.lamports,
+        Rent::default().minimum_balance(program_config_account.data.len())
+    );
+}
+
+#[tokio::test]
+async fn test_whitelist_validators_for_program_batch_too_large() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+
+    let ix = dlp::instruction_builder::whitelist_validators_for_program_batch(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        vec![Pubkey::new_unique(); MAX_WHITELIST_BATCH_VALIDATORS],
+        vec![Pubkey::new_unique()],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(DlpError::TooManyValidatorsInBatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_whitelist_validator_shard_for_program() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let validator = Pubkey::new_unique();
+    let shard_pda =
+        validator_whitelist_shard_pda_from_program_id(&DELEGATED_PDA_OWNER_ID, &validator);
+
+    let ix = dlp::instruction_builder::whitelist_validator_shard_for_program(
+        authority.pubkey(),
+        validator,
+        DELEGATED_PDA_OWNER_ID,
+        true,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+
+    // The program config is created to enforce the whitelist, which holds the validator in
+    // its shard only
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    let program_config =
+        ProgramConfig::try_from_bytes_with_discriminator(&program_config_account.data).unwrap();
+    assert!(program_config.approved_validators.is_empty());
+    let shard_account = banks.get_account(shard_pda).await.unwrap().unwrap();
+    let shard =
+        ValidatorWhitelistShard::try_from_bytes_with_discriminator(&shard_account.data).unwrap();
+    assert_eq!(shard.program, DELEGATED_PDA_OWNER_ID);
+    assert_eq!(shard.validator, validator);
+
+    let status = is_validator_whitelisted(&banks, &authority, validator, blockhash).await;
+    assert!(status.is_whitelisted);
+    let status =
+        is_validator_whitelisted(&banks, &authority, Pubkey::new_unique(), blockhash).await;
+    assert!(!status.is_whitelisted);
+
+    // Remove the validator, closing its shard
+    let ix = dlp::instruction_builder::whitelist_validator_shard_for_program(
+        authority.pubkey(),
+        validator,
+        DELEGATED_PDA_OWNER_ID,
+        false,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+    assert!(banks.get_account(shard_pda).await.unwrap().is_none());
+    let status = is_validator_whitelisted(&banks, &authority, validator, blockhash).await;
+    assert!(!status.is_whitelisted);
+}
+
+#[tokio::test]
+async fn test_migrate_program_config_whitelist() {
+    // Setup
+    let (banks, _, authority, blockhash) = setup_program_test_env().await;
+    let validators: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+    let ix = dlp::instruction_builder::whitelist_validators_for_program_batch(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        validators.clone(),
+        vec![],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction(tx).await;
+    assert!(res.is_ok());
+    let approved_validators: Vec<Pubkey> = BTreeSet::from_iter(validators).into_iter().collect();
+
+    // The shards must be the ones of the first approved validators, in order
+    let ix = dlp::instruction_builder::migrate_program_config_whitelist(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        &approved_validators[1..2],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let err = banks.process_transaction(tx).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+
+    // Migrate two of the three validators
+    let ix = dlp::instruction_builder::migrate_program_config_whitelist(
+        authority.pubkey(),
+        DELEGATED_PDA_OWNER_ID,
+        &approved_validators[..2],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        blockhash,
+    );
+    let res = banks.process_transaction_with_metadata(tx).await.unwrap();
+    assert!(res.result.is_ok());
+    let return_data = res.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.data, 1u32.to_le_bytes());
+
+    let program_config_account = banks
+        .get_account(program_config_from_program_id(&DELEGATED_PDA_OWNER_ID))
+        .await
+        .unwrap()
+        .unwrap();
+    let program_config =
+        ProgramConfig::try_from_bytes_with_discriminator(&program_config_account.data).unwrap();
+    assert_eq!(
+        program_config.approved_validators,
+        BTreeSet::from([approved_validators[2]])
+    );
+
+    // Every validator is still whitelisted, by the program config or its shard
+    for validator in approved_validators {
+        let status = is_validator_whitelisted(&banks, &authority, validator, blockhash).await;
+        assert!(status.is_whitelisted);
+    }
+}
+
 async fn setup_program_test_env() -> (BanksClient, Keypair, Keypair, Hash) {
     let mut program_test = ProgramTest::new("dlp", dlp::ID, None);
     program_test.prefer_bpf(true);
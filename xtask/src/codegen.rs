@@ -0,0 +1,452 @@
+//! Generate the TypeScript and JSON bindings of the delegation program.
+//!
+//! The discriminators, the error codes and the instruction args are parsed from the
+//! sources of the program, and the PDA seeds are read from [dlp::pda::PDA_REGISTRY], so
+//! that the Rust definitions stay the single source of truth.
+
+use std::path::Path;
+
+use dlp::pda::{PdaProgram, SeedKind};
+use serde_json::{json, Value};
+use syn::{Attribute, Expr, Fields, GenericArgument, Item, Lit, PathArguments, Type};
+
+const HEADER: &str =
+    "// Generated by `cargo xtask codegen` from the delegation program sources, do not edit.";
+
+/// A variant of a fieldless enum with an explicit discriminant
+pub struct Variant {
+    pub name: String,
+    pub value: u64,
+    /// The message of the `#[error]` attribute, for the error codes
+    pub message: Option<String>,
+}
+
+/// A Borsh type of an args field
+#[derive(Debug, PartialEq)]
+pub enum BorshType {
+    Primitive(&'static str),
+    Vec(Box<BorshType>),
+    Option(Box<BorshType>),
+    Tuple(Vec<BorshType>),
+    Array(Box<BorshType>, usize),
+    Defined(String),
+}
+
+pub struct Field {
+    pub name: String,
+    pub ty: BorshType,
+    pub docs: Vec<String>,
+}
+
+/// An args struct, serialized with Borsh after the instruction discriminator
+pub struct ArgsStruct {
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+pub struct Bindings {
+    pub discriminators: Vec<Variant>,
+    pub errors: Vec<Variant>,
+    pub args: Vec<ArgsStruct>,
+}
+
+impl Bindings {
+    /// Parse the bindings from the sources of the crate at `root`
+    pub fn parse(root: &Path) -> Result<Self, String> {
+        let src = root.join("src");
+        let discriminators = parse_enum(
+            &parse_file(&src.join("discriminator.rs"))?,
+            "DlpDiscriminator",
+        )?;
+        let errors = parse_enum(&parse_file(&src.join("error.rs"))?, "DlpError")?;
+
+        let mut paths = std::fs::read_dir(src.join("args"))
+            .map_err(|e| format!("src/args: {}", e))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("src/args: {}", e))?;
+        paths.sort();
+        let mut args = vec![];
+        for path in paths {
+            args.extend(parse_args(&parse_file(&path)?)?);
+        }
+
+        Ok(Self {
+            discriminators,
+            errors,
+            args,
+        })
+    }
+
+    pub fn to_ts(&self) -> String {
+        let mut ts = format!(
+            "{}\n\nimport {{ PublicKey }} from \"@solana/web3.js\";\n\n",
+            HEADER
+        );
+        ts += &format!(
+            "export const DELEGATION_PROGRAM_ID = new PublicKey(\"{}\");\n\n",
+            dlp::ID
+        );
+
+        ts += "export enum DlpDiscriminator {\n";
+        for variant in &self.discriminators {
+            ts += &format!("  {} = {},\n", variant.name, variant.value);
+        }
+        ts += "}\n\nexport enum DlpError {\n";
+        for variant in &self.errors {
+            ts += &format!("  {} = {},\n", variant.name, variant.value);
+        }
+        ts += "}\n\nexport const DLP_ERROR_MESSAGES: Record<DlpError, string> = {\n";
+        for variant in &self.errors {
+            ts += &format!(
+                "  [DlpError.{}]: {},\n",
+                variant.name,
+                json!(variant.message.as_deref().unwrap_or_default())
+            );
+        }
+        ts += "};\n\n";
+
+        ts += "export const PDA_SEEDS = {\n";
+        for layout in dlp::pda::registry() {
+            ts += &format!(
+                "  {}: {{ tag: {}, seeds: {}, program: {} }},\n",
+                camel_case(&layout.name.replace(' ', "_")),
+                json!(tag(layout.tag)),
+                json!(layout.seeds.iter().map(seed_kind).collect::<Vec<_>>()),
+                json!(pda_program(layout.program)),
+            );
+        }
+        ts += "} as const;\n";
+
+        for args in &self.args {
+            ts += &format!("\nexport interface {} {{\n", args.name);
+            for field in &args.fields {
+                for doc in &field.docs {
+                    ts += &format!("  ///{}\n", doc);
+                }
+                ts += &format!("  {}: {};\n", camel_case(&field.name), ts_type(&field.ty));
+            }
+            ts += "}\n";
+        }
+        ts
+    }
+
+    pub fn to_json(&self) -> String {
+        let variants = |variants: &[Variant]| -> Vec<Value> {
+            variants
+                .iter()
+                .map(|variant| match &variant.message {
+                    Some(message) => {
+                        json!({ "code": variant.value, "name": variant.name, "msg": message })
+                    }
+                    None => json!({ "name": variant.name, "value": variant.value }),
+                })
+                .collect()
+        };
+        let pdas: Vec<Value> = dlp::pda::registry()
+            .iter()
+            .map(|layout| {
+                json!({
+                    "name": layout.name,
+                    "tag": tag(layout.tag),
+                    "seeds": layout.seeds.iter().map(seed_kind).collect::<Vec<_>>(),
+                    "program": pda_program(layout.program),
+                })
+            })
+            .collect();
+        let args: Vec<Value> = self
+            .args
+            .iter()
+            .map(|args| {
+                json!({
+                    "name": args.name,
+                    "fields": args.fields.iter().map(|field| json!({
+                        "name": field.name,
+                        "type": json_type(&field.ty),
+                        "docs": field.docs.iter().map(|doc| doc.trim()).collect::<Vec<_>>(),
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        let bindings = json!({
+            "address": dlp::ID.to_string(),
+            "discriminators": variants(&self.discriminators),
+            "errors": variants(&self.errors),
+            "pdas": pdas,
+            "args": args,
+        });
+        serde_json::to_string_pretty(&bindings).expect("bindings are serializable") + "\n"
+    }
+}
+
+fn parse_file(path: &Path) -> Result<syn::File, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    syn::parse_file(&source).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Parse the variants of a fieldless enum with explicit discriminants
+fn parse_enum(file: &syn::File, name: &str) -> Result<Vec<Variant>, String> {
+    let item = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            Item::Enum(item) if item.ident == name => Some(item),
+            _ => None,
+        })
+        .ok_or_else(|| format!("enum {} not found", name))?;
+
+    item.variants
+        .iter()
+        .map(|variant| {
+            let value = match &variant.discriminant {
+                Some((
+                    _,
+                    Expr::Lit(syn::ExprLit {
+                        lit: Lit::Int(value),
+                        ..
+                    }),
+                )) => value.base10_parse::<u64>().map_err(|e| e.to_string())?,
+                _ => return Err(format!("{}::{} has no discriminant", name, variant.ident)),
+            };
+            let message = variant
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("error"))
+                .find_map(|attr| attr.parse_args::<syn::LitStr>().ok())
+                .map(|message| message.value());
+            Ok(Variant {
+                name: variant.ident.to_string(),
+                value,
+                message,
+            })
+        })
+        .collect()
+}
+
+/// Parse the structs deriving a Borsh trait
+fn parse_args(file: &syn::File) -> Result<Vec<ArgsStruct>, String> {
+    let mut args = vec![];
+    for item in &file.items {
+        let Item::Struct(item) = item else {
+            continue;
+        };
+        if !derives_borsh(&item.attrs) {
+            continue;
+        }
+        let Fields::Named(fields) = &item.fields else {
+            return Err(format!("{} must have named fields", item.ident));
+        };
+        let fields = fields
+            .named
+            .iter()
+            .filter(|field| !is_borsh_skipped(&field.attrs))
+            .map(|field| {
+                Ok(Field {
+                    name: field.ident.as_ref().expect("named field").to_string(),
+                    ty: borsh_type(&field.ty)?,
+                    docs: docs(&field.attrs),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        args.push(ArgsStruct {
+            name: item.ident.to_string(),
+            fields,
+        });
+    }
+    Ok(args)
+}
+
+fn derives_borsh(attrs: &[Attribute]) -> bool {
+    let mut derives = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            derives |=
+                meta.path.is_ident("BorshSerialize") || meta.path.is_ident("BorshDeserialize");
+            Ok(())
+        });
+    }
+    derives
+}
+
+/// Whether the field is skipped by borsh, e.g. trailing the instruction data instead
+fn is_borsh_skipped(attrs: &[Attribute]) -> bool {
+    let mut skipped = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("borsh")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            skipped |= meta.path.is_ident("skip");
+            Ok(())
+        });
+    }
+    skipped
+}
+
+fn docs(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }),
+                ..
+            }) => Some(doc.value()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn borsh_type(ty: &Type) -> Result<BorshType, String> {
+    match ty {
+        Type::Tuple(tuple) => Ok(BorshType::Tuple(
+            tuple
+                .elems
+                .iter()
+                .map(borsh_type)
+                .collect::<Result<_, _>>()?,
+        )),
+        Type::Array(array) => {
+            let len = match &array.len {
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Int(len), ..
+                }) => len.base10_parse::<usize>().map_err(|e| e.to_string())?,
+                _ => return Err("array lengths must be literals".to_string()),
+            };
+            Ok(BorshType::Array(Box::new(borsh_type(&array.elem)?), len))
+        }
+        Type::Path(path) => {
+            let segment = path.path.segments.last().ok_or("empty type path")?;
+            let generic = || match &segment.arguments {
+                PathArguments::AngleBracketed(args) => match args.args.first() {
+                    Some(GenericArgument::Type(ty)) => borsh_type(ty).map(Box::new),
+                    _ => Err(format!("unsupported generics of {}", segment.ident)),
+                },
+                _ => Err(format!("{} must have a generic type", segment.ident)),
+            };
+            Ok(match segment.ident.to_string().as_str() {
+                "u8" => BorshType::Primitive("u8"),
+                "u16" => BorshType::Primitive("u16"),
+                "u32" => BorshType::Primitive("u32"),
+                "u64" => BorshType::Primitive("u64"),
+                "u128" => BorshType::Primitive("u128"),
+                "i64" => BorshType::Primitive("i64"),
+                "bool" => BorshType::Primitive("bool"),
+                "String" => BorshType::Primitive("string"),
+                "Pubkey" => BorshType::Primitive("pubkey"),
+                "Vec" => BorshType::Vec(generic()?),
+                "Option" => BorshType::Option(generic()?),
+                // Serialized exactly like `Vec<Vec<u8>>`
+                "Seeds" => BorshType::Vec(Box::new(BorshType::Vec(Box::new(
+                    BorshType::Primitive("u8"),
+                )))),
+                name => BorshType::Defined(name.to_string()),
+            })
+        }
+        _ => Err("unsupported args field type".to_string()),
+    }
+}
+
+fn json_type(ty: &BorshType) -> Value {
+    match ty {
+        BorshType::Primitive(name) => json!(name),
+        BorshType::Vec(ty) => json!({ "vec": json_type(ty) }),
+        BorshType::Option(ty) => json!({ "option": json_type(ty) }),
+        BorshType::Tuple(tys) => json!({ "tuple": tys.iter().map(json_type).collect::<Vec<_>>() }),
+        BorshType::Array(ty, len) => json!({ "array": [json_type(ty), len] }),
+        BorshType::Defined(name) => json!({ "defined": name }),
+    }
+}
+
+fn ts_type(ty: &BorshType) -> String {
+    match ty {
+        BorshType::Primitive("u64" | "u128" | "i64") => "bigint".to_string(),
+        BorshType::Primitive("bool") => "boolean".to_string(),
+        BorshType::Primitive("string") => "string".to_string(),
+        BorshType::Primitive("pubkey") => "PublicKey".to_string(),
+        BorshType::Primitive(_) => "number".to_string(),
+        BorshType::Vec(ty) if **ty == BorshType::Primitive("u8") => "Uint8Array".to_string(),
+        BorshType::Vec(ty) | BorshType::Array(ty, _) => format!("{}[]", ts_type(ty)),
+        BorshType::Option(ty) => format!("{} | null", ts_type(ty)),
+        BorshType::Tuple(tys) => format!(
+            "[{}]",
+            tys.iter().map(ts_type).collect::<Vec<_>>().join(", ")
+        ),
+        BorshType::Defined(name) => name.clone(),
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let mut words = name.split('_');
+    let mut camel = words.next().unwrap_or_default().to_string();
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.extend(chars);
+        }
+    }
+    camel
+}
+
+fn tag(tag: &[u8]) -> String {
+    String::from_utf8(tag.to_vec()).expect("PDA tags are utf8")
+}
+
+fn seed_kind(kind: &SeedKind) -> &'static str {
+    match kind {
+        SeedKind::Pubkey => "pubkey",
+        SeedKind::U8 => "u8",
+        SeedKind::U32 => "u32",
+        SeedKind::U64 => "u64",
+    }
+}
+
+fn pda_program(program: PdaProgram) -> &'static str {
+    match program {
+        PdaProgram::Delegation => "delegation",
+        PdaProgram::OwnerProgram => "ownerProgram",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings() -> Bindings {
+        Bindings::parse(Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_discriminators_and_errors() {
+        let bindings = bindings();
+        assert_eq!(bindings.discriminators[0].name, "Delegate");
+        assert_eq!(bindings.discriminators[0].value, 0);
+        assert!(bindings
+            .errors
+            .iter()
+            .all(|error| error.message.as_ref().is_some_and(|msg| !msg.is_empty())));
+        assert!(bindings
+            .errors
+            .iter()
+            .enumerate()
+            .all(|(i, error)| error.value == i as u64));
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let bindings = bindings();
+        let delegate_args = bindings
+            .args
+            .iter()
+            .find(|args| args.name == "DelegateArgs")
+            .unwrap();
+        let types: Vec<_> = delegate_args
+            .fields
+            .iter()
+            .map(|f| ts_type(&f.ty))
+            .collect();
+        assert_eq!(types, ["number", "Uint8Array[]", "PublicKey | null"]);
+        assert!(bindings.to_ts().contains("commitFrequencyMs: number;"));
+    }
+}
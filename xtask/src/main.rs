@@ -0,0 +1,96 @@
+//! Development tasks of the delegation program, run with `cargo xtask <task>`
+//!
+//! Tasks:
+//!
+//! - `codegen [out_dir]`: generate the TypeScript and JSON bindings of the discriminators,
+//!   the instruction args, the PDA seeds and the error codes, in `target/bindings` by
+//!   default
+//! - `replay-capture <rpc_url> [out_dir] [limit]`: capture the latest transactions of the
+//!   program with the state they ran on, in `target/replay` by default, see [replay]
+//! - `replay [fixtures_dir] [program_so]`: replay the captured transactions against the
+//!   local build, `target/deploy/dlp.so` by default, and report the divergences
+//! - `reconcile <rpc_url> <account>`: compare the state of a delegated account finalized
+//!   on L1 with the state reported by its validator, see [reconcile]
+//! - `size`: build the program with each supported feature set, the `minimal` one included,
+//!   and report the size of its `.so`, see [size]
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+mod codegen;
+mod reconcile;
+mod replay;
+mod size;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["codegen"] => codegen(&crate_root().join("target").join("bindings")),
+        ["codegen", out_dir] => codegen(Path::new(out_dir)),
+        ["replay-capture", rpc_url, rest @ ..] if rest.len() <= 2 => {
+            let out_dir = rest
+                .first()
+                .map_or_else(|| crate_root().join("target").join("replay"), PathBuf::from);
+            match rest.get(1).map(|limit| limit.parse()).unwrap_or(Ok(100)) {
+                Ok(limit) => replay::capture(rpc_url, &out_dir, limit),
+                Err(e) => Err(format!("Invalid limit: {}", e)),
+            }
+        }
+        ["replay", rest @ ..] if rest.len() <= 2 => {
+            let target = crate_root().join("target");
+            replay::replay(
+                &rest
+                    .first()
+                    .map_or_else(|| target.join("replay"), PathBuf::from),
+                &rest
+                    .get(1)
+                    .map_or_else(|| target.join("deploy").join("dlp.so"), PathBuf::from),
+            )
+        }
+        ["reconcile", rpc_url, account] => reconcile::reconcile(rpc_url, account),
+        ["size"] => size::report(&crate_root()),
+        _ => Err([
+            "Usage:",
+            "  cargo xtask codegen [out_dir]",
+            "  cargo xtask replay-capture <rpc_url> [out_dir] [limit]",
+            "  cargo xtask replay [fixtures_dir] [program_so]",
+            "  cargo xtask reconcile <rpc_url> <account>",
+            "  cargo xtask size",
+        ]
+        .join("\n")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The root of the delegation program crate
+fn crate_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask is in the crate root")
+        .to_path_buf()
+}
+
+fn codegen(out_dir: &Path) -> Result<(), String> {
+    let bindings = codegen::Bindings::parse(&crate_root())?;
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("{}: {}", out_dir.display(), e))?;
+    for (file, content) in [
+        ("dlp.ts", bindings.to_ts()),
+        ("dlp.json", bindings.to_json()),
+    ] {
+        let path = out_dir.join(file);
+        std::fs::write(&path, content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        println!("Generated {}", path.display());
+    }
+    Ok(())
+}
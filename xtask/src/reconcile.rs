@@ -0,0 +1,57 @@
+//! Compare the state of a delegated account finalized on L1 with the state reported by the
+//! ephemeral rollup of its validator, whose endpoint is read from its validator info, see
+//! [dlp::reconcile]
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::json;
+
+use crate::replay::rpc;
+
+/// Print the divergence report of the account, failing if the states diverge
+pub fn reconcile(rpc_url: &str, account: &str) -> Result<(), String> {
+    let account = account
+        .parse()
+        .map_err(|e| format!("invalid account: {:?}", e))?;
+    let sides = dlp::fetch_reconcile_sides(rpc_url, &account, |url, pubkey| {
+        // The L1 side is read at the finalized commitment, the ER side at its latest state
+        let commitment = if url == rpc_url {
+            "finalized"
+        } else {
+            "processed"
+        };
+        fetch_account_data(url, &pubkey.to_string(), commitment)
+    })
+    .map_err(|e| format!("{:?}", e))?;
+
+    println!(
+        "{}: delegated to {} ({})",
+        account, sides.validator, sides.endpoint
+    );
+    let report = sides.reconcile();
+    println!("{}", report);
+    if report.is_diverged() {
+        return Err(format!("{} diverged from the L1 state", account));
+    }
+    Ok(())
+}
+
+/// Fetch the data of an account, None if it does not exist
+fn fetch_account_data(
+    rpc_url: &str,
+    pubkey: &str,
+    commitment: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let value = rpc(
+        rpc_url,
+        "getAccountInfo",
+        json!([pubkey, { "encoding": "base64", "commitment": commitment }]),
+    )?;
+    if value["value"].is_null() {
+        return Ok(None);
+    }
+    BASE64
+        .decode(value["value"]["data"][0].as_str().ok_or("invalid data")?)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,428 @@
+//! Replay historical transactions of the delegation program against the local build.
+//!
+//! `replay-capture` fetches the latest transactions of the program and the accounts they
+//! load into fixtures, one JSON file per transaction. RPC nodes only serve the latest state
+//! of the accounts, so the pre-state RPC must be restored from a ledger snapshot taken
+//! before the captured transactions (e.g. a validator started from the snapshot, with
+//! `--no-voting` and no peers). If `REPLAY_POST_RPC_URL` is set, the accounts are also
+//! fetched from it as the expected post-state, which requires a snapshot taken right after
+//! each transaction.
+//!
+//! `replay` executes the fixtures in an SVM with the local build of the program, signature
+//! and blockhash checks disabled, and reports every divergence from the recorded outcome:
+//! the transaction status, the lamports of its accounts and, when captured, the owner and
+//! data of the post-state accounts.
+
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use litesvm::LiteSVM;
+use serde_json::{json, Value};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+
+/// The account state of a fixture
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureAccount {
+    pub pubkey: Pubkey,
+    pub account: Account,
+}
+
+/// A captured transaction with the state it ran on
+pub struct Fixture {
+    pub signature: String,
+    pub slot: u64,
+    /// The serialized versioned transaction
+    pub transaction: Vec<u8>,
+    /// The keys of the accounts loaded by the transaction, lookup tables resolved
+    pub account_keys: Vec<Pubkey>,
+    /// The error of the transaction on chain, as returned by the RPC
+    pub err: Value,
+    /// The lamports of the loaded accounts after the transaction on chain
+    pub post_balances: Vec<u64>,
+    pub pre_accounts: Vec<FixtureAccount>,
+    /// The accounts after the transaction, if captured
+    pub post_accounts: Vec<FixtureAccount>,
+}
+
+/// Fetch the latest `limit` transactions of the program into fixtures in `out_dir`
+pub fn capture(rpc_url: &str, out_dir: &Path, limit: usize) -> Result<(), String> {
+    let post_rpc_url = std::env::var("REPLAY_POST_RPC_URL").ok();
+    std::fs::create_dir_all(out_dir).map_err(|e| format!("{}: {}", out_dir.display(), e))?;
+
+    let signatures = rpc(
+        rpc_url,
+        "getSignaturesForAddress",
+        json!([dlp::ID.to_string(), { "limit": limit }]),
+    )?;
+    for entry in signatures.as_array().ok_or("invalid signatures")? {
+        let signature = entry["signature"].as_str().ok_or("invalid signature")?;
+        let tx = rpc(
+            rpc_url,
+            "getTransaction",
+            json!([signature, {
+                "encoding": "base64",
+                "maxSupportedTransactionVersion": 0,
+            }]),
+        )?;
+        let transaction = BASE64
+            .decode(tx["transaction"][0].as_str().ok_or("invalid transaction")?)
+            .map_err(|e| e.to_string())?;
+        let versioned: VersionedTransaction =
+            bincode::deserialize(&transaction).map_err(|e| e.to_string())?;
+
+        let mut account_keys = versioned.message.static_account_keys().to_vec();
+        for loaded in ["writable", "readonly"] {
+            for key in tx["meta"]["loadedAddresses"][loaded]
+                .as_array()
+                .into_iter()
+                .flatten()
+            {
+                account_keys.push(parse_pubkey(key)?);
+            }
+        }
+        let lookup_tables = versioned
+            .message
+            .address_table_lookups()
+            .into_iter()
+            .flatten()
+            .map(|lookup| lookup.account_key);
+
+        let fixture = Fixture {
+            signature: signature.to_string(),
+            slot: tx["slot"].as_u64().ok_or("invalid slot")?,
+            transaction,
+            err: tx["meta"]["err"].clone(),
+            post_balances: tx["meta"]["postBalances"]
+                .as_array()
+                .ok_or("invalid post balances")?
+                .iter()
+                .map(|lamports| lamports.as_u64().ok_or("invalid lamports"))
+                .collect::<Result<_, _>>()?,
+            pre_accounts: fetch_accounts(
+                rpc_url,
+                &account_keys
+                    .iter()
+                    .copied()
+                    .chain(lookup_tables)
+                    .collect::<Vec<_>>(),
+            )?,
+            post_accounts: match &post_rpc_url {
+                Some(post_rpc_url) => fetch_accounts(post_rpc_url, &account_keys)?,
+                None => vec![],
+            },
+            account_keys,
+        };
+        let path = out_dir.join(format!("{}.json", signature));
+        let content = serde_json::to_string_pretty(&fixture.to_json()).unwrap();
+        std::fs::write(&path, content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        println!("Captured {}", path.display());
+    }
+    Ok(())
+}
+
+/// Replay the fixtures of `fixtures_dir` against the program at `program_path`, erroring
+/// if any of them diverges
+pub fn replay(fixtures_dir: &Path, program_path: &Path) -> Result<(), String> {
+    let program =
+        std::fs::read(program_path).map_err(|e| format!("{}: {}", program_path.display(), e))?;
+    let mut paths = std::fs::read_dir(fixtures_dir)
+        .map_err(|e| format!("{}: {}", fixtures_dir.display(), e))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {}", fixtures_dir.display(), e))?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+
+    let mut diverged = 0;
+    for path in &paths {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let value: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        let fixture = Fixture::from_json(&value)?;
+        let divergences = replay_fixture(&fixture, &program)?;
+        if divergences.is_empty() {
+            println!("OK {}", fixture.signature);
+        } else {
+            diverged += 1;
+            println!("DIVERGED {} (slot {})", fixture.signature, fixture.slot);
+            for divergence in divergences {
+                println!("  {}", divergence);
+            }
+        }
+    }
+    match diverged {
+        0 => Ok(()),
+        _ => Err(format!("{} of {} fixtures diverged", diverged, paths.len())),
+    }
+}
+
+/// Execute the transaction of the fixture and return its divergences
+fn replay_fixture(fixture: &Fixture, program: &[u8]) -> Result<Vec<String>, String> {
+    let mut svm = LiteSVM::new()
+        .with_sigverify(false)
+        .with_blockhash_check(false);
+    for FixtureAccount { pubkey, account } in &fixture.pre_accounts {
+        svm.set_account(*pubkey, account.clone())
+            .map_err(|e| format!("{}: {:?}", pubkey, e))?;
+    }
+    // The local build replaces the deployed program
+    svm.add_program(dlp_id(), program);
+
+    let transaction: VersionedTransaction =
+        bincode::deserialize(&fixture.transaction).map_err(|e| e.to_string())?;
+    let result = svm.send_transaction(transaction);
+
+    let mut divergences = vec![];
+    let err = result
+        .as_ref()
+        .err()
+        .map(|failed| format!("{:?}", failed.err));
+    if err.is_some() == fixture.err.is_null() {
+        divergences.push(format!(
+            "status: expected {}, got {}",
+            fixture.err,
+            err.as_deref().unwrap_or("success")
+        ));
+    }
+
+    let post_accounts = fixture
+        .account_keys
+        .iter()
+        .map(|pubkey| FixtureAccount {
+            pubkey: *pubkey,
+            account: svm.get_account(pubkey).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    divergences.extend(diff_post_state(fixture, &post_accounts));
+    Ok(divergences)
+}
+
+/// Compare the accounts after the replay with the recorded post-state
+pub fn diff_post_state(fixture: &Fixture, post_accounts: &[FixtureAccount]) -> Vec<String> {
+    let mut divergences = vec![];
+    for (actual, expected) in post_accounts.iter().zip(&fixture.post_balances) {
+        if actual.account.lamports != *expected {
+            divergences.push(format!(
+                "{}: expected {} lamports, got {}",
+                actual.pubkey, expected, actual.account.lamports
+            ));
+        }
+    }
+    for expected in &fixture.post_accounts {
+        let Some(actual) = post_accounts.iter().find(|a| a.pubkey == expected.pubkey) else {
+            continue;
+        };
+        if actual.account.owner != expected.account.owner {
+            divergences.push(format!(
+                "{}: expected owner {}, got {}",
+                expected.pubkey, expected.account.owner, actual.account.owner
+            ));
+        }
+        if actual.account.data != expected.account.data {
+            let first = actual
+                .account
+                .data
+                .iter()
+                .zip(&expected.account.data)
+                .position(|(a, b)| a != b)
+                .unwrap_or(actual.account.data.len().min(expected.account.data.len()));
+            divergences.push(format!(
+                "{}: data differs from byte {} (expected {} bytes, got {})",
+                expected.pubkey,
+                first,
+                expected.account.data.len(),
+                actual.account.data.len()
+            ));
+        }
+    }
+    divergences
+}
+
+impl Fixture {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "signature": self.signature,
+            "slot": self.slot,
+            "transaction": BASE64.encode(&self.transaction),
+            "accountKeys": self.account_keys.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            "err": self.err,
+            "postBalances": self.post_balances,
+            "preAccounts": self.pre_accounts.iter().map(account_to_json).collect::<Vec<_>>(),
+            "postAccounts": self.post_accounts.iter().map(account_to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        let accounts = |name: &str| {
+            value[name]
+                .as_array()
+                .ok_or(format!("invalid {}", name))?
+                .iter()
+                .map(account_from_json)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            signature: value["signature"]
+                .as_str()
+                .ok_or("invalid signature")?
+                .to_string(),
+            slot: value["slot"].as_u64().ok_or("invalid slot")?,
+            transaction: BASE64
+                .decode(value["transaction"].as_str().ok_or("invalid transaction")?)
+                .map_err(|e| e.to_string())?,
+            account_keys: value["accountKeys"]
+                .as_array()
+                .ok_or("invalid account keys")?
+                .iter()
+                .map(parse_pubkey)
+                .collect::<Result<_, _>>()?,
+            err: value["err"].clone(),
+            post_balances: value["postBalances"]
+                .as_array()
+                .ok_or("invalid post balances")?
+                .iter()
+                .map(|lamports| lamports.as_u64().ok_or("invalid lamports".to_string()))
+                .collect::<Result<_, _>>()?,
+            pre_accounts: accounts("preAccounts")?,
+            post_accounts: accounts("postAccounts")?,
+        })
+    }
+}
+
+fn account_to_json(account: &FixtureAccount) -> Value {
+    json!({
+        "pubkey": account.pubkey.to_string(),
+        "lamports": account.account.lamports,
+        "owner": account.account.owner.to_string(),
+        "executable": account.account.executable,
+        "data": BASE64.encode(&account.account.data),
+    })
+}
+
+fn account_from_json(value: &Value) -> Result<FixtureAccount, String> {
+    Ok(FixtureAccount {
+        pubkey: parse_pubkey(&value["pubkey"])?,
+        account: Account {
+            lamports: value["lamports"].as_u64().ok_or("invalid lamports")?,
+            owner: parse_pubkey(&value["owner"])?,
+            executable: value["executable"].as_bool().unwrap_or(false),
+            data: BASE64
+                .decode(value["data"].as_str().ok_or("invalid data")?)
+                .map_err(|e| e.to_string())?,
+            rent_epoch: u64::MAX,
+        },
+    })
+}
+
+/// Fetch the accounts which exist, with the program data of the upgradeable programs
+fn fetch_accounts(rpc_url: &str, pubkeys: &[Pubkey]) -> Result<Vec<FixtureAccount>, String> {
+    let mut accounts = vec![];
+    let mut pending = pubkeys.to_vec();
+    while let Some(pubkey) = pending.pop() {
+        if accounts.iter().any(|a: &FixtureAccount| a.pubkey == pubkey) {
+            continue;
+        }
+        let value = rpc(
+            rpc_url,
+            "getAccountInfo",
+            json!([pubkey.to_string(), { "encoding": "base64" }]),
+        )?;
+        if value["value"].is_null() {
+            continue;
+        }
+        let mut account = account_from_json(&json!({
+            "pubkey": pubkey.to_string(),
+            "lamports": value["value"]["lamports"],
+            "owner": value["value"]["owner"],
+            "executable": value["value"]["executable"],
+            "data": value["value"]["data"][0],
+        }))?;
+        // An upgradeable program account points to its program data
+        if account.account.executable
+            && account.account.owner == solana_sdk::bpf_loader_upgradeable::id()
+            && account.account.data.len() >= 36
+        {
+            pending.push(Pubkey::try_from(&account.account.data[4..36]).unwrap());
+        }
+        account.account.rent_epoch = u64::MAX;
+        accounts.push(account);
+    }
+    Ok(accounts)
+}
+
+pub(crate) fn rpc(rpc_url: &str, method: &str, params: Value) -> Result<Value, String> {
+    let response: Value = ureq::post(rpc_url)
+        .send_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .map_err(|e| format!("{}: {}", method, e))?
+        .into_json()
+        .map_err(|e| format!("{}: {}", method, e))?;
+    if !response["error"].is_null() {
+        return Err(format!("{}: {}", method, response["error"]));
+    }
+    Ok(response["result"].clone())
+}
+
+fn parse_pubkey(value: &Value) -> Result<Pubkey, String> {
+    value
+        .as_str()
+        .ok_or("invalid pubkey")?
+        .parse()
+        .map_err(|e| format!("invalid pubkey: {:?}", e))
+}
+
+/// The delegation program id, as a key of the SVM
+fn dlp_id() -> Pubkey {
+    Pubkey::new_from_array(dlp::ID.to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_roundtrip_and_diff() {
+        let pubkey = Pubkey::new_unique();
+        let account = Account {
+            lamports: 10,
+            data: vec![1, 2, 3],
+            owner: dlp_id(),
+            executable: false,
+            rent_epoch: u64::MAX,
+        };
+        let fixture = Fixture {
+            signature: "sig".to_string(),
+            slot: 42,
+            transaction: vec![0; 4],
+            account_keys: vec![pubkey],
+            err: Value::Null,
+            post_balances: vec![10],
+            pre_accounts: vec![],
+            post_accounts: vec![FixtureAccount {
+                pubkey,
+                account: account.clone(),
+            }],
+        };
+        let parsed = Fixture::from_json(&fixture.to_json()).unwrap();
+        assert_eq!(parsed.post_accounts, fixture.post_accounts);
+        assert_eq!(parsed.account_keys, fixture.account_keys);
+
+        // Matching post-state
+        let mut actual = FixtureAccount { pubkey, account };
+        assert!(diff_post_state(&fixture, &[actual.clone()]).is_empty());
+
+        // Diverging data and lamports
+        actual.account.data[1] = 9;
+        actual.account.lamports = 11;
+        let divergences = diff_post_state(&fixture, &[actual]);
+        assert_eq!(divergences.len(), 2);
+        assert!(divergences[1].contains("from byte 1"));
+    }
+}
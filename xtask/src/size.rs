@@ -0,0 +1,62 @@
+//! Build the program with each supported feature set and report the size of its `.so`,
+//! deployment costing rent for every byte
+
+use std::path::Path;
+use std::process::Command;
+
+/// The builds compared, by name, with the `cargo build-sbf` feature args of each
+const BUILDS: &[(&str, &[&str])] = &[
+    ("default", &[]),
+    (
+        "minimal",
+        &["--no-default-features", "--features", "minimal"],
+    ),
+    ("logging", &["--features", "logging"]),
+    ("log-cost", &["--features", "log-cost"]),
+];
+
+/// Build every feature set into its own directory of `target/size` and print the sizes
+/// relative to the default build
+pub fn report(crate_root: &Path) -> Result<(), String> {
+    let mut sizes = Vec::with_capacity(BUILDS.len());
+    for (name, args) in BUILDS {
+        let out_dir = crate_root.join("target").join("size").join(name);
+        let status = Command::new("cargo")
+            .arg("build-sbf")
+            .arg("--manifest-path")
+            .arg(crate_root.join("Cargo.toml"))
+            .arg("--sbf-out-dir")
+            .arg(&out_dir)
+            .args(*args)
+            .status()
+            .map_err(|e| format!("cargo build-sbf: {}", e))?;
+        if !status.success() {
+            return Err(format!("cargo build-sbf failed for the {} build", name));
+        }
+        let so = out_dir.join("dlp.so");
+        let size = std::fs::metadata(&so)
+            .map_err(|e| format!("{}: {}", so.display(), e))?
+            .len();
+        sizes.push((*name, size));
+    }
+
+    let default_size = sizes.first().map_or(0, |(_, size)| *size);
+    println!("{:<10} {:>10} {:>8}", "build", "bytes", "delta");
+    for (name, size) in sizes {
+        println!(
+            "{:<10} {:>10} {:>+7.1}%",
+            name,
+            size,
+            relative_delta(size, default_size)
+        );
+    }
+    Ok(())
+}
+
+/// The difference of the size to the default one, in percent
+fn relative_delta(size: u64, default_size: u64) -> f64 {
+    if default_size == 0 {
+        return 0.0;
+    }
+    (size as f64 - default_size as f64) * 100.0 / default_size as f64
+}